@@ -0,0 +1,17 @@
+#include "rc.h"
+
+// Provided by libgc, which must be linked when this allocator is used.
+void GC_init();
+void* GC_malloc(size_t size);
+
+// Garbage collected allocators have no free function, which turns reference counting into no-ops.
+static const struct s_allocator boehm_allocator = {
+    .alloc = GC_malloc,
+    .free = NULL,
+};
+
+// Uses the Boehm garbage collector from libgc to allocate memory.
+void closey_alloc_boehm_init() {
+    GC_init();
+    rc_set_allocator(&boehm_allocator);
+}
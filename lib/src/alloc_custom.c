@@ -0,0 +1,15 @@
+#include "rc.h"
+
+// Provided by the user when this allocator is used.
+void* closey_alloc(size_t size);
+void closey_free(void* ptr);
+
+static const struct s_allocator custom_allocator = {
+    .alloc = closey_alloc,
+    .free = closey_free,
+};
+
+// Uses the user supplied closey_alloc and closey_free functions to allocate memory.
+void closey_alloc_custom_init() {
+    rc_set_allocator(&custom_allocator);
+}
@@ -0,0 +1,15 @@
+#include "rc.h"
+
+// Provided by libc, which must be linked when this allocator is used.
+void* malloc(size_t size);
+void free(void* ptr);
+
+static const struct s_allocator system_allocator = {
+    .alloc = malloc,
+    .free = free,
+};
+
+// Uses malloc and free from libc to allocate memory.
+void closey_alloc_system_init() {
+    rc_set_allocator(&system_allocator);
+}
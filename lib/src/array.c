@@ -0,0 +1,165 @@
+#include "array.h"
+#include "panic.h"
+#include "rc.h"
+
+// The capacity of an array when it is first grown.
+#define MIN_CAPACITY 4
+
+// Writes the decimal representation of a number to a buffer and returns the number of bytes written.
+static size_t format_int(char* buffer, long long n) {
+    char digits[20];
+    size_t count = 0;
+    unsigned long long value = n < 0 ? 0 - (unsigned long long) n : (unsigned long long) n;
+    do {
+        digits[count++] = '0' + value % 10;
+        value /= 10;
+    } while (value != 0);
+
+    size_t length = 0;
+    if (n < 0)
+        buffer[length++] = '-';
+    while (count > 0)
+        buffer[length++] = digits[--count];
+    return length;
+}
+
+// Appends a null terminated string to a buffer and returns the new length.
+static size_t append_cstr(char* buffer, size_t length, const char* s) {
+    while (*s)
+        buffer[length++] = *s++;
+    return length;
+}
+
+// Panics with a message describing an out of bounds index.
+static void* panic_out_of_bounds(struct s_array* array, long long index, struct s_string* loc) {
+    char buffer[96];
+    size_t length = append_cstr(buffer, 0, "index out of bounds: the length is ");
+    length += format_int(buffer + length, array->length);
+    length = append_cstr(buffer, length, " but the index is ");
+    length += format_int(buffer + length, index);
+    return closey_panic(string_new(buffer, length), loc);
+}
+
+// Creates an array of the given length with every item set to the given value. Panics if the
+// length is negative. The location is the source location of the call, of the form
+// `file:line:column`.
+struct s_array* closey_array_make(long long length, void* value, struct s_string* loc) {
+    if (length < 0) {
+        static const char msg[] = "negative array length";
+        return closey_panic(string_new(msg, sizeof(msg) - 1), loc);
+    }
+
+    struct s_array* array = rcalloc(sizeof(struct s_array));
+    if (array == NULL)
+        return NULL;
+
+    array->length = length;
+    array->capacity = length;
+    array->items = length == 0 ? NULL : rcalloc(length * sizeof(void*));
+    for (long long i = 0; i < length; i++) {
+        array->items[i] = value;
+    }
+
+    return array;
+}
+
+// Creates an array containing a single item. Array literals are built from this and
+// closey_array_push.
+struct s_array* closey_array_of(void* value) {
+    struct s_array* array = rcalloc(sizeof(struct s_array));
+    if (array == NULL)
+        return NULL;
+
+    array->items = rcalloc(MIN_CAPACITY * sizeof(void*));
+    if (array->items == NULL)
+        return NULL;
+
+    array->length = 1;
+    array->capacity = MIN_CAPACITY;
+    array->items[0] = value;
+    return array;
+}
+
+// Returns the item at the given index, or panics if the index is out of bounds.
+void* closey_array_get(struct s_array* array, long long index, struct s_string* loc) {
+    if (index < 0 || (size_t) index >= array->length)
+        return panic_out_of_bounds(array, index, loc);
+    return array->items[index];
+}
+
+// Sets the item at the given index and returns the array, or panics if the index is out of bounds.
+struct s_array* closey_array_set(struct s_array* array, long long index, void* value, struct s_string* loc) {
+    if (index < 0 || (size_t) index >= array->length)
+        return panic_out_of_bounds(array, index, loc);
+
+    array->items[index] = value;
+    rcinc(array);
+    return array;
+}
+
+// Appends an item to the end of the array, growing it if needed, and returns the array.
+struct s_array* closey_array_push(struct s_array* array, void* value) {
+    if (array->length == array->capacity) {
+        size_t capacity = array->capacity < MIN_CAPACITY ? MIN_CAPACITY : array->capacity * 2;
+        void** items = rcalloc(capacity * sizeof(void*));
+        if (items == NULL)
+            return NULL;
+
+        for (size_t i = 0; i < array->length; i++) {
+            items[i] = array->items[i];
+        }
+
+        if (array->items != NULL)
+            rcfree(array->items);
+        array->items = items;
+        array->capacity = capacity;
+    }
+
+    array->items[array->length++] = value;
+    rcinc(array);
+    return array;
+}
+
+// Returns the number of items in the array.
+long long closey_array_length(struct s_array* array) {
+    return array->length;
+}
+
+// Returns true if the array contains an item equal to the given value.
+static bool contains(struct s_array* array, void* value, bool (*equals)(void*, void*)) {
+    for (size_t i = 0; i < array->length; i++) {
+        if (equals(array->items[i], value))
+            return true;
+    }
+    return false;
+}
+
+// Returns true if two integer items are equal.
+static bool equals_int(void* a, void* b) {
+    return a == b;
+}
+
+// Returns true if two boolean items are equal. Only the lowest byte of a boolean is set.
+static bool equals_bool(void* a, void* b) {
+    return (unsigned char) (unsigned long long) a == (unsigned char) (unsigned long long) b;
+}
+
+// Returns true if two string items are equal.
+static bool equals_string(void* a, void* b) {
+    return string_equals(a, b);
+}
+
+// Returns true if an array of integers contains the given integer.
+bool closey_array_contains_int(struct s_array* array, long long value) {
+    return contains(array, (void*) value, equals_int);
+}
+
+// Returns true if an array of booleans contains the given boolean.
+bool closey_array_contains_bool(struct s_array* array, bool value) {
+    return contains(array, (void*) (long long) value, equals_bool);
+}
+
+// Returns true if an array of strings contains a string with the same contents as the given string.
+bool closey_array_contains_string(struct s_array* array, struct s_string* value) {
+    return contains(array, value, equals_string);
+}
@@ -0,0 +1,46 @@
+#ifndef ARRAY_H
+#define ARRAY_H
+
+#include <stddef.h>
+
+#include "str.h"
+
+// Represents a reference counted, growable array. Items are integers, the bits of floats, or
+// pointers, and are not reference counted by the array.
+struct s_array {
+    size_t length;
+    size_t capacity;
+    void** items;
+};
+
+// Creates an array of the given length with every item set to the given value. Panics if the
+// length is negative. The location is the source location of the call, of the form
+// `file:line:column`.
+struct s_array* closey_array_make(long long length, void* value, struct s_string* loc);
+
+// Creates an array containing a single item. Array literals are built from this and
+// closey_array_push.
+struct s_array* closey_array_of(void* value);
+
+// Returns the item at the given index, or panics if the index is out of bounds.
+void* closey_array_get(struct s_array* array, long long index, struct s_string* loc);
+
+// Sets the item at the given index and returns the array, or panics if the index is out of bounds.
+struct s_array* closey_array_set(struct s_array* array, long long index, void* value, struct s_string* loc);
+
+// Appends an item to the end of the array, growing it if needed, and returns the array.
+struct s_array* closey_array_push(struct s_array* array, void* value);
+
+// Returns the number of items in the array.
+long long closey_array_length(struct s_array* array);
+
+// Returns true if an array of integers contains the given integer.
+bool closey_array_contains_int(struct s_array* array, long long value);
+
+// Returns true if an array of booleans contains the given boolean.
+bool closey_array_contains_bool(struct s_array* array, bool value);
+
+// Returns true if an array of strings contains a string with the same contents as the given string.
+bool closey_array_contains_string(struct s_array* array, struct s_string* value);
+
+#endif /* ARRAY_H */
@@ -0,0 +1,57 @@
+.intel_syntax noprefix
+.global call_with_args
+
+# void* call_with_args(void* code, unsigned long long argc, void* args[]);
+call_with_args:
+    push rbp
+    mov rbp, rsp
+    push rbx
+    push r12
+    mov rax, rdi
+    mov rbx, rsi
+    mov r12, rdx
+
+    # Push the arguments after the sixth in reverse order, keeping the stack aligned
+    mov rcx, rbx
+    sub rcx, 6
+    jbe call_with_args_registers
+    test rcx, 1
+    jz call_with_args_aligned
+    sub rsp, 8
+call_with_args_aligned:
+    mov rcx, rbx
+call_with_args_push:
+    dec rcx
+    push qword ptr [r12 + rcx * 8]
+    cmp rcx, 6
+    ja call_with_args_push
+
+    # Pass the first six arguments in registers
+call_with_args_registers:
+    mov r11, rax
+    cmp rbx, 1
+    jb call_with_args_call
+    mov rdi, [r12]
+    cmp rbx, 2
+    jb call_with_args_call
+    mov rsi, [r12 + 8]
+    cmp rbx, 3
+    jb call_with_args_call
+    mov rdx, [r12 + 16]
+    cmp rbx, 4
+    jb call_with_args_call
+    mov rcx, [r12 + 24]
+    cmp rbx, 5
+    jb call_with_args_call
+    mov r8, [r12 + 32]
+    cmp rbx, 6
+    jb call_with_args_call
+    mov r9, [r12 + 40]
+
+call_with_args_call:
+    call r11
+    lea rsp, [rbp - 16]
+    pop r12
+    pop rbx
+    pop rbp
+    ret
@@ -0,0 +1,86 @@
+#include "callback.h"
+#include "rc.h"
+#include "syscalls.h"
+
+#define PROT_READ 0x1
+#define PROT_WRITE 0x2
+#define PROT_EXEC 0x4
+#define MAP_PRIVATE 0x02
+#define MAP_ANONYMOUS 0x20
+
+void callback_entry();
+
+// Appends bytes to the code of a callback and returns the new length.
+static unsigned int emit(unsigned char* code, unsigned int length, const void* bytes, unsigned int count) {
+    for (unsigned int i = 0; i < count; i++) {
+        code[length + i] = ((const unsigned char*) bytes)[i];
+    }
+    return length + count;
+}
+
+// Creates a callback that calls the given closure with the given number of arguments.
+struct s_callback* callback_new(void* closure, unsigned int argc) {
+    // Callbacks are executable, so they are mapped rather than allocated on the heap
+    struct s_callback* callback = mmap(NULL, sizeof(struct s_callback), PROT_READ | PROT_WRITE | PROT_EXEC, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    if (callback == (void*) -1)
+        return NULL;
+
+    // Functions are tagged with their lowest bit and are not reference counted
+    callback->closure = closure;
+    if (!(((unsigned long long) closure) & 1))
+        rcinc(closure);
+
+    // mov r10, closure
+    unsigned int length = emit(callback->code, 0, "\x49\xba", 2);
+    length = emit(callback->code, length, &closure, 8);
+
+    // mov eax, argc
+    length = emit(callback->code, length, "\xb8", 1);
+    length = emit(callback->code, length, &argc, 4);
+
+    // mov r11, callback_entry
+    void* entry = callback_entry;
+    length = emit(callback->code, length, "\x49\xbb", 2);
+    length = emit(callback->code, length, &entry, 8);
+
+    // jmp r11
+    emit(callback->code, length, "\x41\xff\xe3", 3);
+    return callback;
+}
+
+// Creates a callback that calls the given closure with 1 argument.
+struct s_callback* closey_callback_new_1(void* closure) {
+    return callback_new(closure, 1);
+}
+
+// Creates a callback that calls the given closure with 2 arguments.
+struct s_callback* closey_callback_new_2(void* closure) {
+    return callback_new(closure, 2);
+}
+
+// Creates a callback that calls the given closure with 3 arguments.
+struct s_callback* closey_callback_new_3(void* closure) {
+    return callback_new(closure, 3);
+}
+
+// Creates a callback that calls the given closure with 4 arguments.
+struct s_callback* closey_callback_new_4(void* closure) {
+    return callback_new(closure, 4);
+}
+
+// Creates a callback that calls the given closure with 5 arguments.
+struct s_callback* closey_callback_new_5(void* closure) {
+    return callback_new(closure, 5);
+}
+
+// Creates a callback that calls the given closure with 6 arguments.
+struct s_callback* closey_callback_new_6(void* closure) {
+    return callback_new(closure, 6);
+}
+
+// Frees a callback and releases its reference to its closure.
+void* closey_callback_free(struct s_callback* callback) {
+    rcfuncfree(callback->closure);
+    munmap(callback, sizeof(struct s_callback));
+    return NULL;
+}
@@ -0,0 +1,39 @@
+#ifndef CALLBACK_H
+#define CALLBACK_H
+
+// Represents a C callable function that calls a closure. The callback holds a reference to the
+// closure until it is freed.
+struct s_callback {
+    // The machine code of the callback. Loads the closure and argument count and jumps to
+    // callback_entry.
+    unsigned char code[32];
+
+    // The closure the callback calls.
+    void* closure;
+};
+
+// Creates a callback that calls the given closure with the given number of arguments.
+struct s_callback* callback_new(void* closure, unsigned int argc);
+
+// Creates a callback that calls the given closure with 1 argument.
+struct s_callback* closey_callback_new_1(void* closure);
+
+// Creates a callback that calls the given closure with 2 arguments.
+struct s_callback* closey_callback_new_2(void* closure);
+
+// Creates a callback that calls the given closure with 3 arguments.
+struct s_callback* closey_callback_new_3(void* closure);
+
+// Creates a callback that calls the given closure with 4 arguments.
+struct s_callback* closey_callback_new_4(void* closure);
+
+// Creates a callback that calls the given closure with 5 arguments.
+struct s_callback* closey_callback_new_5(void* closure);
+
+// Creates a callback that calls the given closure with 6 arguments.
+struct s_callback* closey_callback_new_6(void* closure);
+
+// Frees a callback and releases its reference to its closure.
+void* closey_callback_free(struct s_callback* callback);
+
+#endif /* CALLBACK_H */
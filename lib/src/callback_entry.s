@@ -0,0 +1,25 @@
+.intel_syntax noprefix
+.global callback_entry
+
+# void* callback_entry(...);
+# Called by callbacks with the closure in r10 and the number of arguments in eax. Calls the
+# closure with the register arguments using call_unknown_arity.
+callback_entry:
+    push rbp
+    mov rbp, rsp
+
+    # Store the arguments in order as an array on the stack
+    push r9
+    push r8
+    push rcx
+    push rdx
+    push rsi
+    push rdi
+
+    mov rdi, r10
+    mov esi, eax
+    mov rdx, rsp
+    call call_unknown_arity
+
+    leave
+    ret
@@ -0,0 +1,71 @@
+#include "panic.h"
+#include "char.h"
+
+// Returns true if two chars are the same.
+bool closey_char_eq(unsigned int a, unsigned int b) {
+    return a == b;
+}
+
+// Returns true if two chars are different.
+bool closey_char_ne(unsigned int a, unsigned int b) {
+    return a != b;
+}
+
+// Returns true if the first char comes before the second.
+bool closey_char_lt(unsigned int a, unsigned int b) {
+    return a < b;
+}
+
+// Returns true if the first char comes before or is the same as the second.
+bool closey_char_le(unsigned int a, unsigned int b) {
+    return a <= b;
+}
+
+// Returns true if the first char comes after the second.
+bool closey_char_gt(unsigned int a, unsigned int b) {
+    return a > b;
+}
+
+// Returns true if the first char comes after or is the same as the second.
+bool closey_char_ge(unsigned int a, unsigned int b) {
+    return a >= b;
+}
+
+// Returns a string containing the char encoded as utf-8.
+struct s_string* closey_char_to_string(unsigned int c) {
+    char bytes[4];
+    size_t length;
+    if (c < 0x80) {
+        bytes[0] = c;
+        length = 1;
+    } else if (c < 0x800) {
+        bytes[0] = 0xc0 | (c >> 6);
+        bytes[1] = 0x80 | (c & 0x3f);
+        length = 2;
+    } else if (c < 0x10000) {
+        bytes[0] = 0xe0 | (c >> 12);
+        bytes[1] = 0x80 | ((c >> 6) & 0x3f);
+        bytes[2] = 0x80 | (c & 0x3f);
+        length = 3;
+    } else {
+        bytes[0] = 0xf0 | (c >> 18);
+        bytes[1] = 0x80 | ((c >> 12) & 0x3f);
+        bytes[2] = 0x80 | ((c >> 6) & 0x3f);
+        bytes[3] = 0x80 | (c & 0x3f);
+        length = 4;
+    }
+
+    return string_new(bytes, length);
+}
+
+// Converts an int into the char with that code point, panicking if the int is not a unicode scalar
+// value. The location is the source location of the conversion, of the form `file:line:column`.
+unsigned int closey_int_to_char(long long n, struct s_string* loc) {
+    // Surrogates are code points but not scalar values
+    if (n < 0 || n > 0x10ffff || (n >= 0xd800 && n < 0xe000)) {
+        static const char msg[] = "int is not a unicode scalar value";
+        closey_panic(string_new(msg, sizeof(msg) - 1), loc);
+    }
+
+    return n;
+}
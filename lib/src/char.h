@@ -0,0 +1,36 @@
+#ifndef CHAR_H
+#define CHAR_H
+
+#include <stdbool.h>
+
+#include "str.h"
+
+// Chars are unicode scalar values, passed in and returned from these functions as their code
+// points.
+
+// Returns true if two chars are the same.
+bool closey_char_eq(unsigned int a, unsigned int b);
+
+// Returns true if two chars are different.
+bool closey_char_ne(unsigned int a, unsigned int b);
+
+// Returns true if the first char comes before the second.
+bool closey_char_lt(unsigned int a, unsigned int b);
+
+// Returns true if the first char comes before or is the same as the second.
+bool closey_char_le(unsigned int a, unsigned int b);
+
+// Returns true if the first char comes after the second.
+bool closey_char_gt(unsigned int a, unsigned int b);
+
+// Returns true if the first char comes after or is the same as the second.
+bool closey_char_ge(unsigned int a, unsigned int b);
+
+// Returns a string containing the char encoded as utf-8.
+struct s_string* closey_char_to_string(unsigned int c);
+
+// Converts an int into the char with that code point, panicking if the int is not a unicode scalar
+// value. The location is the source location of the conversion, of the form `file:line:column`.
+unsigned int closey_int_to_char(long long n, struct s_string* loc);
+
+#endif /* CHAR_H */
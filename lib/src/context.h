@@ -0,0 +1,11 @@
+#ifndef CONTEXT_H
+#define CONTEXT_H
+
+// Saves the callee saved registers and stack pointer of the current context, and switches to the
+// context with the given stack pointer.
+void switch_context(void** save_sp, void* sp);
+
+// The entry point of a new task. Calls task_main with the task in r12.
+void task_start();
+
+#endif /* CONTEXT_H */
@@ -0,0 +1,27 @@
+.intel_syntax noprefix
+.global switch_context
+.global task_start
+
+# void switch_context(void** save_sp, void* sp);
+switch_context:
+    push rbx
+    push rbp
+    push r12
+    push r13
+    push r14
+    push r15
+    mov [rdi], rsp
+    mov rsp, rsi
+    pop r15
+    pop r14
+    pop r13
+    pop r12
+    pop rbp
+    pop rbx
+    ret
+
+# void task_start();
+task_start:
+    mov rdi, r12
+    call task_main
+    ud2
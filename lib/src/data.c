@@ -0,0 +1,51 @@
+#include "data.h"
+#include "rc.h"
+
+// Allocates a value with the given tag and fields. Generated code increments the reference counts
+// of the fields before passing them in, so the value holds a reference to each of them.
+static struct s_data* data_new(unsigned int tag, unsigned int count, void** fields) {
+    struct s_data* data = rcalloc(sizeof(struct s_data) + count * sizeof(void*));
+    if (data == NULL)
+        return NULL;
+
+    data->tag = tag;
+    data->count = count;
+    for (unsigned int i = 0; i < count; i++)
+        data->fields[i] = fields[i];
+    return data;
+}
+
+// Creates a value with the given tag and no fields.
+struct s_data* closey_data_0(unsigned int tag) {
+    return data_new(tag, 0, NULL);
+}
+
+// Creates a value with the given tag and one field.
+struct s_data* closey_data_1(unsigned int tag, void* a) {
+    void* fields[] = { a };
+    return data_new(tag, 1, fields);
+}
+
+// Creates a value with the given tag and two fields.
+struct s_data* closey_data_2(unsigned int tag, void* a, void* b) {
+    void* fields[] = { a, b };
+    return data_new(tag, 2, fields);
+}
+
+// Creates a value with the given tag and three fields.
+struct s_data* closey_data_3(unsigned int tag, void* a, void* b, void* c) {
+    void* fields[] = { a, b, c };
+    return data_new(tag, 3, fields);
+}
+
+// Creates a value with the given tag and four fields.
+struct s_data* closey_data_4(unsigned int tag, void* a, void* b, void* c, void* d) {
+    void* fields[] = { a, b, c, d };
+    return data_new(tag, 4, fields);
+}
+
+// Creates a value with the given tag and five fields.
+struct s_data* closey_data_5(unsigned int tag, void* a, void* b, void* c, void* d, void* e) {
+    void* fields[] = { a, b, c, d, e };
+    return data_new(tag, 5, fields);
+}
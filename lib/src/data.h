@@ -0,0 +1,30 @@
+#ifndef DATA_H
+#define DATA_H
+
+// Represents a reference counted value of a type declared in a program. The tag is the index of the
+// constructor that built the value in the declaration of its type.
+struct s_data {
+    unsigned int tag;
+    unsigned int count;
+    void* fields[];
+};
+
+// Creates a value with the given tag and no fields.
+struct s_data* closey_data_0(unsigned int tag);
+
+// Creates a value with the given tag and one field.
+struct s_data* closey_data_1(unsigned int tag, void* a);
+
+// Creates a value with the given tag and two fields.
+struct s_data* closey_data_2(unsigned int tag, void* a, void* b);
+
+// Creates a value with the given tag and three fields.
+struct s_data* closey_data_3(unsigned int tag, void* a, void* b, void* c);
+
+// Creates a value with the given tag and four fields.
+struct s_data* closey_data_4(unsigned int tag, void* a, void* b, void* c, void* d);
+
+// Creates a value with the given tag and five fields.
+struct s_data* closey_data_5(unsigned int tag, void* a, void* b, void* c, void* d, void* e);
+
+#endif /* DATA_H */
@@ -0,0 +1,99 @@
+#include <stdint.h>
+
+#include "eq.h"
+#include "unknown_arity.h"
+
+// Compares two values by calling their equality function. Bools are returned in the lowest byte,
+// so the rest of the result is ignored.
+static bool equal(void* eq, void* a, void* b) {
+    void* args[] = { a, b };
+    return (uint8_t) (uintptr_t) call_unknown_arity(eq, 2, args) != 0;
+}
+
+// Returns true if two arrays have the same length and equal items, compared by the given function.
+bool closey_eq_array(struct s_array* a, struct s_array* b, void* eq) {
+    if (a->length != b->length)
+        return false;
+
+    for (size_t i = 0; i < a->length; i++) {
+        if (!equal(eq, a->items[i], b->items[i]))
+            return false;
+    }
+    return true;
+}
+
+// Returns true if two maps have the same keys with equal values, compared by the given functions,
+// whatever order their entries were inserted in. Keys are looked up by comparing them with every
+// key of the other map, since the map does not know how to hash them.
+bool closey_eq_map(struct s_map* a, struct s_map* b, void* eq_key, void* eq_value) {
+    if (a->length != b->length)
+        return false;
+
+    for (size_t i = 0; i < a->capacity; i++) {
+        struct s_map_entry* entry = &a->entries[i];
+        if (entry->state != MAP_ENTRY_FULL)
+            continue;
+
+        bool found = false;
+        for (size_t j = 0; j < b->capacity && !found; j++) {
+            struct s_map_entry* other = &b->entries[j];
+            if (other->state == MAP_ENTRY_FULL && equal(eq_key, entry->key, other->key)) {
+                if (!equal(eq_value, entry->value, other->value))
+                    return false;
+                found = true;
+            }
+        }
+
+        if (!found)
+            return false;
+    }
+    return true;
+}
+
+// Returns true if two results are both ok with equal values, compared by the given function, or
+// both errors with the same message.
+bool closey_eq_result(struct s_result* a, struct s_result* b, void* eq) {
+    if (a->tag != b->tag)
+        return false;
+    if (a->tag == RESULT_OK)
+        return equal(eq, a->value, b->value);
+    return string_equals(a->error, b->error);
+}
+
+// Returns true if two options are both some with equal values, compared by the given function, or
+// both none. Options are results, with some values stored the same way as ok values.
+bool closey_eq_option(struct s_result* a, struct s_result* b, void* eq) {
+    if (a->tag != b->tag)
+        return false;
+    return a->tag != RESULT_OK || equal(eq, a->value, b->value);
+}
+
+// Returns true if two values of a declared type were built by the same constructor from equal
+// fields. The constructors are listed in order of their tags, each as its number of fields followed
+// by its name and a comma, and the equality functions of the fields of every constructor follow
+// each other in the same order.
+bool closey_eq_data(struct s_data* a, struct s_data* b, struct s_string* constructors, struct s_array* eqs) {
+    if (a->tag != b->tag)
+        return false;
+
+    size_t i = 0;
+    size_t offset = 0;
+    for (unsigned int tag = 0; i < constructors->length; tag++) {
+        size_t count = 0;
+        for (; constructors->bytes[i] >= '0' && constructors->bytes[i] <= '9'; i++)
+            count = count * 10 + (constructors->bytes[i] - '0');
+        for (; constructors->bytes[i] != ','; i++);
+
+        if (tag == a->tag) {
+            for (size_t j = 0; j < count; j++) {
+                if (!equal(eqs->items[offset + j], a->fields[j], b->fields[j]))
+                    return false;
+            }
+            return true;
+        }
+
+        offset += count;
+        i++;
+    }
+    return true;
+}
@@ -0,0 +1,36 @@
+#ifndef EQ_H
+#define EQ_H
+
+#include <stdbool.h>
+
+#include "array.h"
+#include "data.h"
+#include "map.h"
+#include "result.h"
+#include "str.h"
+
+// These functions compare values by their contents for `==` and `!=`. The compiler derives an
+// equality function for each type that is compared this way, which calls the function for the kind
+// of value with the equality functions of the types of the values inside it.
+
+// Returns true if two arrays have the same length and equal items, compared by the given function.
+bool closey_eq_array(struct s_array* a, struct s_array* b, void* eq);
+
+// Returns true if two maps have the same keys with equal values, compared by the given functions,
+// whatever order their entries were inserted in.
+bool closey_eq_map(struct s_map* a, struct s_map* b, void* eq_key, void* eq_value);
+
+// Returns true if two results are both ok with equal values, compared by the given function, or
+// both errors with the same message.
+bool closey_eq_result(struct s_result* a, struct s_result* b, void* eq);
+
+// Returns true if two options are both some with equal values, compared by the given function, or
+// both none.
+bool closey_eq_option(struct s_result* a, struct s_result* b, void* eq);
+
+// Returns true if two values of a declared type were built by the same constructor from equal
+// fields. The constructors and the equality functions of their fields are given the same way as to
+// closey_show_data.
+bool closey_eq_data(struct s_data* a, struct s_data* b, struct s_string* constructors, struct s_array* eqs);
+
+#endif /* EQ_H */
@@ -0,0 +1,162 @@
+#include "file.h"
+#include "rc.h"
+#include "syscalls.h"
+
+#define PATH_MAX 4096
+#define F_OK 0
+#define O_RDONLY 00
+#define O_WRONLY 01
+#define O_CREAT 0100
+#define O_TRUNC 01000
+#define O_APPEND 02000
+
+// Copies a string into a null terminated path. Returns false if the path is too long or contains
+// a null byte.
+static bool to_path(struct s_string* s, char path[PATH_MAX]) {
+    if (s->length >= PATH_MAX)
+        return false;
+
+    for (size_t i = 0; i < s->length; i++) {
+        if (s->bytes[i] == '\0')
+            return false;
+        path[i] = s->bytes[i];
+    }
+    path[s->length] = '\0';
+    return true;
+}
+
+// Creates an error result from the negated error number returned by a syscall.
+static struct s_result* errno_err(long err) {
+    switch (-err) {
+        case 2:
+            return result_err("no such file or directory");
+        case 13:
+            return result_err("permission denied");
+        case 17:
+            return result_err("file exists");
+        case 20:
+            return result_err("not a directory");
+        case 21:
+            return result_err("is a directory");
+        case 28:
+            return result_err("no space left on device");
+        default:
+            return result_err("io error");
+    }
+}
+
+// Opens a file for reading and returns its file descriptor.
+struct s_result* closey_open(struct s_string* path) {
+    char p[PATH_MAX];
+    if (!to_path(path, p))
+        return result_err("invalid path");
+
+    int fd = open(p, O_RDONLY, 0);
+    if (fd < 0)
+        return errno_err(fd);
+    return result_ok_int(fd);
+}
+
+// Closes a file descriptor returned by closey_open.
+struct s_result* closey_close(long long fd) {
+    int err = close(fd);
+    if (err < 0)
+        return errno_err(err);
+    return result_ok(NULL);
+}
+
+// Reads the entire contents of a file into a string.
+struct s_result* closey_read_to_string(struct s_string* path) {
+    char p[PATH_MAX];
+    if (!to_path(path, p))
+        return result_err("invalid path");
+
+    int fd = open(p, O_RDONLY, 0);
+    if (fd < 0)
+        return errno_err(fd);
+
+    size_t capacity = 4096;
+    struct s_string* contents = rcalloc(sizeof(struct s_string) + capacity);
+    if (contents == NULL) {
+        close(fd);
+        return result_err("out of memory");
+    }
+    contents->length = 0;
+
+    while (true) {
+        // Grow the buffer
+        if (contents->length == capacity) {
+            capacity *= 2;
+            struct s_string* bigger = rcalloc(sizeof(struct s_string) + capacity);
+            if (bigger == NULL) {
+                rcfree(contents);
+                close(fd);
+                return result_err("out of memory");
+            }
+
+            bigger->length = contents->length;
+            for (size_t i = 0; i < contents->length; i++) {
+                bigger->bytes[i] = contents->bytes[i];
+            }
+            rcfree(contents);
+            contents = bigger;
+        }
+
+        long count = read(fd, contents->bytes + contents->length, capacity - contents->length);
+        if (count < 0) {
+            rcfree(contents);
+            close(fd);
+            return errno_err(count);
+        } else if (count == 0) {
+            break;
+        }
+        contents->length += count;
+    }
+
+    close(fd);
+    return result_ok(contents);
+}
+
+// Writes a string to a file opened with the given flags.
+static struct s_result* write_with_flags(struct s_string* path, struct s_string* contents, int flags) {
+    char p[PATH_MAX];
+    if (!to_path(path, p))
+        return result_err("invalid path");
+
+    int fd = open(p, O_WRONLY | O_CREAT | flags, 0644);
+    if (fd < 0)
+        return errno_err(fd);
+
+    const char* bytes = contents->bytes;
+    size_t length = contents->length;
+    while (length > 0) {
+        long written = write(fd, bytes, length);
+        if (written < 0) {
+            close(fd);
+            return errno_err(written);
+        }
+        bytes += written;
+        length -= written;
+    }
+
+    close(fd);
+    return result_ok(NULL);
+}
+
+// Writes a string to a file, replacing its contents and creating it if it does not exist.
+struct s_result* closey_write(struct s_string* path, struct s_string* contents) {
+    return write_with_flags(path, contents, O_TRUNC);
+}
+
+// Appends a string to a file, creating it if it does not exist.
+struct s_result* closey_append(struct s_string* path, struct s_string* contents) {
+    return write_with_flags(path, contents, O_APPEND);
+}
+
+// Returns true if a file exists at the given path.
+bool closey_exists(struct s_string* path) {
+    char p[PATH_MAX];
+    if (!to_path(path, p))
+        return false;
+    return access(p, F_OK) == 0;
+}
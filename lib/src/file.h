@@ -0,0 +1,27 @@
+#ifndef FILE_H
+#define FILE_H
+
+#include <stdbool.h>
+
+#include "result.h"
+#include "str.h"
+
+// Opens a file for reading and returns its file descriptor.
+struct s_result* closey_open(struct s_string* path);
+
+// Closes a file descriptor returned by closey_open.
+struct s_result* closey_close(long long fd);
+
+// Reads the entire contents of a file into a string.
+struct s_result* closey_read_to_string(struct s_string* path);
+
+// Writes a string to a file, replacing its contents and creating it if it does not exist.
+struct s_result* closey_write(struct s_string* path, struct s_string* contents);
+
+// Appends a string to a file, creating it if it does not exist.
+struct s_result* closey_append(struct s_string* path, struct s_string* contents);
+
+// Returns true if a file exists at the given path.
+bool closey_exists(struct s_string* path);
+
+#endif /* FILE_H */
@@ -0,0 +1,68 @@
+#include "io.h"
+#include "rc.h"
+#include "syscalls.h"
+
+#define STDIN 0
+#define STDOUT 1
+
+// Writes all bytes to a file descriptor, retrying on short writes.
+static void write_all(int fd, const char* bytes, size_t length) {
+    while (length > 0) {
+        long written = write(fd, bytes, length);
+        if (written <= 0)
+            return;
+        bytes += written;
+        length -= written;
+    }
+}
+
+// Prints a string to stdout.
+void* closey_print(struct s_string* s) {
+    write_all(STDOUT, s->bytes, s->length);
+    return NULL;
+}
+
+// Prints a string followed by a newline to stdout.
+void* closey_println(struct s_string* s) {
+    write_all(STDOUT, s->bytes, s->length);
+    write_all(STDOUT, "\n", 1);
+    return NULL;
+}
+
+// Prints a value shown by the show function derived for its type followed by a newline to stdout.
+void* closey_debug(struct s_string* s) {
+    write_all(STDOUT, s->bytes, s->length);
+    write_all(STDOUT, "\n", 1);
+    return NULL;
+}
+
+// Reads a line from stdin, not including the newline.
+struct s_string* closey_read_line(void* unit) {
+    size_t capacity = 64;
+    struct s_string* line = rcalloc(sizeof(struct s_string) + capacity);
+    if (line == NULL)
+        return NULL;
+    line->length = 0;
+
+    char c;
+    while (read(STDIN, &c, 1) == 1 && c != '\n') {
+        // Grow the buffer
+        if (line->length == capacity) {
+            capacity *= 2;
+            struct s_string* bigger = rcalloc(sizeof(struct s_string) + capacity);
+            if (bigger == NULL)
+                return line;
+
+            bigger->length = line->length;
+            for (size_t i = 0; i < line->length; i++) {
+                bigger->bytes[i] = line->bytes[i];
+            }
+            rcfree(line);
+            line = bigger;
+        }
+
+        line->bytes[line->length++] = c;
+    }
+
+    return line;
+}
@@ -0,0 +1,18 @@
+#ifndef IO_H
+#define IO_H
+
+#include "str.h"
+
+// Prints a string to stdout.
+void* closey_print(struct s_string* s);
+
+// Prints a string followed by a newline to stdout.
+void* closey_println(struct s_string* s);
+
+// Prints a value shown by the show function derived for its type followed by a newline to stdout.
+void* closey_debug(struct s_string* s);
+
+// Reads a line from stdin, not including the newline.
+struct s_string* closey_read_line(void* unit);
+
+#endif /* IO_H */
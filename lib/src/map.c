@@ -0,0 +1,254 @@
+#include "map.h"
+#include "rc.h"
+
+// The capacity of a map when it is first created. Capacities are always powers of two.
+#define MIN_CAPACITY 8
+
+// Hashes a key.
+typedef unsigned long long (*hash_t)(void* key);
+
+// Returns true if two keys are equal.
+typedef bool (*equals_t)(void* a, void* b);
+
+// Hashes an integer key.
+static unsigned long long hash_int(void* key) {
+    // splitmix64 finaliser, so that sequential keys are spread over the table
+    unsigned long long x = (unsigned long long) key;
+    x = (x ^ (x >> 30)) * 0xbf58476d1ce4e5b9ull;
+    x = (x ^ (x >> 27)) * 0x94d049bb133111ebull;
+    return x ^ (x >> 31);
+}
+
+// Returns true if two integer keys are equal.
+static bool equals_int(void* a, void* b) {
+    return a == b;
+}
+
+// Hashes a string key.
+static unsigned long long hash_string(void* key) {
+    // FNV-1a
+    struct s_string* s = key;
+    unsigned long long hash = 0xcbf29ce484222325ull;
+    for (size_t i = 0; i < s->length; i++) {
+        hash ^= (unsigned char) s->bytes[i];
+        hash *= 0x100000001b3ull;
+    }
+    return hash;
+}
+
+// Returns true if two string keys are equal.
+static bool equals_string(void* a, void* b) {
+    return string_equals(a, b);
+}
+
+// Allocates the entries of a map with the given capacity.
+static bool allocate_entries(struct s_map* map, size_t capacity) {
+    struct s_map_entry* entries = rcalloc(capacity * sizeof(struct s_map_entry));
+    if (entries == NULL)
+        return false;
+
+    for (size_t i = 0; i < capacity; i++) {
+        entries[i].state = MAP_ENTRY_EMPTY;
+    }
+
+    map->entries = entries;
+    map->capacity = capacity;
+    map->used = 0;
+    return true;
+}
+
+// Returns the index of the entry holding the key, or the index of the entry the key should be
+// inserted into if the key is not in the map.
+static size_t find(struct s_map* map, void* key, hash_t hash, equals_t equals) {
+    size_t mask = map->capacity - 1;
+    size_t i = hash(key) & mask;
+    size_t removed = map->capacity;
+    while (map->entries[i].state != MAP_ENTRY_EMPTY) {
+        if (map->entries[i].state == MAP_ENTRY_FULL) {
+            if (equals(map->entries[i].key, key))
+                return i;
+        } else if (removed == map->capacity) {
+            removed = i;
+        }
+        i = (i + 1) & mask;
+    }
+
+    // Reuse removed entries when inserting
+    return removed != map->capacity ? removed : i;
+}
+
+// Rebuilds the map with the given capacity, dropping removed entries.
+static bool resize(struct s_map* map, size_t capacity, hash_t hash, equals_t equals) {
+    struct s_map_entry* old = map->entries;
+    size_t old_capacity = map->capacity;
+    if (!allocate_entries(map, capacity))
+        return false;
+
+    for (size_t i = 0; i < old_capacity; i++) {
+        if (old[i].state == MAP_ENTRY_FULL) {
+            size_t j = find(map, old[i].key, hash, equals);
+            map->entries[j] = old[i];
+            map->used++;
+        }
+    }
+
+    rcfree(old);
+    return true;
+}
+
+// Inserts a value into a map, replacing any previous value, and returns the map.
+static struct s_map* insert(struct s_map* map, void* key, void* value, hash_t hash, equals_t equals) {
+    // Keep the load factor, including removed entries, at most 3/4 so probing always terminates
+    if ((map->used + 1) * 4 > map->capacity * 3) {
+        size_t capacity = map->length * 2 >= map->capacity ? map->capacity * 2 : map->capacity;
+        if (!resize(map, capacity, hash, equals))
+            return NULL;
+    }
+
+    size_t i = find(map, key, hash, equals);
+    if (map->entries[i].state != MAP_ENTRY_FULL) {
+        if (map->entries[i].state == MAP_ENTRY_EMPTY)
+            map->used++;
+        map->length++;
+        map->entries[i].state = MAP_ENTRY_FULL;
+        map->entries[i].key = key;
+    }
+
+    map->entries[i].value = value;
+    rcinc(map);
+    return map;
+}
+
+// Returns the value of a key, or an error if the key is not in the map.
+static struct s_result* get(struct s_map* map, void* key, hash_t hash, equals_t equals) {
+    size_t i = find(map, key, hash, equals);
+    if (map->entries[i].state != MAP_ENTRY_FULL)
+        return result_err("key not found");
+    return result_ok(map->entries[i].value);
+}
+
+// Removes a key from a map and returns the map.
+static struct s_map* remove(struct s_map* map, void* key, hash_t hash, equals_t equals) {
+    size_t i = find(map, key, hash, equals);
+    if (map->entries[i].state == MAP_ENTRY_FULL) {
+        map->entries[i].state = MAP_ENTRY_REMOVED;
+        map->length--;
+    }
+
+    rcinc(map);
+    return map;
+}
+
+// Returns true if the map contains the key.
+static bool contains(struct s_map* map, void* key, hash_t hash, equals_t equals) {
+    return map->entries[find(map, key, hash, equals)].state == MAP_ENTRY_FULL;
+}
+
+// Creates an empty map with room for at least the given number of entries.
+struct s_map* closey_map_new(long long capacity) {
+    struct s_map* map = rcalloc(sizeof(struct s_map));
+    if (map == NULL)
+        return NULL;
+
+    size_t real_capacity = MIN_CAPACITY;
+    while (capacity > 0 && real_capacity * 3 < (size_t) capacity * 4)
+        real_capacity *= 2;
+
+    map->length = 0;
+    if (!allocate_entries(map, real_capacity))
+        return NULL;
+    return map;
+}
+
+// Returns the number of entries in the map.
+long long closey_map_length(struct s_map* map) {
+    return map->length;
+}
+
+// Returns an array of the keys or values in the map.
+static struct s_array* collect(struct s_map* map, bool keys) {
+    struct s_array* array = closey_array_make(map->length, NULL, NULL);
+    if (array == NULL)
+        return NULL;
+
+    size_t j = 0;
+    for (size_t i = 0; i < map->capacity; i++) {
+        if (map->entries[i].state == MAP_ENTRY_FULL)
+            array->items[j++] = keys ? map->entries[i].key : map->entries[i].value;
+    }
+
+    return array;
+}
+
+// Returns an array of the keys in the map.
+struct s_array* closey_map_keys(struct s_map* map) {
+    return collect(map, true);
+}
+
+// Returns an array of the values in the map, in the same order as the keys.
+struct s_array* closey_map_values(struct s_map* map) {
+    return collect(map, false);
+}
+
+// Inserts a value into a map with integer keys, replacing any previous value, and returns the map.
+struct s_map* closey_map_insert_int(struct s_map* map, long long key, void* value) {
+    return insert(map, (void*) key, value, hash_int, equals_int);
+}
+
+// Returns the value of a key in a map with integer keys, or an error if the key is not in the map.
+struct s_result* closey_map_get_int(struct s_map* map, long long key) {
+    return get(map, (void*) key, hash_int, equals_int);
+}
+
+// Removes a key from a map with integer keys and returns the map.
+struct s_map* closey_map_remove_int(struct s_map* map, long long key) {
+    return remove(map, (void*) key, hash_int, equals_int);
+}
+
+// Returns true if a map with integer keys contains the key.
+bool closey_map_contains_int(struct s_map* map, long long key) {
+    return contains(map, (void*) key, hash_int, equals_int);
+}
+
+// Booleans are stored as the integers 0 and 1, since only the lowest byte of a boolean argument is
+// defined.
+
+// Inserts a value into a map with boolean keys, replacing any previous value, and returns the map.
+struct s_map* closey_map_insert_bool(struct s_map* map, bool key, void* value) {
+    return insert(map, (void*) (long long) key, value, hash_int, equals_int);
+}
+
+// Returns the value of a key in a map with boolean keys, or an error if the key is not in the map.
+struct s_result* closey_map_get_bool(struct s_map* map, bool key) {
+    return get(map, (void*) (long long) key, hash_int, equals_int);
+}
+
+// Removes a key from a map with boolean keys and returns the map.
+struct s_map* closey_map_remove_bool(struct s_map* map, bool key) {
+    return remove(map, (void*) (long long) key, hash_int, equals_int);
+}
+
+// Returns true if a map with boolean keys contains the key.
+bool closey_map_contains_bool(struct s_map* map, bool key) {
+    return contains(map, (void*) (long long) key, hash_int, equals_int);
+}
+
+// Inserts a value into a map with string keys, replacing any previous value, and returns the map.
+struct s_map* closey_map_insert_string(struct s_map* map, struct s_string* key, void* value) {
+    return insert(map, key, value, hash_string, equals_string);
+}
+
+// Returns the value of a key in a map with string keys, or an error if the key is not in the map.
+struct s_result* closey_map_get_string(struct s_map* map, struct s_string* key) {
+    return get(map, key, hash_string, equals_string);
+}
+
+// Removes a key from a map with string keys and returns the map.
+struct s_map* closey_map_remove_string(struct s_map* map, struct s_string* key) {
+    return remove(map, key, hash_string, equals_string);
+}
+
+// Returns true if a map with string keys contains the key.
+bool closey_map_contains_string(struct s_map* map, struct s_string* key) {
+    return contains(map, key, hash_string, equals_string);
+}
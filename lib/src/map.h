@@ -0,0 +1,82 @@
+#ifndef MAP_H
+#define MAP_H
+
+#include <stddef.h>
+#include <stdbool.h>
+
+#include "array.h"
+#include "result.h"
+#include "str.h"
+
+// The states of an entry in a map.
+enum e_map_entry_state {
+    MAP_ENTRY_EMPTY = 0,
+    MAP_ENTRY_FULL = 1,
+    MAP_ENTRY_REMOVED = 2,
+};
+
+// Represents an entry in a map.
+struct s_map_entry {
+    enum e_map_entry_state state;
+    void* key;
+    void* value;
+};
+
+// Represents a reference counted hash map using open addressing with linear probing. Keys and
+// values are integers, booleans, or pointers, and are not reference counted by the map.
+struct s_map {
+    size_t length;
+    size_t used;
+    size_t capacity;
+    struct s_map_entry* entries;
+};
+
+// Creates an empty map with room for at least the given number of entries.
+struct s_map* closey_map_new(long long capacity);
+
+// Returns the number of entries in the map.
+long long closey_map_length(struct s_map* map);
+
+// Returns an array of the keys in the map.
+struct s_array* closey_map_keys(struct s_map* map);
+
+// Returns an array of the values in the map, in the same order as the keys.
+struct s_array* closey_map_values(struct s_map* map);
+
+// Inserts a value into a map with integer keys, replacing any previous value, and returns the map.
+struct s_map* closey_map_insert_int(struct s_map* map, long long key, void* value);
+
+// Returns the value of a key in a map with integer keys, or an error if the key is not in the map.
+struct s_result* closey_map_get_int(struct s_map* map, long long key);
+
+// Removes a key from a map with integer keys and returns the map.
+struct s_map* closey_map_remove_int(struct s_map* map, long long key);
+
+// Returns true if a map with integer keys contains the key.
+bool closey_map_contains_int(struct s_map* map, long long key);
+
+// Inserts a value into a map with boolean keys, replacing any previous value, and returns the map.
+struct s_map* closey_map_insert_bool(struct s_map* map, bool key, void* value);
+
+// Returns the value of a key in a map with boolean keys, or an error if the key is not in the map.
+struct s_result* closey_map_get_bool(struct s_map* map, bool key);
+
+// Removes a key from a map with boolean keys and returns the map.
+struct s_map* closey_map_remove_bool(struct s_map* map, bool key);
+
+// Returns true if a map with boolean keys contains the key.
+bool closey_map_contains_bool(struct s_map* map, bool key);
+
+// Inserts a value into a map with string keys, replacing any previous value, and returns the map.
+struct s_map* closey_map_insert_string(struct s_map* map, struct s_string* key, void* value);
+
+// Returns the value of a key in a map with string keys, or an error if the key is not in the map.
+struct s_result* closey_map_get_string(struct s_map* map, struct s_string* key);
+
+// Removes a key from a map with string keys and returns the map.
+struct s_map* closey_map_remove_string(struct s_map* map, struct s_string* key);
+
+// Returns true if a map with string keys contains the key.
+bool closey_map_contains_string(struct s_map* map, struct s_string* key);
+
+#endif /* MAP_H */
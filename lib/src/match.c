@@ -0,0 +1,129 @@
+#include <stdint.h>
+
+#include "panic.h"
+#include "data.h"
+#include "match.h"
+#include "result.h"
+#include "show.h"
+#include "unknown_arity.h"
+
+// Matches a value against the pattern starting at the given index, adding the values it binds to
+// the bindings. Returns the index after the end of the pattern, and clears matched if the value
+// does not match. The rest of the pattern is still skipped over if it does not match.
+static size_t match_pattern(void* value, struct s_string* patterns, size_t i, void** bindings, unsigned int* count, bool* matched) {
+    struct s_result* result = value;
+    switch (patterns->bytes[i]) {
+        case 'b':
+            if (*matched)
+                bindings[(*count)++] = value;
+            return i + 1;
+
+        case 'o':
+        case 's':
+            // Options are results, with some values stored the same way as ok values
+            if (*matched && result->tag != RESULT_OK)
+                *matched = false;
+            return match_pattern(*matched ? result->value : NULL, patterns, i + 1, bindings, count, matched);
+
+        case 'e':
+            if (*matched && result->tag != RESULT_ERR)
+                *matched = false;
+            return match_pattern(*matched ? result->error : NULL, patterns, i + 1, bindings, count, matched);
+
+        case 'n':
+            if (*matched && result->tag != RESULT_ERR)
+                *matched = false;
+            return i + 1;
+
+        case 't':
+        case 'f':
+            // Bools are passed as the value itself rather than a pointer to it. Runtime functions
+            // returning bools only set the lowest byte, so the rest of the value is ignored
+            if (*matched && ((uint8_t) (uintptr_t) value != 0) != (patterns->bytes[i] == 't'))
+                *matched = false;
+            return i + 1;
+
+        case 'i':
+        case 'c': {
+            // Ints and chars are passed as the value itself, with sized ints stored the same way
+            // as their literals are encoded. Chars only use the lowest 32 bits
+            uint64_t literal = 0;
+            char code = patterns->bytes[i];
+            for (i++; patterns->bytes[i] != ','; i++)
+                literal = literal * 10 + (patterns->bytes[i] - '0');
+            uint64_t n = code == 'c' ? (uint32_t) (uintptr_t) value : (uint64_t) (uintptr_t) value;
+            if (*matched && n != literal)
+                *matched = false;
+            return i + 1;
+        }
+
+        case '"': {
+            // Strings are followed by their length, ended by a comma, and then their bytes
+            size_t length = 0;
+            for (i++; patterns->bytes[i] != ','; i++)
+                length = length * 10 + (patterns->bytes[i] - '0');
+            i++;
+
+            struct s_string* string = value;
+            if (*matched && string->length != length)
+                *matched = false;
+            for (size_t j = 0; *matched && j < length; j++) {
+                if (string->bytes[j] != patterns->bytes[i + j])
+                    *matched = false;
+            }
+            return i + length;
+        }
+
+        case 'd': {
+            // Values of declared types are followed by the tag and the number of fields, each ended
+            // by a comma
+            unsigned int tag = 0;
+            for (i++; patterns->bytes[i] != ','; i++)
+                tag = tag * 10 + (patterns->bytes[i] - '0');
+            unsigned int fields = 0;
+            for (i++; patterns->bytes[i] != ','; i++)
+                fields = fields * 10 + (patterns->bytes[i] - '0');
+            i++;
+
+            struct s_data* data = value;
+            if (*matched && data->tag != tag)
+                *matched = false;
+            for (unsigned int field = 0; field < fields; field++)
+                i = match_pattern(*matched ? data->fields[field] : NULL, patterns, i, bindings, count, matched);
+            return i;
+        }
+
+        default:
+            return i + 1;
+    }
+}
+
+// Matches a value against the patterns of the arms of a match expression and calls the first arm
+// that matches with the value followed by the values bound by its pattern. Panics if no arm
+// matches, which type checked code only does with a value of a declared type whose tag is not one
+// of its constructors, so the tag is reported.
+void* closey_match(void* value, struct s_string* patterns, struct s_array* arms, struct s_string* loc) {
+    // Every binding takes one byte of the patterns, so this is enough for any arm
+    void* bindings[patterns->length + 1];
+
+    size_t i = 0;
+    for (size_t arm = 0; arm < arms->length; arm++) {
+        bindings[0] = value;
+        unsigned int count = 1;
+        bool matched = true;
+        i = match_pattern(value, patterns, i, bindings, &count, &matched);
+        if (matched)
+            return call_unknown_arity(arms->items[arm], count, bindings);
+    }
+
+    static const char msg[] = "no match arm matched";
+    struct s_string* message = string_new(msg, sizeof(msg) - 1);
+
+    // Every arm has a pattern of the same type, so the first one says whether it has a tag
+    if (arms->length > 0 && patterns->bytes[0] == 'd') {
+        static const char tag[] = " the value with tag ";
+        message = string_concat(message, string_new(tag, sizeof(tag) - 1));
+        message = string_concat(message, closey_show_int(((struct s_data*) value)->tag));
+    }
+    return closey_panic(message, loc);
+}
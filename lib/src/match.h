@@ -0,0 +1,19 @@
+#ifndef MATCH_H
+#define MATCH_H
+
+#include "array.h"
+#include "str.h"
+
+// Matches a value against the patterns of the arms of a match expression and calls the first arm
+// that matches with the value followed by the values bound by its pattern. Patterns are encoded one
+// after another: `_` matches anything, `b` matches anything and binds it, `o` and `e` match ok and
+// error results whose field matches the pattern that follows, `t` and `f` match the bools true and
+// false, and `d` matches values of declared types with the tag and number of fields that follow,
+// each ended by a comma, whose fields match the patterns after that. `i` and `c` match the int or
+// char whose value follows, ended by a comma, and `"` matches the string whose length follows,
+// ended by a comma, and whose bytes come after that. Panics if no arm matches, reporting the tag of
+// a value of a declared type. The location is the source location of the match expression, of the
+// form `file:line:column`.
+void* closey_match(void* value, struct s_string* patterns, struct s_array* arms, struct s_string* loc);
+
+#endif /* MATCH_H */
@@ -0,0 +1,120 @@
+#include "math.h"
+
+// The smallest magnitude at which every double is integral.
+#define INTEGRAL_LIMIT 4503599627370496.0
+
+// Returns the square root of a float.
+unsigned long long closey_sqrt(unsigned long long x) {
+    double r;
+    asm("sqrtsd %0, %1" : "=x" (r) : "x" (from_bits(x)));
+    return to_bits(r);
+}
+
+// Returns the sine of a float in radians.
+unsigned long long closey_sin(unsigned long long x) {
+    long double r;
+    asm("fsin" : "=t" (r) : "0" ((long double) from_bits(x)));
+    return to_bits(r);
+}
+
+// Returns the cosine of a float in radians.
+unsigned long long closey_cos(unsigned long long x) {
+    long double r;
+    asm("fcos" : "=t" (r) : "0" ((long double) from_bits(x)));
+    return to_bits(r);
+}
+
+// Returns the tangent of a float in radians.
+unsigned long long closey_tan(unsigned long long x) {
+    // fptan pushes 1.0 after the result, so pop it off
+    long double r;
+    asm("fptan\n\tfstp st(0)" : "=t" (r) : "0" ((long double) from_bits(x)));
+    return to_bits(r);
+}
+
+// Returns e raised to the power of a float.
+unsigned long long closey_exp(unsigned long long x) {
+    // e^x = 2^(x * log2(e)), and 2^y is calculated as 2^(y - n) * 2^n where n is y rounded
+    long double r;
+    asm(
+        "fldl2e\n\t"
+        "fmulp\n\t"
+        "fld st(0)\n\t"
+        "frndint\n\t"
+        "fsub st(1), st\n\t"
+        "fxch\n\t"
+        "f2xm1\n\t"
+        "fld1\n\t"
+        "faddp\n\t"
+        "fscale\n\t"
+        "fstp st(1)"
+        : "=t" (r) : "0" ((long double) from_bits(x))
+    );
+    return to_bits(r);
+}
+
+// Returns the natural logarithm of a float.
+unsigned long long closey_ln(unsigned long long x) {
+    // ln(x) = ln(2) * log2(x)
+    long double r;
+    asm("fldln2\n\tfxch\n\tfyl2x" : "=t" (r) : "0" ((long double) from_bits(x)));
+    return to_bits(r);
+}
+
+// Returns the largest integral float not greater than a float.
+unsigned long long closey_floor(unsigned long long x) {
+    double v = from_bits(x);
+
+    // Large values, infinities, and NaNs are returned as is
+    if (!(v > -INTEGRAL_LIMIT && v < INTEGRAL_LIMIT))
+        return x;
+
+    double r = (double) (long long) v;
+    if (r > v)
+        r -= 1.0;
+    return to_bits(r);
+}
+
+// Returns the smallest integral float not less than a float.
+unsigned long long closey_ceil(unsigned long long x) {
+    double v = from_bits(x);
+
+    // Large values, infinities, and NaNs are returned as is
+    if (!(v > -INTEGRAL_LIMIT && v < INTEGRAL_LIMIT))
+        return x;
+
+    double r = (double) (long long) v;
+    if (r < v)
+        r += 1.0;
+    return to_bits(r);
+}
+
+// Returns the absolute value of a float.
+unsigned long long closey_abs(unsigned long long x) {
+    return x & 0x7fffffffffffffffull;
+}
+
+// Returns the smaller of two floats.
+unsigned long long closey_min(unsigned long long a, unsigned long long b) {
+    return from_bits(a) <= from_bits(b) ? a : b;
+}
+
+// Returns the larger of two floats.
+unsigned long long closey_max(unsigned long long a, unsigned long long b) {
+    return from_bits(a) >= from_bits(b) ? a : b;
+}
+
+// Returns the absolute value of an integer.
+long long closey_abs_int(long long x) {
+    return x < 0 ? -x : x;
+}
+
+// Returns the smaller of two integers.
+long long closey_min_int(long long a, long long b) {
+    return a <= b ? a : b;
+}
+
+// Returns the larger of two integers.
+long long closey_max_int(long long a, long long b) {
+    return a >= b ? a : b;
+}
@@ -0,0 +1,67 @@
+#ifndef MATH_H
+#define MATH_H
+
+// Floats are passed in and returned from these functions as their bits in general purpose
+// registers, since that is how Closey represents them.
+
+// Converts the bits of a float into a float.
+static inline double from_bits(unsigned long long x) {
+    union {
+        unsigned long long bits;
+        double value;
+    } u = { .bits = x };
+    return u.value;
+}
+
+// Converts a float into its bits.
+static inline unsigned long long to_bits(double x) {
+    union {
+        double value;
+        unsigned long long bits;
+    } u = { .value = x };
+    return u.bits;
+}
+
+// Returns the square root of a float.
+unsigned long long closey_sqrt(unsigned long long x);
+
+// Returns the sine of a float in radians.
+unsigned long long closey_sin(unsigned long long x);
+
+// Returns the cosine of a float in radians.
+unsigned long long closey_cos(unsigned long long x);
+
+// Returns the tangent of a float in radians.
+unsigned long long closey_tan(unsigned long long x);
+
+// Returns e raised to the power of a float.
+unsigned long long closey_exp(unsigned long long x);
+
+// Returns the natural logarithm of a float.
+unsigned long long closey_ln(unsigned long long x);
+
+// Returns the largest integral float not greater than a float.
+unsigned long long closey_floor(unsigned long long x);
+
+// Returns the smallest integral float not less than a float.
+unsigned long long closey_ceil(unsigned long long x);
+
+// Returns the absolute value of a float.
+unsigned long long closey_abs(unsigned long long x);
+
+// Returns the smaller of two floats.
+unsigned long long closey_min(unsigned long long a, unsigned long long b);
+
+// Returns the larger of two floats.
+unsigned long long closey_max(unsigned long long a, unsigned long long b);
+
+// Returns the absolute value of an integer.
+long long closey_abs_int(long long x);
+
+// Returns the smaller of two integers.
+long long closey_min_int(long long a, long long b);
+
+// Returns the larger of two integers.
+long long closey_max_int(long long a, long long b);
+
+#endif /* MATH_H */
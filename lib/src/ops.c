@@ -0,0 +1,404 @@
+#include <limits.h>
+
+#include "panic.h"
+#include "math.h"
+#include "ops.h"
+
+// Panics with the message for dividing by zero.
+static void divide_by_zero(struct s_string* loc) {
+    static const char msg[] = "attempt to divide by zero";
+    closey_panic(string_new(msg, sizeof(msg) - 1), loc);
+}
+
+// Panics with the message for an operation that overflows, which is a string literal.
+#define overflow(msg, loc) closey_panic(string_new(msg, sizeof(msg) - 1), loc)
+
+// Returns the product of two ints.
+long long closey_int_mul(long long a, long long b) {
+    // Arithmetic is done unsigned so that overflow wraps
+    return (unsigned long long) a * (unsigned long long) b;
+}
+
+// Returns the quotient of two ints rounded towards zero, panicking if the divisor is zero. The
+// location is the source location of the division, of the form `file:line:column`.
+long long closey_int_div(long long a, long long b, struct s_string* loc) {
+    if (b == 0)
+        divide_by_zero(loc);
+    return closey_int_div_unchecked(a, b);
+}
+
+// Returns the quotient of two ints rounded towards zero, without checking that the divisor is not
+// zero.
+long long closey_int_div_unchecked(long long a, long long b) {
+    // The quotient of the smallest int and -1 overflows, so it wraps around to itself
+    if (b == -1)
+        return -(unsigned long long) a;
+    return a / b;
+}
+
+// Returns the remainder of dividing two ints, which has the sign of the dividend, panicking if the
+// divisor is zero. The location is the source location of the operation, of the form
+// `file:line:column`.
+long long closey_int_mod(long long a, long long b, struct s_string* loc) {
+    if (b == 0)
+        divide_by_zero(loc);
+    return closey_int_mod_unchecked(a, b);
+}
+
+// Returns the remainder of dividing two ints, without checking that the divisor is not zero.
+long long closey_int_mod_unchecked(long long a, long long b) {
+    if (b == -1)
+        return 0;
+    return a % b;
+}
+
+// Returns the sum of two ints.
+long long closey_int_add(long long a, long long b) {
+    return (unsigned long long) a + (unsigned long long) b;
+}
+
+// Returns the difference of two ints.
+long long closey_int_sub(long long a, long long b) {
+    return (unsigned long long) a - (unsigned long long) b;
+}
+
+// Shifts an int left.
+long long closey_int_shl(long long a, long long b) {
+    return (unsigned long long) a << (b & 63);
+}
+
+// Shifts an int right, keeping its sign.
+long long closey_int_shr(long long a, long long b) {
+    return a >> (b & 63);
+}
+
+// Returns the bitwise and of two ints.
+long long closey_int_and(long long a, long long b) {
+    return a & b;
+}
+
+// Returns the bitwise not of an int.
+long long closey_int_not(long long a) {
+    return ~a;
+}
+
+// Returns the bitwise xor of two ints.
+long long closey_int_xor(long long a, long long b) {
+    return a ^ b;
+}
+
+// Returns the bitwise or of two ints.
+long long closey_int_or(long long a, long long b) {
+    return a | b;
+}
+
+// Returns the product of two ints, panicking on overflow.
+long long closey_int_mul_checked(long long a, long long b, struct s_string* loc) {
+    long long result;
+    if (__builtin_mul_overflow(a, b, &result))
+        overflow("attempt to multiply with overflow", loc);
+    return result;
+}
+
+// Returns the quotient of two ints rounded towards zero, panicking if the divisor is zero or the
+// quotient overflows.
+long long closey_int_div_checked(long long a, long long b, struct s_string* loc) {
+    if (b == 0)
+        divide_by_zero(loc);
+    if (b == -1 && a == LLONG_MIN)
+        overflow("attempt to divide with overflow", loc);
+    return a / b;
+}
+
+// Returns the sum of two ints, panicking on overflow.
+long long closey_int_add_checked(long long a, long long b, struct s_string* loc) {
+    long long result;
+    if (__builtin_add_overflow(a, b, &result))
+        overflow("attempt to add with overflow", loc);
+    return result;
+}
+
+// Returns the difference of two ints, panicking on overflow.
+long long closey_int_sub_checked(long long a, long long b, struct s_string* loc) {
+    long long result;
+    if (__builtin_sub_overflow(a, b, &result))
+        overflow("attempt to subtract with overflow", loc);
+    return result;
+}
+
+// Returns true if the first int is less than the second.
+bool closey_int_lt(long long a, long long b) {
+    return a < b;
+}
+
+// Returns true if the first int is greater than the second.
+bool closey_int_gt(long long a, long long b) {
+    return a > b;
+}
+
+// Returns true if the first int is less than or equal to the second.
+bool closey_int_le(long long a, long long b) {
+    return a <= b;
+}
+
+// Returns true if the first int is greater than or equal to the second.
+bool closey_int_ge(long long a, long long b) {
+    return a >= b;
+}
+
+// Returns true if two ints are equal.
+bool closey_int_eq(long long a, long long b) {
+    return a == b;
+}
+
+// Returns true if two ints are not equal.
+bool closey_int_ne(long long a, long long b) {
+    return a != b;
+}
+
+// Returns the product of two floats.
+unsigned long long closey_float_mul(unsigned long long a, unsigned long long b) {
+    return to_bits(from_bits(a) * from_bits(b));
+}
+
+// Returns the quotient of two floats.
+unsigned long long closey_float_div(unsigned long long a, unsigned long long b) {
+    return to_bits(from_bits(a) / from_bits(b));
+}
+
+// Returns the sum of two floats.
+unsigned long long closey_float_add(unsigned long long a, unsigned long long b) {
+    return to_bits(from_bits(a) + from_bits(b));
+}
+
+// Returns the difference of two floats.
+unsigned long long closey_float_sub(unsigned long long a, unsigned long long b) {
+    return to_bits(from_bits(a) - from_bits(b));
+}
+
+// Returns true if the first float is less than the second.
+bool closey_float_lt(unsigned long long a, unsigned long long b) {
+    return from_bits(a) < from_bits(b);
+}
+
+// Returns true if the first float is greater than the second.
+bool closey_float_gt(unsigned long long a, unsigned long long b) {
+    return from_bits(a) > from_bits(b);
+}
+
+// Returns true if the first float is less than or equal to the second.
+bool closey_float_le(unsigned long long a, unsigned long long b) {
+    return from_bits(a) <= from_bits(b);
+}
+
+// Returns true if the first float is greater than or equal to the second.
+bool closey_float_ge(unsigned long long a, unsigned long long b) {
+    return from_bits(a) >= from_bits(b);
+}
+
+// Returns true if two floats are equal. NaN is not equal to anything, including itself.
+bool closey_float_eq(unsigned long long a, unsigned long long b) {
+    return from_bits(a) == from_bits(b);
+}
+
+// Returns true if two floats are not equal.
+bool closey_float_ne(unsigned long long a, unsigned long long b) {
+    return from_bits(a) != from_bits(b);
+}
+
+// Converts an int into a float, rounding to the nearest float if it cannot be represented exactly.
+unsigned long long closey_int_to_float(long long n) {
+    return to_bits((double) n);
+}
+
+// Converts a float into an int, truncating towards zero. Floats beyond the range of ints saturate
+// to the smallest or largest int, and NaN converts to 0.
+long long closey_float_to_int(unsigned long long bits) {
+    double n = from_bits(bits);
+    if (n != n)
+        return 0;
+    else if (n <= -9223372036854775808.0)
+        return (-9223372036854775807LL - 1);
+    else if (n >= 9223372036854775808.0)
+        return 9223372036854775807LL;
+    return (long long) n;
+}
+
+// Converts a bool into an int, which is 1 for true and 0 for false.
+long long closey_bool_to_int(bool b) {
+    return b ? 1 : 0;
+}
+
+// Returns true if exactly one of the bools is true.
+bool closey_bool_xor(bool a, bool b) {
+    return a != b;
+}
+
+// Returns true if the bool is false.
+bool closey_bool_not(bool a) {
+    return !a;
+}
+
+// Returns true if two bools are the same.
+bool closey_bool_eq(bool a, bool b) {
+    return a == b;
+}
+
+// Returns true if two bools are different.
+bool closey_bool_ne(bool a, bool b) {
+    return a != b;
+}
+
+// Defines the operators on a sized int type. Operands are converted into the type, or into a 64 bit
+// int of the same signedness for division and shifts, so that the operations never overflow in C.
+// Conversions into the type wrap around, except for floats, which saturate to the smallest or
+// largest value of the type.
+#define SIZED_INT_IMPL(name, type, wide, min, max)                                                 \
+    long long closey_##name##_mul(long long a, long long b) {                                      \
+        return (type) ((unsigned long long) a * (unsigned long long) b);                           \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_div(long long a, long long b, struct s_string* loc) {                \
+        if (b == 0)                                                                                \
+            divide_by_zero(loc);                                                                   \
+        return closey_##name##_div_unchecked(a, b);                                                \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_div_unchecked(long long a, long long b) {                            \
+        return (type) ((wide) (type) a / (wide) (type) b);                                         \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_mod(long long a, long long b, struct s_string* loc) {                \
+        if (b == 0)                                                                                \
+            divide_by_zero(loc);                                                                   \
+        return closey_##name##_mod_unchecked(a, b);                                                \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_mod_unchecked(long long a, long long b) {                            \
+        return (type) ((wide) (type) a % (wide) (type) b);                                         \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_add(long long a, long long b) {                                      \
+        return (type) ((unsigned long long) a + (unsigned long long) b);                           \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_sub(long long a, long long b) {                                      \
+        return (type) ((unsigned long long) a - (unsigned long long) b);                           \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_shl(long long a, long long b) {                                      \
+        return (type) ((unsigned long long) a << (b & 63));                                       \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_shr(long long a, long long b) {                                      \
+        return (type) ((wide) (type) a >> (b & 63));                                               \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_and(long long a, long long b) {                                      \
+        return (type) (a & b);                                                                     \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_xor(long long a, long long b) {                                      \
+        return (type) (a ^ b);                                                                     \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_or(long long a, long long b) {                                       \
+        return (type) (a | b);                                                                     \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_not(long long a) {                                                   \
+        return (type) ~a;                                                                          \
+    }                                                                                              \
+                                                                                                   \
+    bool closey_##name##_lt(long long a, long long b) {                                            \
+        return (type) a < (type) b;                                                                \
+    }                                                                                              \
+                                                                                                   \
+    bool closey_##name##_gt(long long a, long long b) {                                            \
+        return (type) a > (type) b;                                                                \
+    }                                                                                              \
+                                                                                                   \
+    bool closey_##name##_le(long long a, long long b) {                                            \
+        return (type) a <= (type) b;                                                               \
+    }                                                                                              \
+                                                                                                   \
+    bool closey_##name##_ge(long long a, long long b) {                                            \
+        return (type) a >= (type) b;                                                               \
+    }                                                                                              \
+                                                                                                   \
+    bool closey_##name##_eq(long long a, long long b) {                                            \
+        return (type) a == (type) b;                                                               \
+    }                                                                                              \
+                                                                                                   \
+    bool closey_##name##_ne(long long a, long long b) {                                            \
+        return (type) a != (type) b;                                                               \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_mul_checked(long long a, long long b, struct s_string* loc) {        \
+        type result;                                                                               \
+        if (__builtin_mul_overflow((type) a, (type) b, &result))                                   \
+            overflow("attempt to multiply with overflow", loc);                                    \
+        return result;                                                                             \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_div_checked(long long a, long long b, struct s_string* loc) {        \
+        if (b == 0)                                                                                \
+            divide_by_zero(loc);                                                                   \
+        wide result = (wide) (type) a / (wide) (type) b;                                           \
+        if (result != (type) result)                                                               \
+            overflow("attempt to divide with overflow", loc);                                      \
+        return (type) result;                                                                      \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_add_checked(long long a, long long b, struct s_string* loc) {        \
+        type result;                                                                               \
+        if (__builtin_add_overflow((type) a, (type) b, &result))                                   \
+            overflow("attempt to add with overflow", loc);                                         \
+        return result;                                                                             \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_##name##_sub_checked(long long a, long long b, struct s_string* loc) {        \
+        type result;                                                                               \
+        if (__builtin_sub_overflow((type) a, (type) b, &result))                                   \
+            overflow("attempt to subtract with overflow", loc);                                    \
+        return result;                                                                             \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_int_to_##name(long long n) {                                                  \
+        return (type) n;                                                                           \
+    }                                                                                              \
+                                                                                                   \
+    long long closey_float_to_##name(unsigned long long bits) {                                    \
+        double n = from_bits(bits);                                                                \
+        if (n != n)                                                                                \
+            return 0;                                                                              \
+        else if (n <= (double) min)                                                                \
+            return min;                                                                            \
+        else if (n >= (double) max)                                                                \
+            return (type) max;                                                                     \
+        return (type) n;                                                                           \
+    }
+
+SIZED_INT_IMPL(int8, int8_t, long long, INT8_MIN, INT8_MAX)
+SIZED_INT_IMPL(int16, int16_t, long long, INT16_MIN, INT16_MAX)
+SIZED_INT_IMPL(int32, int32_t, long long, INT32_MIN, INT32_MAX)
+SIZED_INT_IMPL(uint8, uint8_t, unsigned long long, 0, UINT8_MAX)
+SIZED_INT_IMPL(uint16, uint16_t, unsigned long long, 0, UINT16_MAX)
+SIZED_INT_IMPL(uint32, uint32_t, unsigned long long, 0, UINT32_MAX)
+SIZED_INT_IMPL(uint64, uint64_t, unsigned long long, 0, UINT64_MAX)
+
+// Converts an unsigned 64 bit int into a float, rounding to the nearest float if it cannot be
+// represented exactly.
+unsigned long long closey_uint64_to_float(long long n) {
+    return to_bits((double) (unsigned long long) n);
+}
+
+// Returns true if two strings have the same contents.
+bool closey_string_eq(struct s_string* a, struct s_string* b) {
+    return string_equals(a, b);
+}
+
+// Returns true if two strings have different contents.
+bool closey_string_ne(struct s_string* a, struct s_string* b) {
+    return !string_equals(a, b);
+}
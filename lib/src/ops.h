@@ -0,0 +1,191 @@
+#ifndef OPS_H
+#define OPS_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#include "str.h"
+
+// The infix operators on primitive values. Ints wrap on overflow, and shift amounts are taken
+// modulo 64. Floats are passed in and returned as their bits, the same as in math.h.
+
+// Returns the product of two ints.
+long long closey_int_mul(long long a, long long b);
+
+// Returns the quotient of two ints rounded towards zero, panicking if the divisor is zero. The
+// location is the source location of the division, of the form `file:line:column`.
+long long closey_int_div(long long a, long long b, struct s_string* loc);
+
+// Returns the quotient of two ints rounded towards zero. Dividing by zero is undefined behaviour,
+// so this is only called by programs built with `release`.
+long long closey_int_div_unchecked(long long a, long long b);
+
+// Returns the remainder of dividing two ints, which has the sign of the dividend, panicking if the
+// divisor is zero. The location is the source location of the operation, of the form
+// `file:line:column`.
+long long closey_int_mod(long long a, long long b, struct s_string* loc);
+
+// Returns the remainder of dividing two ints. Dividing by zero is undefined behaviour, so this is
+// only called by programs built with `release`.
+long long closey_int_mod_unchecked(long long a, long long b);
+
+// Returns the sum of two ints.
+long long closey_int_add(long long a, long long b);
+
+// Returns the difference of two ints.
+long long closey_int_sub(long long a, long long b);
+
+// Shifts an int left.
+long long closey_int_shl(long long a, long long b);
+
+// Shifts an int right, keeping its sign.
+long long closey_int_shr(long long a, long long b);
+
+// Returns the bitwise and of two ints.
+long long closey_int_and(long long a, long long b);
+
+// Returns the bitwise not of an int.
+long long closey_int_not(long long a);
+
+// Returns the bitwise xor of two ints.
+long long closey_int_xor(long long a, long long b);
+
+// Returns the bitwise or of two ints.
+long long closey_int_or(long long a, long long b);
+
+// The checked versions of the int operators panic when the result overflows instead of wrapping.
+// The location is the source location of the operation, of the form `file:line:column`.
+
+// Returns the product of two ints, panicking on overflow.
+long long closey_int_mul_checked(long long a, long long b, struct s_string* loc);
+
+// Returns the quotient of two ints rounded towards zero, panicking if the divisor is zero or the
+// quotient overflows.
+long long closey_int_div_checked(long long a, long long b, struct s_string* loc);
+
+// Returns the sum of two ints, panicking on overflow.
+long long closey_int_add_checked(long long a, long long b, struct s_string* loc);
+
+// Returns the difference of two ints, panicking on overflow.
+long long closey_int_sub_checked(long long a, long long b, struct s_string* loc);
+
+// Returns true if the first int is less than the second.
+bool closey_int_lt(long long a, long long b);
+
+// Returns true if the first int is greater than the second.
+bool closey_int_gt(long long a, long long b);
+
+// Returns true if the first int is less than or equal to the second.
+bool closey_int_le(long long a, long long b);
+
+// Returns true if the first int is greater than or equal to the second.
+bool closey_int_ge(long long a, long long b);
+
+// Returns true if two ints are equal.
+bool closey_int_eq(long long a, long long b);
+
+// Returns true if two ints are not equal.
+bool closey_int_ne(long long a, long long b);
+
+// Returns the product of two floats.
+unsigned long long closey_float_mul(unsigned long long a, unsigned long long b);
+
+// Returns the quotient of two floats.
+unsigned long long closey_float_div(unsigned long long a, unsigned long long b);
+
+// Returns the sum of two floats.
+unsigned long long closey_float_add(unsigned long long a, unsigned long long b);
+
+// Returns the difference of two floats.
+unsigned long long closey_float_sub(unsigned long long a, unsigned long long b);
+
+// Returns true if the first float is less than the second.
+bool closey_float_lt(unsigned long long a, unsigned long long b);
+
+// Returns true if the first float is greater than the second.
+bool closey_float_gt(unsigned long long a, unsigned long long b);
+
+// Returns true if the first float is less than or equal to the second.
+bool closey_float_le(unsigned long long a, unsigned long long b);
+
+// Returns true if the first float is greater than or equal to the second.
+bool closey_float_ge(unsigned long long a, unsigned long long b);
+
+// Returns true if two floats are equal. NaN is not equal to anything, including itself.
+bool closey_float_eq(unsigned long long a, unsigned long long b);
+
+// Returns true if two floats are not equal.
+bool closey_float_ne(unsigned long long a, unsigned long long b);
+
+// Converts an int into a float, rounding to the nearest float if it cannot be represented exactly.
+unsigned long long closey_int_to_float(long long n);
+
+// Converts a float into an int, truncating towards zero. Floats beyond the range of ints saturate
+// to the smallest or largest int, and NaN converts to 0.
+long long closey_float_to_int(unsigned long long bits);
+
+// Converts a bool into an int, which is 1 for true and 0 for false.
+long long closey_bool_to_int(bool b);
+
+// Returns true if exactly one of the bools is true.
+bool closey_bool_xor(bool a, bool b);
+
+// Returns true if the bool is false.
+bool closey_bool_not(bool a);
+
+// Returns true if two bools are the same.
+bool closey_bool_eq(bool a, bool b);
+
+// Returns true if two bools are different.
+bool closey_bool_ne(bool a, bool b);
+
+// The infix operators on the sized int types, along with conversions into them. Values of sized
+// int types are passed in 64 bits, sign extended or zero extended from their width, and arithmetic
+// wraps around into the range of the type. The functions for each type are named after it, such as
+// `closey_uint8_add`, and take the same arguments as the functions for ints.
+#define SIZED_INT_OPS(name, type)                                                                  \
+    long long closey_##name##_mul(long long a, long long b);                                       \
+    long long closey_##name##_div(long long a, long long b, struct s_string* loc);                 \
+    long long closey_##name##_mod(long long a, long long b, struct s_string* loc);                 \
+    long long closey_##name##_div_unchecked(long long a, long long b);                             \
+    long long closey_##name##_mod_unchecked(long long a, long long b);                             \
+    long long closey_##name##_add(long long a, long long b);                                       \
+    long long closey_##name##_sub(long long a, long long b);                                       \
+    long long closey_##name##_shl(long long a, long long b);                                       \
+    long long closey_##name##_shr(long long a, long long b);                                       \
+    long long closey_##name##_and(long long a, long long b);                                       \
+    long long closey_##name##_xor(long long a, long long b);                                       \
+    long long closey_##name##_or(long long a, long long b);                                        \
+    long long closey_##name##_not(long long a);                                                    \
+    bool closey_##name##_lt(long long a, long long b);                                             \
+    bool closey_##name##_gt(long long a, long long b);                                             \
+    bool closey_##name##_le(long long a, long long b);                                             \
+    bool closey_##name##_ge(long long a, long long b);                                             \
+    bool closey_##name##_eq(long long a, long long b);                                             \
+    bool closey_##name##_ne(long long a, long long b);                                             \
+    long long closey_##name##_mul_checked(long long a, long long b, struct s_string* loc);         \
+    long long closey_##name##_div_checked(long long a, long long b, struct s_string* loc);         \
+    long long closey_##name##_add_checked(long long a, long long b, struct s_string* loc);         \
+    long long closey_##name##_sub_checked(long long a, long long b, struct s_string* loc);         \
+    long long closey_int_to_##name(long long n);                                                   \
+    long long closey_float_to_##name(unsigned long long bits);
+
+SIZED_INT_OPS(int8, int8_t)
+SIZED_INT_OPS(int16, int16_t)
+SIZED_INT_OPS(int32, int32_t)
+SIZED_INT_OPS(uint8, uint8_t)
+SIZED_INT_OPS(uint16, uint16_t)
+SIZED_INT_OPS(uint32, uint32_t)
+SIZED_INT_OPS(uint64, uint64_t)
+
+// Converts an unsigned 64 bit int into a float, rounding to the nearest float if it cannot be
+// represented exactly.
+unsigned long long closey_uint64_to_float(long long n);
+
+// Returns true if two strings have the same contents.
+bool closey_string_eq(struct s_string* a, struct s_string* b);
+
+// Returns true if two strings have different contents.
+bool closey_string_ne(struct s_string* a, struct s_string* b);
+
+#endif /* OPS_H */
@@ -0,0 +1,40 @@
+#include "panic.h"
+#include "syscalls.h"
+
+#define STDERR 2
+
+// Writes a null terminated string to stderr.
+static void write_cstr(const char* s) {
+    size_t length = 0;
+    while (s[length])
+        length++;
+    write(STDERR, s, length);
+}
+
+// Panics with the given message if the condition is false. The location is the source location of
+// the assertion, of the form `file:line:column`, and the code is the source code of the condition.
+void* closey_assert(bool cond, struct s_string* msg, struct s_string* loc, struct s_string* code) {
+    if (!cond) {
+        write_cstr("assertion `");
+        write(STDERR, code->bytes, code->length);
+        write_cstr("` failed at ");
+        write(STDERR, loc->bytes, loc->length);
+        write_cstr(": ");
+        write(STDERR, msg->bytes, msg->length);
+        write_cstr("\n");
+        exit(PANIC_EXIT_CODE);
+    }
+
+    return NULL;
+}
+
+// Prints the given message and source location to stderr and exits the process.
+void* closey_panic(struct s_string* msg, struct s_string* loc) {
+    write_cstr("panicked at ");
+    write(STDERR, loc->bytes, loc->length);
+    write_cstr(": ");
+    write(STDERR, msg->bytes, msg->length);
+    write_cstr("\n");
+    exit(PANIC_EXIT_CODE);
+    return NULL;
+}
@@ -0,0 +1,18 @@
+#ifndef PANIC_H
+#define PANIC_H
+
+#include <stdbool.h>
+
+#include "str.h"
+
+// The exit code of a process that panicked.
+#define PANIC_EXIT_CODE 101
+
+// Panics with the given message if the condition is false. The location is the source location of
+// the assertion, of the form `file:line:column`, and the code is the source code of the condition.
+void* closey_assert(bool cond, struct s_string* msg, struct s_string* loc, struct s_string* code);
+
+// Prints the given message and source location to stderr and exits the process.
+void* closey_panic(struct s_string* msg, struct s_string* loc);
+
+#endif /* PANIC_H */
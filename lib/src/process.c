@@ -0,0 +1,110 @@
+#include "process.h"
+#include "rc.h"
+#include "syscalls.h"
+
+#define O_RDONLY 00
+#define SHELL "/bin/sh"
+
+// Exits the process with the given exit code.
+void* closey_exit(long long code) {
+    exit(code);
+    return NULL;
+}
+
+// Reads the environment of the current process as a null terminated array of strings, so that it
+// can be passed on to child processes. Returns NULL if the environment cannot be read.
+static char** read_environ() {
+    int fd = open("/proc/self/environ", O_RDONLY, 0);
+    if (fd < 0)
+        return NULL;
+
+    size_t capacity = 4096;
+    size_t length = 0;
+    char* buffer = rcalloc(capacity);
+    while (buffer != NULL) {
+        // Grow the buffer, leaving space for a final null byte
+        if (length + 1 >= capacity) {
+            char* bigger = rcalloc(capacity * 2);
+            if (bigger != NULL) {
+                for (size_t i = 0; i < length; i++) {
+                    bigger[i] = buffer[i];
+                }
+            }
+            rcfree(buffer);
+            buffer = bigger;
+            capacity *= 2;
+            continue;
+        }
+
+        long count = read(fd, buffer + length, capacity - length - 1);
+        if (count <= 0)
+            break;
+        length += count;
+    }
+    close(fd);
+
+    if (buffer == NULL)
+        return NULL;
+    buffer[length] = '\0';
+
+    // Each variable is terminated by a null byte
+    size_t count = 0;
+    for (size_t i = 0; i < length; i++) {
+        if (buffer[i] == '\0')
+            count++;
+    }
+
+    char** environ = rcalloc((count + 1) * sizeof(char*));
+    if (environ == NULL)
+        return NULL;
+
+    size_t var = 0;
+    for (size_t i = 0; i < length; i++) {
+        if (i == 0 || buffer[i - 1] == '\0')
+            environ[var++] = buffer + i;
+    }
+    environ[var] = NULL;
+
+    return environ;
+}
+
+// Runs a command with the shell and waits for it to finish, returning its exit code.
+struct s_result* closey_run_command(struct s_string* cmd) {
+    char* command = rcalloc(cmd->length + 1);
+    if (command == NULL)
+        return result_err("out of memory");
+    for (size_t i = 0; i < cmd->length; i++) {
+        if (cmd->bytes[i] == '\0') {
+            rcfree(command);
+            return result_err("command contains a null byte");
+        }
+        command[i] = cmd->bytes[i];
+    }
+    command[cmd->length] = '\0';
+
+    char* const argv[] = { SHELL, "-c", command, NULL };
+    char** environ = read_environ();
+    char* empty[] = { NULL };
+
+    int pid = fork();
+    if (pid < 0) {
+        rcfree(command);
+        return result_err("could not create process");
+    } else if (pid == 0) {
+        execve(SHELL, argv, environ != NULL ? environ : empty);
+
+        // 127 is what shells exit with if a command cannot be run
+        exit(127);
+    }
+
+    int status = 0;
+    int err = wait4(pid, &status, 0, NULL);
+    rcfree(command);
+    if (err < 0)
+        return result_err("could not wait for process");
+
+    // Processes killed by a signal exit with 128 plus the signal number, like in shells
+    if ((status & 0x7f) != 0)
+        return result_ok_int(128 + (status & 0x7f));
+    return result_ok_int((status >> 8) & 0xff);
+}
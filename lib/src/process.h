@@ -0,0 +1,13 @@
+#ifndef PROCESS_H
+#define PROCESS_H
+
+#include "result.h"
+#include "str.h"
+
+// Exits the process with the given exit code.
+void* closey_exit(long long code);
+
+// Runs a command with the shell and waits for it to finish, returning its exit code.
+struct s_result* closey_run_command(struct s_string* cmd);
+
+#endif /* PROCESS_H */
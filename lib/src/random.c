@@ -0,0 +1,42 @@
+#include "panic.h"
+#include "math.h"
+#include "random.h"
+
+// The state of the generator, which is advanced by a constant for each number generated.
+static unsigned long long state = 0;
+
+// Returns the next 64 random bits.
+static unsigned long long next() {
+    state += 0x9e3779b97f4a7c15;
+    unsigned long long z = state;
+    z = (z ^ (z >> 30)) * 0xbf58476d1ce4e5b9;
+    z = (z ^ (z >> 27)) * 0x94d049bb133111eb;
+    return z ^ (z >> 31);
+}
+
+// Sets the state of the generator.
+void* closey_seed_random(long long seed) {
+    state = seed;
+    return NULL;
+}
+
+// Returns a random int that is at least the lower bound and less than the upper bound, panicking
+// if there is no such int.
+long long closey_random_int(long long lo, long long hi, struct s_string* loc) {
+    if (hi <= lo) {
+        static const char msg[] = "random range is empty";
+        closey_panic(string_new(msg, sizeof(msg) - 1), loc);
+    }
+
+    // The bits are scaled into the range rather than taken modulo its size, so that every int in
+    // the range is equally likely
+    unsigned long long range = (unsigned long long) hi - (unsigned long long) lo;
+    unsigned long long offset = ((unsigned __int128) next() * range) >> 64;
+    return (unsigned long long) lo + offset;
+}
+
+// Returns a random float that is at least 0 and less than 1, as its bits.
+unsigned long long closey_random_float(void* unit) {
+    // A double has 53 bits of precision, so the top 53 bits are scaled down by 2^53
+    return to_bits((double) (next() >> 11) / 9007199254740992.0);
+}
@@ -0,0 +1,22 @@
+#ifndef RANDOM_H
+#define RANDOM_H
+
+#include "str.h"
+
+// These functions generate pseudorandom numbers with splitmix64. Every thread shares one generator,
+// which starts from a seed of 0, so a program gives the same numbers every time it runs unless it
+// seeds the generator with something that changes, such as the time. The interpreter uses the same
+// generator, so a seeded program gives the same numbers when it is interpreted.
+
+// Sets the state of the generator.
+void* closey_seed_random(long long seed);
+
+// Returns a random int that is at least the lower bound and less than the upper bound, panicking
+// if there is no such int. The location is the source location of the call, of the form
+// `file:line:column`.
+long long closey_random_int(long long lo, long long hi, struct s_string* loc);
+
+// Returns a random float that is at least 0 and less than 1, as its bits.
+unsigned long long closey_random_float(void* unit);
+
+#endif /* RANDOM_H */
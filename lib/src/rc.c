@@ -4,21 +4,74 @@
 
 #define PAGE_SIZE 4096
 
+// The reference count of static values, which is high enough that it never drops to 0.
+#define RC_STATIC ((size_t) 1 << 62)
+
+// The header before every pointer allocated with reference counts. The reference count must come
+// last, since generated code increments it directly, and static strings emitted by the LLVM backend
+// have the same header with a reference count of RC_STATIC.
 struct s_rcalloc_header {
   void* next;
   size_t size;
+  bool closure;
   size_t rc;
 };
 
 struct s_rcalloc_header* start = NULL;
 
-// Allocates something on the heap with a reference count of 1
-void* rcalloc(size_t size) {
+// Held while the heap is being searched or extended, since threads share the heap.
+static volatile int heap_lock = 0;
+
+// The allocator used instead of the heap above, or NULL if the heap is used.
+static const struct s_allocator* allocator = NULL;
+
+// Sets the allocator used by the runtime. This must be called before anything is allocated.
+void rc_set_allocator(const struct s_allocator* a) {
+    allocator = a;
+}
+
+// Returns true if reference counts are not kept because the allocator is garbage collected.
+static inline bool is_collected() {
+    return allocator != NULL && allocator->free == NULL;
+}
+
+// Allocates something with a reference count of 1 using the allocator.
+static void* rcalloc_hooked(size_t size) {
+    if (size == 0) {
+        return NULL;
+    }
+
+    // Garbage collected pointers have no header
+    if (is_collected())
+        return allocator->alloc(size);
+
+    struct s_rcalloc_header* header = allocator->alloc(sizeof(struct s_rcalloc_header) + size);
+    if (header == NULL)
+        return NULL;
+
+    header->next = NULL;
+    header->size = size;
+    header->closure = false;
+    header->rc = 1;
+    return (void*) (header + 1);
+}
+
+// Returns a pointer allocated with the allocator once nothing references it.
+static void release(struct s_rcalloc_header* header) {
+    if (allocator != NULL)
+        allocator->free(header);
+}
+
+// Allocates something on the heap with a reference count of 1. The heap must be locked.
+static void* rcalloc_locked(size_t size) {
     // NULL if size is 0
     if (size == 0) {
         return NULL;
     }
 
+    // Keep every pointer aligned, since values with the lowest bit set are not reference counted
+    size = (size + 7) & ~(size_t) 7;
+
     // Create initial part of heap
     if (start == NULL) {
         // Get mmapped pointer
@@ -31,6 +84,7 @@ void* rcalloc(size_t size) {
         // Set metadata
         start->next = NULL;
         start->size = PAGE_SIZE - sizeof(struct s_rcalloc_header);
+        start->closure = false;
         start->rc = 0;
     }
 
@@ -45,12 +99,14 @@ void* rcalloc(size_t size) {
                 struct s_rcalloc_header* q = (struct s_rcalloc_header*) (((void*) (p + 1)) + size);
                 q->next = p->next;
                 q->size = p->size - size - sizeof(struct s_rcalloc_header);
+                q->closure = false;
                 q->rc = 0;
                 p->next = q;
                 p->size = size;
             }
 
             // Mark as used and return
+            p->closure = false;
             p->rc = 1;
             return (void*) (p + 1);
         }
@@ -61,16 +117,24 @@ void* rcalloc(size_t size) {
     }
 
     // Get new mmapped pointer
-    p = mmap(NULL, size > PAGE_SIZE - sizeof(struct s_rcalloc_header) ? size + sizeof(struct s_rcalloc_header) : PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS | MAP_ANON, -1, 0);
+    size_t mapped = size > PAGE_SIZE - sizeof(struct s_rcalloc_header) ? size + sizeof(struct s_rcalloc_header) : PAGE_SIZE;
+    p = mmap(NULL, mapped, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS | MAP_ANON, -1, 0);
     if (p == (void*) -1)
         return NULL;
     last->next = p;
 
+    // Set metadata, since the mapping starts out zeroed
+    p->next = NULL;
+    p->size = mapped - sizeof(struct s_rcalloc_header);
+    p->closure = false;
+    p->rc = 1;
+
     // Shrink if too big
     if (p->size >= size * 2 + sizeof(struct s_rcalloc_header)) {
         struct s_rcalloc_header* q = (struct s_rcalloc_header*) (((void*) (p + 1)) + size);
         q->next = p->next;
         q->size = p->size - size - sizeof(struct s_rcalloc_header);
+        q->closure = false;
         q->rc = 0;
         p->next = q;
         p->size = size;
@@ -79,6 +143,17 @@ void* rcalloc(size_t size) {
     return (void*) (p + 1);
 }
 
+// Allocates something on the heap with a reference count of 1
+void* rcalloc(size_t size) {
+    if (allocator != NULL)
+        return rcalloc_hooked(size);
+
+    while (__atomic_exchange_n(&heap_lock, 1, __ATOMIC_ACQUIRE));
+    void* ptr = rcalloc_locked(size);
+    __atomic_store_n(&heap_lock, 0, __ATOMIC_RELEASE);
+    return ptr;
+}
+
 // Copies a pointer with a given size onto the heap with a reference count of 1.
 void* rccopy(void* ptr, size_t len, size_t size) {
     if (ptr == ((void*) 0))
@@ -92,57 +167,111 @@ void* rccopy(void* ptr, size_t len, size_t size) {
     }
 
     for (size_t i = len; i < size; i++) {
-        ((char*) alloced)[i] = ((char*) ptr)[i];
+        ((char*) alloced)[i] = 0;
     }
 
     return alloced;
 }
 
+// Creates a closure of a function with the given environment of saved values on the heap with a
+// reference count of 1. A closure is the tagged address of the function, followed by the number of
+// saved values and the values themselves, and it takes over the references to them, which
+// rcfuncfree frees along with it.
+void* rcclosure(void* func, size_t saved, void* env[]) {
+    void** closure = rcalloc((saved + 2) * sizeof(void*));
+    if (closure == NULL)
+        return NULL;
+
+    closure[0] = func;
+    closure[1] = (void*) saved;
+    for (size_t i = 0; i < saved; i++) {
+        closure[i + 2] = env[i];
+    }
+
+    if (!is_collected())
+        (((struct s_rcalloc_header*) closure) - 1)->closure = true;
+    return closure;
+}
+
+// Returns true if a value has no reference count, because it is a tagged function address or
+// static string, or because reference counts are not kept.
+static inline bool is_uncounted(void* ptr) {
+    return ptr == NULL || ((unsigned long long) ptr) & 1 || is_collected();
+}
+
 // Increments the reference count.
 inline void rcinc(void* ptr) {
+    if (is_uncounted(ptr))
+        return;
+
     struct s_rcalloc_header* header = ptr;
     header--;
-    header->rc++;
+    __atomic_add_fetch(&header->rc, 1, __ATOMIC_RELAXED);
 }
 
 // Returns true if there is only one reference to the pointer.
 bool has_one_reference(void* ptr) {
+    // Other references cannot be ruled out without reference counts
+    if (is_collected())
+        return false;
+
     struct s_rcalloc_header* header = ptr;
     header--;
-    return header->rc == 1;
+    return __atomic_load_n(&header->rc, __ATOMIC_ACQUIRE) == 1;
 }
 
 // Decrement the reference count.
 void rcfree(void* ptr) {
+    if (is_uncounted(ptr))
+        return;
+
     struct s_rcalloc_header* header = ptr;
     header--;
 
-    if (header->rc)
-        header->rc--;
+    size_t rc = __atomic_load_n(&header->rc, __ATOMIC_RELAXED);
+    while (rc && !__atomic_compare_exchange_n(&header->rc, &rc, rc - 1, false, __ATOMIC_ACQ_REL, __ATOMIC_RELAXED));
+    if (rc == 1)
+        release(header);
+}
+
+// Returns true if the argument of a function at the given index holds a value that is not a
+// pointer, such as a char. Function headers have the argument count followed by a mask of these
+// arguments, and arguments past the first 32 are always pointers.
+bool is_unboxed_arg(void* func, unsigned int i) {
+    unsigned int mask = ((unsigned int*) func)[1];
+    return i < 32 && (mask >> i) & 1;
 }
 
-// Frees a reference counted closure structure.
+// Frees a reference counted closure structure. Values that are not closures are only decremented,
+// since generated code does not know which values are closures.
 void rcfuncfree(void* ptr) {
-    if (((unsigned long long) ptr) & 1)
+    if (is_uncounted(ptr))
         return;
 
     struct s_rcalloc_header* header = ptr;
     header--;
+    if (!header->closure) {
+        rcfree(ptr);
+        return;
+    }
 
-    if (header->rc) {
-        if (header->rc == 1) {
-            unsigned long long* closure = ptr;
-            unsigned int* func = (unsigned int*) closure[0];
-            unsigned int argc = *func;
-            for (unsigned int i = 1; i < argc + 1; i++) {
-                if (closure[i] == 0)
-                    break;
-                rcfuncfree((void*) closure[i]);
+    // Reference counts are decremented atomically, since closures can be shared between threads
+    size_t rc = __atomic_load_n(&header->rc, __ATOMIC_ACQUIRE);
+    do {
+        if (rc == 0) {
+            *((volatile char*) 0) = 69;
+        } else if (rc == 1) {
+            // Nothing else references the closure, so nothing else can change its reference count
+            void** closure = ptr;
+            size_t saved = (size_t) closure[1];
+            for (size_t i = 0; i < saved; i++) {
+                if (!is_unboxed_arg(closure[0], i))
+                    rcfuncfree(closure[i + 2]);
             }
-        }
 
-        header->rc--;
-    } else {
-        *((volatile char*) 0) = 69;
-    }
+            __atomic_store_n(&header->rc, 0, __ATOMIC_RELEASE);
+            release(header);
+            return;
+        }
+    } while (!__atomic_compare_exchange_n(&header->rc, &rc, rc - 1, false, __ATOMIC_ACQ_REL, __ATOMIC_ACQUIRE));
 }
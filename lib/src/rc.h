@@ -4,12 +4,40 @@
 #include <stddef.h>
 #include <stdbool.h>
 
+// Represents an allocator that the runtime allocates through instead of its own heap.
+struct s_allocator {
+    // Allocates memory of the given size, or returns NULL on failure.
+    void* (*alloc)(size_t size);
+
+    // Frees memory returned by alloc. This is NULL if the allocator is garbage collected, in which
+    // case reference counts are not kept at all.
+    void (*free)(void* ptr);
+};
+
+// Sets the allocator used by the runtime. This must be called before anything is allocated.
+void rc_set_allocator(const struct s_allocator* allocator);
+
+// Uses malloc and free from libc to allocate memory.
+void closey_alloc_system_init();
+
+// Uses the Boehm garbage collector from libgc to allocate memory.
+void closey_alloc_boehm_init();
+
+// Uses the user supplied closey_alloc and closey_free functions to allocate memory.
+void closey_alloc_custom_init();
+
 // Allocates something on the heap with a reference count of 1
 void* rcalloc(size_t size);
 
 // Copies a pointer with a given size onto the heap with a reference count of 1.
 void* rccopy(void* ptr, size_t len, size_t size);
 
+// Creates a closure of a function with the given environment of saved values on the heap with a
+// reference count of 1. A closure is the tagged address of the function, followed by the number of
+// saved values and the values themselves, and it takes over the references to them, which
+// rcfuncfree frees along with it.
+void* rcclosure(void* func, size_t saved, void* env[]);
+
 // Increments the reference count.
 void rcinc(void* ptr);
 
@@ -19,7 +47,13 @@ bool has_one_reference(void* ptr);
 // Decrement the reference count.
 void rcfree(void* ptr);
 
-// Frees a reference counted closure structure.
+// Returns true if the argument of a function at the given index holds a value that is not a
+// pointer, such as a char. Function headers have the argument count followed by a mask of these
+// arguments, and arguments past the first 32 are always pointers.
+bool is_unboxed_arg(void* func, unsigned int i);
+
+// Frees a reference counted closure structure. Values that are not closures are only decremented,
+// since generated code does not know which values are closures.
 void rcfuncfree(void* ptr);
 
 #endif /* RC_H */
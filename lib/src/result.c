@@ -0,0 +1,121 @@
+#include "panic.h"
+#include "rc.h"
+#include "result.h"
+#include "str.h"
+
+// Allocates a result with the given tag.
+static struct s_result* result_new(enum e_result_tag tag) {
+    struct s_result* result = rcalloc(sizeof(struct s_result));
+    if (result == NULL)
+        return NULL;
+
+    result->tag = tag;
+    return result;
+}
+
+// Creates an ok result holding an integer.
+struct s_result* result_ok_int(long long value) {
+    struct s_result* result = result_new(RESULT_OK);
+    if (result != NULL)
+        result->int_value = value;
+    return result;
+}
+
+// Creates an ok result holding a float.
+struct s_result* result_ok_float(double value) {
+    struct s_result* result = result_new(RESULT_OK);
+    if (result != NULL)
+        result->float_value = value;
+    return result;
+}
+
+// Creates an ok result holding a pointer.
+struct s_result* result_ok(void* value) {
+    struct s_result* result = result_new(RESULT_OK);
+    if (result != NULL)
+        result->value = value;
+    return result;
+}
+
+// Creates an error result with the given message.
+struct s_result* result_err(const char* msg) {
+    struct s_result* result = result_new(RESULT_ERR);
+    if (result == NULL)
+        return NULL;
+
+    size_t length = 0;
+    while (msg[length])
+        length++;
+
+    result->error = string_new(msg, length);
+    return result;
+}
+
+// Creates an ok result holding the given value.
+struct s_result* closey_ok(void* value) {
+    return result_ok(value);
+}
+
+// Creates an error result holding a copy of the given message.
+struct s_result* closey_err(struct s_string* msg) {
+    // The message is copied because string literals are not reference counted
+    struct s_result* result = result_new(RESULT_ERR);
+    if (result != NULL)
+        result->error = string_new(msg->bytes, msg->length);
+    return result;
+}
+
+// Returns true if the result is ok.
+bool closey_is_ok(struct s_result* result) {
+    return result->tag == RESULT_OK;
+}
+
+// Returns true if the result is an error.
+bool closey_is_err(struct s_result* result) {
+    return result->tag == RESULT_ERR;
+}
+
+// Returns the value of an ok result, or panics with the error message if the result is an error.
+// The location is the source location of the call, of the form `file:line:column`.
+void* closey_unwrap(struct s_result* result, struct s_string* loc) {
+    if (result->tag == RESULT_ERR)
+        return closey_panic(result->error, loc);
+    return result->value;
+}
+
+// Returns the value of an ok result, or the default if the result is an error.
+void* closey_unwrap_or(struct s_result* result, void* def) {
+    if (result->tag == RESULT_ERR)
+        return def;
+    return result->value;
+}
+
+// Returns the message of an error result, or the empty string if the result is ok.
+struct s_string* closey_error(struct s_result* result) {
+    if (result->tag == RESULT_ERR)
+        return result->error;
+    return string_new("", 0);
+}
+
+// Creates an option holding the given value. Options are results whose errors have no message.
+struct s_result* closey_some(void* value) {
+    return result_ok(value);
+}
+
+// Creates an option holding no value.
+struct s_result* closey_none(void) {
+    struct s_result* result = result_new(RESULT_ERR);
+    if (result != NULL)
+        result->error = NULL;
+    return result;
+}
+
+// Returns true if the option holds a value.
+bool closey_is_some(struct s_result* option) {
+    return option->tag == RESULT_OK;
+}
+
+// Returns true if the option holds no value.
+bool closey_is_none(struct s_result* option) {
+    return option->tag == RESULT_ERR;
+}
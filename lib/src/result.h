@@ -0,0 +1,73 @@
+#ifndef RESULT_H
+#define RESULT_H
+
+#include <stdbool.h>
+
+struct s_string;
+
+// The tags of a result.
+enum e_result_tag {
+    RESULT_OK = 0,
+    RESULT_ERR = 1,
+};
+
+// Represents a reference counted result. Ok values hold an integer, the bits of a float, or a
+// pointer, and Err values hold a string describing the error. Options are results whose Err values
+// hold no string.
+struct s_result {
+    enum e_result_tag tag;
+    union {
+        long long int_value;
+        double float_value;
+        void* value;
+        struct s_string* error;
+    };
+};
+
+// Creates an ok result holding an integer.
+struct s_result* result_ok_int(long long value);
+
+// Creates an ok result holding a float.
+struct s_result* result_ok_float(double value);
+
+// Creates an ok result holding a pointer.
+struct s_result* result_ok(void* value);
+
+// Creates an error result with the given message.
+struct s_result* result_err(const char* msg);
+
+// Creates an ok result holding the given value.
+struct s_result* closey_ok(void* value);
+
+// Creates an error result holding a copy of the given message.
+struct s_result* closey_err(struct s_string* msg);
+
+// Returns true if the result is ok.
+bool closey_is_ok(struct s_result* result);
+
+// Returns true if the result is an error.
+bool closey_is_err(struct s_result* result);
+
+// Returns the value of an ok result, or panics with the error message if the result is an error.
+// The location is the source location of the call, of the form `file:line:column`.
+void* closey_unwrap(struct s_result* result, struct s_string* loc);
+
+// Returns the value of an ok result, or the default if the result is an error.
+void* closey_unwrap_or(struct s_result* result, void* def);
+
+// Returns the message of an error result, or the empty string if the result is ok.
+struct s_string* closey_error(struct s_result* result);
+
+// Creates an option holding the given value. Options are results whose errors have no message.
+struct s_result* closey_some(void* value);
+
+// Creates an option holding no value.
+struct s_result* closey_none(void);
+
+// Returns true if the option holds a value.
+bool closey_is_some(struct s_result* option);
+
+// Returns true if the option holds no value.
+bool closey_is_none(struct s_result* option);
+
+#endif /* RESULT_H */
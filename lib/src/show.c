@@ -0,0 +1,508 @@
+#include "char.h"
+#include "math.h"
+#include "rc.h"
+#include "show.h"
+#include "unknown_arity.h"
+
+// Represents a string that is being built up, which grows as bytes are added to it.
+struct s_builder {
+    char* bytes;
+    size_t length;
+    size_t capacity;
+};
+
+// Adds bytes to the end of a string being built.
+static void push(struct s_builder* b, const char* bytes, size_t length) {
+    if (b->length + length > b->capacity) {
+        size_t capacity = b->capacity * 2;
+        if (capacity < b->length + length)
+            capacity = b->length + length;
+        if (capacity < 32)
+            capacity = 32;
+
+        char* grown = rcalloc(capacity);
+        for (size_t i = 0; i < b->length; i++) {
+            grown[i] = b->bytes[i];
+        }
+        if (b->bytes != NULL)
+            rcfree(b->bytes);
+        b->bytes = grown;
+        b->capacity = capacity;
+    }
+
+    for (size_t i = 0; i < length; i++) {
+        b->bytes[b->length++] = bytes[i];
+    }
+}
+
+// Adds a null terminated string to the end of a string being built.
+static void push_str(struct s_builder* b, const char* s) {
+    size_t length = 0;
+    while (s[length] != '\0') {
+        length++;
+    }
+    push(b, s, length);
+}
+
+// Shows a value inside another value by calling its show function. Values shown as more than one
+// word, such as constructors with fields, are put in parentheses.
+static void push_nested(struct s_builder* b, void* show, void* value) {
+    struct s_string* s = call_unknown_arity(show, 1, &value);
+    bool spaced = false;
+    for (size_t i = 0; i < s->length; i++) {
+        if (s->bytes[i] == ' ')
+            spaced = true;
+    }
+
+    char first = s->length > 0 ? s->bytes[0] : '\0';
+    bool word = (first >= 'a' && first <= 'z') || (first >= 'A' && first <= 'Z');
+    if (word && spaced)
+        push(b, "(", 1);
+    push(b, s->bytes, s->length);
+    if (word && spaced)
+        push(b, ")", 1);
+    rcfree(s);
+}
+
+// Shows a value inside a collection by calling its show function, without parentheses.
+static void push_shown(struct s_builder* b, void* show, void* value) {
+    struct s_string* s = call_unknown_arity(show, 1, &value);
+    push(b, s->bytes, s->length);
+    rcfree(s);
+}
+
+// Returns the string that was built.
+static struct s_string* finish(struct s_builder* b) {
+    struct s_string* s = string_new(b->bytes, b->length);
+    if (b->bytes != NULL)
+        rcfree(b->bytes);
+    return s;
+}
+
+// Adds the digits of an unsigned int to the end of a string being built.
+static void push_digits(struct s_builder* b, unsigned long long n) {
+    char digits[20];
+    size_t i = sizeof(digits);
+    do {
+        digits[--i] = '0' + n % 10;
+        n /= 10;
+    } while (n > 0);
+    push(b, digits + i, sizeof(digits) - i);
+}
+
+// Shows an int or a signed sized int.
+struct s_string* closey_show_int(long long n) {
+    struct s_builder b = { NULL, 0, 0 };
+    if (n < 0)
+        push(&b, "-", 1);
+    push_digits(&b, n < 0 ? -(unsigned long long) n : (unsigned long long) n);
+    return finish(&b);
+}
+
+// Shows an unsigned sized int.
+struct s_string* closey_show_uint(unsigned long long n) {
+    struct s_builder b = { NULL, 0, 0 };
+    push_digits(&b, n);
+    return finish(&b);
+}
+
+// Returns 10 raised to the given power.
+static long double power_of_ten(int n) {
+    long double result = 1.0L;
+    long double base = 10.0L;
+    for (unsigned int k = n < 0 ? -n : n; k > 0; k >>= 1) {
+        if (k & 1)
+            result *= base;
+        base *= base;
+    }
+    return n < 0 ? 1.0L / result : result;
+}
+
+// Returns a float multiplied by 10 raised to the given power, dividing by the inverse power when
+// it is negative so that small powers stay exact.
+static long double scale(long double x, int n) {
+    return n < 0 ? x / power_of_ten(-n) : x * power_of_ten(n);
+}
+
+// Returns the decimal exponent of the first digit of a positive float.
+static int decimal_exponent(double x) {
+    // The decimal exponent is estimated from the binary one, and then corrected
+    int binary_exponent = (to_bits(x) >> 52) & 0x7ff;
+    int exponent = (binary_exponent - 1023) * 30103 / 100000;
+    while (x >= power_of_ten(exponent + 1))
+        exponent++;
+    while (x < power_of_ten(exponent))
+        exponent--;
+    return exponent;
+}
+
+// Shows a float, given by its bits, with the fewest digits that read back as the same float.
+// Floats from 1e-4 up to 1e16 are shown in decimal with at least one digit after the point, and
+// others in scientific notation, such as `1e16` or `1.5e-7`.
+struct s_string* closey_show_float(unsigned long long bits) {
+    struct s_builder b = { NULL, 0, 0 };
+    int binary_exponent = (bits >> 52) & 0x7ff;
+    if (binary_exponent == 0x7ff) {
+        if (bits & 0xfffffffffffffULL)
+            push_str(&b, "NaN");
+        else
+            push_str(&b, bits >> 63 ? "-inf" : "inf");
+        return finish(&b);
+    }
+
+    if (bits >> 63)
+        push(&b, "-", 1);
+    double x = from_bits(bits & ~(1ULL << 63));
+    if (x == 0.0) {
+        push_str(&b, "0.0");
+        return finish(&b);
+    }
+
+    int exponent = decimal_exponent(x);
+
+    // Try more and more significant digits until they read back as the float. Seventeen digits
+    // are always enough
+    long long n = 0;
+    int precision;
+    int rounded = exponent;
+    for (precision = 1; precision <= 17; precision++) {
+        // Rounding up can carry into a new digit, such as 9.6 becoming 10
+        n = (long long) (scale(x, precision - 1 - exponent) + 0.5L);
+        rounded = exponent;
+        if (n >= (long long) power_of_ten(precision)) {
+            n /= 10;
+            rounded++;
+        }
+
+        if ((double) scale(n, rounded - precision + 1) == x)
+            break;
+    }
+    exponent = rounded;
+    if (precision > 17)
+        precision = 17;
+    while (precision > 1 && n % 10 == 0) {
+        n /= 10;
+        precision--;
+    }
+
+    char digits[17];
+    for (int i = precision - 1; i >= 0; i--) {
+        digits[i] = '0' + n % 10;
+        n /= 10;
+    }
+
+    if (exponent >= -4 && exponent < 16) {
+        if (exponent >= 0) {
+            for (int i = 0; i <= exponent; i++) {
+                push(&b, i < precision ? &digits[i] : "0", 1);
+            }
+            push(&b, ".", 1);
+            if (exponent + 1 < precision)
+                push(&b, digits + exponent + 1, precision - exponent - 1);
+            else
+                push(&b, "0", 1);
+        } else {
+            push(&b, "0.", 2);
+            for (int i = 0; i < -exponent - 1; i++) {
+                push(&b, "0", 1);
+            }
+            push(&b, digits, precision);
+        }
+    } else {
+        push(&b, digits, 1);
+        if (precision > 1) {
+            push(&b, ".", 1);
+            push(&b, digits + 1, precision - 1);
+        }
+        push(&b, "e", 1);
+        if (exponent < 0)
+            push(&b, "-", 1);
+        push_digits(&b, exponent < 0 ? -exponent : exponent);
+    }
+    return finish(&b);
+}
+
+// Shows a bool as `true` or `false`.
+struct s_string* closey_show_bool(bool b) {
+    return b ? string_new("true", 4) : string_new("false", 5);
+}
+
+// Shows a char as a quoted literal. Control characters are escaped by their code point.
+struct s_string* closey_show_char(unsigned int c) {
+    static const char hex[] = "0123456789abcdef";
+
+    struct s_builder b = { NULL, 0, 0 };
+    push(&b, "'", 1);
+    switch (c) {
+        case '\'':
+            push_str(&b, "\\'");
+            break;
+        case '\\':
+            push_str(&b, "\\\\");
+            break;
+        case '\0':
+            push_str(&b, "\\0");
+            break;
+        case '\n':
+            push_str(&b, "\\n");
+            break;
+        case '\t':
+            push_str(&b, "\\t");
+            break;
+        case '\r':
+            push_str(&b, "\\r");
+            break;
+        default:
+            if (c < 0x20 || c == 0x7f) {
+                push_str(&b, "\\u{");
+                if (c >= 0x10)
+                    push(&b, &hex[c >> 4], 1);
+                push(&b, &hex[c & 0xf], 1);
+                push(&b, "}", 1);
+            } else {
+                struct s_string* s = closey_char_to_string(c);
+                push(&b, s->bytes, s->length);
+                rcfree(s);
+            }
+    }
+    push(&b, "'", 1);
+    return finish(&b);
+}
+
+// Shows a string as a quoted literal, escaping quotes, backslashes, and control characters.
+struct s_string* closey_show_string(struct s_string* s) {
+    static const char hex[] = "0123456789abcdef";
+
+    struct s_builder b = { NULL, 0, 0 };
+    push(&b, "\"", 1);
+    for (size_t i = 0; i < s->length; i++) {
+        unsigned char c = s->bytes[i];
+        switch (c) {
+            case '"':
+                push_str(&b, "\\\"");
+                break;
+            case '\\':
+                push_str(&b, "\\\\");
+                break;
+            case '\n':
+                push_str(&b, "\\n");
+                break;
+            case '\t':
+                push_str(&b, "\\t");
+                break;
+            case '\r':
+                push_str(&b, "\\r");
+                break;
+            default:
+                if (c < 0x20 || c == 0x7f) {
+                    char escape[] = { '\\', 'x', hex[c >> 4], hex[c & 0xf] };
+                    push(&b, escape, 4);
+                } else {
+                    push(&b, (const char*) &c, 1);
+                }
+        }
+    }
+    push(&b, "\"", 1);
+    return finish(&b);
+}
+
+// Shows an array, calling the given function to show each item.
+struct s_string* closey_show_array(struct s_array* array, void* show) {
+    struct s_builder b = { NULL, 0, 0 };
+    push(&b, "[", 1);
+    for (size_t i = 0; i < array->length; i++) {
+        if (i != 0)
+            push(&b, ", ", 2);
+        push_shown(&b, show, array->items[i]);
+    }
+    push(&b, "]", 1);
+    return finish(&b);
+}
+
+// Shows a map, calling the given functions to show each key and value.
+struct s_string* closey_show_map(struct s_map* map, void* show_key, void* show_value) {
+    struct s_builder b = { NULL, 0, 0 };
+    push(&b, "{", 1);
+    bool first = true;
+    for (size_t i = 0; i < map->capacity; i++) {
+        struct s_map_entry* entry = &map->entries[i];
+        if (entry->state != MAP_ENTRY_FULL)
+            continue;
+
+        if (!first)
+            push(&b, ", ", 2);
+        first = false;
+        push_shown(&b, show_key, entry->key);
+        push(&b, ": ", 2);
+        push_shown(&b, show_value, entry->value);
+    }
+    push(&b, "}", 1);
+    return finish(&b);
+}
+
+// Shows a result, calling the given function to show an ok value.
+struct s_string* closey_show_result(struct s_result* result, void* show) {
+    struct s_builder b = { NULL, 0, 0 };
+    if (result->tag == RESULT_OK) {
+        push_str(&b, "ok ");
+        push_nested(&b, show, result->value);
+    } else {
+        push_str(&b, "err ");
+        struct s_string* s = closey_show_string(result->error);
+        push(&b, s->bytes, s->length);
+        rcfree(s);
+    }
+    return finish(&b);
+}
+
+// Shows an option, calling the given function to show a some value. Options are results, with some
+// values stored the same way as ok values.
+struct s_string* closey_show_option(struct s_result* option, void* show) {
+    struct s_builder b = { NULL, 0, 0 };
+    if (option->tag == RESULT_OK) {
+        push_str(&b, "some ");
+        push_nested(&b, show, option->value);
+    } else {
+        push_str(&b, "none");
+    }
+    return finish(&b);
+}
+
+// Shows a value of a declared type. The constructors of the type are listed in order of their tags,
+// each as its number of fields followed by its name and a comma. The show functions of the fields
+// of every constructor follow each other in the same order.
+struct s_string* closey_show_data(struct s_data* data, struct s_string* constructors, struct s_array* shows) {
+    struct s_builder b = { NULL, 0, 0 };
+    size_t i = 0;
+    size_t offset = 0;
+    for (unsigned int tag = 0; i < constructors->length; tag++) {
+        size_t count = 0;
+        for (; constructors->bytes[i] >= '0' && constructors->bytes[i] <= '9'; i++)
+            count = count * 10 + (constructors->bytes[i] - '0');
+
+        size_t start = i;
+        for (; constructors->bytes[i] != ','; i++);
+        if (tag == data->tag) {
+            push(&b, constructors->bytes + start, i - start);
+            for (size_t j = 0; j < count; j++) {
+                push(&b, " ", 1);
+                push_nested(&b, shows->items[offset + j], data->fields[j]);
+            }
+            break;
+        }
+
+        offset += count;
+        i++;
+    }
+    return finish(&b);
+}
+
+// Rounds a positive float to the nearest int, with ties going to the even one.
+static long long round_even(long double x) {
+    long long n = (long long) x;
+    long double fraction = x - n;
+    if (fraction > 0.5L || (fraction == 0.5L && n % 2 != 0))
+        n++;
+    return n;
+}
+
+// Rounds a positive float to the given number of significant digits, from 1 to 17, returning them
+// as an int. The exponent of the first digit is updated if rounding carries into a new digit.
+static long long round_significant(double x, int count, int* exponent) {
+    long long n = round_even(scale(x, count - 1 - *exponent));
+    if (n >= (long long) power_of_ten(count)) {
+        n /= 10;
+        (*exponent)++;
+    }
+    return n;
+}
+
+// Adds a positive float to the end of a string being built, with the given number of digits after
+// the point, and in scientific notation if asked for.
+static void push_float(struct s_builder* b, double x, long long precision, bool scientific) {
+    // The digits are kept as an int along with the exponent of the first digit
+    long long n = 0;
+    int count;
+    int exponent = 0;
+    if (scientific) {
+        count = precision < 16 ? precision + 1 : 17;
+        if (x != 0.0) {
+            exponent = decimal_exponent(x);
+            n = round_significant(x, count, &exponent);
+        }
+    } else if (x == 0.0 || decimal_exponent(x) + 1 + precision <= 17) {
+        // The float is rounded to the last place shown, and the digits give the exponent
+        n = round_even(scale(x, precision));
+        for (count = 1; count < 18 && n >= (long long) power_of_ten(count); count++);
+        exponent = count - 1 - precision;
+    } else {
+        count = 17;
+        exponent = decimal_exponent(x);
+        n = round_significant(x, count, &exponent);
+    }
+
+    char digits[18];
+    for (int i = count - 1; i >= 0; i--) {
+        digits[i] = '0' + n % 10;
+        n /= 10;
+    }
+
+    if (scientific) {
+        push(b, digits, 1);
+        if (precision > 0)
+            push(b, ".", 1);
+        for (long long i = 1; i <= precision; i++) {
+            push(b, i < count ? &digits[i] : "0", 1);
+        }
+        push(b, "e", 1);
+        if (exponent < 0)
+            push(b, "-", 1);
+        push_digits(b, exponent < 0 ? -exponent : exponent);
+    } else {
+        // Each place is shown with its digit, or zero if there is none
+        for (long long place = exponent > 0 ? exponent : 0; place >= -precision; place--) {
+            long long i = exponent - place;
+            push(b, i >= 0 && i < count ? &digits[i] : "0", 1);
+            if (place == 0 && precision > 0)
+                push(b, ".", 1);
+        }
+    }
+}
+
+// Formats a float, given by its bits, with the given number of digits after the point, in
+// scientific notation if asked for, and padded on the left with spaces to at least the given width.
+// Floats hold no more than 17 significant digits, so any digits past those are shown as zeros.
+static struct s_string* format(unsigned long long bits, long long precision, bool scientific, long long width) {
+    struct s_builder f = { NULL, 0, 0 };
+    double x = from_bits(bits & ~(1ULL << 63));
+    if (x != x) {
+        push_str(&f, "NaN");
+    } else {
+        if (bits >> 63)
+            push(&f, "-", 1);
+        if (x == from_bits(0x7ff0000000000000ULL))
+            push_str(&f, "inf");
+        else
+            push_float(&f, x, precision < 0 ? 0 : precision, scientific);
+    }
+
+    struct s_builder b = { NULL, 0, 0 };
+    for (long long i = f.length; i < width; i++) {
+        push(&b, " ", 1);
+    }
+    push(&b, f.bytes, f.length);
+    rcfree(f.bytes);
+    return finish(&b);
+}
+
+// Formats a float, given by its bits, with the given number of digits after the point, padded on
+// the left with spaces to at least the given width.
+struct s_string* closey_format_float(unsigned long long bits, long long precision, long long width) {
+    return format(bits, precision, false, width);
+}
+
+// Formats a float, given by its bits, in scientific notation such as `1.50e3`, with the given
+// number of digits after the point, padded on the left with spaces to at least the given width.
+struct s_string* closey_format_scientific(unsigned long long bits, long long precision, long long width) {
+    return format(bits, precision, true, width);
+}
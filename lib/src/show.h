@@ -0,0 +1,60 @@
+#ifndef SHOW_H
+#define SHOW_H
+
+#include <stdbool.h>
+
+#include "array.h"
+#include "data.h"
+#include "map.h"
+#include "result.h"
+#include "str.h"
+
+// These functions show values the same way the REPL does. The compiler derives a show function for
+// each type that is shown, which calls the function for the kind of value with the show functions
+// of the types of the values inside it.
+
+// Shows an int or a signed sized int.
+struct s_string* closey_show_int(long long n);
+
+// Shows an unsigned sized int.
+struct s_string* closey_show_uint(unsigned long long n);
+
+// Shows a float, given by its bits, with the fewest digits that read back as the same float.
+struct s_string* closey_show_float(unsigned long long bits);
+
+// Shows a bool as `true` or `false`.
+struct s_string* closey_show_bool(bool b);
+
+// Shows a char as a quoted literal.
+struct s_string* closey_show_char(unsigned int c);
+
+// Shows a string as a quoted literal, escaping quotes, backslashes, and control characters.
+struct s_string* closey_show_string(struct s_string* s);
+
+// Shows an array, calling the given function to show each item.
+struct s_string* closey_show_array(struct s_array* array, void* show);
+
+// Shows a map, calling the given functions to show each key and value.
+struct s_string* closey_show_map(struct s_map* map, void* show_key, void* show_value);
+
+// Shows a result, calling the given function to show an ok value.
+struct s_string* closey_show_result(struct s_result* result, void* show);
+
+// Shows an option, calling the given function to show a some value.
+struct s_string* closey_show_option(struct s_result* option, void* show);
+
+// Shows a value of a declared type. The constructors of the type are listed in order of their tags,
+// each as its number of fields followed by its name and a comma. The show functions of the fields
+// of every constructor follow each other in the same order.
+struct s_string* closey_show_data(struct s_data* data, struct s_string* constructors, struct s_array* shows);
+
+// Formats a float, given by its bits, with the given number of digits after the point, padded on
+// the left with spaces to at least the given width. Digits past the seventeenth significant digit
+// are shown as zeros.
+struct s_string* closey_format_float(unsigned long long bits, long long precision, long long width);
+
+// Formats a float, given by its bits, in scientific notation such as `1.50e3`, with the given
+// number of digits after the point, padded on the left with spaces to at least the given width.
+struct s_string* closey_format_scientific(unsigned long long bits, long long precision, long long width);
+
+#endif /* SHOW_H */
@@ -0,0 +1,247 @@
+#include "rc.h"
+#include "str.h"
+
+// Creates a string from a byte array and its length.
+struct s_string* string_new(const char* bytes, size_t length) {
+    struct s_string* s = rcalloc(sizeof(struct s_string) + length);
+    if (s == NULL)
+        return NULL;
+
+    s->length = length;
+    for (size_t i = 0; i < length; i++) {
+        s->bytes[i] = bytes[i];
+    }
+
+    return s;
+}
+
+// Returns the length of a string in bytes.
+size_t string_length(struct s_string* s) {
+    return s->length;
+}
+
+// Returns the byte at the given index, or -1 if the index is out of bounds.
+int string_index(struct s_string* s, size_t index) {
+    if (index >= s->length)
+        return -1;
+    return (unsigned char) s->bytes[index];
+}
+
+// Returns the substring from start (inclusive) to end (exclusive). The bounds are clamped to the
+// length of the string.
+struct s_string* string_slice(struct s_string* s, size_t start, size_t end) {
+    if (end > s->length)
+        end = s->length;
+    if (start > end)
+        start = end;
+    return string_new(s->bytes + start, end - start);
+}
+
+// Returns true if two strings have the same contents.
+bool string_equals(struct s_string* a, struct s_string* b) {
+    if (a->length != b->length)
+        return false;
+
+    for (size_t i = 0; i < a->length; i++) {
+        if (a->bytes[i] != b->bytes[i])
+            return false;
+    }
+
+    return true;
+}
+
+// Returns true if the separator occurs in the string at the given index.
+static bool matches_at(struct s_string* s, size_t i, struct s_string* sep) {
+    if (i + sep->length > s->length)
+        return false;
+
+    for (size_t j = 0; j < sep->length; j++) {
+        if (s->bytes[i + j] != sep->bytes[j])
+            return false;
+    }
+
+    return true;
+}
+
+// Splits a string on every occurrence of a separator. An empty separator splits the string into
+// individual bytes.
+struct s_string_list* string_split(struct s_string* s, struct s_string* sep) {
+    // Count the parts first so the list can be allocated in one go
+    size_t count;
+    if (sep->length == 0) {
+        count = s->length;
+    } else {
+        count = 1;
+        for (size_t i = 0; i < s->length;) {
+            if (matches_at(s, i, sep)) {
+                count++;
+                i += sep->length;
+            } else {
+                i++;
+            }
+        }
+    }
+
+    struct s_string_list* list = rcalloc(sizeof(struct s_string_list) + count * sizeof(struct s_string*));
+    if (list == NULL)
+        return NULL;
+    list->length = count;
+
+    // Split into bytes
+    if (sep->length == 0) {
+        for (size_t i = 0; i < count; i++) {
+            list->items[i] = string_new(s->bytes + i, 1);
+        }
+        return list;
+    }
+
+    // Split on the separator
+    size_t part = 0;
+    size_t start = 0;
+    for (size_t i = 0; i < s->length;) {
+        if (matches_at(s, i, sep)) {
+            list->items[part++] = string_new(s->bytes + start, i - start);
+            i += sep->length;
+            start = i;
+        } else {
+            i++;
+        }
+    }
+    list->items[part] = string_new(s->bytes + start, s->length - start);
+
+    return list;
+}
+
+// Returns a copy of the string with all ascii letters in upper case.
+struct s_string* string_to_upper(struct s_string* s) {
+    struct s_string* result = string_new(s->bytes, s->length);
+    if (result == NULL)
+        return NULL;
+
+    for (size_t i = 0; i < result->length; i++) {
+        char c = result->bytes[i];
+        if (c >= 'a' && c <= 'z')
+            result->bytes[i] = c - 'a' + 'A';
+    }
+
+    return result;
+}
+
+// Returns a copy of the string with all ascii letters in lower case.
+struct s_string* string_to_lower(struct s_string* s) {
+    struct s_string* result = string_new(s->bytes, s->length);
+    if (result == NULL)
+        return NULL;
+
+    for (size_t i = 0; i < result->length; i++) {
+        char c = result->bytes[i];
+        if (c >= 'A' && c <= 'Z')
+            result->bytes[i] = c - 'A' + 'a';
+    }
+
+    return result;
+}
+
+// Returns a new string with the contents of the first string followed by the second.
+struct s_string* string_concat(struct s_string* a, struct s_string* b) {
+    struct s_string* s = rcalloc(sizeof(struct s_string) + a->length + b->length);
+    if (s == NULL)
+        return NULL;
+
+    s->length = a->length + b->length;
+    for (size_t i = 0; i < a->length; i++) {
+        s->bytes[i] = a->bytes[i];
+    }
+    for (size_t i = 0; i < b->length; i++) {
+        s->bytes[a->length + i] = b->bytes[i];
+    }
+
+    return s;
+}
+
+// Parses a decimal integer with an optional sign.
+struct s_result* string_parse_int(struct s_string* s) {
+    size_t i = 0;
+    bool negative = false;
+    if (i < s->length && (s->bytes[i] == '-' || s->bytes[i] == '+')) {
+        negative = s->bytes[i] == '-';
+        i++;
+    }
+
+    if (i == s->length)
+        return result_err("expected digits");
+
+    // Accumulate as unsigned so the most negative integer can be parsed
+    unsigned long long limit = negative ? 0x8000000000000000ull : 0x7fffffffffffffffull;
+    unsigned long long value = 0;
+    for (; i < s->length; i++) {
+        char c = s->bytes[i];
+        if (c < '0' || c > '9')
+            return result_err("invalid digit");
+
+        unsigned long long digit = c - '0';
+        if (value > (limit - digit) / 10)
+            return result_err("integer out of range");
+        value = value * 10 + digit;
+    }
+
+    return result_ok_int(negative ? (long long) (0 - value) : (long long) value);
+}
+
+// Parses a decimal float with an optional sign, fractional part, and exponent.
+struct s_result* string_parse_float(struct s_string* s) {
+    size_t i = 0;
+    bool negative = false;
+    if (i < s->length && (s->bytes[i] == '-' || s->bytes[i] == '+')) {
+        negative = s->bytes[i] == '-';
+        i++;
+    }
+
+    // Integer part
+    double value = 0.0;
+    size_t digits = 0;
+    for (; i < s->length && s->bytes[i] >= '0' && s->bytes[i] <= '9'; i++, digits++) {
+        value = value * 10.0 + (s->bytes[i] - '0');
+    }
+
+    // Fractional part
+    if (i < s->length && s->bytes[i] == '.') {
+        double scale = 0.1;
+        for (i++; i < s->length && s->bytes[i] >= '0' && s->bytes[i] <= '9'; i++, digits++) {
+            value += (s->bytes[i] - '0') * scale;
+            scale *= 0.1;
+        }
+    }
+
+    if (digits == 0)
+        return result_err("expected digits");
+
+    // Exponent
+    if (i < s->length && (s->bytes[i] == 'e' || s->bytes[i] == 'E')) {
+        i++;
+        bool exp_negative = false;
+        if (i < s->length && (s->bytes[i] == '-' || s->bytes[i] == '+')) {
+            exp_negative = s->bytes[i] == '-';
+            i++;
+        }
+
+        if (i == s->length)
+            return result_err("expected exponent");
+
+        int exp = 0;
+        for (; i < s->length && s->bytes[i] >= '0' && s->bytes[i] <= '9'; i++) {
+            // Anything past this overflows or underflows anyway
+            if (exp < 10000)
+                exp = exp * 10 + (s->bytes[i] - '0');
+        }
+
+        for (int j = 0; j < exp; j++) {
+            value = exp_negative ? value / 10.0 : value * 10.0;
+        }
+    }
+
+    if (i != s->length)
+        return result_err("invalid digit");
+
+    return result_ok_float(negative ? -value : value);
+}
@@ -0,0 +1,56 @@
+#ifndef STR_H
+#define STR_H
+
+#include <stddef.h>
+#include <stdbool.h>
+
+#include "result.h"
+
+// Represents a reference counted, immutable string of bytes. Strings are not null terminated.
+struct s_string {
+    size_t length;
+    char bytes[];
+};
+
+// Represents a reference counted list of strings.
+struct s_string_list {
+    size_t length;
+    struct s_string* items[];
+};
+
+// Creates a string from a byte array and its length.
+struct s_string* string_new(const char* bytes, size_t length);
+
+// Returns the length of a string in bytes.
+size_t string_length(struct s_string* s);
+
+// Returns the byte at the given index, or -1 if the index is out of bounds.
+int string_index(struct s_string* s, size_t index);
+
+// Returns the substring from start (inclusive) to end (exclusive). The bounds are clamped to the
+// length of the string.
+struct s_string* string_slice(struct s_string* s, size_t start, size_t end);
+
+// Returns true if two strings have the same contents.
+bool string_equals(struct s_string* a, struct s_string* b);
+
+// Splits a string on every occurrence of a separator. An empty separator splits the string into
+// individual bytes.
+struct s_string_list* string_split(struct s_string* s, struct s_string* sep);
+
+// Returns a copy of the string with all ascii letters in upper case.
+struct s_string* string_to_upper(struct s_string* s);
+
+// Returns a copy of the string with all ascii letters in lower case.
+struct s_string* string_to_lower(struct s_string* s);
+
+// Returns a new string with the contents of the first string followed by the second.
+struct s_string* string_concat(struct s_string* a, struct s_string* b);
+
+// Parses a decimal integer with an optional sign.
+struct s_result* string_parse_int(struct s_string* s);
+
+// Parses a decimal float with an optional sign, fractional part, and exponent.
+struct s_result* string_parse_float(struct s_string* s);
+
+#endif /* STR_H */
@@ -3,10 +3,34 @@
 
 #include <stddef.h>
 
+long read(int fd, void* buf, size_t count);
+
+long write(int fd, const void* buf, size_t count);
+
+int open(const char* path, int flags, int mode);
+
+int close(int fd);
+
+int access(const char* path, int mode);
+
 void* mmap(void* start, size_t length, int prot, int flags, int fd, size_t offset);
 
 int munmap(void* start, size_t length);
 
 void exit(int ecode);
 
+int fork();
+
+int execve(const char* path, char* const argv[], char* const envp[]);
+
+int wait4(int pid, int* status, int options, void* rusage);
+
+int clock_gettime(int clock, void* time);
+
+int nanosleep(const void* duration, void* remaining);
+
+long futex(volatile int* uaddr, int op, int val, void* timeout);
+
+long spawn_thread(unsigned long flags, void* stack, volatile int* ptid, volatile int* ctid, void (*func)(void*), void* arg);
+
 #endif /* SYSCALLS_H */
@@ -1,11 +1,53 @@
 .intel_syntax noprefix
+.global read
+.global write
+.global open
+.global close
+.global access
 .global mmap
 .global munmap
 .global exit
+.global fork
+.global execve
+.global wait4
+.global clock_gettime
+.global nanosleep
+.global futex
+.global spawn_thread
 
 # args passed into registers:
 # rdi, rsi, rdx, rcx, r8, r9
 
+# long read(int fd, void* buf, size_t count);
+read:
+    mov rax, 0
+    syscall
+    ret
+
+# long write(int fd, const void* buf, size_t count);
+write:
+    mov rax, 1
+    syscall
+    ret
+
+# int open(const char* path, int flags, int mode);
+open:
+    mov rax, 2
+    syscall
+    ret
+
+# int close(int fd);
+close:
+    mov rax, 3
+    syscall
+    ret
+
+# int access(const char* path, int mode);
+access:
+    mov rax, 21
+    syscall
+    ret
+
 # void* mmap(void* start, size_t length, int prot, int flags, int fd, size_t offset);
 mmap:
     mov r10, rcx
@@ -20,8 +62,72 @@ munmap:
     ret
 
 # void exit(int ecode);
+# Uses exit_group so that every thread is exited.
 exit:
-    mov rax, 60
+    mov rax, 231
+    syscall
+    ret
+
+# int fork();
+fork:
+    mov rax, 57
+    syscall
+    ret
+
+# int execve(const char* path, char* const argv[], char* const envp[]);
+execve:
+    mov rax, 59
     syscall
     ret
 
+# int wait4(int pid, int* status, int options, void* rusage);
+wait4:
+    mov r10, rcx
+    mov rax, 61
+    syscall
+    ret
+
+# int clock_gettime(int clock, void* time);
+clock_gettime:
+    mov rax, 228
+    syscall
+    ret
+
+# int nanosleep(const void* duration, void* remaining);
+nanosleep:
+    mov rax, 35
+    syscall
+    ret
+
+# long futex(volatile int* uaddr, int op, int val, void* timeout);
+futex:
+    mov r10, rcx
+    mov rax, 202
+    syscall
+    ret
+
+# long spawn_thread(unsigned long flags, void* stack, volatile int* ptid, volatile int* ctid, void (*func)(void*), void* arg);
+# Calls clone with the given stack, and calls func with arg in the new thread. The new thread exits
+# when func returns.
+spawn_thread:
+    # Save the function and its argument on the new stack
+    sub rsi, 16
+    mov [rsi], r8
+    mov [rsi + 8], r9
+    mov r10, rcx
+    xor r8, r8
+    mov rax, 56
+    syscall
+    test rax, rax
+    jnz spawn_thread_parent
+
+    # In the new thread
+    pop rax
+    pop rdi
+    call rax
+    mov rax, 60
+    xor rdi, rdi
+    syscall
+
+spawn_thread_parent:
+    ret
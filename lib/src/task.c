@@ -0,0 +1,174 @@
+#include "context.h"
+#include "rc.h"
+#include "syscalls.h"
+#include "task.h"
+#include "unknown_arity.h"
+
+#define PROT_READ 0x1
+#define PROT_WRITE 0x2
+#define MAP_PRIVATE 0x02
+#define MAP_ANONYMOUS 0x20
+#define MAP_STACK 0x20000
+
+#define CLOCK_MONOTONIC 1
+
+#define STACK_SIZE (256 * 1024)
+
+// Represents a time for clock_gettime and nanosleep.
+struct s_timespec {
+    long long sec;
+    long long nsec;
+};
+
+// The task that is currently running. The code that was running before any task was created is
+// the initial task, and has no stack of its own.
+static struct s_task initial_task;
+static struct s_task* current = &initial_task;
+
+// The first and last tasks in the run queue.
+static struct s_task* queue_head = NULL;
+static struct s_task* queue_tail = NULL;
+
+// Returns the current time in milliseconds.
+static unsigned long long now() {
+    struct s_timespec time;
+    clock_gettime(CLOCK_MONOTONIC, &time);
+    return time.sec * 1000 + time.nsec / 1000000;
+}
+
+// Adds a task to the end of the run queue.
+static void enqueue(struct s_task* task) {
+    task->next = NULL;
+    if (queue_tail == NULL)
+        queue_head = task;
+    else
+        queue_tail->next = task;
+    queue_tail = task;
+}
+
+// Removes the first task from the run queue.
+static struct s_task* dequeue() {
+    struct s_task* task = queue_head;
+    queue_head = task->next;
+    if (queue_head == NULL)
+        queue_tail = NULL;
+    return task;
+}
+
+// Switches to the next task in the run queue that is ready to run, sleeping if every task is
+// asleep. The current task must already be in the run queue unless it has finished.
+static void run_next() {
+    while (true) {
+        // Look for a task that is awake, moving sleeping tasks to the back of the queue
+        unsigned long long time = now();
+        unsigned long long earliest = (unsigned long long) -1;
+        struct s_task* last = queue_tail;
+        while (true) {
+            struct s_task* task = dequeue();
+            if (task->wake_time <= time) {
+                if (task != current) {
+                    struct s_task* previous = current;
+                    current = task;
+                    switch_context(&previous->sp, task->sp);
+                }
+                return;
+            }
+
+            if (task->wake_time < earliest)
+                earliest = task->wake_time;
+            enqueue(task);
+            if (task == last)
+                break;
+        }
+
+        // Every task is asleep
+        unsigned long long ms = earliest - time;
+        struct s_timespec duration = { ms / 1000, (ms % 1000) * 1000000 };
+        nanosleep(&duration, NULL);
+    }
+}
+
+// Calls the function of a task with unit, saves the result, and switches to another task. Called
+// by task_start when a task is first run.
+void task_main(struct s_task* task) {
+    void* args[] = { NULL };
+    task->result = call_unknown_arity(task->func, 1, args);
+    rcfuncfree(task->func);
+    task->done = true;
+
+    // Finished tasks are never resumed. Whatever awaits the task is in the run queue, since it
+    // yielded to let this task run.
+    run_next();
+}
+
+// Creates a task that calls the given function with unit and adds it to the run queue. The task
+// keeps a reference to the function until it returns.
+struct s_task* closey_async(void* func) {
+    struct s_task* task = rcalloc(sizeof(struct s_task));
+    if (task == NULL)
+        return NULL;
+
+    task->stack = mmap(NULL, STACK_SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS | MAP_STACK, -1, 0);
+    if (task->stack == (void*) -1)
+        return NULL;
+
+    // Functions are tagged with their lowest bit and are not reference counted
+    task->func = func;
+    if (!(((unsigned long long) func) & 1))
+        rcinc(func);
+
+    // Set up the stack so that switching to the task pops the registers saved by switch_context
+    // and returns into task_start with the task in r12 and the stack aligned for a call
+    void** sp = task->stack + STACK_SIZE - 24 - 6 * sizeof(void*);
+    sp[0] = NULL; // r15
+    sp[1] = NULL; // r14
+    sp[2] = NULL; // r13
+    sp[3] = task; // r12
+    sp[4] = NULL; // rbp
+    sp[5] = NULL; // rbx
+    sp[6] = task_start;
+    task->sp = sp;
+
+    task->done = false;
+    task->wake_time = 0;
+    enqueue(task);
+    return task;
+}
+
+// Runs other tasks until the given task has finished, and returns the value returned by its
+// function.
+void* closey_await(struct s_task* task) {
+    while (!task->done) {
+        enqueue(current);
+        run_next();
+    }
+
+    // The task is no longer running on its stack
+    if (task->stack != NULL) {
+        munmap(task->stack, STACK_SIZE);
+        task->stack = NULL;
+    }
+
+    return task->result;
+}
+
+// Lets other tasks run before continuing.
+void* closey_yield(void* unit) {
+    (void) unit;
+    if (queue_head != NULL) {
+        enqueue(current);
+        run_next();
+    }
+    return NULL;
+}
+
+// Lets other tasks run for at least the given number of milliseconds before continuing.
+void* closey_sleep(long long ms) {
+    if (ms > 0) {
+        current->wake_time = now() + ms;
+        enqueue(current);
+        run_next();
+        current->wake_time = 0;
+    }
+    return NULL;
+}
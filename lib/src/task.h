@@ -0,0 +1,45 @@
+#ifndef TASK_H
+#define TASK_H
+
+#include <stdbool.h>
+
+// Represents a reference counted task. Tasks are green threads that are scheduled cooperatively:
+// a task runs until it finishes, yields, sleeps, or awaits another task.
+struct s_task {
+    // The function the task calls with unit.
+    void* func;
+
+    // The value returned by the function.
+    void* result;
+
+    // Whether the function has returned.
+    bool done;
+
+    // The stack of the task, or NULL once it has been unmapped or for the initial task.
+    void* stack;
+
+    // The saved stack pointer of the task while it is not running.
+    void* sp;
+
+    // The time in milliseconds before which the task should not be resumed.
+    unsigned long long wake_time;
+
+    // The next task in the run queue.
+    struct s_task* next;
+};
+
+// Creates a task that calls the given function with unit and adds it to the run queue. The task
+// keeps a reference to the function until it returns.
+struct s_task* closey_async(void* func);
+
+// Runs other tasks until the given task has finished, and returns the value returned by its
+// function.
+void* closey_await(struct s_task* task);
+
+// Lets other tasks run before continuing.
+void* closey_yield(void* unit);
+
+// Lets other tasks run for at least the given number of milliseconds before continuing.
+void* closey_sleep(long long ms);
+
+#endif /* TASK_H */
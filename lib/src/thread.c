@@ -0,0 +1,73 @@
+#include "rc.h"
+#include "syscalls.h"
+#include "thread.h"
+#include "unknown_arity.h"
+
+#define PROT_READ 0x1
+#define PROT_WRITE 0x2
+#define MAP_PRIVATE 0x02
+#define MAP_ANONYMOUS 0x20
+#define MAP_STACK 0x20000
+
+#define CLONE_VM 0x00000100
+#define CLONE_FS 0x00000200
+#define CLONE_FILES 0x00000400
+#define CLONE_SIGHAND 0x00000800
+#define CLONE_THREAD 0x00010000
+#define CLONE_SYSVSEM 0x00040000
+#define CLONE_PARENT_SETTID 0x00100000
+#define CLONE_CHILD_CLEARTID 0x00200000
+
+#define FUTEX_WAIT 0
+
+#define STACK_SIZE (1024 * 1024)
+
+// Calls the function of a thread with unit and saves the result.
+static void thread_main(void* arg) {
+    struct s_thread* thread = arg;
+    void* args[] = { NULL };
+    thread->result = call_unknown_arity(thread->func, 1, args);
+    rcfuncfree(thread->func);
+}
+
+// Spawns a thread that calls the given function with unit. The thread keeps a reference to the
+// function until it returns.
+struct s_thread* closey_spawn(void* func) {
+    struct s_thread* thread = rcalloc(sizeof(struct s_thread));
+    if (thread == NULL)
+        return NULL;
+
+    thread->stack = mmap(NULL, STACK_SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS | MAP_STACK, -1, 0);
+    if (thread->stack == (void*) -1)
+        return NULL;
+
+    // Functions are tagged with their lowest bit and are not reference counted
+    thread->func = func;
+    if (!(((unsigned long long) func) & 1))
+        rcinc(func);
+
+    unsigned long flags = CLONE_VM | CLONE_FS | CLONE_FILES | CLONE_SIGHAND | CLONE_THREAD | CLONE_SYSVSEM
+        | CLONE_PARENT_SETTID | CLONE_CHILD_CLEARTID;
+    long tid = spawn_thread(flags, thread->stack + STACK_SIZE, &thread->tid, &thread->tid, thread_main, thread);
+    if (tid < 0) {
+        munmap(thread->stack, STACK_SIZE);
+        return NULL;
+    }
+
+    return thread;
+}
+
+// Waits for a thread to exit and returns the value returned by its function.
+void* closey_join(struct s_thread* thread) {
+    // The kernel clears the id and wakes up waiters once the thread has exited
+    int tid;
+    while ((tid = thread->tid) != 0)
+        futex(&thread->tid, FUTEX_WAIT, tid, NULL);
+
+    // Only one joining thread unmaps the stack
+    void* stack = __atomic_exchange_n(&thread->stack, NULL, __ATOMIC_ACQ_REL);
+    if (stack != NULL)
+        munmap(stack, STACK_SIZE);
+
+    return thread->result;
+}
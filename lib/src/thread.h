@@ -0,0 +1,28 @@
+#ifndef THREAD_H
+#define THREAD_H
+
+#include <stddef.h>
+
+// Represents a reference counted handle to a thread.
+struct s_thread {
+    // The id of the thread, which is cleared by the kernel when the thread exits.
+    volatile int tid;
+
+    // The function the thread calls with unit.
+    void* func;
+
+    // The value returned by the function.
+    void* result;
+
+    // The stack of the thread, or NULL once it has been unmapped.
+    void* stack;
+};
+
+// Spawns a thread that calls the given function with unit. The thread keeps a reference to the
+// function until it returns.
+struct s_thread* closey_spawn(void* func);
+
+// Waits for a thread to exit and returns the value returned by its function.
+void* closey_join(struct s_thread* thread);
+
+#endif /* THREAD_H */
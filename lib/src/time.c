@@ -0,0 +1,26 @@
+#include "syscalls.h"
+#include "time.h"
+
+#define CLOCK_REALTIME 0
+#define CLOCK_MONOTONIC 1
+
+// Represents a time for clock_gettime.
+struct s_timespec {
+    long long sec;
+    long long nsec;
+};
+
+// Returns the number of milliseconds since the Unix epoch, from the system clock.
+long long closey_now_millis(void* unit) {
+    struct s_timespec time;
+    clock_gettime(CLOCK_REALTIME, &time);
+    return time.sec * 1000 + time.nsec / 1000000;
+}
+
+// Returns the number of nanoseconds since an arbitrary point in the past, from a clock that never
+// goes backwards.
+long long closey_clock_monotonic(void* unit) {
+    struct s_timespec time;
+    clock_gettime(CLOCK_MONOTONIC, &time);
+    return time.sec * 1000000000 + time.nsec;
+}
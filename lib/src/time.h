@@ -0,0 +1,11 @@
+#ifndef TIME_H
+#define TIME_H
+
+// Returns the number of milliseconds since the Unix epoch, from the system clock.
+long long closey_now_millis(void* unit);
+
+// Returns the number of nanoseconds since an arbitrary point in the past, from a clock that never
+// goes backwards. Only the differences between its values are meaningful.
+long long closey_clock_monotonic(void* unit);
+
+#endif /* TIME_H */
@@ -1,135 +1,90 @@
+#include "panic.h"
 #include "rc.h"
 #include "unknown_arity.h"
 
+// Calls the code of a function with the given arguments, passing the first six in registers and
+// the rest on the stack.
+void* call_with_args(void* code, unsigned long long argc, void* args[]);
+
 // Applies a function with the given closed values and passed in arguments.
 void* apply_func(void* func, unsigned int* called_argc, unsigned int saved, void* closed[], unsigned int argc, void* args[]) {
-    asm(".intel_syntax noprefix");
-    asm("push rdi");
-    asm("push rsi");
-    asm("push rdx");
-    asm("push rcx");
-    asm("push r8");
-    asm("push r9");
-
-    {
-        void* passed_args[argc];
-        for (unsigned int i = 0; i < saved; i++) {
-            passed_args[i] = closed[i];
-        }
-        for (unsigned int i = saved; i < argc; i++) {
-            passed_args[i] = args[i - saved];
-        }
-
-        // rdi, rsi, rdx, rcx, r8, r9
-        func = (void*) (((unsigned long long) func) + 0xf);
-        asm("mov rax, %0" : "=r" (func));
-        if (argc >= 1) {
-            asm("pop rdi");
-
-            if (argc >= 2) {
-                asm("pop rsi");
-
-                if (argc >= 3) {
-                    asm("pop rdx");
-
-                    if (argc >= 4) {
-                        asm("pop rcx");
-
-                        if (argc >= 5) {
-                            asm("pop r8");
-
-                            if (argc >= 6) {
-                                asm("pop r9");
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        asm("call rax");
-
-        if (argc >= 1) {
-            asm("push rdi");
-
-            if (argc >= 2) {
-                asm("push rsi");
-
-                if (argc >= 3) {
-                    asm("push rdx");
+    void* passed_args[argc];
+    for (unsigned int i = 0; i < saved; i++) {
+        passed_args[i] = closed[i];
+    }
+    for (unsigned int i = saved; i < argc; i++) {
+        passed_args[i] = args[i - saved];
+    }
 
-                    if (argc >= 4) {
-                        asm("push rcx");
+    *called_argc -= argc - saved;
+    return call_with_args(func + 0xf, argc, passed_args);
+}
 
-                        if (argc >= 5) {
-                            asm("push r8");
+// Creates a closure of a function with the given closed values followed by the given arguments.
+// The closure holds its own reference to each of them, so the caller keeps its references.
+static void* extend_closure(void* func, unsigned int saved, void* closed[], unsigned int argc, void* args[]) {
+    void* env[saved + argc + 1];
+    for (unsigned int i = 0; i < saved; i++) {
+        env[i] = closed[i];
+    }
+    for (unsigned int i = saved; i < saved + argc; i++) {
+        env[i] = args[i - saved];
+    }
 
-                            if (argc >= 6) {
-                                asm("push r9");
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    for (unsigned int i = 0; i < saved + argc; i++) {
+        if (!is_unboxed_arg(func, i))
+            rcinc(env[i]);
     }
 
-    asm("pop r9");
-    asm("pop r8");
-    asm("pop rcx");
-    asm("pop rdx");
-    asm("pop rsi");
-    asm("pop rdi");
-    asm("mov %0, rax" : "=r" (func));
-    *called_argc -= argc - saved;
-    return func;
+    return rcclosure(func, saved + argc, env);
 }
 
-// Calls a function with unknown arity.
+// Calls a function with unknown arity. The function and arguments are borrowed, and the result is
+// owned by the caller. Closures are never changed, since other values may reference them, so
+// applying too few arguments creates a new closure.
 void* call_unknown_arity(void* func, unsigned int called_argc, void* args[]) {
+    // Whether func is the result of an earlier call and must be freed once it has been called
+    bool owned = false;
+
     while (called_argc > 0) {
+        // Functions are never null in checked code, but external functions can return null
+        if (func == NULL) {
+            static const char msg[] = "attempt to call a null function";
+            static const char loc[] = "<unknown>";
+            closey_panic(string_new(msg, sizeof(msg) - 1), string_new(loc, sizeof(loc) - 1));
+        }
+
+        void* result;
         if (((unsigned long long) func) & 1) {
             unsigned int argc = *((unsigned int*) func);
 
             if (argc <= called_argc) {
-                func = apply_func(func, &called_argc, 0, NULL, argc, args);
+                result = apply_func(func, &called_argc, 0, NULL, argc, args);
+                args += argc;
             } else {
-                void* array[called_argc + 1];
-                array[0] = func;
-                for (unsigned int i = 1; i < called_argc + 1; i++) {
-                    array[i] = args[i];
-                }
-                return rccopy(array, (called_argc + 1) * 8, (argc + 1) * 8);
+                result = extend_closure(func, 0, NULL, called_argc, args);
+                called_argc = 0;
             }
         } else {
             void** closure = (void**) func;
-            func = *closure;
-            unsigned int argc = *((unsigned int*) func);
-            unsigned int saved = 0;
-            for (unsigned int i = 1; i < argc + 1; i++) {
-                if (closure[i] == NULL) {
-                    saved = i - 1;
-                    break;
-                }
-            }
+            void* code = closure[0];
+            unsigned int argc = *((unsigned int*) code);
+            unsigned int saved = (unsigned long long) closure[1];
 
             if (argc <= called_argc + saved) {
-                func = apply_func(func, &called_argc, saved, closure + 1, argc, args);
+                result = apply_func(code, &called_argc, saved, closure + 2, argc, args);
+                args += argc - saved;
             } else {
-                if (!has_one_reference(closure)) {
-                    closure = rccopy(closure, (saved + 1) * 8, (argc + 1) * 8);
-                }
-
-                for (unsigned int i = saved + 1; i < saved + called_argc + 1; i++) {
-                    closure[i] = args[i - saved - 1];
-                    rcinc(closure[i]);
-                }
-
-                return closure;
+                result = extend_closure(code, saved, closure + 2, called_argc, args);
+                called_argc = 0;
             }
         }
+
+        if (owned)
+            rcfuncfree(func);
+        func = result;
+        owned = true;
     }
 
     return func;
 }
-
@@ -14,8 +14,21 @@ pub mod x86_64;
 /// Module for wasm64 code generation.
 pub mod wasm64;
 
+/// Module for LLVM IR generation.
+pub mod llvm;
+
+/// Module for DWARF debug info generation. This module contains a small writer for the line
+/// tables and function descriptions that debuggers read from object files.
+pub mod dwarf;
+
+/// Module for caching generated code between builds. This module contains the fingerprints that
+/// tell whether a function has changed, and the cache of the code generated for each function.
+pub mod cache;
+
+use logos::Span;
 use std::collections::HashMap;
 use std::ops::Range;
+use tracing::trace;
 
 use ir::IrFunction;
 
@@ -33,12 +46,27 @@ pub const DEFAULT_OS: &str = "linux";
 #[cfg(target_os = "macos")]
 pub const DEFAULT_OS: &str = "macos";
 
+/// Marks where the code generated from a span of source code starts. The code runs until the
+/// next mark or the end of the function it is in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMapping {
+    /// The offset of the code in the generated code.
+    pub offset: usize,
+
+    /// The file the span is in, as an index into the files of the backend IR module.
+    pub file: usize,
+
+    /// The span of source code the code was generated from.
+    pub span: Span,
+}
+
 /// Represents generated code in some architecture.
 #[derive(Default)]
 pub struct GeneratedCode {
     func_addrs: HashMap<String, Range<usize>>,
     func_refs: HashMap<usize, String>,
     data: Vec<u8>,
+    source_map: Vec<SourceMapping>,
 }
 
 impl GeneratedCode {
@@ -48,6 +76,7 @@ impl GeneratedCode {
             func_addrs: HashMap::new(),
             func_refs: HashMap::new(),
             data: Vec::new(),
+            source_map: Vec::new(),
         }
     }
 
@@ -108,29 +137,116 @@ impl GeneratedCode {
     pub fn get_mut_relocation_table(&mut self) -> &mut HashMap<usize, String> {
         &mut self.func_refs
     }
+
+    /// Marks the code generated from here on as generated from a span of source code. Marks of the
+    /// same span in a row are merged, and a mark replaces one at the same offset.
+    pub fn map_source(&mut self, file: usize, span: Span) {
+        let offset = self.len();
+        match self.source_map.last_mut() {
+            Some(last) if last.file == file && last.span == span => (),
+            Some(last) if last.offset == offset => *last = SourceMapping { offset, file, span },
+            _ => self.source_map.push(SourceMapping { offset, file, span }),
+        }
+    }
+
+    /// Gets the marks of the source code the code was generated from, in the order of their
+    /// offsets.
+    pub fn get_source_map(&self) -> &[SourceMapping] {
+        &self.source_map
+    }
 }
 
-/// Performs register allocation by linear scan on an IrFunction.
-pub fn linear_scan(func: &mut IrFunction, register_count: usize) {
-    let mut register_lifetimes = vec![0usize; register_count];
+/// Allocates a register or a spill slot to every local of a function by linear scan, and returns
+/// the number of spill slots used. `callee_saved` has an entry for each register a backend
+/// allocates, which is true if calls keep the value of the register. Locals are given the ids of
+/// their registers in `local_register`, and spill slots are numbered after the registers.
+///
+/// Locals that are alive across an instruction that calls a function are only allocated registers
+/// that calls keep, or spilled. Other locals prefer the registers that calls overwrite, so that the
+/// registers a function has to save for its caller are only used when they are needed.
+pub fn allocate_registers(func: &mut IrFunction, callee_saved: &[bool]) -> usize {
+    // Instructions may have been inserted since lifetimes were last calculated
+    ir::calculate_lifetimes(func);
+
+    let calls: Vec<_> = func
+        .ssas
+        .iter()
+        .enumerate()
+        .filter(|(_, ssa)| ssa.instr.is_call())
+        .map(|(i, _)| i)
+        .collect();
+
+    // The index of the last instruction that uses the local in each register and spill slot
+    let mut registers: Vec<Option<usize>> = vec![None; callee_saved.len()];
+    let mut slots: Vec<Option<usize>> = vec![];
+    for (i, ssa) in func.ssas.iter_mut().enumerate() {
+        if ssa.local.is_none() {
+            continue;
+        }
 
-    'a: for ssa in func.ssas.iter_mut() {
-        for lifetime in register_lifetimes.iter_mut() {
-            if *lifetime > 0 {
-                *lifetime -= 1;
+        // A call at the last use of a local reads it before any register is overwritten
+        let end = i + ssa.local_lifetime;
+        let across_call = calls.iter().any(|c| i < *c && *c < end);
+        let free = |v: &Option<usize>| !matches!(v, Some(v) if *v >= i);
+        let register = (0..registers.len())
+            .filter(|r| free(&registers[*r]) && (callee_saved[*r] || !across_call))
+            .min_by_key(|r| callee_saved[*r]);
+
+        ssa.local_register = match register {
+            Some(r) => {
+                registers[r] = Some(end);
+                r
             }
-        }
 
-        if ssa.local.is_some() {
-            for (reg, lifetime) in register_lifetimes.iter_mut().enumerate() {
-                if *lifetime == 0 {
-                    *lifetime = ssa.local_lifetime;
-                    ssa.local_register = reg;
-                    continue 'a;
-                }
+            None => {
+                let slot = match slots.iter().position(free) {
+                    Some(s) => s,
+                    None => {
+                        slots.push(None);
+                        slots.len() - 1
+                    }
+                };
+                slots[slot] = Some(end);
+                registers.len() + slot
             }
-            ssa.local_register = register_lifetimes.len();
-            register_lifetimes.push(ssa.local_lifetime);
-        }
+        };
+    }
+
+    trace!(
+        function = %func.name,
+        spilled = slots.len(),
+        "allocated registers"
+    );
+    slots.len()
+}
+
+/// Generates a C header declaring the exported functions of a module. The include guard is made
+/// from the given name, usually the name of the library.
+pub fn generate_c_header(module: &ir::IrModule, name: &str) -> String {
+    let guard: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    let mut header = format!(
+        "/* Generated by closeyc. Link with the runtime library. */\n#ifndef {0}_H\n#define {0}_H\n\n#include <stdbool.h>\n#include <stdint.h>\n\n",
+        guard
+    );
+    for export in module.exports.iter() {
+        let args: Vec<_> = export.sig.args.iter().map(|v| v.to_string()).collect();
+        let args = if args.is_empty() {
+            String::from("void")
+        } else {
+            args.join(", ")
+        };
+        header.push_str(&format!("{} {}({});\n", export.sig.ret, export.name, args));
     }
+    header.push_str(&format!("\n#endif /* {}_H */\n", guard));
+    header
 }
@@ -0,0 +1,185 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::Path;
+
+use super::super::options::CompilerOptions;
+use super::ir::{IrFunction, IrModule};
+
+/// The first line of cache files. It names the version of the compiler, so that code generated by
+/// another version is never reused.
+const HEADER: &str = concat!("closey cache ", env!("CARGO_PKG_VERSION"));
+
+/// The code generated for a function, along with the fingerprint the function had when it was
+/// generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedFunction {
+    /// The fingerprint of the function.
+    pub fingerprint: u64,
+
+    /// The declarations of the functions the code calls, by their names.
+    pub declarations: Vec<(String, String)>,
+
+    /// The generated code.
+    pub code: String,
+}
+
+/// Represents the code generated for the functions of a module, kept between builds so that
+/// functions that have not changed are not generated again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodegenCache {
+    /// The fingerprint of the object file last built from the module, or 0 if none has been.
+    pub object: u64,
+
+    /// The code generated for each function, by the names of the functions.
+    functions: HashMap<String, CachedFunction>,
+}
+
+impl CodegenCache {
+    /// Creates an empty cache.
+    pub fn new() -> CodegenCache {
+        CodegenCache::default()
+    }
+
+    /// Loads a cache from a file. A missing file, or one written by another version of the
+    /// compiler, gives an empty cache.
+    pub fn load<P: AsRef<Path>>(path: P) -> CodegenCache {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|v| CodegenCache::parse(&v))
+            .unwrap_or_default()
+    }
+
+    /// Parses the contents of a cache file, as written by `save()`.
+    fn parse(s: &str) -> Option<CodegenCache> {
+        let rest = s.strip_prefix(HEADER)?.strip_prefix('\n')?;
+        let (line, mut rest) = rest.split_once('\n')?;
+        let mut cache = CodegenCache {
+            object: u64::from_str_radix(line.strip_prefix("object ")?, 16).ok()?,
+            functions: HashMap::new(),
+        };
+
+        while !rest.is_empty() {
+            let (line, next) = rest.split_once('\n')?;
+            let mut fields = line.strip_prefix("function ")?.splitn(4, ' ');
+            let fingerprint = u64::from_str_radix(fields.next()?, 16).ok()?;
+            let declarations: usize = fields.next()?.parse().ok()?;
+            let len: usize = fields.next()?.parse().ok()?;
+            let name = fields.next()?;
+            rest = next;
+
+            let mut function = CachedFunction {
+                fingerprint,
+                declarations: vec![],
+                code: String::new(),
+            };
+            for _ in 0..declarations {
+                let (name, next) = rest.split_once('\n')?;
+                let (declaration, next) = next.split_once('\n')?;
+                function
+                    .declarations
+                    .push((String::from(name), String::from(declaration)));
+                rest = next;
+            }
+
+            function.code = String::from(rest.get(..len)?);
+            rest = rest.get(len..)?;
+            cache.functions.insert(String::from(name), function);
+        }
+
+        Some(cache)
+    }
+
+    /// Writes the cache to a file. Each function is written as a line giving its fingerprint, the
+    /// number of declarations, the length of its code, and its name, followed by the name and text
+    /// of each declaration on lines of their own, followed by the code.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = format!("{}\nobject {:016x}\n", HEADER, self.object);
+        let mut names: Vec<_> = self.functions.keys().collect();
+        names.sort();
+        for name in names {
+            let function = &self.functions[name];
+            writeln!(
+                out,
+                "function {:016x} {} {} {}",
+                function.fingerprint,
+                function.declarations.len(),
+                function.code.len(),
+                name
+            )
+            .unwrap();
+            for (name, declaration) in function.declarations.iter() {
+                writeln!(out, "{}\n{}", name, declaration).unwrap();
+            }
+            out.push_str(&function.code);
+        }
+
+        fs::write(path, out)
+    }
+
+    /// Returns the code generated for a function, if it was generated for the same fingerprint.
+    pub fn get(&self, name: &str, fingerprint: u64) -> Option<&CachedFunction> {
+        self.functions
+            .get(name)
+            .filter(|v| v.fingerprint == fingerprint)
+    }
+
+    /// Adds the code generated for a function, replacing the code generated for it before.
+    pub fn insert(&mut self, name: &str, function: CachedFunction) {
+        self.functions.insert(String::from(name), function);
+    }
+
+    /// Removes the code of the functions that are no longer in the module.
+    pub fn retain_module(&mut self, module: &IrModule) {
+        self.functions.retain(|k, _| module.function(k).is_some());
+    }
+}
+
+/// Returns the fingerprint of a function of a module. The fingerprint changes whenever the code
+/// generated for the function could: when its instructions change, or when the signature of a
+/// function it refers to does.
+pub fn fingerprint(func: &IrFunction, module: &IrModule) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    HEADER.hash(&mut hasher);
+    func.to_string().hash(&mut hasher);
+
+    // Sets are hashed in order, since their iteration order changes between runs
+    let mut unboxed: Vec<_> = func.unboxed.iter().map(|v| v.to_string()).collect();
+    unboxed.sort_unstable();
+    unboxed.hash(&mut hasher);
+    let mut pure: Vec<_> = func.pure.iter().collect();
+    pure.sort_unstable();
+    pure.hash(&mut hasher);
+
+    for dep in func.dependencies() {
+        dep.hash(&mut hasher);
+        if let Some(f) = module.function(dep) {
+            f.argc.hash(&mut hasher);
+            f.unboxed_mask().hash(&mut hasher);
+        } else if let Some(sig) = module.externs.get(dep) {
+            sig.ret.to_string().hash(&mut hasher);
+            for arg in sig.args.iter() {
+                arg.to_string().hash(&mut hasher);
+            }
+        }
+    }
+
+    hasher.finish()
+}
+
+/// Returns the fingerprint of an object file built from the given code, such as LLVM IR, with the
+/// given options. An object file only has to be built again when its fingerprint changes.
+pub fn object_fingerprint(code: &str, options: &CompilerOptions) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    HEADER.hash(&mut hasher);
+    code.hash(&mut hasher);
+    options.arch.to_string().hash(&mut hasher);
+    options.backend.to_string().hash(&mut hasher);
+    options.opt_level.hash(&mut hasher);
+    options.debug_info.hash(&mut hasher);
+    options.alloc.to_string().hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,539 @@
+use std::collections::HashMap;
+
+use super::ir::IrModule;
+use super::GeneratedCode;
+
+// Tags
+const DW_TAG_FORMAL_PARAMETER: u8 = 0x05;
+const DW_TAG_COMPILE_UNIT: u8 = 0x11;
+const DW_TAG_BASE_TYPE: u8 = 0x24;
+const DW_TAG_SUBPROGRAM: u8 = 0x2e;
+
+// Attributes
+const DW_AT_LOCATION: u8 = 0x02;
+const DW_AT_NAME: u8 = 0x03;
+const DW_AT_BYTE_SIZE: u8 = 0x0b;
+const DW_AT_STMT_LIST: u8 = 0x10;
+const DW_AT_LOW_PC: u8 = 0x11;
+const DW_AT_HIGH_PC: u8 = 0x12;
+const DW_AT_LANGUAGE: u8 = 0x13;
+const DW_AT_COMP_DIR: u8 = 0x1b;
+const DW_AT_PRODUCER: u8 = 0x25;
+const DW_AT_DECL_FILE: u8 = 0x3a;
+const DW_AT_DECL_LINE: u8 = 0x3b;
+const DW_AT_ENCODING: u8 = 0x3e;
+const DW_AT_TYPE: u8 = 0x49;
+const DW_AT_RANGES: u8 = 0x55;
+
+// Forms
+const DW_FORM_ADDR: u8 = 0x01;
+const DW_FORM_DATA2: u8 = 0x05;
+const DW_FORM_DATA8: u8 = 0x07;
+const DW_FORM_STRING: u8 = 0x08;
+const DW_FORM_DATA1: u8 = 0x0b;
+const DW_FORM_UDATA: u8 = 0x0f;
+const DW_FORM_REF4: u8 = 0x13;
+const DW_FORM_SEC_OFFSET: u8 = 0x17;
+const DW_FORM_EXPRLOC: u8 = 0x18;
+
+// Base type encodings
+const DW_ATE_ADDRESS: u8 = 0x01;
+const DW_ATE_BOOLEAN: u8 = 0x02;
+const DW_ATE_FLOAT: u8 = 0x04;
+const DW_ATE_SIGNED: u8 = 0x05;
+const DW_ATE_UTF: u8 = 0x10;
+
+// Debuggers have no idea what Closey is, but know how to show the values of C programs, which
+// Closey values look like at this level
+const DW_LANG_C99: u16 = 0x0c;
+
+// Line number opcodes
+const DW_LNS_COPY: u8 = 0x01;
+const DW_LNS_ADVANCE_PC: u8 = 0x02;
+const DW_LNS_ADVANCE_LINE: u8 = 0x03;
+const DW_LNS_SET_FILE: u8 = 0x04;
+const DW_LNS_SET_COLUMN: u8 = 0x05;
+const DW_LNE_END_SEQUENCE: u8 = 0x01;
+const DW_LNE_SET_ADDRESS: u8 = 0x02;
+
+// Location expression opcodes
+const DW_OP_REG0: u8 = 0x50;
+const DW_OP_BREG0: u8 = 0x70;
+const DW_OP_REGX: u8 = 0x90;
+const DW_OP_BREGX: u8 = 0x92;
+
+// The number of standard opcodes the line number program header declares, plus one
+const OPCODE_BASE: u8 = 13;
+
+// The code of a function starts after its header, which holds its argument count and the mask of
+// its unboxed arguments
+const FUNCTION_HEADER_SIZE: usize = 16;
+
+// Abbreviation codes
+const ABBREV_COMPILE_UNIT: u8 = 1;
+const ABBREV_SUBPROGRAM: u8 = 2;
+const ABBREV_SUBPROGRAM_WITHOUT_ARGS: u8 = 3;
+const ABBREV_PARAMETER: u8 = 4;
+const ABBREV_BASE_TYPE: u8 = 5;
+
+/// Represents a source file debug info refers to.
+pub struct DebugFile<'a> {
+    /// The path of the file, as the debugger should find it.
+    pub path: String,
+
+    /// The contents of the file, which lines and columns are counted in.
+    pub contents: &'a str,
+}
+
+/// Represents where the value of a variable is kept, given by DWARF register numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableLocation {
+    /// The value is in a register.
+    Register(u16),
+
+    /// The value is in memory, at an offset from the address in a register.
+    Memory(u16, i64),
+}
+
+/// Represents a reference from a debug section to a symbol, which is filled in by the linker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugRelocation {
+    /// The offset of the reference in the section.
+    pub offset: usize,
+
+    /// The function or section referred to.
+    pub target: String,
+
+    /// The offset added to the address of the target.
+    pub addend: i64,
+
+    /// The size of the reference in bytes.
+    pub size: u8,
+}
+
+/// Represents a generated debug section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugSection {
+    /// The name of the section, such as `.debug_line`.
+    pub name: &'static str,
+
+    /// The contents of the section, with zeroes where relocations are.
+    pub data: Vec<u8>,
+
+    /// The references to functions and other sections.
+    pub relocations: Vec<DebugRelocation>,
+}
+
+impl DebugSection {
+    fn new(name: &'static str) -> DebugSection {
+        DebugSection {
+            name,
+            data: vec![],
+            relocations: vec![],
+        }
+    }
+
+    fn u8(&mut self, v: u8) {
+        self.data.push(v);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn uleb(&mut self, v: u64) {
+        write_uleb(&mut self.data, v);
+    }
+
+    fn sleb(&mut self, v: i64) {
+        write_sleb(&mut self.data, v);
+    }
+
+    fn string(&mut self, s: &str) {
+        self.data.extend_from_slice(s.as_bytes());
+        self.data.push(0);
+    }
+
+    /// Adds a reference to a symbol that takes up the given number of bytes.
+    fn reference(&mut self, target: &str, addend: i64, size: u8) {
+        self.relocations.push(DebugRelocation {
+            offset: self.data.len(),
+            target: String::from(target),
+            addend,
+            size,
+        });
+        self.data.resize(self.data.len() + size as usize, 0);
+    }
+
+    /// Fills in a 32 bit length that was written as zero at the given offset, which counts the
+    /// bytes after it.
+    fn patch_length(&mut self, offset: usize) {
+        let length = (self.data.len() - offset - 4) as u32;
+        self.data[offset..offset + 4].copy_from_slice(&length.to_le_bytes());
+    }
+}
+
+/// Writes an unsigned LEB128 number.
+fn write_uleb(data: &mut Vec<u8>, mut v: u64) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            data.push(byte);
+            break;
+        }
+        data.push(byte | 0x80);
+    }
+}
+
+/// Writes a signed LEB128 number.
+fn write_sleb(data: &mut Vec<u8>, mut v: i64) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if (v == 0 && byte & 0x40 == 0) || (v == -1 && byte & 0x40 != 0) {
+            data.push(byte);
+            break;
+        }
+        data.push(byte | 0x80);
+    }
+}
+
+/// Finds the lines and columns of offsets into the files debug info refers to.
+pub struct SourcePositions<'a> {
+    files: Vec<(&'a str, Vec<usize>)>,
+}
+
+impl<'a> SourcePositions<'a> {
+    /// Finds where the lines of each file start.
+    pub fn new(files: &[DebugFile<'a>]) -> SourcePositions<'a> {
+        SourcePositions {
+            files: files
+                .iter()
+                .map(|f| {
+                    let starts = std::iter::once(0)
+                        .chain(f.contents.match_indices('\n').map(|(i, _)| i + 1))
+                        .collect();
+                    (f.contents, starts)
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the line and column of an offset into a file, both counted from 1, or 0 and 0 if
+    /// the file is not known. Columns count characters, not bytes.
+    pub fn position(&self, file: usize, offset: usize) -> (u64, u64) {
+        let (contents, starts) = match self.files.get(file) {
+            Some(v) => v,
+            None => return (0, 0),
+        };
+
+        let line = starts.partition_point(|&v| v <= offset);
+        let column = contents
+            .get(starts[line - 1]..offset)
+            .map(|v| v.chars().count())
+            .unwrap_or(0);
+        (line as u64, column as u64 + 1)
+    }
+}
+
+/// Returns the location expression of a variable location.
+fn location_expression(loc: VariableLocation) -> Vec<u8> {
+    let mut expr = vec![];
+    match loc {
+        VariableLocation::Register(r) if r < 32 => expr.push(DW_OP_REG0 + r as u8),
+        VariableLocation::Register(r) => {
+            expr.push(DW_OP_REGX);
+            write_uleb(&mut expr, r as u64);
+        }
+
+        VariableLocation::Memory(r, offset) if r < 32 => {
+            expr.push(DW_OP_BREG0 + r as u8);
+            write_sleb(&mut expr, offset);
+        }
+        VariableLocation::Memory(r, offset) => {
+            expr.push(DW_OP_BREGX);
+            write_uleb(&mut expr, r as u64);
+            write_sleb(&mut expr, offset);
+        }
+    }
+    expr
+}
+
+/// Returns the encoding and size of the base type debug info describes a type by. Values of types
+/// that are not numbers, booleans, or characters are pointers.
+pub fn base_type(name: &str) -> (u8, u8) {
+    match name {
+        "Int" => (DW_ATE_SIGNED, 8),
+        "Float" => (DW_ATE_FLOAT, 8),
+        "Bool" => (DW_ATE_BOOLEAN, 1),
+        "Char" => (DW_ATE_UTF, 4),
+        _ => (DW_ATE_ADDRESS, 8),
+    }
+}
+
+/// Writes an abbreviation, which declares the tag of a kind of entry, whether it has children, and
+/// the attributes it has with their forms.
+fn write_abbreviation(
+    abbrev: &mut DebugSection,
+    code: u8,
+    tag: u8,
+    children: bool,
+    attributes: &[(u8, u8)],
+) {
+    abbrev.uleb(code as u64);
+    abbrev.uleb(tag as u64);
+    abbrev.u8(children as u8);
+    for (attribute, form) in attributes.iter() {
+        abbrev.uleb(*attribute as u64);
+        abbrev.uleb(*form as u64);
+    }
+    abbrev.u16(0);
+}
+
+/// Writes the abbreviations the debug info uses.
+fn generate_abbrev() -> DebugSection {
+    let mut abbrev = DebugSection::new(".debug_abbrev");
+    write_abbreviation(
+        &mut abbrev,
+        ABBREV_COMPILE_UNIT,
+        DW_TAG_COMPILE_UNIT,
+        true,
+        &[
+            (DW_AT_PRODUCER, DW_FORM_STRING),
+            (DW_AT_LANGUAGE, DW_FORM_DATA2),
+            (DW_AT_NAME, DW_FORM_STRING),
+            (DW_AT_COMP_DIR, DW_FORM_STRING),
+            (DW_AT_STMT_LIST, DW_FORM_SEC_OFFSET),
+            (DW_AT_LOW_PC, DW_FORM_ADDR),
+            (DW_AT_RANGES, DW_FORM_SEC_OFFSET),
+        ],
+    );
+
+    let subprogram = [
+        (DW_AT_NAME, DW_FORM_STRING),
+        (DW_AT_LOW_PC, DW_FORM_ADDR),
+        (DW_AT_HIGH_PC, DW_FORM_DATA8),
+        (DW_AT_DECL_FILE, DW_FORM_UDATA),
+        (DW_AT_DECL_LINE, DW_FORM_UDATA),
+    ];
+    write_abbreviation(
+        &mut abbrev,
+        ABBREV_SUBPROGRAM,
+        DW_TAG_SUBPROGRAM,
+        true,
+        &subprogram,
+    );
+    write_abbreviation(
+        &mut abbrev,
+        ABBREV_SUBPROGRAM_WITHOUT_ARGS,
+        DW_TAG_SUBPROGRAM,
+        false,
+        &subprogram,
+    );
+
+    write_abbreviation(
+        &mut abbrev,
+        ABBREV_PARAMETER,
+        DW_TAG_FORMAL_PARAMETER,
+        false,
+        &[
+            (DW_AT_NAME, DW_FORM_STRING),
+            (DW_AT_TYPE, DW_FORM_REF4),
+            (DW_AT_LOCATION, DW_FORM_EXPRLOC),
+        ],
+    );
+    write_abbreviation(
+        &mut abbrev,
+        ABBREV_BASE_TYPE,
+        DW_TAG_BASE_TYPE,
+        false,
+        &[
+            (DW_AT_NAME, DW_FORM_STRING),
+            (DW_AT_ENCODING, DW_FORM_DATA1),
+            (DW_AT_BYTE_SIZE, DW_FORM_DATA1),
+        ],
+    );
+    abbrev.u8(0);
+    abbrev
+}
+
+/// Generates the DWARF sections describing machine code generated from a backend IR module: a line
+/// table mapping the code to the lines and columns of the source code it was generated from, and
+/// the functions of the module with where their arguments are kept. The files are the files of
+/// the module, in the same order, and the first names the compile unit. The directory is the one
+/// relative paths are relative to. Functions are referred to by name, so the code must not have
+/// been relocated yet.
+pub fn generate_debug_sections(
+    code: &GeneratedCode,
+    module: &IrModule,
+    files: &[DebugFile],
+    dir: &str,
+    arg_location: fn(usize) -> VariableLocation,
+) -> Vec<DebugSection> {
+    let positions = SourcePositions::new(files);
+
+    // Functions with code, in the order they are in
+    let mut funcs: Vec<_> = module
+        .funcs
+        .iter()
+        .filter_map(|f| code.get_funcs().get(&f.name).map(|r| (f, r.clone())))
+        .filter(|(_, r)| r.end > r.start + FUNCTION_HEADER_SIZE)
+        .collect();
+    funcs.sort_by_key(|(_, r)| r.start);
+
+    // Line table
+    let mut line = DebugSection::new(".debug_line");
+    line.u32(0);
+    line.u16(4);
+    let header_length = line.data.len();
+    line.u32(0);
+    line.u8(1); // minimum instruction length
+    line.u8(1); // maximum operations per instruction
+    line.u8(1); // default is_stmt
+    line.u8(-5i8 as u8); // line base
+    line.u8(14); // line range
+    line.u8(OPCODE_BASE);
+    line.data
+        .extend_from_slice(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]);
+    line.u8(0); // include directories
+    for file in files {
+        line.string(&file.path);
+        line.uleb(0);
+        line.uleb(0);
+        line.uleb(0);
+    }
+    line.u8(0);
+    line.patch_length(header_length);
+
+    let map = code.get_source_map();
+    for (func, range) in funcs.iter() {
+        let start = range.start + FUNCTION_HEADER_SIZE;
+        let first = map.partition_point(|v| v.offset < start);
+        let last = map.partition_point(|v| v.offset < range.end);
+        if first == last {
+            continue;
+        }
+
+        line.u8(0);
+        line.uleb(9);
+        line.u8(DW_LNE_SET_ADDRESS);
+        line.reference(&func.name, (map[first].offset - range.start) as i64, 8);
+
+        let (mut file, mut row, mut column, mut offset) = (0, 1, 0, map[first].offset);
+        for mapping in map[first..last].iter() {
+            let (r, c) = positions.position(mapping.file, mapping.span.start);
+            if r == 0 {
+                continue;
+            }
+
+            if mapping.file != file {
+                line.u8(DW_LNS_SET_FILE);
+                line.uleb(mapping.file as u64 + 1);
+                file = mapping.file;
+            }
+            if r != row {
+                line.u8(DW_LNS_ADVANCE_LINE);
+                line.sleb(r as i64 - row as i64);
+                row = r;
+            }
+            if c != column {
+                line.u8(DW_LNS_SET_COLUMN);
+                line.uleb(c);
+                column = c;
+            }
+            if mapping.offset != offset {
+                line.u8(DW_LNS_ADVANCE_PC);
+                line.uleb((mapping.offset - offset) as u64);
+                offset = mapping.offset;
+            }
+            line.u8(DW_LNS_COPY);
+        }
+
+        line.u8(DW_LNS_ADVANCE_PC);
+        line.uleb((range.end - offset) as u64);
+        line.u8(0);
+        line.uleb(1);
+        line.u8(DW_LNE_END_SEQUENCE);
+    }
+    line.patch_length(0);
+
+    // Address ranges of the compile unit
+    let mut ranges = DebugSection::new(".debug_ranges");
+    for (func, range) in funcs.iter() {
+        ranges.reference(&func.name, FUNCTION_HEADER_SIZE as i64, 8);
+        ranges.reference(&func.name, range.len() as i64, 8);
+    }
+    ranges.u64(0);
+    ranges.u64(0);
+
+    // Functions and their arguments
+    let mut info = DebugSection::new(".debug_info");
+    info.u32(0);
+    info.u16(4);
+    info.reference(".debug_abbrev", 0, 4);
+    info.u8(8);
+
+    info.uleb(ABBREV_COMPILE_UNIT as u64);
+    info.string(concat!("closeyc ", env!("CARGO_PKG_VERSION")));
+    info.u16(DW_LANG_C99);
+    info.string(files.first().map(|v| v.path.as_str()).unwrap_or(""));
+    info.string(dir);
+    info.reference(".debug_line", 0, 4);
+    info.u64(0);
+    info.reference(".debug_ranges", 0, 4);
+
+    // Types are referred to by their offset, so they are written before the functions
+    let mut types = HashMap::new();
+    for (func, _) in funcs.iter() {
+        for (_, _type) in func.debug_args.iter() {
+            if !types.contains_key(_type) {
+                let (encoding, size) = base_type(_type);
+                types.insert(_type, info.data.len() as u32);
+                info.uleb(ABBREV_BASE_TYPE as u64);
+                info.string(_type);
+                info.u8(encoding);
+                info.u8(size);
+            }
+        }
+    }
+
+    for (func, range) in funcs.iter() {
+        let (row, _) = positions.position(func.file, func.span.start);
+        if func.debug_args.is_empty() {
+            info.uleb(ABBREV_SUBPROGRAM_WITHOUT_ARGS as u64);
+        } else {
+            info.uleb(ABBREV_SUBPROGRAM as u64);
+        }
+        info.string(&func.name);
+        info.reference(&func.name, FUNCTION_HEADER_SIZE as i64, 8);
+        info.u64((range.len() - FUNCTION_HEADER_SIZE) as u64);
+        info.uleb(func.file as u64 + 1);
+        info.uleb(row);
+
+        for (i, (name, _type)) in func.debug_args.iter().enumerate() {
+            let expr = location_expression(arg_location(i));
+            info.uleb(ABBREV_PARAMETER as u64);
+            info.string(name);
+            info.u32(types[_type]);
+            info.uleb(expr.len() as u64);
+            info.data.extend_from_slice(&expr);
+        }
+
+        if !func.debug_args.is_empty() {
+            info.u8(0);
+        }
+    }
+    info.u8(0);
+    info.patch_length(0);
+
+    vec![generate_abbrev(), info, line, ranges]
+}
@@ -1,10 +1,38 @@
-use std::collections::HashMap;
+pub mod parser;
+
+use logos::Span;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
+use tracing::{info_span, trace};
+
+use super::super::frontend::intrinsics::{self, Lowering};
+use super::super::frontend::ir::{self, ArityInfo, BinOp, CSignature, SExpr, SExprMetadata};
+use super::super::frontend::pretty;
+use super::super::frontend::types::{IntSize, Type, TypeRc};
+use super::super::options::CompilerOptions;
+
+/// The maximum number of arguments of a closure passed to an external function, which is the number
+/// of arguments passed in registers.
+const CALLBACK_MAX_ARGS: usize = 6;
+
+/// The maximum number of fields of a constructor, which is the number of arguments passed in
+/// registers after the tag.
+const CONSTRUCTOR_MAX_FIELDS: usize = 5;
 
-use super::super::frontend::ir::{self, ArityInfo, SExpr, SExprMetadata};
+/// The prefix of the runtime functions that build values of declared types, which is followed by
+/// the number of fields.
+const CONSTRUCTOR_PREFIX: &str = "closey_data_";
+
+/// The prefix of the show functions derived for the types of shown values, which is followed by
+/// the type.
+const SHOW_PREFIX: &str = "show.";
+
+/// The prefix of the equality functions derived for the types of values compared by their contents,
+/// which is followed by the type.
+const EQ_PREFIX: &str = "eq.";
 
 /// An instruction in the low level intermediate representation.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub enum IrInstruction {
     /// Returns an optional parameter from a function.
     Ret,
@@ -12,20 +40,31 @@ pub enum IrInstruction {
     /// Loads a function or argument parameter into a local.
     Load,
 
-    /// Applies a list of arguments to a function pointer or closure struct to form a new closure
-    /// struct. If passed in a closure struct, it allocates a new closure struct if the passed in
-    /// closure struct has a reference count greater than 1.
+    /// Applies a list of arguments to a function to form a new closure struct, which is the
+    /// function followed by the number of arguments saved and the arguments themselves. The
+    /// closure takes over a reference to each argument, and it is never changed once created.
     Apply,
 
     /// Calls a function, function pointer, or closure struct and passes the return value into a
-    /// new local value. True if the arity is known at compile time, false otherwise.
+    /// new local value. True if the arity is known at compile time, false otherwise. The callee
+    /// and arguments are borrowed, and calling a closure with too few arguments creates a new
+    /// closure holding its own references to the saved values.
     Call(bool),
 
+    /// Calls a function with a known arity and returns its return value from the current function.
+    /// Backends reuse the stack frame of the current function for the call where they can, so
+    /// recursion through tail calls does not grow the stack.
+    TailCall,
+
+    /// Calls a function in the runtime library with the C calling convention and passes the return
+    /// value into a new local value.
+    CallRuntime,
+
     /// Increments the reference counter for a closure struct.
     RcInc,
 
     /// Decrements the reference counter for a closure struct and deallocates and decrements child
-    /// nodes if the reference counter reaches 0.
+    /// nodes if the reference counter reaches 0. Values other than closures are only decremented.
     RcFuncFree,
 }
 
@@ -38,12 +77,24 @@ impl Display for IrInstruction {
             Apply => write!(f, "apply"),
             Call(true) => write!(f, "call"),
             Call(false) => write!(f, "call?"),
+            TailCall => write!(f, "tailcall"),
+            CallRuntime => write!(f, "callrt"),
             RcInc => write!(f, "rcinc"),
             RcFuncFree => write!(f, "rcfuncfree"),
         }
     }
 }
 
+impl IrInstruction {
+    /// Returns true if backends call a function to execute the instruction, which overwrites the
+    /// registers that the callee does not have to save. Applications call the runtime library to
+    /// allocate the closure, and freeing calls it to decrement the reference count.
+    pub fn is_call(&self) -> bool {
+        use IrInstruction::*;
+        matches!(self, Apply | Call(_) | TailCall | CallRuntime | RcFuncFree)
+    }
+}
+
 /// An argument passed into an instruction in the low level intermediate representation.
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub enum IrArgument {
@@ -56,6 +107,15 @@ pub enum IrArgument {
 
     /// A function address.
     Function(String),
+
+    /// The address of a static string, as an index into the strings of the module.
+    String(usize),
+
+    /// A char, which is passed as its code point.
+    Char(char),
+
+    /// An integer, or the bits of a float. Tags of constructors are passed as integers too.
+    Int(i64),
 }
 
 impl Display for IrArgument {
@@ -64,7 +124,12 @@ impl Display for IrArgument {
         match self {
             Local(l) => write!(f, "%{}", l),
             Argument(a) => write!(f, "${}", a),
+            // Derived functions are named after types, which can have spaces
+            Function(g) if g.contains(char::is_whitespace) => write!(f, "@{:?}", g),
             Function(g) => write!(f, "@{}", g),
+            String(s) => write!(f, "&{}", s),
+            Char(c) => write!(f, "{:?}", c),
+            Int(n) => write!(f, "{}", n),
         }
     }
 }
@@ -77,7 +142,8 @@ pub struct IrSsa {
     /// The lifetime of the local assigned in this statement.
     pub local_lifetime: usize,
 
-    /// The register the local assigned to in this instruction is allocated in.
+    /// The register the local assigned to in this instruction is allocated in. Ids past the
+    /// registers of the backend are spill slots.
     pub local_register: usize,
 
     /// The instruction (ie opcode) being executed in this instruction.
@@ -85,6 +151,13 @@ pub struct IrSsa {
 
     /// The arguments passed into the instruction.
     pub args: Vec<IrArgument>,
+
+    /// The span of the source code the instruction was lowered from. Instructions added by later
+    /// passes, such as reference counting, take the span of the instruction they are added for.
+    pub span: Span,
+
+    /// The file the span is in, as an index into the files of the module.
+    pub file: usize,
 }
 
 impl Display for IrSsa {
@@ -106,12 +179,30 @@ pub struct IrFunction {
     /// The name of the function.
     pub name: String,
 
+    /// The file the function is defined in, as an index into the files of the module.
+    pub file: usize,
+
+    /// The span of the definition of the function.
+    pub span: Span,
+
     /// The number of arguments (including closed over values) that the function takes in.
     pub argc: usize,
 
+    /// The names and types of the arguments, in the same order, which debug info describes them
+    /// by. Functions that are not lowered from source code have none.
+    pub debug_args: Vec<(String, String)>,
+
     /// The list of all SSAs associated with this function.
     /// TODO: Replace with basic blocks.
     pub ssas: Vec<IrSsa>,
+
+    /// The locals and arguments that hold values which are not pointers, such as chars, and so are
+    /// not reference counted.
+    pub unboxed: HashSet<IrArgument>,
+
+    /// The locals assigned by calls to the runtime library that have no side effects, such as
+    /// operators and constructors, so that a later call with the same arguments can reuse them.
+    pub pure: HashSet<usize>,
 }
 
 impl Display for IrFunction {
@@ -125,6 +216,32 @@ impl Display for IrFunction {
 }
 
 impl IrFunction {
+    /// Returns the mask of the arguments that hold values which are not pointers, which backends
+    /// put in the header of the function so that the runtime library does not reference count them.
+    /// Only the first 32 arguments have bits in the mask.
+    pub fn unboxed_mask(&self) -> u32 {
+        (0..self.argc.min(32))
+            .filter(|i| self.unboxed.contains(&IrArgument::Argument(*i)))
+            .fold(0, |mask, i| mask | 1 << i)
+    }
+
+    /// Returns the names of the functions the function refers to, including functions of the
+    /// runtime library, sorted and without duplicates.
+    pub fn dependencies(&self) -> Vec<&str> {
+        let mut deps: Vec<_> = self
+            .ssas
+            .iter()
+            .flat_map(|v| v.args.iter())
+            .filter_map(|v| match v {
+                IrArgument::Function(f) => Some(f.as_str()),
+                _ => None,
+            })
+            .collect();
+        deps.sort_unstable();
+        deps.dedup();
+        deps
+    }
+
     fn get_last_local(&self) -> Option<usize> {
         for ssa in self.ssas.iter().rev() {
             if let Some(l) = ssa.local {
@@ -144,15 +261,107 @@ impl IrFunction {
     }
 }
 
+/// A function of a module that C code can call, declared with `@export`.
+pub struct IrExport {
+    /// The name of the C function, which is the name of the global.
+    pub name: String,
+
+    /// The internal name of the function the C function calls.
+    pub func: String,
+
+    /// The C signature of the C function.
+    pub sig: CSignature,
+}
+
 /// A module in lower level intermediate representation.
 /// TODO: Have a higher level data structure that represents the list of all modules in the code.
 pub struct IrModule {
     /// The list of all functions in the module.
     pub funcs: Vec<IrFunction>,
+
+    /// The list of all static strings in the module.
+    pub strings: Vec<String>,
+
+    /// The names of the files the functions of the module are defined in, which functions and
+    /// instructions refer to by index. The first is the file of the module itself.
+    pub files: Vec<String>,
+
+    /// The C signatures of the external functions called by the module that take or return values
+    /// other than int64_t, by their C names. Other functions called with `CallRuntime` take and
+    /// return int64_t.
+    pub externs: HashMap<String, CSignature>,
+
+    /// The functions of the module that C code can call, in the order they are defined.
+    pub exports: Vec<IrExport>,
+
+    /// Whether an executable built from the module exits with the value `main` returns, which it
+    /// does when `main` is an int. Otherwise it exits with 0.
+    pub exits_with_main: bool,
+}
+
+impl IrModule {
+    /// Returns the function with the given name.
+    pub fn function(&self, name: &str) -> Option<&IrFunction> {
+        self.funcs.iter().find(|f| f.name == name)
+    }
+}
+
+/// The runtime checks lowered code makes, which panic with the location of the operation that
+/// failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checks {
+    /// Whether int arithmetic that overflows panics, rather than wrapping around.
+    pub overflow: bool,
+
+    /// Whether int division and remainder panic when the divisor is zero.
+    pub division: bool,
+}
+
+impl Checks {
+    /// Returns the checks set by the `overflow_checks` and `release` options. Release builds leave
+    /// out the checks that only catch bugs, but overflow checks are kept if they are asked for.
+    pub fn new(options: &CompilerOptions) -> Checks {
+        Checks {
+            overflow: options.overflow_checks,
+            division: !options.release,
+        }
+    }
 }
 
 impl Display for IrModule {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut externs: Vec<_> = self.externs.iter().collect();
+        externs.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, sig) in externs.iter() {
+            let args: Vec<_> = sig.args.iter().map(|v| v.to_string()).collect();
+            writeln!(f, "extern {} {}({})", sig.ret, name, args.join(", "))?;
+        }
+        if !externs.is_empty() {
+            writeln!(f)?;
+        }
+
+        for export in self.exports.iter() {
+            let args: Vec<_> = export.sig.args.iter().map(|v| v.to_string()).collect();
+            writeln!(
+                f,
+                "export {} {}({}) = {}",
+                export.sig.ret,
+                export.name,
+                args.join(", "),
+                export.func
+            )?;
+        }
+        if !self.exports.is_empty() {
+            writeln!(f)?;
+        }
+
+        for (i, s) in self.strings.iter().enumerate() {
+            writeln!(f, "&{} = {:?}", i, s)?;
+        }
+        if !self.strings.is_empty() {
+            writeln!(f)?;
+        }
+
         for func in self.funcs.iter() {
             write!(f, "{}\n\n", func)?;
         }
@@ -160,41 +369,379 @@ impl Display for IrModule {
     }
 }
 
+fn intern_string(strings: &mut Vec<String>, s: &str) -> usize {
+    if let Some(i) = strings.iter().position(|v| v == s) {
+        i
+    } else {
+        strings.push(String::from(s));
+        strings.len() - 1
+    }
+}
+
 fn get_arg_if_applicable<'a>(
-    args_map: &HashMap<String, usize>,
+    args_map: &HashMap<String, IrArgument>,
     sexpr: &'a SExpr,
     map: &HashMap<String, Vec<String>>,
+    strings: &mut Vec<String>,
 ) -> Result<IrArgument, &'a SExpr> {
     match sexpr {
         SExpr::Symbol(_, s) => {
             if let Some(a) = args_map.get(s) {
-                Ok(IrArgument::Argument(*a))
+                Ok(a.clone())
             } else {
-                todo!("symbols that aren't arguments");
+                todo!("symbols that aren't arguments or bindings");
             }
         }
 
-        SExpr::Function(_, f) if map.get(f).unwrap().is_empty() => {
+        // Globals without arguments are values rather than functions, which are called to get them
+        SExpr::Function(m, f)
+            if map.get(f).unwrap().is_empty() && !matches!(m.arity, ArityInfo::Known(0)) =>
+        {
             Ok(IrArgument::Function(f.clone()))
         }
 
+        SExpr::String(_, s) => Ok(IrArgument::String(intern_string(strings, s))),
+
+        SExpr::Int(_, n) => Ok(IrArgument::Int(*n)),
+
+        SExpr::Float(_, n) => Ok(IrArgument::Int(n.to_bits() as i64)),
+
+        SExpr::Char(_, c) => Ok(IrArgument::Char(*c)),
+
         _ => Err(sexpr),
     }
 }
 
+/// Returns the runtime function that applies the operator with the given name to operands of the
+/// given type, which is named after the type and the operator, such as `closey_int_add`.
+fn operator_function(op: &str, _type: &Type) -> String {
+    let int_name;
+    let type_name = match _type {
+        Type::Int | Type::SizedInt(_) => {
+            int_name = int_type_name(IntSize::of(_type).unwrap());
+            &int_name
+        }
+        Type::Float => "float",
+        Type::Bool => "bool",
+        Type::Char => "char",
+        Type::Symbol(s) if s == "String" => "string",
+        _ => unreachable!("the type checker only allows operators on primitives and strings"),
+    };
+    format!("closey_{}_{}", type_name, op)
+}
+
+/// Returns the name the runtime library gives an int type in the names of its functions, such as
+/// `int` or `uint8`.
+fn int_type_name(size: IntSize) -> String {
+    size.name().to_lowercase()
+}
+
+/// The kinds of functions derived for types while lowering, which are generated once for each type
+/// they are used with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Derived {
+    /// Converts a value into the string the REPL shows it as.
+    Show,
+
+    /// Compares two values by their contents.
+    Eq,
+}
+
+impl Derived {
+    /// Returns the name of the function of this kind derived for a type.
+    fn name(self, _type: &Type) -> String {
+        let prefix = match self {
+            Derived::Show => SHOW_PREFIX,
+            Derived::Eq => EQ_PREFIX,
+        };
+        format!("{}{}", prefix, pretty::type_to_string(_type))
+    }
+
+    /// Returns the prefix of the runtime functions that functions of this kind call for arrays,
+    /// maps, results, options, and declared types, which is followed by the kind of value.
+    fn runtime_prefix(self) -> &'static str {
+        match self {
+            Derived::Show => "closey_show_",
+            Derived::Eq => "closey_eq_",
+        }
+    }
+}
+
+/// Returns true if a function was derived for a type while lowering rather than lowered from source
+/// code.
+pub fn is_derived(name: &str) -> bool {
+    name.starts_with(SHOW_PREFIX) || name.starts_with(EQ_PREFIX)
+}
+
+/// Returns true if values of a type are compared by a derived equality function rather than by the
+/// runtime function of an operator.
+fn compared_by_contents(_type: &Type) -> bool {
+    !_type.is_primitive() && !matches!(_type, Type::Symbol(s) if s == "String")
+}
+
+/// Adds a type to the list of types to derive functions of a kind for, unless it is already in it.
+fn add_derived(derived: &mut Vec<(Derived, TypeRc)>, kind: Derived, _type: &TypeRc) {
+    if !derived.iter().any(|(k, t)| *k == kind && t == _type) {
+        derived.push((kind, _type.clone()));
+    }
+}
+
+/// Adds the types of the values shown by intrinsics and compared by their contents in an expression
+/// to the list of types to derive functions for.
+fn derived_types(sexpr: &SExpr, module: &ir::IrModule, derived: &mut Vec<(Derived, TypeRc)>) {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::String(_, _)
+        | SExpr::Function(_, _) => (),
+
+        SExpr::ExternalFunc(_, name, args) => {
+            let shown = intrinsics::get(name)
+                .filter(|_| !module.externals.contains_key(name))
+                .and_then(|v| v.shown_arg);
+            if let Some(i) = shown {
+                add_derived(derived, Derived::Show, &args[i].get_metadata()._type);
+            }
+
+            for arg in args.iter() {
+                derived_types(arg, module, derived);
+            }
+        }
+
+        SExpr::Infix(_, op, a, b) => {
+            let _type = &a.get_metadata()._type;
+            if matches!(op, BinOp::EQ | BinOp::NEQ) && compared_by_contents(_type) {
+                add_derived(derived, Derived::Eq, _type);
+            }
+
+            derived_types(a, module, derived);
+            derived_types(b, module, derived);
+        }
+
+        SExpr::Chain(_, a, b) => {
+            derived_types(a, module, derived);
+            derived_types(b, module, derived);
+        }
+
+        SExpr::Application(_, func, args) => {
+            derived_types(func, module, derived);
+            for arg in args.iter() {
+                derived_types(arg, module, derived);
+            }
+        }
+
+        SExpr::Prefix(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Assign(_, _, v) => derived_types(v, module, derived),
+
+        SExpr::With(_, assigns, v) => {
+            for assign in assigns.iter() {
+                derived_types(assign, module, derived);
+            }
+            derived_types(v, module, derived);
+        }
+
+        SExpr::Match(_, v, arms) => {
+            derived_types(v, module, derived);
+            for (_, arm, _) in arms.iter() {
+                derived_types(arm, module, derived);
+            }
+        }
+    }
+}
+
+/// Derives a function of a kind for a type by calling the runtime library. Show functions convert
+/// a value into the string the REPL shows it as, and equality functions compare two values by
+/// their contents. Functions of the same kind for the types of the values inside it, such as the
+/// items of arrays and the fields of declared types, are passed to the runtime, and their types are
+/// added to the list of types to derive functions for.
+fn derive_function(
+    kind: Derived,
+    _type: &Type,
+    module: &ir::IrModule,
+    strings: &mut Vec<String>,
+    derived: &mut Vec<(Derived, TypeRc)>,
+) -> IrFunction {
+    let argc = match kind {
+        Derived::Show => 1,
+        Derived::Eq => 2,
+    };
+    let mut f = IrFunction {
+        name: kind.name(_type),
+        file: 0,
+        span: 0..0,
+        argc,
+        debug_args: vec![],
+        ssas: vec![],
+        unboxed: HashSet::new(),
+        pure: HashSet::new(),
+    };
+    if _type.is_primitive() {
+        f.unboxed.extend((0..argc).map(IrArgument::Argument));
+    }
+
+    let mut inner = |v: &TypeRc| {
+        add_derived(derived, kind, v);
+        IrArgument::Function(kind.name(v))
+    };
+    let runtime = |name: &str| format!("{}{}", kind.runtime_prefix(), name);
+
+    // Values are passed to the runtime function followed by the derived functions it calls, except
+    // those that need no runtime function
+    let mut fields = None;
+    let call = match (kind, _type) {
+        (Derived::Show, Type::Int) => Some((runtime("int"), vec![])),
+        (Derived::Show, Type::SizedInt(size)) if size.signed => Some((runtime("int"), vec![])),
+        (Derived::Show, Type::SizedInt(_)) => Some((runtime("uint"), vec![])),
+        (Derived::Show, Type::Float) => Some((runtime("float"), vec![])),
+        (Derived::Show, Type::Bool) => Some((runtime("bool"), vec![])),
+        (Derived::Show, Type::Char) => Some((runtime("char"), vec![])),
+        (Derived::Show, Type::Symbol(s)) if s == "String" => Some((runtime("string"), vec![])),
+
+        // Primitives and strings inside other values are compared by the runtime function of `==`
+        (Derived::Eq, t) if !compared_by_contents(t) => Some((operator_function("eq", t), vec![])),
+
+        (_, Type::Array(a)) => Some((runtime("array"), vec![inner(a)])),
+        (_, Type::Map(k, v)) => Some((runtime("map"), vec![inner(k), inner(v)])),
+        (_, Type::Result(v)) => Some((runtime("result"), vec![inner(v)])),
+        (_, Type::Option(v)) => Some((runtime("option"), vec![inner(v)])),
+
+        // Values of declared types are handled by the runtime from the names of the constructors
+        // and the derived functions of their fields, collected into an array in the same order
+        (_, Type::Symbol(s)) if module.constructors().any(|c| &c.type_name == s) => {
+            let mut names = String::new();
+            for c in module.constructors().filter(|c| &c.type_name == s) {
+                names.push_str(&format!("{}{},", c.field_types.len(), c.name));
+                for field in c.field_types.iter() {
+                    let local = f.get_next_local();
+                    f.ssas.push(IrSsa {
+                        local: Some(local),
+                        local_lifetime: 0,
+                        local_register: 0,
+                        span: f.span.clone(),
+                        file: f.file,
+                        instr: IrInstruction::CallRuntime,
+                        args: match fields {
+                            Some(a) => vec![
+                                IrArgument::Function(String::from("closey_array_push")),
+                                IrArgument::Local(a),
+                                inner(field),
+                            ],
+                            None => vec![
+                                IrArgument::Function(String::from("closey_array_of")),
+                                inner(field),
+                            ],
+                        },
+                    });
+                    fields = Some(local);
+                }
+            }
+
+            let names = IrArgument::String(intern_string(strings, &names));
+            let fields = fields.map(IrArgument::Local).unwrap_or(IrArgument::Int(0));
+            Some((runtime("data"), vec![names, fields]))
+        }
+
+        _ => None,
+    };
+
+    let local = f.get_next_local();
+    match call {
+        Some((runtime, extra)) => {
+            use std::iter::once;
+            f.ssas.push(IrSsa {
+                local: Some(local),
+                local_lifetime: 0,
+                local_register: 0,
+                span: f.span.clone(),
+                file: f.file,
+                instr: IrInstruction::CallRuntime,
+                args: once(IrArgument::Function(runtime))
+                    .chain((0..argc).map(IrArgument::Argument))
+                    .chain(extra)
+                    .collect(),
+            });
+        }
+
+        // Functions, threads, tasks, units, and values of generic types are shown the same
+        // whatever they are. Units are always equal, and the type checker does not allow comparing
+        // the others
+        None => {
+            let value = match (kind, _type) {
+                (Derived::Show, Type::Func(_, _)) => "<function>",
+                (Derived::Show, Type::Thread(_)) => "<thread>",
+                (Derived::Show, Type::Task(_)) => "<task>",
+                (Derived::Show, Type::Symbol(s)) if s == "Unit" => "()",
+                (Derived::Show, _) => "<value>",
+                (Derived::Eq, _) => "",
+            };
+            f.ssas.push(IrSsa {
+                local: Some(local),
+                local_lifetime: 0,
+                local_register: 0,
+                span: f.span.clone(),
+                file: f.file,
+                instr: IrInstruction::Load,
+                args: vec![match kind {
+                    Derived::Show => IrArgument::String(intern_string(strings, value)),
+                    Derived::Eq => IrArgument::Int(1),
+                }],
+            });
+        }
+    }
+    if kind == Derived::Eq {
+        f.unboxed.insert(IrArgument::Local(local));
+    }
+
+    // The array of derived functions is not kept by the runtime
+    if let Some(a) = fields {
+        f.ssas.push(IrSsa {
+            local: None,
+            local_lifetime: 0,
+            local_register: 0,
+            span: f.span.clone(),
+            file: f.file,
+            instr: IrInstruction::RcFuncFree,
+            args: vec![IrArgument::Local(a)],
+        });
+    }
+
+    f.ssas.push(IrSsa {
+        local: None,
+        local_lifetime: 0,
+        local_register: 0,
+        instr: IrInstruction::Ret,
+        span: f.span.clone(),
+        file: f.file,
+        args: vec![IrArgument::Local(local)],
+    });
+    f
+}
+
 fn conversion_helper(
-    args_map: &HashMap<String, usize>,
+    args_map: &HashMap<String, IrArgument>,
     func: &mut IrFunction,
     sexpr: &SExpr,
     map: &HashMap<String, Vec<String>>,
+    module: &ir::IrModule,
+    strings: &mut Vec<String>,
+    checks: Checks,
 ) -> Option<usize> {
-    match get_arg_if_applicable(args_map, sexpr, map) {
+    let span = sexpr.get_metadata().loc.span.clone();
+    let local = match get_arg_if_applicable(args_map, sexpr, map, strings) {
         Ok(v) => {
             let local = Some(func.get_next_local());
             func.ssas.push(IrSsa {
                 local,
                 local_lifetime: 0,
                 local_register: 0,
+                span: span.clone(),
+                file: func.file,
                 instr: IrInstruction::Load,
                 args: vec![v],
             });
@@ -204,9 +751,407 @@ fn conversion_helper(
         Err(SExpr::Empty(_)) => todo!(),
         Err(SExpr::TypeAlias(_, _)) => todo!(),
 
-        Err(SExpr::ExternalFunc(_, _, _)) => todo!(),
+        Err(SExpr::ExternalFunc(_, name, a))
+            if module.constructors.contains_key(name) && !module.externals.contains_key(name) =>
+        {
+            // Values of declared types are built by the runtime function for their number of
+            // fields, which takes the tag of the constructor followed by the fields
+            let constructor = &module.constructors[name];
+            if a.len() > CONSTRUCTOR_MAX_FIELDS {
+                panic!(
+                    "constructors can have at most {} fields, but {} has {}",
+                    CONSTRUCTOR_MAX_FIELDS,
+                    name,
+                    a.len()
+                );
+            }
+
+            let args: Vec<_> = a
+                .iter()
+                .map(|a| match get_arg_if_applicable(args_map, a, map, strings) {
+                    Ok(v) => v,
+                    Err(e) => IrArgument::Local(
+                        conversion_helper(args_map, func, e, map, module, strings, checks).unwrap(),
+                    ),
+                })
+                .collect();
+
+            use std::iter::once;
+            let local = Some(func.get_next_local());
+            func.ssas.push(IrSsa {
+                local,
+                local_lifetime: 0,
+                local_register: 0,
+                span: span.clone(),
+                file: func.file,
+                instr: IrInstruction::CallRuntime,
+                args: once(IrArgument::Function(format!(
+                    "{}{}",
+                    CONSTRUCTOR_PREFIX,
+                    a.len()
+                )))
+                .chain(once(IrArgument::Int(constructor.tag as i64)))
+                .chain(args)
+                .collect(),
+            });
+            local
+        }
+
+        Err(SExpr::ExternalFunc(m, name, a)) => {
+            // Intrinsics without arguments, such as `none`, are called wherever they are used, and
+            // the others are wrapped in functions when they are not applied to all of their arguments
+            if a.is_empty() && !matches!(m.arity, ArityInfo::Known(0)) {
+                unreachable!("external functions are applied to all of their arguments");
+            }
+
+            let mut args: Vec<_> = a
+                .iter()
+                .map(|a| match get_arg_if_applicable(args_map, a, map, strings) {
+                    Ok(v) => v,
+                    Err(e) => IrArgument::Local(
+                        conversion_helper(args_map, func, e, map, module, strings, checks).unwrap(),
+                    ),
+                })
+                .collect();
+
+            use std::iter::once;
+            let mut callbacks = vec![];
+            let runtime_name = if let Some(ext) = module.externals.get(name) {
+                // Closures passed to external functions are wrapped in C callable callbacks that
+                // hold a reference to the closure until the external function returns
+                for (arg, _type) in args.iter_mut().zip(ext.arg_types.iter()) {
+                    if let Some((fargs, _)) = _type.as_function() {
+                        if fargs.len() > CALLBACK_MAX_ARGS {
+                            panic!(
+                                "callbacks passed to {} can take at most {} arguments",
+                                name, CALLBACK_MAX_ARGS
+                            );
+                        }
+
+                        let local = func.get_next_local();
+                        func.ssas.push(IrSsa {
+                            local: Some(local),
+                            local_lifetime: 0,
+                            local_register: 0,
+                            span: span.clone(),
+                            file: func.file,
+                            instr: IrInstruction::CallRuntime,
+                            args: vec![
+                                IrArgument::Function(format!(
+                                    "closey_callback_new_{}",
+                                    fargs.len()
+                                )),
+                                arg.clone(),
+                            ],
+                        });
+                        *arg = IrArgument::Local(local);
+                        callbacks.push(local);
+                    }
+                }
+
+                ext.extern_name.clone()
+            } else {
+                let intrinsic = intrinsics::get(name).unwrap();
+                if intrinsic.located {
+                    let loc = module.location_string(&m.loc);
+                    args.push(IrArgument::String(intern_string(strings, &loc)));
+                }
+                if let Some(i) = intrinsic.quoted_arg {
+                    let code = module.source_string(&a[i].get_metadata().loc);
+                    args.push(IrArgument::String(intern_string(strings, &code)));
+                }
+
+                // Shown values are converted into strings by the show function derived for their
+                // type, which is generated once for the module
+                if let Some(i) = intrinsic.shown_arg {
+                    let local = func.get_next_local();
+                    func.ssas.push(IrSsa {
+                        local: Some(local),
+                        local_lifetime: 0,
+                        local_register: 0,
+                        span: span.clone(),
+                        file: func.file,
+                        instr: IrInstruction::Call(true),
+                        args: vec![
+                            IrArgument::Function(Derived::Show.name(&a[i].get_metadata()._type)),
+                            args[i].clone(),
+                        ],
+                    });
+                    args[i] = IrArgument::Local(local);
+                }
+
+                match intrinsic.lowering {
+                    // Intrinsics such as `show` are the string their argument is shown as
+                    Lowering::Shown => match args[intrinsic.shown_arg.unwrap()] {
+                        IrArgument::Local(l) => return Some(l),
+                        _ => unreachable!("shown arguments are always locals"),
+                    },
+
+                    // Intrinsics such as `true` are a constant loaded into a local
+                    Lowering::Constant(n) => {
+                        let local = Some(func.get_next_local());
+                        func.ssas.push(IrSsa {
+                            local,
+                            local_lifetime: 0,
+                            local_register: 0,
+                            span,
+                            file: func.file,
+                            instr: IrInstruction::Load,
+                            args: vec![IrArgument::Int(n)],
+                        });
+                        return local;
+                    }
+
+                    Lowering::Runtime(name) => {
+                        let mut runtime_name = String::from(name);
+                        if let Some(key) = intrinsic.key_arg {
+                            let key = &a[key].get_metadata()._type;
+                            runtime_name.push_str(intrinsics::key_suffix(key));
+                        }
+                        runtime_name
+                    }
+                }
+            };
+
+            let local = Some(func.get_next_local());
+            func.ssas.push(IrSsa {
+                local,
+                local_lifetime: 0,
+                local_register: 0,
+                span: span.clone(),
+                file: func.file,
+                instr: IrInstruction::CallRuntime,
+                args: once(IrArgument::Function(runtime_name))
+                    .chain(args.into_iter())
+                    .collect(),
+            });
+
+            for callback in callbacks {
+                func.ssas.push(IrSsa {
+                    local: None,
+                    local_lifetime: 0,
+                    local_register: 0,
+                    span: span.clone(),
+                    file: func.file,
+                    instr: IrInstruction::CallRuntime,
+                    args: vec![
+                        IrArgument::Function(String::from("closey_callback_free")),
+                        IrArgument::Local(callback),
+                    ],
+                });
+            }
+            local
+        }
+        Err(SExpr::Prefix(_, op, v)) => {
+            let name = operator_function(op.name(), &v.get_metadata()._type);
+            let value = match get_arg_if_applicable(args_map, v, map, strings) {
+                Ok(v) => v,
+                Err(e) => IrArgument::Local(
+                    conversion_helper(args_map, func, e, map, module, strings, checks).unwrap(),
+                ),
+            };
+
+            let local = Some(func.get_next_local());
+            func.ssas.push(IrSsa {
+                local,
+                local_lifetime: 0,
+                local_register: 0,
+                span: span.clone(),
+                file: func.file,
+                instr: IrInstruction::CallRuntime,
+                args: vec![IrArgument::Function(name), value],
+            });
+            local
+        }
+
+        Err(SExpr::Infix(_, op, l, r))
+            if matches!(op, BinOp::EQ | BinOp::NEQ)
+                && compared_by_contents(&l.get_metadata()._type) =>
+        {
+            // Values other than primitives and strings are compared by the equality function
+            // derived for their type, which is generated once for the module
+            let mut args = vec![IrArgument::Function(
+                Derived::Eq.name(&l.get_metadata()._type),
+            )];
+            for v in [l, r] {
+                args.push(match get_arg_if_applicable(args_map, v, map, strings) {
+                    Ok(v) => v,
+                    Err(e) => IrArgument::Local(
+                        conversion_helper(args_map, func, e, map, module, strings, checks).unwrap(),
+                    ),
+                });
+            }
+
+            let mut local = func.get_next_local();
+            func.ssas.push(IrSsa {
+                local: Some(local),
+                local_lifetime: 0,
+                local_register: 0,
+                span: span.clone(),
+                file: func.file,
+                instr: IrInstruction::Call(true),
+                args,
+            });
+            func.unboxed.insert(IrArgument::Local(local));
+
+            if let BinOp::NEQ = op {
+                local += 1;
+                func.ssas.push(IrSsa {
+                    local: Some(local),
+                    local_lifetime: 0,
+                    local_register: 0,
+                    span: span.clone(),
+                    file: func.file,
+                    instr: IrInstruction::CallRuntime,
+                    args: vec![
+                        IrArgument::Function(String::from("closey_bool_not")),
+                        IrArgument::Local(local - 1),
+                    ],
+                });
+            }
+            Some(local)
+        }
+
+        Err(SExpr::Infix(m, op, l, r)) => {
+            // Checked int arithmetic panics on overflow, reporting where it happened, and release
+            // builds divide without checking for zero
+            let int = IntSize::of(&l.get_metadata()._type).is_some();
+            let checked = checks.overflow
+                && int
+                && matches!(op, BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div);
+            let division = int && matches!(op, BinOp::Div | BinOp::Mod);
+            let mut name = operator_function(op.name(), &l.get_metadata()._type);
+            if checked {
+                name.push_str("_checked");
+            } else if division && !checks.division {
+                name.push_str("_unchecked");
+            }
+
+            let mut args = vec![IrArgument::Function(name)];
+            for v in [l, r] {
+                args.push(match get_arg_if_applicable(args_map, v, map, strings) {
+                    Ok(v) => v,
+                    Err(e) => IrArgument::Local(
+                        conversion_helper(args_map, func, e, map, module, strings, checks).unwrap(),
+                    ),
+                });
+            }
+
+            // Integer division panics on division by zero, reporting where it happened
+            if checked || division && checks.division {
+                let loc = module.location_string(&m.loc);
+                args.push(IrArgument::String(intern_string(strings, &loc)));
+            }
+
+            let local = Some(func.get_next_local());
+            func.ssas.push(IrSsa {
+                local,
+                local_lifetime: 0,
+                local_register: 0,
+                span: span.clone(),
+                file: func.file,
+                instr: IrInstruction::CallRuntime,
+                args,
+            });
+            local
+        }
+
         Err(SExpr::Chain(_, _, _)) => todo!(),
 
+        Err(SExpr::As(m, v)) => {
+            let value = match get_arg_if_applicable(args_map, v, map, strings) {
+                Ok(v) => v,
+                Err(e) => IrArgument::Local(
+                    conversion_helper(args_map, func, e, map, module, strings, checks).unwrap(),
+                ),
+            };
+
+            // Ints are checked to be unicode scalar values when they are converted into chars,
+            // numbers are rounded or truncated by the runtime library, ints are wrapped around into
+            // the range of narrower int types, and every other conversion keeps the value as it is
+            let convert = |name: &str, value| {
+                (
+                    IrInstruction::CallRuntime,
+                    vec![IrArgument::Function(String::from(name)), value],
+                )
+            };
+            let from = IntSize::of(&v.get_metadata()._type);
+            let to = IntSize::of(&m._type);
+
+            // Chars are wrapped around the same way as unsigned 32 bit ints
+            let source = match &*v.get_metadata()._type {
+                Type::Char => Some(IntSize::new(false, 32)),
+                _ => from,
+            };
+            let wraps = match (source, to) {
+                (Some(f), Some(t)) => t.bits < 64 && !f.widens_to(&t),
+                _ => false,
+            };
+
+            let (instr, args) = match (&*v.get_metadata()._type, &*m._type) {
+                // Unsigned 64 bit ints are the only ints not stored as their value
+                (Type::SizedInt(size), Type::Float) if !size.signed && size.bits == 64 => {
+                    convert("closey_uint64_to_float", value)
+                }
+                (_, Type::Float) if from.is_some() => convert("closey_int_to_float", value),
+                (Type::Float, _) if to.is_some() => {
+                    let name = format!("closey_float_to_{}", int_type_name(to.unwrap()));
+                    convert(&name, value)
+                }
+                (Type::Bool, _) if to.is_some() => convert("closey_bool_to_int", value),
+
+                (_, Type::Char) if from.is_some() => {
+                    let loc = module.location_string(&m.loc);
+                    (
+                        IrInstruction::CallRuntime,
+                        vec![
+                            IrArgument::Function(String::from("closey_int_to_char")),
+                            value,
+                            IrArgument::String(intern_string(strings, &loc)),
+                        ],
+                    )
+                }
+                _ if wraps => {
+                    let name = format!("closey_int_to_{}", int_type_name(to.unwrap()));
+                    convert(&name, value)
+                }
+                _ => (IrInstruction::Load, vec![value]),
+            };
+
+            let local = Some(func.get_next_local());
+            func.ssas.push(IrSsa {
+                local,
+                local_lifetime: 0,
+                local_register: 0,
+                span: span.clone(),
+                file: func.file,
+                instr,
+                args,
+            });
+            local
+        }
+
+        // Ascriptions only change the type the value is checked to have
+        Err(SExpr::Ascribe(_, v)) => {
+            conversion_helper(args_map, func, v, map, module, strings, checks)
+        }
+
+        Err(SExpr::Function(m, f))
+            if map.get(f).unwrap().is_empty() && matches!(m.arity, ArityInfo::Known(0)) =>
+        {
+            let local = Some(func.get_next_local());
+            func.ssas.push(IrSsa {
+                local,
+                local_lifetime: 0,
+                local_register: 0,
+                span: span.clone(),
+                file: func.file,
+                instr: IrInstruction::Call(true),
+                args: vec![IrArgument::Function(f.clone())],
+            });
+            local
+        }
+
         Err(SExpr::Function(_, f)) => {
             use std::iter::once;
             let local = Some(func.get_next_local());
@@ -215,6 +1160,7 @@ fn conversion_helper(
                     args_map,
                     &SExpr::Symbol(SExprMetadata::empty(), v.clone()),
                     map,
+                    strings,
                 )
                 .unwrap()
             });
@@ -222,6 +1168,8 @@ fn conversion_helper(
                 local,
                 local_lifetime: 0,
                 local_register: 0,
+                span: span.clone(),
+                file: func.file,
                 instr: IrInstruction::Apply,
                 args: once(IrArgument::Function(f.clone())).chain(args).collect(),
             });
@@ -229,16 +1177,39 @@ fn conversion_helper(
         }
 
         Err(SExpr::Application(m, f, a)) => {
-            let f = match get_arg_if_applicable(args_map, &**f, map) {
-                Ok(v) => v,
-                Err(e) => IrArgument::Local(conversion_helper(args_map, func, e, map).unwrap()),
+            // Saturated applications of closures call their function directly with the closed over
+            // values before the arguments, instead of building the closure and calling it
+            let (f, captured) = match &**f {
+                SExpr::Function(_, g) if matches!(m.arity, ArityInfo::Known(0)) => {
+                    let captured = map[g]
+                        .iter()
+                        .map(|v| {
+                            let v = SExpr::Symbol(SExprMetadata::empty(), v.clone());
+                            get_arg_if_applicable(args_map, &v, map, strings).unwrap()
+                        })
+                        .collect();
+                    (IrArgument::Function(g.clone()), captured)
+                }
+
+                _ => {
+                    let f = match get_arg_if_applicable(args_map, f, map, strings) {
+                        Ok(v) => v,
+                        Err(e) => IrArgument::Local(
+                            conversion_helper(args_map, func, e, map, module, strings, checks)
+                                .unwrap(),
+                        ),
+                    };
+                    (f, vec![])
+                }
             };
 
             let args: Vec<_> = a
                 .iter()
-                .map(|a| match get_arg_if_applicable(args_map, a, map) {
+                .map(|a| match get_arg_if_applicable(args_map, a, map, strings) {
                     Ok(v) => v,
-                    Err(e) => IrArgument::Local(conversion_helper(args_map, func, e, map).unwrap()),
+                    Err(e) => IrArgument::Local(
+                        conversion_helper(args_map, func, e, map, module, strings, checks).unwrap(),
+                    ),
                 })
                 .collect();
 
@@ -249,31 +1220,168 @@ fn conversion_helper(
                     local,
                     local_lifetime: 0,
                     local_register: 0,
+                    span: span.clone(),
+                    file: func.file,
                     instr: IrInstruction::Apply,
-                    args: once(f).chain(args.into_iter()).collect(),
+                    args: once(f).chain(captured).chain(args).collect(),
                 });
             } else {
+                let instr = match m.arity {
+                    ArityInfo::Known(_) if m.tailrec => IrInstruction::TailCall,
+                    ArityInfo::Known(_) => IrInstruction::Call(true),
+                    ArityInfo::Unknown => IrInstruction::Call(false),
+                };
                 func.ssas.push(IrSsa {
                     local,
                     local_lifetime: 0,
                     local_register: 0,
-                    instr: IrInstruction::Call(matches!(m.arity, ArityInfo::Known(_))),
-                    args: once(f).chain(args.into_iter()).collect(),
+                    span: span.clone(),
+                    file: func.file,
+                    instr,
+                    args: once(f).chain(captured).chain(args).collect(),
                 });
             }
 
             local
         }
 
-        Err(SExpr::Assign(_, _, _)) => todo!(),
-        Err(SExpr::With(_, _, _)) => todo!(),
-        Err(SExpr::Match(_, _, _)) => todo!(),
+        Err(SExpr::Assign(_, _, _)) => {
+            unreachable!("bindings are lowered by the `let` they are in")
+        }
+        Err(SExpr::With(_, assigns, body)) => {
+            // Bindings are locals, or the arguments and literals they are bound to, which the body
+            // and the bindings after them refer to by name
+            let mut scope = args_map.clone();
+            for assign in assigns {
+                if let SExpr::Assign(_, name, value) = assign {
+                    let value = match get_arg_if_applicable(&scope, value, map, strings) {
+                        Ok(v) => v,
+                        Err(e) => IrArgument::Local(
+                            conversion_helper(&scope, func, e, map, module, strings, checks)
+                                .unwrap(),
+                        ),
+                    };
+                    scope.insert(name.clone(), value);
+                }
+            }
 
-        Err(SExpr::Symbol(_, _)) => unreachable!(),
+            conversion_helper(&scope, func, body, map, module, strings, checks)
+        }
+        Err(SExpr::Match(m, v, arms)) => {
+            let value = match get_arg_if_applicable(args_map, v, map, strings) {
+                Ok(v) => v,
+                Err(e) => IrArgument::Local(
+                    conversion_helper(args_map, func, e, map, module, strings, checks).unwrap(),
+                ),
+            };
+
+            // The arms are collected into an array, and the runtime library calls the first arm
+            // whose pattern matches. Arrays do not count references to their items, so closures
+            // over the bindings of the enclosing arms are kept alive until the match is done
+            let mut patterns = String::new();
+            let mut array = None;
+            let mut closures = vec![];
+            for (pattern, arm, _) in arms {
+                pattern.encode(module, &mut patterns);
+                let arm = match get_arg_if_applicable(args_map, arm, map, strings) {
+                    Ok(v) => v,
+                    Err(e) => IrArgument::Local(
+                        conversion_helper(args_map, func, e, map, module, strings, checks).unwrap(),
+                    ),
+                };
+                if is_counted(func, &arm) {
+                    func.ssas.push(IrSsa {
+                        local: None,
+                        local_lifetime: 0,
+                        local_register: 0,
+                        span: span.clone(),
+                        file: func.file,
+                        instr: IrInstruction::RcInc,
+                        args: vec![arm.clone()],
+                    });
+                    closures.push(arm.clone());
+                }
+
+                let local = func.get_next_local();
+                func.ssas.push(IrSsa {
+                    local: Some(local),
+                    local_lifetime: 0,
+                    local_register: 0,
+                    span: span.clone(),
+                    file: func.file,
+                    instr: IrInstruction::CallRuntime,
+                    args: match array {
+                        Some(a) => vec![
+                            IrArgument::Function(String::from("closey_array_push")),
+                            IrArgument::Local(a),
+                            arm,
+                        ],
+                        None => vec![IrArgument::Function(String::from("closey_array_of")), arm],
+                    },
+                });
+                array = Some(local);
+            }
+
+            let loc = module.location_string(&m.loc);
+            let local = Some(func.get_next_local());
+            func.ssas.push(IrSsa {
+                local,
+                local_lifetime: 0,
+                local_register: 0,
+                span: span.clone(),
+                file: func.file,
+                instr: IrInstruction::CallRuntime,
+                args: vec![
+                    IrArgument::Function(String::from("closey_match")),
+                    value,
+                    IrArgument::String(intern_string(strings, &patterns)),
+                    IrArgument::Local(array.unwrap()),
+                    IrArgument::String(intern_string(strings, &loc)),
+                ],
+            });
+            for closure in closures {
+                func.ssas.push(IrSsa {
+                    local: None,
+                    local_lifetime: 0,
+                    local_register: 0,
+                    span: span.clone(),
+                    file: func.file,
+                    instr: IrInstruction::RcFuncFree,
+                    args: vec![closure],
+                });
+            }
+            local
+        }
+
+        Err(SExpr::Symbol(_, _))
+        | Err(SExpr::Int(_, _))
+        | Err(SExpr::Float(_, _))
+        | Err(SExpr::Char(_, _))
+        | Err(SExpr::String(_, _)) => unreachable!(),
+    };
+
+    if let Some(l) = local {
+        if sexpr.get_metadata()._type.is_primitive() {
+            func.unboxed.insert(IrArgument::Local(l));
+        }
+
+        // Applications of Closey functions are not reused, since functions are not checked to be
+        // pure
+        let pure = match sexpr {
+            SExpr::Prefix(_, _, _) | SExpr::Infix(_, _, _, _) | SExpr::As(_, _) => true,
+            SExpr::ExternalFunc(m, _, _) => !m.impure,
+            _ => false,
+        };
+        if pure {
+            func.pure.insert(l);
+        }
     }
+    local
 }
 
-fn calculate_lifetimes(func: &mut IrFunction) {
+/// Sets the lifetime of every local to the number of instructions from the one that assigns it to
+/// its last use, or 0 if it is never used.
+pub fn calculate_lifetimes(func: &mut IrFunction) {
     let mut iter = func.ssas.iter_mut();
     let mut i = 0;
     while let Some(ssa) = iter.next() {
@@ -281,6 +1389,7 @@ fn calculate_lifetimes(func: &mut IrFunction) {
             continue;
         }
         let local = ssa.local.unwrap();
+        ssa.local_lifetime = 0;
 
         let mut j = i + 1;
         for next in iter.as_slice() {
@@ -300,20 +1409,268 @@ fn calculate_lifetimes(func: &mut IrFunction) {
     }
 }
 
+/// Removes the loads and applications whose locals are never used, which have no effects, and
+/// renumbers the remaining locals so that they stay contiguous. Lifetimes must have been calculated,
+/// and are recalculated afterwards.
+fn eliminate_dead_code(func: &mut IrFunction) {
+    // Removing an instruction can leave the locals it used unused as well
+    loop {
+        let before = func.ssas.len();
+        func.ssas.retain(|ssa| {
+            !(matches!(ssa.instr, IrInstruction::Load | IrInstruction::Apply)
+                && ssa.local.is_some()
+                && ssa.local_lifetime == 0)
+        });
+
+        if func.ssas.len() == before {
+            break;
+        }
+        trace!(removed = before - func.ssas.len(), "eliminated dead code");
+        calculate_lifetimes(func);
+    }
+
+    let mut locals = HashMap::new();
+    for ssa in func.ssas.iter_mut() {
+        for arg in ssa.args.iter_mut() {
+            if let IrArgument::Local(l) = arg {
+                *l = locals[l];
+            }
+        }
+
+        if let Some(l) = ssa.local {
+            let new = locals.len();
+            locals.insert(l, new);
+            ssa.local = Some(new);
+        }
+    }
+
+    func.unboxed = func
+        .unboxed
+        .iter()
+        .filter_map(|v| match v {
+            IrArgument::Local(l) => locals.get(l).map(|l| IrArgument::Local(*l)),
+            _ => Some(v.clone()),
+        })
+        .collect();
+    func.pure = func
+        .pure
+        .iter()
+        .filter_map(|l| locals.get(l).copied())
+        .collect();
+}
+
+/// Returns the number of times each local is used as an argument.
+fn count_uses(func: &IrFunction) -> HashMap<usize, usize> {
+    let mut uses = HashMap::new();
+    for arg in func.ssas.iter().flat_map(|v| v.args.iter()) {
+        if let IrArgument::Local(l) = arg {
+            *uses.entry(*l).or_insert(0) += 1;
+        }
+    }
+    uses
+}
+
+/// Passes the values of loads directly to the instruction after them if it is their only use, and
+/// merges applications of closures made by applying a function into one application of the
+/// function. The instructions left unused are removed by dead code elimination, so lifetimes must
+/// have been calculated, and it must run afterwards.
+fn peephole_optimise(func: &mut IrFunction) {
+    let mut uses = count_uses(func);
+    let mut applied: HashMap<usize, Vec<IrArgument>> = HashMap::new();
+    for i in 0..func.ssas.len() {
+        if let (IrInstruction::Apply, Some(IrArgument::Local(l))) =
+            (func.ssas[i].instr, func.ssas[i].args.first())
+        {
+            // The closure applied to is not kept, so its arguments are moved to this application
+            if uses[l] == 1 {
+                if let Some(args) = applied.get(l) {
+                    let l = *l;
+                    func.ssas[i].args.splice(0..1, args.iter().cloned());
+                    uses.insert(l, 0);
+                }
+            }
+        }
+
+        let ssa = &func.ssas[i];
+        match (ssa.instr, ssa.local, ssa.args.first()) {
+            (IrInstruction::Apply, Some(l), Some(IrArgument::Function(_))) => {
+                applied.insert(l, ssa.args.clone());
+            }
+
+            // Backends only call or apply functions by their names
+            (IrInstruction::Load, Some(l), Some(value)) if uses.get(&l) == Some(&1) => {
+                let value = value.clone();
+                let next = match func.ssas.get_mut(i + 1) {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                let callee = matches!(
+                    next.instr,
+                    IrInstruction::Apply
+                        | IrInstruction::Call(_)
+                        | IrInstruction::TailCall
+                        | IrInstruction::CallRuntime
+                );
+                let position = next.args.iter().position(|v| *v == IrArgument::Local(l));
+                match position {
+                    Some(0) if callee && !matches!(value, IrArgument::Function(_)) => (),
+                    Some(p) => {
+                        next.args[p] = value;
+                        uses.insert(l, 0);
+                    }
+                    None => (),
+                }
+            }
+
+            _ => (),
+        }
+    }
+
+    calculate_lifetimes(func);
+}
+
+/// Reuses the local of an earlier instruction in place of a later one that computes the same value,
+/// which is one that is the same instruction with the same arguments and has no side effects.
+/// Loads, applications, and pure calls to the runtime library are reused, and the instructions
+/// that are replaced are removed. Functions have no branches, so every earlier instruction is run
+/// before the later one.
+fn eliminate_common_subexpressions(func: &mut IrFunction) {
+    let mut seen: HashMap<(IrInstruction, Vec<IrArgument>), usize> = HashMap::new();
+    let mut replaced = HashMap::new();
+    let pure = &func.pure;
+    func.ssas.retain_mut(|ssa| {
+        for arg in ssa.args.iter_mut() {
+            if let IrArgument::Local(l) = arg {
+                if let Some(r) = replaced.get(l) {
+                    *l = *r;
+                }
+            }
+        }
+
+        let local = match ssa.local {
+            Some(l) => l,
+            None => return true,
+        };
+        let reusable = match ssa.instr {
+            IrInstruction::Load | IrInstruction::Apply => true,
+            IrInstruction::CallRuntime => pure.contains(&local),
+            _ => false,
+        };
+        if !reusable {
+            return true;
+        }
+
+        match seen.get(&(ssa.instr, ssa.args.clone())) {
+            Some(l) => {
+                trace!(from = local, to = l, "reused local");
+                replaced.insert(local, *l);
+                false
+            }
+
+            None => {
+                seen.insert((ssa.instr, ssa.args.clone()), local);
+                true
+            }
+        }
+    });
+
+    calculate_lifetimes(func);
+}
+
+/// Removes increments of reference counts that are undone by a decrement with no calls between
+/// them, since nothing can see the count in between. Reference counting instructions must have
+/// been inserted.
+fn remove_rc_pairs(func: &mut IrFunction) {
+    let mut i = 0;
+    while i < func.ssas.len() {
+        if let (IrInstruction::RcInc, Some(value)) = (func.ssas[i].instr, func.ssas[i].args.first())
+        {
+            // Decrements call the runtime library, so the decrement must be the first call
+            let next_call = func.ssas[i + 1..].iter().position(|v| v.instr.is_call());
+            if let Some(d) = next_call {
+                let dec = &func.ssas[i + 1 + d];
+                if matches!(dec.instr, IrInstruction::RcFuncFree) && dec.args.first() == Some(value)
+                {
+                    func.ssas.remove(i + 1 + d);
+                    func.ssas.remove(i);
+                    continue;
+                }
+            }
+        }
+
+        i += 1;
+    }
+}
+
+/// Removes the functions that cannot be reached from the given functions, such as the parts of the
+/// prelude a program does not use.
+pub fn remove_unused_functions(module: &mut IrModule, roots: &[&str]) {
+    let mut used: HashSet<String> = HashSet::new();
+    let mut stack: Vec<String> = roots.iter().map(|v| String::from(*v)).collect();
+    while let Some(name) = stack.pop() {
+        if used.contains(&name) {
+            continue;
+        }
+
+        if let Some(func) = module.function(&name) {
+            for ssa in func.ssas.iter() {
+                for arg in ssa.args.iter() {
+                    if let IrArgument::Function(f) = arg {
+                        stack.push(f.clone());
+                    }
+                }
+            }
+            used.insert(name);
+        }
+    }
+
+    let before = module.funcs.len();
+    module.funcs.retain(|v| used.contains(&v.name));
+    trace!(
+        removed = before - module.funcs.len(),
+        "removed unused functions"
+    );
+}
+
+/// Returns true if an argument is reference counted. Function addresses, static strings, and values
+/// that are not pointers are not.
+fn is_counted(func: &IrFunction, arg: &IrArgument) -> bool {
+    !matches!(
+        arg,
+        IrArgument::Function(_) | IrArgument::String(_) | IrArgument::Char(_) | IrArgument::Int(_)
+    ) && !func.unboxed.contains(arg)
+}
+
+/// Returns true if an instruction stores its arguments in the value it creates, the same as
+/// applying arguments to a closure, so that they must be kept alive along with it.
+fn stores_arguments(ssa: &IrSsa) -> bool {
+    match (&ssa.instr, ssa.args.first()) {
+        (IrInstruction::Apply, _) => true,
+        (IrInstruction::CallRuntime, Some(IrArgument::Function(f))) => {
+            f.starts_with(CONSTRUCTOR_PREFIX)
+        }
+        _ => false,
+    }
+}
+
 fn insert_rc_instructions(func: &mut IrFunction) {
     let mut i = 0;
-    let mut local_lifetimes: HashMap<IrArgument, usize> = HashMap::new();
+    // Locals are freed in order when several die at once, so lowering always gives the same code
+    let mut local_lifetimes: BTreeMap<usize, usize> = BTreeMap::new();
     while let Some(mut ssa) = func.ssas.get(i) {
-        if let IrInstruction::Apply = ssa.instr {
+        if stores_arguments(ssa) {
             let mut inserts = vec![];
             for arg in ssa.args.iter().skip(1) {
-                if !matches!(arg, IrArgument::Function(_)) {
+                if is_counted(func, arg) {
                     inserts.push(IrSsa {
                         local: None,
                         local_lifetime: 0,
                         local_register: 0,
                         instr: IrInstruction::RcInc,
                         args: vec![arg.clone()],
+                        span: ssa.span.clone(),
+                        file: ssa.file,
                     });
                 }
             }
@@ -325,11 +1682,14 @@ fn insert_rc_instructions(func: &mut IrFunction) {
 
             ssa = func.ssas.get(i).unwrap();
             if let Some(local) = ssa.local {
-                local_lifetimes.insert(IrArgument::Local(local), ssa.local_lifetime + 1);
+                local_lifetimes.insert(local, ssa.local_lifetime + 1);
             }
-        } else if let IrInstruction::Call(_) = ssa.instr {
-            if let Some(local) = ssa.local {
-                local_lifetimes.insert(IrArgument::Local(local), ssa.local_lifetime + 1);
+        } else if let IrInstruction::Call(_) | IrInstruction::TailCall = ssa.instr {
+            if let Some(local) = ssa
+                .local
+                .filter(|v| is_counted(func, &IrArgument::Local(*v)))
+            {
+                local_lifetimes.insert(local, ssa.local_lifetime + 1);
             }
         }
 
@@ -337,13 +1697,15 @@ fn insert_rc_instructions(func: &mut IrFunction) {
             let mut befores = vec![];
             let mut afters = vec![];
             for arg in ssa.args.iter().skip(1) {
-                if !matches!(arg, IrArgument::Function(_)) {
+                if is_counted(func, arg) {
                     befores.push(IrSsa {
                         local: None,
                         local_lifetime: 0,
                         local_register: 0,
                         instr: IrInstruction::RcInc,
                         args: vec![arg.clone()],
+                        span: ssa.span.clone(),
+                        file: ssa.file,
                     });
                     afters.push(IrSsa {
                         local: None,
@@ -351,6 +1713,8 @@ fn insert_rc_instructions(func: &mut IrFunction) {
                         local_register: 0,
                         instr: IrInstruction::RcFuncFree,
                         args: vec![arg.clone()],
+                        span: ssa.span.clone(),
+                        file: ssa.file,
                     });
                 }
             }
@@ -372,17 +1736,19 @@ fn insert_rc_instructions(func: &mut IrFunction) {
             let lifetime = local_lifetimes.get_mut(&local).unwrap();
             *lifetime -= 1;
             if *lifetime == 0 {
+                // The local is freed after its last use, which it takes the span of
                 local_lifetimes.remove(&local);
-                func.ssas.insert(
-                    i + 1,
-                    IrSsa {
-                        local: None,
-                        local_lifetime: 0,
-                        local_register: 0,
-                        instr: IrInstruction::RcFuncFree,
-                        args: vec![local],
-                    },
-                );
+                let last = &func.ssas[i];
+                let free = IrSsa {
+                    local: None,
+                    local_lifetime: 0,
+                    local_register: 0,
+                    instr: IrInstruction::RcFuncFree,
+                    args: vec![IrArgument::Local(local)],
+                    span: last.span.clone(),
+                    file: last.file,
+                };
+                func.ssas.insert(i + 1, free);
                 i += 1;
             }
         }
@@ -391,37 +1757,127 @@ fn insert_rc_instructions(func: &mut IrFunction) {
     }
 }
 
-/// Converts the frontend IR language to the backend IR language.
-pub fn convert_frontend_ir_to_backend_ir(module: &ir::IrModule) -> IrModule {
-    let mut new = IrModule { funcs: vec![] };
+/// Turns tail calls back into calls if anything other than returning their value follows them, such
+/// as freeing the locals still alive at the call.
+fn demote_tail_calls(func: &mut IrFunction) {
+    for i in 0..func.ssas.len() {
+        if let IrInstruction::TailCall = func.ssas[i].instr {
+            let returned = match func.ssas.get(i + 1) {
+                Some(IrSsa {
+                    instr: IrInstruction::Ret,
+                    args,
+                    ..
+                }) => args.first() == func.ssas[i].local.map(IrArgument::Local).as_ref(),
+                _ => false,
+            };
+
+            if !returned {
+                func.ssas[i].instr = IrInstruction::Call(true);
+            }
+        }
+    }
+}
+
+/// Converts the frontend IR language to the backend IR language. Functions are emitted in call
+/// order so that the output is deterministic. Operations the checks apply to call runtime functions
+/// that panic with their location when they fail.
+pub fn convert_frontend_ir_to_backend_ir(module: &ir::IrModule, checks: Checks) -> IrModule {
+    convert_new_functions_to_backend_ir(module, &HashSet::new(), checks)
+}
+
+/// Converts the functions of a module that are not in the given set into the backend IR. The
+/// REPL uses this to convert only the functions defined by each line, since the functions of
+/// earlier lines are already compiled.
+pub fn convert_new_functions_to_backend_ir(
+    module: &ir::IrModule,
+    converted: &HashSet<String>,
+    checks: Checks,
+) -> IrModule {
+    let mut new = IrModule {
+        funcs: vec![],
+        strings: vec![],
+        files: vec![module.filename.clone()],
+        externs: module
+            .externals
+            .values()
+            .map(|v| (v.extern_name.clone(), v.c_signature()))
+            .filter(|v| !v.1.is_word_only())
+            .collect(),
+        exports: module
+            .c_exports
+            .iter()
+            .map(|v| {
+                let func = &module.funcs[&module.globals[v]];
+                IrExport {
+                    name: v.clone(),
+                    func: func.name.clone(),
+                    sig: func.c_signature(),
+                }
+            })
+            .collect(),
+        exits_with_main: module.exits_with_main(),
+    };
 
     let map: HashMap<_, _> = module
         .funcs
         .iter()
         .map(|v| (v.0.clone(), v.1.captured_names.clone()))
         .collect();
-    for func in module.funcs.iter() {
+    let _span = info_span!("convert_frontend_ir_to_backend_ir", module = %module.name).entered();
+    for func in module.call_order().filter(|v| !converted.contains(&v.name)) {
+        let _span = info_span!("function", name = %func.name).entered();
         let mut f = IrFunction {
-            name: func.1.name.clone(),
-            argc: func.1.args.len() + func.1.captured.len(),
+            name: func.name.clone(),
+            file: intern_string(&mut new.files, &func.loc.filename),
+            span: func.loc.span.clone(),
+            argc: func.args.len() + func.captured.len(),
+            debug_args: vec![],
             ssas: vec![],
+            unboxed: HashSet::new(),
+            pure: HashSet::new(),
         };
-        let args_map: HashMap<String, usize> = func
-            .1
+        // Closed over values are passed before the arguments
+        let args_map: HashMap<String, IrArgument> = func
             .captured_names
             .iter()
             .cloned()
+            .chain(func.args.iter().map(|v| v.0.clone()))
             .enumerate()
-            .chain(func.1.args.iter().map(|v| v.0.clone()).enumerate())
-            .map(|v| (v.1, v.0))
+            .map(|v| (v.1, IrArgument::Argument(v.0)))
             .collect();
+        let arg_types = func
+            .captured_names
+            .iter()
+            .map(|v| &func.captured[v])
+            .chain(func.args.iter().map(|v| &v.1));
+        let arg_names = func
+            .captured_names
+            .iter()
+            .chain(func.args.iter().map(|v| &v.0));
+        for (i, (name, _type)) in arg_names.zip(arg_types).enumerate() {
+            if _type.is_primitive() {
+                f.unboxed.insert(IrArgument::Argument(i));
+            }
+            f.debug_args
+                .push((name.clone(), pretty::type_to_string(_type)));
+        }
 
-        conversion_helper(&args_map, &mut f, &func.1.body, &map);
+        conversion_helper(
+            &args_map,
+            &mut f,
+            &func.body,
+            &map,
+            module,
+            &mut new.strings,
+            checks,
+        );
         f.ssas.push(IrSsa {
             local: None,
             local_lifetime: 0,
             local_register: 0,
             instr: IrInstruction::Ret,
+            span: func.body.get_metadata().loc.span.clone(),
+            file: f.file,
             args: if let Some(l) = f.get_last_local() {
                 vec![IrArgument::Local(l)]
             } else {
@@ -429,11 +1885,67 @@ pub fn convert_frontend_ir_to_backend_ir(module: &ir::IrModule) -> IrModule {
             },
         });
 
-        calculate_lifetimes(&mut f);
-        insert_rc_instructions(&mut f);
+        optimise(&mut f);
+        trace!("lowered to\n{}", f);
 
         new.funcs.push(f);
     }
 
+    // Show and equality functions are derived once for each type they are used with, including the
+    // types of the values inside them
+    let mut derived = vec![];
+    for func in new.funcs.iter() {
+        derived_types(&module.funcs[&func.name].body, module, &mut derived);
+    }
+    derive_functions(&mut new, module, converted, derived);
     new
 }
+
+/// Adds the function that shows values of a type to a module converted from the frontend IR,
+/// along with the show functions it calls that are not in the given set, and returns its name. The
+/// REPL shows the values of compiled lines with it.
+pub fn add_show_function(
+    new: &mut IrModule,
+    module: &ir::IrModule,
+    converted: &HashSet<String>,
+    _type: &TypeRc,
+) -> String {
+    derive_functions(new, module, converted, vec![(Derived::Show, _type.clone())]);
+    Derived::Show.name(_type)
+}
+
+/// Adds the functions derived for each type in a list to a module, along with those derived for
+/// the types of the values inside them, unless they are in the given set or the module already
+/// has them.
+fn derive_functions(
+    new: &mut IrModule,
+    module: &ir::IrModule,
+    converted: &HashSet<String>,
+    mut derived: Vec<(Derived, TypeRc)>,
+) {
+    let mut i = 0;
+    while let Some((kind, _type)) = derived.get(i).cloned() {
+        i += 1;
+        let name = kind.name(&_type);
+        if converted.contains(&name) || new.function(&name).is_some() {
+            continue;
+        }
+
+        let _span = info_span!("derived function", name = %name).entered();
+        let mut f = derive_function(kind, &_type, module, &mut new.strings, &mut derived);
+        optimise(&mut f);
+        trace!("derived\n{}", f);
+        new.funcs.push(f);
+    }
+}
+
+/// Runs the passes over a lowered function that optimise it and insert reference counting.
+fn optimise(f: &mut IrFunction) {
+    calculate_lifetimes(f);
+    peephole_optimise(f);
+    eliminate_common_subexpressions(f);
+    eliminate_dead_code(f);
+    insert_rc_instructions(f);
+    remove_rc_pairs(f);
+    demote_tail_calls(f);
+}
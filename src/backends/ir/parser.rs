@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use super::super::super::frontend::ir::{CSignature, CType};
+use super::super::super::frontend::types::IntSize;
+use super::{
+    calculate_lifetimes, IrArgument, IrExport, IrFunction, IrInstruction, IrModule, IrSsa,
+};
+
+/// An error in the textual form of the backend IR.
+#[derive(Debug)]
+pub struct IrParseError {
+    /// The line the error is on, starting from 1.
+    pub line: usize,
+
+    /// The message describing the error.
+    pub msg: String,
+}
+
+impl Display for IrParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+/// Parses a module from the form it is printed in, so that backend passes can be run on small
+/// handwritten modules instead of ones converted from Closey code. The lifetimes of locals are
+/// calculated, since they are not printed. Which values are unboxed or pure is not printed either,
+/// so every local and argument of a parsed function is treated as reference counted, and calls to
+/// the runtime library are never reused. Parsed functions are not lowered from source code, so
+/// they and their instructions are in a file with no name, and every instruction has an empty span.
+pub fn parse_module(s: &str) -> Result<IrModule, IrParseError> {
+    let mut module = IrModule {
+        funcs: vec![],
+        strings: vec![],
+        files: vec![String::new()],
+        externs: HashMap::new(),
+        exports: vec![],
+        exits_with_main: true,
+    };
+
+    for (i, line) in s.lines().enumerate() {
+        let error = |msg: String| IrParseError { line: i + 1, msg };
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        // Instructions are indented under the function they are in
+        if line.starts_with(char::is_whitespace) {
+            match module.funcs.last_mut() {
+                Some(func) => {
+                    let ssa = parse_ssa(trimmed).map_err(error)?;
+                    check_locals(func, &ssa).map_err(error)?;
+                    func.ssas.push(ssa);
+                }
+
+                None => return Err(error(String::from("instruction outside of a function"))),
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("extern ") {
+            let (name, sig) = parse_signature(rest).map_err(error)?;
+            module.externs.insert(name, sig);
+        } else if let Some(rest) = trimmed.strip_prefix("export ") {
+            let (sig, func) = rest
+                .split_once(" = ")
+                .ok_or_else(|| error(String::from("expected `=` after an export")))?;
+            let (name, sig) = parse_signature(sig).map_err(error)?;
+            module.exports.push(IrExport {
+                name,
+                func: String::from(func.trim()),
+                sig,
+            });
+        } else if let Some(rest) = trimmed.strip_prefix('&') {
+            let (index, string) = rest
+                .split_once(" = ")
+                .ok_or_else(|| error(String::from("expected `=` after a string")))?;
+            if index.parse() != Ok(module.strings.len()) {
+                return Err(error(format!(
+                    "expected string &{} but found &{}",
+                    module.strings.len(),
+                    index
+                )));
+            }
+
+            match unescape(string, '"') {
+                Some(v) => module.strings.push(v),
+                None => return Err(error(format!("invalid string {}", string))),
+            }
+        } else {
+            module.funcs.push(parse_header(trimmed).map_err(error)?);
+        }
+    }
+
+    for func in module.funcs.iter_mut() {
+        calculate_lifetimes(func);
+    }
+    Ok(module)
+}
+
+/// Parses a single function from the form it is printed in, such as `f(1):` followed by indented
+/// instructions. The function is parsed the same way as the functions of a module.
+pub fn parse_function(s: &str) -> Result<IrFunction, IrParseError> {
+    let mut module = parse_module(s)?;
+    match module.funcs.len() {
+        1 => Ok(module.funcs.remove(0)),
+        n => Err(IrParseError {
+            line: 1,
+            msg: format!("expected one function but found {}", n),
+        }),
+    }
+}
+
+/// Parses the header of a function, which is its name followed by the number of arguments it takes.
+fn parse_header(s: &str) -> Result<IrFunction, String> {
+    let header = s
+        .strip_suffix("):")
+        .and_then(|v| v.rsplit_once('('))
+        .filter(|(name, _)| !name.is_empty());
+    match header {
+        Some((name, argc)) => Ok(IrFunction {
+            name: String::from(name),
+            file: 0,
+            span: 0..0,
+            argc: argc
+                .parse()
+                .map_err(|_| format!("invalid argument count {}", argc))?,
+            debug_args: vec![],
+            ssas: vec![],
+            unboxed: HashSet::new(),
+            pure: HashSet::new(),
+        }),
+
+        None => Err(format!("expected a function header but found `{}`", s)),
+    }
+}
+
+/// Parses a C signature with a name, such as `int64_t f(double, bool)`.
+fn parse_signature(s: &str) -> Result<(String, CSignature), String> {
+    let (ret, rest) = s
+        .split_once(' ')
+        .ok_or_else(|| format!("expected a return type and name in `{}`", s))?;
+    let (name, args) = rest
+        .strip_suffix(')')
+        .and_then(|v| v.split_once('('))
+        .ok_or_else(|| format!("expected arguments after {}", rest))?;
+
+    let args = if args.trim().is_empty() {
+        vec![]
+    } else {
+        args.split(',')
+            .map(|v| parse_c_type(v.trim()))
+            .collect::<Result<_, _>>()?
+    };
+    Ok((
+        String::from(name),
+        CSignature {
+            args,
+            ret: parse_c_type(ret)?,
+        },
+    ))
+}
+
+/// Parses a C type. `uint32_t` is parsed as a char, since chars and `UInt32` values are printed
+/// the same way.
+fn parse_c_type(s: &str) -> Result<CType, String> {
+    match s {
+        "int64_t" => Ok(CType::Word),
+        "double" => Ok(CType::Double),
+        "bool" => Ok(CType::Bool),
+        "uint32_t" => Ok(CType::Char),
+        _ => IntSize::ALL
+            .iter()
+            .find(|v| CType::Int(**v).to_string() == s)
+            .map(|v| CType::Int(*v))
+            .ok_or_else(|| format!("unknown C type {}", s)),
+    }
+}
+
+/// Parses an instruction, along with the local it is assigned to.
+fn parse_ssa(s: &str) -> Result<IrSsa, String> {
+    let (local, rest) = match s.split_once(" = ") {
+        Some((l, rest)) if l.starts_with('%') => (Some(parse_index(&l[1..])?), rest),
+        _ => (None, s),
+    };
+
+    let tokens = tokenise(rest)?;
+    let instr = match tokens.first().map(String::as_str) {
+        Some("ret") => IrInstruction::Ret,
+        Some("load") => IrInstruction::Load,
+        Some("apply") => IrInstruction::Apply,
+        Some("call") => IrInstruction::Call(true),
+        Some("call?") => IrInstruction::Call(false),
+        Some("tailcall") => IrInstruction::TailCall,
+        Some("callrt") => IrInstruction::CallRuntime,
+        Some("rcinc") => IrInstruction::RcInc,
+        Some("rcfuncfree") => IrInstruction::RcFuncFree,
+        Some(v) => return Err(format!("unknown instruction {}", v)),
+        None => return Err(String::from("expected an instruction")),
+    };
+
+    Ok(IrSsa {
+        local,
+        local_lifetime: 0,
+        local_register: 0,
+        instr,
+        args: tokens[1..]
+            .iter()
+            .map(|v| parse_argument(v))
+            .collect::<Result<_, _>>()?,
+        span: 0..0,
+        file: 0,
+    })
+}
+
+/// Returns an error if an instruction uses a local that is not assigned before it, or assigns a
+/// local that is already assigned.
+fn check_locals(func: &IrFunction, ssa: &IrSsa) -> Result<(), String> {
+    let assigned = |l: usize| func.ssas.iter().any(|v| v.local == Some(l));
+    for arg in ssa.args.iter() {
+        if let IrArgument::Local(l) = arg {
+            if !assigned(*l) {
+                return Err(format!("%{} is used before it is assigned", l));
+            }
+        }
+    }
+
+    match ssa.local {
+        Some(l) if assigned(l) => Err(format!("%{} is assigned more than once", l)),
+        _ => Ok(()),
+    }
+}
+
+/// Splits an instruction into its name and arguments. Chars and the names of functions that have
+/// spaces are quoted, so they may hold spaces.
+fn tokenise(s: &str) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        let mut token = String::from(c);
+        let quote = match c {
+            '\'' => Some('\''),
+            '@' if chars.peek() == Some(&'"') => {
+                token.extend(chars.next());
+                Some('"')
+            }
+            _ => None,
+        };
+
+        if let Some(quote) = quote {
+            loop {
+                match chars.next() {
+                    Some('\\') => {
+                        token.push('\\');
+                        token.extend(chars.next());
+                    }
+
+                    Some(c) if c == quote => {
+                        token.push(c);
+                        break;
+                    }
+
+                    Some(c) => token.push(c),
+                    None if quote == '"' => {
+                        return Err(format!("unterminated function name {}", token))
+                    }
+                    None => return Err(format!("unterminated char {}", token)),
+                }
+            }
+        } else {
+            while let Some(c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                token.push(*c);
+                chars.next();
+            }
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Parses an argument of an instruction.
+fn parse_argument(s: &str) -> Result<IrArgument, String> {
+    match s.chars().next() {
+        Some('%') => Ok(IrArgument::Local(parse_index(&s[1..])?)),
+        Some('$') => Ok(IrArgument::Argument(parse_index(&s[1..])?)),
+        Some('&') => Ok(IrArgument::String(parse_index(&s[1..])?)),
+        Some('@') if s[1..].starts_with('"') => unescape(&s[1..], '"')
+            .map(IrArgument::Function)
+            .ok_or_else(|| format!("invalid function name {}", s)),
+        Some('@') if s.len() > 1 => Ok(IrArgument::Function(String::from(&s[1..]))),
+
+        Some('\'') => {
+            let string = unescape(s, '\'').ok_or_else(|| format!("invalid char {}", s))?;
+            let mut chars = string.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(IrArgument::Char(c)),
+                _ => Err(format!("invalid char {}", s)),
+            }
+        }
+
+        _ => s
+            .parse()
+            .map(IrArgument::Int)
+            .map_err(|_| format!("invalid argument {}", s)),
+    }
+}
+
+/// Parses the number of a local, argument, or string.
+fn parse_index(s: &str) -> Result<usize, String> {
+    s.parse().map_err(|_| format!("invalid index {}", s))
+}
+
+/// Removes the quotes around a string or char printed with its debug representation, and replaces
+/// its escape sequences with the characters they stand for.
+fn unescape(s: &str, quote: char) -> Option<String> {
+    let s = s.strip_prefix(quote)?.strip_suffix(quote)?;
+    let mut result = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        result.push(match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'u' => {
+                let code: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                let code = code.strip_prefix('{')?;
+                std::char::from_u32(u32::from_str_radix(code, 16).ok()?)?
+            }
+            c => c,
+        });
+    }
+    Some(result)
+}
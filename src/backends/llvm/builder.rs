@@ -0,0 +1,93 @@
+use std::fmt::{Display, Write};
+
+/// Builds the text of an LLVM function. Code is generated by adding instructions, blocks, and
+/// temporaries, and the builder lays them out: instructions are indented on lines of their own,
+/// blocks start with their label, and temporaries are numbered in the order they are made.
+pub struct FunctionBuilder {
+    /// The text of the function so far.
+    out: String,
+
+    /// The number of the next temporary or label.
+    temp: usize,
+}
+
+impl FunctionBuilder {
+    /// Starts a function with the given header, such as `define i64 @"f"(i64 %a0)`.
+    pub fn define<T: Display>(header: T) -> FunctionBuilder {
+        FunctionBuilder {
+            out: format!("{} {{\n", header),
+            temp: 0,
+        }
+    }
+
+    /// Returns a new temporary, such as `%t0`.
+    pub fn temp(&mut self) -> String {
+        self.temp += 1;
+        format!("%t{}", self.temp - 1)
+    }
+
+    /// Returns the name of a new label, which is numbered along with temporaries. Labels are
+    /// referred to with a `%` before them.
+    pub fn label(&mut self) -> String {
+        let temp = self.temp();
+        String::from(&temp[1..])
+    }
+
+    /// Adds an instruction that has no result.
+    pub fn stmt<T: Display>(&mut self, instr: T) {
+        writeln!(self.out, "    {}", instr).unwrap();
+    }
+
+    /// Adds an instruction whose result is given to a new temporary, and returns the temporary.
+    pub fn value<T: Display>(&mut self, instr: T) -> String {
+        let temp = self.temp();
+        self.assign(&temp, instr);
+        temp
+    }
+
+    /// Adds an instruction whose result is given to the named value.
+    pub fn assign<T: Display>(&mut self, name: &str, instr: T) {
+        writeln!(self.out, "    {} = {}", name, instr).unwrap();
+    }
+
+    /// Adds an instruction, giving its result to the named value if there is one.
+    pub fn instr<T: Display>(&mut self, name: Option<&str>, instr: T) {
+        match name {
+            Some(name) => self.assign(name, instr),
+            None => self.stmt(instr),
+        }
+    }
+
+    /// Starts a new block with the given label.
+    pub fn block(&mut self, label: &str) {
+        writeln!(self.out, "{}:", label).unwrap();
+    }
+
+    /// Returns where the next instruction will be added, so that instructions added from then on
+    /// can be given a location with `locate()`.
+    pub fn position(&self) -> usize {
+        self.out.len()
+    }
+
+    /// Attaches a debug location to the instructions added since the given position.
+    pub fn locate(&mut self, start: usize, location: &str) {
+        let lines: String = self.out[start..]
+            .lines()
+            .map(|line| {
+                if line.starts_with("    ") {
+                    format!("{}, !dbg {}\n", line, location)
+                } else {
+                    format!("{}\n", line)
+                }
+            })
+            .collect();
+        self.out.truncate(start);
+        self.out.push_str(&lines);
+    }
+
+    /// Ends the function and returns its text.
+    pub fn finish(mut self) -> String {
+        self.out.push_str("}\n\n");
+        self.out
+    }
+}
@@ -0,0 +1,834 @@
+use logos::Span;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Write};
+use std::thread;
+use tracing::{info_span, trace};
+
+use super::super::super::frontend::ir::{CSignature, CType};
+use super::super::super::options::Allocator;
+use super::super::cache::{self, CachedFunction, CodegenCache};
+use super::super::dwarf::{self, DebugFile, SourcePositions};
+use super::super::ir::{IrArgument, IrExport, IrFunction, IrInstruction, IrModule};
+use super::builder::FunctionBuilder;
+
+/// The offset from the code of a function to its tagged address. Functions are preceded by a 16
+/// byte header whose second to fifth bytes are the argument count, followed by the mask of unboxed
+/// arguments, and tagged addresses point to the argument count, the same as in the native backends.
+const TAGGED_OFFSET: usize = 15;
+
+/// The reference count of static strings, which matches `RC_STATIC` in the runtime library and is
+/// high enough that it never drops to 0.
+const STATIC_RC: u64 = 1 << 62;
+
+/// The size of the header the runtime library puts before reference counted values, which static
+/// strings are emitted with so that reference counting them works the same as for other strings.
+const RC_HEADER_SIZE: usize = 32;
+
+/// The fewest functions generated by each thread when functions are generated in parallel.
+const MIN_FUNCTIONS_PER_THREAD: usize = 32;
+
+/// Represents generated LLVM IR in textual form.
+#[derive(Default)]
+pub struct LlvmCode {
+    declarations: BTreeMap<String, String>,
+    defined: HashSet<String>,
+    definitions: String,
+    named_metadata: Vec<String>,
+    metadata: Vec<String>,
+    metadata_ids: HashMap<String, usize>,
+}
+
+/// Represents the debug info of the module being generated.
+struct DebugInfo<'a> {
+    /// Finds the lines and columns of spans.
+    positions: SourcePositions<'a>,
+
+    /// The metadata of each file of the module.
+    files: Vec<String>,
+
+    /// The metadata of the compile unit.
+    unit: String,
+}
+
+impl LlvmCode {
+    /// Creates new empty LLVM IR.
+    pub fn new() -> LlvmCode {
+        LlvmCode {
+            declarations: BTreeMap::new(),
+            defined: HashSet::new(),
+            definitions: String::new(),
+            named_metadata: vec![],
+            metadata: vec![],
+            metadata_ids: HashMap::new(),
+        }
+    }
+
+    /// Adds a metadata node, unless the same node has already been added, and returns the
+    /// reference to it.
+    fn metadata(&mut self, node: String) -> String {
+        let id = match self.metadata_ids.get(&node) {
+            Some(id) => *id,
+            None => {
+                self.metadata.push(node.clone());
+                self.metadata_ids.insert(node, self.metadata.len() - 1);
+                self.metadata.len() - 1
+            }
+        };
+        format!("!{}", id)
+    }
+
+    /// Declares an external function with the given return type and argument types, unless it has
+    /// already been declared. Declarations of functions defined in the module are left out when the
+    /// code is printed.
+    fn declare(&mut self, name: &str, ret: &str, args: &[&str]) {
+        if !self.declarations.contains_key(name) {
+            self.declarations.insert(
+                String::from(name),
+                format!("declare {} @\"{}\"({})", ret, name, args.join(", ")),
+            );
+        }
+    }
+}
+
+impl Display for LlvmCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut declared = false;
+        for (name, declaration) in self.declarations.iter() {
+            if !self.defined.contains(name) {
+                writeln!(f, "{}", declaration)?;
+                declared = true;
+            }
+        }
+        if declared {
+            writeln!(f)?;
+        }
+
+        write!(f, "{}", self.definitions)?;
+
+        for named in self.named_metadata.iter() {
+            writeln!(f, "{}", named)?;
+        }
+        for (i, node) in self.metadata.iter().enumerate() {
+            writeln!(f, "!{} = {}", i, node)?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes a string for use in metadata.
+fn metadata_string(s: &str) -> String {
+    format!("\"{}\"", escape_bytes(s))
+}
+
+/// Converts an argument into an LLVM operand, adding the instructions needed to compute it.
+fn generate_operand(
+    code: &mut LlvmCode,
+    b: &mut FunctionBuilder,
+    arg: &IrArgument,
+    locals: &HashMap<usize, String>,
+) -> String {
+    match arg {
+        IrArgument::Local(l) => locals.get(l).unwrap().clone(),
+
+        IrArgument::Argument(a) => format!("%a{}", a),
+
+        IrArgument::Function(f) => {
+            code.declare(f, "i64", &[]);
+            let addr = b.value(format_args!("ptrtoint ptr @\"{}\" to i64", f));
+            b.value(format_args!("sub i64 {}, {}", addr, TAGGED_OFFSET))
+        }
+
+        IrArgument::String(i) => b.value(format_args!(
+            "ptrtoint ptr getelementptr inbounds (i8, ptr {}, i64 {}) to i64",
+            string_label(*i),
+            RC_HEADER_SIZE
+        )),
+
+        IrArgument::Char(c) => (*c as u32).to_string(),
+
+        IrArgument::Int(n) => n.to_string(),
+    }
+}
+
+/// Returns the label of the static string with the given index.
+fn string_label(i: usize) -> String {
+    format!("@.string.{}", i)
+}
+
+/// Escapes bytes for use in an LLVM string constant.
+fn escape_bytes(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if (0x20..0x7f).contains(&b) && b != b'"' && b != b'\\' {
+            escaped.push(b as char);
+        } else {
+            write!(escaped, "\\{:02X}", b).unwrap();
+        }
+    }
+    escaped
+}
+
+/// Returns the LLVM type a C type is passed as.
+fn c_type(_type: CType) -> &'static str {
+    match _type {
+        CType::Word => "i64",
+        CType::Double => "double",
+        CType::Bool => "i1",
+        CType::Char => "i32",
+        CType::Int(size) => match size.bits {
+            8 => "i8",
+            16 => "i16",
+            32 => "i32",
+            _ => "i64",
+        },
+    }
+}
+
+/// Returns the LLVM type a C type is passed as along with the attributes C compilers give it.
+/// Bools, chars, and unsigned ints narrower than 64 bits are zero extended, signed ones are sign
+/// extended, and the attributes come before return types but after argument types.
+fn c_type_with_attrs(_type: CType, ret: bool) -> String {
+    let ext = match _type {
+        CType::Bool | CType::Char => "zeroext",
+        CType::Int(size) if size.bits < 64 && size.signed => "signext",
+        CType::Int(size) if size.bits < 64 => "zeroext",
+        _ => return String::from(c_type(_type)),
+    };
+
+    if ret {
+        format!("{} {}", ext, c_type(_type))
+    } else {
+        format!("{} {}", c_type(_type), ext)
+    }
+}
+
+/// Converts an i64 into a value of a C type, returning the operand holding the converted value.
+fn generate_to_c(b: &mut FunctionBuilder, value: String, _type: CType) -> String {
+    match _type {
+        CType::Word => value,
+        CType::Int(size) if size.bits == 64 => value,
+        CType::Double => b.value(format_args!("bitcast i64 {} to double", value)),
+        CType::Bool | CType::Char | CType::Int(_) => {
+            b.value(format_args!("trunc i64 {} to {}", value, c_type(_type)))
+        }
+    }
+}
+
+/// Converts a value of a C type into an i64, returning the operand holding the converted value.
+fn generate_from_c(b: &mut FunctionBuilder, value: String, _type: CType) -> String {
+    match _type {
+        CType::Word => value,
+        CType::Int(size) if size.bits == 64 => value,
+        CType::Double => b.value(format_args!("bitcast double {} to i64", value)),
+        CType::Bool | CType::Char | CType::Int(_) => {
+            // Values of sized int types are stored sign extended or zero extended from their width
+            let ext = match _type {
+                CType::Int(size) if size.signed => "sext",
+                _ => "zext",
+            };
+            b.value(format_args!("{} {} {} to i64", ext, c_type(_type), value))
+        }
+    }
+}
+
+/// Generates a call to an external function with its C signature, converting the arguments from
+/// i64s and the return value back into an i64. Returns the operand holding the return value.
+fn generate_extern_call(
+    code: &mut LlvmCode,
+    b: &mut FunctionBuilder,
+    f: &str,
+    sig: &CSignature,
+    args: &[IrArgument],
+    locals: &HashMap<usize, String>,
+) -> String {
+    let mut operands = vec![];
+    for (arg, _type) in args.iter().zip(sig.args.iter()) {
+        let value = generate_operand(code, b, arg, locals);
+        let value = generate_to_c(b, value, *_type);
+        operands.push(format!("{} {}", c_type_with_attrs(*_type, false), value));
+    }
+
+    let arg_types: Vec<_> = sig
+        .args
+        .iter()
+        .map(|v| c_type_with_attrs(*v, false))
+        .collect();
+    let arg_types: Vec<_> = arg_types.iter().map(String::as_str).collect();
+    code.declare(f, &c_type_with_attrs(sig.ret, true), &arg_types);
+
+    let ret = b.value(format_args!(
+        "call {} @\"{}\"({})",
+        c_type(sig.ret),
+        f,
+        operands.join(", ")
+    ));
+    generate_from_c(b, ret, sig.ret)
+}
+
+/// Generates the C function for an exported function, which converts its arguments from their C
+/// types, calls the function, and converts the return value back.
+fn generate_export(code: &mut LlvmCode, export: &IrExport) {
+    code.defined.insert(export.name.clone());
+
+    let args: Vec<_> = export
+        .sig
+        .args
+        .iter()
+        .enumerate()
+        .map(|(i, v)| format!("{} %a{}", c_type_with_attrs(*v, false), i))
+        .collect();
+    let mut b = FunctionBuilder::define(format_args!(
+        "define {} @\"{}\"({})",
+        c_type_with_attrs(export.sig.ret, true),
+        export.name,
+        args.join(", ")
+    ));
+
+    let mut operands = vec![];
+    for (i, _type) in export.sig.args.iter().enumerate() {
+        let value = generate_from_c(&mut b, format!("%a{}", i), *_type);
+        operands.push(format!("i64 {}", value));
+    }
+
+    let ret = b.value(format_args!(
+        "call i64 @\"{}\"({})",
+        export.func,
+        operands.join(", ")
+    ));
+    let ret = generate_to_c(&mut b, ret, export.sig.ret);
+    b.stmt(format_args!("ret {} {}", c_type(export.sig.ret), ret));
+
+    code.definitions.push_str(&b.finish());
+}
+
+/// Returns the debug location of a span of source code in a function.
+fn debug_location(
+    code: &mut LlvmCode,
+    debug: &DebugInfo,
+    scope: &str,
+    file: usize,
+    span: &Span,
+) -> String {
+    let (line, column) = debug.positions.position(file, span.start);
+    code.metadata(format!(
+        "!DILocation(line: {}, column: {}, scope: {})",
+        line, column, scope
+    ))
+}
+
+/// Describes a function and its arguments in debug info, and returns the reference to the
+/// description along with the calls that give the arguments their values.
+fn generate_subprogram(
+    code: &mut LlvmCode,
+    debug: &DebugInfo,
+    func: &IrFunction,
+) -> (String, Vec<String>) {
+    let file = &debug.files[func.file];
+    let (line, _) = debug.positions.position(func.file, func.span.start);
+    let _type = code.metadata(String::from("!DISubroutineType(types: !{})"));
+    let subprogram = code.metadata(format!(
+        "distinct !DISubprogram(name: {}, scope: {}, file: {1}, line: {}, type: {}, scopeLine: {2}, spFlags: DISPFlagDefinition, unit: {})",
+        metadata_string(&func.name),
+        file,
+        line,
+        _type,
+        debug.unit
+    ));
+
+    let mut values = vec![];
+    for (i, (name, type_name)) in func.debug_args.iter().enumerate() {
+        let (encoding, size) = dwarf::base_type(type_name);
+        let _type = code.metadata(format!(
+            "!DIBasicType(name: {}, size: {}, encoding: {})",
+            metadata_string(type_name),
+            size as usize * 8,
+            encoding
+        ));
+        let variable = code.metadata(format!(
+            "!DILocalVariable(name: {}, arg: {}, scope: {}, file: {}, line: {}, type: {})",
+            metadata_string(name),
+            i + 1,
+            subprogram,
+            file,
+            line,
+            _type
+        ));
+
+        code.declare(
+            "llvm.dbg.value",
+            "void",
+            &["metadata", "metadata", "metadata"],
+        );
+        values.push(format!(
+            "call void @\"llvm.dbg.value\"(metadata i64 %a{}, metadata {}, metadata !DIExpression())",
+            i, variable
+        ));
+    }
+    (subprogram, values)
+}
+
+/// Generates a function. Every value is an i64, and arguments are passed with the C calling
+/// convention, so functions can be called by the runtime library. With debug info, every
+/// instruction is given the location of the instruction of the backend IR it is generated from.
+fn generate_function(
+    code: &mut LlvmCode,
+    func: &IrFunction,
+    module: &IrModule,
+    debug: Option<&DebugInfo>,
+) {
+    let mut locals = HashMap::new();
+    code.defined.insert(func.name.clone());
+
+    let subprogram = debug.map(|debug| generate_subprogram(code, debug, func));
+    let args: Vec<_> = (0..func.argc).map(|i| format!("i64 %a{}", i)).collect();
+    let mut b = FunctionBuilder::define(format_args!(
+        "define i64 @\"{}\"({}) align 16 prefix <{{ i8, i32, i32, [7 x i8] }}> <{{ i8 0, i32 {}, i32 {}, [7 x i8] zeroinitializer }}>{}",
+        func.name,
+        args.join(", "),
+        func.argc,
+        func.unboxed_mask(),
+        match &subprogram {
+            Some((subprogram, _)) => format!(" !dbg {}", subprogram),
+            None => String::new(),
+        }
+    ));
+
+    // The instructions generated for each instruction of the backend IR get its location once
+    // they are all generated
+    let mut located = b.position();
+    let mut location = None;
+    if let (Some(debug), Some((subprogram, values))) = (debug, &subprogram) {
+        for value in values.iter() {
+            b.stmt(value);
+        }
+        location = Some(debug_location(
+            code, debug, subprogram, func.file, &func.span,
+        ));
+    }
+
+    for ssa in func.ssas.iter() {
+        if let Some(location) = &location {
+            b.locate(located, location);
+        }
+        located = b.position();
+        if let (Some(debug), Some((subprogram, _))) = (debug, &subprogram) {
+            location = Some(debug_location(code, debug, subprogram, ssa.file, &ssa.span));
+        }
+
+        let local = ssa.local.map(|l| format!("%l{}", l));
+        let local = local.as_deref();
+
+        match ssa.instr {
+            IrInstruction::Ret => {
+                let value = match ssa.args.first() {
+                    Some(arg) => generate_operand(code, &mut b, arg, &locals),
+                    None => String::from("0"),
+                };
+                b.stmt(format_args!("ret i64 {}", value));
+            }
+
+            IrInstruction::Load => {
+                if let (Some(local), Some(arg)) = (ssa.local, ssa.args.first()) {
+                    // Loads are copies, so the local is replaced by the loaded operand
+                    let value = generate_operand(code, &mut b, arg, &locals);
+                    locals.insert(local, value);
+                    continue;
+                }
+            }
+
+            IrInstruction::Apply => {
+                // Closures are created from the function and an array of the values they save
+                let f = generate_operand(code, &mut b, &ssa.args[0], &locals);
+                let env = generate_array(code, &mut b, &ssa.args[1..], &locals);
+                code.declare("rcclosure", "i64", &["i64", "i64", "ptr"]);
+                b.instr(
+                    local,
+                    format_args!(
+                        "call i64 @\"rcclosure\"(i64 {}, i64 {}, ptr {})",
+                        f,
+                        ssa.args.len() - 1,
+                        env
+                    ),
+                );
+            }
+
+            IrInstruction::CallRuntime if matches!(ssa.args.first(), Some(IrArgument::Function(f)) if module.externs.contains_key(f)) =>
+            {
+                let f = match ssa.args.first().unwrap() {
+                    IrArgument::Function(f) => f,
+                    _ => unreachable!(),
+                };
+                let value = generate_extern_call(
+                    code,
+                    &mut b,
+                    f,
+                    &module.externs[f],
+                    &ssa.args[1..],
+                    &locals,
+                );
+                // The converted return value is used in place of the local
+                if let Some(l) = ssa.local {
+                    locals.insert(l, value);
+                }
+                continue;
+            }
+
+            IrInstruction::Call(true) | IrInstruction::TailCall | IrInstruction::CallRuntime
+                if matches!(ssa.args.first(), Some(IrArgument::Function(_))) =>
+            {
+                let f = match ssa.args.first().unwrap() {
+                    IrArgument::Function(f) => f,
+                    _ => unreachable!(),
+                };
+
+                let args: Vec<_> = ssa
+                    .args
+                    .iter()
+                    .skip(1)
+                    .map(|a| format!("i64 {}", generate_operand(code, &mut b, a, &locals)))
+                    .collect();
+                code.declare(f, "i64", &vec!["i64"; args.len()]);
+
+                // LLVM reuses the stack frame of the function when the arguments fit in registers
+                let tail = match ssa.instr {
+                    IrInstruction::TailCall => "tail ",
+                    _ => "",
+                };
+                b.instr(
+                    local,
+                    format_args!("{}call i64 @\"{}\"({})", tail, f, args.join(", ")),
+                );
+            }
+
+            IrInstruction::Call(_) | IrInstruction::TailCall | IrInstruction::CallRuntime => {
+                // Functions that are not known at compile time are called through the runtime
+                let f = generate_operand(code, &mut b, &ssa.args[0], &locals);
+                let array = generate_array(code, &mut b, &ssa.args[1..], &locals);
+                code.declare("call_unknown_arity", "i64", &["i64", "i32", "ptr"]);
+                b.instr(
+                    local,
+                    format_args!(
+                        "call i64 @\"call_unknown_arity\"(i64 {}, i32 {}, ptr {})",
+                        f,
+                        ssa.args.len() - 1,
+                        array
+                    ),
+                );
+            }
+
+            IrInstruction::RcInc => {
+                let arg = ssa.args.first().unwrap();
+                if matches!(
+                    arg,
+                    IrArgument::Function(_)
+                        | IrArgument::String(_)
+                        | IrArgument::Char(_)
+                        | IrArgument::Int(_)
+                ) {
+                    continue;
+                }
+
+                // Tagged function addresses are not reference counted
+                let value = generate_operand(code, &mut b, arg, &locals);
+                let tag = b.value(format_args!("and i64 {}, 1", value));
+                let untagged = b.value(format_args!("icmp eq i64 {}, 0", tag));
+                let label = b.label();
+                b.stmt(format_args!(
+                    "br i1 {}, label %{}.inc, label %{}.end",
+                    untagged, label, label
+                ));
+                b.block(&format!("{}.inc", label));
+                code.declare("rcinc", "void", &["i64"]);
+                b.stmt(format_args!("call void @\"rcinc\"(i64 {})", value));
+                b.stmt(format_args!("br label %{}.end", label));
+                b.block(&format!("{}.end", label));
+            }
+
+            IrInstruction::RcFuncFree => {
+                let arg = ssa.args.first().unwrap();
+                if matches!(
+                    arg,
+                    IrArgument::Function(_)
+                        | IrArgument::String(_)
+                        | IrArgument::Char(_)
+                        | IrArgument::Int(_)
+                ) {
+                    continue;
+                }
+
+                let value = generate_operand(code, &mut b, arg, &locals);
+                code.declare("rcfuncfree", "void", &["i64"]);
+                b.stmt(format_args!("call void @\"rcfuncfree\"(i64 {})", value));
+            }
+        }
+
+        if let Some(local) = ssa.local {
+            locals.insert(local, format!("%l{}", local));
+        }
+    }
+
+    if let Some(location) = &location {
+        b.locate(located, location);
+    }
+    code.definitions.push_str(&b.finish());
+}
+
+/// Stores arguments in an array on the stack and returns the pointer to the array.
+fn generate_array(
+    code: &mut LlvmCode,
+    b: &mut FunctionBuilder,
+    args: &[IrArgument],
+    locals: &HashMap<usize, String>,
+) -> String {
+    let array = b.value(format_args!(
+        "alloca [{} x i64], align 8",
+        args.len().max(1)
+    ));
+    for (i, arg) in args.iter().enumerate() {
+        let value = generate_operand(code, b, arg, locals);
+        let ptr = b.value(format_args!(
+            "getelementptr [{} x i64], ptr {}, i64 0, i64 {}",
+            args.len(),
+            array,
+            i
+        ));
+        b.stmt(format_args!("store i64 {}, ptr {}, align 8", value, ptr));
+    }
+    array
+}
+
+/// Generates the _start function, which sets up the allocator, calls main, and calls the exit
+/// syscall with the value main returns if `exits_with_main` is set, or with 0 otherwise.
+pub fn generate_start_func(code: &mut LlvmCode, alloc: Allocator, exits_with_main: bool) {
+    let mut b = FunctionBuilder::define("define void @\"_start\"() noreturn");
+    if let Some(init) = alloc.init_func() {
+        code.declare(init, "void", &[]);
+        b.stmt(format_args!("call void @\"{}\"()", init));
+    }
+
+    code.declare("main", "i64", &[]);
+    code.declare("exit", "void", &["i64"]);
+    if exits_with_main {
+        b.assign("%r", "call i64 @\"main\"()");
+        b.stmt("call void @\"exit\"(i64 %r)");
+    } else {
+        b.stmt("call i64 @\"main\"()");
+        b.stmt("call void @\"exit\"(i64 0)");
+    }
+    b.stmt("unreachable");
+    code.definitions.push_str(&b.finish());
+}
+
+/// Generates the functions of a module into LLVM IR without debug info. Functions whose code is in
+/// the cache for the same fingerprint are taken from it, and the others are generated in parallel
+/// and added to it. The code of each function is added in the order of the functions of the
+/// module, so the LLVM IR does not depend on how the functions were spread across threads.
+fn generate_functions(
+    code: &mut LlvmCode,
+    module: &IrModule,
+    mut cache: Option<&mut CodegenCache>,
+) {
+    // Fingerprints are only needed to find functions in the cache
+    let fingerprints: Vec<_> = match cache {
+        Some(_) => module
+            .funcs
+            .iter()
+            .map(|v| cache::fingerprint(v, module))
+            .collect(),
+        None => vec![0; module.funcs.len()],
+    };
+    let found: Vec<_> = module
+        .funcs
+        .iter()
+        .zip(fingerprints.iter())
+        .map(|(func, fingerprint)| {
+            cache
+                .as_deref()
+                .and_then(|v| v.get(&func.name, *fingerprint))
+                .cloned()
+        })
+        .collect();
+
+    let missing: Vec<_> = module
+        .funcs
+        .iter()
+        .zip(fingerprints)
+        .zip(found.iter())
+        .filter(|(_, found)| found.is_none())
+        .map(|(v, _)| v)
+        .collect();
+    let mut generated = generate_in_parallel(&missing, module).into_iter();
+
+    for (func, found) in module.funcs.iter().zip(found) {
+        let function = match found {
+            Some(v) => {
+                trace!(name = %func.name, "reused cached code");
+                v
+            }
+
+            None => generated.next().unwrap(),
+        };
+
+        code.defined.insert(func.name.clone());
+        for (name, declaration) in function.declarations.iter() {
+            code.declarations
+                .entry(name.clone())
+                .or_insert_with(|| declaration.clone());
+        }
+        code.definitions.push_str(&function.code);
+
+        if let Some(cache) = cache.as_deref_mut() {
+            cache.insert(&func.name, function);
+        }
+    }
+}
+
+/// Generates each function, given with its fingerprint, into LLVM IR of its own, so that the
+/// declarations it needs are known. Generating a function only reads the module, so the functions
+/// are split between a thread for each core, and the results are in the same order as the
+/// functions.
+fn generate_in_parallel(funcs: &[(&IrFunction, u64)], module: &IrModule) -> Vec<CachedFunction> {
+    let generate = |(func, fingerprint): &(&IrFunction, u64)| {
+        let _span = info_span!("function", name = %func.name).entered();
+        let mut own = LlvmCode::new();
+        generate_function(&mut own, func, module, None);
+        trace!("generated");
+        CachedFunction {
+            fingerprint: *fingerprint,
+            declarations: own.declarations.into_iter().collect(),
+            code: own.definitions,
+        }
+    };
+
+    // Starting a thread costs more than generating a few small functions
+    let threads = thread::available_parallelism()
+        .map_or(1, |v| v.get())
+        .min(funcs.len().div_ceil(MIN_FUNCTIONS_PER_THREAD));
+    if threads <= 1 {
+        return funcs.iter().map(generate).collect();
+    }
+
+    thread::scope(|s| {
+        let handles: Vec<_> = funcs
+            .chunks(funcs.len().div_ceil(threads))
+            .map(|chunk| s.spawn(move || chunk.iter().map(generate).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|v| v.join().unwrap())
+            .collect()
+    })
+}
+
+/// Transforms an IrModule into textual LLVM IR.
+pub fn generate_code(module: &IrModule) -> LlvmCode {
+    let mut code = LlvmCode::new();
+    generate_into(&mut code, module, None, None);
+    code
+}
+
+/// Transforms an IrModule into textual LLVM IR, reusing the code in the cache for functions whose
+/// fingerprints have not changed since it was generated. The code of the other functions is added
+/// to the cache, and functions that are no longer in the module are removed from it.
+pub fn generate_code_cached(module: &IrModule, cache: &mut CodegenCache) -> LlvmCode {
+    let mut code = LlvmCode::new();
+    generate_into(&mut code, module, None, Some(cache));
+    cache.retain_module(module);
+    code
+}
+
+/// Transforms an IrModule into textual LLVM IR with debug info, so that debuggers can step
+/// through the source code of the compiled program. The files are the files of the module, in the
+/// same order, and the first names the compile unit. The directory is the one relative paths are
+/// relative to.
+pub fn generate_code_with_debug_info(
+    module: &IrModule,
+    files: &[DebugFile],
+    dir: &str,
+) -> LlvmCode {
+    let mut code = LlvmCode::new();
+    let files_metadata: Vec<_> = files
+        .iter()
+        .map(|f| {
+            code.metadata(format!(
+                "!DIFile(filename: {}, directory: {})",
+                metadata_string(&f.path),
+                metadata_string(dir)
+            ))
+        })
+        .collect();
+
+    // Debuggers know how to show the values of C programs, which Closey values look like here
+    let unit = code.metadata(format!(
+        "distinct !DICompileUnit(language: DW_LANG_C99, file: {}, producer: {}, isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)",
+        files_metadata.first().cloned().unwrap_or_default(),
+        metadata_string(concat!("closeyc ", env!("CARGO_PKG_VERSION")))
+    ));
+    let version = code.metadata(String::from("!{i32 2, !\"Dwarf Version\", i32 4}"));
+    let info_version = code.metadata(String::from("!{i32 2, !\"Debug Info Version\", i32 3}"));
+    code.named_metadata
+        .push(format!("!llvm.dbg.cu = !{{{}}}", unit));
+    code.named_metadata.push(format!(
+        "!llvm.module.flags = !{{{}, {}}}",
+        version, info_version
+    ));
+
+    let debug = DebugInfo {
+        positions: SourcePositions::new(files),
+        files: files_metadata,
+        unit,
+    };
+    generate_into(&mut code, module, Some(&debug), None);
+    code
+}
+
+/// Generates the strings, functions, and exports of a module into LLVM IR. Functions are generated
+/// through the cache if there is one.
+fn generate_into(
+    code: &mut LlvmCode,
+    module: &IrModule,
+    debug: Option<&DebugInfo>,
+    cache: Option<&mut CodegenCache>,
+) {
+    let _span = info_span!("generate_code", backend = "llvm").entered();
+
+    // Static strings are writable, since their reference counts are updated like any other
+    for (i, string) in module.strings.iter().enumerate() {
+        writeln!(
+            code.definitions,
+            "{} = private unnamed_addr global <{{ ptr, i64, i64, i64, i64, [{} x i8] }}> <{{ ptr null, i64 {}, i64 0, i64 {}, i64 {}, [{} x i8] c\"{}\" }}>, align 8",
+            string_label(i),
+            string.len(),
+            string.len() + 8,
+            STATIC_RC,
+            string.len(),
+            string.len(),
+            escape_bytes(string)
+        )
+        .unwrap();
+    }
+    if !module.strings.is_empty() {
+        code.definitions.push('\n');
+    }
+
+    match debug {
+        // Metadata is numbered in the order it is added, so functions with debug info are
+        // generated one after another
+        Some(debug) => {
+            for func in module.funcs.iter() {
+                let _span = info_span!("function", name = %func.name).entered();
+                generate_function(code, func, module, Some(debug));
+                trace!("generated");
+            }
+        }
+
+        None => generate_functions(code, module, cache),
+    }
+
+    // Exports of functions that were removed as unused are left out
+    for export in module.exports.iter() {
+        if module.function(&export.func).is_some() {
+            generate_export(code, export);
+        }
+    }
+}
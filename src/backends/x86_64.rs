@@ -1,10 +1,11 @@
 pub mod codegen;
 
+use super::super::options::AsmSyntax;
 use super::GeneratedCode;
 
-/// Disassembles x86 machine code into human readable assembly to stdout.
-pub fn disassemble(code: &GeneratedCode, base: *const u8) {
-    use iced_x86::{Decoder, DecoderOptions, Formatter, Instruction, NasmFormatter};
+/// Disassembles x86 machine code into human readable assembly in the given syntax to stdout.
+pub fn disassemble(code: &GeneratedCode, base: *const u8, syntax: AsmSyntax) {
+    use iced_x86::{Decoder, DecoderOptions, Formatter, GasFormatter, Instruction, NasmFormatter};
 
     for (name, range) in code.func_addrs.iter() {
         println!("\n{}:", name);
@@ -16,7 +17,10 @@ pub fn disassemble(code: &GeneratedCode, base: *const u8) {
             DecoderOptions::NONE,
         );
 
-        let mut formatter = NasmFormatter::new();
+        let mut formatter: Box<dyn Formatter> = match syntax {
+            AsmSyntax::Intel => Box::new(NasmFormatter::new()),
+            AsmSyntax::Att => Box::new(GasFormatter::new()),
+        };
 
         formatter.options_mut().set_digit_separator("`");
         formatter.options_mut().set_first_operand_char_index(0);
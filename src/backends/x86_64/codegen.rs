@@ -1,11 +1,16 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use tracing::{info_span, trace};
 
 use super::super::super::backends;
+use super::super::super::frontend::ir::{CSignature, CType};
+use super::super::super::options::Allocator;
+use super::super::dwarf::VariableLocation;
 use super::super::ir::{IrArgument, IrInstruction, IrModule};
 use super::super::GeneratedCode;
 
 const ARG_REGISTER_COUNT: usize = 6;
-const NONARG_REGISTER_COUNT: usize = 8;
+const NONARG_REGISTER_COUNT: usize = 7;
 
 enum InstructionRegister {
     Bit32(u8),
@@ -83,34 +88,16 @@ impl Register {
 
         match id {
             0 => Rbx,
-            1 => Rdx,
-            2 => R10,
-            3 => R11,
-            4 => R12,
-            5 => R13,
-            6 => R14,
-            7 => R15,
+            1 => R10,
+            2 => R11,
+            3 => R12,
+            4 => R13,
+            5 => R14,
+            6 => R15,
             _ => Spilled(id - NONARG_REGISTER_COUNT),
         }
     }
 
-    fn revert_to_nonarg_register_id(&self) -> usize {
-        use Register::*;
-
-        match self {
-            Rbx => 0,
-            Rdx => 1,
-            R10 => 2,
-            R11 => 3,
-            R12 => 4,
-            R13 => 5,
-            R14 => 6,
-            R15 => 7,
-            Spilled(id) => id + NONARG_REGISTER_COUNT,
-            _ => panic!("Arguments are not not arguments!"),
-        }
-    }
-
     fn is_callee_saved(&self) -> bool {
         use Register::*;
         matches!(self, Rbx | Rsp | Rbp | R12 | R13 | R14 | R15)
@@ -143,12 +130,7 @@ impl Register {
     }
 }
 
-fn generate_mov(
-    code: &mut GeneratedCode,
-    dest: Register,
-    source: Register,
-    stack_allocated_local_count: &mut usize,
-) {
+fn generate_mov(code: &mut GeneratedCode, dest: Register, source: Register) {
     let dest_location = dest.convert_to_instr_arg();
     let source_location = source.convert_to_instr_arg();
 
@@ -186,15 +168,6 @@ fn generate_mov(
             let offset = if let InstructionRegister::Arg(a) = dest_location {
                 (a as u32 + 2) * 8
             } else if let InstructionRegister::Spilled(s) = dest_location {
-                if s <= *stack_allocated_local_count {
-                    // push reg
-                    *stack_allocated_local_count += 1;
-                    if source_location.is_64_bit() != 0 {
-                        code.data.push(0x41);
-                    }
-                    code.data.push(0x50 | source_location.get_register());
-                    return;
-                }
                 (-(s as i32 + 1) * 8) as u32
             } else {
                 unreachable!();
@@ -212,20 +185,42 @@ fn generate_mov(
 
         (false, false) => {
             // mov rax, [rbp +- offset]
-            generate_mov(code, Register::Rax, source, stack_allocated_local_count);
+            generate_mov(code, Register::Rax, source);
 
             // mov [rbp +- offset], rax
-            generate_mov(code, dest, Register::Rax, stack_allocated_local_count);
+            generate_mov(code, dest, Register::Rax);
         }
     }
 }
 
-fn generate_lea(
-    code: &mut GeneratedCode,
-    dest: Register,
-    source: &str,
-    stack_allocated_local_count: &mut usize,
-) {
+/// Restores the callee saved registers and the stack frame of the caller before returning or
+/// jumping to a function.
+fn generate_epilogue(code: &mut GeneratedCode, used_registers: &[usize]) {
+    // lea rsp, [rbp - saved]
+    if !used_registers.is_empty() {
+        code.data.push(0x48);
+        code.data.push(0x8d);
+        code.data.push(0x65);
+        code.data.push((-(used_registers.len() as i8) * 8) as u8);
+    }
+
+    // Pop used registers
+    for register in used_registers.iter().rev() {
+        let register = Register::convert_nonarg_register_id(*register).convert_to_instr_arg();
+        if register.is_64_bit() != 0 {
+            code.data.push(0x41);
+        }
+        code.data.push(0x58 | register.get_register());
+    }
+
+    // mov rsp, rbp
+    generate_mov(code, Register::Rsp, Register::Rbp);
+
+    // pop rbp
+    code.data.push(0x5d);
+}
+
+fn generate_lea(code: &mut GeneratedCode, dest: Register, source: &str) {
     let dest_location = dest.convert_to_instr_arg();
     if dest_location.is_register() {
         code.data.push(0x48 | (dest_location.is_64_bit() << 2));
@@ -245,16 +240,136 @@ fn generate_lea(
         code.data.push(0x00);
         code.data.push(0x00);
         code.data.push(0x00);
-        generate_mov(code, dest, Register::Rax, stack_allocated_local_count);
+        generate_mov(code, dest, Register::Rax);
+    }
+}
+
+/// Moves a 32 bit immediate into a register, zero extending it to 64 bits.
+fn generate_mov_immediate(code: &mut GeneratedCode, dest: Register, value: u64) {
+    let dest_location = dest.convert_to_instr_arg();
+    let register = if dest_location.is_register() {
+        dest_location.get_register()
+    } else {
+        0
+    };
+    let rex_b = dest_location.is_register() && dest_location.is_64_bit() != 0;
+
+    if let Ok(value) = u32::try_from(value) {
+        // mov dest_reg, imm32
+        // Writing to the lower half of a register clears the upper half
+        if rex_b {
+            code.data.push(0x41);
+        }
+        code.data.push(0xb8 | register);
+        code.data.extend_from_slice(&value.to_le_bytes());
+    } else {
+        // movabs dest_reg, imm64
+        code.data.push(if rex_b { 0x49 } else { 0x48 });
+        code.data.push(0xb8 | register);
+        code.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    if !dest_location.is_register() {
+        // mov dest, rax
+        generate_mov(code, dest, Register::Rax);
+    }
+}
+
+/// Moves the arguments of a call to an external function from the registers they were loaded
+/// into, one per argument, into the registers its C signature expects. Doubles are moved into the
+/// xmm registers and the remaining arguments are packed into the integer registers in order.
+fn generate_c_args(code: &mut GeneratedCode, sig: &CSignature) {
+    let mut next_int = 0;
+    let mut next_float = 0u8;
+    for (i, arg) in sig.args.iter().enumerate().take(ARG_REGISTER_COUNT) {
+        let source = Register::convert_arg_register_id(i);
+        if *arg == CType::Double {
+            // movq xmm, source
+            let source = source.convert_to_instr_arg();
+            code.data.push(0x66);
+            code.data.push(0x48 | source.is_64_bit());
+            code.data.push(0x0f);
+            code.data.push(0x6e);
+            code.data
+                .push(0xc0 | (next_float << 3) | source.get_register());
+            next_float += 1;
+        } else {
+            if next_int != i {
+                let dest = Register::convert_arg_register_id(next_int);
+                generate_mov(code, dest, source);
+            }
+            next_int += 1;
+        }
+    }
+
+    // Variadic functions read the number of xmm registers used from al
+    generate_mov_immediate(code, Register::Rax, next_float as u64);
+}
+
+/// Converts the return value of an external function from its C type back into a word in rax.
+fn generate_c_ret(code: &mut GeneratedCode, ret: CType) {
+    match ret {
+        CType::Word => (),
+
+        CType::Double => {
+            // movq rax, xmm0
+            code.data.extend_from_slice(&[0x66, 0x48, 0x0f, 0x7e, 0xc0]);
+        }
+
+        CType::Bool => {
+            // movzx eax, al
+            code.data.extend_from_slice(&[0x0f, 0xb6, 0xc0]);
+        }
+
+        CType::Char => {
+            // mov eax, eax
+            code.data.extend_from_slice(&[0x89, 0xc0]);
+        }
+
+        // Values of sized int types are stored sign extended or zero extended from their width
+        CType::Int(size) => match (size.signed, size.bits) {
+            // movsx rax, al
+            (true, 8) => code.data.extend_from_slice(&[0x48, 0x0f, 0xbe, 0xc0]),
+
+            // movsx rax, ax
+            (true, 16) => code.data.extend_from_slice(&[0x48, 0x0f, 0xbf, 0xc0]),
+
+            // movsxd rax, eax
+            (true, 32) => code.data.extend_from_slice(&[0x48, 0x63, 0xc0]),
+
+            // movzx eax, al
+            (false, 8) => code.data.extend_from_slice(&[0x0f, 0xb6, 0xc0]),
+
+            // movzx eax, ax
+            (false, 16) => code.data.extend_from_slice(&[0x0f, 0xb7, 0xc0]),
+
+            // mov eax, eax
+            (false, 32) => code.data.extend_from_slice(&[0x89, 0xc0]),
+
+            _ => (),
+        },
     }
 }
 
-/// Generates the _start function, which calls main and the exit syscall.
-pub fn generate_start_func(code: &mut GeneratedCode) {
+/// Generates the _start function, which sets up the allocator, calls main, and calls the exit
+/// syscall with the value main returns if `exits_with_main` is set, or with 0 otherwise.
+pub fn generate_start_func(code: &mut GeneratedCode, alloc: Allocator, exits_with_main: bool) {
     code.func_addrs
         .insert(String::from("_start"), code.len()..code.len() + 1);
     code.func_addrs.insert(String::from("exit"), 0..0);
 
+    if let Some(init) = alloc.init_func() {
+        code.func_addrs.insert(String::from(init), 0..0);
+
+        // call init
+        code.data.push(0xe8);
+        code.func_refs.insert(code.len(), String::from(init));
+        code.data.push(0x00);
+        code.data.push(0x00);
+        code.data.push(0x00);
+        code.data.push(0x00);
+    }
+
     // call main
     code.data.push(0xe8);
     code.func_refs.insert(code.len(), String::from("main"));
@@ -263,10 +378,16 @@ pub fn generate_start_func(code: &mut GeneratedCode) {
     code.data.push(0x00);
     code.data.push(0x00);
 
-    // mov rdi, rax
-    code.data.push(0x48);
-    code.data.push(0x89);
-    code.data.push(0xc7);
+    if exits_with_main {
+        // mov rdi, rax
+        code.data.push(0x48);
+        code.data.push(0x89);
+        code.data.push(0xc7);
+    } else {
+        // xor edi, edi
+        code.data.push(0x31);
+        code.data.push(0xff);
+    }
 
     // call exit
     code.data.push(0xe8);
@@ -279,15 +400,29 @@ pub fn generate_start_func(code: &mut GeneratedCode) {
     code.func_addrs.get_mut("_start").unwrap().end = code.len();
 }
 
+/// Returns whether calls keep the value of each register locals are allocated in, in the order of
+/// their ids, for passing to `allocate_registers`.
+pub fn callee_saved_registers() -> Vec<bool> {
+    (0..NONARG_REGISTER_COUNT)
+        .map(|r| Register::convert_nonarg_register_id(r).is_callee_saved())
+        .collect()
+}
+
 /// Transforms an IrModule into x86 machine code.
 pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
+    let _span = info_span!("generate_code", arch = "x86_64").entered();
     let mut code = GeneratedCode::new();
 
-    for func in module.funcs.iter_mut() {
-        backends::linear_scan(func, NONARG_REGISTER_COUNT);
-    }
+    let callee_saved = callee_saved_registers();
+    let spill_slots: Vec<_> = module
+        .funcs
+        .iter_mut()
+        .map(|func| backends::allocate_registers(func, &callee_saved))
+        .collect();
+
+    for (func, spilled) in module.funcs.iter().zip(spill_slots) {
+        let _span = info_span!("function", name = %func.name).entered();
 
-    for func in module.funcs.iter() {
         // Add padding
         while code.data.len() % 16 != 0 {
             code.data.push(0);
@@ -306,35 +441,37 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
         code.data.push(((func.argc >> 16) & 0xff) as u8);
         code.data.push(((func.argc >> 24) & 0xff) as u8);
 
+        // Mask of unboxed arguments
+        code.data
+            .extend_from_slice(&func.unboxed_mask().to_le_bytes());
+
         // Padding
         while code.data.len() % 16 != 0 {
             code.data.push(0);
         }
 
+        // The prologue is part of the definition of the function
+        code.map_source(func.file, func.span.clone());
+
         // push rbp
         code.data.push(0x55);
-        let mut stack_allocated_local_count = 0usize;
 
         // mov rbp, rsp
-        generate_mov(
-            &mut code,
-            Register::Rbp,
-            Register::Rsp,
-            &mut stack_allocated_local_count,
-        );
-
-        let mut used_registers = HashSet::new();
-        for ssa in func.ssas.iter() {
-            if ssa.local.is_some()
-                && Register::convert_nonarg_register_id(ssa.local_register).is_callee_saved()
-                && !used_registers.contains(&ssa.local_register)
-            {
-                used_registers.insert(ssa.local_register);
-            }
-        }
+        generate_mov(&mut code, Register::Rbp, Register::Rsp);
+
+        let mut used_registers: Vec<_> = func
+            .ssas
+            .iter()
+            .filter(|ssa| {
+                ssa.local.is_some()
+                    && Register::convert_nonarg_register_id(ssa.local_register).is_callee_saved()
+            })
+            .map(|ssa| ssa.local_register)
+            .collect();
+        used_registers.sort_unstable();
+        used_registers.dedup();
 
         // Push used registers
-        let used_registers: Vec<_> = used_registers.into_iter().collect();
         for register in used_registers.iter() {
             let register = Register::convert_nonarg_register_id(*register).convert_to_instr_arg();
             if register.is_64_bit() != 0 {
@@ -343,59 +480,73 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
             code.data.push(0x50 | register.get_register());
         }
 
-        let mut local_to_register = HashMap::new();
-        let mut register_lifetimes = vec![0; NONARG_REGISTER_COUNT];
+        // Spill slots are placed below the saved registers, keeping the stack aligned to 16 bytes
+        let reserved = (spilled + (used_registers.len() + spilled) % 2) * 8;
+        if reserved != 0 {
+            // sub rsp, reserved
+            code.data.push(0x48);
+            code.data.push(0x81);
+            code.data.push(0xec);
+            code.data
+                .extend_from_slice(&(reserved as u32).to_le_bytes());
+        }
+
+        let local_to_register: HashMap<_, _> = func
+            .ssas
+            .iter()
+            .filter_map(|ssa| {
+                let register = match Register::convert_nonarg_register_id(ssa.local_register) {
+                    Register::Spilled(s) => Register::Spilled(used_registers.len() + s),
+                    r => r,
+                };
+                ssa.local.map(|l| (l, register))
+            })
+            .collect();
+
         for ssa in func.ssas.iter() {
-            for lifetime in register_lifetimes.iter_mut() {
-                if *lifetime != 0 {
-                    *lifetime -= 1;
-                }
-            }
+            code.map_source(ssa.file, ssa.span.clone());
+            match ssa.instr {
+                IrInstruction::Ret => {
+                    match ssa.args.first() {
+                        Some(IrArgument::Local(local)) => {
+                            // mov rax, local
+                            let register = local_to_register.get(local).unwrap();
+                            generate_mov(&mut code, Register::Rax, *register);
+                        }
 
-            if let Some(local) = ssa.local {
-                let register = Register::convert_nonarg_register_id(ssa.local_register);
+                        Some(IrArgument::Argument(arg)) => {
+                            // mov rax, arg
+                            generate_mov(
+                                &mut code,
+                                Register::Rax,
+                                Register::convert_arg_register_id(*arg),
+                            );
+                        }
 
-                if register_lifetimes.len() < ssa.local_register {
-                    register_lifetimes[ssa.local_register] = ssa.local_lifetime;
-                } else {
-                    register_lifetimes.push(ssa.local_lifetime);
-                }
+                        Some(IrArgument::Function(func)) => {
+                            // lea rax, [rel func]
+                            generate_lea(&mut code, Register::Rax, func);
+                        }
 
-                local_to_register.insert(local, register);
-            }
+                        Some(IrArgument::String(i)) => {
+                            // lea rax, [rel string]
+                            generate_lea(&mut code, Register::Rax, &string_label(*i));
+                        }
 
-            match ssa.instr {
-                IrInstruction::Ret => {
-                    if let Some(IrArgument::Local(arg)) = ssa.args.first() {
-                        let register = local_to_register.get(arg).unwrap();
-                        generate_mov(
-                            &mut code,
-                            Register::Rax,
-                            *register,
-                            &mut stack_allocated_local_count,
-                        );
-                    }
+                        Some(IrArgument::Char(c)) => {
+                            // mov rax, char
+                            generate_mov_immediate(&mut code, Register::Rax, *c as u64);
+                        }
 
-                    // Pop used registers
-                    for register in used_registers.iter().rev() {
-                        let register =
-                            Register::convert_nonarg_register_id(*register).convert_to_instr_arg();
-                        if register.is_64_bit() != 0 {
-                            code.data.push(0x41);
+                        Some(IrArgument::Int(n)) => {
+                            // mov rax, int
+                            generate_mov_immediate(&mut code, Register::Rax, *n as u64);
                         }
-                        code.data.push(0x58 | register.get_register());
-                    }
 
-                    // mov rsp, rbp
-                    generate_mov(
-                        &mut code,
-                        Register::Rsp,
-                        Register::Rbp,
-                        &mut stack_allocated_local_count,
-                    );
+                        None => (),
+                    }
 
-                    // pop rbp
-                    code.data.push(0x5d);
+                    generate_epilogue(&mut code, &used_registers);
 
                     // ret
                     code.data.push(0xc3);
@@ -412,17 +563,25 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                     &mut code,
                                     local_reg,
                                     Register::convert_arg_register_id(*arg),
-                                    &mut stack_allocated_local_count,
                                 );
                             }
 
                             Some(IrArgument::Function(func)) => {
-                                generate_lea(
-                                    &mut code,
-                                    local_reg,
-                                    func,
-                                    &mut stack_allocated_local_count,
-                                );
+                                generate_lea(&mut code, local_reg, func);
+                            }
+
+                            Some(IrArgument::String(i)) => {
+                                generate_lea(&mut code, local_reg, &string_label(*i));
+                            }
+
+                            Some(IrArgument::Char(c)) => {
+                                // mov local, char
+                                generate_mov_immediate(&mut code, local_reg, *c as u64);
+                            }
+
+                            Some(IrArgument::Int(n)) => {
+                                // mov local, int
+                                generate_mov_immediate(&mut code, local_reg, *n as u64);
                             }
 
                             _ => (),
@@ -442,7 +601,13 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                             unreachable!("Arguments are called with unknown arity")
                         }
 
-                        IrArgument::Function(f) => {
+                        IrArgument::String(_) => unreachable!("Strings are not functions"),
+
+                        IrArgument::Char(_) => unreachable!("Chars are not functions"),
+
+                        IrArgument::Int(_) => unreachable!("Ints are not functions"),
+
+                        IrArgument::Function(_) => {
                             for arg in ssa.args.iter().rev() {
                                 match arg {
                                     IrArgument::Local(local) => {
@@ -456,12 +621,7 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                             code.data.push(0x50 | local_location.get_register());
                                         } else {
                                             // mov rax, [rbp - offset]
-                                            generate_mov(
-                                                &mut code,
-                                                Register::Rax,
-                                                *local_reg,
-                                                &mut stack_allocated_local_count,
-                                            );
+                                            generate_mov(&mut code, Register::Rax, *local_reg);
 
                                             // push rax
                                             code.data.push(0x50);
@@ -474,7 +634,6 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                             &mut code,
                                             Register::Rax,
                                             Register::convert_arg_register_id(*arg),
-                                            &mut stack_allocated_local_count,
                                         );
 
                                         // push rax
@@ -483,12 +642,31 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
 
                                     IrArgument::Function(f) => {
                                         // lea rax, [rel func]
-                                        generate_lea(
-                                            &mut code,
-                                            Register::Rax,
-                                            f,
-                                            &mut stack_allocated_local_count,
-                                        );
+                                        generate_lea(&mut code, Register::Rax, f);
+
+                                        // push rax
+                                        code.data.push(0x50);
+                                    }
+
+                                    IrArgument::String(i) => {
+                                        // lea rax, [rel string]
+                                        generate_lea(&mut code, Register::Rax, &string_label(*i));
+
+                                        // push rax
+                                        code.data.push(0x50);
+                                    }
+
+                                    IrArgument::Char(c) => {
+                                        // mov rax, char
+                                        generate_mov_immediate(&mut code, Register::Rax, *c as u64);
+
+                                        // push rax
+                                        code.data.push(0x50);
+                                    }
+
+                                    IrArgument::Int(n) => {
+                                        // mov rax, int
+                                        generate_mov_immediate(&mut code, Register::Rax, *n as u64);
 
                                         // push rax
                                         code.data.push(0x50);
@@ -497,12 +675,7 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                             }
 
                             // mov rax, rsp
-                            generate_mov(
-                                &mut code,
-                                Register::Rax,
-                                Register::Rsp,
-                                &mut stack_allocated_local_count,
-                            );
+                            generate_mov(&mut code, Register::Rax, Register::Rsp);
 
                             // Push arguments
                             for i in 0..func.argc {
@@ -519,42 +692,31 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                 code.data.push(0x50 | reg.get_register());
                             }
 
-                            // mov rdi, rax
-                            generate_mov(
-                                &mut code,
-                                Register::Rdi,
-                                Register::Rax,
-                                &mut stack_allocated_local_count,
-                            );
+                            // mov rdi, [rax]
+                            code.data.push(0x48);
+                            code.data.push(0x8b);
+                            code.data.push(0x38);
 
-                            // mov rsi, len
-                            let len = ssa.args.len() * 8;
+                            // mov esi, saved
+                            let saved = ssa.args.len() - 1;
                             code.data.push(0xbe);
-                            code.data.push((len & 0xff) as u8);
-                            code.data.push(((len >> 8) & 0xff) as u8);
-                            code.data.push(((len >> 16) & 0xff) as u8);
-                            code.data.push(((len >> 24) & 0xff) as u8);
+                            code.data.push((saved & 0xff) as u8);
+                            code.data.push(((saved >> 8) & 0xff) as u8);
+                            code.data.push(((saved >> 16) & 0xff) as u8);
+                            code.data.push(((saved >> 24) & 0xff) as u8);
 
-                            // mov rdx, size
-                            let mut size = 0;
-                            for func in module.funcs.iter() {
-                                if &func.name == f {
-                                    size = (func.argc + 1) * 8;
-                                    break;
-                                }
-                            }
-                            code.data.push(0xba);
-                            code.data.push((size & 0xff) as u8);
-                            code.data.push(((size >> 8) & 0xff) as u8);
-                            code.data.push(((size >> 16) & 0xff) as u8);
-                            code.data.push(((size >> 24) & 0xff) as u8);
+                            // lea rdx, [rax + 8]
+                            code.data.push(0x48);
+                            code.data.push(0x8d);
+                            code.data.push(0x50);
+                            code.data.push(0x08);
 
-                            // call rccopy
+                            // call rcclosure
                             code.data.push(0xe8);
                             code.func_refs
-                                .insert(code.data.len(), String::from("rccopy"));
-                            if !code.func_addrs.contains_key("rccopy") {
-                                code.func_addrs.insert(String::from("rccopy"), 0..0);
+                                .insert(code.data.len(), String::from("rcclosure"));
+                            if !code.func_addrs.contains_key("rcclosure") {
+                                code.func_addrs.insert(String::from("rcclosure"), 0..0);
                             }
                             code.data.push(0x00);
                             code.data.push(0x00);
@@ -562,11 +724,11 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                             code.data.push(0x00);
 
                             // Pop original arguments
-                            for i in 0..func.argc {
+                            for i in (0..func.argc).rev() {
                                 let reg =
                                     Register::convert_arg_register_id(i).convert_to_instr_arg();
                                 if !reg.is_register() {
-                                    break;
+                                    continue;
                                 }
 
                                 if reg.is_64_bit() != 0 {
@@ -576,10 +738,11 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                 code.data.push(0x58 | reg.get_register());
                             }
 
-                            // sub rsp, len
+                            // add rsp, len
+                            let len = ssa.args.len() * 8;
                             code.data.push(0x48);
                             code.data.push(0x81);
-                            code.data.push(0xec);
+                            code.data.push(0xc4);
                             code.data.push((len & 0xff) as u8);
                             code.data.push(((len >> 8) & 0xff) as u8);
                             code.data.push(((len >> 16) & 0xff) as u8);
@@ -591,20 +754,95 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                     &mut code,
                                     *local_to_register.get(&local).unwrap(),
                                     Register::Rax,
-                                    &mut stack_allocated_local_count,
                                 );
                             }
                         }
                     }
                 }
 
-                IrInstruction::Call(known_arity) => {
-                    if register_lifetimes[Register::R11.revert_to_nonarg_register_id()] != 0 {
-                        // push r11
-                        code.data.push(0x41);
-                        code.data.push(0x53);
+                IrInstruction::TailCall
+                    if ssa.args.len() - 1 <= ARG_REGISTER_COUNT
+                        && matches!(ssa.args.first(), Some(IrArgument::Function(_))) =>
+                {
+                    // The arguments are pushed before any argument register is overwritten, since
+                    // they may be the arguments of the current function
+                    for arg in ssa.args.iter().skip(1) {
+                        match arg {
+                            IrArgument::Local(local) => {
+                                // mov rax, local
+                                generate_mov(
+                                    &mut code,
+                                    Register::Rax,
+                                    *local_to_register.get(local).unwrap(),
+                                );
+                            }
+
+                            IrArgument::Argument(arg) => {
+                                // mov rax, arg
+                                generate_mov(
+                                    &mut code,
+                                    Register::Rax,
+                                    Register::convert_arg_register_id(*arg),
+                                );
+                            }
+
+                            IrArgument::Function(func) => {
+                                // lea rax, [rel func]
+                                generate_lea(&mut code, Register::Rax, func);
+                            }
+
+                            IrArgument::String(i) => {
+                                // lea rax, [rel string]
+                                generate_lea(&mut code, Register::Rax, &string_label(*i));
+                            }
+
+                            IrArgument::Char(c) => {
+                                // mov rax, char
+                                generate_mov_immediate(&mut code, Register::Rax, *c as u64);
+                            }
+
+                            IrArgument::Int(n) => {
+                                // mov rax, int
+                                generate_mov_immediate(&mut code, Register::Rax, *n as u64);
+                            }
+                        }
+
+                        // push rax
+                        code.data.push(0x50);
+                    }
+
+                    // Pop the arguments into the argument registers
+                    for i in (0..ssa.args.len() - 1).rev() {
+                        let reg = Register::convert_arg_register_id(i).convert_to_instr_arg();
+                        if reg.is_64_bit() != 0 {
+                            code.data.push(0x41);
+                        }
+                        code.data.push(0x58 | reg.get_register());
                     }
 
+                    // The called function returns directly to the caller of the current function
+                    generate_epilogue(&mut code, &used_registers);
+
+                    // jmp func
+                    let func = match ssa.args.first().unwrap() {
+                        IrArgument::Function(func) => func,
+                        _ => unreachable!(),
+                    };
+                    code.data.push(0xe9);
+                    code.func_refs.insert(code.data.len(), func.clone());
+                    code.data.push(0x10);
+                    code.data.push(0x00);
+                    code.data.push(0x00);
+                    code.data.push(0x00);
+                }
+
+                IrInstruction::Call(_) | IrInstruction::TailCall | IrInstruction::CallRuntime => {
+                    // Runtime functions always have a known arity, and functions and closures in
+                    // locals and arguments are called through the runtime like those of unknown
+                    // arity, since their code is not known
+                    let known_arity = !matches!(ssa.instr, IrInstruction::Call(false))
+                        && matches!(ssa.args.first(), Some(IrArgument::Function(_)));
+
                     // Push arguments
                     for i in 0..func.argc {
                         let reg = Register::convert_arg_register_id(i).convert_to_instr_arg();
@@ -620,98 +858,164 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                     }
 
                     if known_arity {
-                        // First 6 arguments are stored in registers
-                        for (i, arg) in ssa.args.iter().skip(1).enumerate() {
-                            let arg_reg = Register::convert_arg_register_id(i);
-
+                        // Arguments after the first 6 are stored on the stack. They are pushed
+                        // first, since they may be read from the argument registers
+                        for arg in ssa.args.iter().skip(ARG_REGISTER_COUNT + 1).rev() {
                             match arg {
                                 IrArgument::Local(local) => {
                                     let local_reg = *local_to_register.get(local).unwrap();
+                                    let local_location = local_reg.convert_to_instr_arg();
 
-                                    // mov arg, local
-                                    generate_mov(
-                                        &mut code,
-                                        arg_reg,
-                                        local_reg,
-                                        &mut stack_allocated_local_count,
-                                    );
+                                    if local_location.is_register() {
+                                        // push local
+                                        if local_location.is_64_bit() != 0 {
+                                            code.data.push(0x41);
+                                        }
+                                        code.data.push(0x50 | local_location.get_register());
+                                    } else {
+                                        // mov rax, [rbp - offset]
+                                        generate_mov(&mut code, Register::Rax, local_reg);
+
+                                        // push rax
+                                        code.data.push(0x50);
+                                    }
                                 }
 
                                 IrArgument::Argument(arg) => {
-                                    let local_reg = Register::convert_arg_register_id(*arg);
-
-                                    // mov arg, local
+                                    // mov rax, arg
                                     generate_mov(
                                         &mut code,
-                                        arg_reg,
-                                        local_reg,
-                                        &mut stack_allocated_local_count,
+                                        Register::Rax,
+                                        Register::convert_arg_register_id(*arg),
                                     );
+
+                                    // push rax
+                                    code.data.push(0x50);
                                 }
 
                                 IrArgument::Function(func) => {
-                                    // lea arg, [rel func]
-                                    generate_lea(
-                                        &mut code,
-                                        arg_reg,
-                                        func,
-                                        &mut stack_allocated_local_count,
-                                    );
+                                    // lea rax, [rel func]
+                                    generate_lea(&mut code, Register::Rax, func);
+
+                                    // push rax
+                                    code.data.push(0x50);
                                 }
-                            }
 
-                            if i == ARG_REGISTER_COUNT - 1 {
-                                break;
+                                IrArgument::String(i) => {
+                                    // lea rax, [rel string]
+                                    generate_lea(&mut code, Register::Rax, &string_label(*i));
+
+                                    // push rax
+                                    code.data.push(0x50);
+                                }
+
+                                IrArgument::Char(c) => {
+                                    // mov rax, char
+                                    generate_mov_immediate(&mut code, Register::Rax, *c as u64);
+
+                                    // push rax
+                                    code.data.push(0x50);
+                                }
+
+                                IrArgument::Int(n) => {
+                                    // mov rax, int
+                                    generate_mov_immediate(&mut code, Register::Rax, *n as u64);
+
+                                    // push rax
+                                    code.data.push(0x50);
+                                }
                             }
                         }
 
-                        // Rest of the arguments are stored on the stack
-                        for arg in ssa.args.iter().skip(ARG_REGISTER_COUNT + 1).rev() {
+                        // The first 6 arguments are stored in registers. They are pushed before any
+                        // argument register is overwritten, since they may be the arguments of the
+                        // current function
+                        let register_args = (ssa.args.len() - 1).min(ARG_REGISTER_COUNT);
+                        for arg in ssa.args.iter().skip(1).take(register_args) {
                             match arg {
                                 IrArgument::Local(local) => {
                                     let local_reg = *local_to_register.get(local).unwrap();
-                                    let local_location = local_reg.convert_to_instr_arg();
 
-                                    if local_location.is_register() {
-                                        // push local
-                                        if local_location.is_64_bit() != 0 {
-                                            code.data.push(0x41);
-                                        }
-                                        code.data.push(0x50 | local_location.get_register());
-                                    } else {
-                                        // mov rax, [rbp - offset]
-                                        generate_mov(
-                                            &mut code,
-                                            Register::Rax,
-                                            local_reg,
-                                            &mut stack_allocated_local_count,
-                                        );
-
-                                        // push rax
-                                        code.data.push(0x50);
-                                    }
+                                    // mov rax, local
+                                    generate_mov(&mut code, Register::Rax, local_reg);
                                 }
 
-                                IrArgument::Argument(_) => todo!(),
+                                IrArgument::Argument(arg) => {
+                                    let local_reg = Register::convert_arg_register_id(*arg);
+
+                                    // mov rax, local
+                                    generate_mov(&mut code, Register::Rax, local_reg);
+                                }
 
                                 IrArgument::Function(func) => {
                                     // lea rax, [rel func]
-                                    generate_lea(
-                                        &mut code,
-                                        Register::Rax,
-                                        func,
-                                        &mut stack_allocated_local_count,
-                                    );
+                                    generate_lea(&mut code, Register::Rax, func);
+                                }
 
-                                    // push rax
-                                    code.data.push(0x50);
+                                IrArgument::String(i) => {
+                                    // lea rax, [rel string]
+                                    generate_lea(&mut code, Register::Rax, &string_label(*i));
                                 }
+
+                                IrArgument::Char(c) => {
+                                    // mov rax, char
+                                    generate_mov_immediate(&mut code, Register::Rax, *c as u64);
+                                }
+
+                                IrArgument::Int(n) => {
+                                    // mov rax, int
+                                    generate_mov_immediate(&mut code, Register::Rax, *n as u64);
+                                }
+                            }
+
+                            // push rax
+                            code.data.push(0x50);
+                        }
+
+                        // Pop the arguments into the argument registers
+                        for i in (0..register_args).rev() {
+                            let reg = Register::convert_arg_register_id(i).convert_to_instr_arg();
+                            if reg.is_64_bit() != 0 {
+                                code.data.push(0x41);
                             }
+                            code.data.push(0x58 | reg.get_register());
                         }
 
                         match ssa.args.first().unwrap() {
                             IrArgument::Local(_) => todo!(),
                             IrArgument::Argument(_) => todo!(),
+                            IrArgument::String(_) => unreachable!("Strings are not functions"),
+                            IrArgument::Char(_) => unreachable!("Chars are not functions"),
+
+                            IrArgument::Int(_) => unreachable!("Ints are not functions"),
+
+                            IrArgument::Function(func)
+                                if matches!(ssa.instr, IrInstruction::CallRuntime) =>
+                            {
+                                let sig = module.externs.get(func);
+                                if let Some(sig) = sig {
+                                    generate_c_args(&mut code, sig);
+                                }
+
+                                // call func
+                                code.data.push(0xe8);
+
+                                // Insert the label
+                                code.func_refs.insert(code.data.len(), func.clone());
+                                if !code.func_addrs.contains_key(func) {
+                                    code.func_addrs.insert(func.clone(), 0..0);
+                                }
+
+                                // Runtime functions have no header to skip
+                                code.data.push(0x00);
+                                code.data.push(0x00);
+                                code.data.push(0x00);
+                                code.data.push(0x00);
+
+                                if let Some(sig) = sig {
+                                    generate_c_ret(&mut code, sig.ret);
+                                }
+                            }
 
                             IrArgument::Function(func) => {
                                 // call func
@@ -736,7 +1040,6 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                         &mut code,
                                         Register::Rax,
                                         *local_to_register.get(local).unwrap(),
-                                        &mut stack_allocated_local_count,
                                     );
                                 }
 
@@ -746,18 +1049,27 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                         &mut code,
                                         Register::Rax,
                                         Register::convert_arg_register_id(*arg),
-                                        &mut stack_allocated_local_count,
                                     );
                                 }
 
                                 IrArgument::Function(func) => {
                                     // lea rax, [rel func]
-                                    generate_lea(
-                                        &mut code,
-                                        Register::Rax,
-                                        func,
-                                        &mut stack_allocated_local_count,
-                                    );
+                                    generate_lea(&mut code, Register::Rax, func);
+                                }
+
+                                IrArgument::String(i) => {
+                                    // lea rax, [rel string]
+                                    generate_lea(&mut code, Register::Rax, &string_label(*i));
+                                }
+
+                                IrArgument::Char(c) => {
+                                    // mov rax, char
+                                    generate_mov_immediate(&mut code, Register::Rax, *c as u64);
+                                }
+
+                                IrArgument::Int(n) => {
+                                    // mov rax, int
+                                    generate_mov_immediate(&mut code, Register::Rax, *n as u64);
                                 }
                             }
 
@@ -780,7 +1092,6 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                     &mut code,
                                     Register::Rdi,
                                     *local_to_register.get(local).unwrap(),
-                                    &mut stack_allocated_local_count,
                                 );
                             }
 
@@ -790,28 +1101,23 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                     &mut code,
                                     Register::Rdi,
                                     Register::convert_arg_register_id(*arg),
-                                    &mut stack_allocated_local_count,
                                 );
                             }
 
                             IrArgument::Function(func) => {
                                 // lea rdi, [rel func]
-                                generate_lea(
-                                    &mut code,
-                                    Register::Rdi,
-                                    func,
-                                    &mut stack_allocated_local_count,
-                                );
+                                generate_lea(&mut code, Register::Rdi, func);
                             }
+
+                            IrArgument::String(_) => unreachable!("Strings are not functions"),
+
+                            IrArgument::Char(_) => unreachable!("Chars are not functions"),
+
+                            IrArgument::Int(_) => unreachable!("Ints are not functions"),
                         }
 
                         // mov rdx, rsp
-                        generate_mov(
-                            &mut code,
-                            Register::Rdx,
-                            Register::Rsp,
-                            &mut stack_allocated_local_count,
-                        );
+                        generate_mov(&mut code, Register::Rdx, Register::Rsp);
 
                         // call call_unknown_arity
                         code.data.push(0xe8);
@@ -862,20 +1168,12 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                         code.data.push(0x58 | reg.get_register());
                     }
 
-                    if register_lifetimes[Register::R11.revert_to_nonarg_register_id()] != 0 {
-                        // pop r11
-                        code.data.push(0x41);
-                        code.data.push(0x5b);
-                    }
-
                     if let Some(local) = ssa.local {
                         // mov local, rax
-                        let local_reg = Register::convert_nonarg_register_id(local);
                         generate_mov(
                             &mut code,
-                            local_reg,
+                            *local_to_register.get(&local).unwrap(),
                             Register::Rax,
-                            &mut stack_allocated_local_count,
                         );
                     }
                 }
@@ -891,27 +1189,27 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                             register = Register::convert_arg_register_id(*arg);
                         }
 
-                        IrArgument::Function(_) => (),
+                        IrArgument::Function(_)
+                        | IrArgument::String(_)
+                        | IrArgument::Char(_)
+                        | IrArgument::Int(_) => (),
                     }
 
                     if !matches!(register, Register::Rax) {
                         // mov rax, register
-                        generate_mov(
-                            &mut code,
-                            Register::Rax,
-                            register,
-                            &mut stack_allocated_local_count,
-                        );
+                        generate_mov(&mut code, Register::Rax, register);
 
                         // test al, 0x1
                         code.data.push(0xa8);
                         code.data.push(0x01);
 
-                        // jne rip+4
+                        // jne rip+5
                         code.data.push(0x75);
-                        code.data.push(0x04);
+                        code.data.push(0x05);
 
-                        // add dword ptr [rax - 8], 0x1
+                        // lock add dword ptr [rax - 8], 0x1
+                        // The increment is atomic since closures may be shared between threads
+                        code.data.push(0xf0);
                         code.data.push(0x83);
                         code.data.push(0x40);
                         code.data.push(0xf8);
@@ -920,7 +1218,13 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                 }
 
                 IrInstruction::RcFuncFree => {
-                    if !matches!(ssa.args.first().unwrap(), IrArgument::Function(_)) {
+                    if !matches!(
+                        ssa.args.first().unwrap(),
+                        IrArgument::Function(_)
+                            | IrArgument::String(_)
+                            | IrArgument::Char(_)
+                            | IrArgument::Int(_)
+                    ) {
                         // Push arguments
                         for i in 0..func.argc {
                             let reg = Register::convert_arg_register_id(i).convert_to_instr_arg();
@@ -945,16 +1249,16 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                 register = Register::convert_arg_register_id(*arg);
                             }
 
-                            &IrArgument::Function(_) => unreachable!(),
+                            &IrArgument::Function(_)
+                            | &IrArgument::String(_)
+                            | &IrArgument::Char(_)
+                            | &IrArgument::Int(_) => {
+                                unreachable!()
+                            }
                         }
 
                         // mov rdi, register
-                        generate_mov(
-                            &mut code,
-                            Register::Rdi,
-                            register,
-                            &mut stack_allocated_local_count,
-                        );
+                        generate_mov(&mut code, Register::Rdi, register);
 
                         // call rcfuncfree
                         code.data.push(0xe8);
@@ -979,33 +1283,151 @@ pub fn generate_code(module: &mut IrModule) -> GeneratedCode {
                                 code.data.push(0x41);
                             }
 
-                            code.data.push(0x50 | reg.get_register());
+                            code.data.push(0x58 | reg.get_register());
                         }
                     }
                 }
             }
         }
         code.func_addrs.get_mut(&func.name).unwrap().end = code.len();
+        trace!(
+            bytes = code.func_addrs.get(&func.name).unwrap().len(),
+            "generated"
+        );
+    }
+
+    for (i, string) in module.strings.iter().enumerate() {
+        generate_string(&mut code, &string_label(i), string);
     }
 
     code
 }
 
+/// Returns the label of the static string with the given index.
+fn string_label(i: usize) -> String {
+    format!(".string.{}", i)
+}
+
+/// Generates a static string. Strings are laid out like reference counted strings in the runtime
+/// library, as the length followed by the bytes, but are never freed.
+fn generate_string(code: &mut GeneratedCode, label: &str, string: &str) {
+    // Add padding
+    while code.data.len() % 16 != 0 {
+        code.data.push(0);
+    }
+
+    // Offset by 1, since addresses are loaded with an offset of 1
+    code.func_addrs
+        .insert(String::from(label), code.len()..code.len());
+    code.data.push(0x00);
+
+    // Length
+    code.data
+        .extend_from_slice(&(string.len() as u64).to_le_bytes());
+
+    // Bytes
+    code.data.extend_from_slice(string.as_bytes());
+    code.func_addrs.get_mut(label).unwrap().end = code.len();
+}
+
+/// Returns where an argument of a function is kept, for debug info. Arguments passed in registers
+/// are saved and restored around calls, so they are in their registers whenever the code of an
+/// instruction of the backend IR starts.
+pub fn arg_location(arg: usize) -> VariableLocation {
+    use Register::*;
+
+    // DWARF numbers the registers in its own order
+    match Register::convert_arg_register_id(arg) {
+        Rdi => VariableLocation::Register(5),
+        Rsi => VariableLocation::Register(4),
+        Rdx => VariableLocation::Register(1),
+        Rcx => VariableLocation::Register(2),
+        R8 => VariableLocation::Register(8),
+        R9 => VariableLocation::Register(9),
+
+        // The frame pointer is register 6
+        Arg(a) => VariableLocation::Memory(6, (a as i64 + 2) * 8),
+
+        _ => unreachable!("Arguments are in argument registers or on the stack"),
+    }
+}
+
+/// Returns the addend of the reference to a function at an offset into the code. This is the value
+/// left at that offset, such as the size of the header that calls skip, less the size of the
+/// reference itself, since references are relative to the end of the instruction. Object files
+/// keep addends in their relocations rather than in the code, so they must be given this addend.
+pub fn relocation_addend(code: &GeneratedCode, code_addr: usize) -> i32 {
+    let mut value = [0; 4];
+    value.copy_from_slice(&code.data[code_addr..code_addr + 4]);
+    i32::from_le_bytes(value) - 4
+}
+
+/// Adds a stub to the end of the code for each runtime function it calls, which jumps to the
+/// address `resolve` gives for the function. Calls are relocated to the stubs rather than the
+/// functions themselves, since a call cannot reach a function more than 2GiB away. Returns the
+/// names of the runtime functions `resolve` has no address for.
+pub fn generate_runtime_stubs(
+    code: &mut GeneratedCode,
+    resolve: impl Fn(&str) -> Option<usize>,
+) -> Vec<String> {
+    // Runtime functions are given empty ranges, since they are not in the code
+    let mut runtime: Vec<_> = code
+        .func_addrs
+        .iter()
+        .filter(|(_, range)| range.is_empty())
+        .map(|(name, _)| name.clone())
+        .collect();
+    runtime.sort();
+
+    let mut missing = vec![];
+    for name in runtime {
+        let addr = match resolve(&name) {
+            Some(v) => v as u64,
+            None => {
+                missing.push(name);
+                continue;
+            }
+        };
+
+        code.data.resize((code.len() + 15) & !15, 0);
+
+        // jmp [rip]
+        let start = code.len();
+        code.data
+            .extend_from_slice(&[0xff, 0x25, 0x00, 0x00, 0x00, 0x00]);
+        code.data.extend_from_slice(&addr.to_le_bytes());
+        code.func_addrs.insert(name, start..code.len());
+    }
+    missing
+}
+
 /// Relocates all function addresses to their offset.
 pub fn relocate(code: &mut GeneratedCode) {
-    for (code_addr, func) in code.func_refs.iter() {
-        if let Some(range) = code.func_addrs.get(func) {
-            let addr = ((range.start as i32 - *code_addr as i32) as i64
-                + unsafe { *(code.data.as_ptr().add(*code_addr) as *const i32) } as i64
-                - 4) as u64;
-
-            for (i, byte) in code.data.iter_mut().skip(*code_addr).enumerate() {
-                if i >= 4 {
-                    break;
-                }
+    relocate_at(code, 0, &HashMap::new());
+}
 
-                *byte = ((addr >> (i * 8)) & 0xff) as u8;
+/// Relocates code that is placed at an offset into a region of memory, given the offsets of the
+/// functions already placed in that region. References to functions in neither the code nor the
+/// region are left alone.
+pub fn relocate_at(code: &mut GeneratedCode, offset: usize, placed: &HashMap<String, usize>) {
+    for (code_addr, func) in code.func_refs.iter() {
+        let target = match code.func_addrs.get(func) {
+            Some(range) => range.start + offset,
+            None => match placed.get(func) {
+                Some(v) => *v,
+                None => continue,
+            },
+        };
+
+        let addr = ((target as i64 - (*code_addr + offset) as i64)
+            + relocation_addend(code, *code_addr) as i64) as u64;
+
+        for (i, byte) in code.data.iter_mut().skip(*code_addr).enumerate() {
+            if i >= 4 {
+                break;
             }
+
+            *byte = ((addr >> (i * 8)) & 0xff) as u8;
         }
     }
 }
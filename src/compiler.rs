@@ -0,0 +1,212 @@
+use codespan_reporting::diagnostic::Severity;
+use tracing::info_span;
+
+use crate::backends::{self, ir as backend_ir, llvm, x86_64, GeneratedCode};
+use crate::diagnostics::{self, Diagnostic, Renderer};
+use crate::frontend::correctness;
+use crate::frontend::exhaustive;
+use crate::frontend::fold;
+use crate::frontend::ir::{self, Ir};
+use crate::frontend::lints;
+use crate::frontend::parser::{self, Ast};
+use crate::frontend::prelude;
+use crate::frontend::specialise;
+use crate::frontend::uncurry;
+use crate::options::{Arch, CompilerOptions};
+
+/// The result of a stage of compilation. The value is missing if the stage reported an error, and
+/// the diagnostics hold every error and warning reported, in the order they were found.
+pub struct Output<T> {
+    /// The output of the stage, if it succeeded.
+    pub value: Option<T>,
+
+    /// The errors and warnings reported by the stage.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl<T> Output<T> {
+    /// Creates an output from the value of a stage and the diagnostics it reported.
+    fn new(value: Option<T>, diagnostics: Vec<Diagnostic>) -> Output<T> {
+        Output { value, diagnostics }
+    }
+
+    /// Returns true if the stage succeeded, even if it reported warnings.
+    pub fn is_ok(&self) -> bool {
+        self.value.is_some()
+    }
+
+    /// Returns the diagnostics that are errors.
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|v| v.severity >= Severity::Error)
+    }
+}
+
+/// Drives compilation for tools that use closeyc as a library, such as editors, build systems, and
+/// test harnesses. Each stage takes the output of the one before it, and diagnostics are returned
+/// rather than printed, pointing into the files the compiler has seen so far.
+pub struct Compiler {
+    options: CompilerOptions,
+    files: Renderer,
+}
+
+impl Compiler {
+    /// Creates a compiler with the given options.
+    pub fn new(options: CompilerOptions) -> Compiler {
+        Compiler {
+            options,
+            files: Renderer::new(),
+        }
+    }
+
+    /// Returns the options the compiler was created with.
+    pub fn options(&self) -> &CompilerOptions {
+        &self.options
+    }
+
+    /// Returns the renderer that prints diagnostics with the source they point at. Every file
+    /// passed to `parse` or `check` is added to it.
+    pub fn renderer(&self) -> &Renderer {
+        &self.files
+    }
+
+    /// Parses a file into a list of top level definitions.
+    pub fn parse(&mut self, name: &str, source: &str) -> Output<Vec<Ast>> {
+        let file_id = self.files.add_file(name, source);
+        match parser::parse(source) {
+            Ok(ast) => Output::new(Some(ast), vec![]),
+            Err(errors) => Output::new(
+                None,
+                errors
+                    .iter()
+                    .map(|e| diagnostics::parse_diagnostic(e, file_id))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Parses and checks a file, returning its IR with constant expressions folded and applications
+    /// uncurried. The file is converted into a module with the given name, preceded by the prelude
+    /// if it is enabled, then linted, type checked, and checked for incomplete matches.
+    pub fn check(&mut self, name: &str, source: &str) -> Output<Ir> {
+        let _span = info_span!("check", name).entered();
+        let parsed = self.parse(name, source);
+        let ast = match parsed.value {
+            Some(v) => v,
+            None => return Output::new(None, parsed.diagnostics),
+        };
+
+        let prelude = if self.options.prelude {
+            prelude::parse()
+        } else {
+            vec![]
+        };
+
+        let mut root = Ir::new();
+        if let Err(errors) = ir::convert_ast_to_ir(name, source, prelude, ast, &mut root) {
+            let diagnostics = errors
+                .iter()
+                .map(|e| diagnostics::ir_diagnostic(e, &self.files))
+                .collect();
+            return Output::new(None, diagnostics);
+        }
+
+        // Lints only look at names, so they are checked before expressions they cannot type yet
+        let mut diagnostics: Vec<_> = lints::check_lints(&root)
+            .iter()
+            .filter_map(|w| diagnostics::lint_diagnostic(w, &self.files, &self.options.lints))
+            .collect();
+        if has_errors(&diagnostics) {
+            return Output::new(None, diagnostics);
+        }
+
+        let errors = correctness::check_correctness(&mut root, true, self.options.warn_lossy);
+        diagnostics.extend(
+            errors
+                .iter()
+                .map(|e| diagnostics::correctness_diagnostic(e, &self.files)),
+        );
+        if has_errors(&diagnostics) {
+            return Output::new(None, diagnostics);
+        }
+
+        diagnostics.extend(
+            exhaustive::check_matches(&root)
+                .iter()
+                .filter_map(|e| diagnostics::match_diagnostic(e, &self.files, &self.options.lints)),
+        );
+        if has_errors(&diagnostics) {
+            return Output::new(None, diagnostics);
+        }
+
+        fold::fold_constants(&mut root);
+        uncurry::uncurry_applications(&mut root);
+        specialise::specialise_applications(&mut root);
+        Output::new(Some(root), diagnostics)
+    }
+
+    /// Lowers a checked module into the backend IR, or returns None if there is no module with the
+    /// given name. Every function is kept; `backends::ir::remove_unused_functions` leaves out the
+    /// ones an executable or library does not need.
+    pub fn lower(&self, ir: &Ir, module: &str) -> Option<backend_ir::IrModule> {
+        ir.module(module).map(|m| {
+            backend_ir::convert_frontend_ir_to_backend_ir(m, backend_ir::Checks::new(&self.options))
+        })
+    }
+
+    /// Generates textual LLVM IR for a lowered module. Executables also get a `_start` function
+    /// that sets up the allocator and calls `main`.
+    pub fn codegen_llvm(&self, module: &backend_ir::IrModule, executable: bool) -> String {
+        let mut code = llvm::codegen::generate_code(module);
+        if executable {
+            llvm::codegen::generate_start_func(
+                &mut code,
+                self.options.alloc,
+                module.exits_with_main,
+            );
+        }
+        code.to_string()
+    }
+
+    /// Generates machine code for a lowered module with the native backend for the architecture in
+    /// the options. Executables also get a `_start` function that sets up the allocator and calls
+    /// `main`.
+    pub fn codegen_native(
+        &self,
+        module: &mut backend_ir::IrModule,
+        executable: bool,
+    ) -> Output<GeneratedCode> {
+        match self.options.arch {
+            Arch::X86_64 => {
+                let mut code = x86_64::codegen::generate_code(module);
+                if executable {
+                    x86_64::codegen::generate_start_func(
+                        &mut code,
+                        self.options.alloc,
+                        module.exits_with_main,
+                    );
+                }
+                Output::new(Some(code), vec![])
+            }
+
+            arch => Output::new(
+                None,
+                vec![diagnostics::error(format!(
+                    "the native backend cannot generate code for {} yet",
+                    arch
+                ))],
+            ),
+        }
+    }
+
+    /// Generates a C header declaring the functions of a lowered module annotated with `@export`.
+    pub fn c_header(&self, module: &backend_ir::IrModule, name: &str) -> String {
+        backends::generate_c_header(module, name)
+    }
+}
+
+/// Returns true if any of the diagnostics is an error.
+fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|v| v.severity >= Severity::Error)
+}
@@ -0,0 +1,531 @@
+use codespan_reporting::diagnostic::{self, Label, Severity};
+use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use codespan_reporting::term::{self, Config};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::frontend::correctness::CorrectnessError;
+use crate::frontend::exhaustive::MatchError;
+use crate::frontend::ir::{BinOp, IrError, IrTest, Location};
+use crate::frontend::lints::LintWarning;
+use crate::frontend::parser::ParseError;
+use crate::frontend::prelude;
+use crate::frontend::pretty;
+use crate::frontend::types::{IntSize, Type};
+use crate::interpreter::InterpreterError;
+use crate::options::{Lint, LintLevel, LintLevels};
+
+/// A problem found while compiling. Its labels point into the files of a `Renderer`.
+pub type Diagnostic = diagnostic::Diagnostic<usize>;
+
+/// The name of the empty file that locations in unknown files point into.
+const UNKNOWN_FILE: &str = "<unknown>";
+
+/// Renders diagnostics to stderr. Each label is shown with the line it points at underlined,
+/// below the file name, line, and column it starts at. Colours are only used when stderr is a
+/// terminal, so that errors written to files or pipes stay readable.
+pub struct Renderer {
+    files: SimpleFiles<String, String>,
+    ids: HashMap<String, usize>,
+    color: ColorChoice,
+}
+
+impl Default for Renderer {
+    fn default() -> Renderer {
+        let color = if unsafe { libc::isatty(libc::STDERR_FILENO) } == 1 {
+            ColorChoice::Auto
+        } else {
+            ColorChoice::Never
+        };
+
+        let mut renderer = Renderer {
+            files: SimpleFiles::new(),
+            ids: HashMap::new(),
+            color,
+        };
+
+        // Every module can have locations in the prelude
+        renderer.add_file(UNKNOWN_FILE, "");
+        renderer.add_file(prelude::FILENAME, prelude::SOURCE);
+        renderer
+    }
+}
+
+impl Renderer {
+    /// Creates a renderer that knows about the prelude.
+    pub fn new() -> Renderer {
+        Renderer::default()
+    }
+
+    /// Adds a source file, returning its id. Locations with the file's name point into the last
+    /// file added with that name.
+    pub fn add_file(&mut self, name: &str, source: &str) -> usize {
+        let id = self.files.add(String::from(name), String::from(source));
+        self.ids.insert(String::from(name), id);
+        id
+    }
+
+    /// Returns the id of the file a location is in. Locations in files that were never added point
+    /// into an empty file.
+    pub fn file_id(&self, loc: &Location) -> usize {
+        self.ids
+            .get(&loc.filename)
+            .or_else(|| self.ids.get(UNKNOWN_FILE))
+            .copied()
+            .unwrap()
+    }
+
+    /// Returns a label pointing at a location.
+    pub fn primary(&self, loc: &Location) -> Label<usize> {
+        Label::primary(self.file_id(loc), loc.span.clone())
+    }
+
+    /// Returns a label pointing at a location that explains the primary label.
+    pub fn secondary(&self, loc: &Location) -> Label<usize> {
+        Label::secondary(self.file_id(loc), loc.span.clone())
+    }
+
+    /// Returns the files that diagnostics point into.
+    pub fn files(&self) -> &SimpleFiles<String, String> {
+        &self.files
+    }
+
+    /// Writes a diagnostic to stderr.
+    pub fn emit(&self, diagnostic: &Diagnostic) {
+        // Failing to write to stderr leaves nowhere to report the failure
+        let writer = StandardStream::stderr(self.color);
+        let _ = term::emit(
+            &mut writer.lock(),
+            &Config::default(),
+            &self.files,
+            diagnostic,
+        );
+    }
+
+    /// Writes a diagnostic for an error without a location, such as an error from generating code
+    /// or linking.
+    pub fn error<T: Display>(&self, message: T) {
+        self.emit(&error(message));
+    }
+}
+
+/// Creates the diagnostic for an error without a location.
+pub fn error<T: Display>(message: T) -> Diagnostic {
+    Diagnostic::error().with_message(message.to_string())
+}
+
+/// Creates the diagnostic reported for a syntax error.
+pub fn parse_diagnostic(e: &ParseError, file_id: usize) -> Diagnostic {
+    Diagnostic::error()
+        .with_message(&e.msg)
+        .with_labels(vec![Label::primary(file_id, e.span.clone())])
+}
+
+/// Creates the diagnostic reported for an error found while converting syntax into IR.
+pub fn ir_diagnostic(e: &IrError, files: &Renderer) -> Diagnostic {
+    let diagnostic = Diagnostic::error();
+    match e {
+        IrError::InvalidType(s) => diagnostic
+            .with_message("Invalid type used")
+            .with_labels(vec![files.primary(s).with_message("Undeclared type")]),
+
+        IrError::DuplicateTypeInUnion(s1, s2, t) => {
+            let t = pretty::type_to_string(t);
+            diagnostic
+                .with_message("Duplicate type in union type declaration")
+                .with_labels(vec![
+                    files.secondary(s1).with_message("Type used here first"),
+                    files
+                        .primary(s2)
+                        .with_message(format!("Type `{}` used a second time here", t)),
+                ])
+        }
+
+        IrError::DoubleExport(s1, s2, e) => diagnostic
+            .with_message("Value exported twice")
+            .with_labels(vec![
+                files
+                    .secondary(s1)
+                    .with_message("Value exported here first"),
+                files
+                    .primary(s2)
+                    .with_message(format!("Value {} exported a second time here", e)),
+            ]),
+
+        IrError::RedefineImportAlias(s1, s2, a) => diagnostic
+            .with_message("Alias defined twice")
+            .with_labels(vec![
+                files.secondary(s1).with_message("Alias defined here first"),
+                files
+                    .primary(s2)
+                    .with_message(format!("Alias {} defined a second time here", a)),
+            ]),
+
+        IrError::UnsupportedAnnotation(s, a) => diagnostic
+            .with_message("Unsupported annotation used")
+            .with_labels(vec![files
+                .primary(s)
+                .with_message(format!("Annotation {} is unsupported", a))]),
+
+        IrError::InvalidFFIType(s, t) => {
+            let t = pretty::type_to_string(t);
+            diagnostic
+                .with_message("Unsupported type used for FFI")
+                .with_labels(vec![files
+                    .primary(s)
+                    .with_message(format!("Type `{}` is unsupported by FFI", t))])
+        }
+
+        IrError::DuplicateModule(v, _t) => {
+            diagnostic.with_message(format!("Duplicate module `{}`", v))
+        }
+
+        IrError::InvalidConstructor(s) => diagnostic
+            .with_message("Invalid constructor in type declaration")
+            .with_labels(vec![files.primary(s).with_message(
+                "Expected a lowercase constructor name followed by the types of its fields",
+            )]),
+
+        IrError::DuplicateConstructor(s1, s2, c) => diagnostic
+            .with_message("Constructor declared twice")
+            .with_labels(vec![
+                files
+                    .secondary(s1)
+                    .with_message("Constructor declared here first"),
+                files
+                    .primary(s2)
+                    .with_message(format!("Constructor `{}` declared a second time here", c)),
+            ]),
+
+        IrError::InfiniteType(s, t) => diagnostic
+            .with_message("Type has no finite values")
+            .with_labels(vec![files.primary(s).with_message(format!(
+                "Every constructor of `{}` needs a value that can never be built",
+                t
+            ))])
+            .with_notes(vec![format!(
+                "Add a constructor whose fields do not need a `{}`, such as one without fields",
+                t
+            )]),
+    }
+}
+
+/// Applies the level of the lint that reported a warning. Allowed lints are not reported, and
+/// denied lints are reported as errors.
+fn lint_level(diagnostic: Diagnostic, lint: Lint, levels: &LintLevels) -> Option<Diagnostic> {
+    match levels.level(lint) {
+        LintLevel::Allow => None,
+        LintLevel::Warn => Some(diagnostic.with_notes(vec![format!(
+            "Reported by the `{}` lint, which `-A {}` turns off",
+            lint, lint
+        )])),
+        LintLevel::Deny => {
+            let mut diagnostic = diagnostic.with_notes(vec![format!(
+                "Reported by the `{}` lint, which is denied",
+                lint
+            )]);
+            diagnostic.severity = Severity::Error;
+            Some(diagnostic)
+        }
+    }
+}
+
+/// Creates the diagnostic reported for a problem with the arms of a match expression, if it is
+/// reported at all. Unreachable arms are reported by the `unreachable_arms` lint.
+pub fn match_diagnostic(
+    e: &MatchError,
+    files: &Renderer,
+    levels: &LintLevels,
+) -> Option<Diagnostic> {
+    match e {
+        MatchError::NonExhaustive(loc, missing) => {
+            let missing: Vec<_> = missing.iter().map(|v| format!("`{}`", v)).collect();
+            Some(
+                Diagnostic::error()
+                    .with_message("Match expression does not match every value")
+                    .with_labels(vec![files.primary(loc).with_message(format!(
+                        "Patterns {} not matched",
+                        missing.join(", ")
+                    ))]),
+            )
+        }
+
+        MatchError::UnreachableArm(loc) => lint_level(
+            Diagnostic::warning()
+                .with_message("Unreachable match arm")
+                .with_labels(vec![files.primary(loc).with_message(
+                    "Every value matched here is matched by an earlier arm",
+                )]),
+            Lint::UnreachableArms,
+            levels,
+        ),
+    }
+}
+
+/// Creates the diagnostic reported for code found by a lint, if the lint is not allowed.
+pub fn lint_diagnostic(
+    w: &LintWarning,
+    files: &Renderer,
+    levels: &LintLevels,
+) -> Option<Diagnostic> {
+    let (diagnostic, lint) = match w {
+        LintWarning::UnusedVariable(loc, name) => (
+            Diagnostic::warning()
+                .with_message(format!("Unused variable `{}`", name))
+                .with_labels(vec![files
+                    .primary(loc)
+                    .with_message(format!("`{}` is bound here but never used", name))]),
+            Lint::UnusedVariables,
+        ),
+
+        LintWarning::UnusedArgument(loc, name) => (
+            Diagnostic::warning()
+                .with_message(format!("Unused argument `{}`", name))
+                .with_labels(vec![files.primary(loc).with_message(format!(
+                    "This function never uses its argument `{}`",
+                    name
+                ))]),
+            Lint::UnusedArguments,
+        ),
+
+        LintWarning::Shadowing(loc, name) => (
+            Diagnostic::warning()
+                .with_message(format!("Variable `{}` shadows another variable", name))
+                .with_labels(vec![files.primary(loc).with_message(format!(
+                    "`{}` is already bound in an enclosing scope",
+                    name
+                ))]),
+            Lint::Shadowing,
+        ),
+    };
+
+    // Unused variables can be silenced one at a time as well as all at once
+    let diagnostic = match w {
+        LintWarning::UnusedVariable(_, name) | LintWarning::UnusedArgument(_, name) => diagnostic
+            .with_notes(vec![format!(
+                "Rename it to `_{}` if it is unused on purpose",
+                name
+            )]),
+        LintWarning::Shadowing(_, _) => diagnostic,
+    };
+    lint_level(diagnostic, lint, levels)
+}
+
+/// Creates the diagnostic reported for a type error or warning found by the correctness checker.
+pub fn correctness_diagnostic(e: &CorrectnessError, files: &Renderer) -> Diagnostic {
+    match e {
+        CorrectnessError::InvalidOperands(loc, op, left, right) => {
+            let types = pretty::types_to_strings(&[left, right]);
+            let mut notes = vec![format!(
+                "`{}` takes two {} of the same type",
+                op,
+                op.operand_types()
+            )];
+
+            // Bools have operators of their own rather than the bitwise operators
+            let bool_op = match op {
+                BinOp::BitAnd => Some("and"),
+                BinOp::BitXor => Some("xor"),
+                BinOp::BitOr => Some("or"),
+                _ => None,
+            };
+            if let Some(bool_op) = bool_op.filter(|_| **left == Type::Bool) {
+                notes.push(format!("Use `{}` to combine `Bool`s", bool_op));
+            }
+
+            Diagnostic::error()
+                .with_message(format!("Invalid operands for `{}`", op))
+                .with_labels(vec![files.primary(loc)
+                    .with_message(format!(
+                        "`{}` cannot be applied to `{}` and `{}`",
+                        op, types[0], types[1]
+                    ))])
+                .with_notes(notes)
+        }
+
+        CorrectnessError::InvalidOperand(loc, op, operand) => Diagnostic::error()
+            .with_message(format!("Invalid operand for `{}`", op))
+            .with_labels(vec![files.primary(loc).with_message(format!(
+                "`{}` cannot be applied to `{}`",
+                op,
+                pretty::type_to_string(operand)
+            ))])
+            .with_notes(vec![format!(
+                "`{}` takes {}",
+                op,
+                op.operand_types()
+            )]),
+
+        CorrectnessError::InexactLiteral(loc, n) => Diagnostic::warning()
+            .with_message("Int literal is not exactly representable as a float")
+            .with_labels(vec![files.primary(loc)
+                .with_message(format!("This is converted to `{}`", *n as f64))])
+            .with_notes(vec![String::from(
+                "Ints mixed with floats are converted into floats, which only hold ints up to 2^53 exactly",
+            )]),
+
+        CorrectnessError::LiteralOutOfRange(loc, n, _type) => {
+            let size = IntSize::of(_type).unwrap();
+            Diagnostic::error()
+                .with_message(format!(
+                    "Int literal is out of the range of `{}`",
+                    size.name()
+                ))
+                .with_labels(vec![files
+                    .primary(loc)
+                    .with_message(format!("`{}` does not fit in `{}`", n, size.name()))])
+                .with_notes(vec![format!(
+                    "`{}` holds values from {} to {}",
+                    size.name(),
+                    size.min(),
+                    size.max()
+                )])
+        }
+
+        CorrectnessError::LossyConversion(loc, from, to) => {
+            let types = pretty::types_to_strings(&[from, to]);
+            Diagnostic::warning()
+                .with_message("Implicit conversion may lose precision")
+                .with_labels(vec![files.primary(loc).with_message(
+                    format!("This is converted from `{}` to `{}`", types[0], types[1]),
+                )])
+                .with_notes(vec![format!(
+                    "`{}`s beyond 2^53 are rounded to the nearest `{}`",
+                    types[0], types[1]
+                )])
+        }
+
+        CorrectnessError::UnknownVariable(loc, name) => Diagnostic::error()
+            .with_message(format!("Unknown variable `{}`", name))
+            .with_labels(vec![files
+                .primary(loc)
+                .with_message("Not defined in this scope or as a global")]),
+
+        CorrectnessError::InvalidConversion(loc, from, to) => {
+            let types = pretty::types_to_strings(&[from, to]);
+            Diagnostic::error()
+                .with_message("Invalid conversion")
+                .with_labels(vec![files.primary(loc).with_message(
+                    format!("`{}` cannot be converted to `{}`", types[0], types[1]),
+                )])
+                .with_notes(vec![String::from(
+                    "`as` converts between `Int` and `Float`, between `Int` and `Char`, from `Bool` to `Int`, and into supertypes",
+                )])
+        }
+
+        CorrectnessError::MismatchedAscription(loc, found, expected) => {
+            let types = pretty::types_to_strings(&[found, expected]);
+            Diagnostic::error()
+                .with_message("Value does not have the type it is ascribed")
+                .with_labels(vec![files
+                    .primary(loc)
+                    .with_message(format!("This has type `{}`, not `{}`", types[0], types[1]))])
+                .with_notes(vec![String::from(
+                    "`(value : Type)` only checks the type of the value, and `as` converts it",
+                )])
+        }
+
+        CorrectnessError::InvalidExport(loc, name, _type) => Diagnostic::error()
+            .with_message(format!("`{}` cannot be exported to C", name))
+            .with_labels(vec![files.primary(loc).with_message(format!(
+                "`{}` has type `{}`",
+                name,
+                pretty::type_to_string(_type)
+            ))])
+            .with_notes(vec![String::from(
+                "Exported functions can only take and return ints, floats, bools, chars, strings, arrays, maps, results, options, and values of data types",
+            )]),
+
+        CorrectnessError::MismatchedArms(loc, arm, first) => {
+            let types = pretty::types_to_strings(&[arm, first]);
+            Diagnostic::error()
+                .with_message("Match arms have different types")
+                .with_labels(vec![files
+                    .primary(loc)
+                    .with_message(format!("This has type `{}`", types[0]))])
+                .with_notes(vec![format!(
+                    "Every arm must have the type of the first arm, `{}`, including the branches of an `if` and the operands of `and` and `or`",
+                    types[1]
+                )])
+        }
+
+        CorrectnessError::InvalidPattern(loc, pattern, _type) => {
+            let diagnostic = Diagnostic::error()
+                .with_message("Invalid pattern")
+                .with_labels(vec![files.primary(loc).with_message(format!(
+                    "`{}` cannot match values of type `{}`",
+                    pattern,
+                    pretty::type_to_string(_type)
+                ))]);
+
+            // The patterns of an `if`, `and`, or `or` point at the value they match
+            if pattern == "true" || pattern == "false" {
+                diagnostic.with_notes(vec![String::from(
+                    "The condition of an `if` and the left operand of `and` and `or` are matched against `true` and `false`, so they must be `Bool`s",
+                )])
+            } else {
+                diagnostic
+            }
+        }
+
+        CorrectnessError::InvalidPropagation(loc, _type) => Diagnostic::error()
+            .with_message("Invalid use of `?`")
+            .with_labels(vec![files.primary(loc).with_message(format!(
+                "`?` cannot be applied to `{}`",
+                pretty::type_to_string(_type)
+            ))])
+            .with_notes(vec![String::from(
+                "`?` takes a `Result` or an `Option`",
+            )]),
+
+        CorrectnessError::MismatchedPropagation(loc, value, body) => {
+            let types = pretty::types_to_strings(&[value, body]);
+            let (kind, propagated) = if let Type::Result(_) = **value {
+                ("a `Result`", "the error")
+            } else {
+                ("an `Option`", "`none`")
+            };
+            Diagnostic::error()
+                .with_message(format!("`?` used where {} is not returned", kind))
+                .with_labels(vec![files.primary(loc).with_message(format!(
+                    "This propagates `{}` out of a value of type `{}`",
+                    types[0], types[1]
+                ))])
+                .with_notes(vec![format!(
+                    "`?` gives {} as the value of the enclosing function, lambda, or match arm, so that value must be {} as well",
+                    propagated, kind
+                )])
+        }
+
+        CorrectnessError::InvalidTest(loc, name, _type) => Diagnostic::error()
+            .with_message(format!("Test \"{}\" is not a `Bool`", name))
+            .with_labels(vec![files.primary(loc).with_message(format!(
+                "This has type `{}`",
+                pretty::type_to_string(_type)
+            ))])
+            .with_notes(vec![String::from(
+                "A test passes when its value is `true`, so it must be a `Bool`",
+            )]),
+    }
+}
+
+/// Creates the diagnostic reported for a test that failed, either by evaluating to false or with
+/// the error that stopped it.
+pub fn test_diagnostic(
+    test: &IrTest,
+    error: Option<&InterpreterError>,
+    files: &Renderer,
+) -> Diagnostic {
+    let diagnostic = Diagnostic::error().with_message(format!("Test \"{}\" failed", test.name));
+    match error {
+        Some(e) => diagnostic
+            .with_labels(vec![files
+                .primary(&test.loc)
+                .with_message("This stopped before finishing")])
+            .with_notes(vec![e.to_string()]),
+        None => diagnostic.with_labels(vec![files
+            .primary(&test.loc)
+            .with_message("This evaluated to `false`")]),
+    }
+}
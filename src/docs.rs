@@ -0,0 +1,185 @@
+use std::str::FromStr;
+
+use crate::frontend::ir::{IrModule, Location};
+use crate::frontend::pretty;
+use crate::frontend::types::Type;
+
+/// Represents the formats documentation can be rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl FromStr for DocFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<DocFormat, ()> {
+        match s {
+            "markdown" | "md" => Ok(DocFormat::Markdown),
+            "html" => Ok(DocFormat::Html),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Represents a documented definition.
+struct Item<'a> {
+    /// Where the definition is, which orders the items.
+    loc: &'a Location,
+
+    /// The name of the definition.
+    name: &'a str,
+
+    /// The declaration of a type, or the signature of a value.
+    declaration: String,
+
+    /// The doc comment above the definition, if any.
+    doc: Option<&'a str>,
+}
+
+/// Returns the types declared in a module, in the order they are declared. Each is shown as its
+/// declaration, with fields that are not a single word in parentheses.
+fn types(module: &IrModule) -> Vec<Item<'_>> {
+    let mut items: Vec<Item> = vec![];
+    for c in module.constructors() {
+        if c.loc.filename != module.filename {
+            continue;
+        }
+
+        let mut constructor = c.name.clone();
+        for field in c.field_types.iter() {
+            let field = pretty::type_to_string(field);
+            if field.contains(' ') {
+                constructor.push_str(&format!(" ({})", field));
+            } else {
+                constructor.push_str(&format!(" {}", field));
+            }
+        }
+
+        match items.iter_mut().find(|v| v.name == c.type_name) {
+            Some(item) => item.declaration.push_str(&format!(" | {}", constructor)),
+            None => items.push(Item {
+                loc: &c.loc,
+                name: &c.type_name,
+                declaration: format!("type {} = {}", c.type_name, constructor),
+                doc: module.docs.get(&c.type_name).map(|v| v.as_str()),
+            }),
+        }
+    }
+
+    items.sort_by_key(|v| v.loc.span.start);
+    items
+}
+
+/// Returns the globals and external functions defined in a module with their signatures, in the
+/// order they are defined.
+fn values(module: &IrModule) -> Vec<Item<'_>> {
+    let globals = module
+        .global_functions()
+        .filter(|(_, f)| f.loc.filename == module.filename)
+        .map(|(name, f)| Item {
+            loc: &f.loc,
+            name,
+            declaration: pretty::signature_to_string(name, &f._type),
+            doc: module.docs.get(name).map(|v| v.as_str()),
+        });
+
+    let externals = module
+        .externals
+        .iter()
+        .filter(|(_, e)| e.loc.filename == module.filename)
+        .map(|(name, e)| Item {
+            loc: &e.loc,
+            name,
+            declaration: pretty::signature_to_string(
+                name,
+                &Type::function(&e.arg_types, e.ret_type.clone()),
+            ),
+            doc: module.docs.get(name).map(|v| v.as_str()),
+        });
+
+    let mut items: Vec<_> = globals.chain(externals).collect();
+    items.sort_by_key(|v| v.loc.span.start);
+    items
+}
+
+/// Escapes the characters of text that have a meaning in HTML.
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders a section of documentation as Markdown. Doc comments are already Markdown, so they are
+/// written as they are.
+fn markdown_section(title: &str, items: &[Item], out: &mut String) {
+    if items.is_empty() {
+        return;
+    }
+
+    out.push_str(&format!("\n## {}\n", title));
+    for item in items {
+        out.push_str(&format!(
+            "\n### `{}`\n\n```ocaml\n{}\n```\n",
+            item.name, item.declaration
+        ));
+        if let Some(doc) = item.doc {
+            out.push_str(&format!("\n{}\n", doc));
+        }
+    }
+}
+
+/// Renders a section of documentation as HTML. Doc comments are split into paragraphs at blank
+/// lines.
+fn html_section(title: &str, items: &[Item], out: &mut String) {
+    if items.is_empty() {
+        return;
+    }
+
+    out.push_str(&format!("<h2>{}</h2>\n", title));
+    for item in items {
+        out.push_str(&format!(
+            "<h3 id=\"{0}\"><code>{0}</code></h3>\n<pre><code>{1}</code></pre>\n",
+            escape_html(item.name),
+            escape_html(&item.declaration)
+        ));
+        for paragraph in item.doc.unwrap_or("").split("\n\n") {
+            if !paragraph.trim().is_empty() {
+                out.push_str(&format!("<p>{}</p>\n", escape_html(paragraph.trim())));
+            }
+        }
+    }
+}
+
+/// Renders the documentation of a module, listing the types and values defined in its file with
+/// their declarations and signatures, and the doc comments above them. The module must have been
+/// type checked, so that the signatures are known.
+pub fn render(module: &IrModule, format: DocFormat) -> String {
+    let types = types(module);
+    let values = values(module);
+    let mut out = String::new();
+
+    match format {
+        DocFormat::Markdown => {
+            out.push_str(&format!("# {}\n", module.name));
+            markdown_section("Types", &types, &mut out);
+            markdown_section("Values", &values, &mut out);
+        }
+
+        DocFormat::Html => {
+            let name = escape_html(&module.name);
+            out.push_str(&format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n\
+                 </head>\n<body>\n<h1>{0}</h1>\n",
+                name
+            ));
+            html_section("Types", &types, &mut out);
+            html_section("Values", &values, &mut out);
+            out.push_str("</body>\n</html>\n");
+        }
+    }
+
+    out
+}
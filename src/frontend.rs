@@ -2,16 +2,48 @@
 /// for example, determining arity and type checking.
 pub mod correctness;
 
+/// Module for checking match expressions. This module contains functions for finding values that
+/// no arm of a match expression matches and arms that can never be reached.
+pub mod exhaustive;
+
+/// Module for constant folding. This module contains the pass that replaces expressions whose values
+/// are known at compile time with literals.
+pub mod fold;
+
+/// Module for intrinsics. This module contains the registry of functions that are built into the
+/// compiler and implemented by the runtime library.
+pub mod intrinsics;
+
 /// Module for the frontend intermediate representation. This module contains functions for
 /// generating the IR and handling it.
 pub mod ir;
 
+/// Module for lints. This module contains functions for finding code that is valid but is probably
+/// a mistake, such as unused variables.
+pub mod lints;
+
 /// Module for parsing the source text.
 pub mod parser;
 
+/// Module for pretty printing. This module contains functions for printing types and expressions
+/// in the syntax they would be written in.
+pub mod pretty;
+
+/// Module for the prelude. This module contains the definitions included before every program.
+pub mod prelude;
+
 /// Module for scopes. This module contains functions for manipulating scopes and variables.
 pub mod scopes;
 
+/// Module for specialisation. This module contains the pass that copies functions for the known
+/// functions and partial applications passed to them, so that they are called directly instead of
+/// through closures.
+pub mod specialise;
+
+/// Module for uncurrying. This module contains the pass that turns applications of partial
+/// applications of known functions into single applications with flat argument lists.
+pub mod uncurry;
+
 /// Module for types. This module contains functions to help with type checking and manipulating
 /// types.
 pub mod types;
@@ -1,11 +1,96 @@
 use logos::Span;
 use std::collections::HashMap;
 use std::sync::Arc;
+use tracing::{debug, info_span};
 
-use super::ir::{ArityInfo, Ir, IrFunction, IrModule, Location, SExpr, SExprMetadata};
-use super::types::{arc, Type};
+use super::intrinsics;
+use super::ir::{
+    ArityInfo, BinOp, CType, Ir, IrFunction, IrModule, Location, Pattern, PrefixOp, SExpr,
+    SExprMetadata, PROPAGATED_VALUE_PREFIX,
+};
+use super::prelude;
+use super::pretty;
+use super::types::{arc, GenericPair, IntSize, Type, TypeRc};
 
-pub enum CorrectnessError {}
+// Represents an error in the types of a program.
+pub enum CorrectnessError {
+    // Operands an infix operator cannot be applied to, given by the location of the operation,
+    // the operator, and the types of the left and right operands
+    InvalidOperands(Location, BinOp, TypeRc, TypeRc),
+
+    // An operand a prefix operator cannot be applied to, given by the location of the operation,
+    // the operator, and the type of the operand
+    InvalidOperand(Location, PrefixOp, TypeRc),
+
+    // An int literal mixed with floats that has no exact float representation, given by the
+    // location and value of the literal
+    InexactLiteral(Location, i64),
+
+    // An int literal used as a value of a sized int type it is out of the range of, given by the
+    // location and value of the literal and the type
+    LiteralOutOfRange(Location, i64, TypeRc),
+
+    // An implicit conversion that can lose precision, given by the location of the converted
+    // value and the types it is converted from and into. These are only reported when lossy
+    // conversions are warned about
+    LossyConversion(Location, TypeRc, TypeRc),
+
+    // A variable that is not defined, given by the location where it is used and its name
+    UnknownVariable(Location, String),
+
+    // A value converted with `as` into a type it cannot be converted into, given by the location
+    // of the conversion and the types it is converted from and into
+    InvalidConversion(Location, TypeRc, TypeRc),
+
+    // A value whose type is not the type it is ascribed, given by the location of the ascription
+    // and the types of the value and the ascription
+    MismatchedAscription(Location, TypeRc, TypeRc),
+
+    // A function annotated with `@export` whose arguments or return value cannot be passed to or
+    // from C, given by the location, name, and type of the function
+    InvalidExport(Location, String, TypeRc),
+
+    // A pattern that cannot match the values a match expression matches, given by the location of
+    // the pattern, the pattern, and the type of the matched value
+    InvalidPattern(Location, String, TypeRc),
+
+    // An arm of a match expression whose type differs from the type of the first arm, given by the
+    // location of the arm and the types of the arm and the first arm
+    MismatchedArms(Location, TypeRc, TypeRc),
+
+    // A value `?` is applied to that is neither a result nor an option, given by the location of
+    // the `?` and the type of the value
+    InvalidPropagation(Location, TypeRc),
+
+    // A `?` in a body whose value is not the same kind of result or option as the value of the
+    // `?`, given by the location of the `?` and the types of the value and the rest of the body
+    MismatchedPropagation(Location, TypeRc, TypeRc),
+
+    // A test whose value is not a `Bool`, given by the location, name, and type of the test
+    InvalidTest(Location, String, TypeRc),
+}
+
+impl CorrectnessError {
+    // is_error(&self) -> bool
+    // Returns true if the problem is an error rather than a warning.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            CorrectnessError::InvalidOperands(_, _, _, _)
+                | CorrectnessError::InvalidOperand(_, _, _)
+                | CorrectnessError::LiteralOutOfRange(_, _, _)
+                | CorrectnessError::UnknownVariable(_, _)
+                | CorrectnessError::InvalidConversion(_, _, _)
+                | CorrectnessError::MismatchedAscription(_, _, _)
+                | CorrectnessError::InvalidExport(_, _, _)
+                | CorrectnessError::InvalidPattern(_, _, _)
+                | CorrectnessError::MismatchedArms(_, _, _)
+                | CorrectnessError::InvalidPropagation(_, _)
+                | CorrectnessError::MismatchedPropagation(_, _, _)
+                | CorrectnessError::InvalidTest(_, _, _)
+        )
+    }
+}
 
 fn check_sexpr(
     parent_func: &mut IrFunction,
@@ -18,6 +103,9 @@ fn check_sexpr(
 
         SExpr::TypeAlias(_, _) => todo!(),
 
+        // Literals are typed when they are created
+        SExpr::Int(_, _) | SExpr::Float(_, _) | SExpr::Char(_, _) | SExpr::String(_, _) => (),
+
         SExpr::Symbol(m, s) => {
             if let Some((_type, arity, _, _, _)) = module.scope.get_var(s) {
                 m._type = _type.clone();
@@ -26,18 +114,42 @@ fn check_sexpr(
                     parent_func.captured_names.push(s.clone());
                     parent_func.captured.insert(s.clone(), _type.clone());
                 }
-            } else if let Some(func) = module.globals.get(s) {
+            } else if let Some(func) = module
+                .prelude
+                .get(s)
+                .filter(|_| parent_func.loc.filename == prelude::FILENAME)
+                .or_else(|| module.globals.get(s))
+                .filter(|_| !module.constructors.contains_key(s))
+                .map(|v| match module.shadowed.get(v) {
+                    Some(old) if *v == parent_func.name => old,
+                    _ => v,
+                })
+            {
+                // Constructors shadow global values, such as the list functions of the prelude, and
+                // the prelude only uses its own globals so that programs can shadow them safely
                 *sexpr = SExpr::Function(m.clone(), func.clone());
                 check_sexpr(parent_func, sexpr, module, errors);
+            } else if let Some((_type, arity, impure)) = external_signature(module, s) {
+                m._type = _type;
+                m.arity = ArityInfo::Known(arity);
+                m.impure = impure;
+                *sexpr = SExpr::ExternalFunc(m.clone(), s.clone(), vec![]);
             } else {
-                panic!("variable {} not found", s);
+                // The variable can be used as any type, so that it is only reported once
+                errors.push(CorrectnessError::UnknownVariable(m.loc.clone(), s.clone()));
+                module.last_uid += 1;
+                m._type = arc::new(Type::generic("a", module.last_uid));
             }
         }
 
         SExpr::Function(m, f) => {
             if let Some(func) = module.funcs.get(f) {
                 if func.checked {
-                    m._type = func._type.clone();
+                    m._type = if func.global {
+                        arc::new(func._type.instantiate(&mut module.last_uid))
+                    } else {
+                        func._type.clone()
+                    };
                     m.arity = ArityInfo::Known(func.args.len());
                 } else {
                     let mut func = module.funcs.remove(f).unwrap();
@@ -62,27 +174,150 @@ fn check_sexpr(
 
                     module.scope.pop_scope();
 
+                    // Values the function captures from outside the function it is made in are
+                    // captured by that function too, so that it has them to make the closure with
+                    for name in func.captured_names.iter() {
+                        if module.scope.is_captured(name)
+                            && !parent_func.captured_names.contains(name)
+                        {
+                            parent_func.captured_names.push(name.clone());
+                            parent_func
+                                .captured
+                                .insert(name.clone(), func.captured[name].clone());
+                        }
+                    }
+
                     let mut _type = func.body.get_metadata()._type.clone();
                     for arg in func.args.iter().rev() {
                         _type = arc::new(Type::Func(arg.1.clone(), _type));
                     }
 
                     func._type = _type;
-                    m._type = func._type.clone();
+                    m._type = if func.global {
+                        arc::new(func._type.instantiate(&mut module.last_uid))
+                    } else {
+                        func._type.clone()
+                    };
                     m.arity = ArityInfo::Known(func.args.len());
 
                     func.checked = true;
                     module.funcs.insert(f.clone(), func);
                 }
+            } else if let Some(_type) = module.signatures.get(f) {
+                // The function is still being checked, so its signature is used as is, which
+                // keeps recursive references to a function monomorphic
+                m._type = _type.clone();
+                m.arity = ArityInfo::Known(_type.as_function().map_or(0, |v| v.0.len()));
             } else {
-                panic!("this shouldn't happen i believe");
+                panic!("function {} not found", f);
             }
         }
 
-        SExpr::ExternalFunc(_, _, _) => todo!(),
+        SExpr::ExternalFunc(m, name, args) => {
+            let (mut _type, arity, impure) = external_signature(module, name).unwrap();
+            let mut generics_map = HashMap::new();
+            for arg in args.iter_mut() {
+                check_sexpr(parent_func, arg, module, errors);
+
+                if let Type::Func(at, rt) = &*_type.clone() {
+                    if !arg
+                        .get_metadata()
+                        ._type
+                        .is_subtype(at, &module.types, &mut generics_map)
+                    {
+                        let types = pretty::types_to_strings(&[&arg.get_metadata()._type, at]);
+                        panic!("{} is not a subtype of {}", types[0], types[1]);
+                    }
+                    _type = rt.clone();
+                }
+            }
+
+            m._type = _type;
+            Arc::make_mut(&mut m._type).replace_generics(&generics_map);
+            m.arity = ArityInfo::Known(arity - args.len());
+            m.impure = impure;
+        }
+
+        SExpr::Prefix(m, op, v) => {
+            check_sexpr(parent_func, v, module, errors);
+
+            let operand = v.get_metadata()._type.clone();
+            if !op.accepts(&operand) {
+                errors.push(CorrectnessError::InvalidOperand(
+                    m.loc.clone(),
+                    *op,
+                    operand.clone(),
+                ));
+            }
+
+            m._type = operand;
+            m.arity = ArityInfo::Known(0);
+        }
+
+        SExpr::Infix(m, op, l, r) => {
+            check_sexpr(parent_func, l, module, errors);
+            check_sexpr(parent_func, r, module, errors);
+            promote_operands(*op, l, r, errors);
+
+            let left = l.get_metadata()._type.clone();
+            let right = r.get_metadata()._type.clone();
+            let operand = match operand_type(&left, &right, module) {
+                Some(t) if op.accepts(&t) => t,
+                Some(t)
+                    if matches!(op, BinOp::EQ | BinOp::NEQ)
+                        && comparable(&t, module, &mut vec![]) =>
+                {
+                    t
+                }
+                _ => {
+                    errors.push(CorrectnessError::InvalidOperands(
+                        m.loc.clone(),
+                        *op,
+                        left.clone(),
+                        right,
+                    ));
+                    left
+                }
+            };
+
+            m._type = if op.is_comparison() {
+                arc::new(Type::Bool)
+            } else {
+                operand
+            };
+            m.arity = ArityInfo::Known(0);
+        }
 
         SExpr::Chain(_, _, _) => todo!(),
 
+        SExpr::As(m, v) => {
+            check_sexpr(parent_func, v, module, errors);
+            let from = &v.get_metadata()._type;
+            if !convertible(from, &m._type, module) {
+                errors.push(CorrectnessError::InvalidConversion(
+                    m.loc.clone(),
+                    from.clone(),
+                    m._type.clone(),
+                ));
+            }
+            m.arity = v.get_metadata().arity;
+        }
+
+        SExpr::Ascribe(m, v) => {
+            // Int literals take the sized int type they are ascribed, like arguments do
+            check_sexpr(parent_func, v, module, errors);
+            retype_literal(v, &m._type, errors);
+            let found = v.get_metadata()._type.clone();
+            if !found.is_subtype(&m._type, &module.types, &mut HashMap::new()) {
+                errors.push(CorrectnessError::MismatchedAscription(
+                    m.loc.clone(),
+                    found,
+                    m._type.clone(),
+                ));
+            }
+            m.arity = v.get_metadata().arity;
+        }
+
         SExpr::Application(m, func, args) => {
             check_sexpr(parent_func, func, module, errors);
             for arg in args.iter_mut() {
@@ -97,8 +332,9 @@ fn check_sexpr(
             swap(&mut args_temp, args);
             let mut arity = func.get_metadata().arity;
             let last_index = args_temp.len();
-            for (i, arg) in args_temp.into_iter().enumerate() {
+            for (i, mut arg) in args_temp.into_iter().enumerate() {
                 if let Type::Func(at, rt) = &*ft {
+                    retype_literal(&mut arg, at, errors);
                     if arg
                         .get_metadata()
                         ._type
@@ -108,7 +344,8 @@ fn check_sexpr(
                         ft = rt.clone();
                         Arc::make_mut(&mut m._type).replace_generics(&generics_map);
                     } else {
-                        panic!("{} is not a subtype of {}", arg.get_metadata()._type, at);
+                        let types = pretty::types_to_strings(&[&arg.get_metadata()._type, at]);
+                        panic!("{} is not a subtype of {}", types[0], types[1]);
                     }
 
                     args.push(arg);
@@ -145,9 +382,13 @@ fn check_sexpr(
                             func.clone(),
                             temp,
                         );
+                        saturate_external(func);
                     }
                 } else {
-                    panic!("type {} is not a function", func.get_metadata()._type);
+                    panic!(
+                        "type {} is not a function",
+                        pretty::type_to_string(&func.get_metadata()._type)
+                    );
                 }
             }
 
@@ -165,20 +406,695 @@ fn check_sexpr(
                 .put_var(a, &m._type, m.arity, &m.loc, true, &module.name);
         }
 
-        SExpr::With(_, _, _) => todo!(),
+        SExpr::With(m, assigns, body) => {
+            // The bindings are only visible in the body, and belong to the enclosing function
+            module.scope.push_scope(false);
+            for assign in assigns.iter_mut() {
+                check_sexpr(parent_func, assign, module, errors);
+            }
+            check_sexpr(parent_func, body, module, errors);
+            module.scope.pop_scope();
+
+            m._type = body.get_metadata()._type.clone();
+            m.arity = body.get_metadata().arity;
+        }
+
+        SExpr::Match(m, value, arms) => {
+            check_sexpr(parent_func, value, module, errors);
+            let value_type = value.get_metadata()._type.clone();
+
+            // Matches made by `?` are written for results, and match options with `some` and `none`
+            let propagated = is_propagation(arms);
+            let mut invalid = false;
+            if propagated {
+                match &*value_type {
+                    Type::Result(_) => (),
+                    Type::Option(_) => {
+                        let fields = std::mem::take(match &mut arms[0].0 {
+                            Pattern::Constructor(_, fields) => fields,
+                            _ => unreachable!(),
+                        });
+                        arms[0].0 = Pattern::Constructor(String::from("some"), fields);
+                        arms[1].0 = Pattern::Constructor(String::from("none"), vec![]);
+                    }
+                    _ => {
+                        errors.push(CorrectnessError::InvalidPropagation(
+                            m.loc.clone(),
+                            value_type.clone(),
+                        ));
+                        invalid = true;
+                    }
+                }
+            }
+
+            let mut _type: Option<TypeRc> = None;
+            let mut generics_map = HashMap::new();
+            for (i, (pattern, arm, loc)) in arms.iter_mut().enumerate() {
+                // The arm takes the matched value followed by the values bound by the pattern
+                let mut arg_types = vec![value_type.clone()];
+                let before = errors.len();
+                pattern_types(pattern, &value_type, loc, module, &mut arg_types, errors);
+
+                // Patterns that cannot match usually all fail for the same reason, such as the
+                // condition of an `if` not being a bool, so only the first is reported
+                if invalid {
+                    errors.truncate(before);
+                } else {
+                    invalid = errors.len() > before;
+                }
+                if let SExpr::Function(_, f) = arm {
+                    let func = module.funcs.get_mut(f).unwrap();
+                    for (arg, t) in func.args.iter_mut().zip(arg_types.iter()) {
+                        arg.1 = t.clone();
+                    }
+                }
+                check_sexpr(parent_func, arm, module, errors);
+
+                let mut arm_type = arm.get_metadata()._type.clone();
+                for _ in arg_types.iter() {
+                    if let Type::Func(_, r) = &*arm_type.clone() {
+                        arm_type = r.clone();
+                    }
+                }
+
+                // Every arm must have the type of the first arm. Arms of a match with invalid
+                // patterns are not compared, since the matched value already has the wrong type
+                match &_type {
+                    // The error or none given by `?` only needs the rest of the body to be the same
+                    // kind of result or option, since it holds no value
+                    Some(t) if propagated && i == 1 => {
+                        module.last_uid += 1;
+                        let value = arc::new(Type::generic("a", module.last_uid));
+                        let expected = match &*value_type {
+                            Type::Result(_) => Type::Result(value),
+                            _ => Type::Option(value),
+                        };
+                        if !invalid && !t.is_subtype(&expected, &module.types, &mut generics_map) {
+                            errors.push(CorrectnessError::MismatchedPropagation(
+                                m.loc.clone(),
+                                value_type.clone(),
+                                t.clone(),
+                            ));
+                        }
+                    }
+
+                    Some(t) => {
+                        if !invalid && !arm_type.is_subtype(t, &module.types, &mut generics_map) {
+                            errors.push(CorrectnessError::MismatchedArms(
+                                arm.get_metadata().loc.clone(),
+                                arm_type,
+                                t.clone(),
+                            ));
+                        }
+                    }
+                    None => _type = Some(arm_type),
+                }
+            }
+
+            m._type = _type.unwrap();
+            Arc::make_mut(&mut m._type).replace_generics(&generics_map);
+            m.arity = ArityInfo::Unknown;
+        }
+    }
+
+    saturate_external(sexpr);
+}
+
+// is_propagation(&[(Pattern, SExpr, Location)]) -> bool
+// Returns true if the arms are those of a match made by `?`, whose first pattern binds the value to
+// a name only `?` uses.
+fn is_propagation(arms: &[(Pattern, SExpr, Location)]) -> bool {
+    match arms.first() {
+        Some((Pattern::Constructor(_, fields), _, _)) => {
+            matches!(fields.as_slice(), [Pattern::Binding(b)] if b.starts_with(PROPAGATED_VALUE_PREFIX))
+        }
+        _ => false,
+    }
+}
+
+// operand_type(&TypeRc, &TypeRc, &IrModule) -> Option<TypeRc>
+// Returns the type both operands of an infix operator have, or None if their types differ. An
+// instantiated generic operand has the type of the other operand.
+fn operand_type(left: &TypeRc, right: &TypeRc, module: &IrModule) -> Option<TypeRc> {
+    let (generic, concrete) = match (&**left, &**right) {
+        (Type::Generic(_, _), Type::Generic(_, _)) => return None,
+        (Type::Generic(_, _), _) => (left, right),
+        _ => (right, left),
+    };
+
+    if generic.is_subtype(concrete, &module.types, &mut HashMap::new()) {
+        Some(concrete.clone())
+    } else {
+        None
+    }
+}
+
+// comparable(&Type, &IrModule, &mut Vec<String>) -> bool
+// Returns true if values of a type can be compared with `==` and `!=`, which compares them by their
+// contents. Functions, threads, and tasks have no contents to compare, and values of generic types
+// cannot be compared since the comparison depends on the type. Declared types are comparable if the
+// fields of every constructor are, with the declared types already being checked assumed to be.
+fn comparable(_type: &Type, module: &IrModule, checking: &mut Vec<String>) -> bool {
+    match _type {
+        Type::Int | Type::SizedInt(_) | Type::Float | Type::Bool | Type::Char => true,
+        Type::Symbol(s) if s == "String" || s == "Unit" || checking.contains(s) => true,
+        Type::Symbol(s) => {
+            checking.push(s.clone());
+            let mut constructors = module
+                .constructors()
+                .filter(|c| &c.type_name == s)
+                .peekable();
+            constructors.peek().is_some()
+                && constructors.all(|c| {
+                    c.field_types
+                        .iter()
+                        .all(|v| comparable(v, module, checking))
+                })
+        }
+        Type::Array(v) | Type::Result(v) | Type::Option(v) => comparable(v, module, checking),
+        Type::Map(k, v) => comparable(k, module, checking) && comparable(v, module, checking),
+        _ => false,
+    }
+}
+
+// promote_operands(BinOp, &mut SExpr, &mut SExpr, &mut Vec<CorrectnessError>) -> ()
+// Converts an operand into the type of the other operand if none of its values are lost, or an int
+// operand into a float if the other operand is a float and the operator works on floats. Int
+// literals are converted while checking, and other values are wrapped in an `as`.
+fn promote_operands(
+    op: BinOp,
+    left: &mut SExpr,
+    right: &mut SExpr,
+    errors: &mut Vec<CorrectnessError>,
+) {
+    let left_type = left.get_metadata()._type.clone();
+    let right_type = right.get_metadata()._type.clone();
+    match (IntSize::of(&left_type), IntSize::of(&right_type)) {
+        // Unsuffixed literals take the type of the other operand
+        (Some(_), Some(_)) if left_type == right_type => (),
+        (Some(_), Some(_)) if is_unsuffixed_literal(left) => {
+            retype_literal(left, &right_type, errors)
+        }
+        (Some(_), Some(_)) if is_unsuffixed_literal(right) => {
+            retype_literal(right, &left_type, errors)
+        }
+
+        // Ints are widened into the other int type if it holds all of their values
+        (Some(l), Some(r)) if l.widens_to(&r) => convert_operand(left, right_type),
+        (Some(l), Some(r)) if r.widens_to(&l) => convert_operand(right, left_type),
+
+        (Some(size), None) if *right_type == Type::Float && op.accepts(&Type::Float) => {
+            promote_to_float(left, size, errors)
+        }
+        (None, Some(size)) if *left_type == Type::Float && op.accepts(&Type::Float) => {
+            promote_to_float(right, size, errors)
+        }
+
+        _ => (),
+    }
+}
+
+// promote_to_float(&mut SExpr, IntSize, &mut Vec<CorrectnessError>) -> ()
+// Converts an int operand of the given size into a float. Ints beyond 2^53 may be rounded, so
+// literals without an exact float are reported, and conversions of other ints that may be rounded
+// are reported as lossy.
+fn promote_to_float(int: &mut SExpr, size: IntSize, errors: &mut Vec<CorrectnessError>) {
+    if let SExpr::Int(m, n) = int {
+        // Ints beyond 2^53 are rounded to the nearest float
+        let value = size.value(*n);
+        let f = value as f64;
+        if f as i128 != value {
+            errors.push(CorrectnessError::InexactLiteral(m.loc.clone(), *n));
+        }
+        let mut m = m.clone();
+        m._type = arc::new(Type::Float);
+        debug!(from = *n, to = f, "promoted literal");
+        *int = SExpr::Float(m, f);
+    } else {
+        let from = int.get_metadata()._type.clone();
+        if size.bits > 53 {
+            errors.push(CorrectnessError::LossyConversion(
+                int.get_metadata().loc.clone(),
+                from,
+                arc::new(Type::Float),
+            ));
+        }
+        convert_operand(int, arc::new(Type::Float));
+    }
+}
+
+// convert_operand(&mut SExpr, TypeRc) -> ()
+// Wraps an operand in an `as` converting it into the given type.
+fn convert_operand(operand: &mut SExpr, _type: TypeRc) {
+    let value = std::mem::replace(operand, SExpr::Empty(SExprMetadata::empty()));
+    let inner = value.get_metadata();
+    let m = SExprMetadata {
+        loc: inner.loc.clone(),
+        origin: inner.origin.clone(),
+        _type,
+        arity: ArityInfo::Known(0),
+        ..SExprMetadata::empty()
+    };
+    *operand = SExpr::As(m, Box::new(value));
+}
+
+// is_unsuffixed_literal(&SExpr) -> bool
+// Returns true if an s expression is an int literal without a suffix giving its type.
+fn is_unsuffixed_literal(sexpr: &SExpr) -> bool {
+    matches!(sexpr, SExpr::Int(m, _) if *m._type == Type::Int)
+}
+
+// retype_literal(&mut SExpr, &Type, &mut Vec<CorrectnessError>) -> ()
+// Gives an int literal without a suffix the sized int type it is used as, reporting it if it is
+// out of the range of the type. Other values are left as they are.
+fn retype_literal(sexpr: &mut SExpr, _type: &Type, errors: &mut Vec<CorrectnessError>) {
+    let size = match _type {
+        Type::SizedInt(size) if is_unsuffixed_literal(sexpr) => *size,
+        _ => return,
+    };
+
+    // Literals out of range still get the type, so that they are not reported again
+    if let SExpr::Int(m, n) = sexpr {
+        m._type = arc::new(Type::SizedInt(size));
+        if !size.fits(*n as i128) {
+            errors.push(CorrectnessError::LiteralOutOfRange(
+                m.loc.clone(),
+                *n,
+                m._type.clone(),
+            ));
+        }
+        *n = size.wrap(*n as i128);
+    }
+}
 
-        SExpr::Match(_, _, _) => todo!(),
+// convertible(&TypeRc, &TypeRc, &IrModule) -> bool
+// Returns true if values of the first type can be converted into the second type with `as`. Ints
+// convert into each other, wrapping around if they are out of range, ints convert into and from
+// floats and chars, bools convert into ints, and any value converts into its supertypes.
+fn convertible(from: &TypeRc, to: &TypeRc, module: &IrModule) -> bool {
+    let int = |t: &Type| IntSize::of(t).is_some();
+    match (&**from, &**to) {
+        (f, t) if int(f) && int(t) => true,
+        (f, Type::Float | Type::Char) if int(f) => true,
+        (Type::Float | Type::Char | Type::Bool, t) if int(t) => true,
+        _ => from.is_subtype(to, &module.types, &mut HashMap::new()),
     }
 }
 
-pub fn check_correctness(ir: &mut Ir, _require_main: bool) -> Result<(), Vec<CorrectnessError>> {
+// pattern_types(&Pattern, &TypeRc, &Location, &mut IrModule, &mut Vec<TypeRc>, &mut Vec<CorrectnessError>) -> ()
+// Checks that a pattern at the given location can match values of the given type and collects the
+// types of the values it binds. The fields of a constructor that cannot match are still given the
+// types of the fields of the constructor.
+fn pattern_types(
+    pattern: &Pattern,
+    _type: &TypeRc,
+    loc: &Location,
+    module: &mut IrModule,
+    types: &mut Vec<TypeRc>,
+    errors: &mut Vec<CorrectnessError>,
+) {
+    match pattern {
+        Pattern::Wildcard => (),
+
+        Pattern::Binding(_) => types.push(_type.clone()),
+
+        // Int literals match any int type they are in the range of
+        Pattern::Int(n) => match IntSize::of(_type) {
+            Some(size) if size.fits(*n as i128) => (),
+            Some(_) => errors.push(CorrectnessError::LiteralOutOfRange(
+                loc.clone(),
+                *n,
+                _type.clone(),
+            )),
+            None => errors.push(CorrectnessError::InvalidPattern(
+                loc.clone(),
+                pattern.to_string(),
+                _type.clone(),
+            )),
+        },
+
+        Pattern::Char(_) if **_type == Type::Char => (),
+        Pattern::String(_) if **_type == Type::Symbol(String::from("String")) => (),
+        Pattern::Char(_) | Pattern::String(_) => errors.push(CorrectnessError::InvalidPattern(
+            loc.clone(),
+            pattern.to_string(),
+            _type.clone(),
+        )),
+
+        Pattern::Constructor(c, fields) => {
+            // Declared constructors shadow the constructors of the runtime library
+            let (arity, constructor_type) = match module.constructors.get(c) {
+                Some(v) => (v.arity(), v._type()),
+                None => match intrinsics::get_constructor(c) {
+                    Some(v) => (v.arity(), v._type()),
+                    None => panic!("constructor {} not found", c),
+                },
+            };
+            if fields.len() != arity {
+                panic!("{} must be matched with {} fields", c, arity);
+            }
+
+            let constructor_type = arc::new(constructor_type.instantiate(&mut module.last_uid));
+            let (field_types, ret_type) = constructor_type
+                .as_function()
+                .unwrap_or_else(|| (vec![], constructor_type.clone()));
+            let mut generics_map = HashMap::new();
+            if !_type.is_subtype(&ret_type, &module.types, &mut generics_map) {
+                errors.push(CorrectnessError::InvalidPattern(
+                    loc.clone(),
+                    pattern.to_string(),
+                    _type.clone(),
+                ));
+            }
+
+            // The fields of generic constructors take the types the matched value was built with
+            for (field, t) in fields.iter().zip(field_types.iter()) {
+                let mut t = t.clone();
+                Arc::make_mut(&mut t).replace_generics(&generics_map);
+                pattern_types(field, &t, loc, module, types, errors);
+            }
+        }
+    }
+}
+
+// external_signature(&mut IrModule, &str) -> Option<(TypeRc, usize, bool)>
+// Returns the instantiated type, arity, and purity of an external function, constructor, or
+// intrinsic. External functions and constructors declared in the module shadow intrinsics.
+fn external_signature(module: &mut IrModule, name: &str) -> Option<(TypeRc, usize, bool)> {
+    if let Some(ext) = module.externals.get(name) {
+        let _type = Type::function(&ext.arg_types, ext.ret_type.clone());
+        Some((
+            arc::new(_type.instantiate(&mut module.last_uid)),
+            ext.arg_types.len(),
+            ext.impure,
+        ))
+    } else if let Some(constructor) = module.constructors.get(name) {
+        Some((
+            arc::new(constructor._type().instantiate(&mut module.last_uid)),
+            constructor.arity(),
+            false,
+        ))
+    } else if let Some(intrinsic) = intrinsics::get(name) {
+        Some((
+            arc::new(intrinsic._type().instantiate(&mut module.last_uid)),
+            intrinsic.arity(),
+            intrinsic.impure,
+        ))
+    } else {
+        None
+    }
+}
+
+// saturate_external(&mut SExpr) -> ()
+// Turns an application of an external function or intrinsic to all of its arguments into a single
+// external function application. Applications to fewer arguments are left for expand_externals.
+fn saturate_external(sexpr: &mut SExpr) {
+    if let SExpr::Application(m, func, args) = sexpr {
+        if let SExpr::ExternalFunc(fm, name, a) = &**func {
+            if a.is_empty() && matches!(fm.arity, ArityInfo::Known(v) if v == args.len()) {
+                use std::mem::swap;
+                let mut args_temp = vec![];
+                swap(&mut args_temp, args);
+                m.impure = fm.impure;
+                *sexpr = SExpr::ExternalFunc(m.clone(), name.clone(), args_temp);
+            }
+        }
+    }
+}
+
+// expand_externals(&mut SExpr, &mut IrModule) -> ()
+// Wraps external functions and intrinsics that are not applied to all of their arguments, such as
+// those partially applied or passed to other functions, in a function that applies them to its
+// arguments. Backends only call externals with all of their arguments, and the wrapper can be
+// partially applied and passed around like any other function.
+fn expand_externals(sexpr: &mut SExpr, module: &mut IrModule) {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _)
+        | SExpr::Function(_, _) => (),
+
+        SExpr::ExternalFunc(m, name, args) => {
+            if args.is_empty() && !matches!(m.arity, ArityInfo::Known(0)) {
+                let arity = external_arity(m);
+                let arg_types = match m._type.as_function() {
+                    Some((args, _)) => args.into_iter().take(arity).collect(),
+                    None => vec![],
+                };
+                let m = m.clone();
+                let name = name.clone();
+                *sexpr = eta_expand(module, m, &name, arg_types);
+            } else {
+                for arg in args {
+                    expand_externals(arg, module);
+                }
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            expand_externals(a, module);
+            expand_externals(b, module);
+        }
+
+        SExpr::Application(m, f, args) => {
+            for arg in args.iter_mut() {
+                expand_externals(arg, module);
+            }
+
+            // The types of the arguments given are those the external is applied to, and the rest
+            // are those of the function the application returns
+            match &**f {
+                SExpr::ExternalFunc(fm, name, a) if a.is_empty() => {
+                    let mut arg_types: Vec<_> = args
+                        .iter()
+                        .map(|v| v.get_metadata()._type.clone())
+                        .collect();
+                    if let Some((rest, _)) = m._type.as_function() {
+                        arg_types.extend(rest.into_iter().take(external_arity(fm) - args.len()));
+                    }
+                    let fm = fm.clone();
+                    let name = name.clone();
+                    **f = eta_expand(module, fm, &name, arg_types);
+                }
+                _ => expand_externals(f, module),
+            }
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => expand_externals(v, module),
+
+        SExpr::With(_, assigns, v) => {
+            for assign in assigns {
+                expand_externals(assign, module);
+            }
+            expand_externals(v, module);
+        }
+
+        SExpr::Match(_, v, arms) => {
+            expand_externals(v, module);
+            for (_, arm, _) in arms {
+                expand_externals(arm, module);
+            }
+        }
+    }
+}
+
+// external_arity(&SExprMetadata) -> usize
+// Returns the number of arguments an external function or intrinsic is applied to.
+fn external_arity(m: &SExprMetadata) -> usize {
+    match m.arity {
+        ArityInfo::Known(v) => v,
+        ArityInfo::Unknown => unreachable!("external functions have known arities"),
+    }
+}
+
+// eta_expand(&mut IrModule, SExprMetadata, &str, Vec<TypeRc>) -> SExpr
+// Creates a function taking arguments of the given types that applies an external function or
+// intrinsic to them, and returns a reference to it with the metadata of the external.
+fn eta_expand(
+    module: &mut IrModule,
+    m: SExprMetadata,
+    name: &str,
+    arg_types: Vec<TypeRc>,
+) -> SExpr {
+    let args: Vec<_> = arg_types
+        .into_iter()
+        .enumerate()
+        .map(|(i, t)| (format!("{}.{}", name, i), t))
+        .collect();
+    let mut ret = m._type.clone();
+    for _ in 0..args.len() {
+        if let Type::Func(_, r) = &*ret.clone() {
+            ret = r.clone();
+        }
+    }
+
+    let body = SExpr::ExternalFunc(
+        SExprMetadata {
+            loc: m.loc.clone(),
+            loc2: Location::empty(),
+            origin: String::with_capacity(0),
+            _type: ret.clone(),
+            arity: ArityInfo::Known(0),
+            tailrec: false,
+            impure: m.impure,
+        },
+        name.to_string(),
+        args.iter()
+            .map(|(a, t)| {
+                SExpr::Symbol(
+                    SExprMetadata {
+                        loc: m.loc.clone(),
+                        loc2: Location::empty(),
+                        origin: String::with_capacity(0),
+                        _type: t.clone(),
+                        arity: ArityInfo::Unknown,
+                        tailrec: false,
+                        impure: false,
+                    },
+                    a.clone(),
+                )
+            })
+            .collect(),
+    );
+
+    let mut expanded = format!("{}.expanded", name);
+    let mut n = 0;
+    while module.funcs.contains_key(&expanded) {
+        expanded = format!("{}.expanded.{}", name, n);
+        n += 1;
+    }
+
+    debug!(external = %name, function = %expanded, "expanded");
+    let arg_types: Vec<_> = args.iter().map(|v| v.1.clone()).collect();
+    let _type = arc::new(Type::function(&arg_types, ret));
+    module.funcs.insert(
+        expanded.clone(),
+        IrFunction {
+            loc: m.loc.clone(),
+            name: expanded.clone(),
+            _type: _type.clone(),
+            args,
+            captured: HashMap::new(),
+            captured_names: vec![],
+            body,
+            global: false,
+            checked: true,
+            written: false,
+            impure: m.impure,
+        },
+    );
+
+    SExpr::Function(
+        SExprMetadata {
+            _type,
+            arity: ArityInfo::Known(arg_types.len()),
+            impure: false,
+            ..m
+        },
+        expanded,
+    )
+}
+
+// mark_tail_calls(&mut SExpr) -> ()
+// Marks the applications in tail position, whose value is returned by the function they are in.
+// Backends can reuse the stack frame of the function for these calls, so recursion through tail
+// calls, including mutual recursion, runs in constant stack space.
+fn mark_tail_calls(sexpr: &mut SExpr) {
+    match sexpr {
+        SExpr::Application(m, _, _) => m.tailrec = true,
+        SExpr::Chain(_, _, v) | SExpr::With(_, _, v) => mark_tail_calls(v),
+        _ => (),
+    }
+}
+
+// replace_generics(&mut SExpr, &HashMap<GenericPair, TypeRc>) -> ()
+// Replaces generics in the types of an s expression and everything in it, such as the return
+// generics of the signatures used by recursive calls.
+fn replace_generics(sexpr: &mut SExpr, generics_map: &HashMap<GenericPair, TypeRc>) {
+    Arc::make_mut(&mut sexpr.get_mutable_metadata()._type).replace_generics(generics_map);
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _)
+        | SExpr::Function(_, _) => (),
+
+        SExpr::ExternalFunc(_, _, args) => {
+            for arg in args {
+                replace_generics(arg, generics_map);
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            replace_generics(a, generics_map);
+            replace_generics(b, generics_map);
+        }
+
+        SExpr::Application(_, f, args) => {
+            replace_generics(f, generics_map);
+            for arg in args {
+                replace_generics(arg, generics_map);
+            }
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => replace_generics(v, generics_map),
+
+        SExpr::With(_, assigns, v) => {
+            for assign in assigns {
+                replace_generics(assign, generics_map);
+            }
+            replace_generics(v, generics_map);
+        }
+
+        SExpr::Match(_, v, arms) => {
+            replace_generics(v, generics_map);
+            for (_, arm, _) in arms {
+                replace_generics(arm, generics_map);
+            }
+        }
+    }
+}
+
+// check_correctness(&mut Ir, bool, bool) -> Vec<CorrectnessError>
+// Checks the types of every module, returning the errors and warnings found. Implicit conversions
+// that can lose precision are only reported if lossy conversions are warned about.
+pub fn check_correctness(
+    ir: &mut Ir,
+    _require_main: bool,
+    warn_lossy: bool,
+) -> Vec<CorrectnessError> {
+    let _span = info_span!("check_correctness").entered();
     let mut errors = vec![];
 
     for (_, module) in ir.modules.iter_mut() {
-        let globals = module.globals.clone();
-        for (_, raw) in globals {
+        let _span = info_span!("module", name = %module.name).entered();
+        // Prelude functions the program shadows are checked as well, since the prelude uses them
+        let mut globals: Vec<_> = module.globals.values().cloned().collect();
+        globals.extend(
+            module
+                .prelude
+                .values()
+                .filter(|v| !module.globals.values().any(|g| g == *v))
+                .cloned(),
+        );
+        globals.extend(module.tests.iter().map(|v| v.func.clone()));
+        for raw in globals {
             use std::mem::swap;
 
+            let _span = info_span!("function", name = %raw).entered();
             let mut func = module.funcs.remove(&raw).unwrap();
             if func.checked {
                 module.funcs.insert(raw, func);
@@ -208,16 +1124,93 @@ pub fn check_correctness(ir: &mut Ir, _require_main: bool) -> Result<(), Vec<Cor
             }
 
             func._type = _type;
+            func.checked = true;
+            debug!(_type = %pretty::type_to_string(&func._type), "checked");
 
             module.scope.pop_scope();
 
             module.funcs.insert(raw, func);
         }
+
+        // Externals that are not applied to all of their arguments become functions that apply
+        // them, which are named in the order of the functions they are used in
+        let mut names: Vec<_> = module.funcs.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            let mut body = std::mem::replace(
+                &mut module.funcs.get_mut(&name).unwrap().body,
+                SExpr::Empty(SExprMetadata::empty()),
+            );
+            expand_externals(&mut body, module);
+            module.funcs.get_mut(&name).unwrap().body = body;
+        }
+
+        // Replace the return generics of signatures with the return types that were checked
+        let mut generics_map = HashMap::new();
+        for (name, signature) in module.signatures.iter() {
+            let (arity, ret) = match signature.as_function() {
+                Some((args, ret)) => (args.len(), ret),
+                None => (0, signature.clone()),
+            };
+            let mut _type = module.funcs[name]._type.clone();
+            for _ in 0..arity {
+                if let Type::Func(_, r) = &*_type.clone() {
+                    _type = r.clone();
+                }
+            }
+
+            if let Type::Generic(g, uid) = &*ret {
+                if _type != ret {
+                    generics_map.insert(GenericPair::new(g, *uid), _type);
+                }
+            }
+        }
+
+        // Recursive calls used the signatures, so the values they return in bodies are replaced
+        // as well, which backends need to tell pointers from other values
+        for func in module.funcs.values_mut() {
+            Arc::make_mut(&mut func._type).replace_generics(&generics_map);
+            for t in func.captured.values_mut() {
+                Arc::make_mut(t).replace_generics(&generics_map);
+            }
+            replace_generics(&mut func.body, &generics_map);
+            mark_tail_calls(&mut func.body);
+        }
+
+        // Tests pass when their value is true
+        for test in module.tests.iter() {
+            let _type = &module.funcs[&test.func]._type;
+            if !_type.is_subtype(&Type::Bool, &module.types, &mut HashMap::new()) {
+                errors.push(CorrectnessError::InvalidTest(
+                    test.loc.clone(),
+                    test.name.clone(),
+                    _type.clone(),
+                ));
+            }
+        }
+
+        // Exported functions are called from C, so every argument and the return value need a C type
+        for name in module.c_exports.iter() {
+            let func = &module.funcs[&module.globals[name]];
+            let ret = func.ret_type();
+            if !func
+                .args
+                .iter()
+                .map(|v| &v.1)
+                .chain(std::iter::once(&ret))
+                .all(|v| CType::representable(v))
+            {
+                errors.push(CorrectnessError::InvalidExport(
+                    func.loc.clone(),
+                    name.clone(),
+                    func._type.clone(),
+                ));
+            }
+        }
     }
 
-    if errors.is_empty() {
-        Ok(())
-    } else {
-        Err(errors)
+    if !warn_lossy {
+        errors.retain(|e| !matches!(e, CorrectnessError::LossyConversion(_, _, _)));
     }
+    errors
 }
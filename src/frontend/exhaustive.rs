@@ -0,0 +1,342 @@
+use tracing::{debug, info_span};
+
+use super::intrinsics;
+use super::ir::{Ir, IrModule, Location, Pattern, SExpr};
+
+// Represents a problem with the arms of a match expression.
+pub enum MatchError {
+    // Values that no arm matches, given by the location of the match expression and patterns for
+    // the values that are missed
+    NonExhaustive(Location, Vec<Pattern>),
+
+    // An arm that can never be reached, because the arms before it match every value it matches
+    UnreachableArm(Location),
+}
+
+impl MatchError {
+    // is_error(&self) -> bool
+    // Returns true if the problem is an error rather than a warning.
+    pub fn is_error(&self) -> bool {
+        matches!(self, MatchError::NonExhaustive(_, _))
+    }
+
+    // loc(&self) -> &Location
+    // Returns the location of the match expression or arm with the problem.
+    pub fn loc(&self) -> &Location {
+        match self {
+            MatchError::NonExhaustive(loc, _) | MatchError::UnreachableArm(loc) => loc,
+        }
+    }
+}
+
+// Represents a constructor patterns can match, either declared in the module or built into the
+// runtime library.
+struct Constructor<'a> {
+    name: &'a str,
+    arity: usize,
+    type_name: &'a str,
+}
+
+// module_constructors(&IrModule) -> Vec<Constructor<'_>>
+// Returns the constructors patterns in a module can match, with the constructors of each type in
+// the order of their tags. Declared constructors come first, since they shadow the constructors of
+// the runtime library.
+fn module_constructors(module: &IrModule) -> Vec<Constructor<'_>> {
+    let declared = module.constructors().map(|v| Constructor {
+        name: &v.name,
+        arity: v.arity(),
+        type_name: &v.type_name,
+    });
+    let intrinsic = intrinsics::CONSTRUCTORS.iter().map(|v| Constructor {
+        name: v.name,
+        arity: v.arity(),
+        type_name: v.ret_type,
+    });
+    declared.chain(intrinsic).collect()
+}
+
+// constructor_of(&Pattern, &'a [Constructor<'b>]) -> Option<&'a Constructor<'b>>
+// Returns the constructor a pattern matches, or None if the pattern matches any value.
+fn constructor_of<'a, 'b>(
+    pattern: &Pattern,
+    constructors: &'a [Constructor<'b>],
+) -> Option<&'a Constructor<'b>> {
+    match pattern {
+        Pattern::Constructor(c, _) => constructors.iter().find(|v| v.name == c),
+        _ => None,
+    }
+}
+
+// matches_any(&Pattern) -> bool
+// Returns true if a pattern matches every value.
+fn matches_any(pattern: &Pattern) -> bool {
+    matches!(pattern, Pattern::Wildcard | Pattern::Binding(_))
+}
+
+// constructors_of_type(&'a [Constructor<'b>], &str) -> Vec<&'a Constructor<'b>>
+// Returns every constructor of a type in the order of their tags.
+fn constructors_of_type<'a, 'b>(
+    constructors: &'a [Constructor<'b>],
+    type_name: &str,
+) -> Vec<&'a Constructor<'b>> {
+    constructors
+        .iter()
+        .filter(|v| v.type_name == type_name)
+        .collect()
+}
+
+// specialise(&[Vec<&'a Pattern>], &Constructor) -> Vec<Vec<&'a Pattern>>
+// Keeps the rows whose first pattern matches values built by the constructor, replacing that
+// pattern with the patterns of the constructor's fields.
+fn specialise<'a>(rows: &[Vec<&'a Pattern>], constructor: &Constructor) -> Vec<Vec<&'a Pattern>> {
+    rows.iter()
+        .filter_map(|row| match row[0] {
+            Pattern::Constructor(c, fields) if c == constructor.name => {
+                Some(fields.iter().chain(row[1..].iter().cloned()).collect())
+            }
+
+            Pattern::Constructor(_, _)
+            | Pattern::Int(_)
+            | Pattern::Char(_)
+            | Pattern::String(_) => None,
+
+            _ => Some(
+                (0..constructor.arity)
+                    .map(|_| &Pattern::Wildcard)
+                    .chain(row[1..].iter().cloned())
+                    .collect(),
+            ),
+        })
+        .collect()
+}
+
+// default_rows(&[Vec<&'a Pattern>]) -> Vec<Vec<&'a Pattern>>
+// Keeps the rows whose first pattern matches any value, without that pattern.
+fn default_rows<'a>(rows: &[Vec<&'a Pattern>]) -> Vec<Vec<&'a Pattern>> {
+    rows.iter()
+        .filter(|row| matches_any(row[0]))
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+// missing(&[Vec<&Pattern>], usize, &[Constructor]) -> Vec<Vec<Pattern>>
+// Returns rows of patterns for the values that none of the given rows match, where each row has
+// the given number of columns. An empty result means the rows match every value.
+fn missing(
+    rows: &[Vec<&Pattern>],
+    columns: usize,
+    constructors: &[Constructor],
+) -> Vec<Vec<Pattern>> {
+    if columns == 0 {
+        return if rows.is_empty() {
+            vec![vec![]]
+        } else {
+            vec![]
+        };
+    }
+
+    // Get the constructors used in the first column and every constructor of their type
+    let used: Vec<_> = rows
+        .iter()
+        .filter_map(|row| constructor_of(row[0], constructors))
+        .collect();
+    let all = match used.first() {
+        Some(c) => constructors_of_type(constructors, c.type_name),
+        None => vec![],
+    };
+    let unused: Vec<_> = all
+        .iter()
+        .filter(|c| !used.iter().any(|u| u.name == c.name))
+        .collect();
+
+    // Every constructor is used, so the fields of each one are checked in turn
+    if !all.is_empty() && unused.is_empty() {
+        let mut result = vec![];
+        for c in all {
+            for mut row in missing(&specialise(rows, c), c.arity + columns - 1, constructors) {
+                let rest = row.split_off(c.arity);
+                result.push(
+                    std::iter::once(Pattern::Constructor(String::from(c.name), row))
+                        .chain(rest)
+                        .collect(),
+                );
+            }
+        }
+        return result;
+    }
+
+    // Otherwise only the rows that match any value in the first column can match the constructors
+    // that are not used, or the values of types with literals, which can never all be listed
+    let mut result = vec![];
+    for rest in missing(&default_rows(rows), columns - 1, constructors) {
+        if unused.is_empty() {
+            result.push(std::iter::once(Pattern::Wildcard).chain(rest).collect());
+        } else {
+            for c in unused.iter() {
+                let fields = vec![Pattern::Wildcard; c.arity];
+                result.push(
+                    std::iter::once(Pattern::Constructor(String::from(c.name), fields))
+                        .chain(rest.iter().cloned())
+                        .collect(),
+                );
+            }
+        }
+    }
+    result
+}
+
+// useful(&[Vec<&Pattern>], Vec<&Pattern>, &[Constructor]) -> bool
+// Returns true if the row of patterns matches a value that none of the given rows match.
+fn useful(rows: &[Vec<&Pattern>], row: Vec<&Pattern>, constructors: &[Constructor]) -> bool {
+    if row.is_empty() {
+        return rows.is_empty();
+    }
+
+    if let Some(c) = constructor_of(row[0], constructors) {
+        let row = specialise(&[row], c).remove(0);
+        return useful(&specialise(rows, c), row, constructors);
+    }
+
+    // A literal is only matched by the same literal or by patterns that match any value
+    if !matches_any(row[0]) {
+        let rows: Vec<_> = rows
+            .iter()
+            .filter(|v| v[0] == row[0] || matches_any(v[0]))
+            .map(|v| v[1..].to_vec())
+            .collect();
+        return useful(&rows, row[1..].to_vec(), constructors);
+    }
+
+    // A pattern that matches any value is useful if it matches a constructor that every row
+    // before it misses
+    let used: Vec<_> = rows
+        .iter()
+        .filter_map(|row| constructor_of(row[0], constructors))
+        .collect();
+    let all = match used.first() {
+        Some(c) => constructors_of_type(constructors, c.type_name),
+        None => vec![],
+    };
+
+    if !all.is_empty() && all.iter().all(|c| used.iter().any(|u| u.name == c.name)) {
+        all.into_iter().any(|c| {
+            let row = specialise(std::slice::from_ref(&row), c).remove(0);
+            useful(&specialise(rows, c), row, constructors)
+        })
+    } else {
+        useful(&default_rows(rows), row[1..].to_vec(), constructors)
+    }
+}
+
+// check_match(&[(Pattern, SExpr, Location)], &Location, &[Constructor], &mut Vec<MatchError>) -> ()
+// Checks that the arms of a match expression match every value and that every arm is reachable.
+fn check_match(
+    arms: &[(Pattern, SExpr, Location)],
+    loc: &Location,
+    constructors: &[Constructor],
+    errors: &mut Vec<MatchError>,
+) {
+    // Unknown constructors are reported by the type checker
+    fn known(pattern: &Pattern, constructors: &[Constructor]) -> bool {
+        match pattern {
+            Pattern::Constructor(_, fields) => {
+                constructor_of(pattern, constructors).is_some()
+                    && fields.iter().all(|v| known(v, constructors))
+            }
+            _ => true,
+        }
+    }
+    if !arms.iter().all(|v| known(&v.0, constructors)) {
+        return;
+    }
+
+    // An arm is unreachable if every value it matches is matched by an earlier arm
+    let mut rows: Vec<Vec<&Pattern>> = vec![];
+    for (pattern, _, arm_loc) in arms {
+        if !useful(&rows, vec![pattern], constructors) {
+            debug!(pattern = %pattern, "unreachable arm");
+            errors.push(MatchError::UnreachableArm(arm_loc.clone()));
+        }
+        rows.push(vec![pattern]);
+    }
+
+    let unmatched = missing(&rows, 1, constructors);
+    if !unmatched.is_empty() {
+        errors.push(MatchError::NonExhaustive(
+            loc.clone(),
+            unmatched.into_iter().map(|mut v| v.remove(0)).collect(),
+        ));
+    }
+}
+
+// check_sexpr(&SExpr, &[Constructor], &mut Vec<MatchError>) -> ()
+// Checks the match expressions in an s expression. The arms of match expressions are functions,
+// so they are checked along with the other functions of the module.
+fn check_sexpr(sexpr: &SExpr, constructors: &[Constructor], errors: &mut Vec<MatchError>) {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _)
+        | SExpr::Function(_, _) => (),
+
+        SExpr::ExternalFunc(_, _, args) => {
+            for arg in args {
+                check_sexpr(arg, constructors, errors);
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            check_sexpr(a, constructors, errors);
+            check_sexpr(b, constructors, errors);
+        }
+
+        SExpr::Application(_, func, args) => {
+            check_sexpr(func, constructors, errors);
+            for arg in args {
+                check_sexpr(arg, constructors, errors);
+            }
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => check_sexpr(v, constructors, errors),
+
+        SExpr::With(_, assigns, v) => {
+            for assign in assigns {
+                check_sexpr(assign, constructors, errors);
+            }
+            check_sexpr(v, constructors, errors);
+        }
+
+        SExpr::Match(m, v, arms) => {
+            check_sexpr(v, constructors, errors);
+            check_match(arms, &m.loc, constructors, errors);
+        }
+    }
+}
+
+// check_module(&IrModule) -> Vec<MatchError>
+// Checks every match expression in a module, returning the problems found in the order they
+// appear in the source.
+pub fn check_module(module: &IrModule) -> Vec<MatchError> {
+    let _span = info_span!("check_module", name = %module.name).entered();
+    let constructors = module_constructors(module);
+    let mut errors = vec![];
+    for func in module.funcs.values() {
+        check_sexpr(&func.body, &constructors, &mut errors);
+    }
+
+    errors.sort_by_key(|e| (e.loc().filename.clone(), e.loc().span.start));
+    errors
+}
+
+// check_matches(&Ir) -> Vec<MatchError>
+// Checks every match expression in every module.
+pub fn check_matches(ir: &Ir) -> Vec<MatchError> {
+    ir.modules.values().flat_map(check_module).collect()
+}
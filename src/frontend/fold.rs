@@ -0,0 +1,533 @@
+use std::cmp::Ordering;
+use tracing::{debug, info_span};
+
+use super::ir::{
+    ArityInfo, BinOp, Ir, IrModule, Location, Pattern, PrefixOp, SExpr, SExprMetadata,
+    MATCH_VALUE_ARG,
+};
+use super::types::{arc, IntSize, Type};
+
+// is_shadowed(&str, &IrModule) -> bool
+// Returns true if an external function or constructor declared in the module shadows the intrinsic
+// with the given name.
+fn is_shadowed(name: &str, module: &IrModule) -> bool {
+    module.externals.contains_key(name) || module.constructors.contains_key(name)
+}
+
+// bool_value(&SExpr, &IrModule) -> Option<bool>
+// Returns the value of a bool literal, which is `true` or `false` unless they are shadowed.
+fn bool_value(sexpr: &SExpr, module: &IrModule) -> Option<bool> {
+    match sexpr {
+        SExpr::ExternalFunc(_, name, args) if args.is_empty() && !is_shadowed(name, module) => {
+            match name.as_str() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            }
+        }
+
+        _ => None,
+    }
+}
+
+// bool_literal(&SExprMetadata, bool, &IrModule) -> Option<SExpr>
+// Creates a bool literal at the location of the given metadata, or returns None if `true` or
+// `false` is shadowed.
+fn bool_literal(m: &SExprMetadata, b: bool, module: &IrModule) -> Option<SExpr> {
+    let name = if b { "true" } else { "false" };
+    if is_shadowed(name, module) {
+        return None;
+    }
+
+    let m = SExprMetadata {
+        _type: arc::new(Type::Bool),
+        arity: ArityInfo::Known(0),
+        tailrec: false,
+        impure: false,
+        ..m.clone()
+    };
+    Some(SExpr::ExternalFunc(m, String::from(name), vec![]))
+}
+
+// is_literal(&SExpr, &IrModule) -> bool
+// Returns true if an s expression is a literal, which can be used in place of a variable bound to
+// it.
+fn is_literal(sexpr: &SExpr, module: &IrModule) -> bool {
+    matches!(
+        sexpr,
+        SExpr::Int(_, _) | SExpr::Float(_, _) | SExpr::Char(_, _) | SExpr::String(_, _)
+    ) || bool_value(sexpr, module).is_some()
+}
+
+// compare(BinOp, Option<Ordering>) -> bool
+// Returns the value of a comparison of two operands ordered as given. Operands without an order,
+// such as floats that are NaN, are neither less than, greater than, nor equal to each other.
+fn compare(op: BinOp, ordering: Option<Ordering>) -> bool {
+    match op {
+        BinOp::LT => ordering == Some(Ordering::Less),
+        BinOp::GT => ordering == Some(Ordering::Greater),
+        BinOp::LEQ => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        BinOp::GEQ => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        BinOp::EQ => ordering == Some(Ordering::Equal),
+        BinOp::NEQ => ordering != Some(Ordering::Equal),
+        _ => unreachable!("only comparisons order their operands"),
+    }
+}
+
+// fold_int_infix(BinOp, i128, i128, IntSize) -> Option<i128>
+// Returns the value of an arithmetic or bitwise operation on the values of two ints of the given
+// size, the same as the runtime library computes it. Operations whose result depends on whether
+// overflow is checked, because the result does not fit in the type, and division by zero, which
+// panics, are left to run.
+fn fold_int_infix(op: BinOp, a: i128, b: i128, size: IntSize) -> Option<i128> {
+    let exact = match op {
+        BinOp::Div | BinOp::Mod if b == 0 => return None,
+
+        // The product of two large unsigned 64 bit ints is beyond even 128 bits
+        BinOp::Mul => a.checked_mul(b)?,
+        BinOp::Div => a / b,
+        BinOp::Add => a + b,
+        BinOp::Sub => a - b,
+
+        // The remainder always fits, and shift amounts are taken modulo 64
+        BinOp::Mod => return Some(a % b),
+        BinOp::LShift => return Some(a << (b & 63)),
+        BinOp::RShift => return Some(a >> (b & 63)),
+        BinOp::BitAnd => return Some(a & b),
+        BinOp::BitXor => return Some(a ^ b),
+        BinOp::BitOr => return Some(a | b),
+        _ => return None,
+    };
+
+    if size.fits(exact) {
+        Some(exact)
+    } else {
+        None
+    }
+}
+
+// fold_infix(&SExprMetadata, BinOp, &SExpr, &SExpr, &IrModule) -> Option<SExpr>
+// Returns the literal an infix operator applied to two literals evaluates to, if it is known at
+// compile time.
+fn fold_infix(
+    m: &SExprMetadata,
+    op: BinOp,
+    left: &SExpr,
+    right: &SExpr,
+    module: &IrModule,
+) -> Option<SExpr> {
+    match (left, right) {
+        (SExpr::Int(lm, a), SExpr::Int(rm, b)) => {
+            let size = IntSize::of(&lm._type)?;
+            if IntSize::of(&rm._type) != Some(size) {
+                return None;
+            }
+
+            let (a, b) = (size.value(*a), size.value(*b));
+            if op.is_comparison() {
+                bool_literal(m, compare(op, Some(a.cmp(&b))), module)
+            } else {
+                let n = fold_int_infix(op, a, b, size)?;
+                Some(SExpr::Int(m.clone(), size.wrap(n)))
+            }
+        }
+
+        (SExpr::Float(_, a), SExpr::Float(_, b)) => match op {
+            BinOp::Mul => Some(SExpr::Float(m.clone(), a * b)),
+            BinOp::Div => Some(SExpr::Float(m.clone(), a / b)),
+            BinOp::Add => Some(SExpr::Float(m.clone(), a + b)),
+            BinOp::Sub => Some(SExpr::Float(m.clone(), a - b)),
+            _ if op.is_comparison() => bool_literal(m, compare(op, a.partial_cmp(b)), module),
+            _ => None,
+        },
+
+        (SExpr::Char(_, a), SExpr::Char(_, b)) if op.is_comparison() => {
+            bool_literal(m, compare(op, Some(a.cmp(b))), module)
+        }
+
+        (SExpr::String(_, a), SExpr::String(_, b)) if matches!(op, BinOp::EQ | BinOp::NEQ) => {
+            bool_literal(m, compare(op, Some(a.cmp(b))), module)
+        }
+
+        _ => {
+            let (a, b) = (bool_value(left, module)?, bool_value(right, module)?);
+            match op {
+                BinOp::Xor | BinOp::NEQ => bool_literal(m, a != b, module),
+                BinOp::EQ => bool_literal(m, a == b, module),
+                _ => None,
+            }
+        }
+    }
+}
+
+// fold_constant(&SExpr, &IrModule) -> Option<SExpr>
+// Returns the literal an s expression evaluates to if its value is known at compile time. Only
+// operators and intrinsics without side effects are evaluated, and external functions and
+// constructors declared in the module shadow the intrinsics with the same name.
+fn fold_constant(sexpr: &SExpr, module: &IrModule) -> Option<SExpr> {
+    match sexpr {
+        SExpr::ExternalFunc(m, name, args) if !is_shadowed(name, module) => {
+            match (name.as_str(), args.as_slice()) {
+                ("concat", [SExpr::String(_, a), SExpr::String(_, b)]) => {
+                    Some(SExpr::String(m.clone(), format!("{}{}", a, b)))
+                }
+
+                ("char_to_string", [SExpr::Char(_, c)]) => {
+                    Some(SExpr::String(m.clone(), c.to_string()))
+                }
+
+                _ => None,
+            }
+        }
+
+        SExpr::Prefix(m, PrefixOp::Not, v) => bool_literal(m, !bool_value(v, module)?, module),
+
+        SExpr::Prefix(m, PrefixOp::BitNot, v) => match &**v {
+            SExpr::Int(_, n) => {
+                let size = IntSize::of(&m._type)?;
+                Some(SExpr::Int(m.clone(), size.wrap(!size.value(*n))))
+            }
+            _ => None,
+        },
+
+        SExpr::Infix(m, op, a, b) => fold_infix(m, *op, a, b, module),
+
+        // Literals are already given the types they are ascribed while type checking
+        SExpr::Ascribe(_, v) if is_literal(v, module) => Some((**v).clone()),
+
+        _ => None,
+    }
+}
+
+// pattern_matches(&Pattern, &SExpr, &IrModule) -> Option<bool>
+// Returns whether a pattern matches a literal, or None if that is not known at compile time.
+fn pattern_matches(pattern: &Pattern, value: &SExpr, module: &IrModule) -> Option<bool> {
+    match (pattern, value) {
+        (Pattern::Wildcard, _) | (Pattern::Binding(_), _) => Some(true),
+
+        (Pattern::Constructor(c, fields), _) if fields.is_empty() => {
+            let b = bool_value(value, module)?;
+            match c.as_str() {
+                "true" => Some(b),
+                "false" => Some(!b),
+                _ => None,
+            }
+        }
+
+        (Pattern::Int(n), SExpr::Int(m, v)) => Some(IntSize::of(&m._type)?.value(*v) == *n as i128),
+        (Pattern::Char(c), SExpr::Char(_, v)) => Some(c == v),
+        (Pattern::String(s), SExpr::String(_, v)) => Some(s == v),
+
+        _ => None,
+    }
+}
+
+// fold_match(&SExprMetadata, &SExpr, &[(Pattern, SExpr, Location)], &IrModule) -> Option<SExpr>
+// Returns the body of the arm a match of a literal takes, such as the `then` branch of `if true`,
+// with the matched value and the values bound by the pattern bound to their names around it. The
+// arms after it are never taken, so they are left out.
+fn fold_match(
+    m: &SExprMetadata,
+    value: &SExpr,
+    arms: &[(Pattern, SExpr, Location)],
+    module: &IrModule,
+) -> Option<SExpr> {
+    if !is_literal(value, module) {
+        return None;
+    }
+
+    let mut taken = None;
+    for (pattern, arm, _) in arms {
+        if pattern_matches(pattern, value, module)? {
+            taken = Some((pattern, arm));
+            break;
+        }
+    }
+
+    // A literal no arm matches panics when it is matched, which is left to run
+    let (pattern, arm) = taken?;
+    let func = match arm {
+        SExpr::Function(_, f) => module.funcs.get(f)?,
+        _ => return None,
+    };
+
+    // Every value a pattern matching a literal binds is the literal itself
+    let mut names = vec![String::from(MATCH_VALUE_ARG)];
+    pattern.bindings(&mut names);
+    if names.len() != func.args.len() {
+        return None;
+    }
+
+    let assigns = func
+        .args
+        .iter()
+        .map(|(name, _)| {
+            SExpr::Assign(
+                value.get_metadata().clone(),
+                name.clone(),
+                Box::new(value.clone()),
+            )
+        })
+        .collect();
+    Some(SExpr::With(m.clone(), assigns, Box::new(func.body.clone())))
+}
+
+// substitute(&mut SExpr, &str, &SExpr) -> bool
+// Replaces the uses of a variable in an s expression with a literal, stopping where the variable
+// is shadowed. The arms of match expressions and other functions only see the variable if they
+// capture it, so they are left as they are. Returns true if the variable is shadowed by an
+// assignment, after which nothing else sees it.
+fn substitute(sexpr: &mut SExpr, name: &str, literal: &SExpr) -> bool {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _)
+        | SExpr::Function(_, _) => (),
+
+        SExpr::Symbol(m, s) => {
+            if s == name {
+                let mut value = literal.clone();
+                value.get_mutable_metadata().loc = m.loc.clone();
+                *sexpr = value;
+            }
+        }
+
+        SExpr::ExternalFunc(_, _, args) => {
+            for arg in args {
+                substitute(arg, name, literal);
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            substitute(a, name, literal);
+            substitute(b, name, literal);
+        }
+
+        SExpr::Application(_, func, args) => {
+            substitute(func, name, literal);
+            for arg in args {
+                substitute(arg, name, literal);
+            }
+        }
+
+        // The value of an assignment is evaluated before the name it is assigned to is bound
+        SExpr::Assign(_, a, v) => {
+            substitute(v, name, literal);
+            return a == name;
+        }
+
+        SExpr::As(_, v) | SExpr::Ascribe(_, v) | SExpr::Prefix(_, _, v) => {
+            substitute(v, name, literal);
+        }
+
+        SExpr::With(_, assigns, v) => {
+            for assign in assigns {
+                if substitute(assign, name, literal) {
+                    return false;
+                }
+            }
+            substitute(v, name, literal);
+        }
+
+        SExpr::Match(_, v, _) => {
+            substitute(v, name, literal);
+        }
+    }
+
+    false
+}
+
+// captures(&SExpr, &str, &IrModule) -> bool
+// Returns true if a function made in an s expression captures a variable with the given name.
+fn captures(sexpr: &SExpr, name: &str, module: &IrModule) -> bool {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _) => false,
+
+        SExpr::Function(_, f) => {
+            matches!(module.funcs.get(f), Some(f) if f.captured.contains_key(name))
+        }
+
+        SExpr::ExternalFunc(_, _, args) => args.iter().any(|v| captures(v, name, module)),
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            captures(a, name, module) || captures(b, name, module)
+        }
+
+        SExpr::Application(_, func, args) => {
+            captures(func, name, module) || args.iter().any(|v| captures(v, name, module))
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => captures(v, name, module),
+
+        SExpr::With(_, assigns, v) => {
+            assigns.iter().any(|a| captures(a, name, module)) || captures(v, name, module)
+        }
+
+        SExpr::Match(_, v, arms) => {
+            captures(v, name, module) || arms.iter().any(|(_, a, _)| captures(a, name, module))
+        }
+    }
+}
+
+// propagate(&mut Vec<SExpr>, &mut SExpr, &IrModule) -> ()
+// Folds the bindings of a with expression in order, replacing the uses of variables bound to
+// literals with the literals so that the bindings after them can be folded too, and removes the
+// bindings that nothing else uses. Bindings captured by functions made in the body are
+// kept, since the functions take them from the enclosing function.
+fn propagate(assigns: &mut Vec<SExpr>, body: &mut SExpr, module: &IrModule) {
+    let mut i = 0;
+    while i < assigns.len() {
+        fold_sexpr(&mut assigns[i], false, module);
+        let (name, literal) = match &assigns[i] {
+            SExpr::Assign(_, name, v) if is_literal(v, module) => (name.clone(), (**v).clone()),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        let mut shadowed = false;
+        for assign in assigns[i + 1..].iter_mut() {
+            if substitute(assign, &name, &literal) {
+                shadowed = true;
+                break;
+            }
+        }
+        if !shadowed {
+            substitute(body, &name, &literal);
+        }
+
+        let captured = assigns[i + 1..].iter().any(|v| captures(v, &name, module))
+            || !shadowed && captures(body, &name, module);
+        if captured {
+            i += 1;
+        } else {
+            debug!(name = %name, value = %literal, "propagated");
+            assigns.remove(i);
+        }
+    }
+}
+
+// clear_tail_calls(&mut SExpr) -> ()
+// Unmarks the applications in tail position of an s expression, for when it is moved to where its
+// value is not returned.
+fn clear_tail_calls(sexpr: &mut SExpr) {
+    match sexpr {
+        SExpr::Application(m, _, _) => m.tailrec = false,
+        SExpr::Chain(_, _, v) | SExpr::With(_, _, v) => clear_tail_calls(v),
+        _ => (),
+    }
+}
+
+// fold_sexpr(&mut SExpr, bool, &IrModule) -> ()
+// Replaces the subexpressions of an s expression whose values are known at compile time with
+// literals, propagates literals bound in with expressions to where they are used, and replaces
+// matches of literals with the arm they take. The arms of match expressions are functions, so
+// they are folded along with the other functions of the module as well. `tail` is true if the
+// value of the s expression is returned by the function it is in.
+fn fold_sexpr(sexpr: &mut SExpr, tail: bool, module: &IrModule) {
+    // Arguments are folded first, so that the expressions using them can be folded as well
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _)
+        | SExpr::Function(_, _) => (),
+
+        SExpr::ExternalFunc(_, _, args) => {
+            for arg in args {
+                fold_sexpr(arg, false, module);
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) => {
+            fold_sexpr(a, false, module);
+            fold_sexpr(b, false, module);
+        }
+
+        SExpr::Chain(_, a, b) => {
+            fold_sexpr(a, false, module);
+            fold_sexpr(b, tail, module);
+        }
+
+        SExpr::Application(_, func, args) => {
+            fold_sexpr(func, false, module);
+            for arg in args {
+                fold_sexpr(arg, false, module);
+            }
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => fold_sexpr(v, false, module),
+
+        SExpr::With(_, assigns, v) => {
+            propagate(assigns, v, module);
+            fold_sexpr(v, tail, module);
+
+            if assigns.is_empty() {
+                let body = std::mem::replace(&mut **v, SExpr::Empty(SExprMetadata::empty()));
+                *sexpr = body;
+            }
+            return;
+        }
+
+        SExpr::Match(m, v, arms) => {
+            fold_sexpr(v, false, module);
+            if let Some(mut taken) = fold_match(m, v, arms, module) {
+                debug!(from = %sexpr, "folded match");
+                if !tail {
+                    clear_tail_calls(&mut taken);
+                }
+                fold_sexpr(&mut taken, tail, module);
+                *sexpr = taken;
+            }
+            return;
+        }
+    }
+
+    if let Some(folded) = fold_constant(sexpr, module) {
+        debug!(from = %sexpr, to = %folded, "folded");
+        *sexpr = folded;
+    }
+}
+
+// fold_module(&mut IrModule) -> ()
+// Folds the constant expressions in every function of a module. The module must have been type
+// checked.
+pub fn fold_module(module: &mut IrModule) {
+    let _span = info_span!("fold_module", name = %module.name).entered();
+    let names: Vec<_> = module.funcs.keys().cloned().collect();
+    for name in names {
+        let mut body = std::mem::replace(
+            &mut module.funcs.get_mut(&name).unwrap().body,
+            SExpr::Empty(SExprMetadata::empty()),
+        );
+        fold_sexpr(&mut body, true, module);
+        module.funcs.get_mut(&name).unwrap().body = body;
+    }
+}
+
+// fold_constants(&mut Ir) -> ()
+// Folds the constant expressions in every module.
+pub fn fold_constants(ir: &mut Ir) {
+    for module in ir.modules.values_mut() {
+        fold_module(module);
+    }
+}
@@ -0,0 +1,1099 @@
+use logos::Span;
+use std::collections::HashMap;
+
+use super::parser::Ast;
+use super::types::{self, arc, Type, TypeRc};
+
+// Represents a function that is built into the compiler and implemented by the runtime library.
+pub struct Intrinsic {
+    // The name the intrinsic is referred to by in code.
+    pub name: &'static str,
+
+    // How uses of the intrinsic are lowered into the backend IR, which every backend generates code
+    // from.
+    pub lowering: Lowering,
+
+    // The names of the argument types.
+    pub arg_types: &'static [&'static str],
+
+    // The name of the return type.
+    pub ret_type: &'static str,
+
+    // Whether the intrinsic has side effects.
+    pub impure: bool,
+
+    // Whether the source location of each use is passed to the runtime function after the
+    // arguments, as a string of the form `file:line:column`.
+    pub located: bool,
+
+    // The index of the argument that is the key of a map or the item searched for in an array, if
+    // any. Keys are hashed and compared differently depending on their type, so the runtime
+    // function called is the runtime name followed by `_int`, `_bool`, or `_string`.
+    pub key_arg: Option<usize>,
+
+    // The index of the argument whose source code is passed to the runtime function after the
+    // location, if any, so that the code can be shown when the intrinsic fails.
+    pub quoted_arg: Option<usize>,
+
+    // The index of the argument that is shown before it is passed to the runtime function, if any.
+    // The backend derives a show function for the type of the argument, which converts values of
+    // that type into the string the REPL shows them as.
+    pub shown_arg: Option<usize>,
+}
+
+// Represents how an intrinsic is lowered into the backend IR. The arguments are lowered first, and
+// the location, quoted code, and shown argument of the intrinsic are applied to them.
+pub enum Lowering {
+    // Calls the runtime library function with the given name, followed by the suffix for the type
+    // of the key argument if there is one.
+    Runtime(&'static str),
+
+    // Evaluates to the string the shown argument is shown as, without calling the runtime library.
+    Shown,
+
+    // Evaluates to the given int, without calling the runtime library. Bools are passed as their
+    // values, so `true` and `false` are the ints 1 and 0.
+    Constant(i64),
+}
+
+// The registry of all intrinsics. The type checker, the lowering into the backend IR, and the
+// interpreter all look intrinsics up here, so an intrinsic is added by adding it to the registry,
+// implementing it in the runtime library, and evaluating it in the interpreter.
+pub const INTRINSICS: &[Intrinsic] = &[
+    // Console IO
+    Intrinsic {
+        name: "print",
+        lowering: Lowering::Runtime("closey_print"),
+        arg_types: &["String"],
+        ret_type: "Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "println",
+        lowering: Lowering::Runtime("closey_println"),
+        arg_types: &["String"],
+        ret_type: "Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "debug",
+        lowering: Lowering::Runtime("closey_debug"),
+        arg_types: &["'a"],
+        ret_type: "Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: Some(0),
+    },
+    Intrinsic {
+        name: "read_line",
+        lowering: Lowering::Runtime("closey_read_line"),
+        arg_types: &["Unit"],
+        ret_type: "String",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Strings
+    Intrinsic {
+        name: "concat",
+        lowering: Lowering::Runtime("string_concat"),
+        arg_types: &["String", "String"],
+        ret_type: "String",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "string_eq",
+        lowering: Lowering::Runtime("string_equals"),
+        arg_types: &["String", "String"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "show",
+        lowering: Lowering::Shown,
+        arg_types: &["'a"],
+        ret_type: "String",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: Some(0),
+    },
+    Intrinsic {
+        name: "format_float",
+        lowering: Lowering::Runtime("closey_format_float"),
+        arg_types: &["Float", "Int", "Int"],
+        ret_type: "String",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "format_scientific",
+        lowering: Lowering::Runtime("closey_format_scientific"),
+        arg_types: &["Float", "Int", "Int"],
+        ret_type: "String",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Chars
+    Intrinsic {
+        name: "char_eq",
+        lowering: Lowering::Runtime("closey_char_eq"),
+        arg_types: &["Char", "Char"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "char_lt",
+        lowering: Lowering::Runtime("closey_char_lt"),
+        arg_types: &["Char", "Char"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "char_le",
+        lowering: Lowering::Runtime("closey_char_le"),
+        arg_types: &["Char", "Char"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "char_gt",
+        lowering: Lowering::Runtime("closey_char_gt"),
+        arg_types: &["Char", "Char"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "char_ge",
+        lowering: Lowering::Runtime("closey_char_ge"),
+        arg_types: &["Char", "Char"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "char_to_string",
+        lowering: Lowering::Runtime("closey_char_to_string"),
+        arg_types: &["Char"],
+        ret_type: "String",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // File IO
+    Intrinsic {
+        name: "open",
+        lowering: Lowering::Runtime("closey_open"),
+        arg_types: &["String"],
+        ret_type: "Result Int",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "close",
+        lowering: Lowering::Runtime("closey_close"),
+        arg_types: &["Int"],
+        ret_type: "Result Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "read_to_string",
+        lowering: Lowering::Runtime("closey_read_to_string"),
+        arg_types: &["String"],
+        ret_type: "Result String",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "write",
+        lowering: Lowering::Runtime("closey_write"),
+        arg_types: &["String", "String"],
+        ret_type: "Result Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "append",
+        lowering: Lowering::Runtime("closey_append"),
+        arg_types: &["String", "String"],
+        ret_type: "Result Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "exists",
+        lowering: Lowering::Runtime("closey_exists"),
+        arg_types: &["String"],
+        ret_type: "Bool",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Maths
+    Intrinsic {
+        name: "sqrt",
+        lowering: Lowering::Runtime("closey_sqrt"),
+        arg_types: &["Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "sin",
+        lowering: Lowering::Runtime("closey_sin"),
+        arg_types: &["Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "cos",
+        lowering: Lowering::Runtime("closey_cos"),
+        arg_types: &["Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "tan",
+        lowering: Lowering::Runtime("closey_tan"),
+        arg_types: &["Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "exp",
+        lowering: Lowering::Runtime("closey_exp"),
+        arg_types: &["Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "ln",
+        lowering: Lowering::Runtime("closey_ln"),
+        arg_types: &["Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "floor",
+        lowering: Lowering::Runtime("closey_floor"),
+        arg_types: &["Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "ceil",
+        lowering: Lowering::Runtime("closey_ceil"),
+        arg_types: &["Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "abs",
+        lowering: Lowering::Runtime("closey_abs"),
+        arg_types: &["Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "min",
+        lowering: Lowering::Runtime("closey_min"),
+        arg_types: &["Float", "Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "max",
+        lowering: Lowering::Runtime("closey_max"),
+        arg_types: &["Float", "Float"],
+        ret_type: "Float",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "abs_int",
+        lowering: Lowering::Runtime("closey_abs_int"),
+        arg_types: &["Int"],
+        ret_type: "Int",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "min_int",
+        lowering: Lowering::Runtime("closey_min_int"),
+        arg_types: &["Int", "Int"],
+        ret_type: "Int",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "max_int",
+        lowering: Lowering::Runtime("closey_max_int"),
+        arg_types: &["Int", "Int"],
+        ret_type: "Int",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Process control
+    Intrinsic {
+        name: "exit",
+        lowering: Lowering::Runtime("closey_exit"),
+        arg_types: &["Int"],
+        ret_type: "Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "run_command",
+        lowering: Lowering::Runtime("closey_run_command"),
+        arg_types: &["String"],
+        ret_type: "Result Int",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Time
+    Intrinsic {
+        name: "now_millis",
+        lowering: Lowering::Runtime("closey_now_millis"),
+        arg_types: &["Unit"],
+        ret_type: "Int",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "clock_monotonic",
+        lowering: Lowering::Runtime("closey_clock_monotonic"),
+        arg_types: &["Unit"],
+        ret_type: "Int",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Random numbers
+    Intrinsic {
+        name: "seed_random",
+        lowering: Lowering::Runtime("closey_seed_random"),
+        arg_types: &["Int"],
+        ret_type: "Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "random_int",
+        lowering: Lowering::Runtime("closey_random_int"),
+        arg_types: &["Int", "Int"],
+        ret_type: "Int",
+        impure: true,
+        located: true,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "random_float",
+        lowering: Lowering::Runtime("closey_random_float"),
+        arg_types: &["Unit"],
+        ret_type: "Float",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Results
+    Intrinsic {
+        name: "ok",
+        lowering: Lowering::Runtime("closey_ok"),
+        arg_types: &["'a"],
+        ret_type: "Result 'a",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "err",
+        lowering: Lowering::Runtime("closey_err"),
+        arg_types: &["String"],
+        ret_type: "Result 'a",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "is_ok",
+        lowering: Lowering::Runtime("closey_is_ok"),
+        arg_types: &["Result 'a"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "is_err",
+        lowering: Lowering::Runtime("closey_is_err"),
+        arg_types: &["Result 'a"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "unwrap",
+        lowering: Lowering::Runtime("closey_unwrap"),
+        arg_types: &["Result 'a"],
+        ret_type: "'a",
+        impure: true,
+        located: true,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "unwrap_or",
+        lowering: Lowering::Runtime("closey_unwrap_or"),
+        arg_types: &["Result 'a", "'a"],
+        ret_type: "'a",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "error",
+        lowering: Lowering::Runtime("closey_error"),
+        arg_types: &["Result 'a"],
+        ret_type: "String",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Bools
+    Intrinsic {
+        name: "true",
+        lowering: Lowering::Constant(1),
+        arg_types: &[],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "false",
+        lowering: Lowering::Constant(0),
+        arg_types: &[],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Options
+    Intrinsic {
+        name: "some",
+        lowering: Lowering::Runtime("closey_some"),
+        arg_types: &["'a"],
+        ret_type: "Option 'a",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "none",
+        lowering: Lowering::Runtime("closey_none"),
+        arg_types: &[],
+        ret_type: "Option 'a",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "is_some",
+        lowering: Lowering::Runtime("closey_is_some"),
+        arg_types: &["Option 'a"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "is_none",
+        lowering: Lowering::Runtime("closey_is_none"),
+        arg_types: &["Option 'a"],
+        ret_type: "Bool",
+        impure: false,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Arrays
+    Intrinsic {
+        name: "array_make",
+        lowering: Lowering::Runtime("closey_array_make"),
+        arg_types: &["Int", "'a"],
+        ret_type: "Array 'a",
+        impure: true,
+        located: true,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "array_of",
+        lowering: Lowering::Runtime("closey_array_of"),
+        arg_types: &["'a"],
+        ret_type: "Array 'a",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "array_get",
+        lowering: Lowering::Runtime("closey_array_get"),
+        arg_types: &["Array 'a", "Int"],
+        ret_type: "'a",
+        impure: true,
+        located: true,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "array_set",
+        lowering: Lowering::Runtime("closey_array_set"),
+        arg_types: &["Array 'a", "Int", "'a"],
+        ret_type: "Array 'a",
+        impure: true,
+        located: true,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "array_push",
+        lowering: Lowering::Runtime("closey_array_push"),
+        arg_types: &["Array 'a", "'a"],
+        ret_type: "Array 'a",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "array_length",
+        lowering: Lowering::Runtime("closey_array_length"),
+        arg_types: &["Array 'a"],
+        ret_type: "Int",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "array_contains",
+        lowering: Lowering::Runtime("closey_array_contains"),
+        arg_types: &["Array 'a", "'a"],
+        ret_type: "Bool",
+        impure: true,
+        located: false,
+        key_arg: Some(1),
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Maps
+    Intrinsic {
+        name: "map_new",
+        lowering: Lowering::Runtime("closey_map_new"),
+        arg_types: &["Int"],
+        ret_type: "Map 'k 'v",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "map_insert",
+        lowering: Lowering::Runtime("closey_map_insert"),
+        arg_types: &["Map 'k 'v", "'k", "'v"],
+        ret_type: "Map 'k 'v",
+        impure: true,
+        located: false,
+        key_arg: Some(1),
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "map_get",
+        lowering: Lowering::Runtime("closey_map_get"),
+        arg_types: &["Map 'k 'v", "'k"],
+        ret_type: "Result 'v",
+        impure: true,
+        located: false,
+        key_arg: Some(1),
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "map_remove",
+        lowering: Lowering::Runtime("closey_map_remove"),
+        arg_types: &["Map 'k 'v", "'k"],
+        ret_type: "Map 'k 'v",
+        impure: true,
+        located: false,
+        key_arg: Some(1),
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "map_contains",
+        lowering: Lowering::Runtime("closey_map_contains"),
+        arg_types: &["Map 'k 'v", "'k"],
+        ret_type: "Bool",
+        impure: true,
+        located: false,
+        key_arg: Some(1),
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "map_length",
+        lowering: Lowering::Runtime("closey_map_length"),
+        arg_types: &["Map 'k 'v"],
+        ret_type: "Int",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "map_keys",
+        lowering: Lowering::Runtime("closey_map_keys"),
+        arg_types: &["Map 'k 'v"],
+        ret_type: "Array 'k",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "map_values",
+        lowering: Lowering::Runtime("closey_map_values"),
+        arg_types: &["Map 'k 'v"],
+        ret_type: "Array 'v",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Threads
+    Intrinsic {
+        name: "spawn",
+        lowering: Lowering::Runtime("closey_spawn"),
+        arg_types: &["Unit -> 'a"],
+        ret_type: "Thread 'a",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "join",
+        lowering: Lowering::Runtime("closey_join"),
+        arg_types: &["Thread 'a"],
+        ret_type: "'a",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Tasks
+    Intrinsic {
+        name: "async",
+        lowering: Lowering::Runtime("closey_async"),
+        arg_types: &["Unit -> 'a"],
+        ret_type: "Task 'a",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "await",
+        lowering: Lowering::Runtime("closey_await"),
+        arg_types: &["Task 'a"],
+        ret_type: "'a",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "yield",
+        lowering: Lowering::Runtime("closey_yield"),
+        arg_types: &["Unit"],
+        ret_type: "Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "sleep",
+        lowering: Lowering::Runtime("closey_sleep"),
+        arg_types: &["Int"],
+        ret_type: "Unit",
+        impure: true,
+        located: false,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+    // Assertions
+    Intrinsic {
+        name: "assert",
+        lowering: Lowering::Runtime("closey_assert"),
+        arg_types: &["Bool", "String"],
+        ret_type: "Unit",
+        impure: true,
+        located: true,
+        key_arg: None,
+        quoted_arg: Some(0),
+        shown_arg: None,
+    },
+    Intrinsic {
+        name: "panic",
+        lowering: Lowering::Runtime("closey_panic"),
+        arg_types: &["String"],
+        ret_type: "'a",
+        impure: true,
+        located: true,
+        key_arg: None,
+        quoted_arg: None,
+        shown_arg: None,
+    },
+];
+
+impl Intrinsic {
+    // arity(&self) -> usize
+    // Returns the number of arguments the intrinsic takes.
+    pub fn arity(&self) -> usize {
+        self.arg_types.len()
+    }
+
+    // _type(&self) -> TypeRc
+    // Returns the type of the intrinsic. Generics in the type should be instantiated before use.
+    pub fn _type(&self) -> TypeRc {
+        function_type(self.arg_types, self.ret_type)
+    }
+}
+
+// Represents a constructor of values built into the runtime library, which can be destructured in
+// the arms of match expressions.
+pub struct Constructor {
+    // The name the constructor is referred to by in patterns.
+    pub name: &'static str,
+
+    // The character that stands for the constructor in the patterns passed to the runtime library.
+    pub code: char,
+
+    // The names of the types of the fields.
+    pub field_types: &'static [&'static str],
+
+    // The name of the type of the values built by the constructor.
+    pub ret_type: &'static str,
+}
+
+// The registry of all constructors.
+pub const CONSTRUCTORS: &[Constructor] = &[
+    Constructor {
+        name: "ok",
+        code: 'o',
+        field_types: &["'a"],
+        ret_type: "Result 'a",
+    },
+    Constructor {
+        name: "err",
+        code: 'e',
+        field_types: &["String"],
+        ret_type: "Result 'a",
+    },
+    Constructor {
+        name: "some",
+        code: 's',
+        field_types: &["'a"],
+        ret_type: "Option 'a",
+    },
+    Constructor {
+        name: "none",
+        code: 'n',
+        field_types: &[],
+        ret_type: "Option 'a",
+    },
+    Constructor {
+        name: "true",
+        code: 't',
+        field_types: &[],
+        ret_type: "Bool",
+    },
+    Constructor {
+        name: "false",
+        code: 'f',
+        field_types: &[],
+        ret_type: "Bool",
+    },
+];
+
+impl Constructor {
+    // arity(&self) -> usize
+    // Returns the number of fields of the constructor.
+    pub fn arity(&self) -> usize {
+        self.field_types.len()
+    }
+
+    // _type(&self) -> TypeRc
+    // Returns the type of the constructor as a function from its fields. Generics in the type
+    // should be instantiated before use.
+    pub fn _type(&self) -> TypeRc {
+        function_type(self.field_types, self.ret_type)
+    }
+}
+
+// get_constructor(&str) -> Option<&'static Constructor>
+// Returns the constructor with the given name.
+pub fn get_constructor(name: &str) -> Option<&'static Constructor> {
+    CONSTRUCTORS.iter().find(|v| v.name == name)
+}
+
+// function_type(&[&str], &str) -> TypeRc
+// Converts the names of argument types and a return type into the type of a function.
+fn function_type(arg_types: &[&str], ret_type: &str) -> TypeRc {
+    let mut generic_uids = HashMap::new();
+    let mut last_uid = 0;
+    let mut _type = arc::new(type_from_name(ret_type, &mut generic_uids, &mut last_uid));
+    for arg in arg_types.iter().rev() {
+        let arg = type_from_name(arg, &mut generic_uids, &mut last_uid);
+        _type = arc::new(Type::Func(arc::new(arg), _type));
+    }
+    _type
+}
+
+// key_suffix(&Type) -> &'static str
+// Returns the suffix of the runtime functions used for keys of the given type.
+pub fn key_suffix(key: &Type) -> &'static str {
+    match key {
+        // Chars are stored as their code points, so they are hashed and compared as ints
+        Type::Int | Type::SizedInt(_) | Type::Char => "_int",
+        Type::Bool => "_bool",
+        Type::Symbol(s) if s == "String" => "_string",
+        _ => panic!("{} cannot be used as a key", key),
+    }
+}
+
+// type_from_name(&str, &mut HashMap<String, usize>, &mut usize) -> Type
+// Converts the name of a type into a type the same way a type annotation is converted.
+fn type_from_name(
+    name: &str,
+    generic_uids: &mut HashMap<String, usize>,
+    last_uid: &mut usize,
+) -> Type {
+    types::convert_ast_to_type(ast_from_name(name), "", generic_uids, last_uid)
+}
+
+// ast_from_name(&str) -> Ast
+// Converts the name of a type, generic, type application (Array 'a), or function type
+// (Unit -> 'a) into the ast of a type annotation.
+fn ast_from_name(name: &str) -> Ast {
+    let span = Span { start: 0, end: 0 };
+    if let Some((arg, ret)) = name.split_once(" -> ") {
+        return Ast::Infix(
+            span,
+            String::from("->"),
+            Box::new(ast_from_name(arg)),
+            Box::new(ast_from_name(ret)),
+        );
+    }
+
+    let mut parts = name
+        .split_whitespace()
+        .map(|name| match name.strip_prefix('\'') {
+            Some(g) => Ast::Generic(span.clone(), String::from(g)),
+            None => Ast::Symbol(span.clone(), String::from(name)),
+        });
+
+    let func = parts.next().unwrap();
+    let args: Vec<_> = parts.collect();
+    if args.is_empty() {
+        func
+    } else {
+        Ast::Application(span, Box::new(func), args)
+    }
+}
+
+// get(&str) -> Option<&'static Intrinsic>
+// Returns the intrinsic with the given name.
+pub fn get(name: &str) -> Option<&'static Intrinsic> {
+    INTRINSICS.iter().find(|v| v.name == name)
+}
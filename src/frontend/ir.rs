@@ -1,12 +1,15 @@
 use logos::Span;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use tracing::{info_span, trace};
 
-use super::parser::Ast;
-use super::scopes::Scope;
+use super::intrinsics;
+use super::parser::{self, Ast};
+use super::prelude;
+use super::scopes::{Scope, ScopeSnapshot};
 use super::types;
-use super::types::{arc, Type, TypeRc};
+use super::types::{arc, IntSize, Type, TypeRc};
 
 // Represents a location
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -44,6 +47,9 @@ pub enum IrError {
     UnsupportedAnnotation(Location, String),
     InvalidFFIType(Location, TypeRc),
     DuplicateModule(String, DuplicateModuleInfo),
+    InvalidConstructor(Location),
+    DuplicateConstructor(Location, Location, String),
+    InfiniteType(Location, String),
 }
 
 pub enum DuplicateModuleInfo {
@@ -69,6 +75,7 @@ pub struct SExprMetadata {
     pub origin: String,
     pub _type: TypeRc,
     pub arity: ArityInfo,
+    // Whether the expression is an application in tail position
     pub tailrec: bool,
     pub impure: bool,
 }
@@ -98,26 +105,26 @@ pub enum SExpr {
     // Type alias
     TypeAlias(SExprMetadata, String),
 
-    /*
-    // Ints
+    // Ints of any int type, stored in 64 bits
     Int(SExprMetadata, i64),
 
     // Floats
     Float(SExprMetadata, f64),
 
+    /*
     // Words
     Word(SExprMetadata, u64),
-
-    // Chars
-    Char(SExprMetadata, u8),
     */
+    // Chars
+    Char(SExprMetadata, char),
+
     // Symbols
     Symbol(SExprMetadata, String),
 
-    /*
     // Strings
     String(SExprMetadata, String),
 
+    /*
     // Lists
     List(SExprMetadata, Vec<SExpr>),
     */
@@ -127,9 +134,22 @@ pub enum SExpr {
     // External function application
     ExternalFunc(SExprMetadata, String, Vec<SExpr>),
 
+    // Prefix operators
+    Prefix(SExprMetadata, PrefixOp, Box<SExpr>),
+
+    // Binary operators
+    Infix(SExprMetadata, BinOp, Box<SExpr>, Box<SExpr>),
+
     // Chain operator
     Chain(SExprMetadata, Box<SExpr>, Box<SExpr>),
 
+    // Conversion of a value into the type in the metadata
+    As(SExprMetadata, Box<SExpr>),
+
+    // Ascription of the type in the metadata to a value, which is checked to have the type rather
+    // than converted into it
+    Ascribe(SExprMetadata, Box<SExpr>),
+
     // Function application
     Application(SExprMetadata, Box<SExpr>, Vec<SExpr>),
 
@@ -140,8 +160,9 @@ pub enum SExpr {
     With(SExprMetadata, Vec<SExpr>, Box<SExpr>),
     //Walrus(SExprMetadata, String, Box<SExpr>),
 
-    // Match expressions
-    Match(SExprMetadata, Box<SExpr>, Vec<(TypeRc, SExpr, Location)>),
+    // Match expressions. The value of each arm is a function taking the matched value followed by
+    // the values bound by the pattern.
+    Match(SExprMetadata, Box<SExpr>, Vec<(Pattern, SExpr, Location)>),
     // Member access
     // MemberAccess(SExprMetadata, Vec<String>),
 }
@@ -152,9 +173,26 @@ impl Display for SExpr {
             SExpr::Empty(_) => todo!(),
             SExpr::TypeAlias(_, _) => todo!(),
             SExpr::Symbol(m, s) => write!(f, "{}: {}", s, m._type),
+            SExpr::Int(m, n) => match IntSize::of(&m._type) {
+                Some(size) => write!(f, "{}: {}", size.value(*n), m._type),
+                None => write!(f, "{}: {}", n, m._type),
+            },
+            SExpr::Float(m, n) => write!(f, "{:?}: {}", n, m._type),
+            SExpr::Char(m, c) => write!(f, "{:?}: {}", c, m._type),
+            SExpr::String(m, s) => write!(f, "{:?}: {}", s, m._type),
             SExpr::Function(m, func) => write!(f, "func-get {}: {}", func, m._type),
-            SExpr::ExternalFunc(_, _, _) => todo!(),
+            SExpr::ExternalFunc(m, func, args) => {
+                write!(f, "extern {}", func)?;
+                for arg in args {
+                    write!(f, " ({})", arg)?;
+                }
+                write!(f, " : {}", m._type)
+            }
+            SExpr::Prefix(m, op, v) => write!(f, "{} ({}) : {}", op, v, m._type),
+            SExpr::Infix(m, op, l, r) => write!(f, "({}) {} ({}) : {}", l, op, r, m._type),
             SExpr::Chain(_, _, _) => todo!(),
+            SExpr::As(m, v) => write!(f, "({}) as {}", v, m._type),
+            SExpr::Ascribe(m, v) => write!(f, "({}) : {}", v, m._type),
             SExpr::Application(m, func, args) => {
                 write!(f, "({})", func)?;
                 for arg in args {
@@ -168,8 +206,320 @@ impl Display for SExpr {
             }
 
             SExpr::Assign(m, v, a) => write!(f, "set {}: {} = ({})", v, m._type, a),
-            SExpr::With(_, _, _) => todo!(),
-            SExpr::Match(_, _, _) => todo!(),
+            SExpr::With(m, assigns, body) => {
+                write!(f, "with")?;
+                for assign in assigns {
+                    write!(f, " ({})", assign)?;
+                }
+                write!(f, " in ({}) : {}", body, m._type)
+            }
+            SExpr::Match(m, v, arms) => {
+                write!(f, "match ({})", v)?;
+                for (pattern, arm, _) in arms {
+                    write!(f, " to {} => ({})", pattern, arm)?;
+                }
+                write!(f, " : {}", m._type)
+            }
+        }
+    }
+}
+
+// Represents a prefix operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixOp {
+    Not,
+    BitNot,
+}
+
+impl PrefixOp {
+    // parse(&str) -> Option<PrefixOp>
+    // Returns the operator written as the given string.
+    pub fn parse(op: &str) -> Option<PrefixOp> {
+        Some(match op {
+            "not" => PrefixOp::Not,
+            "~" => PrefixOp::BitNot,
+            _ => return None,
+        })
+    }
+
+    // as_str(&self) -> &'static str
+    // Returns the operator as it is written in code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrefixOp::Not => "not",
+            PrefixOp::BitNot => "~",
+        }
+    }
+
+    // name(&self) -> &'static str
+    // Returns the name of the operator, which the runtime functions implementing it are named
+    // after. Both are named `not`, since they apply to different types.
+    pub fn name(&self) -> &'static str {
+        "not"
+    }
+
+    // accepts(&self, &Type) -> bool
+    // Returns true if the operator can be applied to an operand of the given type. `not` works on
+    // bools and `~` on ints.
+    pub fn accepts(&self, _type: &Type) -> bool {
+        match self {
+            PrefixOp::Not => *_type == Type::Bool,
+            PrefixOp::BitNot => IntSize::of(_type).is_some(),
+        }
+    }
+
+    // operand_types(&self) -> &'static str
+    // Returns a description of the types the operator accepts, for error messages.
+    pub fn operand_types(&self) -> &'static str {
+        match self {
+            PrefixOp::Not => "`Bool`s",
+            PrefixOp::BitNot => "ints",
+        }
+    }
+}
+
+impl Display for PrefixOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// Represents a binary operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Mul,
+    Div,
+    Mod,
+    Add,
+    Sub,
+    LShift,
+    RShift,
+    BitAnd,
+    BitXor,
+    BitOr,
+    LT,
+    GT,
+    LEQ,
+    GEQ,
+    EQ,
+    NEQ,
+    Xor,
+}
+
+impl BinOp {
+    // parse(&str) -> Option<BinOp>
+    // Returns the operator written as the given string.
+    pub fn parse(op: &str) -> Option<BinOp> {
+        Some(match op {
+            "*" => BinOp::Mul,
+            "/" => BinOp::Div,
+            "%" => BinOp::Mod,
+            "+" => BinOp::Add,
+            "-" => BinOp::Sub,
+            "<<" => BinOp::LShift,
+            ">>" => BinOp::RShift,
+            "&" => BinOp::BitAnd,
+            "^" => BinOp::BitXor,
+            "|" => BinOp::BitOr,
+            "<" => BinOp::LT,
+            ">" => BinOp::GT,
+            "<=" => BinOp::LEQ,
+            ">=" => BinOp::GEQ,
+            "==" => BinOp::EQ,
+            "!=" => BinOp::NEQ,
+            "xor" => BinOp::Xor,
+            _ => return None,
+        })
+    }
+
+    // as_str(&self) -> &'static str
+    // Returns the operator as it is written in code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Mod => "%",
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::LShift => "<<",
+            BinOp::RShift => ">>",
+            BinOp::BitAnd => "&",
+            BinOp::BitXor => "^",
+            BinOp::BitOr => "|",
+            BinOp::LT => "<",
+            BinOp::GT => ">",
+            BinOp::LEQ => "<=",
+            BinOp::GEQ => ">=",
+            BinOp::EQ => "==",
+            BinOp::NEQ => "!=",
+            BinOp::Xor => "xor",
+        }
+    }
+
+    // name(&self) -> &'static str
+    // Returns the name of the operator, which the runtime functions implementing it are named
+    // after.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BinOp::Mul => "mul",
+            BinOp::Div => "div",
+            BinOp::Mod => "mod",
+            BinOp::Add => "add",
+            BinOp::Sub => "sub",
+            BinOp::LShift => "shl",
+            BinOp::RShift => "shr",
+            BinOp::BitAnd => "and",
+            BinOp::BitXor => "xor",
+            BinOp::BitOr => "or",
+            BinOp::LT => "lt",
+            BinOp::GT => "gt",
+            BinOp::LEQ => "le",
+            BinOp::GEQ => "ge",
+            BinOp::EQ => "eq",
+            BinOp::NEQ => "ne",
+            BinOp::Xor => "xor",
+        }
+    }
+
+    // is_comparison(&self) -> bool
+    // Returns true if the operator compares its operands, which makes its value a bool.
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinOp::LT | BinOp::GT | BinOp::LEQ | BinOp::GEQ | BinOp::EQ | BinOp::NEQ
+        )
+    }
+
+    // accepts(&self, &Type) -> bool
+    // Returns true if the operator can be applied to operands of the given type. Arithmetic works
+    // on numbers, remainders, shifts, and bitwise operators on ints, `xor` on bools, ordering on
+    // numbers and chars, and equality on every primitive and strings. Equality also works on values
+    // built from these, which the type checker checks against the declared types.
+    pub fn accepts(&self, _type: &Type) -> bool {
+        let int = IntSize::of(_type).is_some();
+        match self {
+            BinOp::Mul | BinOp::Div | BinOp::Add | BinOp::Sub => int || *_type == Type::Float,
+            BinOp::Mod | BinOp::LShift | BinOp::RShift => int,
+            BinOp::BitAnd | BinOp::BitXor | BinOp::BitOr => int,
+            BinOp::Xor => *_type == Type::Bool,
+            BinOp::LT | BinOp::GT | BinOp::LEQ | BinOp::GEQ => {
+                int || matches!(_type, Type::Float | Type::Char)
+            }
+            BinOp::EQ | BinOp::NEQ => {
+                int || matches!(_type, Type::Float | Type::Char | Type::Bool)
+                    || matches!(_type, Type::Symbol(s) if s == "String")
+            }
+        }
+    }
+
+    // operand_types(&self) -> &'static str
+    // Returns a description of the types the operator accepts, for error messages.
+    pub fn operand_types(&self) -> &'static str {
+        match self {
+            BinOp::Mul | BinOp::Div | BinOp::Add | BinOp::Sub => "ints or `Float`s",
+            BinOp::Mod | BinOp::LShift | BinOp::RShift => "ints",
+            BinOp::BitAnd | BinOp::BitXor | BinOp::BitOr => "ints",
+            BinOp::Xor => "`Bool`s",
+            BinOp::LT | BinOp::GT | BinOp::LEQ | BinOp::GEQ => "ints, `Float`s, or `Char`s",
+            BinOp::EQ | BinOp::NEQ => "values holding no functions, threads, tasks, or generics",
+        }
+    }
+}
+
+impl Display for BinOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// The name of the argument the matched value is passed in to the arms of a match expression.
+pub const MATCH_VALUE_ARG: &str = ".match";
+
+// The prefix of the names that the values unwrapped by `?` are bound to in the matches it becomes.
+pub const PROPAGATED_VALUE_PREFIX: &str = ".try";
+
+// Represents a pattern in an arm of a match expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    // Matches any value
+    Wildcard,
+
+    // Matches any value and binds it to a name
+    Binding(String),
+
+    // Matches values built by a constructor whose fields match the given patterns
+    Constructor(String, Vec<Pattern>),
+
+    // Matches values equal to a literal
+    Int(i64),
+    Char(char),
+    String(String),
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Binding(b) => write!(f, "{}", b),
+            Pattern::Int(n) => write!(f, "{}", n),
+            Pattern::Char(c) => write!(f, "{:?}", c),
+            Pattern::String(s) => write!(f, "{:?}", s),
+            Pattern::Constructor(c, fields) => {
+                write!(f, "{}", c)?;
+                for field in fields {
+                    if let Pattern::Constructor(_, v) = field {
+                        if !v.is_empty() {
+                            write!(f, " ({})", field)?;
+                            continue;
+                        }
+                    }
+                    write!(f, " {}", field)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Pattern {
+    // bindings(&self, &mut Vec<String>) -> ()
+    // Collects the names bound by the pattern from left to right, which is the order the bound
+    // values are passed to the arm in.
+    pub fn bindings(&self, names: &mut Vec<String>) {
+        match self {
+            Pattern::Wildcard | Pattern::Int(_) | Pattern::Char(_) | Pattern::String(_) => (),
+            Pattern::Binding(b) => names.push(b.clone()),
+            Pattern::Constructor(_, fields) => {
+                for field in fields {
+                    field.bindings(names);
+                }
+            }
+        }
+    }
+
+    // encode(&self, &IrModule, &mut String) -> ()
+    // Encodes the pattern in the form the runtime library matches values against: `_` for a
+    // wildcard, `b` for a binding, and the code of a constructor followed by its fields. Declared
+    // constructors are encoded as `d` followed by their tag and arity, each ended by a comma. Int
+    // and char literals are encoded as `i` and `c` followed by their value and a comma, and string
+    // literals as `"` followed by their length in bytes, a comma, and their bytes.
+    pub fn encode(&self, module: &IrModule, code: &mut String) {
+        match self {
+            Pattern::Wildcard => code.push('_'),
+            Pattern::Binding(_) => code.push('b'),
+            Pattern::Int(n) => code.push_str(&format!("i{},", n)),
+            Pattern::Char(c) => code.push_str(&format!("c{},", *c as u32)),
+            Pattern::String(s) => code.push_str(&format!("\"{},{}", s.len(), s)),
+            Pattern::Constructor(c, fields) => {
+                if let Some(c) = module.constructors.get(c) {
+                    code.push_str(&format!("d{},{},", c.tag, c.arity()));
+                } else {
+                    code.push(intrinsics::get_constructor(c).unwrap().code);
+                }
+                for field in fields {
+                    field.encode(module, code);
+                }
+            }
         }
     }
 }
@@ -181,17 +531,20 @@ impl SExpr {
         match self {
             Self::Empty(m)
             | Self::TypeAlias(m, _)
-            /*| Self::Int(m, _)
+            | Self::Int(m, _)
             | Self::Float(m, _)
-            | Self::Word(m, _)
-            | Self::Char(m, _)*/
+            //| Self::Word(m, _)
+            | Self::Char(m, _)
             | Self::Symbol(m, _)
-            //| Self::String(m, _)
+            | Self::String(m, _)
             //| Self::List(m, _)
             | Self::Function(m, _)
             | Self::ExternalFunc(m, _, _)
+            | Self::Prefix(m, _, _)
+            | Self::Infix(m, _, _, _)
             | Self::Chain(m, _, _)
-            //| Self::As(m, _)
+            | Self::As(m, _)
+            | Self::Ascribe(m, _)
             | Self::Application(m, _, _)
             | Self::Assign(m, _, _)
             | Self::With(m, _, _)
@@ -207,17 +560,20 @@ impl SExpr {
         match self {
             Self::Empty(m)
             | Self::TypeAlias(m, _)
-            /*| Self::Int(m, _)
+            | Self::Int(m, _)
             | Self::Float(m, _)
-            | Self::Word(m, _)
-            | Self::Char(m, _)*/
+            //| Self::Word(m, _)
+            | Self::Char(m, _)
             | Self::Symbol(m, _)
-            //| Self::String(m, _)
+            | Self::String(m, _)
             //| Self::List(m, _)
             | Self::Function(m, _)
             | Self::ExternalFunc(m, _, _)
+            | Self::Prefix(m, _, _)
+            | Self::Infix(m, _, _, _)
             | Self::Chain(m, _, _)
-            //| Self::As(m, _)
+            | Self::As(m, _)
+            | Self::Ascribe(m, _)
             | Self::Application(m, _, _)
             | Self::Assign(m, _, _)
             | Self::With(m, _, _)
@@ -235,8 +591,15 @@ pub struct IrFunction {
     pub name: String,
     pub _type: TypeRc,
     pub args: Vec<(String, TypeRc)>,
+
+    // The variables of enclosing functions that the function uses. Values are never mutated, so
+    // they are captured by value: each takes one word in the closure, whatever its type, and is
+    // passed to the function before its arguments in the order of `captured_names`. Ints, floats,
+    // bools, and chars are stored as their values, and every other value is a pointer that the
+    // closure holds a reference to.
     pub captured: HashMap<String, TypeRc>,
     pub captured_names: Vec<String>,
+
     pub body: SExpr,
     pub global: bool,
     pub checked: bool,
@@ -257,6 +620,30 @@ impl Display for IrFunction {
     }
 }
 
+impl IrFunction {
+    // ret_type(&self) -> TypeRc
+    // Returns the type of the value the function returns once it is applied to all of its
+    // arguments.
+    pub fn ret_type(&self) -> TypeRc {
+        let mut ret = self._type.clone();
+        for _ in self.args.iter() {
+            if let Type::Func(_, r) = &*ret.clone() {
+                ret = r.clone();
+            }
+        }
+        ret
+    }
+
+    // c_signature(&self) -> CSignature
+    // Returns the C signature the function is called with when it is exported.
+    pub fn c_signature(&self) -> CSignature {
+        CSignature {
+            args: self.args.iter().map(|v| CType::from_type(&v.1)).collect(),
+            ret: CType::from_type(&self.ret_type()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct IrImport {
     pub name: String,
@@ -274,6 +661,151 @@ pub struct IrExtern {
     pub impure: bool,
 }
 
+impl IrExtern {
+    // c_signature(&self) -> CSignature
+    // Returns the C signature the external function is called with.
+    pub fn c_signature(&self) -> CSignature {
+        CSignature {
+            args: self.arg_types.iter().map(|v| CType::from_type(v)).collect(),
+            ret: CType::from_type(&self.ret_type),
+        }
+    }
+}
+
+// The prefix of the internal names of exported functions. The C function for an exported global
+// takes the name of the global itself.
+pub const EXPORT_PREFIX: &str = "export.";
+
+// The most arguments passed to external functions in registers. Floats are only moved into the
+// registers C expects them in when every argument is passed in a register, and callbacks take at
+// most this many arguments.
+pub const FFI_REGISTER_ARGS: usize = 6;
+
+// Represents the C type a value is passed to or returned from an external function as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CType {
+    // int64_t, or a pointer to a value such as a string, an array, or a callback
+    Word,
+
+    // double
+    Double,
+
+    // bool
+    Bool,
+
+    // uint32_t, holding a unicode scalar value
+    Char,
+
+    // int8_t through uint64_t, for the sized int types
+    Int(IntSize),
+}
+
+impl CType {
+    // from_type(&Type) -> CType
+    // Returns the C type values of a type are passed as.
+    pub fn from_type(_type: &Type) -> CType {
+        match _type {
+            Type::Float => CType::Double,
+            Type::Bool => CType::Bool,
+            Type::Char => CType::Char,
+            Type::SizedInt(size) => CType::Int(*size),
+            _ => CType::Word,
+        }
+    }
+
+    // representable(&Type) -> bool
+    // Returns true if values of a type can be passed to and returned from C. Strings, arrays, and
+    // values of data types are passed as pointers to the values of the runtime library, while
+    // functions and values whose type is not known are not passed at all.
+    pub fn representable(_type: &Type) -> bool {
+        matches!(
+            _type,
+            Type::Int
+                | Type::SizedInt(_)
+                | Type::Float
+                | Type::Bool
+                | Type::Char
+                | Type::Symbol(_)
+                | Type::Array(_)
+                | Type::Map(_, _)
+                | Type::Result(_)
+                | Type::Option(_)
+        )
+    }
+}
+
+impl Display for CType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CType::Word => write!(f, "int64_t"),
+            CType::Double => write!(f, "double"),
+            CType::Bool => write!(f, "bool"),
+            CType::Char => write!(f, "uint32_t"),
+            CType::Int(size) => {
+                let prefix = if size.signed { "int" } else { "uint" };
+                write!(f, "{}{}_t", prefix, size.bits)
+            }
+        }
+    }
+}
+
+// Represents the C signature of an external function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CSignature {
+    pub args: Vec<CType>,
+    pub ret: CType,
+}
+
+impl CSignature {
+    // is_word_only(&self) -> bool
+    // Returns true if every argument and the return value are passed as int64_t, the same as
+    // functions of the runtime library.
+    pub fn is_word_only(&self) -> bool {
+        self.ret == CType::Word && self.args.iter().all(|v| *v == CType::Word)
+    }
+}
+
+// Represents a constructor of a type declared in a module.
+#[derive(Debug)]
+pub struct IrConstructor {
+    pub loc: Location,
+    pub name: String,
+
+    // The name of the type of the values built by the constructor.
+    pub type_name: String,
+
+    // The index of the constructor in the declaration of its type, which identifies the
+    // constructor of a value at runtime.
+    pub tag: usize,
+
+    pub field_types: Vec<TypeRc>,
+}
+
+impl IrConstructor {
+    // arity(&self) -> usize
+    // Returns the number of fields of the constructor.
+    pub fn arity(&self) -> usize {
+        self.field_types.len()
+    }
+
+    // _type(&self) -> TypeRc
+    // Returns the type of the constructor as a function from its fields.
+    pub fn _type(&self) -> TypeRc {
+        let ret_type = arc::new(Type::Symbol(self.type_name.clone()));
+        arc::new(Type::function(&self.field_types, ret_type))
+    }
+}
+
+// Represents a test declared in a module, whose value must be true for the test to pass.
+#[derive(Debug, Clone)]
+pub struct IrTest {
+    pub loc: Location,
+    pub name: String,
+
+    // The internal name of the function without arguments that evaluates the test.
+    pub func: String,
+}
+
 // Represents a module of the ir.
 #[derive(Debug)]
 pub struct IrModule {
@@ -288,22 +820,88 @@ pub struct IrModule {
     pub funcs: HashMap<String, IrFunction>,
     pub types: HashMap<String, TypeRc>,
     pub globals: HashMap<String, String>,
+
+    // The globals defined by the prelude and the functions they are bound to. Prelude functions
+    // refer to each other through these even when the program shadows them.
+    pub prelude: HashMap<String, String>,
+
+    // The functions of globals without arguments that shadow another global, and the functions of
+    // the globals they shadow. The body of such a global refers to the global it shadows by its
+    // name, since referring to itself would never finish, so `x = x + 1` adds 1 to the earlier `x`.
+    pub shadowed: HashMap<String, String>,
+
+    // The constructors of the types declared in the module.
+    pub constructors: HashMap<String, IrConstructor>,
+
+    // The globals annotated with `@export`, in the order they are defined, which are callable from
+    // C under their own names.
+    pub c_exports: Vec<String>,
+
+    // The tests declared in the module, in the order they are declared. Their functions are not
+    // globals, so they cannot be referred to by the program.
+    pub tests: Vec<IrTest>,
+
+    // The types of global functions before their bodies are checked, used by references to
+    // functions that are still being checked. Each returns a generic that stands for its return
+    // type until the function has been checked.
+    pub signatures: HashMap<String, TypeRc>,
+
+    // The doc comments of the globals and types defined in the module, by name.
+    pub docs: HashMap<String, String>,
+
+    // The last uid assigned to an instantiated generic in this module.
+    pub last_uid: usize,
+
+    // The number of functions given each name so far, used to give every function a unique
+    // internal name.
+    pub func_names: HashMap<String, usize>,
+}
+
+// Represents the definitions in a module at some point, used to undo the definitions added
+// after it.
+pub struct ModuleSnapshot {
+    funcs: HashSet<String>,
+    externals: HashSet<String>,
+    constructors: HashSet<String>,
+    signatures: HashSet<String>,
+    globals: HashMap<String, String>,
+    types: HashMap<String, TypeRc>,
+    c_exports: Vec<String>,
+    tests: usize,
+    docs: HashMap<String, String>,
+    scope: ScopeSnapshot,
 }
 
 impl Display for IrModule {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "(module {}", self.name)?;
-        for func in self.funcs.iter() {
-            write!(f, "\n        {}", func.1)?;
+        for c in self.constructors() {
+            write!(
+                f,
+                "\n        (constructor {} {} : {})",
+                c.name,
+                c.tag,
+                c._type()
+            )?;
         }
 
-        for (global, raw) in self.globals.iter() {
+        for func in self.functions() {
+            write!(f, "\n        {}", func)?;
+        }
+
+        for (global, func) in self.global_functions() {
             write!(
                 f,
                 "\n        (global {} = func-get {} : {})",
-                global,
-                raw,
-                self.funcs.get(raw).unwrap()._type
+                global, func.name, func._type
+            )?;
+        }
+
+        for test in self.tests.iter() {
+            write!(
+                f,
+                "\n        (test {:?} = func-get {})",
+                test.name, test.func
             )?;
         }
 
@@ -319,8 +917,8 @@ pub struct Ir {
 impl Display for Ir {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "(")?;
-        for module in self.modules.iter() {
-            write!(f, "\n    {}", module.1)?;
+        for module in self.modules() {
+            write!(f, "\n    {}", module)?;
         }
         write!(f, ")")
     }
@@ -332,6 +930,40 @@ impl Ir {
             modules: HashMap::new(),
         }
     }
+
+    // module(&self, &str) -> Option<&IrModule>
+    // Returns the module with the given name.
+    pub fn module(&self, name: &str) -> Option<&IrModule> {
+        self.modules.get(name)
+    }
+
+    // modules(&self) -> impl Iterator<Item = &IrModule>
+    // Returns an iterator over all modules, sorted by name.
+    pub fn modules(&self) -> impl Iterator<Item = &IrModule> {
+        let mut modules: Vec<_> = self.modules.values().collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+        modules.into_iter()
+    }
+
+    // functions(&self) -> impl Iterator<Item = (&IrModule, &IrFunction)>
+    // Returns an iterator over all functions in every module, sorted by module name and then by
+    // function name.
+    pub fn functions(&self) -> impl Iterator<Item = (&IrModule, &IrFunction)> {
+        self.modules()
+            .flat_map(|m| m.functions().map(move |f| (m, f)))
+    }
+
+    // lookup(&self, &str) -> Option<(&IrModule, &IrFunction)>
+    // Looks up a global value by name. Qualified names of the form `Module::name` are looked up in
+    // the given module; unqualified names are looked up in each module in order of name.
+    pub fn lookup(&self, name: &str) -> Option<(&IrModule, &IrFunction)> {
+        if let Some((module, global)) = name.rsplit_once("::") {
+            let module = self.module(module)?;
+            return module.global(global).map(|f| (module, f));
+        }
+
+        self.modules().find_map(|m| m.global(name).map(|f| (m, f)))
+    }
 }
 
 impl IrModule {
@@ -351,11 +983,250 @@ impl IrModule {
             funcs: HashMap::with_capacity(0),
             types: HashMap::with_capacity(0),
             globals: HashMap::with_capacity(0),
+            prelude: HashMap::with_capacity(0),
+            shadowed: HashMap::with_capacity(0),
+            constructors: HashMap::with_capacity(0),
+            c_exports: vec![],
+            tests: vec![],
+            signatures: HashMap::with_capacity(0),
+            docs: HashMap::with_capacity(0),
+            last_uid: types::INSTANTIATED_UID_BASE,
+            func_names: std::iter::once((String::with_capacity(0), 0)).collect(),
+        }
+    }
+
+    // function(&self, &str) -> Option<&IrFunction>
+    // Returns the function with the given internal name.
+    pub fn function(&self, name: &str) -> Option<&IrFunction> {
+        self.funcs.get(name)
+    }
+
+    // function_mut(&mut self, &str) -> Option<&mut IrFunction>
+    // Returns a mutable reference to the function with the given internal name.
+    pub fn function_mut(&mut self, name: &str) -> Option<&mut IrFunction> {
+        self.funcs.get_mut(name)
+    }
+
+    // global(&self, &str) -> Option<&IrFunction>
+    // Returns the function a global value is bound to.
+    pub fn global(&self, name: &str) -> Option<&IrFunction> {
+        self.globals.get(name).and_then(|v| self.funcs.get(v))
+    }
+
+    // exits_with_main(&self) -> bool
+    // Returns whether a program exits with the value of main, which it does when main is an int.
+    // Programs whose main is any other value exit with 0, unless they call exit.
+    pub fn exits_with_main(&self) -> bool {
+        match self.global("main") {
+            Some(v) => v.args.is_empty() && matches!(&*v._type, Type::Int | Type::SizedInt(_)),
+            None => false,
+        }
+    }
+
+    // snapshot(&self) -> ModuleSnapshot
+    // Records the definitions in the module, so that the definitions added after this can be
+    // removed with restore.
+    pub fn snapshot(&self) -> ModuleSnapshot {
+        ModuleSnapshot {
+            funcs: self.funcs.keys().cloned().collect(),
+            externals: self.externals.keys().cloned().collect(),
+            constructors: self.constructors.keys().cloned().collect(),
+            signatures: self.signatures.keys().cloned().collect(),
+            globals: self.globals.clone(),
+            types: self.types.clone(),
+            c_exports: self.c_exports.clone(),
+            tests: self.tests.len(),
+            docs: self.docs.clone(),
+            scope: self.scope.snapshot(),
+        }
+    }
+
+    // restore(&mut self, ModuleSnapshot) -> ()
+    // Removes the definitions added since a snapshot was taken, and binds the globals and types
+    // they shadowed again.
+    pub fn restore(&mut self, snapshot: ModuleSnapshot) {
+        self.funcs.retain(|k, _| snapshot.funcs.contains(k));
+        self.shadowed.retain(|k, _| snapshot.funcs.contains(k));
+        self.externals.retain(|k, _| snapshot.externals.contains(k));
+        self.constructors
+            .retain(|k, _| snapshot.constructors.contains(k));
+        self.signatures
+            .retain(|k, _| snapshot.signatures.contains(k));
+        self.globals = snapshot.globals;
+        self.types = snapshot.types;
+        self.c_exports = snapshot.c_exports;
+        self.tests.truncate(snapshot.tests);
+        self.docs = snapshot.docs;
+        self.scope.restore(snapshot.scope);
+    }
+
+    // location_string(&self, &Location) -> String
+    // Converts a location in the module or the prelude into the form `file:line:column`.
+    pub fn location_string(&self, loc: &Location) -> String {
+        let contents = if loc.filename == prelude::FILENAME {
+            prelude::SOURCE
+        } else {
+            &self.contents
+        };
+
+        let before = &contents[..loc.span.start.min(contents.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        format!("{}:{}:{}", loc.filename, line, column)
+    }
+
+    // source_string(&self, &Location) -> String
+    // Returns the code at a location in the module or the prelude, with each run of whitespace
+    // replaced by a single space so that the code fits on one line.
+    pub fn source_string(&self, loc: &Location) -> String {
+        let contents = if loc.filename == prelude::FILENAME {
+            prelude::SOURCE
+        } else {
+            &self.contents
+        };
+
+        contents
+            .get(loc.span.clone())
+            .unwrap_or("")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // functions(&self) -> impl Iterator<Item = &IrFunction>
+    // Returns an iterator over all functions in the module, sorted by name.
+    pub fn functions(&self) -> impl Iterator<Item = &IrFunction> {
+        let mut funcs: Vec<_> = self.funcs.values().collect();
+        funcs.sort_by(|a, b| a.name.cmp(&b.name));
+        funcs.into_iter()
+    }
+
+    // global_functions(&self) -> impl Iterator<Item = (&str, &IrFunction)>
+    // Returns an iterator over all global values and the functions they are bound to, sorted by
+    // name.
+    pub fn global_functions(&self) -> impl Iterator<Item = (&str, &IrFunction)> {
+        let mut globals: Vec<_> = self
+            .globals
+            .iter()
+            .filter_map(|(g, f)| self.funcs.get(f).map(|f| (g.as_str(), f)))
+            .collect();
+        globals.sort_by(|a, b| a.0.cmp(b.0));
+        globals.into_iter()
+    }
+
+    // constructors(&self) -> impl Iterator<Item = &IrConstructor>
+    // Returns an iterator over the constructors of the types declared in the module, sorted by
+    // type name and then by tag.
+    pub fn constructors(&self) -> impl Iterator<Item = &IrConstructor> {
+        let mut constructors: Vec<_> = self.constructors.values().collect();
+        constructors.sort_by(|a, b| (&a.type_name, a.tag).cmp(&(&b.type_name, b.tag)));
+        constructors.into_iter()
+    }
+
+    // call_order(&self) -> impl Iterator<Item = &IrFunction>
+    // Returns an iterator over all functions in the module such that every function comes after
+    // the functions it references, except where functions are mutually recursive. Ties are broken
+    // by name so the order is deterministic.
+    pub fn call_order(&self) -> impl Iterator<Item = &IrFunction> {
+        let mut order = vec![];
+        let mut visited = HashSet::new();
+        for func in self.functions() {
+            self.call_order_helper(func, &mut visited, &mut order);
+        }
+        order.into_iter()
+    }
+
+    // dependencies(&self, &'a IrFunction) -> Vec<&'a IrFunction>
+    // Returns the functions a function references, directly or through other functions, such that
+    // every function comes after the functions it references.
+    pub fn dependencies<'a>(&'a self, func: &'a IrFunction) -> Vec<&'a IrFunction> {
+        let mut order = vec![];
+        self.call_order_helper(func, &mut HashSet::new(), &mut order);
+
+        // The function itself is always visited last
+        order.pop();
+        order
+    }
+
+    // call_order_helper(&self, &'a IrFunction, &mut HashSet<&'a str>, &mut Vec<&'a IrFunction>) -> ()
+    // Visits the functions referenced by a function before adding it to the call order.
+    fn call_order_helper<'a>(
+        &'a self,
+        func: &'a IrFunction,
+        visited: &mut HashSet<&'a str>,
+        order: &mut Vec<&'a IrFunction>,
+    ) {
+        if !visited.insert(&func.name) {
+            return;
+        }
+
+        let mut callees = vec![];
+        self.referenced_functions(&func.body, &mut callees);
+        for callee in callees {
+            self.call_order_helper(callee, visited, order);
+        }
+
+        order.push(func);
+    }
+
+    // referenced_functions(&self, &'a SExpr, &mut Vec<&'a IrFunction>) -> ()
+    // Collects the functions referenced by a checked sexpression in the order they appear.
+    fn referenced_functions<'a>(&'a self, sexpr: &SExpr, funcs: &mut Vec<&'a IrFunction>) {
+        match sexpr {
+            SExpr::Function(_, f) => {
+                if let Some(f) = self.funcs.get(f) {
+                    funcs.push(f);
+                }
+            }
+
+            // Checking replaces global values with the functions they are bound to, so symbols are
+            // local variables, which may have the same name as a global
+            SExpr::Empty(_) | SExpr::Symbol(_, _) | SExpr::TypeAlias(_, _) => (),
+
+            // Literals reference no functions
+            SExpr::Int(_, _) | SExpr::Float(_, _) | SExpr::Char(_, _) | SExpr::String(_, _) => (),
+
+            SExpr::ExternalFunc(_, _, args) => {
+                for arg in args {
+                    self.referenced_functions(arg, funcs);
+                }
+            }
+
+            SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+                self.referenced_functions(a, funcs);
+                self.referenced_functions(b, funcs);
+            }
+
+            SExpr::Application(_, f, args) => {
+                self.referenced_functions(f, funcs);
+                for arg in args {
+                    self.referenced_functions(arg, funcs);
+                }
+            }
+
+            SExpr::Assign(_, _, v)
+            | SExpr::As(_, v)
+            | SExpr::Ascribe(_, v)
+            | SExpr::Prefix(_, _, v) => self.referenced_functions(v, funcs),
+
+            SExpr::With(_, assigns, body) => {
+                for assign in assigns {
+                    self.referenced_functions(assign, funcs);
+                }
+                self.referenced_functions(body, funcs);
+            }
+
+            SExpr::Match(_, v, arms) => {
+                self.referenced_functions(v, funcs);
+                for (_, arm, _) in arms {
+                    self.referenced_functions(arm, funcs);
+                }
+            }
         }
     }
 }
 
-// convert_node(Ast, &str, bool, &mut HashMap<String, IrFunction>, &mut HashMap<String, TypeRc>) -> SExpr
+// convert_node(Ast, &str, bool, &mut HashMap<String, IrFunction>, &mut HashMap<String, TypeRc>, &HashMap<String, IrConstructor>) -> SExpr
 // Converts an ast node into an sexpression.
 fn convert_node(
     ast: Ast,
@@ -364,13 +1235,13 @@ fn convert_node(
     global: bool,
     seen_funcs: &mut HashMap<String, usize>,
     types: &mut HashMap<String, TypeRc>,
+    constructors: &HashMap<String, IrConstructor>,
     generic_uids: &mut HashMap<String, usize>,
     last_uid: &mut usize,
 ) -> SExpr {
     match ast {
         Ast::Empty => unreachable!("never empty"),
 
-        /*
         // Int
         Ast::Int(span, n) => SExpr::Int(
             SExprMetadata {
@@ -378,7 +1249,21 @@ fn convert_node(
                 loc2: Location::empty(),
                 origin: String::with_capacity(0),
                 _type: arc::new(Type::Int),
-                arity: 0,
+                arity: ArityInfo::Known(0),
+                tailrec: false,
+                impure: false,
+            },
+            n,
+        ),
+
+        // Int with a suffix giving its type
+        Ast::SizedInt(span, n, size) => SExpr::Int(
+            SExprMetadata {
+                loc: Location::new(span, filename),
+                loc2: Location::empty(),
+                origin: String::with_capacity(0),
+                _type: arc::new(size.into_type()),
+                arity: ArityInfo::Known(0),
                 tailrec: false,
                 impure: false,
             },
@@ -392,13 +1277,14 @@ fn convert_node(
                 loc2: Location::empty(),
                 origin: String::with_capacity(0),
                 _type: arc::new(Type::Float),
-                arity: 0,
+                arity: ArityInfo::Known(0),
                 tailrec: false,
                 impure: false,
             },
             n,
         ),
 
+        /*
         // Word
         Ast::Word(span, n) => SExpr::Word(
             SExprMetadata {
@@ -456,15 +1342,86 @@ fn convert_node(
             s,
         ),
 
+        // Char
+        Ast::Char(span, c) => SExpr::Char(
+            SExprMetadata {
+                loc: Location::new(span, filename),
+                loc2: Location::empty(),
+                origin: String::with_capacity(0),
+                _type: arc::new(Type::Char),
+                arity: ArityInfo::Known(0),
+                tailrec: false,
+                impure: false,
+            },
+            c,
+        ),
+
+        // String
+        Ast::String(span, s) => SExpr::String(
+            SExprMetadata {
+                loc: Location::new(span, filename),
+                loc2: Location::empty(),
+                origin: String::with_capacity(0),
+                _type: arc::new(Type::Symbol(String::from("String"))),
+                arity: ArityInfo::Known(0),
+                tailrec: false,
+                impure: false,
+            },
+            s,
+        ),
+
+        // Array literals are built by creating an array with the first item and pushing the rest
+        Ast::List(span, list) => {
+            let metadata = SExprMetadata {
+                loc: Location::new(span, filename),
+                loc2: Location::empty(),
+                origin: String::with_capacity(0),
+                _type: arc::new(Type::Error),
+                arity: ArityInfo::Unknown,
+                tailrec: false,
+                impure: true,
+            };
+
+            let mut items = list.into_iter().map(|v| {
+                convert_node(
+                    v,
+                    filename,
+                    funcs,
+                    global,
+                    seen_funcs,
+                    types,
+                    constructors,
+                    generic_uids,
+                    last_uid,
+                )
+            });
+            let first = match items.next() {
+                Some(v) => v,
+                None => panic!("empty array literals are not supported"),
+            };
+
+            let mut array =
+                SExpr::ExternalFunc(metadata.clone(), String::from("array_of"), vec![first]);
+            for item in items {
+                array = SExpr::ExternalFunc(
+                    metadata.clone(),
+                    String::from("array_push"),
+                    vec![array, item],
+                );
+            }
+            array
+        }
+
         Ast::Generic(_, _)
         | Ast::Annotation(_, _)
         | Ast::Import(_, _, _)
         | Ast::QualifiedImport(_, _, _)
         | Ast::Header(_, _, _, _)
         | Ast::LibHeader(_, _, _)
-        | Ast::Extern(_, _, _, _) => {
+        | Ast::Extern(_, _, _, _)
+        | Ast::Test(_, _, _) => {
             unreachable!(
-                "generics, annotations, imports, headers, and external declarations are already handled!"
+                "generics, annotations, imports, headers, external declarations, and tests are already handled!"
             );
         }
 
@@ -590,6 +1547,40 @@ fn convert_node(
                 }
             }
         */
+        // Boolean operators short circuit, so like an `if` they are matches of the left operand
+        // against `true` and `false`. The arm that skips the right operand gives the matched value,
+        // and comes first so that a right operand of another type is the arm reported
+        Ast::Infix(span, op, l, r) if op == "and" || op == "or" => {
+            let l_span = l.get_span();
+            let (skip, eval) = if op == "and" {
+                ("false", "true")
+            } else {
+                ("true", "false")
+            };
+            let ast = Ast::Match(
+                span,
+                l,
+                vec![
+                    (
+                        Ast::Symbol(l_span.clone(), String::from(skip)),
+                        Ast::Symbol(l_span.clone(), String::from(MATCH_VALUE_ARG)),
+                    ),
+                    (Ast::Symbol(l_span, String::from(eval)), *r),
+                ],
+            );
+            convert_node(
+                ast,
+                filename,
+                funcs,
+                global,
+                seen_funcs,
+                types,
+                constructors,
+                generic_uids,
+                last_uid,
+            )
+        }
+
         // Infix
         Ast::Infix(span, op, l, r) => {
             if op == "$" {
@@ -600,6 +1591,7 @@ fn convert_node(
                     global,
                     seen_funcs,
                     types,
+                    constructors,
                     generic_uids,
                     last_uid,
                 );
@@ -610,6 +1602,7 @@ fn convert_node(
                     global,
                     seen_funcs,
                     types,
+                    constructors,
                     generic_uids,
                     last_uid,
                 );
@@ -633,11 +1626,84 @@ fn convert_node(
                         vec![arg],
                     )
                 }
+            } else if let Some(op) = BinOp::parse(&op) {
+                SExpr::Infix(
+                    SExprMetadata {
+                        loc: Location::new(span, filename),
+                        loc2: Location::empty(),
+                        origin: String::with_capacity(0),
+                        _type: arc::new(Type::Error),
+                        arity: ArityInfo::Known(0),
+                        tailrec: false,
+                        impure: false,
+                    },
+                    op,
+                    Box::new(convert_node(
+                        *l,
+                        filename,
+                        funcs,
+                        global,
+                        seen_funcs,
+                        types,
+                        constructors,
+                        generic_uids,
+                        last_uid,
+                    )),
+                    Box::new(convert_node(
+                        *r,
+                        filename,
+                        funcs,
+                        global,
+                        seen_funcs,
+                        types,
+                        constructors,
+                        generic_uids,
+                        last_uid,
+                    )),
+                )
+            } else if op != ";" {
+                // Operators declared with `infixl` or `infixr` apply the function they name
+                convert_node(
+                    Ast::Application(span.clone(), Box::new(Ast::Symbol(span, op)), vec![*l, *r]),
+                    filename,
+                    funcs,
+                    global,
+                    seen_funcs,
+                    types,
+                    constructors,
+                    generic_uids,
+                    last_uid,
+                )
             } else {
                 unreachable!("uwu moment");
             }
         }
 
+        // Prefix
+        Ast::Prefix(span, op, v) => SExpr::Prefix(
+            SExprMetadata {
+                loc: Location::new(span, filename),
+                loc2: Location::empty(),
+                origin: String::with_capacity(0),
+                _type: arc::new(Type::Error),
+                arity: ArityInfo::Known(0),
+                tailrec: false,
+                impure: false,
+            },
+            PrefixOp::parse(&op).expect("the parser only produces valid prefix operators"),
+            Box::new(convert_node(
+                *v,
+                filename,
+                funcs,
+                global,
+                seen_funcs,
+                types,
+                constructors,
+                generic_uids,
+                last_uid,
+            )),
+        ),
+
         // Application
         Ast::Application(span, l, r) => SExpr::Application(
             SExprMetadata {
@@ -656,6 +1722,7 @@ fn convert_node(
                 global,
                 seen_funcs,
                 types,
+                constructors,
                 generic_uids,
                 last_uid,
             )),
@@ -668,6 +1735,7 @@ fn convert_node(
                         global,
                         seen_funcs,
                         types,
+                        constructors,
                         generic_uids,
                         last_uid,
                     )
@@ -678,30 +1746,23 @@ fn convert_node(
         // Assignment
         Ast::Assign(span, name, val) => {
             let sexpr = convert_node(
-                *val,
+                propagate(*val),
                 filename,
                 funcs,
                 false,
                 seen_funcs,
                 types,
+                constructors,
                 generic_uids,
                 last_uid,
             );
             if global && name != "_" {
-                let func_name = if seen_funcs.contains_key(&name) {
-                    let seen = seen_funcs.get_mut(&name).unwrap();
-                    let name = format!("{}.{}", name, seen);
-                    *seen += 1;
-                    name
-                } else {
-                    seen_funcs.insert(name.clone(), 0);
-                    name.clone()
-                };
+                let func_name = function_name(&name, filename, seen_funcs);
                 funcs.insert(
                     func_name.clone(),
                     IrFunction {
                         loc: Location::new(span.clone(), filename),
-                        name: name.clone(),
+                        name: func_name.clone(),
                         _type: arc::new(Type::Unknown),
                         args: Vec::with_capacity(0),
                         captured: HashMap::with_capacity(0),
@@ -766,12 +1827,13 @@ fn convert_node(
             ));
 
             let sexpr = convert_node(
-                *val,
+                propagate(*val),
                 filename,
                 funcs,
                 false,
                 seen_funcs,
                 types,
+                constructors,
                 generic_uids,
                 last_uid,
             );
@@ -881,15 +1943,7 @@ fn convert_node(
         // Assigning functions
         Ast::AssignFunction(span, name, args, val) => {
             // Get function id
-            let func_name = if seen_funcs.contains_key(&name) {
-                let seen = seen_funcs.get_mut(&name).unwrap();
-                let name = format!("{}.{}", name, seen);
-                *seen += 1;
-                name
-            } else {
-                seen_funcs.insert(name.clone(), 0);
-                name.clone()
-            };
+            let func_name = function_name(&name, filename, seen_funcs);
 
             let func_id = SExpr::Function(
                 SExprMetadata {
@@ -905,6 +1959,7 @@ fn convert_node(
             );
 
             // Create the function
+            let outer_generics = generic_uids.clone();
             let func = IrFunction {
                 loc: Location::new(
                     Span {
@@ -913,7 +1968,7 @@ fn convert_node(
                     },
                     filename,
                 ),
-                name: name.clone(),
+                name: func_name.clone(),
                 _type: arc::new(Type::Unknown),
                 args: args
                     .into_iter()
@@ -932,12 +1987,13 @@ fn convert_node(
                 captured: HashMap::with_capacity(0),
                 captured_names: Vec::with_capacity(0),
                 body: convert_node(
-                    *val,
+                    propagate(*val),
                     filename,
                     funcs,
                     false,
                     seen_funcs,
                     types,
+                    constructors,
                     generic_uids,
                     last_uid,
                 ),
@@ -958,14 +2014,8 @@ fn convert_node(
                 }
             }
 
-            // Remove generics
-            let mut generics_list = vec![];
-            for (_, t) in func.args.iter() {
-                t.get_generics(&mut generics_list);
-            }
-            for (g, _) in generics_list {
-                generic_uids.remove(g);
-            }
+            // Remove generics introduced by this function, keeping those of enclosing functions
+            *generic_uids = outer_generics;
 
             // Return assigning to the function id
             funcs.insert(func_name, func);
@@ -1007,6 +2057,7 @@ fn convert_node(
             );
 
             // Create the function
+            let outer_generics = generic_uids.clone();
             let func = IrFunction {
                 loc: Location::new(span, filename),
                 name: func_name.clone(),
@@ -1028,12 +2079,13 @@ fn convert_node(
                 captured: HashMap::with_capacity(0),
                 captured_names: Vec::with_capacity(0),
                 body: convert_node(
-                    *val,
+                    propagate(*val),
                     filename,
                     funcs,
                     false,
                     seen_funcs,
                     types,
+                    constructors,
                     generic_uids,
                     last_uid,
                 ),
@@ -1054,14 +2106,8 @@ fn convert_node(
                 }
             }
 
-            // Remove generics
-            let mut generics_list = vec![];
-            for (_, t) in func.args.iter() {
-                t.get_generics(&mut generics_list);
-            }
-            for (g, _) in generics_list {
-                generic_uids.remove(g);
-            }
+            // Remove generics introduced by this function, keeping those of enclosing functions
+            *generic_uids = outer_generics;
 
             // Return the function id
             funcs.insert(func_name, func);
@@ -1089,6 +2135,7 @@ fn convert_node(
                         false,
                         seen_funcs,
                         types,
+                        constructors,
                         generic_uids,
                         last_uid,
                     )
@@ -1101,6 +2148,7 @@ fn convert_node(
                 false,
                 seen_funcs,
                 types,
+                constructors,
                 generic_uids,
                 last_uid,
             )),
@@ -1120,51 +2168,430 @@ fn convert_node(
             a,
             Box::new(convert_node(*v, filename, funcs, false, seen_funcs, types)),
         ),
-        Ast::Match(span, v, a) => SExpr::Match(
+        */
+        // Match expressions
+        Ast::Match(span, v, a) => {
+            let value = convert_node(
+                *v,
+                filename,
+                funcs,
+                false,
+                seen_funcs,
+                types,
+                constructors,
+                generic_uids,
+                last_uid,
+            );
+
+            // The value of each arm becomes a function of the matched value and the bindings of
+            // the pattern, so that only the arm that matches is evaluated
+            let arms = a
+                .into_iter()
+                .map(|(pattern, arm)| {
+                    let loc = Location::new(pattern.get_span(), filename);
+                    let pattern = convert_pattern(pattern, constructors);
+                    let func_name = {
+                        let seen = seen_funcs.get_mut("").unwrap();
+                        let name = format!(".{}", seen);
+                        *seen += 1;
+                        name
+                    };
+
+                    let mut bindings = vec![String::from(MATCH_VALUE_ARG)];
+                    pattern.bindings(&mut bindings);
+                    let func = IrFunction {
+                        loc: Location::new(arm.get_span(), filename),
+                        name: func_name.clone(),
+                        _type: arc::new(Type::Unknown),
+                        args: bindings
+                            .into_iter()
+                            .map(|b| (b, arc::new(Type::Unknown)))
+                            .collect(),
+                        captured: HashMap::with_capacity(0),
+                        captured_names: Vec::with_capacity(0),
+                        body: convert_node(
+                            propagate(arm),
+                            filename,
+                            funcs,
+                            false,
+                            seen_funcs,
+                            types,
+                            constructors,
+                            generic_uids,
+                            last_uid,
+                        ),
+                        global: false,
+                        checked: false,
+                        written: false,
+                        impure: false,
+                    };
+                    let arm = SExpr::Function(
+                        SExprMetadata {
+                            loc: func.loc.clone(),
+                            loc2: Location::empty(),
+                            origin: String::with_capacity(0),
+                            _type: arc::new(Type::Error),
+                            arity: ArityInfo::Unknown,
+                            tailrec: false,
+                            impure: false,
+                        },
+                        func_name.clone(),
+                    );
+                    funcs.insert(func_name, func);
+                    (pattern, arm, loc)
+                })
+                .collect();
+
+            SExpr::Match(
+                SExprMetadata {
+                    loc: Location::new(span, filename),
+                    loc2: Location::empty(),
+                    origin: String::with_capacity(0),
+                    _type: arc::new(Type::Error),
+                    arity: ArityInfo::Unknown,
+                    tailrec: false,
+                    impure: false,
+                },
+                Box::new(value),
+                arms,
+            )
+        }
+
+        // Conversions
+        Ast::As(span, v, t) => SExpr::As(
+            SExprMetadata {
+                loc: Location::new(span, filename),
+                loc2: Location::empty(),
+                origin: String::with_capacity(0),
+                _type: arc::new(types::convert_ast_to_type(
+                    *t,
+                    filename,
+                    generic_uids,
+                    last_uid,
+                )),
+                arity: ArityInfo::Unknown,
+                tailrec: false,
+                impure: false,
+            },
+            Box::new(convert_node(
+                *v,
+                filename,
+                funcs,
+                global,
+                seen_funcs,
+                types,
+                constructors,
+                generic_uids,
+                last_uid,
+            )),
+        ),
+
+        // Ascriptions
+        Ast::Ascribe(span, v, t) => SExpr::Ascribe(
             SExprMetadata {
                 loc: Location::new(span, filename),
                 loc2: Location::empty(),
                 origin: String::with_capacity(0),
-                _type: arc::new(Type::Error),
+                _type: arc::new(types::convert_ast_to_type(
+                    *t,
+                    filename,
+                    generic_uids,
+                    last_uid,
+                )),
                 arity: ArityInfo::Unknown,
                 tailrec: false,
                 impure: false,
             },
-            Box::new(convert_node(*v, filename, funcs, global, seen_funcs, types, generic_uids, last_uid)),
-            a.into_iter()
-                .map(|a| {
-                    let span2 = a.0.get_span();
-                    (
-                        arc::new(types::convert_ast_to_type(a.0, filename)),
-                        convert_node(a.1, filename, funcs, global, seen_funcs, types, generic_uids, last_uid),
-                        Location::new(span2, filename),
-                    )
-                })
-                .collect(),
+            Box::new(convert_node(
+                *v,
+                filename,
+                funcs,
+                global,
+                seen_funcs,
+                types,
+                constructors,
+                generic_uids,
+                last_uid,
+            )),
         ),
-        */
-        Ast::Match(_, _, _) => todo!(),
-        Ast::Int(_, _) => todo!(),
-        Ast::Float(_, _) => todo!(),
-        Ast::Word(_, _) => todo!(),
-        Ast::Char(_, _) => todo!(),
-        Ast::String(_, _) => todo!(),
+
+        // `?` outside of any function is matched as if it were the whole body
+        Ast::Try(span, value) => convert_node(
+            propagate(Ast::Try(span, value)),
+            filename,
+            funcs,
+            global,
+            seen_funcs,
+            types,
+            constructors,
+            generic_uids,
+            last_uid,
+        ),
+
         Ast::Enum(_, _) => todo!(),
-        Ast::List(_, _) => todo!(),
-        Ast::Prefix(_, _, _) => todo!(),
-        Ast::As(_, _, _) => todo!(),
         Ast::Walrus(_, _, _) => todo!(),
     }
 }
 
-// extract_types_to_ir(&Vec<Ast>, &mut IrModule) -> ()
-// Extracts types and inserts them into the Ir's list of types.
-fn extract_types_to_ir(asts: &[Ast], module: &mut IrModule) {
+// propagate(Ast) -> Ast
+// Converts every `?` in the body of a function, lambda, or match arm into a match on its value. The
+// arm for ok or some values binds the value and continues with the rest of the body, and the arm for
+// errors or none gives the matched value as the value of the body. The type checker changes the
+// patterns into `some` and `none` if the value is an option.
+fn propagate(mut body: Ast) -> Ast {
+    match take_try(&mut body) {
+        Some((span, value)) => {
+            let name = format!("{}{}", PROPAGATED_VALUE_PREFIX, span.end);
+            Ast::Match(
+                span.clone(),
+                Box::new(value),
+                vec![
+                    (
+                        Ast::Application(
+                            span.clone(),
+                            Box::new(Ast::Symbol(span.clone(), String::from("ok"))),
+                            vec![Ast::Symbol(span.clone(), name)],
+                        ),
+                        propagate(body),
+                    ),
+                    (
+                        Ast::Application(
+                            span.clone(),
+                            Box::new(Ast::Symbol(span.clone(), String::from("err"))),
+                            vec![Ast::Symbol(span.clone(), String::from("_"))],
+                        ),
+                        Ast::Symbol(span, String::from(MATCH_VALUE_ARG)),
+                    ),
+                ],
+            )
+        }
+
+        None => body,
+    }
+}
+
+// take_try(&mut Ast) -> Option<(Span, Ast)>
+// Replaces the first `?` evaluated in an expression with the name its value is bound to, returning
+// the span of the `?` and the value before it. Lambdas, the arms of matches, and the right operands
+// of `and` and `or` are left alone, since they are only evaluated later if at all.
+fn take_try(ast: &mut Ast) -> Option<(Span, Ast)> {
+    match ast {
+        Ast::Try(span, value) => {
+            if let Some(found) = take_try(value) {
+                return Some(found);
+            }
+
+            let span = span.clone();
+            let name = format!("{}{}", PROPAGATED_VALUE_PREFIX, span.end);
+            match std::mem::replace(ast, Ast::Symbol(span.clone(), name)) {
+                Ast::Try(_, value) => Some((span, *value)),
+                _ => unreachable!(),
+            }
+        }
+
+        Ast::Application(_, f, args) => take_try(f).or_else(|| args.iter_mut().find_map(take_try)),
+        Ast::List(_, items) => items.iter_mut().find_map(take_try),
+        Ast::Prefix(_, _, value)
+        | Ast::As(_, value, _)
+        | Ast::Ascribe(_, value, _)
+        | Ast::Match(_, value, _) => take_try(value),
+        Ast::Infix(_, op, l, _) if op == "and" || op == "or" => take_try(l),
+        Ast::Infix(_, _, l, r) => take_try(l).or_else(|| take_try(r)),
+        _ => None,
+    }
+}
+
+// convert_pattern(Ast, &HashMap<String, IrConstructor>) -> Pattern
+// Converts the ast of a pattern into a pattern. Symbols are constructors if a constructor with that
+// name exists, and bindings otherwise.
+fn convert_pattern(ast: Ast, constructors: &HashMap<String, IrConstructor>) -> Pattern {
+    match ast {
+        Ast::Int(_, n) => Pattern::Int(n),
+        Ast::Char(_, c) => Pattern::Char(c),
+        Ast::String(_, s) => Pattern::String(s),
+        Ast::Symbol(_, s) if s == "_" => Pattern::Wildcard,
+        Ast::Symbol(_, s)
+            if constructors.contains_key(&s) || intrinsics::get_constructor(&s).is_some() =>
+        {
+            Pattern::Constructor(s, vec![])
+        }
+        Ast::Symbol(_, s) => Pattern::Binding(s),
+        Ast::Application(_, c, fields) => match *c {
+            Ast::Symbol(_, c) => Pattern::Constructor(
+                c,
+                fields
+                    .into_iter()
+                    .map(|v| convert_pattern(v, constructors))
+                    .collect(),
+            ),
+            _ => unreachable!("the parser only allows symbols as constructors"),
+        },
+        _ => unreachable!("the parser only allows symbols, literals, and constructors in patterns"),
+    }
+}
+
+// union_variants(&Ast) -> Vec<&Ast>
+// Returns the variants of a union type from left to right.
+fn union_variants(ast: &Ast) -> Vec<&Ast> {
+    match ast {
+        Ast::Infix(_, op, l, r) if op == "|" => {
+            let mut variants = union_variants(l);
+            variants.push(r);
+            variants
+        }
+        _ => vec![ast],
+    }
+}
+
+// constructor_name(&Ast) -> Option<&str>
+// Returns the name of the constructor a variant of a type declaration declares, if any.
+// Constructors are named in lowercase like other values, which sets them apart from types.
+fn constructor_name(variant: &Ast) -> Option<&str> {
+    let name = match variant {
+        Ast::Application(_, c, _) => &**c,
+        _ => variant,
+    };
+
+    match name {
+        Ast::Symbol(_, c) if c.starts_with(|c: char| c.is_lowercase()) => Some(c),
+        _ => None,
+    }
+}
+
+// declares_constructors(&Ast) -> bool
+// Returns true if a type declaration declares a type with named constructors rather than an alias.
+fn declares_constructors(_type: &Ast) -> bool {
+    union_variants(_type)
+        .into_iter()
+        .any(|v| constructor_name(v).is_some())
+}
+
+// is_finite(&Type, &HashSet<String>, &IrModule) -> bool
+// Returns true if a value of the type can be built without a value of a declared type that is not
+// in the set of types known to have finite values. Functions, arrays, maps, options, and results
+// can always be built without a value of the types they contain.
+fn is_finite(_type: &Type, finite: &HashSet<String>, module: &IrModule) -> bool {
+    match _type {
+        Type::Symbol(s) => {
+            finite.contains(s) || !module.constructors.values().any(|c| c.type_name == *s)
+        }
+        Type::Thread(t) | Type::Task(t) => is_finite(t, finite, module),
+        Type::Union(fields) => fields.0.iter().any(|t| is_finite(t, finite, module)),
+        _ => true,
+    }
+}
+
+// check_finite_types(&[(String, Location)], &IrModule, &mut Vec<IrError>) -> ()
+// Reports the declared types whose values would all contain another value of a declared type
+// without finite values, such as `type Stream = next Int Stream`. Values of declared types are
+// boxed, so recursive fields only need a constructor that ends the recursion.
+fn check_finite_types(
+    declared: &[(String, Location)],
+    module: &IrModule,
+    errors: &mut Vec<IrError>,
+) {
+    let mut finite = HashSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for c in module.constructors.values() {
+            if !finite.contains(&c.type_name)
+                && c.field_types.iter().all(|t| is_finite(t, &finite, module))
+            {
+                finite.insert(c.type_name.clone());
+                changed = true;
+            }
+        }
+    }
+
+    for (name, loc) in declared {
+        if !finite.contains(name) {
+            errors.push(IrError::InfiniteType(loc.clone(), name.clone()));
+        }
+    }
+}
+
+// extract_types_to_ir(&[Ast], &str, &mut IrModule, &mut Vec<IrError>) -> ()
+// Extracts types and inserts them into the Ir's list of types. Types with named constructors are
+// nominal, and each constructor is tagged with its index in the declaration of the type. Types
+// may refer to themselves and to each other, but must have values that are finite.
+fn extract_types_to_ir(
+    asts: &[Ast],
+    filename: &str,
+    module: &mut IrModule,
+    errors: &mut Vec<IrError>,
+) {
+    // Types with errors in their constructors are not checked for finite values
+    let mut declared = vec![];
     for ast in asts {
-        if let Ast::AssignType(_, v, _) = ast {
-            module.types.insert(v.clone(), arc::new(Type::Unknown));
+        let (name, _type) = match ast {
+            Ast::AssignType(_, name, _type) => (name, _type),
+            _ => continue,
+        };
+
+        if !declares_constructors(_type) {
+            module.types.insert(name.clone(), arc::new(Type::Unknown));
+            continue;
+        }
+        let error_count = errors.len();
+        module
+            .types
+            .insert(name.clone(), arc::new(Type::Symbol(name.clone())));
+
+        for (tag, variant) in union_variants(_type).into_iter().enumerate() {
+            let loc = Location::new(variant.get_span(), filename);
+            let c = match constructor_name(variant) {
+                Some(v) => String::from(v),
+                None => {
+                    errors.push(IrError::InvalidConstructor(loc));
+                    continue;
+                }
+            };
+
+            // Get the types of the fields
+            let mut field_types = vec![];
+            if let Ast::Application(_, _, fields) = variant {
+                let mut generic_uids = HashMap::new();
+                let mut last_uid = 0;
+                for field in fields {
+                    match types::convert_ast_to_type(
+                        field.clone(),
+                        filename,
+                        &mut generic_uids,
+                        &mut last_uid,
+                    ) {
+                        Type::UndeclaredTypeError(s) => errors.push(IrError::InvalidType(s)),
+                        Type::DuplicateTypeError(s1, s2, t) => {
+                            errors.push(IrError::DuplicateTypeInUnion(s1, s2, t))
+                        }
+                        t => field_types.push(arc::new(t)),
+                    }
+                }
+            }
+
+            match module.constructors.entry(c.clone()) {
+                Entry::Occupied(e) => {
+                    errors.push(IrError::DuplicateConstructor(e.get().loc.clone(), loc, c));
+                }
+
+                Entry::Vacant(e) => {
+                    e.insert(IrConstructor {
+                        loc,
+                        name: c,
+                        type_name: name.clone(),
+                        tag,
+                        field_types,
+                    });
+                }
+            }
+        }
+
+        if errors.len() == error_count {
+            declared.push((name.clone(), Location::new(ast.get_span(), filename)));
         }
     }
+
+    check_finite_types(&declared, module, errors);
 }
 
 // Represents the purity of the next function.
@@ -1174,30 +2601,50 @@ enum Purity {
     Default,
 }
 
-// convert_ast_to_ir(Vec<Ast>) -> Ir
-// Converts a list of asts into ir.
-pub fn convert_ast_to_ir(
-    filename: &str,
-    contents: &str,
-    asts: Vec<Ast>,
-    ir: &mut Ir,
-) -> Result<(), Vec<IrError>> {
-    // Set up
-    let mut module = IrModule::new(filename, contents);
-    extract_types_to_ir(&asts, &mut module);
+// function_name(&str, &str, &mut HashMap<String, usize>) -> String
+// Returns a unique internal name for a function defined with the given name. Functions defined
+// more than once are numbered, and prelude functions are prefixed with `prelude.`, so that they
+// never collide with the functions of the program.
+fn function_name(name: &str, filename: &str, seen_funcs: &mut HashMap<String, usize>) -> String {
+    let name = if filename == prelude::FILENAME {
+        format!("{}{}", prelude::PREFIX, name)
+    } else {
+        String::from(name)
+    };
+
+    match seen_funcs.get_mut(&name) {
+        Some(seen) => {
+            let numbered = format!("{}.{}", name, seen);
+            *seen += 1;
+            numbered
+        }
+        None => {
+            seen_funcs.insert(name.clone(), 0);
+            name
+        }
+    }
+}
 
-    let mut seen_funcs = HashMap::new();
-    seen_funcs.insert(String::with_capacity(0), 0);
+// convert_definitions(&mut IrModule, impl Iterator<Item = (Ast, &str)>, &mut Vec<IrError>) -> String
+// Converts the top level definitions of a list of asts into a module, each along with the name of
+// the file it is in. Returns the module name given by a header, or an empty string if there is
+// none.
+fn convert_definitions<'a>(
+    module: &mut IrModule,
+    asts: impl Iterator<Item = (Ast, &'a str)>,
+    errors: &mut Vec<IrError>,
+) -> String {
+    // Functions defined by earlier calls keep their names
+    let mut seen_funcs = std::mem::take(&mut module.func_names);
 
     let mut module_name = String::with_capacity(0);
-    let mut errors = vec![];
     let mut purity = Purity::Default;
+    let mut export = None;
 
     let mut generic_uids = HashMap::new();
     let mut last_uid = 0;
 
-    // Iterate over every ast node
-    for ast in asts {
+    for (ast, filename) in asts {
         // Deal with the header
         if let Ast::Header(_, name, _exports, imports) = ast {
             // Get module name
@@ -1328,6 +2775,8 @@ pub fn convert_ast_to_ir(
                 purity = Purity::Pure;
             } else if a == "@impure" {
                 purity = Purity::Impure;
+            } else if a == "@export" && filename != prelude::FILENAME {
+                export = Some(Location::new(span, filename));
             } else {
                 errors.push(IrError::UnsupportedAnnotation(
                     Location::new(span, filename),
@@ -1356,11 +2805,34 @@ pub fn convert_ast_to_ir(
                     ret_type = a.clone();
                 }
 
+                // Callbacks only take and return words
+                let loc = Location::new(span, &module.filename);
+                for arg in arg_types.iter() {
+                    let unsupported = match arg.as_function() {
+                        Some((args, ret)) => {
+                            args.len() > FFI_REGISTER_ARGS
+                                || args
+                                    .iter()
+                                    .chain(std::iter::once(&ret))
+                                    .any(|v| **v == Type::Float)
+                        }
+                        None => **arg == Type::Float && arg_types.len() > FFI_REGISTER_ARGS,
+                    };
+                    if unsupported {
+                        errors.push(IrError::InvalidFFIType(loc.clone(), arg.clone()));
+                    }
+                }
+
+                // External functions are already C functions
+                if let Some(loc) = export.take() {
+                    errors.push(IrError::UnsupportedAnnotation(loc, String::from("@export")));
+                }
+
                 // Add external function
                 module.externals.insert(
                     n,
                     IrExtern {
-                        loc: Location::new(span, &module.filename),
+                        loc,
                         extern_name: c,
                         arg_types,
                         ret_type,
@@ -1370,6 +2842,37 @@ pub fn convert_ast_to_ir(
             }
 
             purity = Purity::Default;
+        } else if let Ast::Test(span, name, value) = ast {
+            // Tests are functions without arguments that are only called by the test runner
+            let loc = Location::new(span.clone(), filename);
+            let v = convert_node(
+                Ast::Assign(span, String::from("test"), value),
+                filename,
+                &mut module.funcs,
+                true,
+                &mut seen_funcs,
+                &mut module.types,
+                &module.constructors,
+                &mut generic_uids,
+                &mut last_uid,
+            );
+
+            if let SExpr::Assign(_, _, v) = v {
+                if let SExpr::Function(_, func) = *v {
+                    module.funcs.get_mut(&func).unwrap().impure = matches!(purity, Purity::Impure);
+                    module.tests.push(IrTest { loc, name, func });
+                }
+            }
+            if let Some(loc) = export.take() {
+                errors.push(IrError::UnsupportedAnnotation(loc, String::from("@export")));
+            }
+            purity = Purity::Default;
+        } else if matches!(&ast, Ast::AssignType(_, _, t) if declares_constructors(t)) {
+            // Types with constructors were extracted before anything else, since patterns and
+            // expressions anywhere in the module can refer to their constructors
+            if let Some(loc) = export.take() {
+                errors.push(IrError::UnsupportedAnnotation(loc, String::from("@export")));
+            }
         } else {
             let v = convert_node(
                 ast,
@@ -1378,20 +2881,127 @@ pub fn convert_ast_to_ir(
                 true,
                 &mut seen_funcs,
                 &mut module.types,
+                &module.constructors,
                 &mut generic_uids,
                 &mut last_uid,
             );
 
             if let SExpr::Assign(_, a, v) = v {
-                if let SExpr::Function(_, f) = *v {
+                if let SExpr::Function(_, mut f) = *v {
                     module.funcs.get_mut(&f).unwrap().impure = matches!(purity, Purity::Impure);
-                    module.globals.insert(a, f);
+                    if filename == prelude::FILENAME {
+                        module.prelude.insert(a.clone(), f.clone());
+                    }
+
+                    // Exported functions are renamed, so that the C function can take their name
+                    module.c_exports.retain(|v| *v != a);
+                    if export.take().is_some() {
+                        let mut func = module.funcs.remove(&f).unwrap();
+                        f = format!("{}{}", EXPORT_PREFIX, f);
+                        func.name = f.clone();
+                        module.funcs.insert(f.clone(), func);
+                        module.c_exports.push(a.clone());
+                    }
+                    if let Some(old) = module.globals.insert(a, f.clone()) {
+                        if module.funcs[&f].args.is_empty() {
+                            module.shadowed.insert(f, old);
+                        }
+                    }
                 }
             }
+            if let Some(loc) = export.take() {
+                errors.push(IrError::UnsupportedAnnotation(loc, String::from("@export")));
+            }
             purity = Purity::Default;
         }
     }
 
+    // Collect the signatures of every new global function before any body is checked, so that
+    // functions can refer to themselves and to each other. Prelude functions the program shadows
+    // are still used by the rest of the prelude.
+    let names: HashSet<_> = module
+        .globals
+        .values()
+        .chain(module.prelude.values())
+        .filter(|v| !module.signatures.contains_key(*v))
+        .cloned()
+        .collect();
+    for name in names.iter() {
+        module.last_uid += 1;
+        let ret = arc::new(Type::Generic(String::from("r"), module.last_uid));
+        let args: Vec<_> = module.funcs[name]
+            .args
+            .iter()
+            .map(|v| v.1.clone())
+            .collect();
+        module
+            .signatures
+            .insert(name.clone(), arc::new(Type::function(&args, ret)));
+    }
+
+    module.func_names = seen_funcs;
+    module_name
+}
+
+// extract_docs(&[Ast], &str, &mut IrModule) -> ()
+// Attaches the doc comments in a file to the top level definitions directly below them. Only
+// whitespace, other doc comments, and annotations can be between a doc comment and its definition.
+fn extract_docs(asts: &[Ast], contents: &str, module: &mut IrModule) {
+    let comments = parser::doc_comments(contents);
+    for ast in asts {
+        let (span, name) = match ast {
+            Ast::Assign(span, name, _)
+            | Ast::AssignTyped(span, name, _, _)
+            | Ast::AssignType(span, name, _)
+            | Ast::AssignFunction(span, name, _, _)
+            | Ast::Extern(span, _, name, _) => (span, name),
+            _ => continue,
+        };
+
+        // Collect the comments above the definition, from the bottom up
+        let mut lines = vec![];
+        let mut start = span.start;
+        let mut i = comments.partition_point(|v| v.0.end <= span.start);
+        while i > 0
+            && contents[comments[i - 1].0.end..start]
+                .split_whitespace()
+                .all(|v| v.starts_with('@'))
+        {
+            i -= 1;
+            start = comments[i].0.start;
+            lines.push(comments[i].1.as_str());
+        }
+
+        if !lines.is_empty() {
+            lines.reverse();
+            module.docs.insert(name.clone(), lines.join("\n"));
+        }
+    }
+}
+
+// convert_ast_to_ir(&str, &str, Vec<Ast>, Vec<Ast>, &mut Ir) -> Result<(), Vec<IrError>>
+// Converts a list of asts into ir, preceded by the asts of the prelude.
+pub fn convert_ast_to_ir(
+    filename: &str,
+    contents: &str,
+    prelude: Vec<Ast>,
+    asts: Vec<Ast>,
+    ir: &mut Ir,
+) -> Result<(), Vec<IrError>> {
+    let _span = info_span!("convert_ast_to_ir", filename).entered();
+
+    // Set up
+    let mut module = IrModule::new(filename, contents);
+    let mut errors = vec![];
+    extract_types_to_ir(&prelude, prelude::FILENAME, &mut module, &mut errors);
+    extract_types_to_ir(&asts, filename, &mut module, &mut errors);
+    extract_docs(&asts, contents, &mut module);
+
+    // Convert every ast node, starting with the prelude so that the program can shadow it
+    let prelude = prelude.into_iter().map(|v| (v, prelude::FILENAME));
+    let asts = prelude.chain(asts.into_iter().map(|v| (v, filename)));
+    let mut module_name = convert_definitions(&mut module, asts, &mut errors);
+
     // Check module name
     if module_name.is_empty() {
         module_name = filename
@@ -1405,6 +3015,8 @@ pub fn convert_ast_to_ir(
     }
     module.name = module_name.clone();
 
+    trace!("converted module\n{}", module);
+
     // Add module to ir root and error if already exists
     match ir.modules.entry(module_name) {
         Entry::Occupied(e) => {
@@ -1429,6 +3041,33 @@ pub fn convert_ast_to_ir(
     }
 }
 
+// extend_module(&mut IrModule, &str, &str, Vec<Ast>) -> Result<(), Vec<IrError>>
+// Converts a list of asts into ir after the definitions already in a module, so that they can use
+// those definitions and shadow them. Only the new functions are checked by the next call to
+// check_correctness, since it skips the functions that are already checked.
+pub fn extend_module(
+    module: &mut IrModule,
+    filename: &str,
+    contents: &str,
+    asts: Vec<Ast>,
+) -> Result<(), Vec<IrError>> {
+    let _span = info_span!("extend_module", name = %module.name, filename).entered();
+    let mut errors = vec![];
+    extract_types_to_ir(&asts, filename, module, &mut errors);
+    extract_docs(&asts, contents, module);
+    convert_definitions(module, asts.into_iter().map(|v| (v, filename)), &mut errors);
+
+    // Locations are relative to the file they are in, so only the newest file can be pointed into
+    module.contents = String::from(contents);
+
+    trace!("extended module\n{}", module);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 /*
 pub fn convert_library_header(
     filename: &str,
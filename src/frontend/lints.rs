@@ -0,0 +1,305 @@
+use logos::Span;
+use std::collections::HashSet;
+use tracing::{debug, info_span};
+
+use super::ir::{Ir, IrFunction, IrModule, Location, SExpr};
+use super::prelude;
+
+// Represents code that is valid but is probably a mistake.
+pub enum LintWarning {
+    // A variable bound by a pattern or a with expression that is never used, given by the location
+    // of the pattern or binding and the name of the variable
+    UnusedVariable(Location, String),
+
+    // An argument that is never used, given by the location of the function and the name of the
+    // argument
+    UnusedArgument(Location, String),
+
+    // A variable with the same name as a variable of an enclosing function or with expression,
+    // given by the location it is bound at and its name
+    Shadowing(Location, String),
+}
+
+impl LintWarning {
+    // loc(&self) -> &Location
+    // Returns the location the warning points at.
+    pub fn loc(&self) -> &Location {
+        match self {
+            LintWarning::UnusedVariable(loc, _)
+            | LintWarning::UnusedArgument(loc, _)
+            | LintWarning::Shadowing(loc, _) => loc,
+        }
+    }
+}
+
+// ignored(&str) -> bool
+// Returns true if a variable is never warned about. Variables starting with `_` are unused on
+// purpose, and those starting with `.` are generated by the compiler.
+fn ignored(name: &str) -> bool {
+    name.starts_with('_') || name.starts_with('.')
+}
+
+// name_loc(&Location, &str) -> Location
+// Returns the location of a name at the start of a definition.
+fn name_loc(loc: &Location, name: &str) -> Location {
+    Location::new(
+        Span {
+            start: loc.span.start,
+            end: loc.span.start + name.len(),
+        },
+        &loc.filename,
+    )
+}
+
+// local_func(&SExpr, &'a IrModule) -> Option<&'a IrFunction>
+// Returns the function an s expression refers to if it is a lambda, a local function, or a match
+// arm. Global functions are checked separately.
+fn local_func<'a>(sexpr: &SExpr, module: &'a IrModule) -> Option<&'a IrFunction> {
+    match sexpr {
+        SExpr::Function(_, f) => module.funcs.get(f).filter(|v| !v.global),
+        _ => None,
+    }
+}
+
+// used_names(&SExpr, &IrModule, &mut HashSet<String>) -> ()
+// Adds the names of the variables an s expression uses without binding them itself.
+fn used_names(sexpr: &SExpr, module: &IrModule, used: &mut HashSet<String>) {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _) => (),
+
+        SExpr::Symbol(_, s) => {
+            used.insert(s.clone());
+        }
+
+        SExpr::Function(_, _) => {
+            if let Some(func) = local_func(sexpr, module) {
+                used.extend(func_uses(func, module));
+            }
+        }
+
+        SExpr::ExternalFunc(_, _, args) => {
+            for arg in args {
+                used_names(arg, module, used);
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            used_names(a, module, used);
+            used_names(b, module, used);
+        }
+
+        SExpr::Application(_, func, args) => {
+            used_names(func, module, used);
+            for arg in args {
+                used_names(arg, module, used);
+            }
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => used_names(v, module, used),
+
+        SExpr::With(_, assigns, v) => used.extend(with_uses(assigns, v, module)),
+
+        SExpr::Match(_, v, arms) => {
+            used_names(v, module, used);
+            for (_, arm, _) in arms {
+                used_names(arm, module, used);
+            }
+        }
+    }
+}
+
+// func_uses(&IrFunction, &IrModule) -> HashSet<String>
+// Returns the names of the variables a function uses from the scope it is defined in.
+fn func_uses(func: &IrFunction, module: &IrModule) -> HashSet<String> {
+    let mut used = HashSet::new();
+    used_names(&func.body, module, &mut used);
+    for (arg, _) in func.args.iter() {
+        used.remove(arg);
+    }
+    used
+}
+
+// with_uses(&[SExpr], &SExpr, &IrModule) -> HashSet<String>
+// Returns the names of the variables the bindings and body of a with expression use without
+// binding them. Each binding is in scope for the bindings after it and the body.
+fn with_uses(assigns: &[SExpr], body: &SExpr, module: &IrModule) -> HashSet<String> {
+    let mut used = HashSet::new();
+    used_names(body, module, &mut used);
+    for assign in assigns.iter().rev() {
+        if let SExpr::Assign(_, name, v) = assign {
+            used.remove(name);
+            used_names(v, module, &mut used);
+        } else {
+            used_names(assign, module, &mut used);
+        }
+    }
+    used
+}
+
+// check_shadowing(&str, &Location, &[String], &mut Vec<LintWarning>) -> ()
+// Warns about a variable with the same name as a variable in an enclosing scope.
+fn check_shadowing(name: &str, loc: &Location, scope: &[String], warnings: &mut Vec<LintWarning>) {
+    if !ignored(name) && scope.iter().any(|v| v == name) {
+        debug!(name, "shadowed");
+        warnings.push(LintWarning::Shadowing(loc.clone(), String::from(name)));
+    }
+}
+
+// check_func(&IrFunction, &Location, bool, &IrModule, &mut Vec<String>, &mut Vec<LintWarning>) -> ()
+// Checks the arguments and body of a function, given the variables of the functions and with
+// expressions enclosing it. The arguments of match arms are the variables bound by the pattern at
+// the given location.
+fn check_func(
+    func: &IrFunction,
+    loc: &Location,
+    arm: bool,
+    module: &IrModule,
+    scope: &mut Vec<String>,
+    warnings: &mut Vec<LintWarning>,
+) {
+    let used = {
+        let mut used = HashSet::new();
+        used_names(&func.body, module, &mut used);
+        used
+    };
+
+    for (arg, _) in func.args.iter().filter(|v| !ignored(&v.0)) {
+        check_shadowing(arg, loc, scope, warnings);
+        if !used.contains(arg) {
+            debug!(func = %func.name, arg = %arg, "unused");
+            warnings.push(if arm {
+                LintWarning::UnusedVariable(loc.clone(), arg.clone())
+            } else {
+                LintWarning::UnusedArgument(loc.clone(), arg.clone())
+            });
+        }
+    }
+
+    let len = scope.len();
+    scope.extend(func.args.iter().map(|v| v.0.clone()));
+    check_sexpr(&func.body, module, scope, warnings);
+    scope.truncate(len);
+}
+
+// check_sexpr(&SExpr, &IrModule, &mut Vec<String>, &mut Vec<LintWarning>) -> ()
+// Checks the lambdas, match arms, and with expressions in an s expression, given the variables of
+// the functions and with expressions enclosing it.
+fn check_sexpr(
+    sexpr: &SExpr,
+    module: &IrModule,
+    scope: &mut Vec<String>,
+    warnings: &mut Vec<LintWarning>,
+) {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _) => (),
+
+        SExpr::Function(_, _) => {
+            if let Some(func) = local_func(sexpr, module) {
+                check_func(func, &func.loc, false, module, scope, warnings);
+            }
+        }
+
+        SExpr::ExternalFunc(_, _, args) => {
+            for arg in args {
+                check_sexpr(arg, module, scope, warnings);
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            check_sexpr(a, module, scope, warnings);
+            check_sexpr(b, module, scope, warnings);
+        }
+
+        SExpr::Application(_, func, args) => {
+            check_sexpr(func, module, scope, warnings);
+            for arg in args {
+                check_sexpr(arg, module, scope, warnings);
+            }
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => check_sexpr(v, module, scope, warnings),
+
+        SExpr::With(_, assigns, body) => {
+            let len = scope.len();
+            for (i, assign) in assigns.iter().enumerate() {
+                let (m, name, v) = match assign {
+                    SExpr::Assign(m, name, v) => (m, name, v),
+                    _ => {
+                        check_sexpr(assign, module, scope, warnings);
+                        continue;
+                    }
+                };
+
+                // Local functions are pointed at by their name rather than their whole definition
+                let loc = name_loc(&m.loc, name);
+                check_shadowing(name, &loc, scope, warnings);
+                match local_func(v, module) {
+                    Some(func) => check_func(func, &loc, false, module, scope, warnings),
+                    None => check_sexpr(v, module, scope, warnings),
+                }
+
+                if !ignored(name) && !with_uses(&assigns[i + 1..], body, module).contains(name) {
+                    debug!(name = %name, "unused");
+                    warnings.push(LintWarning::UnusedVariable(loc, name.clone()));
+                }
+                scope.push(name.clone());
+            }
+
+            check_sexpr(body, module, scope, warnings);
+            scope.truncate(len);
+        }
+
+        SExpr::Match(_, v, arms) => {
+            check_sexpr(v, module, scope, warnings);
+            for (_, arm, loc) in arms {
+                if let Some(func) = local_func(arm, module) {
+                    check_func(func, loc, true, module, scope, warnings);
+                }
+            }
+        }
+    }
+}
+
+// check_module(&IrModule) -> Vec<LintWarning>
+// Checks every global function of a module outside the prelude, returning the warnings found in
+// the order they appear in the source. Lambdas, local functions, and match arms are checked along
+// with the function they are in.
+pub fn check_module(module: &IrModule) -> Vec<LintWarning> {
+    let _span = info_span!("lint_module", name = %module.name).entered();
+    let mut warnings = vec![];
+    for (name, func) in module.globals.iter() {
+        let func = &module.funcs[func];
+        if func.loc.filename == prelude::FILENAME {
+            continue;
+        }
+
+        let loc = name_loc(&func.loc, name);
+        check_func(func, &loc, false, module, &mut vec![], &mut warnings);
+    }
+
+    warnings.sort_by_key(|w| (w.loc().filename.clone(), w.loc().span.start));
+    warnings
+}
+
+// check_lints(&Ir) -> Vec<LintWarning>
+// Checks every module for code that is probably a mistake.
+pub fn check_lints(ir: &Ir) -> Vec<LintWarning> {
+    ir.modules.values().flat_map(check_module).collect()
+}
@@ -1,26 +1,119 @@
-use logos::{Lexer, Logos, Span};
+use logos::{Lexer, Logos, Skip, Span};
+use std::collections::HashMap;
+use std::str::Chars;
+use tracing::{debug, info_span};
+
+use super::types::IntSize;
+
+// unescape(&mut Chars) -> Option<char>
+// Converts the escape sequence following a backslash into the character it stands for, or None if
+// it is not a valid escape sequence. `\xNN` escapes an ascii character and `\u{N}` escapes any
+// unicode scalar value.
+fn unescape(iter: &mut Chars) -> Option<char> {
+    match iter.next()? {
+        '\\' => Some('\\'),
+        '\"' => Some('\"'),
+        '\'' => Some('\''),
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        '0' => Some('\0'),
+        'x' => {
+            let digits: String = iter.take(2).collect();
+            let n = u8::from_str_radix(&digits, 16).ok()?;
+            if digits.len() == 2 && n < 0x80 {
+                Some(n as char)
+            } else {
+                None
+            }
+        }
+        'u' => {
+            if iter.next()? != '{' {
+                return None;
+            }
+            let mut n = 0;
+            let mut digits = 0;
+            loop {
+                match iter.next()? {
+                    '}' if digits > 0 => break char::from_u32(n),
+                    c if digits < 6 => n = n * 16 + c.to_digit(16)?,
+                    _ => break None,
+                }
+                digits += 1;
+            }
+        }
+        _ => None,
+    }
+}
+
+// split_radix(&str) -> (&str, u32)
+// Splits the digits of an int literal from its `0x`, `0o`, or `0b` prefix, returning them along
+// with their radix. Digits without a prefix are decimal.
+fn split_radix(s: &str) -> (&str, u32) {
+    match s.get(..2) {
+        Some("0x") => (&s[2..], 16),
+        Some("0o") => (&s[2..], 8),
+        Some("0b") => (&s[2..], 2),
+        _ => (s, 10),
+    }
+}
+
+// convert_int(&str) -> Option<u64>
+// Converts the digits of an int literal into its value, or None if it does not fit in 64 bits. The
+// digits may be separated by underscores.
+fn convert_int(s: &str) -> Option<u64> {
+    let (digits, radix) = split_radix(s);
+    u64::from_str_radix(&digits.replace('_', ""), radix).ok()
+}
+
+// convert_unsuffixed_int(&str) -> Option<i64>
+// Converts an int literal without a suffix into its value, or None if it is out of the range of
+// `Int`. The magnitude of the smallest `Int` is converted into the smallest `Int`, since it is in
+// range when it is negated.
+fn convert_unsuffixed_int(s: &str) -> Option<i64> {
+    let n = convert_int(s)?;
+    if n <= i64::MIN.unsigned_abs() {
+        Some(n as i64)
+    } else {
+        None
+    }
+}
+
+// convert_sized_int(&str) -> Option<(i64, IntSize)>
+// Converts an int literal with a suffix giving its type, such as `255u8` or `0x7fi16`, into the
+// value as it is stored in 64 bits and the size of its type. Returns None if the value is out of
+// the range of the type. As with `Int`, the magnitude of the smallest value of a signed type is
+// converted into the smallest value.
+fn convert_sized_int(s: &str) -> Option<(i64, IntSize)> {
+    let split = s.rfind(['i', 'u'])?;
+    let (digits, suffix) = s.split_at(split);
+    let size = IntSize::from_suffix(suffix)?;
+    let n = convert_int(digits)? as i128;
+
+    if size.fits(n) || size.signed && n == -size.min() {
+        Some((size.wrap(n), size))
+    } else {
+        None
+    }
+}
 
 // convert_chars(&str) -> String
-// Converts escaped characters into an unescaped string.
+// Converts escaped characters into an unescaped string. Invalid escape sequences are kept as they
+// are.
 fn convert_chars(s: &str, off: usize) -> String {
     let mut iter = s[off..s.len() - off].chars();
     let mut s = String::new();
 
     while let Some(c) = iter.next() {
         if c == '\\' {
-            match iter.next().unwrap() {
-                '\\' => s.push('\\'),
-                '\"' => s.push('\"'),
-                '\'' => s.push('\''),
-                'n' => s.push('\n'),
-                'r' => s.push('\r'),
-                't' => s.push('\t'),
-                '0' => s.push('\0'),
-                c => {
-                    s.push('\\');
-                    s.push(c)
+            let mut escaped = iter.clone();
+            match unescape(&mut escaped) {
+                Some(c) => {
+                    s.push(c);
+                    iter = escaped;
                 }
-            };
+                None => s.push('\\'),
+            }
         } else {
             s.push(c);
         }
@@ -29,8 +122,34 @@ fn convert_chars(s: &str, off: usize) -> String {
     s
 }
 
-// The tokens parsed by the lexer.
+// convert_char(&str) -> Option<char>
+// Converts a character literal into the character it stands for.
+fn convert_char(s: &str) -> Option<char> {
+    let mut iter = s[1..s.len() - 1].chars();
+    let c = match iter.next()? {
+        '\\' => unescape(&mut iter)?,
+        c => c,
+    };
+
+    if iter.next().is_none() {
+        Some(c)
+    } else {
+        None
+    }
+}
+
+// doc_comment(&mut Lexer<Token>) -> Skip
+// Records the text of a doc comment in the lexer, without the `##` and the space after it.
+fn doc_comment(lex: &mut Lexer<Token>) -> Skip {
+    let text = &lex.slice()[2..];
+    let text = String::from(text.strip_prefix(' ').unwrap_or(text));
+    lex.extras.push((lex.span(), text));
+    Skip
+}
+
+// The tokens parsed by the lexer. Doc comments are collected in the extras of the lexer.
 #[derive(Logos, PartialEq, Debug, Clone)]
+#[logos(extras = Vec<(Span, String)>)]
 pub enum Token {
     // Brackets
     #[token("(")]
@@ -59,6 +178,7 @@ pub enum Token {
     Whitespace,
 
     #[regex(r"#[^\n]*", logos::skip)]
+    #[regex(r"##[^\n]*", doc_comment)]
     #[regex(r"\{-([^-]*-+)+\}", logos::skip)]
     Comment,
 
@@ -94,34 +214,73 @@ pub enum Token {
     #[token("=")]
     Assign,
 
-    #[regex(r"(;~!\$%\^&\*\-\+|\./\?)+")]
+    // Operators
+    #[token("*")]
+    Mul,
+
+    #[token("/")]
+    Div,
+
+    #[token("%")]
+    Mod,
+
+    #[token("+")]
+    Add,
+
+    #[token("-")]
+    Sub,
+
+    #[token("<<")]
+    LShift,
+
+    #[token(">>")]
+    RShift,
+
+    #[token("&")]
+    Ampersand,
+
+    #[token("^")]
+    Caret,
+
+    #[token("~")]
+    Tilde,
+
+    #[token("?")]
+    Question,
+
+    #[token("<")]
+    LT,
+
+    #[token(">")]
+    GT,
+
+    #[token("<=")]
+    LEQ,
+
+    #[token(">=")]
+    GEQ,
+
+    #[token("==")]
+    EQ,
+
+    #[token("!=")]
+    NEQ,
+
+    #[regex(r"[!$%&*+\-/<=>^|~]+")]
     Operator,
 
     // Numbers
-    #[regex(r"[0-9]+", |lex| lex.slice().parse())]
-    #[regex(r"0x[0-9a-fA-F]+", |lex| i64::from_str_radix(&lex.slice()[2..], 16))]
-    #[regex(r"0b[01]+", |lex| i64::from_str_radix(&lex.slice()[2..], 2))]
+    #[regex(r"[0-9][0-9_]*|0x[0-9a-fA-F_]+|0o[0-7_]+|0b[01_]+", |lex| convert_unsuffixed_int(lex.slice()))]
     Int(i64),
 
-    #[regex(r"[0-9]+(\.[0-9]*([eE][+-]?[0-9]+)?|[eE][+-]?[0-9]+)", |lex| lex.slice().parse())]
+    #[regex(r"[0-9][0-9_]*(\.[0-9_]*([eE][+-]?[0-9_]+)?|[eE][+-]?[0-9_]+)", |lex| lex.slice().replace('_', "").parse())]
     Float(f64),
 
-    #[regex(r"[0-9]+u", |lex| {
-        let v = lex.slice();
-        v[..v.len() - 1].parse()
-    })]
-    #[regex(r"[a-fA-F0-9]+h", |lex| {
-        let v = lex.slice();
-        u64::from_str_radix(&v[..v.len() - 1], 16)
-    })]
-    #[regex(r"[01]+b", |lex| {
-        let v = lex.slice();
-        u64::from_str_radix(&v[..v.len() - 1], 2)
-    })]
-    Word(u64),
-
-    #[regex(r#"'([^\\']|\\[nrt'"0])'"#, |lex| convert_chars(lex.slice(), 1).bytes().next().unwrap())]
-    Char(u8),
+    #[regex(r"([0-9][0-9_]*|0x[0-9a-fA-F_]+|0o[0-7_]+|0b[01_]+)[iu](8|16|32|64)", |lex| convert_sized_int(lex.slice()))]
+    SizedInt((i64, IntSize)),
+
+    #[regex(r#"'([^\\'\n]|\\[nrt'"0\\]|\\x[0-9a-fA-F][0-9a-fA-F]|\\u\{[0-9a-fA-F]+\})'"#, |lex| convert_char(lex.slice()))]
+    Char(char),
 
     #[regex(r"'[a-zA-Z_0-9]+", |lex| lex.slice()[1..].to_owned())]
     Generic(String),
@@ -156,6 +315,9 @@ pub enum Token {
     #[token("in")]
     In,
 
+    #[token("where")]
+    Where,
+
     #[token("import")]
     Import,
 
@@ -177,9 +339,104 @@ pub enum Token {
     #[token("to")]
     To,
 
+    #[token("if")]
+    If,
+
+    #[token("then")]
+    Then,
+
+    #[token("else")]
+    Else,
+
+    #[token("as")]
+    As,
+
+    #[token("not")]
+    Not,
+
+    #[token("and")]
+    And,
+
+    #[token("or")]
+    Or,
+
+    #[token("xor")]
+    Xor,
+
+    #[token("infixl")]
+    Infixl,
+
+    #[token("infixr")]
+    Infixr,
+
+    #[token("test")]
+    Test,
+
     Unreachable,
 }
 
+// Represents the associativity of an infix operator.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Assoc {
+    Left,
+    Right,
+}
+
+// The precedence and associativity of the built in infix operators. Operators with a higher
+// precedence bind tighter, and every infix operator binds looser than prefix operators.
+const OPERATORS: &[(&str, usize, Assoc)] = &[
+    (";", 0, Assoc::Right),
+    ("$", 1, Assoc::Right),
+    ("or", 2, Assoc::Left),
+    ("xor", 3, Assoc::Left),
+    ("and", 4, Assoc::Left),
+    ("<", 5, Assoc::Left),
+    (">", 5, Assoc::Left),
+    ("<=", 5, Assoc::Left),
+    (">=", 5, Assoc::Left),
+    ("==", 5, Assoc::Left),
+    ("!=", 5, Assoc::Left),
+    ("|", 6, Assoc::Left),
+    ("^", 7, Assoc::Left),
+    ("&", 8, Assoc::Left),
+    ("<<", 9, Assoc::Left),
+    (">>", 9, Assoc::Left),
+    ("+", 10, Assoc::Left),
+    ("-", 10, Assoc::Left),
+    ("*", 11, Assoc::Left),
+    ("/", 11, Assoc::Left),
+    ("%", 11, Assoc::Left),
+];
+
+// operator_table(&str) -> HashMap<String, (usize, Assoc)>
+// Returns the precedence and associativity of the built in operators and of the operators declared
+// with `infixl` or `infixr` in the given code. Declarations are found before parsing, so operators
+// can be used before they are declared.
+fn operator_table(s: &str) -> HashMap<String, (usize, Assoc)> {
+    let mut table: HashMap<_, _> = OPERATORS
+        .iter()
+        .map(|&(op, prec, assoc)| (String::from(op), (prec, assoc)))
+        .collect();
+
+    let tokens: Vec<_> = Token::lexer(s).spanned().collect();
+    for window in tokens.windows(3) {
+        let assoc = match window[0].0 {
+            Token::Infixl => Assoc::Left,
+            Token::Infixr => Assoc::Right,
+            _ => continue,
+        };
+
+        if let (Token::Int(prec), Token::Operator) = (&window[1].0, &window[2].0) {
+            table.insert(
+                String::from(&s[window[2].1.clone()]),
+                (*prec as usize, assoc),
+            );
+        }
+    }
+
+    table
+}
+
 // Represents a parser.
 struct Parser<'a> {
     // The lexer the parser uses internally.
@@ -190,6 +447,9 @@ struct Parser<'a> {
 
     // The current position of the parser.
     token_pos: usize,
+
+    // The precedence and associativity of every infix operator
+    operators: HashMap<String, (usize, Assoc)>,
 }
 
 impl<'a> Parser<'a> {
@@ -200,6 +460,7 @@ impl<'a> Parser<'a> {
             lexer: Token::lexer(s),
             tokens: vec![],
             token_pos: 0,
+            operators: operator_table(s),
         }
     }
 
@@ -272,6 +533,28 @@ impl<'a> Parser<'a> {
     fn return_state(&mut self, state: usize) {
         self.token_pos = state;
     }
+
+    // synchronise(&mut self, &Span) -> ()
+    // Skips from the token at the given span to the start of the next top level definition, which
+    // is the first token after a newline that is not indented.
+    fn synchronise(&mut self, span: &Span) {
+        self.token_pos = self
+            .tokens
+            .iter()
+            .position(|v| v.1.start >= span.start)
+            .unwrap_or(self.tokens.len());
+
+        while let Some((token, span)) = self.peek() {
+            let newline = *token == Token::Newline;
+            self.next();
+            if newline {
+                match self.peek() {
+                    Some((_, next)) if next.start == span.end => break,
+                    _ => (),
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -281,8 +564,8 @@ pub enum Ast {
     // Numbers
     Int(Span, i64),
     Float(Span, f64),
-    Word(Span, u64),
-    Char(Span, u8),
+    SizedInt(Span, i64, IntSize),
+    Char(Span, char),
 
     // String
     String(Span, String),
@@ -308,12 +591,18 @@ pub enum Ast {
     // Prefix expressions
     Prefix(Span, String, Box<Ast>),
 
+    // Propagating the error of a result or the absence of an option (x?)
+    Try(Span, Box<Ast>),
+
     // Infix expressions
     Infix(Span, String, Box<Ast>, Box<Ast>),
 
     // Casting
     As(Span, Box<Ast>, Box<Ast>),
 
+    // Type ascription ((x : T))
+    Ascribe(Span, Box<Ast>, Box<Ast>),
+
     // Assignments
     Assign(Span, String, Box<Ast>),
 
@@ -329,7 +618,7 @@ pub enum Ast {
     // Lambda functions
     Lambda(Span, Vec<(String, Ast)>, Box<Ast>),
 
-    // Match expressions
+    // Match expressions, with the pattern and value of each arm
     Match(Span, Box<Ast>, Vec<(Ast, Ast)>),
 
     // Scoping
@@ -346,6 +635,9 @@ pub enum Ast {
 
     // External functions
     Extern(Span, String, String, Box<Ast>),
+
+    // Tests, with the name and the value that must be true
+    Test(Span, String, Box<Ast>),
 }
 
 impl Ast {
@@ -353,7 +645,7 @@ impl Ast {
         match self {
             Self::Int(s, _)
             | Self::Float(s, _)
-            | Self::Word(s, _)
+            | Self::SizedInt(s, _, _)
             | Self::Char(s, _)
             | Self::String(s, _)
             | Self::List(s, _)
@@ -363,8 +655,10 @@ impl Ast {
             | Self::Annotation(s, _)
             | Self::Application(s, _, _)
             | Self::Prefix(s, _, _)
+            | Self::Try(s, _)
             | Self::Infix(s, _, _, _)
             | Self::As(s, _, _)
+            | Self::Ascribe(s, _, _)
             | Self::Assign(s, _, _)
             | Self::AssignTyped(s, _, _, _)
             | Self::AssignType(s, _, _)
@@ -377,7 +671,8 @@ impl Ast {
             | Self::QualifiedImport(s, _, _)
             | Self::Header(s, _, _, _)
             | Self::LibHeader(s, _, _)
-            | Self::Extern(s, _, _, _) => s.clone(),
+            | Self::Extern(s, _, _, _)
+            | Self::Test(s, _, _) => s.clone(),
 
             Self::Empty => panic!("uwu moment"),
         }
@@ -497,10 +792,10 @@ macro_rules! consume_save
     }
 }
 
-// infixl_op(ident, ident, pat, pat) -> Result<Ast, ParseError>
-// Parses a left associative infix operator.
+// infixl_op(ident, ident, pat+) -> Result<Ast, ParseError>
+// Parses a left associative infix operator that matches any of the given patterns.
 macro_rules! infixl_op {
-    ($parser: ident, $subfunc: ident, $op1: pat, $op2: pat) => {{
+    ($parser: ident, $subfunc: ident, $($op: pat),+) => {{
         // Set up
         let state = $parser.save_state();
         let mut left = call_func!($subfunc, $parser, state);
@@ -514,7 +809,7 @@ macro_rules! infixl_op {
             if let Some(op) = $parser.peek() {
                 // Get operator
                 let op = match op.0 {
-                    $op1 | $op2 => String::from($parser.slice()),
+                    $($op)|+ => String::from($parser.slice()),
                     _ => {
                         $parser.return_state(state2);
                         break;
@@ -659,6 +954,98 @@ fn access_member(parser: &mut Parser) -> Result<Ast, ParseError> {
     infixl_op!(parser, symbol, Token::ColonColon, Token::Unreachable)
 }
 
+// literal_out_of_range(&mut Parser) -> Option<ParseError>
+// Returns an error if the next token is an int literal that is out of the range of its type, which
+// the lexer does not accept as an int, or the magnitude of the smallest value of a signed type,
+// which is only in range when it is negated.
+fn literal_out_of_range(parser: &mut Parser) -> Option<ParseError> {
+    match parser.peek()?.0 {
+        Token::Int(n) if *n == i64::MIN => {
+            return Some(out_of_range(parser, IntSize::new(true, 64)))
+        }
+        Token::SizedInt((n, size)) if size.signed && *n as i128 == size.min() => {
+            let size = *size;
+            return Some(out_of_range(parser, size));
+        }
+        Token::Error => (),
+        _ => return None,
+    }
+
+    let s = parser.slice();
+    let suffixed = s
+        .rfind(['i', 'u'])
+        .and_then(|split| Some((&s[..split], IntSize::from_suffix(&s[split..])?)));
+    let (literal, size) = suffixed.unwrap_or((&s, IntSize::new(true, 64)));
+    let (digits, radix) = split_radix(literal);
+    let digit = |c: char| c.is_digit(radix);
+    if !digits.chars().any(digit) || !digits.chars().all(|c| c == '_' || digit(c)) {
+        return None;
+    }
+
+    Some(out_of_range(parser, size))
+}
+
+// out_of_range(&mut Parser, IntSize) -> ParseError
+// Returns the error for an int literal at the next token that is out of the range of its type.
+fn out_of_range(parser: &mut Parser, size: IntSize) -> ParseError {
+    ParseError {
+        span: parser.span(),
+        msg: format!("Int literal is out of the range of `{}`", size.into_type()),
+        fatal: true,
+    }
+}
+
+// negative_literal(&mut Parser, bool) -> Result<Ast, ParseError>
+// Parses `-` followed by a number literal as the negated literal. Only int literals are parsed if
+// ints_only is set, as in patterns. `-` is otherwise an infix operator, so a negative literal can
+// only be used where a value starts, such as after `(` or another operator.
+fn negative_literal(parser: &mut Parser, ints_only: bool) -> Result<Ast, ParseError> {
+    let state = parser.save_state();
+    let start = match parser.peek() {
+        Some((Token::Sub, span)) => span.start,
+        _ => return ParseError::empty(),
+    };
+    parser.next();
+
+    // Magnitudes too large to negate are not lexed as ints
+    if let Some((Token::Error, _)) = parser.peek() {
+        if let Some(e) = literal_out_of_range(parser) {
+            return Err(e);
+        }
+    }
+
+    let (token, span) = match parser.peek() {
+        Some(v) => v,
+        None => {
+            parser.return_state(state);
+            return ParseError::empty();
+        }
+    };
+    let span = Span {
+        start,
+        end: span.end,
+    };
+
+    let literal = match token {
+        Token::Int(n) => Ast::Int(span, n.wrapping_neg()),
+        Token::SizedInt((n, size)) if !ints_only && (size.signed || *n == 0) => {
+            Ast::SizedInt(span, size.wrap(-(*n as i128)), *size)
+        }
+        Token::SizedInt((_, size)) if !ints_only => {
+            let size = *size;
+            return Err(out_of_range(parser, size));
+        }
+        Token::Float(f) if !ints_only => Ast::Float(span, -f),
+
+        _ => {
+            parser.return_state(state);
+            return ParseError::empty();
+        }
+    };
+    parser.next();
+    Ok(literal)
+}
+
 // value(&mut Parser) -> Result<Ast, ParseError>
 // Gets the next value.
 fn value(parser: &mut Parser) -> Result<Ast, ParseError> {
@@ -667,27 +1054,54 @@ fn value(parser: &mut Parser) -> Result<Ast, ParseError> {
         return Ok(v);
     }
 
+    if let Some(e) = literal_out_of_range(parser) {
+        return Err(e);
+    }
+
     // Get token
-    let (token, _span) = match parser.peek() {
+    let (token, span) = match parser.peek() {
         Some(v) => v,
         None => return ParseError::empty(),
     };
 
-    /*
+    // Check for string
+    if let Token::String(s) = token {
+        let s = s.clone();
+        parser.next();
+        return Ok(Ast::String(span, s));
+    }
+
+    // Check for char
+    if let Token::Char(c) = token {
+        let c = *c;
+        parser.next();
+        return Ok(Ast::Char(span, c));
+    }
+
     // Check for int
     if let Token::Int(n) = token {
         let n = *n;
         parser.next();
-        Ok(Ast::Int(span, n))
+        return Ok(Ast::Int(span, n));
+    }
+
+    // Check for int with a suffix giving its type
+    if let Token::SizedInt((n, size)) = token {
+        let (n, size) = (*n, *size);
+        parser.next();
+        return Ok(Ast::SizedInt(span, n, size));
+    }
 
     // Check for float
-    } else if let Token::Float(n) = token {
+    if let Token::Float(n) = token {
         let n = *n;
         parser.next();
-        Ok(Ast::Float(span, n))
+        return Ok(Ast::Float(span, n));
+    }
 
+    /*
     // Check for word
-    } else if let Token::Word(n) = token {
+    if let Token::Word(n) = token {
         let n = *n;
         parser.next();
         Ok(Ast::Word(span, n))
@@ -735,6 +1149,22 @@ fn value(parser: &mut Parser) -> Result<Ast, ParseError> {
         let state = parser.save_state();
         parser.next();
         newline(parser);
+
+        // Operators in parentheses are the functions they apply
+        if let Some((Token::Operator, _)) = parser.peek() {
+            let op = parser.slice();
+            parser.next();
+            newline(parser);
+            let (_, end) =
+                consume_save!(parser, RParen, state, true, "Expected `)` after operator");
+            return Ok(Ast::Symbol(
+                Span {
+                    start: span.start,
+                    end: end.end,
+                },
+                op,
+            ));
+        }
         let value = match expression(parser) {
             Ok(v) => v,
             Err(e) => {
@@ -743,8 +1173,25 @@ fn value(parser: &mut Parser) -> Result<Ast, ParseError> {
             }
         };
 
-        // Get right parenthesis
+        // Values in parentheses can be followed by the type they are checked to have
         newline(parser);
+        if let Some((Token::Colon, _)) = parser.peek() {
+            parser.next();
+            newline(parser);
+            let _type = call_func_fatal!(type_expr, parser, "Expected type after `:`");
+            newline(parser);
+            let (_, end) = consume_save!(parser, RParen, state, true, "Expected `)` after type");
+            return Ok(Ast::Ascribe(
+                Span {
+                    start: span.start,
+                    end: end.end,
+                },
+                Box::new(value),
+                Box::new(_type),
+            ));
+        }
+
+        // Get right parenthesis
         consume_nosave!(parser, RParen, state, true, "");
         Ok(value)
 
@@ -754,12 +1201,33 @@ fn value(parser: &mut Parser) -> Result<Ast, ParseError> {
     }
 }
 
+// propagate(&mut Parser) -> Result<Ast, ParseError>
+// Parses a value followed by any number of `?`, which bind tighter than function application.
+fn propagate(parser: &mut Parser) -> Result<Ast, ParseError> {
+    let mut value = value(parser)?;
+
+    while let Some((Token::Question, span)) = parser.peek() {
+        parser.next();
+        value = Ast::Try(
+            Span {
+                start: value.get_span().start,
+                end: span.end,
+            },
+            Box::new(value),
+        );
+    }
+
+    Ok(value)
+}
+
+// _as(&mut Parser) -> Result<Ast, ParseError>
+// Parses a conversion of a value into another type.
 fn _as(parser: &mut Parser) -> Result<Ast, ParseError> {
-    let value = value(parser)?;
+    let value = propagate(parser)?;
 
-    if let Some((Token::Colon, _)) = parser.peek() {
+    if let Some((Token::As, _)) = parser.peek() {
         parser.next();
-        let _type = call_func_fatal!(type_expr, parser, "Expected type after `:`");
+        let _type = call_func_fatal!(type_expr, parser, "Expected type after `as`");
 
         Ok(Ast::As(
             Span {
@@ -905,6 +1373,72 @@ fn lambda(parser: &mut Parser) -> Result<Ast, ParseError> {
     ))
 }
 
+// pattern_atom(&mut Parser) -> Result<Ast, ParseError>
+// Parses a symbol, an int, char, or string literal, or a parenthesised pattern.
+fn pattern_atom(parser: &mut Parser) -> Result<Ast, ParseError> {
+    if let Some(e) = literal_out_of_range(parser) {
+        return Err(e);
+    }
+
+    let state = parser.save_state();
+    let (token, span) = match parser.peek() {
+        Some(v) => v,
+        None => return ParseError::empty(),
+    };
+
+    let literal = match token {
+        Token::Int(n) => Ast::Int(span, *n),
+        Token::Char(c) => Ast::Char(span, *c),
+        Token::String(s) => Ast::String(span, s.clone()),
+        Token::Sub => return negative_literal(parser, true),
+
+        Token::LParen => {
+            parser.next();
+            let pattern = call_func_fatal!(pattern, parser, "Expected pattern after `(`");
+            consume_nosave!(parser, RParen, state, true, "Expected `)` after pattern");
+            return Ok(pattern);
+        }
+
+        _ => return symbol(parser),
+    };
+    parser.next();
+    Ok(literal)
+}
+
+// pattern(&mut Parser) -> Result<Ast, ParseError>
+// Parses a pattern, which is a wildcard, a binding, a literal, or a constructor followed by the
+// patterns of its fields.
+fn pattern(parser: &mut Parser) -> Result<Ast, ParseError> {
+    let constructor = pattern_atom(parser)?;
+    let mut fields = vec![];
+    loop {
+        match pattern_atom(parser) {
+            Ok(v) => fields.push(v),
+            Err(e) if e.fatal => return Err(e),
+            Err(_) => break,
+        }
+    }
+
+    if fields.is_empty() {
+        Ok(constructor)
+    } else if !matches!(constructor, Ast::Symbol(_, _)) {
+        Err(ParseError {
+            span: constructor.get_span(),
+            msg: String::from("Expected constructor before the patterns of its fields"),
+            fatal: true,
+        })
+    } else {
+        Ok(Ast::Application(
+            Span {
+                start: constructor.get_span().start,
+                end: fields.last().unwrap().get_span().end,
+            },
+            Box::new(constructor),
+            fields,
+        ))
+    }
+}
+
 // matchy(&mut Parser) -> Result<Ast, ParseError>
 // Parses a match expression.
 fn matchy(parser: &mut Parser) -> Result<Ast, ParseError> {
@@ -918,12 +1452,18 @@ fn matchy(parser: &mut Parser) -> Result<Ast, ParseError> {
 
     while let Some((Token::To, _)) = parser.peek() {
         parser.next();
-        let _type = call_func_fatal!(type_expr, parser, "Expected type after `to`");
+        let pattern = call_func_fatal!(pattern, parser, "Expected pattern after `to`");
         newline(parser);
-        consume_nosave!(parser, ThiccArrow, state, true, "Expected `=>` after type");
+        consume_nosave!(
+            parser,
+            ThiccArrow,
+            state,
+            true,
+            "Expected `=>` after pattern"
+        );
         newline(parser);
         let value = call_func_fatal!(apply_op, parser, "Expected expression after `=>`");
-        arms.push((_type, value));
+        arms.push((pattern, value));
         newline(parser);
     }
 
@@ -949,6 +1489,44 @@ fn matchy(parser: &mut Parser) -> Result<Ast, ParseError> {
     ))
 }
 
+// ify(&mut Parser) -> Result<Ast, ParseError>
+// Parses an if expression, which becomes a match of the condition against `true` and `false`. An
+// `else if` chain becomes a match nested in the `false` arm of each match before it.
+fn ify(parser: &mut Parser) -> Result<Ast, ParseError> {
+    let state = parser.save_state();
+    let (_, span) = consume_save!(parser, If, state, false, "");
+
+    let cond = call_func_fatal!(apply_op, parser, "Expected condition after `if`");
+    newline(parser);
+    consume_nosave!(parser, Then, state, true, "Expected `then` after condition");
+    newline(parser);
+    let then = call_func_fatal!(apply_op, parser, "Expected expression after `then`");
+    newline(parser);
+    consume_nosave!(
+        parser,
+        Else,
+        state,
+        true,
+        "Expected `else` after `then` branch"
+    );
+    newline(parser);
+    let elsy = call_func_fatal!(apply_op, parser, "Expected expression after `else`");
+
+    // The patterns point at the condition, which is what a type error in them is about
+    let cond_span = cond.get_span();
+    Ok(Ast::Match(
+        Span {
+            start: span.start,
+            end: elsy.get_span().end,
+        },
+        Box::new(cond),
+        vec![
+            (Ast::Symbol(cond_span.clone(), String::from("true")), then),
+            (Ast::Symbol(cond_span, String::from("false")), elsy),
+        ],
+    ))
+}
+
 // expression_values(&mut Parser) -> Result<Ast, ParseError>
 // Parses an expression.
 fn expression_values(parser: &mut Parser) -> Result<Ast, ParseError> {
@@ -960,21 +1538,206 @@ fn expression_values(parser: &mut Parser) -> Result<Ast, ParseError> {
         Ok(list)
     } else if let Ok(matchy) = call_optional!(matchy, parser) {
         Ok(matchy)
+    } else if let Ok(ify) = call_optional!(ify, parser) {
+        Ok(ify)
     } else {
         application(parser)
     }
 }
 
-// apply_op(&mut Parser) -> Result<Ast::Infix, ParseError>
-// Gets the next infix application.
+// prefix(&mut Parser) -> Result<Ast, ParseError>
+// Parses a value with any number of prefix operators (`not` and `~`) before it. Prefix operators
+// bind looser than function application but tighter than any infix operator.
+fn prefix(parser: &mut Parser) -> Result<Ast, ParseError> {
+    match parser.peek() {
+        Some((Token::Not, _)) | Some((Token::Tilde, _)) => {
+            let op = parser.slice();
+            let (_, span) = parser.next().unwrap().clone();
+            let value = call_func_fatal!(prefix, parser, "Expected value after prefix operator");
+
+            Ok(Ast::Prefix(
+                Span {
+                    start: span.start,
+                    end: value.get_span().end,
+                },
+                op,
+                Box::new(value),
+            ))
+        }
+
+        Some((Token::Sub, _)) => negative_literal(parser, false),
+
+        _ => expression_values(parser),
+    }
+}
+
+// infix(&mut Parser, usize) -> Result<Ast, ParseError>
+// Parses values separated by infix operators whose precedence is at least the given precedence,
+// and groups them by the precedence and associativity of the operators.
+fn infix(parser: &mut Parser, min: usize) -> Result<Ast, ParseError> {
+    let state = parser.save_state();
+    let mut values = vec![call_func!(prefix, parser, state)];
+    let mut ops = vec![];
+
+    loop {
+        // Save current state
+        let state2 = parser.save_state();
+        newline(parser);
+
+        // Check for operator
+        let (user, span) = match parser.peek() {
+            Some((t, s)) => (*t == Token::Operator, s),
+            None => break,
+        };
+        let op = parser.slice();
+        let (prec, assoc) = match parser.operators.get(&op) {
+            Some(&(prec, assoc)) if prec >= min => (prec, assoc),
+
+            None if user => {
+                return Err(ParseError {
+                    span,
+                    msg: format!(
+                        "Operator `{}` is not declared with `infixl` or `infixr`",
+                        op
+                    ),
+                    fatal: true,
+                });
+            }
+
+            _ => {
+                parser.return_state(state2);
+                break;
+            }
+        };
+        parser.next();
+        newline(parser);
+
+        // Get right hand side
+        let right = call_func_fatal!(prefix, parser, "Expected value after infix operator");
+        ops.push((op, span, prec, assoc));
+        values.push(right);
+    }
+
+    resolve_operators(values, ops)
+}
+
+// resolve_operators(Vec<Ast>, Vec<(String, Span, usize, Assoc)>) -> Result<Ast, ParseError>
+// Groups values separated by infix operators into infix asts, so that operators with a higher
+// precedence are applied first and operators with the same precedence are applied in the order
+// their associativity gives.
+fn resolve_operators(
+    values: Vec<Ast>,
+    ops: Vec<(String, Span, usize, Assoc)>,
+) -> Result<Ast, ParseError> {
+    let mut values = values.into_iter();
+    let mut operands = vec![values.next().unwrap()];
+    let mut pending: Vec<(String, Span, usize, Assoc)> = vec![];
+
+    for (op, value) in ops.into_iter().zip(values) {
+        while let Some(top) = pending.last() {
+            if top.2 == op.2 && top.3 != op.3 {
+                return Err(ParseError {
+                    span: op.1,
+                    msg: format!(
+                        "`{}` and `{}` have the same precedence but different associativity, so \
+                         one of them needs parentheses",
+                        top.0, op.0
+                    ),
+                    fatal: true,
+                });
+            } else if top.2 < op.2 || (top.2 == op.2 && op.3 == Assoc::Right) {
+                break;
+            }
+
+            let top = pending.pop().unwrap();
+            apply_operator(&mut operands, top.0);
+        }
+
+        pending.push(op);
+        operands.push(value);
+    }
+
+    while let Some(top) = pending.pop() {
+        apply_operator(&mut operands, top.0);
+    }
+    Ok(operands.pop().unwrap())
+}
+
+// apply_operator(&mut Vec<Ast>, String) -> ()
+// Replaces the last two values on the stack with an infix ast applying the operator to them.
+fn apply_operator(operands: &mut Vec<Ast>, op: String) {
+    let right = operands.pop().unwrap();
+    let left = operands.pop().unwrap();
+    operands.push(Ast::Infix(
+        Span {
+            start: left.get_span().start,
+            end: right.get_span().end,
+        },
+        op,
+        Box::new(left),
+        Box::new(right),
+    ));
+}
+
+// apply_op(&mut Parser) -> Result<Ast, ParseError>
+// Parses an expression with infix operators other than `;`.
 fn apply_op(parser: &mut Parser) -> Result<Ast, ParseError> {
-    infixr_op!(parser, expression_values, Token::Dollar, Token::Unreachable)
+    infix(parser, 1)
 }
 
 // expression(&mut Parser) -> Result<Ast, ParseError>
 // Parses expressions chained by ;.
 fn expression(parser: &mut Parser) -> Result<Ast, ParseError> {
-    infixr_op!(parser, apply_op, Token::Semicolon, Token::Unreachable)
+    infix(parser, 0)
+}
+
+// fixity(&mut Parser) -> Result<Ast, ParseError>
+// Parses the declaration of an infix operator, such as `infixl 6 <+> = add_vec`, which defines the
+// operator as a function of two arguments. The precedence and associativity were already added to
+// the table of operators before parsing started.
+fn fixity(parser: &mut Parser) -> Result<Ast, ParseError> {
+    let state = parser.save_state();
+    let span = match parser.peek() {
+        Some((Token::Infixl, span)) | Some((Token::Infixr, span)) => span,
+        _ => return ParseError::empty(),
+    };
+    parser.next();
+
+    // Get the precedence and operator
+    match parser.peek() {
+        Some((Token::Int(_), _)) => {
+            parser.next();
+        }
+
+        _ => {
+            return Err(ParseError {
+                span: parser.span(),
+                msg: String::from("Expected precedence after fixity declaration"),
+                fatal: true,
+            })
+        }
+    }
+    let (op, _) = consume_save!(
+        parser,
+        Operator,
+        state,
+        true,
+        "Expected operator after precedence"
+    );
+
+    // Get the function the operator applies
+    consume_nosave!(parser, Assign, state, true, "Expected `=` after operator");
+    newline(parser);
+    let value = call_func_fatal!(expression, parser, "Expected value after `=`");
+
+    Ok(Ast::Assign(
+        Span {
+            start: span.start,
+            end: value.get_span().end,
+        },
+        op,
+        Box::new(value),
+    ))
 }
 
 // annotation(&mut Parser) -> Result<Ast, ParseError>
@@ -1055,6 +1818,34 @@ fn type_symbol(parser: &mut Parser) -> Result<Ast, ParseError> {
     }
 }
 
+// type_application(&mut Parser) -> Result<Ast, ParseError>
+// Parses the application of a type constructor to types (Array Int).
+fn type_application(parser: &mut Parser) -> Result<Ast, ParseError> {
+    let mut func = type_symbol(parser)?;
+
+    loop {
+        let right = match type_symbol(parser) {
+            Ok(v) => v,
+            Err(e) if e.fatal => break Err(e),
+            Err(_) => break Ok(func),
+        };
+
+        if let Ast::Application(s, _, v) = &mut func {
+            s.end = right.get_span().end;
+            v.push(right);
+        } else {
+            func = Ast::Application(
+                Span {
+                    start: func.get_span().start,
+                    end: right.get_span().end,
+                },
+                Box::new(func),
+                vec![right],
+            );
+        }
+    }
+}
+
 // type_tagged(&mut Parser) -> Result<Ast< ParseError>
 // Parses a tagged type (a: T).
 fn type_tagged(parser: &mut Parser) -> Result<Ast, ParseError> {
@@ -1080,7 +1871,7 @@ fn type_field(parser: &mut Parser) -> Result<Ast, ParseError> {
     if let Ok(v) = call_optional!(type_tagged, parser) {
         Ok(v)
     } else {
-        type_symbol(parser)
+        type_application(parser)
     }
 }
 
@@ -1250,6 +2041,89 @@ fn with(parser: &mut Parser) -> Result<Ast, ParseError> {
     ))
 }
 
+// indented_newline(&mut Parser) -> bool
+// Skips newlines and returns true if the line after them is indented, like the lines that continue
+// a top level definition.
+fn indented_newline(parser: &mut Parser) -> bool {
+    let mut end = None;
+    while let Some((Token::Newline, span)) = parser.peek() {
+        end = Some(span.end);
+        parser.next();
+    }
+
+    match (end, parser.peek()) {
+        (Some(end), Some((_, span))) => span.start != end,
+        _ => false,
+    }
+}
+
+// where_clause(&mut Parser, Ast) -> Result<Ast, ParseError>
+// Parses the `where` bindings that may follow a top level assignment or test, which become a `let`
+// around its value. Each binding after the first is on an indented line of its own, so the
+// bindings end at the next top level definition.
+fn where_clause(parser: &mut Parser, assign: Ast) -> Result<Ast, ParseError> {
+    // Get the where keyword
+    let state = parser.save_state();
+    newline(parser);
+    if let Some((Token::Where, _)) = parser.peek() {
+        parser.next();
+        let state2 = parser.save_state();
+        if !indented_newline(parser) {
+            parser.return_state(state2);
+        }
+    } else {
+        parser.return_state(state);
+        return Ok(assign);
+    }
+
+    // Get assignments
+    let mut assigns = vec![call_func_fatal!(
+        assignment,
+        parser,
+        "Expected binding after `where`"
+    )];
+    loop {
+        let state2 = parser.save_state();
+        if !indented_newline(parser) {
+            parser.return_state(state2);
+            break;
+        }
+
+        match assignment(parser) {
+            Ok(v) => assigns.push(v),
+            Err(e) if e.fatal => return Err(e),
+            Err(_) => {
+                parser.return_state(state2);
+                break;
+            }
+        }
+    }
+
+    // Wrap the value in the bindings
+    let end = assigns.last().unwrap().get_span().end;
+    let with = |value: Box<Ast>| {
+        Box::new(Ast::With(
+            Span {
+                start: value.get_span().start,
+                end,
+            },
+            assigns,
+            value,
+        ))
+    };
+    Ok(match assign {
+        Ast::Assign(span, name, value) => Ast::Assign(Span { end, ..span }, name, with(value)),
+
+        Ast::AssignFunction(span, name, args, value) => {
+            Ast::AssignFunction(Span { end, ..span }, name, args, with(value))
+        }
+
+        Ast::Test(span, name, value) => Ast::Test(Span { end, ..span }, name, with(value)),
+
+        _ => unreachable!("assignments and tests are the only definitions with values"),
+    })
+}
+
 /*
 // import(&mut Parser) -> Result<Ast, ParseError>
 // Parses an import statement.
@@ -1446,6 +2320,8 @@ fn header(parser: &mut Parser) -> Result<Ast, ParseError> {
     ))
 }
 
+*/
+
 // externy(&mut Parser) -> Result<Ast, ParseError>
 // Parses an external function declaration.
 fn externy(parser: &mut Parser) -> Result<Ast, ParseError> {
@@ -1490,13 +2366,87 @@ fn externy(parser: &mut Parser) -> Result<Ast, ParseError> {
         Box::new(_type),
     ))
 }
-*/
 
-// parse(&str) -> Result<Ast, ParseError>
-// Parses curly code.
-pub fn parse(s: &str) -> Result<Vec<Ast>, ParseError> {
+// test_declaration(&mut Parser) -> Result<Ast, ParseError>
+// Parses a test, such as `test "addition" = 1 + 1 == 2`.
+fn test_declaration(parser: &mut Parser) -> Result<Ast, ParseError> {
+    let state = parser.save_state();
+    let (_, span) = consume_save!(parser, Test, state, false, "");
+
+    let name = if let Some((Token::String(s), _)) = parser.peek() {
+        s.clone()
+    } else {
+        return Err(ParseError {
+            span: parser.span(),
+            msg: String::from("Expected string literal after `test`"),
+            fatal: true,
+        });
+    };
+
+    parser.next();
+    consume_nosave!(parser, Assign, state, true, "Expected `=` after test name");
+    newline(parser);
+    let value = call_func_fatal!(expression, parser, "Expected value after `=`");
+
+    Ok(Ast::Test(
+        Span {
+            start: span.start,
+            end: value.get_span().end,
+        },
+        name,
+        Box::new(value),
+    ))
+}
+
+// top_level(&mut Parser) -> Result<Ast, ParseError>
+// Parses a top level definition.
+fn top_level(p: &mut Parser) -> Result<Ast, ParseError> {
+    if let Ok(annotation) = call_optional!(annotation, p) {
+        Ok(annotation)
+    } else if let Ok(assign) = call_optional!(assignment, p) {
+        where_clause(p, assign)
+    } else if let Ok(fixity) = call_optional!(fixity, p) {
+        Ok(fixity)
+    } else if let Ok(ext) = call_optional!(externy, p) {
+        Ok(ext)
+    } else if let Ok(test) = call_optional!(test_declaration, p) {
+        where_clause(p, test)
+    } else {
+        match type_assignment(p) {
+            Ok(v) => Ok(v),
+            Err(e) if e.fatal => Err(e),
+            Err(_) => {
+                let peeked = if p.peek().is_some() {
+                    p.slice()
+                } else {
+                    String::from("eof")
+                };
+                Err(ParseError {
+                    span: p.span(),
+                    msg: format!("Unexpected `{}`", peeked),
+                    fatal: true,
+                })
+            }
+        }
+    }
+}
+
+// doc_comments(&str) -> Vec<(Span, String)>
+// Returns the doc comments in curly code, which start with `##`, along with where they are.
+pub fn doc_comments(s: &str) -> Vec<(Span, String)> {
+    let mut lexer = Token::lexer(s);
+    lexer.by_ref().for_each(drop);
+    lexer.extras
+}
+
+// parse(&str) -> Result<Vec<Ast>, Vec<ParseError>>
+// Parses curly code. After a syntax error, parsing resumes at the next top level definition, so
+// that every error in the code is returned at once.
+pub fn parse(s: &str) -> Result<Vec<Ast>, Vec<ParseError>> {
+    let _span = info_span!("parse").entered();
     let mut parser = Parser::new(s);
     let mut lines = vec![];
+    let mut errors = vec![];
     let p = &mut parser;
 
     newline(p);
@@ -1507,53 +2457,30 @@ pub fn parse(s: &str) -> Result<Vec<Ast>, ParseError> {
 
     while p.peek().is_some() {
         // Parse one line
-        if let Ok(annotation) = call_optional!(annotation, p) {
-            lines.push(annotation);
-        } else if let Ok(assign) = call_optional!(assignment, p) {
-            lines.push(assign);
-        } else {
-            lines.push(match type_assignment(p) {
-                Ok(v) => v,
-                Err(e) if e.fatal => return Err(e),
-                Err(_) => {
-                    let peeked = if p.peek().is_some() {
-                        p.slice()
-                    } else {
-                        String::from("eof")
+        match top_level(p) {
+            Ok(v) => lines.push(v),
+            Err(mut e) => {
+                // Some errors only give where they happened
+                if e.msg.is_empty() {
+                    e.msg = match &s[e.span.clone()] {
+                        "" => String::from("Unexpected end of file"),
+                        v => format!("Unexpected `{}`", v),
                     };
-                    return Err(ParseError {
-                        span: p.span(),
-                        msg: format!("Unexpected `{}`", peeked),
-                        fatal: true,
-                    });
                 }
-            });
+                debug!(msg = %e.msg, "syntax error");
+                p.synchronise(&e.span);
+                errors.push(e);
+            }
         }
-        /*
-        } else if let Ok(_type) = call_optional!(type_assignment, p) {
-            lines.push(_type);
-        } else {
-            lines.push(match externy(p) {
-                Ok(v) => v,
-                Err(e) if e.fatal => return Err(e),
-                Err(_) => {
-                    let peeked = if p.peek().is_some() {
-                        p.slice()
-                    } else {
-                        String::from("eof")
-                    };
-                    return Err(ParseError {
-                        span: p.span(),
-                        msg: format!("Unexpected `{}`", peeked),
-                        fatal: true,
-                    });
-                }
-            });
-            */
 
         // Skip newlines
         newline(p);
     }
 
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    debug!(?lines, "parsed");
     Ok(lines)
 }
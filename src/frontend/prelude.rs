@@ -0,0 +1,17 @@
+use super::parser::{self, Ast};
+
+/// The name used as the filename of prelude definitions.
+pub const FILENAME: &str = "<prelude>";
+
+/// The prefix of the internal names of prelude functions. Names in programs cannot contain `.`, so
+/// prelude functions never collide with the functions of a program.
+pub const PREFIX: &str = "prelude.";
+
+/// The source code of the prelude.
+pub const SOURCE: &str = include_str!("../../lib/prelude.cly");
+
+// parse() -> Vec<Ast>
+// Parses the prelude. The prelude is embedded in the compiler, so it always parses.
+pub fn parse() -> Vec<Ast> {
+    parser::parse(SOURCE).expect("prelude should always parse")
+}
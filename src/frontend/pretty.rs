@@ -0,0 +1,586 @@
+use std::collections::HashMap;
+
+use super::ir::{BinOp, IrFunction, IrModule, PrefixOp, SExpr};
+use super::types::Type;
+
+/// The width pretty printed output tries to fit within by default.
+pub const DEFAULT_WIDTH: usize = 100;
+
+// Represents a document that can be laid out flat or broken over multiple lines.
+enum Doc {
+    // Text that is never broken.
+    Text(String),
+
+    // A space if the enclosing group is flat, a newline otherwise.
+    Line,
+
+    // A newline that forces every enclosing group to be broken.
+    HardLine,
+
+    // A document with additional indentation after newlines.
+    Nest(usize, Box<Doc>),
+
+    // A document that is laid out flat if it fits in the remaining width.
+    Group(Box<Doc>),
+
+    // A sequence of documents.
+    Concat(Vec<Doc>),
+}
+
+impl Doc {
+    fn text(s: &str) -> Doc {
+        Doc::Text(String::from(s))
+    }
+
+    fn nest(indent: usize, doc: Doc) -> Doc {
+        Doc::Nest(indent, Box::new(doc))
+    }
+
+    fn group(doc: Doc) -> Doc {
+        Doc::Group(Box::new(doc))
+    }
+
+    fn parens(doc: Doc) -> Doc {
+        Doc::Concat(vec![Doc::text("("), doc, Doc::text(")")])
+    }
+}
+
+// fits(isize, Vec<(usize, bool, &Doc)>) -> bool
+// Returns true if the given documents fit in the remaining width when laid out flat, stopping at
+// the first newline of a broken group.
+fn fits(mut width: isize, mut stack: Vec<(usize, bool, &Doc)>) -> bool {
+    while let Some((indent, flat, doc)) = stack.pop() {
+        if width < 0 {
+            return false;
+        }
+
+        match doc {
+            Doc::Text(s) => width -= s.len() as isize,
+            Doc::Line if flat => width -= 1,
+            Doc::Line => return true,
+            Doc::HardLine => return false,
+            Doc::Nest(i, d) => stack.push((indent + i, flat, d)),
+            Doc::Group(d) => stack.push((indent, flat, d)),
+            Doc::Concat(ds) => {
+                for d in ds.iter().rev() {
+                    stack.push((indent, flat, d));
+                }
+            }
+        }
+    }
+
+    width >= 0
+}
+
+// layout(&Doc, usize) -> String
+// Lays out a document, breaking groups that do not fit in the given width.
+fn layout(doc: &Doc, width: usize) -> String {
+    let mut output = String::new();
+    let mut column = 0;
+    let mut stack = vec![(0, false, doc)];
+
+    while let Some((indent, flat, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(s) => {
+                output.push_str(s);
+                column += s.len();
+            }
+
+            Doc::Line if flat => {
+                output.push(' ');
+                column += 1;
+            }
+
+            Doc::Line | Doc::HardLine => {
+                while output.ends_with(' ') {
+                    output.pop();
+                }
+                output.push('\n');
+                output.push_str(&" ".repeat(indent));
+                column = indent;
+            }
+
+            Doc::Nest(i, d) => stack.push((indent + i, flat, d)),
+
+            Doc::Group(d) => {
+                let flat = flat || {
+                    let mut rest = stack.clone();
+                    rest.push((indent, true, d));
+                    fits(width as isize - column as isize, rest)
+                };
+                stack.push((indent, flat, d));
+            }
+
+            Doc::Concat(ds) => {
+                for d in ds.iter().rev() {
+                    stack.push((indent, flat, d));
+                }
+            }
+        }
+    }
+
+    output
+}
+
+// escape_string(&str) -> String
+// Quotes a string, escaping the characters that the lexer unescapes.
+fn escape_string(s: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\0' => escaped.push_str("\\0"),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// escape_char(char) -> String
+// Escapes a character and surrounds it with single quotes.
+fn escape_char(c: char) -> String {
+    match c {
+        '\\' => String::from("'\\\\'"),
+        '\'' => String::from("'\\''"),
+        '\n' => String::from("'\\n'"),
+        '\r' => String::from("'\\r'"),
+        '\t' => String::from("'\\t'"),
+        '\0' => String::from("'\\0'"),
+        c => format!("'{}'", c),
+    }
+}
+
+// value_name(&str) -> String
+// Returns the name a value is referred to by, which is parenthesised if it is an operator.
+fn value_name(name: &str) -> String {
+    if name.chars().all(|c| "!$%&*+-/<=>^|~".contains(c)) {
+        format!("({})", name)
+    } else {
+        String::from(name)
+    }
+}
+
+// Precedence levels of expressions, from loosest to tightest binding.
+// Infix operators bind between expressions and prefix operators.
+const PREC_CHAIN: u8 = 0;
+const PREC_EXPR: u8 = 1;
+const PREC_PREFIX: u8 = 10;
+const PREC_APP: u8 = 11;
+const PREC_ATOM: u8 = 12;
+
+// infix_prec(BinOp) -> u8
+// Returns the precedence of an infix operator.
+fn infix_prec(op: BinOp) -> u8 {
+    PREC_EXPR
+        + match op {
+            BinOp::Xor => 1,
+            BinOp::LT | BinOp::GT | BinOp::LEQ | BinOp::GEQ | BinOp::EQ | BinOp::NEQ => 2,
+            BinOp::BitOr => 3,
+            BinOp::BitXor => 4,
+            BinOp::BitAnd => 5,
+            BinOp::LShift | BinOp::RShift => 6,
+            BinOp::Add | BinOp::Sub => 7,
+            BinOp::Mul | BinOp::Div | BinOp::Mod => 8,
+        }
+}
+
+// Represents the state of the pretty printer.
+struct Printer<'a> {
+    // The module functions are looked up in, if any.
+    module: Option<&'a IrModule>,
+
+    // The names assigned to each generic.
+    generics: HashMap<(String, usize), String>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(module: Option<&'a IrModule>) -> Printer<'a> {
+        Printer {
+            module,
+            generics: HashMap::new(),
+        }
+    }
+
+    // generic_name(&mut self, &str, usize) -> String
+    // Returns a printable name for a generic. Generics that share a name but have different uids
+    // are given distinct names.
+    fn generic_name(&mut self, name: &str, uid: usize) -> String {
+        if let Some(v) = self.generics.get(&(String::from(name), uid)) {
+            return v.clone();
+        }
+
+        let mut new = String::from(name);
+        let mut i = 1;
+        while self.generics.values().any(|v| *v == new) {
+            new = format!("{}{}", name, i);
+            i += 1;
+        }
+        self.generics.insert((String::from(name), uid), new.clone());
+        new
+    }
+
+    // _type(&mut self, &Type) -> String
+    // Prints a type with the minimal amount of parentheses.
+    fn _type(&mut self, _type: &Type) -> String {
+        match _type {
+            Type::Generic(g, uid) => format!("'{}", self.generic_name(g, *uid)),
+
+            // Function arguments that are functions need parentheses since arrows are right
+            // associative; unions bind tighter than arrows so they do not
+            Type::Func(arg, ret) => {
+                let arg_s = self._type(arg);
+                let ret_s = self._type(ret);
+                if let Type::Func(_, _) = **arg {
+                    format!("({}) -> {}", arg_s, ret_s)
+                } else {
+                    format!("{} -> {}", arg_s, ret_s)
+                }
+            }
+
+            Type::Array(item) => {
+                let item_s = self._type(item);
+                if item.needs_parentheses() {
+                    format!("Array ({})", item_s)
+                } else {
+                    format!("Array {}", item_s)
+                }
+            }
+
+            Type::Thread(result) => {
+                let result_s = self._type(result);
+                if result.needs_parentheses() {
+                    format!("Thread ({})", result_s)
+                } else {
+                    format!("Thread {}", result_s)
+                }
+            }
+
+            Type::Task(result) => {
+                let result_s = self._type(result);
+                if result.needs_parentheses() {
+                    format!("Task ({})", result_s)
+                } else {
+                    format!("Task {}", result_s)
+                }
+            }
+
+            Type::Result(value) => {
+                let value_s = self._type(value);
+                if value.needs_parentheses() {
+                    format!("Result ({})", value_s)
+                } else {
+                    format!("Result {}", value_s)
+                }
+            }
+
+            Type::Option(value) => {
+                let value_s = self._type(value);
+                if value.needs_parentheses() {
+                    format!("Option ({})", value_s)
+                } else {
+                    format!("Option {}", value_s)
+                }
+            }
+
+            Type::Map(key, value) => {
+                let mut s = String::from("Map");
+                for t in [key, value] {
+                    let t_s = self._type(t);
+                    if t.needs_parentheses() {
+                        s.push_str(&format!(" ({})", t_s));
+                    } else {
+                        s.push_str(&format!(" {}", t_s));
+                    }
+                }
+                s
+            }
+
+            Type::Union(_) => {
+                let mut fields = vec![];
+                for field in _type.as_union().unwrap() {
+                    let s = self._type(&field);
+                    if let Type::Func(_, _) = *field {
+                        fields.push(format!("({})", s));
+                    } else {
+                        fields.push(s);
+                    }
+                }
+                fields.join(" | ")
+            }
+
+            _ => _type.to_string(),
+        }
+    }
+
+    // args(&mut self, &IrFunction) -> String
+    // Prints the arguments of a function as `a: T, b: U`.
+    fn args(&mut self, func: &IrFunction) -> String {
+        let mut args = vec![];
+        for (name, _type) in func.args.iter() {
+            args.push(format!("{}: {}", name, self._type(_type)));
+        }
+        args.join(", ")
+    }
+
+    // sexpr(&mut self, &SExpr, u8) -> Doc
+    // Creates the document for an sexpression, parenthesising it if it binds looser than the given
+    // precedence.
+    fn sexpr(&mut self, sexpr: &SExpr, prec: u8) -> Doc {
+        let (doc, own) = match sexpr {
+            SExpr::Empty(_) => (Doc::text(""), PREC_ATOM),
+
+            SExpr::TypeAlias(m, name) => (
+                Doc::Text(format!("type {} = {}", name, self._type(&m._type))),
+                PREC_CHAIN,
+            ),
+
+            SExpr::Symbol(_, s) => (Doc::Text(value_name(s)), PREC_ATOM),
+
+            SExpr::Int(m, n) => match &*m._type {
+                Type::SizedInt(size) => (
+                    Doc::Text(format!("{}{}", size.value(*n), size.suffix())),
+                    PREC_ATOM,
+                ),
+                _ => (Doc::Text(n.to_string()), PREC_ATOM),
+            },
+
+            SExpr::Float(_, n) => (Doc::Text(format!("{:?}", n)), PREC_ATOM),
+
+            SExpr::Char(_, c) => (Doc::Text(escape_char(*c)), PREC_ATOM),
+
+            SExpr::String(_, s) => (Doc::Text(escape_string(s)), PREC_ATOM),
+
+            SExpr::Function(_, name) => {
+                match self.module.and_then(|m| m.function(name)) {
+                    // Lambdas are printed inline
+                    Some(func) if !func.global => {
+                        let args = self.args(func);
+                        let body = self.sexpr(&func.body, PREC_EXPR);
+                        (
+                            Doc::group(Doc::Concat(vec![
+                                Doc::Text(format!("\\{} .", args)),
+                                Doc::nest(4, Doc::Concat(vec![Doc::Line, body])),
+                            ])),
+                            PREC_APP,
+                        )
+                    }
+
+                    // Globals are printed by the name they are bound to
+                    Some(func) => {
+                        let name = self
+                            .module
+                            .and_then(|m| {
+                                m.globals
+                                    .iter()
+                                    .chain(m.prelude.iter())
+                                    .find(|(_, f)| **f == func.name)
+                            })
+                            .map_or(&func.name, |(g, _)| g);
+                        (Doc::Text(value_name(name)), PREC_ATOM)
+                    }
+                    None => (Doc::text(name), PREC_ATOM),
+                }
+            }
+
+            SExpr::ExternalFunc(_, name, args) => {
+                let mut docs = vec![Doc::text(name)];
+                for arg in args.iter() {
+                    docs.push(Doc::Line);
+                    docs.push(self.sexpr(arg, PREC_ATOM));
+                }
+                (
+                    Doc::group(Doc::nest(4, Doc::Concat(docs))),
+                    if args.is_empty() { PREC_ATOM } else { PREC_APP },
+                )
+            }
+
+            SExpr::Prefix(_, op, value) => {
+                let op = match op {
+                    PrefixOp::Not => "not ",
+                    PrefixOp::BitNot => "~",
+                };
+                (
+                    Doc::Concat(vec![Doc::text(op), self.sexpr(value, PREC_PREFIX)]),
+                    PREC_PREFIX,
+                )
+            }
+
+            SExpr::Infix(_, op, a, b) => {
+                // Operators are left associative, so only the right operand is parenthesised if
+                // it has the same precedence
+                let own = infix_prec(*op);
+                (
+                    Doc::group(Doc::Concat(vec![
+                        self.sexpr(a, own),
+                        Doc::Text(format!(" {}", op)),
+                        Doc::nest(4, Doc::Concat(vec![Doc::Line, self.sexpr(b, own + 1)])),
+                    ])),
+                    own,
+                )
+            }
+
+            SExpr::Chain(_, a, b) => (
+                Doc::Concat(vec![
+                    self.sexpr(a, PREC_EXPR),
+                    Doc::text(";"),
+                    Doc::Line,
+                    self.sexpr(b, PREC_CHAIN),
+                ]),
+                PREC_CHAIN,
+            ),
+
+            SExpr::As(m, value) => (
+                Doc::Concat(vec![
+                    self.sexpr(value, PREC_ATOM),
+                    Doc::Text(format!(" as {}", self._type(&m._type))),
+                ]),
+                PREC_APP,
+            ),
+
+            SExpr::Ascribe(m, value) => (
+                Doc::Concat(vec![
+                    Doc::text("("),
+                    self.sexpr(value, PREC_EXPR),
+                    Doc::Text(format!(" : {})", self._type(&m._type))),
+                ]),
+                PREC_ATOM,
+            ),
+
+            SExpr::Application(_, func, args) => {
+                // Applications are left associative so applied applications need no parentheses
+                let mut docs = vec![if let SExpr::Application(_, _, _) = **func {
+                    self.sexpr(func, PREC_APP)
+                } else {
+                    self.sexpr(func, PREC_ATOM)
+                }];
+                for arg in args.iter() {
+                    docs.push(Doc::Line);
+                    docs.push(self.sexpr(arg, PREC_ATOM));
+                }
+                (Doc::group(Doc::nest(4, Doc::Concat(docs))), PREC_APP)
+            }
+
+            SExpr::Assign(_, name, value) => (self.assign(name, value), PREC_CHAIN),
+
+            SExpr::With(_, assigns, body) => {
+                let mut docs = vec![Doc::text("let")];
+                for assign in assigns.iter() {
+                    docs.push(Doc::HardLine);
+                    docs.push(self.sexpr(assign, PREC_CHAIN));
+                }
+                (
+                    Doc::Concat(vec![
+                        Doc::nest(4, Doc::Concat(docs)),
+                        Doc::HardLine,
+                        Doc::text("in "),
+                        self.sexpr(body, PREC_CHAIN),
+                    ]),
+                    PREC_EXPR,
+                )
+            }
+
+            SExpr::Match(_, value, arms) => {
+                let mut docs = vec![Doc::text("match "), self.sexpr(value, PREC_APP)];
+                for (pattern, arm, _) in arms.iter() {
+                    docs.push(Doc::Line);
+                    docs.push(Doc::Text(format!("to {} => ", pattern)));
+
+                    // Arms are printed as their bodies rather than as the functions they become
+                    let body = match arm {
+                        SExpr::Function(_, f) => self.module.and_then(|m| m.function(f)),
+                        _ => None,
+                    };
+                    match body {
+                        Some(func) => docs.push(self.sexpr(&func.body, PREC_APP)),
+                        None => docs.push(self.sexpr(arm, PREC_APP)),
+                    }
+                }
+                (Doc::group(Doc::nest(4, Doc::Concat(docs))), PREC_EXPR)
+            }
+        };
+
+        if own < prec {
+            Doc::parens(doc)
+        } else {
+            doc
+        }
+    }
+
+    // assign(&mut self, &str, &SExpr) -> Doc
+    // Creates the document for an assignment, printing function arguments on the left hand side.
+    fn assign(&mut self, name: &str, value: &SExpr) -> Doc {
+        if let SExpr::Function(_, f) = value {
+            if let Some(func) = self.module.and_then(|m| m.function(f)) {
+                if func.global {
+                    return self.function(name, func);
+                }
+            }
+        }
+
+        let value = self.sexpr(value, PREC_CHAIN);
+        Doc::group(Doc::Concat(vec![
+            Doc::Text(format!("{} =", name)),
+            Doc::nest(4, Doc::Concat(vec![Doc::Line, value])),
+        ]))
+    }
+
+    // function(&mut self, &str, &IrFunction) -> Doc
+    // Creates the document for a top level function definition.
+    fn function(&mut self, name: &str, func: &IrFunction) -> Doc {
+        let lhs = if func.args.is_empty() {
+            format!("{} =", name)
+        } else {
+            format!("{} {} =", name, self.args(func))
+        };
+        let body = self.sexpr(&func.body, PREC_CHAIN);
+        Doc::group(Doc::Concat(vec![
+            Doc::Text(lhs),
+            Doc::nest(4, Doc::Concat(vec![Doc::Line, body])),
+        ]))
+    }
+}
+
+// type_to_string(&Type) -> String
+// Prints a type in the syntax it would be written in, with minimal parentheses.
+pub fn type_to_string(_type: &Type) -> String {
+    Printer::new(None)._type(_type)
+}
+
+// types_to_strings(&[&Type]) -> Vec<String>
+// Prints several types that are shown together, so that distinct generics sharing a name are given
+// distinct names across all of them.
+pub fn types_to_strings(types: &[&Type]) -> Vec<String> {
+    let mut printer = Printer::new(None);
+    types.iter().map(|t| printer._type(t)).collect()
+}
+
+// sexpr_to_string(&SExpr, Option<&IrModule>, usize) -> String
+// Prints an sexpression in the syntax it would be written in, breaking lines so that the output
+// fits within the given width where possible. If a module is given, lambdas are printed inline.
+pub fn sexpr_to_string(sexpr: &SExpr, module: Option<&IrModule>, width: usize) -> String {
+    let doc = Printer::new(module).sexpr(sexpr, PREC_CHAIN);
+    layout(&doc, width)
+}
+
+// function_to_string(&str, &IrFunction, &IrModule, usize) -> String
+// Prints a top level function definition in the syntax it would be written in.
+pub fn function_to_string(
+    name: &str,
+    func: &IrFunction,
+    module: &IrModule,
+    width: usize,
+) -> String {
+    let doc = Printer::new(Some(module)).function(name, func);
+    layout(&doc, width)
+}
+
+// signature_to_string(&str, &Type) -> String
+// Prints the signature of a value as `name : type`.
+pub fn signature_to_string(name: &str, _type: &Type) -> String {
+    format!("{} : {}", name, type_to_string(_type))
+}
@@ -3,25 +3,70 @@ use std::collections::HashMap;
 use super::ir::{ArityInfo, Location};
 use super::types::TypeRc;
 
-#[derive(Debug, Default)]
+// Represents a variable in a scope: its type, arity, definition location, whether it has been
+// assigned, and the module it originates from.
+pub type ScopeEntry = (TypeRc, ArityInfo, Location, bool, String);
+
+// Represents a variable bound in the environment.
+#[derive(Debug)]
+struct Binding {
+    name: String,
+    entry: ScopeEntry,
+
+    // The index of the scope the variable is bound in
+    frame: usize,
+
+    // The index of the binding of the same name this binding shadows, if any
+    shadowed: Option<usize>,
+}
+
+// Represents a scope pushed onto the environment.
+#[derive(Debug)]
+struct Frame {
+    // The index of the first binding of the scope
+    start: usize,
+
+    // The index of the innermost scope up to and including this one that starts a new function
+    func: usize,
+}
+
+// Represents the environment of variables, as a stack of bindings and the scopes they are in.
+// Every variable name maps to the index of its innermost binding, so looking a variable up takes
+// constant time however deeply scopes are nested. Bindings are never overwritten, only shadowed,
+// so that popping a scope or restoring a snapshot only has to remove the newest bindings.
+#[derive(Debug)]
 pub struct Scope {
-    pub variables: HashMap<String, (TypeRc, ArityInfo, Location, bool, String)>,
-    pub parent: Option<Box<Scope>>,
-    new_func: bool,
+    bindings: Vec<Binding>,
+    names: HashMap<String, usize>,
+    frames: Vec<Frame>,
+}
+
+// Represents the state of an environment, which can be restored after bindings and scopes are
+// added to it.
+#[derive(Debug, Clone, Copy)]
+pub struct ScopeSnapshot {
+    bindings: usize,
+    frames: usize,
+}
+
+impl Default for Scope {
+    fn default() -> Scope {
+        Scope::new()
+    }
 }
 
 impl Scope {
     // new() -> Scope
-    // Creates a new empty scope.
+    // Creates a new environment with an empty global scope.
     pub fn new() -> Scope {
         Scope {
-            variables: HashMap::with_capacity(0),
-            parent: None,
-            new_func: false,
+            bindings: vec![],
+            names: HashMap::new(),
+            frames: vec![Frame { start: 0, func: 0 }],
         }
     }
 
-    // put_var_raw(&mut self, String, TypeRc, ArityInfo, Span, bool) -> ()
+    // put_var_raw(&mut self, String, TypeRc, ArityInfo, Location, bool, String) -> ()
     // Puts a variable in the current scope.
     pub fn put_var_raw(
         &mut self,
@@ -32,11 +77,17 @@ impl Scope {
         assigned: bool,
         origin: String,
     ) {
-        self.variables
-            .insert(name, (_type, arity, loc, assigned, origin));
+        let index = self.bindings.len();
+        let shadowed = self.names.insert(name.clone(), index);
+        self.bindings.push(Binding {
+            name,
+            entry: (_type, arity, loc, assigned, origin),
+            frame: self.frames.len() - 1,
+            shadowed,
+        });
     }
 
-    // put_var(&mut self, &str, ArityInfo, Span, bool) -> ()
+    // put_var(&mut self, &str, &TypeRc, ArityInfo, &Location, bool, &str) -> ()
     // Puts a variable in the current scope.
     pub fn put_var(
         &mut self,
@@ -47,94 +98,216 @@ impl Scope {
         assigned: bool,
         origin: &str,
     ) {
-        self.variables.insert(
+        self.put_var_raw(
             String::from(name),
-            (
-                _type.clone(),
-                arity,
-                loc.clone(),
-                assigned,
-                String::from(origin),
-            ),
+            _type.clone(),
+            arity,
+            loc.clone(),
+            assigned,
+            String::from(origin),
         );
     }
 
-    // get_var(&self, &str) -> Option<&(Type, ArityInfo, Location, bool, String)>
-    // Gets a variable from the stack of scopes.
-    pub fn get_var(&self, name: &str) -> Option<&(TypeRc, ArityInfo, Location, bool, String)> {
-        // Set up
-        let name = String::from(name);
-        let mut scope = self;
-
-        loop {
-            // Return success if found
-            if let Some(v) = scope.variables.get(&name) {
-                return Some(v);
-            }
-
-            // Get next scope
-            scope = match &scope.parent {
-                Some(v) => &**v,
-                None => break None,
-            }
+    // get_var(&self, &str) -> Option<&ScopeEntry>
+    // Gets the innermost variable with the given name.
+    pub fn get_var(&self, name: &str) -> Option<&ScopeEntry> {
+        self.names.get(name).map(|&i| &self.bindings[i].entry)
+    }
+
+    // resolve(&self, &str) -> Option<&Location>
+    // Returns the location a variable visible from the current scope was defined at.
+    pub fn resolve(&self, name: &str) -> Option<&Location> {
+        self.get_var(name).map(|v| &v.2)
+    }
+
+    // iter(&self) -> ScopeIter
+    // Returns an iterator over the current scope and the scopes it is in, from innermost to
+    // outermost.
+    pub fn iter(&self) -> ScopeIter<'_> {
+        ScopeIter {
+            scope: self,
+            next: Some(self.frames.len() - 1),
         }
     }
 
+    // depth(&self) -> usize
+    // Returns the number of scopes pushed onto the global scope.
+    pub fn depth(&self) -> usize {
+        self.frames.len() - 1
+    }
+
+    // scope_of(&self, &str) -> Option<usize>
+    // Returns the depth of the scope the innermost variable with the given name is bound in, where
+    // the global scope is 0.
+    pub fn scope_of(&self, name: &str) -> Option<usize> {
+        self.names.get(name).map(|&i| self.bindings[i].frame)
+    }
+
+    // visible_bindings(&self) -> Vec<(&str, &ScopeEntry)>
+    // Returns every variable visible from the current scope sorted by name, with variables in inner
+    // scopes shadowing variables of the same name in outer scopes.
+    pub fn visible_bindings(&self) -> Vec<(&str, &ScopeEntry)> {
+        let mut bindings: Vec<_> = self
+            .names
+            .iter()
+            .map(|(name, &i)| (name.as_str(), &self.bindings[i].entry))
+            .collect();
+        bindings.sort_by(|a, b| a.0.cmp(b.0));
+        bindings
+    }
+
+    // visible_bindings_at(&self, &Location) -> Vec<(&str, &ScopeEntry)>
+    // Returns every variable visible from the current scope that is defined before the given
+    // location. Variables without a location (ie, arguments) and variables from other files are
+    // always visible.
+    pub fn visible_bindings_at(&self, loc: &Location) -> Vec<(&str, &ScopeEntry)> {
+        self.visible_bindings()
+            .into_iter()
+            .filter(|(_, entry)| {
+                let def = &entry.2;
+                def.filename != loc.filename
+                    || def.span.start == def.span.end
+                    || def.span.start <= loc.span.start
+            })
+            .collect()
+    }
+
     // push_scope(&mut self, bool) -> ()
     // Pushes a new scope to the top of the scope stack.
     pub fn push_scope(&mut self, new_func: bool) {
-        use std::mem::swap;
-
-        let mut scope = Scope::new();
-        scope.new_func = new_func;
+        let func = if new_func {
+            self.frames.len()
+        } else {
+            self.frames.last().unwrap().func
+        };
 
-        swap(&mut scope, self);
-        self.parent = Some(Box::new(scope));
+        self.frames.push(Frame {
+            start: self.bindings.len(),
+            func,
+        });
     }
 
-    // pop_scop(&mut self) -> ()
-    // Pops a scope from the stack if a parent scope exists.
+    // pop_scope(&mut self) -> ()
+    // Pops a scope from the stack if it is not the global scope.
     pub fn pop_scope(&mut self) {
-        use std::mem::swap;
+        if self.frames.len() > 1 {
+            let frame = self.frames.pop().unwrap();
+            self.truncate(frame.start);
+        }
+    }
 
-        if let Some(v) = &mut self.parent {
-            let mut scope = Scope::new();
+    // snapshot(&self) -> ScopeSnapshot
+    // Records the bindings and scopes of the environment, so that the ones added after this can be
+    // removed with restore.
+    pub fn snapshot(&self) -> ScopeSnapshot {
+        ScopeSnapshot {
+            bindings: self.bindings.len(),
+            frames: self.frames.len(),
+        }
+    }
 
-            swap(&mut scope, v);
-            swap(self, &mut scope);
+    // restore(&mut self, ScopeSnapshot) -> ()
+    // Removes the bindings and scopes added since a snapshot was taken, and binds the variables
+    // they shadowed again.
+    pub fn restore(&mut self, snapshot: ScopeSnapshot) {
+        self.frames.truncate(snapshot.frames);
+        self.truncate(snapshot.bindings);
+    }
+
+    // truncate(&mut self, usize) -> ()
+    // Removes the bindings from the given index onwards, newest first.
+    fn truncate(&mut self, len: usize) {
+        while self.bindings.len() > len {
+            let binding = self.bindings.pop().unwrap();
+            match binding.shadowed {
+                Some(i) => self.names.insert(binding.name, i),
+                None => self.names.remove(&binding.name),
+            };
         }
     }
 
     // is_captured(&self, &str) -> bool
     // Returns true if captured from a new function
     pub fn is_captured(&self, name: &str) -> bool {
-        // Set up
-        let mut scope = self;
-        let mut last_new_func = false;
-        let mut new_func;
-
-        loop {
-            // Global scope is not captured
-            if scope.parent.is_none() {
-                break false;
-            }
-
-            // Update new_func if in a new function
-            new_func = last_new_func;
-            if scope.new_func {
-                last_new_func = true;
-            }
-
-            // Return success if found
-            if scope.variables.get(name).is_some() {
-                break new_func;
-            }
-
-            // Get next scope
-            scope = match &scope.parent {
-                Some(v) => &**v,
-                None => break false,
-            }
+        // Global scope is not captured
+        match self.scope_of(name) {
+            Some(frame) => frame != 0 && self.frames.last().unwrap().func > frame,
+            None => false,
+        }
+    }
+}
+
+// Represents one scope of an environment, which holds the variables bound directly in it.
+#[derive(Debug, Clone, Copy)]
+pub struct ScopeFrame<'a> {
+    scope: &'a Scope,
+    index: usize,
+}
+
+impl<'a> ScopeFrame<'a> {
+    // depth(&self) -> usize
+    // Returns the depth of the scope, where the global scope is 0.
+    pub fn depth(&self) -> usize {
+        self.index
+    }
+
+    // is_function(&self) -> bool
+    // Returns true if the scope starts a new function, whose variables are captured by closures.
+    pub fn is_function(&self) -> bool {
+        self.index != 0 && self.scope.frames[self.index].func == self.index
+    }
+
+    // bindings(&self) -> &[Binding]
+    // Returns the bindings made in the scope, oldest first.
+    fn bindings(&self) -> &'a [Binding] {
+        let start = self.scope.frames[self.index].start;
+        let end = match self.scope.frames.get(self.index + 1) {
+            Some(frame) => frame.start,
+            None => self.scope.bindings.len(),
+        };
+        &self.scope.bindings[start..end]
+    }
+
+    // get_var(&self, &str) -> Option<&ScopeEntry>
+    // Gets the variable with the given name bound in this scope, ignoring other scopes.
+    pub fn get_var(&self, name: &str) -> Option<&'a ScopeEntry> {
+        self.bindings()
+            .iter()
+            .rev()
+            .find(|v| v.name == name)
+            .map(|v| &v.entry)
+    }
+
+    // variables(&self) -> Vec<(&str, &ScopeEntry)>
+    // Returns the variables bound in this scope sorted by name. A variable bound more than once in
+    // the scope is only returned once, with its newest entry.
+    pub fn variables(&self) -> Vec<(&'a str, &'a ScopeEntry)> {
+        let mut variables: HashMap<&str, &ScopeEntry> = HashMap::new();
+        for binding in self.bindings() {
+            variables.insert(binding.name.as_str(), &binding.entry);
         }
+
+        let mut variables: Vec<_> = variables.into_iter().collect();
+        variables.sort_by(|a, b| a.0.cmp(b.0));
+        variables
+    }
+}
+
+// Represents an iterator over the scopes of an environment, from innermost to outermost.
+pub struct ScopeIter<'a> {
+    scope: &'a Scope,
+    next: Option<usize>,
+}
+
+impl<'a> Iterator for ScopeIter<'a> {
+    type Item = ScopeFrame<'a>;
+
+    fn next(&mut self) -> Option<ScopeFrame<'a>> {
+        let index = self.next?;
+        self.next = index.checked_sub(1);
+        Some(ScopeFrame {
+            scope: self.scope,
+            index,
+        })
     }
 }
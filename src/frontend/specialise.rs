@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use tracing::{debug, info_span};
+
+use super::ir::{ArityInfo, Ir, IrFunction, IrModule, SExpr, SExprMetadata};
+use super::types::{arc, Type, TypeRc};
+
+// The copies of functions specialised on known closures, by the name of the function, the index of
+// the argument, the name of the known function, and the number of values applied to it.
+type Copies = HashMap<(String, usize, String, usize), String>;
+
+// A partial application of a known function passed as an argument to a known function, which is
+// only ever called there and so never needs to exist as a closure.
+struct KnownClosure {
+    // The index of the argument the partial application is passed as
+    index: usize,
+
+    // The applied function and its metadata
+    func: String,
+    meta: SExprMetadata,
+
+    // The values applied to the function so far
+    applied: Vec<SExpr>,
+
+    // The number of arguments the function still takes
+    remaining: usize,
+}
+
+// only_called(&SExpr, &str, &IrModule, &mut usize) -> bool
+// Returns true if an argument is only ever applied in an s expression, and is never passed along,
+// returned, captured by a lambda or match arm, or shadowed. The largest number of values it is
+// applied to at once is stored in `most`.
+fn only_called(sexpr: &SExpr, arg: &str, module: &IrModule, most: &mut usize) -> bool {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _) => true,
+
+        SExpr::Symbol(_, s) => s != arg,
+
+        SExpr::Function(_, f) => {
+            !matches!(module.funcs.get(f), Some(f) if f.captured.contains_key(arg))
+        }
+
+        SExpr::ExternalFunc(_, _, args) => args.iter().all(|v| only_called(v, arg, module, most)),
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            only_called(a, arg, module, most) && only_called(b, arg, module, most)
+        }
+
+        SExpr::Application(_, func, args) => {
+            let callee = match &**func {
+                SExpr::Symbol(_, s) if s == arg => {
+                    *most = (*most).max(args.len());
+                    true
+                }
+                _ => only_called(func, arg, module, most),
+            };
+            callee && args.iter().all(|v| only_called(v, arg, module, most))
+        }
+
+        SExpr::Assign(_, name, v) => name != arg && only_called(v, arg, module, most),
+
+        SExpr::As(_, v) | SExpr::Ascribe(_, v) | SExpr::Prefix(_, _, v) => {
+            only_called(v, arg, module, most)
+        }
+
+        SExpr::With(_, assigns, v) => {
+            assigns.iter().all(|v| only_called(v, arg, module, most))
+                && only_called(v, arg, module, most)
+        }
+
+        SExpr::Match(_, v, arms) => {
+            only_called(v, arg, module, most)
+                && arms
+                    .iter()
+                    .all(|(_, arm, _)| only_called(arm, arg, module, most))
+        }
+    }
+}
+
+// known_closure(&[SExpr], &IrFunction, &IrModule) -> Option<KnownClosure>
+// Returns the first argument of a saturated application of a known function that is a known
+// function or a partial application of one, if the function only ever calls the argument with at
+// most as many values as the partially applied function still takes. Functions with closed over
+// values are left alone, since their values are only visible where the function is defined.
+fn known_closure(args: &[SExpr], func: &IrFunction, module: &IrModule) -> Option<KnownClosure> {
+    if !func.captured.is_empty() || args.len() != func.args.len() {
+        return None;
+    }
+
+    args.iter().enumerate().find_map(|(index, arg)| {
+        let (meta, g, applied, remaining) = match arg {
+            SExpr::Function(meta, g) => (meta, g, &[][..], module.funcs.get(g)?.args.len()),
+            SExpr::Application(m, f, applied) => match (&**f, &m.arity) {
+                (SExpr::Function(meta, g), ArityInfo::Known(n)) => {
+                    (meta, g, applied.as_slice(), *n)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let g_func = module.funcs.get(g)?;
+        if !g_func.captured.is_empty()
+            || remaining == 0
+            || applied.len() + remaining != g_func.args.len()
+        {
+            return None;
+        }
+
+        let mut most = 0;
+        if only_called(&func.body, &func.args[index].0, module, &mut most) && most <= remaining {
+            Some(KnownClosure {
+                index,
+                func: g.clone(),
+                meta: meta.clone(),
+                applied: applied.to_vec(),
+                remaining,
+            })
+        } else {
+            None
+        }
+    })
+}
+
+// call_known_closure(&mut SExpr, &str, &KnownClosure, &[(String, TypeRc)], bool) -> ()
+// Replaces the applications of an argument in an s expression with applications of the known
+// function it is bound to, passing the given arguments for the values applied to it first.
+fn call_known_closure(
+    sexpr: &mut SExpr,
+    arg: &str,
+    known: &KnownClosure,
+    params: &[(String, TypeRc)],
+    impure: bool,
+) {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _)
+        | SExpr::Function(_, _) => (),
+
+        SExpr::ExternalFunc(_, _, args) => {
+            for v in args {
+                call_known_closure(v, arg, known, params, impure);
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            call_known_closure(a, arg, known, params, impure);
+            call_known_closure(b, arg, known, params, impure);
+        }
+
+        SExpr::Application(m, func, args) => {
+            for v in args.iter_mut() {
+                call_known_closure(v, arg, known, params, impure);
+            }
+
+            if matches!(&**func, SExpr::Symbol(_, s) if s == arg) {
+                let mut flat: Vec<_> = params
+                    .iter()
+                    .map(|(name, _type)| {
+                        let meta = SExprMetadata {
+                            _type: _type.clone(),
+                            ..SExprMetadata::empty()
+                        };
+                        SExpr::Symbol(meta, name.clone())
+                    })
+                    .collect();
+                flat.append(args);
+
+                m.arity = ArityInfo::Known(known.remaining - (flat.len() - params.len()));
+                m.impure |= impure;
+                **func = SExpr::Function(known.meta.clone(), known.func.clone());
+                *args = flat;
+            } else {
+                call_known_closure(func, arg, known, params, impure);
+            }
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => call_known_closure(v, arg, known, params, impure),
+
+        SExpr::With(_, assigns, v) => {
+            for assign in assigns {
+                call_known_closure(assign, arg, known, params, impure);
+            }
+            call_known_closure(v, arg, known, params, impure);
+        }
+
+        SExpr::Match(_, v, _) => call_known_closure(v, arg, known, params, impure),
+    }
+}
+
+// specialise_function(&mut IrModule, &str, &KnownClosure) -> String
+// Creates a copy of a function that calls a known function directly wherever the original calls
+// the argument the known closure is passed as. The values applied to the known function are taken
+// as arguments in place of that argument. Returns the name of the copy.
+fn specialise_function(module: &mut IrModule, name: &str, known: &KnownClosure) -> String {
+    let func = &module.funcs[name];
+    let arg = &func.args[known.index].0;
+    let params: Vec<_> = known
+        .applied
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (format!("{}.{}", arg, i), v.get_metadata()._type.clone()))
+        .collect();
+    let impure = module.funcs[&known.func].impure;
+
+    let mut body = func.body.clone();
+    call_known_closure(&mut body, arg, known, &params, impure);
+
+    let mut args = func.args.clone();
+    args.splice(known.index..known.index + 1, params);
+    let arg_types: Vec<_> = args.iter().map(|v| v.1.clone()).collect();
+
+    let mut specialised = format!("{}.{}", name, known.func);
+    let mut n = 0;
+    while module.funcs.contains_key(&specialised) {
+        specialised = format!("{}.{}.{}", name, known.func, n);
+        n += 1;
+    }
+
+    debug!(from = %name, to = %specialised, closure = %known.func, "specialised");
+    let func = IrFunction {
+        loc: func.loc.clone(),
+        name: specialised.clone(),
+        _type: arc::new(Type::function(&arg_types, func.ret_type())),
+        args,
+        captured: HashMap::new(),
+        captured_names: vec![],
+        body,
+        global: false,
+        checked: func.checked,
+        written: func.written,
+        impure: func.impure || impure,
+    };
+    module.funcs.insert(specialised.clone(), func);
+    specialised
+}
+
+// specialise_application(&mut SExpr, &mut IrModule, &mut Copies) -> bool
+// Rewrites a saturated application of a known function that is passed a known closure into an
+// application of a copy of the function specialised on the closure, passing the values applied to
+// the closure instead of the closure itself. Copies are shared by the applications that pass the
+// same function with the same number of values applied. Returns true if the application was
+// rewritten.
+fn specialise_application(sexpr: &mut SExpr, module: &mut IrModule, copies: &mut Copies) -> bool {
+    let (m, func, args) = match sexpr {
+        SExpr::Application(m, func, args) => (m, func, args),
+        _ => return false,
+    };
+    let name = match &**func {
+        SExpr::Function(_, f) if matches!(m.arity, ArityInfo::Known(0)) => f.clone(),
+        _ => return false,
+    };
+
+    let known = match module
+        .funcs
+        .get(&name)
+        .and_then(|f| known_closure(args, f, module))
+    {
+        Some(v) => v,
+        None => return false,
+    };
+
+    let key = (
+        name.clone(),
+        known.index,
+        known.func.clone(),
+        known.applied.len(),
+    );
+    let specialised = match copies.get(&key) {
+        Some(v) => v.clone(),
+        None => {
+            let v = specialise_function(module, &name, &known);
+            copies.insert(key, v.clone());
+            v
+        }
+    };
+
+    let f = &module.funcs[&specialised];
+    let fm = func.get_mutable_metadata();
+    fm._type = f._type.clone();
+    fm.arity = ArityInfo::Known(f.args.len());
+    **func = SExpr::Function(fm.clone(), specialised);
+    args.splice(known.index..known.index + 1, known.applied);
+    true
+}
+
+// specialise_sexpr(&mut SExpr, &mut IrModule, &mut Copies) -> ()
+// Specialises the applications in an s expression, innermost first. The arms of match expressions
+// are functions, so they are specialised along with the other functions of the module.
+fn specialise_sexpr(sexpr: &mut SExpr, module: &mut IrModule, copies: &mut Copies) {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _)
+        | SExpr::Function(_, _) => (),
+
+        SExpr::ExternalFunc(_, _, args) => {
+            for arg in args {
+                specialise_sexpr(arg, module, copies);
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            specialise_sexpr(a, module, copies);
+            specialise_sexpr(b, module, copies);
+        }
+
+        SExpr::Application(_, func, args) => {
+            specialise_sexpr(func, module, copies);
+            for arg in args {
+                specialise_sexpr(arg, module, copies);
+            }
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => specialise_sexpr(v, module, copies),
+
+        SExpr::With(_, assigns, v) => {
+            for assign in assigns {
+                specialise_sexpr(assign, module, copies);
+            }
+            specialise_sexpr(v, module, copies);
+        }
+
+        SExpr::Match(_, v, _) => specialise_sexpr(v, module, copies),
+    }
+
+    // Each rewrite takes one known closure out of the arguments, and the values applied to it are
+    // never known closures of the copy, since the copy passes them to the known function
+    let mut count = sexpr_args(sexpr);
+    while count > 0 && specialise_application(sexpr, module, copies) {
+        count -= 1;
+    }
+}
+
+// sexpr_args(&SExpr) -> usize
+// Returns the number of arguments an application passes, or 0 if the s expression is not an
+// application.
+fn sexpr_args(sexpr: &SExpr) -> usize {
+    match sexpr {
+        SExpr::Application(_, _, args) => args.len(),
+        _ => 0,
+    }
+}
+
+// specialise_module(&mut IrModule) -> ()
+// Rewrites the saturated applications of known functions in every function of a module that pass
+// a known function or a partial application of one as an argument the function only ever calls.
+// Each becomes an application of a copy of the function that calls the known function directly,
+// so the closure is never allocated and its calls do not go through the runtime library. The
+// module must have been type checked and uncurried.
+pub fn specialise_module(module: &mut IrModule) {
+    let _span = info_span!("specialise_module", name = %module.name).entered();
+    let mut copies = HashMap::new();
+
+    // Copies are not specialised themselves, so that recursion cannot create copies forever. Bodies
+    // are cloned rather than taken out, since a function may pass a known closure to itself
+    let names: Vec<_> = module.funcs.keys().cloned().collect();
+    for name in names {
+        let mut body = module.funcs[&name].body.clone();
+        specialise_sexpr(&mut body, module, &mut copies);
+        module.funcs.get_mut(&name).unwrap().body = body;
+    }
+}
+
+// specialise_applications(&mut Ir) -> ()
+// Specialises the applications in every module.
+pub fn specialise_applications(ir: &mut Ir) {
+    for module in ir.modules.values_mut() {
+        specialise_module(module);
+    }
+}
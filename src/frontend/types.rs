@@ -8,6 +8,10 @@ use std::sync::Arc;
 use super::ir::Location;
 use super::parser::Ast;
 
+// The first uid given to generics created by instantiating a generic value. Generics written in
+// the source are rigid, but instantiated generics can be inferred from the types they are used as.
+pub const INSTANTIATED_UID_BASE: usize = usize::MAX / 2;
+
 #[derive(Clone, Debug)]
 pub struct HashSetWrapper<T>(pub HashSet<T>);
 
@@ -41,14 +45,20 @@ pub enum Type {
     DuplicateTypeError(Location, Location, TypeRc),
     Unknown,
     Int,
+    SizedInt(IntSize),
     Float,
     Bool,
-    Word,
     Char,
     Symbol(String),
     Generic(String, usize),
     Func(TypeRc, TypeRc),
     Union(HashSetWrapper<TypeRc>),
+    Array(TypeRc),
+    Map(TypeRc, TypeRc),
+    Thread(TypeRc),
+    Task(TypeRc),
+    Result(TypeRc),
+    Option(TypeRc),
 }
 
 impl Display for Type {
@@ -78,8 +88,8 @@ impl Display for Type {
             Type::Bool => {
                 write!(f, "Bool")?;
             }
-            Type::Word => {
-                write!(f, "Word")?;
+            Type::SizedInt(size) => {
+                write!(f, "{}", size.name())?;
             }
             Type::Char => {
                 write!(f, "Char")?;
@@ -101,17 +111,74 @@ impl Display for Type {
                 write!(f, " -> {}", ret)?;
             }
 
+            // Array types
+            Type::Array(item) => {
+                if item.needs_parentheses() {
+                    write!(f, "Array ({})", item)?;
+                } else {
+                    write!(f, "Array {}", item)?;
+                }
+            }
+
+            // Thread types
+            Type::Thread(result) => {
+                if result.needs_parentheses() {
+                    write!(f, "Thread ({})", result)?;
+                } else {
+                    write!(f, "Thread {}", result)?;
+                }
+            }
+
+            // Task types
+            Type::Task(result) => {
+                if result.needs_parentheses() {
+                    write!(f, "Task ({})", result)?;
+                } else {
+                    write!(f, "Task {}", result)?;
+                }
+            }
+
+            // Result types
+            Type::Result(value) => {
+                if value.needs_parentheses() {
+                    write!(f, "Result ({})", value)?;
+                } else {
+                    write!(f, "Result {}", value)?;
+                }
+            }
+
+            // Option types
+            Type::Option(value) => {
+                if value.needs_parentheses() {
+                    write!(f, "Option ({})", value)?;
+                } else {
+                    write!(f, "Option {}", value)?;
+                }
+            }
+
+            // Map types
+            Type::Map(key, value) => {
+                write!(f, "Map")?;
+                for t in [key, value] {
+                    if t.needs_parentheses() {
+                        write!(f, " ({})", t)?;
+                    } else {
+                        write!(f, " {}", t)?;
+                    }
+                }
+            }
+
             // Union types
-            Type::Union(fields) => {
+            Type::Union(_) => {
                 let mut bar = false;
-                for field in fields.0.iter() {
+                for field in self.as_union().unwrap() {
                     if bar {
                         write!(f, " | ")?;
                     } else {
                         bar = true;
                     }
 
-                    if let Type::Func(_, _) = **field {
+                    if let Type::Func(_, _) = *field {
                         write!(f, "({})", field)?;
                     } else {
                         write!(f, "{}", field)?;
@@ -129,7 +196,269 @@ pub struct GenericPair {
     uid: usize,
 }
 
+impl GenericPair {
+    // new(&str, usize) -> GenericPair
+    // Creates the key for the generic with the given name and uid.
+    pub fn new(generic: &str, uid: usize) -> GenericPair {
+        GenericPair {
+            generic: String::from(generic),
+            uid,
+        }
+    }
+}
+
+// Represents the signedness and width of a sized int type. `Int` is the signed 64 bit int, and the
+// sized types are the other widths, along with the unsigned 64 bit int. Values of every int type
+// are stored in 64 bits, sign extended or zero extended from their width.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct IntSize {
+    pub signed: bool,
+    pub bits: u32,
+}
+
+impl IntSize {
+    // The sized int types, in the order they are listed in errors.
+    pub const ALL: [IntSize; 7] = [
+        IntSize::new(true, 8),
+        IntSize::new(true, 16),
+        IntSize::new(true, 32),
+        IntSize::new(false, 8),
+        IntSize::new(false, 16),
+        IntSize::new(false, 32),
+        IntSize::new(false, 64),
+    ];
+
+    // new(bool, u32) -> IntSize
+    // Creates the size of an int type with the given signedness and width.
+    pub const fn new(signed: bool, bits: u32) -> IntSize {
+        IntSize { signed, bits }
+    }
+
+    // of(&Type) -> Option<IntSize>
+    // Returns the size of an int type, or None if the type is not an int. `Int` is a signed 64 bit
+    // int.
+    pub fn of(_type: &Type) -> Option<IntSize> {
+        match _type {
+            Type::Int => Some(IntSize::new(true, 64)),
+            Type::SizedInt(size) => Some(*size),
+            _ => None,
+        }
+    }
+
+    // from_name(&str) -> Option<IntSize>
+    // Returns the size of the sized int type with the given name, such as `Int8` or `UInt64`.
+    pub fn from_name(name: &str) -> Option<IntSize> {
+        IntSize::ALL.iter().copied().find(|v| v.name() == name)
+    }
+
+    // from_suffix(&str) -> Option<IntSize>
+    // Returns the size of int literals with the given suffix, such as `i8` or `u64`. The suffix
+    // `i64` is the suffix of `Int`.
+    pub fn from_suffix(suffix: &str) -> Option<IntSize> {
+        let signed = match suffix.chars().next()? {
+            'i' => true,
+            'u' => false,
+            _ => return None,
+        };
+        let size = IntSize::new(signed, suffix[1..].parse().ok()?);
+        if IntSize::ALL.contains(&size) || size == IntSize::new(true, 64) {
+            Some(size)
+        } else {
+            None
+        }
+    }
+
+    // into_type(self) -> Type
+    // Returns the int type of the size.
+    pub fn into_type(self) -> Type {
+        if self == IntSize::new(true, 64) {
+            Type::Int
+        } else {
+            Type::SizedInt(self)
+        }
+    }
+
+    // name(&self) -> &'static str
+    // Returns the name of the type of the size.
+    pub fn name(&self) -> &'static str {
+        match (self.signed, self.bits) {
+            (true, 8) => "Int8",
+            (true, 16) => "Int16",
+            (true, 32) => "Int32",
+            (true, _) => "Int",
+            (false, 8) => "UInt8",
+            (false, 16) => "UInt16",
+            (false, 32) => "UInt32",
+            (false, _) => "UInt64",
+        }
+    }
+
+    // suffix(&self) -> String
+    // Returns the suffix of int literals of the type.
+    pub fn suffix(&self) -> String {
+        format!("{}{}", if self.signed { 'i' } else { 'u' }, self.bits)
+    }
+
+    // min(&self) -> i128
+    // Returns the smallest value of the type.
+    pub fn min(&self) -> i128 {
+        if self.signed {
+            -(1 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+
+    // max(&self) -> i128
+    // Returns the largest value of the type.
+    pub fn max(&self) -> i128 {
+        if self.signed {
+            (1 << (self.bits - 1)) - 1
+        } else {
+            (1 << self.bits) - 1
+        }
+    }
+
+    // fits(&self, i128) -> bool
+    // Returns true if a value is in the range of the type.
+    pub fn fits(&self, n: i128) -> bool {
+        self.min() <= n && n <= self.max()
+    }
+
+    // widens_to(&self, &IntSize) -> bool
+    // Returns true if every value of the type is also a value of the other type, so that values
+    // can be converted implicitly.
+    pub fn widens_to(&self, other: &IntSize) -> bool {
+        other.min() <= self.min() && self.max() <= other.max()
+    }
+
+    // wrap(&self, i128) -> i64
+    // Wraps a value around into the range of the type, returning it as it is stored in 64 bits.
+    pub fn wrap(&self, n: i128) -> i64 {
+        let shift = 128 - self.bits;
+        if self.signed {
+            ((n << shift) >> shift) as i64
+        } else {
+            (((n as u128) << shift) >> shift) as i64
+        }
+    }
+
+    // value(&self, i64) -> i128
+    // Returns the value a value of the type stored in 64 bits represents.
+    pub fn value(&self, n: i64) -> i128 {
+        if self.signed {
+            n as i128
+        } else {
+            n as u64 as i128
+        }
+    }
+}
+
 impl Type {
+    // function(&[TypeRc], TypeRc) -> Type
+    // Creates a curried function type taking the given arguments and returning the given type.
+    pub fn function(args: &[TypeRc], ret: TypeRc) -> Type {
+        let mut _type = ret;
+        for arg in args.iter().rev() {
+            _type = arc::new(Type::Func(arg.clone(), _type));
+        }
+        (*_type).clone()
+    }
+
+    // union<I: IntoIterator<Item = TypeRc>>(I) -> Type
+    // Creates a union type from a list of variants, flattening nested unions and removing
+    // duplicates. A union of a single variant is just that variant.
+    pub fn union<I: IntoIterator<Item = TypeRc>>(variants: I) -> Type {
+        let mut fields = HashSet::new();
+        for variant in variants {
+            if let Type::Union(v) = &*variant {
+                fields.extend(v.0.iter().cloned());
+            } else {
+                fields.insert(variant);
+            }
+        }
+
+        if fields.len() == 1 {
+            (*fields.into_iter().next().unwrap()).clone()
+        } else {
+            Type::Union(HashSetWrapper(fields))
+        }
+    }
+
+    // generic(&str, usize) -> Type
+    // Creates a generic type with the given name and unique id.
+    pub fn generic(name: &str, uid: usize) -> Type {
+        Type::Generic(String::from(name), uid)
+    }
+
+    // is_error(&self) -> bool
+    // Returns true if the type represents an error.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Type::Error | Type::UndeclaredTypeError(_) | Type::DuplicateTypeError(_, _, _)
+        )
+    }
+
+    // is_primitive(&self) -> bool
+    // Returns true if the type is a primitive type.
+    pub fn is_primitive(&self) -> bool {
+        matches!(
+            self,
+            Type::Int | Type::SizedInt(_) | Type::Float | Type::Bool | Type::Char
+        )
+    }
+
+    // needs_parentheses(&self) -> bool
+    // Returns true if the type needs parentheses when it is the argument of a type constructor.
+    pub fn needs_parentheses(&self) -> bool {
+        matches!(
+            self,
+            Type::Func(_, _)
+                | Type::Union(_)
+                | Type::Array(_)
+                | Type::Map(_, _)
+                | Type::Thread(_)
+                | Type::Task(_)
+                | Type::Result(_)
+                | Type::Option(_)
+        )
+    }
+
+    // is_generic(&self) -> bool
+    // Returns true if the type is a generic type.
+    pub fn is_generic(&self) -> bool {
+        matches!(self, Type::Generic(_, _))
+    }
+
+    // as_function(&self) -> Option<(Vec<TypeRc>, TypeRc)>
+    // Returns the list of argument types and the final return type if the type is a function.
+    pub fn as_function(&self) -> Option<(Vec<TypeRc>, TypeRc)> {
+        if let Type::Func(arg, ret) = self {
+            let mut args = vec![arg.clone()];
+            let mut ret = ret.clone();
+            while let Type::Func(a, r) = &*ret.clone() {
+                args.push(a.clone());
+                ret = r.clone();
+            }
+            Some((args, ret))
+        } else {
+            None
+        }
+    }
+
+    // as_union(&self) -> Option<Vec<TypeRc>>
+    // Returns the variants of the type in canonical order if the type is a union.
+    pub fn as_union(&self) -> Option<Vec<TypeRc>> {
+        if let Type::Union(fields) = self {
+            let mut variants: Vec<_> = fields.0.iter().cloned().collect();
+            variants.sort_by_cached_key(|v| (v.to_string(), v.sum_hash()));
+            Some(variants)
+        } else {
+            None
+        }
+    }
+
     // sum_hash(&self) -> u64
     // Returns the hash value used by codegenned sum/union types.
     pub fn sum_hash(&self) -> u64 {
@@ -150,12 +479,31 @@ impl Type {
             return true;
         }
 
+        // Instantiated generics are inferred from the supertype
+        if let Type::Generic(g, uid) = self {
+            if *uid >= INSTANTIATED_UID_BASE && !matches!(supertype, Type::Generic(_, _)) {
+                let generic_pair = GenericPair {
+                    generic: g.clone(),
+                    uid: *uid,
+                };
+
+                return if let Some(t) = generics_map.get(&generic_pair).cloned() {
+                    t.is_subtype(supertype, types, generics_map)
+                } else if !supertype.contains_generic(&generic_pair) {
+                    generics_map.insert(generic_pair, arc::new(supertype.clone()));
+                    true
+                } else {
+                    false
+                };
+            }
+        }
+
         match supertype {
             // Primitives
             Type::Int => *self == Type::Int,
             Type::Float => *self == Type::Float,
             Type::Bool => *self == Type::Bool,
-            Type::Word => *self == Type::Word,
+            Type::SizedInt(size) => *self == Type::SizedInt(*size),
             Type::Char => *self == Type::Char,
 
             // Functions
@@ -167,6 +515,60 @@ impl Type {
                 }
             }
 
+            // Arrays
+            Type::Array(si) => {
+                if let Type::Array(i) = self {
+                    i.is_subtype(si, types, generics_map)
+                } else {
+                    false
+                }
+            }
+
+            // Threads
+            Type::Thread(sr) => {
+                if let Type::Thread(r) = self {
+                    r.is_subtype(sr, types, generics_map)
+                } else {
+                    false
+                }
+            }
+
+            // Tasks
+            Type::Task(sr) => {
+                if let Type::Task(r) = self {
+                    r.is_subtype(sr, types, generics_map)
+                } else {
+                    false
+                }
+            }
+
+            // Results
+            Type::Result(sv) => {
+                if let Type::Result(v) = self {
+                    v.is_subtype(sv, types, generics_map)
+                } else {
+                    false
+                }
+            }
+
+            // Options
+            Type::Option(sv) => {
+                if let Type::Option(v) = self {
+                    v.is_subtype(sv, types, generics_map)
+                } else {
+                    false
+                }
+            }
+
+            // Maps
+            Type::Map(sk, sv) => {
+                if let Type::Map(k, v) = self {
+                    k.is_subtype(sk, types, generics_map) && v.is_subtype(sv, types, generics_map)
+                } else {
+                    false
+                }
+            }
+
             // Generics
             Type::Generic(g, uid) => {
                 let generic_pair = GenericPair {
@@ -174,21 +576,41 @@ impl Type {
                     uid: *uid,
                 };
 
-                if let Some(t) = generics_map.get(&generic_pair) {
-                    if let Type::Generic(t, _) = &**t {
-                        if let Type::Generic(s, _) = self {
-                            t == s
-                        } else {
-                            false
+                // A generic is always a subtype of itself
+                if let Type::Generic(s, suid) = self {
+                    if s == g && suid == uid {
+                        return true;
+                    }
+                }
+
+                if let Some(t) = generics_map.get(&generic_pair).cloned() {
+                    match &*t {
+                        // Instantiated generics may still be inferred
+                        Type::Generic(_, tuid) if *tuid >= INSTANTIATED_UID_BASE => {
+                            self.is_subtype(&t, types, generics_map)
                         }
-                    } else {
-                        self.is_subtype(&*t.clone(), types, generics_map)
+
+                        Type::Generic(t, _) => {
+                            if let Type::Generic(s, _) = self {
+                                t == s
+                            } else {
+                                false
+                            }
+                        }
+
+                        _ => self.is_subtype(&t, types, generics_map),
                     }
-                } else if !self.contains_generic(&generic_pair) {
-                    generics_map.insert(generic_pair, arc::new(self.clone()));
-                    true
                 } else {
-                    false
+                    // Follow mappings of the subtype so that generics never map to themselves
+                    let resolved = self.resolve_generic(generics_map);
+                    if *resolved == *supertype {
+                        true
+                    } else if !resolved.contains_generic(&generic_pair) {
+                        generics_map.insert(generic_pair, resolved);
+                        true
+                    } else {
+                        false
+                    }
                 }
             }
 
@@ -238,9 +660,9 @@ impl Type {
             | Type::DuplicateTypeError(_, _, _)
             | Type::Unknown
             | Type::Int
+            | Type::SizedInt(_)
             | Type::Float
             | Type::Bool
-            | Type::Word
             | Type::Char
             | Type::Symbol(_) => false,
 
@@ -248,6 +670,16 @@ impl Type {
 
             Type::Func(a, r) => a.contains_generic(generic) || r.contains_generic(generic),
 
+            Type::Array(i) => i.contains_generic(generic),
+
+            Type::Map(k, v) => k.contains_generic(generic) || v.contains_generic(generic),
+
+            Type::Thread(r) => r.contains_generic(generic),
+
+            Type::Task(r) => r.contains_generic(generic),
+
+            Type::Result(v) | Type::Option(v) => v.contains_generic(generic),
+
             Type::Union(_) => todo!(),
         }
     }
@@ -260,16 +692,36 @@ impl Type {
                 Arc::make_mut(a).replace_generics(generics_map);
             }
 
-            // Generics
-            Type::Generic(g, uid) => {
-                let generic_pair = GenericPair {
-                    generic: g.clone(),
-                    uid: *uid,
-                };
+            // Arrays
+            Type::Array(i) => {
+                Arc::make_mut(i).replace_generics(generics_map);
+            }
 
-                if let Some(t) = generics_map.get(&generic_pair) {
-                    *self = (**t).clone();
-                }
+            // Threads
+            Type::Thread(r) => {
+                Arc::make_mut(r).replace_generics(generics_map);
+            }
+
+            // Tasks
+            Type::Task(r) => {
+                Arc::make_mut(r).replace_generics(generics_map);
+            }
+
+            // Results and options
+            Type::Result(v) | Type::Option(v) => {
+                Arc::make_mut(v).replace_generics(generics_map);
+            }
+
+            // Maps
+            Type::Map(k, v) => {
+                Arc::make_mut(k).replace_generics(generics_map);
+                Arc::make_mut(v).replace_generics(generics_map);
+            }
+
+            // Generics
+            Type::Generic(_, _) => {
+                // Generics may be mapped to other generics, so follow the mappings to the end
+                *self = (*self.resolve_generic(generics_map)).clone();
             }
 
             // Union types
@@ -283,14 +735,60 @@ impl Type {
             | Type::DuplicateTypeError(_, _, _)
             | Type::Unknown
             | Type::Int
+            | Type::SizedInt(_)
             | Type::Float
             | Type::Bool
-            | Type::Word
             | Type::Char
             | Type::Symbol(_) => {}
         }
     }
 
+    // resolve_generic(&self, &HashMap<GenericPair, TypeRc>) -> TypeRc
+    // Follows the mappings of a generic until a type that is not a mapped generic is reached.
+    fn resolve_generic(&self, generics_map: &HashMap<GenericPair, TypeRc>) -> TypeRc {
+        let mut _type = arc::new(self.clone());
+        for _ in 0..generics_map.len() {
+            let next = match &*_type {
+                Type::Generic(g, uid) => generics_map.get(&GenericPair {
+                    generic: g.clone(),
+                    uid: *uid,
+                }),
+                _ => None,
+            };
+
+            match next {
+                Some(t) => _type = t.clone(),
+                None => break,
+            }
+        }
+        _type
+    }
+
+    // instantiate(&self, &mut usize) -> Type
+    // Returns a copy of the type with every generic replaced by a fresh generic, so that separate
+    // uses of a generic value do not constrain each other.
+    pub fn instantiate(&self, last_uid: &mut usize) -> Type {
+        let mut generics = vec![];
+        self.get_generics(&mut generics);
+
+        let mut generics_map = HashMap::new();
+        for (g, uid) in generics {
+            generics_map
+                .entry(GenericPair {
+                    generic: String::from(g),
+                    uid,
+                })
+                .or_insert_with(|| {
+                    *last_uid += 1;
+                    arc::new(Type::Generic(String::from(g), *last_uid))
+                });
+        }
+
+        let mut _type = self.clone();
+        _type.replace_generics(&generics_map);
+        _type
+    }
+
     pub fn get_generics<'a>(&'a self, v: &mut Vec<(&'a str, usize)>) {
         match self {
             Type::Error
@@ -298,9 +796,9 @@ impl Type {
             | Type::DuplicateTypeError(_, _, _)
             | Type::Unknown
             | Type::Int
+            | Type::SizedInt(_)
             | Type::Float
             | Type::Bool
-            | Type::Word
             | Type::Char
             | Type::Symbol(_) => (),
 
@@ -311,6 +809,19 @@ impl Type {
                 r.get_generics(v);
             }
 
+            Type::Array(i) => i.get_generics(v),
+
+            Type::Map(k, val) => {
+                k.get_generics(v);
+                val.get_generics(v);
+            }
+
+            Type::Thread(r) => r.get_generics(v),
+
+            Type::Task(r) => r.get_generics(v),
+
+            Type::Result(val) | Type::Option(val) => val.get_generics(v),
+
             Type::Union(_) => todo!(),
         }
     }
@@ -371,11 +882,14 @@ pub fn convert_ast_to_type(
                 "Int" => Type::Int,
                 "Float" => Type::Float,
                 "Bool" => Type::Bool,
-                "Word" => Type::Word,
+                "Int64" => Type::Int,
                 "Char" => Type::Char,
 
                 // Symbol
-                _ => Type::Symbol(v),
+                _ => match IntSize::from_name(&v) {
+                    Some(size) => Type::SizedInt(size),
+                    None => Type::Symbol(v),
+                },
             }
         }
 
@@ -440,6 +954,82 @@ pub fn convert_ast_to_type(
             }
         }
 
+        // Array types
+        Ast::Application(_, f, mut args)
+            if args.len() == 1 && matches!(&*f, Ast::Symbol(_, v) if v == "Array") =>
+        {
+            match convert_ast_to_type(args.remove(0), filename, generic_uids, last_uid) {
+                Type::UndeclaredTypeError(s) => Type::UndeclaredTypeError(s),
+                Type::DuplicateTypeError(a, b, c) => Type::DuplicateTypeError(a, b, c),
+                item => Type::Array(arc::new(item)),
+            }
+        }
+
+        // Thread types
+        Ast::Application(_, f, mut args)
+            if args.len() == 1 && matches!(&*f, Ast::Symbol(_, v) if v == "Thread") =>
+        {
+            match convert_ast_to_type(args.remove(0), filename, generic_uids, last_uid) {
+                Type::UndeclaredTypeError(s) => Type::UndeclaredTypeError(s),
+                Type::DuplicateTypeError(a, b, c) => Type::DuplicateTypeError(a, b, c),
+                result => Type::Thread(arc::new(result)),
+            }
+        }
+
+        // Task types
+        Ast::Application(_, f, mut args)
+            if args.len() == 1 && matches!(&*f, Ast::Symbol(_, v) if v == "Task") =>
+        {
+            match convert_ast_to_type(args.remove(0), filename, generic_uids, last_uid) {
+                Type::UndeclaredTypeError(s) => Type::UndeclaredTypeError(s),
+                Type::DuplicateTypeError(a, b, c) => Type::DuplicateTypeError(a, b, c),
+                result => Type::Task(arc::new(result)),
+            }
+        }
+
+        // Result types
+        Ast::Application(_, f, mut args)
+            if args.len() == 1 && matches!(&*f, Ast::Symbol(_, v) if v == "Result") =>
+        {
+            match convert_ast_to_type(args.remove(0), filename, generic_uids, last_uid) {
+                Type::UndeclaredTypeError(s) => Type::UndeclaredTypeError(s),
+                Type::DuplicateTypeError(a, b, c) => Type::DuplicateTypeError(a, b, c),
+                value => Type::Result(arc::new(value)),
+            }
+        }
+
+        // Option types
+        Ast::Application(_, f, mut args)
+            if args.len() == 1 && matches!(&*f, Ast::Symbol(_, v) if v == "Option") =>
+        {
+            match convert_ast_to_type(args.remove(0), filename, generic_uids, last_uid) {
+                Type::UndeclaredTypeError(s) => Type::UndeclaredTypeError(s),
+                Type::DuplicateTypeError(a, b, c) => Type::DuplicateTypeError(a, b, c),
+                value => Type::Option(arc::new(value)),
+            }
+        }
+
+        // Map types
+        Ast::Application(_, f, args)
+            if args.len() == 2 && matches!(&*f, Ast::Symbol(_, v) if v == "Map") =>
+        {
+            let mut args = args.into_iter();
+            let k = convert_ast_to_type(args.next().unwrap(), filename, generic_uids, last_uid);
+            let v = convert_ast_to_type(args.next().unwrap(), filename, generic_uids, last_uid);
+
+            if let Type::UndeclaredTypeError(s) = k {
+                Type::UndeclaredTypeError(s)
+            } else if let Type::DuplicateTypeError(a, b, c) = k {
+                Type::DuplicateTypeError(a, b, c)
+            } else if let Type::UndeclaredTypeError(s) = v {
+                Type::UndeclaredTypeError(s)
+            } else if let Type::DuplicateTypeError(a, b, c) = v {
+                Type::DuplicateTypeError(a, b, c)
+            } else {
+                Type::Map(arc::new(k), arc::new(v))
+            }
+        }
+
         // Function types
         Ast::Infix(_, op, l, r) if op == "->" => {
             let l = convert_ast_to_type(*l, filename, generic_uids, last_uid);
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use tracing::{debug, info_span};
+
+use super::ir::{ArityInfo, Ir, IrFunction, IrModule, SExpr, SExprMetadata};
+
+// An argument a forwarding function passes to the function it applies.
+enum Forwarded {
+    // The argument of the forwarding function with the given index
+    Param(usize),
+
+    // A literal
+    Value(SExpr),
+}
+
+// forwarding_target(&IrFunction, &IrModule) -> Option<(String, Vec<Forwarded>)>
+// Returns the function a function forwards its arguments to and the arguments it passes, if its
+// body is only an application of a known function to literals and each of its own arguments once
+// and in order, such as `part = add3 1` or `\x . add3 x`. Functions with closed over values are
+// never forwarded to, since their values are only visible where the function is defined.
+fn forwarding_target(func: &IrFunction, module: &IrModule) -> Option<(String, Vec<Forwarded>)> {
+    if !func.captured.is_empty() {
+        return None;
+    }
+
+    let (target, args) = match &func.body {
+        SExpr::Function(_, g) => (g, &[][..]),
+        SExpr::Application(_, f, args) => match &**f {
+            SExpr::Function(_, g) => (g, args.as_slice()),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    match module.funcs.get(target) {
+        Some(g) if g.captured.is_empty() && g.name != func.name => (),
+        _ => return None,
+    }
+
+    let mut forwarded = vec![];
+    let mut next = 0;
+    for arg in args {
+        forwarded.push(match arg {
+            SExpr::Int(_, _) | SExpr::Float(_, _) | SExpr::Char(_, _) | SExpr::String(_, _) => {
+                Forwarded::Value(arg.clone())
+            }
+
+            SExpr::Symbol(_, s) if func.args.get(next).map(|v| &v.0) == Some(s) => {
+                next += 1;
+                Forwarded::Param(next - 1)
+            }
+
+            _ => return None,
+        });
+    }
+
+    if next == func.args.len() {
+        Some((target.clone(), forwarded))
+    } else {
+        None
+    }
+}
+
+// uncurry_application(&mut SExpr, &HashMap<String, (String, Vec<Forwarded>)>, &IrModule) -> bool
+// Rewrites an application into a single application of a known function with a flat argument list
+// if it applies the result of applying a known function, or applies a function that forwards its
+// arguments to a known function. The known function must take at least as many arguments as the
+// flat list holds. Returns true if the application was rewritten.
+fn uncurry_application(
+    sexpr: &mut SExpr,
+    targets: &HashMap<String, (String, Vec<Forwarded>)>,
+    module: &IrModule,
+) -> bool {
+    let (m, func, args) = match sexpr {
+        SExpr::Application(m, func, args) => (m, func, args),
+        _ => return false,
+    };
+
+    // Applications that take more arguments than the known function does are left as they are
+    let (target, count) = match &**func {
+        SExpr::Application(_, f, inner) => match &**f {
+            SExpr::Function(_, g) => (g.clone(), inner.len() + args.len()),
+            _ => return false,
+        },
+
+        SExpr::Function(_, f) => match targets.get(f) {
+            Some((g, forwarded)) if args.len() >= module.funcs[f].args.len() => {
+                let count = forwarded.len() + args.len() - module.funcs[f].args.len();
+                (g.clone(), count)
+            }
+            _ => return false,
+        },
+
+        _ => return false,
+    };
+    let arity = module.funcs[&target].args.len();
+    if count > arity {
+        return false;
+    }
+
+    let (fm, mut flat) = match &mut **func {
+        SExpr::Application(_, f, inner) => (f.get_metadata().clone(), std::mem::take(inner)),
+
+        SExpr::Function(fm, f) => {
+            let mut given: Vec<_> = args.drain(..module.funcs[f].args.len()).map(Some).collect();
+            let flat = targets[f]
+                .1
+                .iter()
+                .map(|v| match v {
+                    Forwarded::Param(i) => given[*i].take().unwrap(),
+                    Forwarded::Value(v) => v.clone(),
+                })
+                .collect();
+
+            let fm = SExprMetadata {
+                _type: module.funcs[&target]._type.clone(),
+                arity: ArityInfo::Known(arity),
+                ..fm.clone()
+            };
+            (fm, flat)
+        }
+
+        _ => unreachable!(),
+    };
+
+    debug!(from = %func, to = %target, "uncurried");
+    flat.append(args);
+    m.arity = ArityInfo::Known(arity - count);
+    **func = SExpr::Function(fm, target);
+    *args = flat;
+    true
+}
+
+// uncurry_sexpr(&mut SExpr, &HashMap<String, (String, Vec<Forwarded>)>, &IrModule) -> ()
+// Uncurries the applications in an s expression, innermost first, so that applications of the
+// applications they flatten are flattened as well. The arms of match expressions are functions,
+// so they are uncurried along with the other functions of the module.
+fn uncurry_sexpr(
+    sexpr: &mut SExpr,
+    targets: &HashMap<String, (String, Vec<Forwarded>)>,
+    module: &IrModule,
+) {
+    match sexpr {
+        SExpr::Empty(_)
+        | SExpr::TypeAlias(_, _)
+        | SExpr::Symbol(_, _)
+        | SExpr::Int(_, _)
+        | SExpr::Float(_, _)
+        | SExpr::Char(_, _)
+        | SExpr::String(_, _)
+        | SExpr::Function(_, _) => (),
+
+        SExpr::ExternalFunc(_, _, args) => {
+            for arg in args {
+                uncurry_sexpr(arg, targets, module);
+            }
+        }
+
+        SExpr::Infix(_, _, a, b) | SExpr::Chain(_, a, b) => {
+            uncurry_sexpr(a, targets, module);
+            uncurry_sexpr(b, targets, module);
+        }
+
+        SExpr::Application(_, func, args) => {
+            uncurry_sexpr(func, targets, module);
+            for arg in args {
+                uncurry_sexpr(arg, targets, module);
+            }
+        }
+
+        SExpr::Assign(_, _, v)
+        | SExpr::As(_, v)
+        | SExpr::Ascribe(_, v)
+        | SExpr::Prefix(_, _, v) => uncurry_sexpr(v, targets, module),
+
+        SExpr::With(_, assigns, v) => {
+            for assign in assigns {
+                uncurry_sexpr(assign, targets, module);
+            }
+            uncurry_sexpr(v, targets, module);
+        }
+
+        SExpr::Match(_, v, _) => uncurry_sexpr(v, targets, module),
+    }
+
+    // Functions can forward to each other in a cycle, so each application is only rewritten as
+    // many times as there are functions
+    for _ in 0..module.funcs.len() {
+        if !uncurry_application(sexpr, targets, module) {
+            break;
+        }
+    }
+}
+
+// uncurry_module(&mut IrModule) -> ()
+// Rewrites the applications in every function of a module that apply a known function through
+// partial applications into single applications of the known function, so that saturated ones
+// become direct calls instead of building closures. The module must have been type checked.
+pub fn uncurry_module(module: &mut IrModule) {
+    let _span = info_span!("uncurry_module", name = %module.name).entered();
+    let targets: HashMap<_, _> = module
+        .funcs
+        .values()
+        .filter_map(|f| forwarding_target(f, module).map(|v| (f.name.clone(), v)))
+        .collect();
+
+    let names: Vec<_> = module.funcs.keys().cloned().collect();
+    for name in names {
+        let mut body = std::mem::replace(
+            &mut module.funcs.get_mut(&name).unwrap().body,
+            SExpr::Empty(SExprMetadata::empty()),
+        );
+        uncurry_sexpr(&mut body, &targets, module);
+        module.funcs.get_mut(&name).unwrap().body = body;
+    }
+}
+
+// uncurry_applications(&mut Ir) -> ()
+// Uncurries the applications in every module.
+pub fn uncurry_applications(ir: &mut Ir) {
+    for module in ir.modules.values_mut() {
+        uncurry_module(module);
+    }
+}
@@ -0,0 +1,399 @@
+use logos::Span;
+use proptest::prelude::*;
+use proptest::sample::select;
+use proptest::strategy::Union;
+use proptest::test_runner::{Config, TestCaseError, TestError, TestRunner};
+use std::fmt::Display;
+use std::fs;
+use std::os::unix::process::ExitStatusExt;
+use std::process::Command;
+
+use crate::backends::ir as backend_ir;
+use crate::compiler::Compiler;
+use crate::frontend::ir::Ir;
+use crate::frontend::parser::Ast;
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::linker;
+use crate::options::CompilerOptions;
+
+/// The depth of the bodies of generated programs.
+pub const DEFAULT_DEPTH: u32 = 4;
+
+/// The name of the module generated programs are compiled as.
+const MODULE: &str = "Main";
+
+/// The operators on ints that generated expressions use.
+const INT_OPERATORS: &[&str] = &["+", "-", "*", "/", "%", "&", "|", "^", "<<", ">>"];
+
+/// The operators comparing ints that generated expressions use.
+const COMPARISONS: &[&str] = &["<", "<=", ">", ">=", "==", "!="];
+
+/// The operators on bools that generated expressions use.
+const BOOL_OPERATORS: &[&str] = &["and", "or", "xor"];
+
+/// Int literals that are likely to find mistakes, such as the bounds of ints and shift amounts
+/// around the width of an int.
+const EDGE_INTS: &[i64] = &[0, 1, -1, 63, 64, i64::MAX, i64::MIN];
+
+/// The names of the `Int` globals generated programs define.
+const INT_GLOBALS: &[&str] = &["g0", "g1"];
+
+/// The name of the `Bool` global generated programs define.
+const BOOL_GLOBAL: &str = "q";
+
+/// Represents a generated program, which applies a function `f a: Int, b: Int, p: Bool` with a
+/// generated body to generated arguments. The program also defines the globals `g0` and `g1`,
+/// which are `Int`s, and `q`, which is a `Bool`, that `f` and the globals after them can use.
+#[derive(Debug, Clone)]
+pub struct Program {
+    /// The bodies of the `Int` globals, each of which can use the globals before it.
+    pub int_globals: Vec<Ast>,
+
+    /// The body of the `Bool` global, which can use the `Int` globals.
+    pub bool_global: Ast,
+
+    /// The body of `f`, which is a well typed `Int` expression.
+    pub body: Ast,
+
+    /// The arguments `f` is applied to.
+    pub a: i64,
+    pub b: i64,
+    pub p: bool,
+}
+
+impl Program {
+    /// Returns the source code of the program. The exit code of a compiled program is only the
+    /// lowest byte of the value `main` returns, so `main` xors every byte of the result of `f`
+    /// together to return a byte that depends on all of them.
+    pub fn source(&self) -> String {
+        let p = if self.p { "(0 < 1)" } else { "(1 < 0)" };
+        let bytes: Vec<_> = (0..8).map(|i| format!("(r >> {})", i * 8)).collect();
+        let globals: String = INT_GLOBALS
+            .iter()
+            .zip(self.int_globals.iter())
+            .chain(std::iter::once((&BOOL_GLOBAL, &self.bool_global)))
+            .map(|(name, body)| format!("{} = {}\n", name, expression_source(body)))
+            .collect();
+        format!(
+            "{}f a: Int, b: Int, p: Bool = {}\nmain = let r = f {} {} {} in ({}) & 255\n",
+            globals,
+            expression_source(&self.body),
+            int_source(self.a),
+            int_source(self.b),
+            p,
+            bytes.join(" ^ ")
+        )
+    }
+}
+
+impl Arbitrary for Program {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Program>;
+
+    fn arbitrary_with(_: ()) -> BoxedStrategy<Program> {
+        programs(DEFAULT_DEPTH)
+    }
+}
+
+/// Represents how a program finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The program exited with the given exit code.
+    Exited(i32),
+
+    /// The program panicked, or an assertion failed.
+    Panicked,
+
+    /// The program was killed by the given signal. Only compiled programs can crash.
+    Crashed(i32),
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Exited(c) => write!(f, "exit code {}", c),
+            Outcome::Panicked => write!(f, "a panic"),
+            Outcome::Crashed(s) => write!(f, "signal {}", s),
+        }
+    }
+}
+
+/// Returns the span of generated ast nodes. Generated asts are turned into source code before they
+/// are compiled, so their spans are never used.
+fn span() -> Span {
+    Span { start: 0, end: 0 }
+}
+
+/// Returns a strategy for int literals.
+fn int_literal() -> BoxedStrategy<i64> {
+    prop_oneof![4 => -1000i64..1000, 1 => select(EDGE_INTS), 1 => any::<i64>()].boxed()
+}
+
+/// Returns a strategy for well typed `Int` expressions no deeper than the given depth, using the
+/// given `Int` and `Bool` variables. Expressions use arithmetic, bitwise operators, `if`, `let`,
+/// and lambdas that capture the variables around them.
+pub fn int_expression(ints: Vec<String>, bools: Vec<String>, depth: u32) -> BoxedStrategy<Ast> {
+    let mut leaves = vec![int_literal().prop_map(|v| Ast::Int(span(), v)).boxed()];
+    if !ints.is_empty() {
+        leaves.push(
+            select(ints.clone())
+                .prop_map(|v| Ast::Symbol(span(), v))
+                .boxed(),
+        );
+    }
+    let leaf = Union::new(leaves);
+    if depth == 0 {
+        return leaf.boxed();
+    }
+
+    // Each depth binds its own name, so bindings never shadow each other
+    let name = format!("x{}", depth);
+    let mut bound = ints.clone();
+    bound.push(name.clone());
+
+    let int = int_expression(ints.clone(), bools.clone(), depth - 1);
+    let scoped = int_expression(bound, bools.clone(), depth - 1);
+    let cond = bool_expression(ints, bools, depth - 1);
+    let (let_name, lambda_name) = (name.clone(), name);
+    prop_oneof![
+        2 => leaf,
+        4 => (int.clone(), select(INT_OPERATORS), int.clone()).prop_map(|(l, op, r)| {
+            Ast::Infix(span(), String::from(op), Box::new(l), Box::new(r))
+        }),
+        1 => int.clone().prop_map(|v| Ast::Prefix(span(), String::from("~"), Box::new(v))),
+        2 => (cond, int.clone(), int.clone()).prop_map(|(c, t, e)| Ast::Match(
+            span(),
+            Box::new(c),
+            vec![
+                (Ast::Symbol(span(), String::from("true")), t),
+                (Ast::Symbol(span(), String::from("false")), e),
+            ],
+        )),
+        1 => (int.clone(), scoped.clone()).prop_map(move |(v, body)| Ast::With(
+            span(),
+            vec![Ast::Assign(span(), let_name.clone(), Box::new(v))],
+            Box::new(body),
+        )),
+        1 => (scoped, int).prop_map(move |(body, arg)| Ast::Application(
+            span(),
+            Box::new(Ast::Lambda(
+                span(),
+                vec![(lambda_name.clone(), Ast::Symbol(span(), String::from("Int")))],
+                Box::new(body),
+            )),
+            vec![arg],
+        )),
+    ]
+    .boxed()
+}
+
+/// Returns a strategy for well typed `Bool` expressions no deeper than the given depth, using the
+/// given `Int` and `Bool` variables. Expressions compare ints and combine bools with `and`, `or`,
+/// `xor`, and `not`.
+pub fn bool_expression(ints: Vec<String>, bools: Vec<String>, depth: u32) -> BoxedStrategy<Ast> {
+    let int = int_expression(ints.clone(), bools.clone(), depth.saturating_sub(1));
+    let comparison = (int.clone(), select(COMPARISONS), int)
+        .prop_map(|(l, op, r)| Ast::Infix(span(), String::from(op), Box::new(l), Box::new(r)));
+    let mut leaves = vec![comparison.boxed()];
+    if !bools.is_empty() {
+        leaves.push(
+            select(bools.clone())
+                .prop_map(|v| Ast::Symbol(span(), v))
+                .boxed(),
+        );
+    }
+    let leaf = Union::new(leaves);
+    if depth == 0 {
+        return leaf.boxed();
+    }
+
+    let boolean = bool_expression(ints, bools, depth - 1);
+    prop_oneof![
+        2 => leaf,
+        2 => (boolean.clone(), select(BOOL_OPERATORS), boolean.clone()).prop_map(|(l, op, r)| {
+            Ast::Infix(span(), String::from(op), Box::new(l), Box::new(r))
+        }),
+        1 => boolean.prop_map(|v| Ast::Prefix(span(), String::from("not"), Box::new(v))),
+    ]
+    .boxed()
+}
+
+/// Returns a strategy for programs whose bodies are no deeper than the given depth. The bodies of
+/// globals are half as deep.
+pub fn programs(depth: u32) -> BoxedStrategy<Program> {
+    let names = |v: &[&str]| v.iter().map(|v| String::from(*v)).collect::<Vec<_>>();
+    let globals = (
+        int_expression(vec![], vec![], depth / 2),
+        int_expression(names(&INT_GLOBALS[..1]), vec![], depth / 2),
+        bool_expression(names(INT_GLOBALS), vec![], depth / 2),
+    );
+
+    let mut ints = names(&["a", "b"]);
+    ints.extend(names(INT_GLOBALS));
+    let bools = names(&["p", BOOL_GLOBAL]);
+    (
+        globals,
+        int_expression(ints, bools, depth),
+        int_literal(),
+        int_literal(),
+        any::<bool>(),
+    )
+        .prop_map(|((g0, g1, q), body, a, b, p)| Program {
+            int_globals: vec![g0, g1],
+            bool_global: q,
+            body,
+            a,
+            b,
+            p,
+        })
+        .boxed()
+}
+
+/// Returns the source code of an int literal. Closey has no negative literals, so negative ints
+/// are subtracted from zero.
+fn int_source(n: i64) -> String {
+    match n {
+        i64::MIN => format!("(0 - {} - 1)", i64::MAX),
+        n if n < 0 => format!("(0 - {})", -n),
+        n => n.to_string(),
+    }
+}
+
+/// Returns the source code of a generated expression, with every subexpression in parentheses.
+pub fn expression_source(ast: &Ast) -> String {
+    match ast {
+        Ast::Int(_, n) => int_source(*n),
+        Ast::Symbol(_, s) => s.clone(),
+        Ast::Infix(_, op, l, r) => {
+            format!("({} {} {})", expression_source(l), op, expression_source(r))
+        }
+        Ast::Prefix(_, op, v) => format!("({} {})", op, expression_source(v)),
+
+        // Generated matches are always `if`s
+        Ast::Match(_, c, arms) => format!(
+            "(if {} then {} else {})",
+            expression_source(c),
+            expression_source(&arms[0].1),
+            expression_source(&arms[1].1)
+        ),
+
+        Ast::With(_, assigns, body) => match &assigns[..] {
+            [Ast::Assign(_, name, v)] => format!(
+                "(let {} = {} in {})",
+                name,
+                expression_source(v),
+                expression_source(body)
+            ),
+            _ => unreachable!("generated lets bind one value"),
+        },
+
+        Ast::Application(_, f, args) => {
+            let args: Vec<_> = args.iter().map(expression_source).collect();
+            format!("({} {})", expression_source(f), args.join(" "))
+        }
+
+        Ast::Lambda(_, args, body) => {
+            let args: Vec<_> = args
+                .iter()
+                .map(|(name, t)| format!("{}: {}", name, expression_source(t)))
+                .collect();
+            format!("(\\{} . {})", args.join(", "), expression_source(body))
+        }
+
+        _ => unreachable!("expressions are never generated with {:?}", ast),
+    }
+}
+
+/// Checks a program, returning the first error reported if it does not compile.
+fn check(compiler: &mut Compiler, source: &str) -> Result<Ir, String> {
+    let output = compiler.check(MODULE, source);
+    let error = output.errors().next().map(|e| e.message.clone());
+    output.value.ok_or_else(|| {
+        format!(
+            "the program does not compile: {}",
+            error.unwrap_or_default()
+        )
+    })
+}
+
+/// Runs a program with the interpreter.
+pub fn interpret(source: &str, options: &CompilerOptions) -> Result<Outcome, String> {
+    let ir = check(&mut Compiler::new(options.clone()), source)?;
+    let interpreter = Interpreter::new(&ir, options.overflow_checks);
+    match interpreter.run_main(MODULE) {
+        Ok(c) => Ok(Outcome::Exited(c & 255)),
+        Err(InterpreterError::Panic(_, _)) | Err(InterpreterError::AssertionFailed(_, _, _)) => {
+            Ok(Outcome::Panicked)
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Compiles a program with the LLVM backend, links it with the runtime library, and runs it. This
+/// needs `llc` and a linker, the same as `closeyc build --backend llvm`.
+pub fn compile_and_run(source: &str, options: &CompilerOptions) -> Result<Outcome, String> {
+    let compiler = &mut Compiler::new(options.clone());
+    let ir = check(compiler, source)?;
+    let mut module = compiler.lower(&ir, MODULE).unwrap();
+    backend_ir::remove_unused_functions(&mut module, &["main"]);
+    let code = compiler.codegen_llvm(&module, true);
+
+    let object = linker::temp_path("o");
+    let executable = linker::temp_path("out");
+    let result = linker::compile_llvm_ir(&code, &object, options)
+        .and_then(|_| linker::link(&object, &executable.display().to_string(), options))
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            Command::new(&executable)
+                .output()
+                .map_err(|e| format!("could not run the program: {}", e))
+        });
+    let _ = fs::remove_file(&object);
+    let _ = fs::remove_file(&executable);
+
+    // Programs can exit with the same code as a panic, but only panics write to stderr
+    let output = result?;
+    let status = output.status;
+    Ok(match (status.code(), status.signal()) {
+        (Some(101), _) if !output.stderr.is_empty() => Outcome::Panicked,
+        (Some(c), _) => Outcome::Exited(c),
+        (None, Some(s)) => Outcome::Crashed(s),
+        (None, None) => unreachable!("processes either exit or are killed by a signal"),
+    })
+}
+
+/// Runs a program with the interpreter and as compiled code, returning an error describing the
+/// difference if they finish differently or either cannot run it.
+pub fn check_program(program: &Program, options: &CompilerOptions) -> Result<(), String> {
+    let source = program.source();
+    let interpreted = interpret(&source, options)?;
+    let compiled = compile_and_run(&source, options)?;
+    if interpreted == compiled {
+        Ok(())
+    } else {
+        Err(format!(
+            "the interpreter gave {} but compiled code gave {}",
+            interpreted, compiled
+        ))
+    }
+}
+
+/// Checks that the interpreter and compiled code agree on the given number of generated programs.
+/// A program they disagree on is shrunk to a smaller program they still disagree on, which is
+/// returned in the error along with how each finished.
+pub fn fuzz_backends(options: &CompilerOptions, cases: u32) -> Result<(), String> {
+    let mut runner = TestRunner::new(Config {
+        cases,
+        failure_persistence: None,
+        ..Config::default()
+    });
+
+    runner
+        .run(&any::<Program>(), |program| {
+            check_program(&program, options).map_err(TestCaseError::fail)
+        })
+        .map_err(|e| match e {
+            TestError::Abort(reason) => reason.to_string(),
+            TestError::Fail(reason, program) => format!("{} for\n{}", reason, program.source()),
+        })
+}
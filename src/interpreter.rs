@@ -0,0 +1,1093 @@
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, ErrorKind, Write};
+use std::os::unix::io::IntoRawFd;
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::process::Command;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use crate::frontend::intrinsics;
+use crate::frontend::ir::{
+    BinOp, Ir, IrFunction, IrModule, IrTest, Location, Pattern, PrefixOp, SExpr,
+};
+use crate::frontend::types::{IntSize, Type};
+
+/// A function that has been applied to some (possibly none) of its arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Closure {
+    /// The name of the module the function is in.
+    pub module: String,
+
+    /// The internal name of the function.
+    pub func: String,
+
+    /// The values passed into the function so far. Closed over values come first, followed by the
+    /// applied arguments, in the same order as in the backends.
+    pub args: Vec<Value>,
+}
+
+/// A value produced by the interpreter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// The unit value.
+    Unit,
+
+    /// A 64 bit integer.
+    Int(i64),
+
+    /// An integer of a sized int type, stored in 64 bits the same way as in compiled code.
+    SizedInt(i64, IntSize),
+
+    /// A 64 bit float.
+    Float(f64),
+
+    /// A unicode scalar value.
+    Char(char),
+
+    /// A boolean, built by `true` and `false` or returned by intrinsics. The Church booleans of the
+    /// prelude are closures.
+    Bool(bool),
+
+    /// An immutable string.
+    String(Rc<String>),
+
+    /// A function or closure.
+    Closure(Closure),
+
+    /// An array. Arrays are copied when modified if they are shared.
+    Array(Rc<Vec<Value>>),
+
+    /// A map, as a list of key value pairs in insertion order. Maps are copied when modified if
+    /// they are shared.
+    Map(Rc<Vec<(Value, Value)>>),
+
+    /// A result of a fallible operation.
+    Result(Result<Box<Value>, Rc<String>>),
+
+    /// An optional value.
+    Option(Option<Box<Value>>),
+
+    /// A finished thread. Threads are run to completion when they are spawned.
+    Thread(Box<Value>),
+
+    /// A finished task. Tasks are run to completion when they are created.
+    Task(Box<Value>),
+
+    /// A value of a declared type, given by the name of its constructor and its fields.
+    Data(Rc<String>, Rc<Vec<Value>>),
+}
+
+impl Value {
+    /// Creates a new string value.
+    fn string(s: &str) -> Value {
+        Value::String(Rc::new(String::from(s)))
+    }
+
+    /// Creates an int of the given size, wrapping the value around if it is out of range.
+    fn int(n: i128, size: IntSize) -> Value {
+        match size.into_type() {
+            Type::Int => Value::Int(size.wrap(n)),
+            _ => Value::SizedInt(size.wrap(n), size),
+        }
+    }
+
+    /// Creates a new error result.
+    fn err(msg: &str) -> Value {
+        Value::Result(Err(Rc::new(String::from(msg))))
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::SizedInt(n, size) => write!(f, "{}", size.value(*n)),
+            Value::Float(n) => write!(f, "{:?}", n),
+            Value::Char(c) => write!(f, "{:?}", c),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::String(s) => write!(f, "{}", debug_string(s)),
+            Value::Closure(c) => write!(f, "<function {}>", c.func),
+
+            Value::Array(a) => {
+                write!(f, "[")?;
+                for (i, v) in a.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", v)?;
+                }
+                write!(f, "]")
+            }
+
+            Value::Map(m) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in m.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", k, v)?;
+                }
+                write!(f, "}}")
+            }
+
+            Value::Result(Ok(v)) => write!(f, "ok {}", nested(v)),
+            Value::Result(Err(e)) => write!(f, "err {}", debug_string(e)),
+            Value::Option(Some(v)) => write!(f, "some {}", nested(v)),
+            Value::Option(None) => write!(f, "none"),
+            Value::Thread(_) => write!(f, "<thread>"),
+            Value::Task(_) => write!(f, "<task>"),
+
+            Value::Data(c, fields) => {
+                write!(f, "{}", c)?;
+                for field in fields.iter() {
+                    write!(f, " {}", nested(field))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Represents an error that stops evaluation.
+#[derive(Debug)]
+pub enum InterpreterError {
+    /// The program panicked. The fields are the message and the location of the panic.
+    Panic(String, String),
+
+    /// An assertion failed. The fields are the message, the location of the assertion, and the
+    /// source code of its condition.
+    AssertionFailed(String, String, String),
+
+    /// The program called `exit` with the given exit code.
+    Exit(i32),
+
+    /// A value that is not a function was applied to arguments.
+    NotAFunction(Value),
+
+    /// The expression cannot be evaluated by the interpreter.
+    Unsupported(String),
+}
+
+impl Display for InterpreterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InterpreterError::Panic(m, l) => write!(f, "panicked at {}: {}", l, m),
+            InterpreterError::AssertionFailed(m, l, c) => {
+                write!(f, "assertion `{}` failed at {}: {}", c, l, m)
+            }
+            InterpreterError::Exit(c) => write!(f, "exited with code {}", c),
+            InterpreterError::NotAFunction(v) => write!(f, "{} is not a function", v),
+            InterpreterError::Unsupported(s) => {
+                write!(f, "{} is not supported by the interpreter", s)
+            }
+        }
+    }
+}
+
+/// Shows a value inside another value. Values shown as more than one word, such as constructors
+/// with fields, are put in parentheses, the same as the show functions of compiled code do.
+fn nested(v: &Value) -> String {
+    let shown = v.to_string();
+    if shown.starts_with(|c: char| c.is_ascii_alphabetic()) && shown.contains(' ') {
+        format!("({})", shown)
+    } else {
+        shown
+    }
+}
+
+/// Formats a float with the given number of digits after the point, in scientific notation if
+/// asked for, and padded on the left with spaces to at least the given width.
+fn format_float(x: f64, precision: i64, scientific: bool, width: i64) -> String {
+    let formatted = if x.is_finite() {
+        format_finite(x, precision.max(0), scientific)
+    } else {
+        format!("{:?}", x)
+    };
+    format!("{:>1$}", formatted, width.max(0) as usize)
+}
+
+/// Formats a finite float the way `format_float` does. Digits past the seventeenth significant
+/// digit are shown as zeros, the same as in compiled code.
+fn format_finite(x: f64, precision: i64, scientific: bool) -> String {
+    let rounded = format!("{:.16e}", x.abs());
+    let (mantissa, exponent) = rounded.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "").into_bytes();
+    let exponent: i64 = exponent.parse().unwrap();
+
+    if scientific && precision <= 16 {
+        return format!("{:.*e}", precision as usize, x);
+    } else if !scientific && (x == 0.0 || exponent + 1 + precision <= 17) {
+        return format!("{:.*}", precision as usize, x);
+    }
+
+    let mut formatted = String::from(if x.is_sign_negative() { "-" } else { "" });
+    let digit = |i: i64| {
+        if (0..17).contains(&i) {
+            digits[i as usize] as char
+        } else {
+            '0'
+        }
+    };
+    if scientific {
+        formatted.push(digit(0));
+        formatted.push('.');
+        formatted.extend((1..=precision).map(digit));
+        formatted.push_str(&format!("e{}", exponent));
+    } else {
+        for place in (-precision..=exponent.max(0)).rev() {
+            formatted.push(digit(exponent - place));
+            if place == 0 && precision > 0 {
+                formatted.push('.');
+            }
+        }
+    }
+    formatted
+}
+
+/// Escapes a string the same way the show functions of compiled code do, including the quotes.
+fn debug_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c if (c as u32) < 0x20 || c as u32 == 0x7f => {
+                escaped.push_str(&format!("\\x{:02x}", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Converts an io error into the message the runtime library uses for it.
+fn io_error_message(e: &io::Error) -> &'static str {
+    match e.kind() {
+        ErrorKind::NotFound => "no such file or directory",
+        ErrorKind::PermissionDenied => "permission denied",
+        ErrorKind::AlreadyExists => "file exists",
+        ErrorKind::InvalidInput => "invalid path",
+        _ => match e.raw_os_error() {
+            Some(libc::ENOTDIR) => "not a directory",
+            Some(libc::EISDIR) => "is a directory",
+            Some(libc::ENOSPC) => "no space left on device",
+            _ => "io error",
+        },
+    }
+}
+
+/// Returns the time of a clock in nanoseconds, read the same way as by the runtime library.
+fn clock_nanos(clock: libc::clockid_t) -> i64 {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe { libc::clock_gettime(clock, &mut time) };
+    time.tv_sec * 1_000_000_000 + time.tv_nsec
+}
+
+/// Converts the result of an io operation into a result value.
+fn io_result<T>(result: io::Result<T>, f: impl FnOnce(T) -> Value) -> Value {
+    match result {
+        Ok(v) => Value::Result(Ok(Box::new(f(v)))),
+        Err(e) => Value::err(io_error_message(&e)),
+    }
+}
+
+/// Evaluates the higher level intermediate representation directly, without generating code.
+/// Intrinsics are implemented natively, so no runtime library or toolchain is needed.
+pub struct Interpreter<'a> {
+    ir: &'a Ir,
+    overflow_checks: bool,
+
+    /// The values of globals without arguments that have already been evaluated, by the internal
+    /// name of their function. Values are reference counted, so strings, arrays, maps, and values
+    /// of declared types are shared by every line that uses them rather than copied or freed.
+    values: RefCell<HashMap<String, Value>>,
+
+    /// The state of the random number generator, which starts from a seed of 0 the same as in
+    /// compiled code.
+    random: Cell<u64>,
+}
+
+impl<'a> Interpreter<'a> {
+    /// Creates a new interpreter over the given IR. With overflow checks, int arithmetic that
+    /// overflows panics instead of wrapping, the same as compiled code built with them.
+    pub fn new(ir: &'a Ir, overflow_checks: bool) -> Interpreter<'a> {
+        Interpreter {
+            ir,
+            overflow_checks,
+            values: RefCell::new(HashMap::new()),
+            random: Cell::new(0),
+        }
+    }
+
+    /// Sets the values of globals that have already been evaluated, such as those kept by the
+    /// REPL from earlier lines, which are used instead of evaluating the globals again.
+    pub fn with_values(self, values: HashMap<String, Value>) -> Interpreter<'a> {
+        Interpreter {
+            values: RefCell::new(values),
+            ..self
+        }
+    }
+
+    /// Returns the values of the globals evaluated by eval_global, along with those the
+    /// interpreter was given, so that they can be kept for later.
+    pub fn into_values(self) -> HashMap<String, Value> {
+        self.values.into_inner()
+    }
+
+    /// Evaluates a global value in the given module. Globals with arguments evaluate to closures.
+    /// The value of a global without arguments is kept, and used wherever the global is used
+    /// afterwards instead of evaluating it again.
+    pub fn eval_global(&self, module: &str, name: &str) -> Result<Value, InterpreterError> {
+        let module = self
+            .ir
+            .module(module)
+            .ok_or_else(|| InterpreterError::Unsupported(format!("module `{}`", module)))?;
+        let func = module
+            .global(name)
+            .ok_or_else(|| InterpreterError::Unsupported(format!("global `{}`", name)))?;
+
+        let value = self.global_value(module, func)?;
+        if func.args.is_empty() && func.captured_names.is_empty() {
+            self.values
+                .borrow_mut()
+                .insert(func.name.clone(), value.clone());
+        }
+        Ok(value)
+    }
+
+    /// Evaluates `main` in the given module and returns the code the program exits with, the same
+    /// as a compiled program: the value of `main` if it is an int, the code passed to `exit` if the
+    /// program calls it, or 0 otherwise.
+    pub fn run_main(&self, module: &str) -> Result<i32, InterpreterError> {
+        match self.eval_global(module, "main") {
+            Ok(Value::Int(v)) | Ok(Value::SizedInt(v, _)) => Ok(v as i32),
+            Ok(_) => Ok(0),
+            Err(InterpreterError::Exit(c)) => Ok(c),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Evaluates a test in the given module, returning whether it passed. A test that stops with
+    /// an error, such as a failed assertion, returns the error instead.
+    pub fn run_test(&self, module: &str, test: &IrTest) -> Result<bool, InterpreterError> {
+        let closure = Closure {
+            module: String::from(module),
+            func: test.func.clone(),
+            args: vec![],
+        };
+
+        match self.call(closure)? {
+            Value::Bool(b) => Ok(b),
+            v => Err(InterpreterError::Unsupported(format!("test value {}", v))),
+        }
+    }
+
+    /// Applies a function value to a list of arguments. Functions are called once they have
+    /// received all of their arguments; any remaining arguments are applied to the result.
+    pub fn apply(&self, func: Value, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        let mut func = func;
+        let mut args = args.into_iter();
+        loop {
+            let mut closure = match func {
+                Value::Closure(c) => c,
+                v => return Err(InterpreterError::NotAFunction(v)),
+            };
+
+            let (_, f) = self.function(&closure)?;
+            let arity = f.captured_names.len() + f.args.len();
+            while closure.args.len() < arity {
+                match args.next() {
+                    Some(a) => closure.args.push(a),
+                    None => return Ok(Value::Closure(closure)),
+                }
+            }
+
+            func = self.call(closure)?;
+            if args.as_slice().is_empty() {
+                return Ok(func);
+            }
+        }
+    }
+
+    /// Returns the module and function a closure refers to.
+    fn function(
+        &self,
+        closure: &Closure,
+    ) -> Result<(&'a IrModule, &'a IrFunction), InterpreterError> {
+        self.ir
+            .module(&closure.module)
+            .and_then(|m| m.function(&closure.func).map(|f| (m, f)))
+            .ok_or_else(|| InterpreterError::Unsupported(format!("function `{}`", closure.func)))
+    }
+
+    /// Calls a closure that has received all of its arguments.
+    fn call(&self, closure: Closure) -> Result<Value, InterpreterError> {
+        let (module, func) = self.function(&closure)?;
+        let env: HashMap<_, _> = func
+            .captured_names
+            .iter()
+            .chain(func.args.iter().map(|(a, _)| a))
+            .cloned()
+            .zip(closure.args)
+            .collect();
+        self.eval(module, &func.body, &env)
+    }
+
+    /// Returns the value of a global. Globals without arguments are evaluated.
+    fn global_value(
+        &self,
+        module: &IrModule,
+        func: &IrFunction,
+    ) -> Result<Value, InterpreterError> {
+        let closure = Closure {
+            module: module.name.clone(),
+            func: func.name.clone(),
+            args: vec![],
+        };
+
+        if func.args.is_empty() && func.captured_names.is_empty() {
+            if let Some(v) = self.values.borrow().get(&func.name) {
+                return Ok(v.clone());
+            }
+            self.call(closure)
+        } else {
+            Ok(Value::Closure(closure))
+        }
+    }
+
+    /// Evaluates an sexpression in the given module with the given local values.
+    pub fn eval(
+        &self,
+        module: &IrModule,
+        sexpr: &SExpr,
+        env: &HashMap<String, Value>,
+    ) -> Result<Value, InterpreterError> {
+        match sexpr {
+            SExpr::Symbol(_, s) => {
+                if let Some(v) = env.get(s) {
+                    return Ok(v.clone());
+                }
+
+                if let Some(f) = module.global(s) {
+                    self.global_value(module, f)
+                } else if let Some((m, f)) = self.ir.lookup(s) {
+                    self.global_value(m, f)
+                } else {
+                    Err(InterpreterError::Unsupported(format!("symbol `{}`", s)))
+                }
+            }
+
+            SExpr::Int(m, n) => match &*m._type {
+                Type::SizedInt(size) => Ok(Value::SizedInt(*n, *size)),
+                _ => Ok(Value::Int(*n)),
+            },
+
+            SExpr::Float(_, n) => Ok(Value::Float(*n)),
+
+            SExpr::Char(_, c) => Ok(Value::Char(*c)),
+
+            SExpr::String(_, s) => Ok(Value::string(s)),
+
+            SExpr::Function(_, f) => {
+                let func = module
+                    .function(f)
+                    .ok_or_else(|| InterpreterError::Unsupported(format!("function `{}`", f)))?;
+
+                // The type checker replaces references to globals with their functions
+                if func.global {
+                    return self.global_value(module, func);
+                }
+
+                Ok(Value::Closure(Closure {
+                    module: module.name.clone(),
+                    func: f.clone(),
+                    args: func
+                        .captured_names
+                        .iter()
+                        .map(|c| env.get(c).cloned().unwrap_or(Value::Unit))
+                        .collect(),
+                }))
+            }
+
+            SExpr::ExternalFunc(m, name, sexprs) => {
+                let args = sexprs
+                    .iter()
+                    .map(|a| self.eval(module, a, env))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if module.constructors.contains_key(name) && !module.externals.contains_key(name) {
+                    return Ok(Value::Data(Rc::new(name.clone()), Rc::new(args)));
+                }
+
+                if module.externals.contains_key(name) || intrinsics::get(name).is_none() {
+                    return Err(InterpreterError::Unsupported(format!(
+                        "external function `{}`",
+                        name
+                    )));
+                }
+                self.intrinsic(name, args, module, &m.loc, sexprs)
+            }
+
+            SExpr::Application(_, f, args) => {
+                let f = self.eval(module, f, env)?;
+                let args = args
+                    .iter()
+                    .map(|a| self.eval(module, a, env))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.apply(f, args)
+            }
+
+            SExpr::Empty(_) => Err(InterpreterError::Unsupported(String::from(
+                "empty expression",
+            ))),
+            SExpr::TypeAlias(_, _) => {
+                Err(InterpreterError::Unsupported(String::from("type alias")))
+            }
+            SExpr::Prefix(_, op, v) => Ok(match (op, self.eval(module, v, env)?) {
+                (PrefixOp::Not, Value::Bool(b)) => Value::Bool(!b),
+                (PrefixOp::BitNot, Value::Int(n)) => Value::Int(!n),
+                (PrefixOp::BitNot, Value::SizedInt(n, size)) => Value::int(!size.value(n), size),
+                _ => unreachable!("the type checker only allows operators on values they support"),
+            }),
+
+            SExpr::Infix(m, op, l, r) => {
+                let l = self.eval(module, l, env)?;
+                let r = self.eval(module, r, env)?;
+                infix(*op, l, r, self.overflow_checks).map_err(|msg| {
+                    InterpreterError::Panic(String::from(msg), module.location_string(&m.loc))
+                })
+            }
+
+            SExpr::Chain(_, _, _) => Err(InterpreterError::Unsupported(String::from("chain"))),
+
+            SExpr::As(m, v) => {
+                let value = self.eval(module, v, env)?;
+                if let Some(size) = IntSize::of(&m._type) {
+                    let n = match value {
+                        Value::Int(n) => n as i128,
+                        Value::SizedInt(n, from) => from.value(n),
+                        Value::Char(c) => c as i128,
+                        Value::Bool(b) => b as i128,
+
+                        // Floats beyond the range of the type saturate, and NaN converts to 0
+                        Value::Float(n) => (n as i128).clamp(size.min(), size.max()),
+                        _ => return Ok(value),
+                    };
+                    return Ok(Value::int(n, size));
+                }
+
+                let int = match value {
+                    Value::Int(n) => n as i128,
+                    Value::SizedInt(n, size) => size.value(n),
+                    _ => return Ok(value),
+                };
+                match &*m._type {
+                    Type::Char => {
+                        let c = u32::try_from(int).ok().and_then(char::from_u32);
+                        c.map(Value::Char).ok_or_else(|| {
+                            InterpreterError::Panic(
+                                String::from("int is not a unicode scalar value"),
+                                module.location_string(&m.loc),
+                            )
+                        })
+                    }
+                    Type::Float => Ok(Value::Float(int as f64)),
+                    _ => Ok(value),
+                }
+            }
+
+            SExpr::Ascribe(_, v) => self.eval(module, v, env),
+
+            SExpr::Assign(_, _, _) => {
+                Err(InterpreterError::Unsupported(String::from("assignment")))
+            }
+
+            // Each binding can use the ones before it
+            SExpr::With(_, assigns, body) => {
+                let mut env = env.clone();
+                for assign in assigns {
+                    if let SExpr::Assign(_, name, value) = assign {
+                        let value = self.eval(module, value, &env)?;
+                        env.insert(name.clone(), value);
+                    }
+                }
+                self.eval(module, body, &env)
+            }
+            SExpr::Match(m, value, arms) => {
+                let value = self.eval(module, value, env)?;
+                for (pattern, arm, _) in arms {
+                    let mut bound = vec![value.clone()];
+                    if match_pattern(pattern, &value, &mut bound) {
+                        let arm = self.eval(module, arm, env)?;
+                        return self.apply(arm, bound);
+                    }
+                }
+
+                // Checked matches only miss constructors they do not know about, so the tag is
+                // reported the same way as in compiled code
+                let tag = match &value {
+                    Value::Data(c, _) => module.constructors.get(&**c).map(|v| v.tag),
+                    _ => None,
+                };
+                let msg = match tag {
+                    Some(tag) => format!("no match arm matched the value with tag {}", tag),
+                    None => String::from("no match arm matched"),
+                };
+                Err(InterpreterError::Panic(msg, module.location_string(&m.loc)))
+            }
+        }
+    }
+
+    /// Returns the next 64 bits from the random number generator. The generator is splitmix64, the
+    /// same as in the runtime library, so a seeded program gives the same numbers either way.
+    fn next_random(&self) -> u64 {
+        let state = self.random.get().wrapping_add(0x9e3779b97f4a7c15);
+        self.random.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Calls an intrinsic with evaluated arguments. Located intrinsics report the location of the
+    /// call when they panic, and assertions the code of their condition as well, the same as in
+    /// compiled code.
+    fn intrinsic(
+        &self,
+        name: &str,
+        args: Vec<Value>,
+        module: &IrModule,
+        loc: &Location,
+        sexprs: &[SExpr],
+    ) -> Result<Value, InterpreterError> {
+        use Value::*;
+
+        let panic = |msg: std::string::String| {
+            Err(InterpreterError::Panic(msg, module.location_string(loc)))
+        };
+        let v = match (name, args.as_slice()) {
+            ("print", [String(s)]) => {
+                print!("{}", s);
+                io::stdout().flush().unwrap();
+                Unit
+            }
+            ("println", [String(s)]) => {
+                println!("{}", s);
+                Unit
+            }
+            ("debug", [v]) => {
+                println!("{}", v);
+                Unit
+            }
+            ("show", [v]) => Value::string(&v.to_string()),
+            ("format_float", [Float(x), Int(precision), Int(width)]) => {
+                Value::string(&format_float(*x, *precision, false, *width))
+            }
+            ("format_scientific", [Float(x), Int(precision), Int(width)]) => {
+                Value::string(&format_float(*x, *precision, true, *width))
+            }
+            ("read_line", [_]) => {
+                let mut line = std::string::String::new();
+                io::stdin().lock().read_line(&mut line).unwrap();
+                if line.ends_with('\n') {
+                    line.pop();
+                }
+                String(Rc::new(line))
+            }
+
+            ("concat", [String(a), String(b)]) => String(Rc::new(format!("{}{}", a, b))),
+            ("string_eq", [String(a), String(b)]) => Bool(a == b),
+
+            ("char_eq", [Char(a), Char(b)]) => Bool(a == b),
+            ("char_lt", [Char(a), Char(b)]) => Bool(a < b),
+            ("char_le", [Char(a), Char(b)]) => Bool(a <= b),
+            ("char_gt", [Char(a), Char(b)]) => Bool(a > b),
+            ("char_ge", [Char(a), Char(b)]) => Bool(a >= b),
+            ("char_to_string", [Char(c)]) => Value::string(&c.to_string()),
+
+            ("open", [String(p)]) => io_result(File::open(&**p), |f| Int(f.into_raw_fd() as i64)),
+            ("close", [Int(fd)]) => {
+                if unsafe { libc::close(*fd as i32) } < 0 {
+                    Value::err(io_error_message(&io::Error::last_os_error()))
+                } else {
+                    Result(Ok(Box::new(Unit)))
+                }
+            }
+            ("read_to_string", [String(p)]) => io_result(fs::read(&**p), |b| {
+                Value::string(&std::string::String::from_utf8_lossy(&b))
+            }),
+            ("write", [String(p), String(c)]) => io_result(fs::write(&**p, c.as_bytes()), |_| Unit),
+            ("append", [String(p), String(c)]) => io_result(
+                OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(&**p)
+                    .and_then(|mut f| f.write_all(c.as_bytes())),
+                |_| Unit,
+            ),
+            ("exists", [String(p)]) => Bool(Path::new(&**p).exists()),
+
+            ("sqrt", [Float(x)]) => Float(x.sqrt()),
+            ("sin", [Float(x)]) => Float(x.sin()),
+            ("cos", [Float(x)]) => Float(x.cos()),
+            ("tan", [Float(x)]) => Float(x.tan()),
+            ("exp", [Float(x)]) => Float(x.exp()),
+            ("ln", [Float(x)]) => Float(x.ln()),
+            ("floor", [Float(x)]) => Float(x.floor()),
+            ("ceil", [Float(x)]) => Float(x.ceil()),
+            ("abs", [Float(x)]) => Float(x.abs()),
+            ("min", [Float(a), Float(b)]) => Float(a.min(*b)),
+            ("max", [Float(a), Float(b)]) => Float(a.max(*b)),
+            ("abs_int", [Int(x)]) => Int(x.wrapping_abs()),
+            ("min_int", [Int(a), Int(b)]) => Int(*a.min(b)),
+            ("max_int", [Int(a), Int(b)]) => Int(*a.max(b)),
+
+            ("now_millis", [_]) => Int(clock_nanos(libc::CLOCK_REALTIME) / 1_000_000),
+            ("clock_monotonic", [_]) => Int(clock_nanos(libc::CLOCK_MONOTONIC)),
+
+            ("seed_random", [Int(seed)]) => {
+                self.random.set(*seed as u64);
+                Unit
+            }
+            ("random_int", [Int(lo), Int(hi)]) => {
+                if hi <= lo {
+                    return panic(std::string::String::from("random range is empty"));
+                }
+
+                // The bits are scaled into the range the same way as in the runtime library
+                let range = hi.wrapping_sub(*lo) as u64;
+                let offset = (self.next_random() as u128 * range as u128) >> 64;
+                Int(lo.wrapping_add(offset as i64))
+            }
+            ("random_float", [_]) => Float((self.next_random() >> 11) as f64 / 9007199254740992.0),
+
+            ("exit", [Int(c)]) => return Err(InterpreterError::Exit(*c as i32)),
+            ("run_command", [String(c)]) => {
+                if c.contains('\0') {
+                    Value::err("command contains a null byte")
+                } else {
+                    match Command::new("/bin/sh").arg("-c").arg(&**c).status() {
+                        Ok(s) => Result(Ok(Box::new(Int(match s.signal() {
+                            Some(sig) => 128 + sig as i64,
+                            None => s.code().unwrap_or(0) as i64,
+                        })))),
+                        Err(_) => Value::err("could not create process"),
+                    }
+                }
+            }
+
+            ("ok", [v]) => Result(Ok(Box::new(v.clone()))),
+            ("err", [String(e)]) => Result(Err(e.clone())),
+            ("is_ok", [Result(r)]) => Bool(r.is_ok()),
+            ("is_err", [Result(r)]) => Bool(r.is_err()),
+            ("unwrap", [Result(r)]) => match r {
+                Ok(v) => (**v).clone(),
+                Err(e) => return panic((**e).clone()),
+            },
+            ("unwrap_or", [Result(r), d]) => match r {
+                Ok(v) => (**v).clone(),
+                Err(_) => d.clone(),
+            },
+            ("error", [Result(r)]) => match r {
+                Ok(_) => Value::string(""),
+                Err(e) => String(e.clone()),
+            },
+
+            ("true", []) => Bool(true),
+            ("false", []) => Bool(false),
+
+            ("some", [v]) => Option(Some(Box::new(v.clone()))),
+            ("none", []) => Option(None),
+            ("is_some", [Option(o)]) => Bool(o.is_some()),
+            ("is_none", [Option(o)]) => Bool(o.is_none()),
+
+            ("array_make", [Int(n), v]) => {
+                if *n < 0 {
+                    return panic(std::string::String::from("negative array length"));
+                }
+                Array(Rc::new(vec![v.clone(); *n as usize]))
+            }
+            ("array_of", [v]) => Array(Rc::new(vec![v.clone()])),
+            ("array_get", [Array(a), Int(i)]) => {
+                if *i < 0 || *i as usize >= a.len() {
+                    return panic(out_of_bounds(a.len(), *i));
+                }
+                a[*i as usize].clone()
+            }
+            ("array_set", [Array(a), Int(i), v]) => {
+                if *i < 0 || *i as usize >= a.len() {
+                    return panic(out_of_bounds(a.len(), *i));
+                }
+                let mut a = a.clone();
+                Rc::make_mut(&mut a)[*i as usize] = v.clone();
+                Array(a)
+            }
+            ("array_push", [Array(a), v]) => {
+                let mut a = a.clone();
+                Rc::make_mut(&mut a).push(v.clone());
+                Array(a)
+            }
+            ("array_length", [Array(a)]) => Int(a.len() as i64),
+            ("array_contains", [Array(a), v]) => Bool(a.contains(v)),
+
+            ("map_new", [Int(_)]) => Map(Rc::new(vec![])),
+            ("map_insert", [Map(m), k, v]) => {
+                let mut m = m.clone();
+                let entries = Rc::make_mut(&mut m);
+                match entries.iter_mut().find(|(key, _)| key == k) {
+                    Some(entry) => entry.1 = v.clone(),
+                    None => entries.push((k.clone(), v.clone())),
+                }
+                Map(m)
+            }
+            ("map_get", [Map(m), k]) => match m.iter().find(|(key, _)| key == k) {
+                Some((_, v)) => Result(Ok(Box::new(v.clone()))),
+                None => Value::err("key not found"),
+            },
+            ("map_remove", [Map(m), k]) => {
+                let mut m = m.clone();
+                Rc::make_mut(&mut m).retain(|(key, _)| key != k);
+                Map(m)
+            }
+            ("map_contains", [Map(m), k]) => Bool(m.iter().any(|(key, _)| key == k)),
+            ("map_length", [Map(m)]) => Int(m.len() as i64),
+            ("map_keys", [Map(m)]) => Array(Rc::new(m.iter().map(|(k, _)| k.clone()).collect())),
+            ("map_values", [Map(m)]) => Array(Rc::new(m.iter().map(|(_, v)| v.clone()).collect())),
+
+            ("spawn", [f]) => Thread(Box::new(self.apply(f.clone(), vec![Unit])?)),
+            ("join", [Thread(v)]) => (**v).clone(),
+            ("async", [f]) => Task(Box::new(self.apply(f.clone(), vec![Unit])?)),
+            ("await", [Task(v)]) => (**v).clone(),
+            ("yield", [_]) => Unit,
+            ("sleep", [Int(ms)]) => {
+                thread::sleep(Duration::from_millis((*ms).max(0) as u64));
+                Unit
+            }
+
+            ("assert", [Bool(c), String(m)]) => {
+                if !c {
+                    return Err(InterpreterError::AssertionFailed(
+                        (**m).clone(),
+                        module.location_string(loc),
+                        module.source_string(&sexprs[0].get_metadata().loc),
+                    ));
+                }
+                Unit
+            }
+            ("panic", [String(m)]) => return panic((**m).clone()),
+
+            _ => {
+                return Err(InterpreterError::Unsupported(format!(
+                    "intrinsic `{}` with these arguments",
+                    name
+                )))
+            }
+        };
+
+        Ok(v)
+    }
+}
+
+/// Applies an infix operator to two values of the same type, returning the panic message
+/// if the operation fails. Integer arithmetic wraps unless overflow is checked, and shift amounts
+/// are taken modulo 64, the same as in the runtime library.
+fn infix(op: BinOp, l: Value, r: Value, overflow_checks: bool) -> Result<Value, &'static str> {
+    use Value::*;
+
+    let v = match (op, l, r) {
+        (BinOp::Div, Int(_), Int(0)) | (BinOp::Mod, Int(_), Int(0)) => {
+            return Err("attempt to divide by zero")
+        }
+
+        (op, SizedInt(a, size), SizedInt(b, _)) if !op.is_comparison() => {
+            return sized_infix(op, size.value(a), size.value(b), size, overflow_checks)
+        }
+
+        (BinOp::Mul, Int(a), Int(b)) if overflow_checks => Int(a
+            .checked_mul(b)
+            .ok_or("attempt to multiply with overflow")?),
+        (BinOp::Div, Int(a), Int(b)) if overflow_checks => {
+            Int(a.checked_div(b).ok_or("attempt to divide with overflow")?)
+        }
+        (BinOp::Add, Int(a), Int(b)) if overflow_checks => {
+            Int(a.checked_add(b).ok_or("attempt to add with overflow")?)
+        }
+        (BinOp::Sub, Int(a), Int(b)) if overflow_checks => Int(a
+            .checked_sub(b)
+            .ok_or("attempt to subtract with overflow")?),
+
+        (BinOp::Mul, Int(a), Int(b)) => Int(a.wrapping_mul(b)),
+        (BinOp::Div, Int(a), Int(b)) => Int(a.wrapping_div(b)),
+        (BinOp::Mod, Int(a), Int(b)) => Int(a.wrapping_rem(b)),
+        (BinOp::Add, Int(a), Int(b)) => Int(a.wrapping_add(b)),
+        (BinOp::Sub, Int(a), Int(b)) => Int(a.wrapping_sub(b)),
+        (BinOp::LShift, Int(a), Int(b)) => Int(a.wrapping_shl(b as u32)),
+        (BinOp::RShift, Int(a), Int(b)) => Int(a.wrapping_shr(b as u32)),
+        (BinOp::BitAnd, Int(a), Int(b)) => Int(a & b),
+        (BinOp::BitXor, Int(a), Int(b)) => Int(a ^ b),
+        (BinOp::BitOr, Int(a), Int(b)) => Int(a | b),
+
+        (BinOp::Mul, Float(a), Float(b)) => Float(a * b),
+        (BinOp::Div, Float(a), Float(b)) => Float(a / b),
+        (BinOp::Add, Float(a), Float(b)) => Float(a + b),
+        (BinOp::Sub, Float(a), Float(b)) => Float(a - b),
+
+        (BinOp::Xor, Bool(a), Bool(b)) => Bool(a != b),
+
+        (BinOp::LT, a, b) => Bool(compare(&a, &b) == Some(Ordering::Less)),
+        (BinOp::GT, a, b) => Bool(compare(&a, &b) == Some(Ordering::Greater)),
+        (BinOp::LEQ, a, b) => Bool(matches!(
+            compare(&a, &b),
+            Some(Ordering::Less | Ordering::Equal)
+        )),
+        (BinOp::GEQ, a, b) => Bool(matches!(
+            compare(&a, &b),
+            Some(Ordering::Greater | Ordering::Equal)
+        )),
+        (BinOp::EQ, a, b) => Bool(equal(&a, &b)),
+        (BinOp::NEQ, a, b) => Bool(!equal(&a, &b)),
+
+        _ => unreachable!("the type checker only allows operators on values they support"),
+    };
+    Ok(v)
+}
+
+/// Returns true if two values of the same type have the same contents, the same as the equality
+/// functions derived for their types in compiled code. Maps are equal if they have the same
+/// entries, whatever order they were inserted in.
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| equal(a, b))
+        }
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, v)| b.iter().any(|(l, w)| equal(k, l) && equal(v, w)))
+        }
+        (Value::Result(Ok(a)), Value::Result(Ok(b))) => equal(a, b),
+        (Value::Option(Some(a)), Value::Option(Some(b))) => equal(a, b),
+        (Value::Data(c, a), Value::Data(d, b)) => {
+            c == d && a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| equal(a, b))
+        }
+        _ => a == b,
+    }
+}
+
+/// Applies an arithmetic or bitwise operator to the values of two ints of a sized int type. The
+/// result wraps around into the range of the type unless overflow is checked, and shift amounts
+/// are taken modulo 64, the same as in the runtime library.
+fn sized_infix(
+    op: BinOp,
+    a: i128,
+    b: i128,
+    size: IntSize,
+    overflow_checks: bool,
+) -> Result<Value, &'static str> {
+    if matches!(op, BinOp::Div | BinOp::Mod) && b == 0 {
+        return Err("attempt to divide by zero");
+    }
+
+    // The product of two large unsigned 64 bit ints is beyond even 128 bits
+    let exact = match op {
+        BinOp::Mul => a.checked_mul(b),
+        BinOp::Div => Some(a / b),
+        BinOp::Mod => Some(a % b),
+        BinOp::Add => Some(a + b),
+        BinOp::Sub => Some(a - b),
+        BinOp::LShift => Some(a << (b & 63)),
+        BinOp::RShift => Some(a >> (b & 63)),
+        BinOp::BitAnd => Some(a & b),
+        BinOp::BitXor => Some(a ^ b),
+        BinOp::BitOr => Some(a | b),
+        _ => unreachable!("comparisons are applied to the values themselves"),
+    };
+
+    if overflow_checks && !matches!(exact, Some(n) if size.fits(n)) {
+        match op {
+            BinOp::Mul => return Err("attempt to multiply with overflow"),
+            BinOp::Div => return Err("attempt to divide with overflow"),
+            BinOp::Add => return Err("attempt to add with overflow"),
+            BinOp::Sub => return Err("attempt to subtract with overflow"),
+            _ => (),
+        }
+    }
+    Ok(Value::int(exact.unwrap_or_else(|| a.wrapping_mul(b)), size))
+}
+
+/// Compares two ints, floats, or chars. Floats are unordered if either is NaN.
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Int(a), Value::Int(b)) => a.partial_cmp(b),
+        (Value::SizedInt(a, size), Value::SizedInt(b, _)) => {
+            size.value(*a).partial_cmp(&size.value(*b))
+        }
+        (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+        (Value::Char(a), Value::Char(b)) => a.partial_cmp(b),
+        _ => None,
+    }
+}
+
+/// Matches a value against a pattern, adding the values it binds to the list of bound values.
+fn match_pattern(pattern: &Pattern, value: &Value, bound: &mut Vec<Value>) -> bool {
+    match (pattern, value) {
+        (Pattern::Wildcard, _) => true,
+
+        (Pattern::Binding(_), v) => {
+            bound.push(v.clone());
+            true
+        }
+
+        (Pattern::Constructor(c, fields), Value::Data(name, values)) if c == &**name => fields
+            .iter()
+            .zip(values.iter())
+            .all(|(p, v)| match_pattern(p, v, bound)),
+
+        (Pattern::Constructor(c, fields), Value::Result(Ok(v))) if c == "ok" => {
+            match_pattern(&fields[0], v, bound)
+        }
+
+        (Pattern::Constructor(c, fields), Value::Result(Err(e))) if c == "err" => {
+            match_pattern(&fields[0], &Value::String(e.clone()), bound)
+        }
+
+        (Pattern::Constructor(c, fields), Value::Option(Some(v))) if c == "some" => {
+            match_pattern(&fields[0], v, bound)
+        }
+
+        (Pattern::Constructor(c, _), Value::Option(None)) => c == "none",
+
+        (Pattern::Constructor(c, _), Value::Bool(b)) => (c == "true") == *b,
+
+        (Pattern::Int(n), Value::Int(v)) => n == v,
+        (Pattern::Int(n), Value::SizedInt(v, size)) => size.value(*v) == *n as i128,
+        (Pattern::Char(c), Value::Char(v)) => c == v,
+        (Pattern::String(s), Value::String(v)) => s == &**v,
+
+        _ => false,
+    }
+}
+
+/// Returns the panic message for an out of bounds array access.
+fn out_of_bounds(length: usize, index: i64) -> String {
+    format!(
+        "index out of bounds: the length is {} but the index is {}",
+        length, index
+    )
+}
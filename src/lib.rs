@@ -6,53 +6,76 @@
 /// emitting code.
 pub mod backends;
 
+/// Module for driving compilation from other Rust programs. This module contains the `Compiler`,
+/// which runs each stage of the compiler and returns its output along with the diagnostics it
+/// reported.
+pub mod compiler;
+
+/// Module for diagnostics. This module contains the diagnostics reported for errors and warnings
+/// found while compiling, and the renderer that prints them with the source they point at.
+pub mod diagnostics;
+
+/// Module for generating documentation. This module renders the doc comments of the definitions in
+/// a module, along with their declarations and signatures, as Markdown or HTML.
+pub mod docs;
+
+/// Module for fuzzing the backends, enabled by the `fuzzing` feature. This module contains
+/// proptest strategies for well typed programs and a harness that checks that the interpreter and
+/// compiled code run them the same way.
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+
 /// Module that contains helper functions transforming the source text into higher level
 /// intermediate representation. This includes functions for parsing, functions for transforming
 /// the text into IR, and functions for checking the correctness of IR.
 pub mod frontend;
 
-use codespan_reporting::diagnostic::{Diagnostic, Label};
-use codespan_reporting::files::SimpleFiles;
-use codespan_reporting::term;
-use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+/// Module for interpreting the higher level intermediate representation directly. This is used by
+/// the REPL so that lines can be evaluated without generating code.
+pub mod interpreter;
+
+/// Module for linking. This module contains functions for turning generated code into executables
+/// with the system toolchain and for describing its errors.
+pub mod linker;
+
+/// Module for compiler options. This module contains the options that control compilation and
+/// functions for reading them from the configuration file, the environment, and the command line.
+pub mod options;
+
+/// Module for projects. This module contains the description of a project read from the
+/// configuration file, and functions for finding its source files and where its artefacts go.
+pub mod project;
+
+use codespan_reporting::diagnostic::{Label, Severity};
 use logos::Span;
-use std::collections::HashMap;
 
-use crate::frontend::ir::{self, Ir, IrError};
+use crate::diagnostics::{Diagnostic, Renderer};
+use crate::frontend::exhaustive;
+use crate::frontend::ir::{self, Ir};
+use crate::frontend::lints;
 use crate::frontend::parser;
+use crate::frontend::prelude;
+use crate::options::LintLevels;
 
-/// Determines whether the compiler should output debug information or not.
-static DEBUG: bool = false;
+pub use crate::compiler::{Compiler, Output};
 
-/// The return type of check<>().
-pub type Res<'a> = Result<
-    (Vec<Diagnostic<usize>>, SimpleFiles<&'a String, String>),
-    (Vec<Diagnostic<usize>>, SimpleFiles<&'a String, String>),
->;
+/// The return type of check().
+pub type Res = Result<(Vec<Diagnostic>, Renderer), (Vec<Diagnostic>, Renderer)>;
 
 /// Checks whether given code is valid.
-pub fn check<'a>(
-    filenames: &'a [(String, bool)],
+pub fn check(
+    filenames: &[(String, bool)],
     codes: &[String],
     ir: &mut Ir,
     _require_main: bool,
     emit: bool,
-) -> Res<'a> {
-    // Set up codespan
-    let mut files = SimpleFiles::new();
-    let mut file_hash = HashMap::new();
-    for file in filenames.iter().enumerate() {
-        file_hash.insert(&file.1 .0, files.add(&file.1 .0, codes[file.0].clone()));
-    }
-    let file_hash = file_hash;
-
-    let writer = StandardStream::stderr(ColorChoice::Auto);
-    let config = term::Config::default();
+) -> Res {
+    let mut files = Renderer::new();
     let mut diagnostics = Vec::new();
     let mut fail = false;
 
     for (file, code) in filenames.iter().zip(codes.iter()) {
-        let file_id = *file_hash.get(&file.0).unwrap();
+        let file_id = files.add_file(&file.0, code);
 
         if let Some(start) = code.find("uwu") {
             let loc = Span {
@@ -63,7 +86,7 @@ pub fn check<'a>(
                 .with_message("owo")
                 .with_labels(vec![Label::primary(file_id, loc).with_message("nya")]);
             if emit {
-                term::emit(&mut writer.lock(), &config, &files, &diagnostic).unwrap();
+                files.emit(&diagnostic);
             }
             diagnostics.push(diagnostic);
         }
@@ -75,134 +98,49 @@ pub fn check<'a>(
         } else {
             let ast = match parser::parse(code) {
                 Ok(v) => v,
-                Err(e) => {
-                    let diagnostic = Diagnostic::error()
-                        .with_message(&e.msg)
-                        .with_labels(vec![Label::primary(file_id, e.span)]);
-                    if emit {
-                        term::emit(&mut writer.lock(), &config, &files, &diagnostic).unwrap();
+                Err(errors) => {
+                    for e in errors.iter() {
+                        let diagnostic = diagnostics::parse_diagnostic(e, file_id);
+                        if emit {
+                            files.emit(&diagnostic);
+                        }
+                        diagnostics.push(diagnostic);
                     }
-                    diagnostics.push(diagnostic);
                     return Err((diagnostics, files));
                 }
             };
 
-            // Print out the ast
-            if DEBUG {
-                println!("{:#?}", &ast);
-            }
-            match ir::convert_ast_to_ir(&file.0, code, ast, ir) {
-                Ok(_) if DEBUG => {
-                    dbg!(&ir);
-                }
-                Ok(_) => (),
-                Err(e) => {
-                    for e in e {
-                        let mut diagnostic = Diagnostic::error();
-                        match e {
-                            IrError::InvalidType(s) => {
-                                diagnostic = diagnostic
-                                    .with_message("Invalid type used")
-                                    .with_labels(vec![Label::primary(
-                                        *file_hash.get(&s.filename).unwrap(),
-                                        s.span,
-                                    )
-                                    .with_message("Undeclared type")])
-                            }
-
-                            IrError::DuplicateTypeInUnion(s1, s2, t) => {
-                                diagnostic = diagnostic
-                                    .with_message("Duplicate type in union type declaration")
-                                    .with_labels(vec![
-                                        Label::secondary(
-                                            *file_hash.get(&s1.filename).unwrap(),
-                                            s1.span,
-                                        )
-                                        .with_message("Type used here first"),
-                                        Label::primary(
-                                            *file_hash.get(&s2.filename).unwrap(),
-                                            s2.span,
-                                        )
-                                        .with_message(
-                                            format!("Type `{}` used a second time here", t),
-                                        ),
-                                    ])
-                            }
-
-                            IrError::DoubleExport(s1, s2, e) => {
-                                diagnostic = diagnostic
-                                    .with_message("Value exported twice")
-                                    .with_labels(vec![
-                                        Label::secondary(
-                                            *file_hash.get(&s1.filename).unwrap(),
-                                            s1.span,
-                                        )
-                                        .with_message("Value exported here first"),
-                                        Label::primary(
-                                            *file_hash.get(&s2.filename).unwrap(),
-                                            s2.span,
-                                        )
-                                        .with_message(
-                                            format!("Value {} exported a second time here", e),
-                                        ),
-                                    ])
-                            }
-
-                            IrError::RedefineImportAlias(s1, s2, a) => {
-                                diagnostic = diagnostic
-                                    .with_message("Alias defined twice")
-                                    .with_labels(vec![
-                                        Label::secondary(
-                                            *file_hash.get(&s1.filename).unwrap(),
-                                            s1.span,
-                                        )
-                                        .with_message("Alias defined here first"),
-                                        Label::primary(
-                                            *file_hash.get(&s2.filename).unwrap(),
-                                            s2.span,
-                                        )
-                                        .with_message(
-                                            format!("Alias {} defined a second time here", a),
-                                        ),
-                                    ])
-                            }
-
-                            IrError::UnsupportedAnnotation(s, a) => {
-                                diagnostic = diagnostic
-                                    .with_message("Unsupported annotation used")
-                                    .with_labels(vec![Label::primary(
-                                        *file_hash.get(&s.filename).unwrap(),
-                                        s.span,
-                                    )
-                                    .with_message(format!("Annotation {} is unsupported", a))])
-                            }
-
-                            IrError::InvalidFFIType(s, t) => {
-                                diagnostic = diagnostic
-                                    .with_message("Unsupported type used for FFI")
-                                    .with_labels(vec![Label::primary(
-                                        *file_hash.get(&s.filename).unwrap(),
-                                        s.span,
-                                    )
-                                    .with_message(format!("Type {} is unsupported by FFI", t))])
-                            }
-
-                            IrError::DuplicateModule(v, _t) => {
-                                diagnostic =
-                                    diagnostic.with_message(format!("Duplicate module `{}`", v))
-                            }
-                        }
-                        if emit {
-                            term::emit(&mut writer.lock(), &config, &files, &diagnostic).unwrap();
-                        }
-                        diagnostics.push(diagnostic);
-                        fail = true;
+            if let Err(e) = ir::convert_ast_to_ir(&file.0, code, prelude::parse(), ast, ir) {
+                for e in e {
+                    let diagnostic = diagnostics::ir_diagnostic(&e, &files);
+                    if emit {
+                        files.emit(&diagnostic);
                     }
+                    diagnostics.push(diagnostic);
+                    fail = true;
                 }
             }
         }
     }
 
+    // Lints are reported at their default levels
+    let levels = LintLevels::default();
+    let lints = lints::check_lints(ir);
+    let matches = exhaustive::check_matches(ir);
+    let lints = lints
+        .iter()
+        .filter_map(|w| diagnostics::lint_diagnostic(w, &files, &levels));
+    let matches = matches
+        .iter()
+        .filter_map(|e| diagnostics::match_diagnostic(e, &files, &levels));
+    for diagnostic in lints.chain(matches) {
+        if emit {
+            files.emit(&diagnostic);
+        }
+        fail |= diagnostic.severity == Severity::Error;
+        diagnostics.push(diagnostic);
+    }
+
     if fail {
         Err((diagnostics, files))
     } else {
@@ -0,0 +1,274 @@
+use goblin::archive::Archive;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output};
+use tracing::debug;
+
+use crate::options::{Allocator, CompilerOptions};
+
+/// Represents an error in turning generated code into an executable.
+#[derive(Debug)]
+pub enum LinkError {
+    /// A tool could not be started. The fields are the tool, the option that names it if any, and
+    /// the error from starting it.
+    MissingTool(String, Option<&'static str>, io::Error),
+
+    /// `llc` rejected the generated LLVM IR, with the first error it reported.
+    InvalidLlvmIr(String),
+
+    /// `llc` is from a version of LLVM that cannot read opaque pointers, which the LLVM backend
+    /// generates. The field is the major version.
+    OldLlvm(u32),
+
+    /// The runtime library does not exist at the given path.
+    MissingRuntime(String),
+
+    /// The program uses functions that neither the runtime library nor the libraries in
+    /// `link_flags` define.
+    UndefinedFunctions(Vec<String>),
+
+    /// The linker failed for any other reason, with the errors it reported.
+    LinkerFailed(String),
+
+    /// The runtime library could not be loaded into the compiler, with the error from loading it.
+    LoadFailed(String),
+
+    /// A temporary file could not be written.
+    Io(String, io::Error),
+}
+
+impl Display for LinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkError::MissingTool(t, Some(o), e) => write!(
+                f,
+                "could not run `{}`: {}; set the `{}` option to the one to use",
+                t, e, o
+            ),
+            LinkError::MissingTool(t, None, e) => write!(
+                f,
+                "could not run `{}`: {}; LLVM must be installed to build with the LLVM backend",
+                t, e
+            ),
+            LinkError::InvalidLlvmIr(e) => write!(
+                f,
+                "the generated LLVM IR is invalid, which is a bug in the compiler: {}",
+                e
+            ),
+            LinkError::OldLlvm(v) => write!(
+                f,
+                "`llc` is from LLVM {}, which cannot compile the generated LLVM IR; LLVM {} or later is needed",
+                v, OPAQUE_POINTERS_OPTION_VERSION
+            ),
+            LinkError::MissingRuntime(p) => write!(
+                f,
+                "the runtime library was not found at {}; build it with `make` in lib/ or set the `runtime` option to its path",
+                p
+            ),
+            LinkError::UndefinedFunctions(v) => {
+                let v: Vec<_> = v.iter().map(|v| format!("`{}`", v)).collect();
+                write!(
+                    f,
+                    "undefined functions {}; the runtime library may be out of date, or the library defining an external function is missing from `link_flags`",
+                    v.join(", ")
+                )
+            }
+            LinkError::LinkerFailed(e) => write!(f, "linking failed:\n{}", e),
+            LinkError::LoadFailed(e) => write!(f, "could not load the runtime library: {}", e),
+            LinkError::Io(p, e) => write!(f, "error writing {}: {}", p, e),
+        }
+    }
+}
+
+/// The first version of LLVM that reads opaque pointers when asked to with `-opaque-pointers`.
+const OPAQUE_POINTERS_OPTION_VERSION: u32 = 14;
+
+/// The first version of LLVM that reads opaque pointers by default.
+const OPAQUE_POINTERS_VERSION: u32 = 15;
+
+/// Returns a path in the temporary directory for an intermediate file with the given extension.
+/// Paths are unique to the process.
+pub fn temp_path(ext: &str) -> PathBuf {
+    env::temp_dir().join(format!("closey-{}.{}", process::id(), ext))
+}
+
+/// Runs a tool, returning its output.
+fn run(
+    command: &mut Command,
+    tool: &str,
+    option: Option<&'static str>,
+) -> Result<Output, LinkError> {
+    debug!(?command, "running");
+    command
+        .output()
+        .map_err(|e| LinkError::MissingTool(String::from(tool), option, e))
+}
+
+/// Returns the major version of LLVM that `llc` is from, or None if `llc` does not print it in the
+/// usual form, such as `LLVM version 14.0.6`.
+fn llc_version() -> Result<Option<u32>, LinkError> {
+    let output = run(Command::new("llc").arg("--version"), "llc", None)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .split_once("LLVM version ")
+        .and_then(|(_, rest)| rest.split('.').next())
+        .and_then(|v| v.trim().parse().ok()))
+}
+
+/// Compiles LLVM IR into an object file with `llc`. The IR uses opaque pointers, so LLVM 14 is asked
+/// to read them, and earlier versions are reported as too old.
+pub fn compile_llvm_ir(
+    ir: &str,
+    object: &Path,
+    options: &CompilerOptions,
+) -> Result<(), LinkError> {
+    let mut command = Command::new("llc");
+    match llc_version()? {
+        Some(v) if v < OPAQUE_POINTERS_OPTION_VERSION => return Err(LinkError::OldLlvm(v)),
+        Some(v) if v < OPAQUE_POINTERS_VERSION => {
+            command.arg("-opaque-pointers");
+        }
+        _ => (),
+    }
+
+    let ir_path = temp_path("ll");
+    fs::write(&ir_path, ir).map_err(|e| LinkError::Io(ir_path.display().to_string(), e))?;
+
+    let output = run(
+        command
+            .arg(format!("-O{}", options.opt_level))
+            .arg("-filetype=obj")
+            .arg("-relocation-model=pic")
+            .arg("-o")
+            .arg(object)
+            .arg(&ir_path),
+        "llc",
+        None,
+    );
+    let _ = fs::remove_file(&ir_path);
+
+    let output = output?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        // Only the first error is useful, since the rest usually follow from it
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let first = stderr
+            .lines()
+            .find(|v| v.contains("error"))
+            .unwrap_or_else(|| stderr.trim());
+        Err(LinkError::InvalidLlvmIr(String::from(first)))
+    }
+}
+
+/// Archives an object file into a static library with `llvm-ar`, replacing the library if it
+/// exists.
+pub fn archive(object: &Path, output: &str) -> Result<(), LinkError> {
+    let _ = fs::remove_file(output);
+    let result = run(
+        Command::new("llvm-ar").arg("rcs").arg(output).arg(object),
+        "llvm-ar",
+        None,
+    )?;
+    if result.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        Err(LinkError::LinkerFailed(String::from(stderr.trim())))
+    }
+}
+
+/// Returns the names of the undefined functions in the errors reported by a linker. GNU ld reports
+/// them as ``undefined reference to `name'``, and lld as `undefined symbol: name`.
+fn undefined_functions(stderr: &str) -> Vec<String> {
+    let mut names = vec![];
+    for line in stderr.lines() {
+        let name = match line.split_once("undefined reference to `") {
+            Some((_, rest)) => rest.split('\'').next(),
+            None => line
+                .split_once("undefined symbol: ")
+                .map(|(_, rest)| rest.trim()),
+        };
+
+        if let Some(name) = name {
+            if !names.iter().any(|v| v == name) {
+                names.push(String::from(name));
+            }
+        }
+    }
+    names
+}
+
+/// Links an object file with the runtime library into an executable. The libraries the allocator
+/// needs are linked as well, followed by `link_flags`.
+pub fn link(object: &Path, output: &str, options: &CompilerOptions) -> Result<(), LinkError> {
+    if !Path::new(&options.runtime).exists() {
+        return Err(LinkError::MissingRuntime(options.runtime.clone()));
+    }
+
+    let mut command = Command::new(&options.linker);
+    command
+        .arg("-o")
+        .arg(output)
+        .arg(object)
+        .arg(&options.runtime);
+    match options.alloc {
+        Allocator::System => {
+            command.arg("-lc");
+        }
+        Allocator::Boehm => {
+            command.arg("-lgc");
+        }
+        Allocator::Rc | Allocator::Custom => (),
+    }
+    command.args(options.link_flags.split_whitespace());
+    run_linker(&mut command, options)
+}
+
+/// Links the runtime library into a shared library, which the JIT loads to call runtime functions.
+/// Every member of the runtime is linked except those of the allocators, which need libraries of
+/// their own, so JIT compiled code allocates with the heap built into the runtime.
+pub fn link_shared_runtime(output: &Path, options: &CompilerOptions) -> Result<(), LinkError> {
+    let buffer = fs::read(&options.runtime)
+        .map_err(|_| LinkError::MissingRuntime(options.runtime.clone()))?;
+    let archive = Archive::parse(&buffer)
+        .map_err(|e| LinkError::LinkerFailed(format!("{}: {}", options.runtime, e)))?;
+    let allocators: Vec<_> = [Allocator::System, Allocator::Boehm, Allocator::Custom]
+        .iter()
+        .filter_map(|v| v.init_func())
+        .collect();
+
+    // Symbols are bound within the library, so its system calls are not replaced by libc's
+    let mut command = Command::new(&options.linker);
+    command
+        .args(["-shared", "-Bsymbolic", "-z", "noexecstack", "-o"])
+        .arg(output);
+    for (_, _, symbols) in archive.summarize() {
+        if !symbols.iter().any(|v| allocators.contains(v)) {
+            for symbol in symbols {
+                command.arg("-u").arg(symbol);
+            }
+        }
+    }
+    command.arg(&options.runtime);
+    run_linker(&mut command, options)
+}
+
+/// Runs the linker, turning the errors it reports into a `LinkError`.
+fn run_linker(command: &mut Command, options: &CompilerOptions) -> Result<(), LinkError> {
+    let result = run(command, &options.linker, Some("linker"))?;
+    if result.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    let undefined = undefined_functions(&stderr);
+    if undefined.is_empty() {
+        Err(LinkError::LinkerFailed(String::from(stderr.trim())))
+    } else {
+        Err(LinkError::UndefinedFunctions(undefined))
+    }
+}
@@ -1,19 +1,40 @@
-use clap::{crate_version, App, Arg, SubCommand};
-use faerie::{ArtifactBuilder, Decl, Link};
-use goblin::Object;
+use clap::{crate_version, App, Arg, ArgMatches, SubCommand};
+use codespan_reporting::diagnostic::Severity;
+use faerie::{ArtifactBuilder, Decl, Link, Reloc, SectionKind};
 use rustyline::{error::ReadlineError, Editor};
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::ffi::{CStr, CString};
 use std::fs::{self, File};
+use std::path::Path;
 use std::process::exit;
 use target_lexicon::Triple;
+use tracing_subscriber::EnvFilter;
 
 #[allow(unused_imports)]
 use closeyc::backends::{
-    aarch64, ir as backend_ir, riscv64, wasm64, x86_64, GeneratedCode, DEFAULT_ARCH,
+    self as backends, aarch64,
+    cache::{self, CodegenCache},
+    dwarf, ir as backend_ir, llvm, riscv64, wasm64, x86_64, GeneratedCode, DEFAULT_ARCH,
 };
+use closeyc::diagnostics::{self, Renderer};
+use closeyc::docs::{self, DocFormat};
 use closeyc::frontend::correctness;
+use closeyc::frontend::exhaustive;
+use closeyc::frontend::fold;
 use closeyc::frontend::ir as frontend_ir;
-use closeyc::frontend::parser;
+use closeyc::frontend::lints;
+use closeyc::frontend::parser::{self, Ast};
+use closeyc::frontend::prelude;
+use closeyc::frontend::pretty;
+use closeyc::frontend::specialise;
+use closeyc::frontend::uncurry;
+use closeyc::interpreter::{Interpreter, InterpreterError};
+use closeyc::linker::{self, LinkError};
+use closeyc::options::{
+    Arch, Backend, CompilerOptions, EmitKind, OptionSource, OptionsError, CONFIG_FILE, OPTION_NAMES,
+};
+use closeyc::project::Project;
 
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 static MAP_JIT: i32 = 0x0800;
@@ -29,6 +50,58 @@ enum CloseyCode<'a> {
     None,
     Exec(&'a str),
     Files(Vec<&'a str>),
+    Project,
+}
+
+/// The runtime library, loaded into the compiler so that JIT compiled code can call it.
+struct Runtime {
+    handle: *mut libc::c_void,
+}
+
+impl Runtime {
+    /// Links the runtime library into a shared library and loads it.
+    fn load(options: &CompilerOptions) -> Result<Runtime, LinkError> {
+        let path = linker::temp_path("so");
+        linker::link_shared_runtime(&path, options)?;
+
+        // The library stays loaded after its file is removed
+        let name = CString::new(path.display().to_string()).unwrap();
+        let handle = unsafe { libc::dlopen(name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        let _ = fs::remove_file(&path);
+        if handle.is_null() {
+            let error = unsafe { CStr::from_ptr(libc::dlerror()) };
+            return Err(LinkError::LoadFailed(error.to_string_lossy().into_owned()));
+        }
+        Ok(Runtime { handle })
+    }
+
+    /// Returns the address of a function in the runtime library.
+    fn symbol(&self, name: &str) -> Option<usize> {
+        let name = CString::new(name).ok()?;
+        let addr = unsafe { libc::dlsym(self.handle, name.as_ptr()) };
+        if addr.is_null() {
+            None
+        } else {
+            Some(addr as usize)
+        }
+    }
+
+    /// Adds stubs to code for the runtime functions it calls.
+    fn generate_stubs(&self, code: &mut GeneratedCode) -> Result<(), LinkError> {
+        let missing = match DEFAULT_ARCH {
+            "aarch64" => todo!(),
+            "riscv64" => todo!(),
+            "wasm64" => todo!(),
+            "x86_64" => x86_64::codegen::generate_runtime_stubs(code, |v| self.symbol(v)),
+            _ => panic!("unsupported architecture!"),
+        };
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(LinkError::UndefinedFunctions(missing))
+        }
+    }
 }
 
 struct Jit {
@@ -37,7 +110,8 @@ struct Jit {
 }
 
 impl Jit {
-    fn new(mut code: GeneratedCode) -> Jit {
+    fn new(mut code: GeneratedCode, runtime: &Runtime) -> Result<Jit, LinkError> {
+        runtime.generate_stubs(&mut code)?;
         let mem = unsafe {
             libc::mmap(
                 std::ptr::null_mut(),
@@ -68,7 +142,7 @@ impl Jit {
             pthread_jit_write_protect_np(true);
         }
 
-        Jit { code, mem }
+        Ok(Jit { code, mem })
     }
 
     unsafe fn call(&self, func: &str) -> Option<*const u8> {
@@ -84,6 +158,121 @@ impl Drop for Jit {
     }
 }
 
+/// The size of the executable memory the REPL places compiled lines in.
+const JIT_ARENA_SIZE: usize = 64 << 20;
+
+/// Executable memory that the REPL places the code of each line in, after the code of the lines
+/// before it. Code is relocated against the functions already placed, so functions from earlier
+/// lines are called where they are rather than compiled again.
+struct JitArena {
+    mem: *mut u8,
+    len: usize,
+    funcs: HashMap<String, usize>,
+    runtime: Runtime,
+}
+
+impl JitArena {
+    fn new(runtime: Runtime) -> JitArena {
+        let mem = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                JIT_ARENA_SIZE,
+                libc::PROT_WRITE | libc::PROT_READ,
+                libc::MAP_ANONYMOUS | libc::MAP_PRIVATE | MAP_JIT,
+                -1,
+                0,
+            )
+        } as *mut u8;
+
+        JitArena {
+            mem,
+            len: 0,
+            funcs: HashMap::new(),
+            runtime,
+        }
+    }
+
+    /// Returns the names of the functions placed so far.
+    fn compiled(&self) -> HashSet<String> {
+        self.funcs.keys().cloned().collect()
+    }
+
+    /// Relocates code and places it after the code placed so far, with stubs for the runtime
+    /// functions it calls.
+    fn place(&mut self, mut code: GeneratedCode) -> Result<(), String> {
+        self.runtime
+            .generate_stubs(&mut code)
+            .map_err(|e| e.to_string())?;
+        let offset = (self.len + 15) & !15;
+        if offset + code.len() > JIT_ARENA_SIZE {
+            return Err(String::from(
+                "the REPL has run out of memory for compiled code",
+            ));
+        }
+
+        match DEFAULT_ARCH {
+            "aarch64" => todo!(),
+            "riscv64" => todo!(),
+            "wasm64" => todo!(),
+            "x86_64" => x86_64::codegen::relocate_at(&mut code, offset, &self.funcs),
+            _ => panic!("unsupported architecture!"),
+        }
+
+        unsafe {
+            pthread_jit_write_protect_np(false);
+            libc::mprotect(
+                self.mem as *mut libc::c_void,
+                JIT_ARENA_SIZE,
+                libc::PROT_READ | libc::PROT_WRITE,
+            );
+            std::ptr::copy(code.data().as_ptr(), self.mem.add(offset), code.len());
+            libc::mprotect(
+                self.mem as *mut libc::c_void,
+                JIT_ARENA_SIZE,
+                libc::PROT_READ | libc::PROT_EXEC,
+            );
+            pthread_jit_write_protect_np(true);
+        }
+
+        for (name, range) in code.get_funcs() {
+            self.funcs.insert(name.clone(), offset + range.start);
+        }
+        self.len = offset + code.len();
+        Ok(())
+    }
+
+    unsafe fn call(&self, func: &str) -> Option<*const u8> {
+        self.funcs.get(func).map(|v| {
+            let f: unsafe extern "C" fn() -> *const u8 = std::mem::transmute(self.mem.add(v + 16));
+            f()
+        })
+    }
+
+    /// Shows a value with the show function placed for its type, which shows it the same way the
+    /// interpreter does.
+    unsafe fn show(&self, show: &str, value: *const u8) -> Option<String> {
+        self.funcs.get(show).map(|v| {
+            let f: unsafe extern "C" fn(*const u8) -> *const u8 =
+                std::mem::transmute(self.mem.add(v + 16));
+
+            // Strings are their length followed by their bytes, and strings in the code itself are
+            // not aligned
+            let s = f(value);
+            let len = (s as *const u64).read_unaligned() as usize;
+            let bytes = std::slice::from_raw_parts(s.add(8), len);
+            String::from_utf8_lossy(bytes).into_owned()
+        })
+    }
+}
+
+impl Drop for JitArena {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.mem as *mut libc::c_void, JIT_ARENA_SIZE);
+        }
+    }
+}
+
 fn main() {
     let files = Arg::with_name("files")
         .multiple(true)
@@ -98,9 +287,135 @@ fn main() {
         App::new("closeyc")
             .version(crate_version!())
             .about("Compiler for the Closey language.")
+            .arg(
+                Arg::with_name("arch")
+                    .long("arch")
+                    .help("The architecture to generate code for; by default this is the host")
+                    .global(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("backend")
+                    .long("backend")
+                    .help("The backend to generate code with, native or llvm")
+                    .global(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("opt_level")
+                    .long("opt-level")
+                    .short("O")
+                    .help("The optimisation level, from 0 to 3")
+                    .global(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("no_prelude")
+                    .long("no-prelude")
+                    .help("Does not include the prelude before the program")
+                    .global(true),
+            )
+            .arg(
+                Arg::with_name("warn_lossy")
+                    .long("warn-lossy")
+                    .help("Warns about implicit conversions that can lose precision")
+                    .global(true),
+            )
+            .arg(
+                Arg::with_name("overflow_checks")
+                    .long("overflow-checks")
+                    .help("Panics on int arithmetic that overflows instead of wrapping")
+                    .global(true),
+            )
+            .arg(
+                Arg::with_name("release")
+                    .long("release")
+                    .help("Leaves out runtime checks, such as for dividing by zero")
+                    .global(true),
+            )
+            .arg(
+                Arg::with_name("debug_info")
+                    .short("g")
+                    .long("debug-info")
+                    .help("Includes debug info so that debuggers can step through the program")
+                    .global(true),
+            )
+            .arg(
+                Arg::with_name("allow")
+                    .short("A")
+                    .long("allow")
+                    .help("Turns off lints, given as a comma separated list or `all`")
+                    .global(true)
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1),
+            )
+            .arg(
+                Arg::with_name("warn")
+                    .short("W")
+                    .long("warn")
+                    .help("Reports lints as warnings, given as a comma separated list or `all`")
+                    .global(true)
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1),
+            )
+            .arg(
+                Arg::with_name("deny")
+                    .short("D")
+                    .long("deny")
+                    .help("Reports lints as errors, given as a comma separated list or `all`")
+                    .global(true)
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1),
+            )
+            .arg(
+                Arg::with_name("alloc")
+                    .long("alloc")
+                    .help("The allocator for compiled programs: rc, system, boehm, or custom")
+                    .global(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("log")
+                    .long("log")
+                    .help("Filter for compiler logs, such as `debug` or `closeyc::backends=trace`")
+                    .global(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("linker")
+                    .long("linker")
+                    .help("The linker executables are linked with; by default this is ld")
+                    .global(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("link_flags")
+                    .long("link-flags")
+                    .help("Extra flags passed to the linker, separated by spaces")
+                    .global(true)
+                    .takes_value(true)
+                    .allow_hyphen_values(true),
+            )
+            .arg(
+                Arg::with_name("runtime")
+                    .long("runtime")
+                    .help("The path to libclosey.a; by default this is lib/bin/libclosey.a in the crate closeyc was built in")
+                    .global(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("emit")
+                    .long("emit")
+                    .help("Writes out intermediate representations while compiling, as a comma separated list of ast, ir, ssa, llvm, obj, or staticlib, each optionally followed by =path")
+                    .global(true)
+                    .takes_value(true),
+            )
             .subcommand(
                 SubCommand::with_name("build")
-                    .about("Builds Closey code and exports as an object file.")
+                    .about("Builds Closey code into an executable.")
                     .arg(
                         Arg::with_name("output")
                             .long("output")
@@ -109,7 +424,17 @@ fn main() {
                             .min_values(1)
                             .max_values(1),
                     )
-                    .arg(files.clone().help("The Closey files to compile."))
+                    .arg(
+                        Arg::with_name("compile_only")
+                            .short("c")
+                            .help("Only compiles to an object file, or LLVM IR with the LLVM backend, without linking"),
+                    )
+                    .arg(
+                        files
+                            .clone()
+                            .required(false)
+                            .help("The Closey files to compile; by default this is the project described by closey.toml"),
+                    )
                     .arg(exec.clone().help("A Closey command to compile.")),
             )
             .subcommand(
@@ -118,6 +443,12 @@ fn main() {
                     .arg(files.clone().help("The Closey files to run."))
                     .arg(exec.clone().help("A Closey command to run.")),
             )
+            .subcommand(
+                SubCommand::with_name("test")
+                    .about("Runs the tests in Closey code with the interpreter.")
+                    .arg(files.clone().help("The Closey files to test."))
+                    .arg(exec.clone().help("A Closey command to test.")),
+            )
             .subcommand(
                 SubCommand::with_name("analyse")
                     .alias("analyze")
@@ -135,6 +466,12 @@ fn main() {
                 SubCommand::with_name("assembly")
                     .alias("asm")
                     .about("Prints out the assembly for the given Closey code")
+                    .arg(
+                        Arg::with_name("syntax")
+                            .long("syntax")
+                            .help("The assembly syntax, intel or att; by default this is intel")
+                            .takes_value(true),
+                    )
                     .arg(
                         files
                             .clone()
@@ -145,6 +482,18 @@ fn main() {
                             .help("The Closey command to generate assembly for."),
                     ),
             )
+            .subcommand(
+                SubCommand::with_name("doc")
+                    .about("Prints the documentation of the given Closey code")
+                    .arg(
+                        Arg::with_name("format")
+                            .long("format")
+                            .help("The format of the documentation, markdown or html; by default this is markdown")
+                            .takes_value(true),
+                    )
+                    .arg(files.clone().help("The Closey files to document."))
+                    .arg(exec.clone().help("The Closey command to document.")),
+            )
             .subcommand(
                 SubCommand::with_name("llir")
                     .about("Prints out the low level IR for the given Closey code")
@@ -156,6 +505,14 @@ fn main() {
             ));
 
     let matches = app.get_matches();
+    let options = match get_options(&matches) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    };
+    init_logging(&options);
 
     let code = match matches.subcommand_name() {
         Some("repl") | None => CloseyCode::None,
@@ -164,11 +521,20 @@ fn main() {
             let matches = matches.subcommand_matches(s).unwrap();
             match matches.value_of("exec") {
                 Some(v) => CloseyCode::Exec(v),
-                None => CloseyCode::Files(matches.values_of("files").unwrap().collect()),
+                None => match matches.values_of("files") {
+                    Some(v) => CloseyCode::Files(v.collect()),
+                    None => CloseyCode::Project,
+                },
             }
         }
     };
 
+    // The file the code is in, which debug info refers to
+    let source_path = match &code {
+        CloseyCode::Files(v) => v[0],
+        _ => "Main",
+    };
+
     let contents = match code {
         CloseyCode::Exec(s) => Some(s.to_owned()),
         CloseyCode::Files(v) => match fs::read_to_string(v.first().unwrap()) {
@@ -178,320 +544,1335 @@ fn main() {
                 exit(1);
             }
         },
-        CloseyCode::None => None,
+        CloseyCode::Project | CloseyCode::None => None,
     };
 
     match matches.subcommand_name() {
         Some("analyse") => {
             let contents = contents.unwrap();
             let mut root = frontend_ir::Ir::new();
-            check(&contents, "Main", &mut root);
-            print!("{}", root);
+            check(&contents, "Main", &mut root, &options);
+
+            if matches
+                .subcommand_matches("analyse")
+                .unwrap()
+                .is_present("hlir")
+            {
+                print!("{}", root);
+            } else {
+                for module in root.modules() {
+                    for (global, func) in module.global_functions() {
+                        if func.loc.filename == prelude::FILENAME {
+                            continue;
+                        }
+
+                        println!("{}", pretty::signature_to_string(global, &func._type));
+                        println!(
+                            "{}\n",
+                            pretty::function_to_string(global, func, module, pretty::DEFAULT_WIDTH)
+                        );
+                    }
+                }
+            }
         }
 
         Some("assembly") => {
             let contents = contents.unwrap();
             let mut root = frontend_ir::Ir::new();
-            check(&contents, "Main", &mut root);
+            check(&contents, "Main", &mut root, &options);
 
             let mut module = backend_ir::convert_frontend_ir_to_backend_ir(
-                &root.modules.iter().next().unwrap().1,
+                root.modules().next().unwrap(),
+                backend_ir::Checks::new(&options),
             );
 
-            let mut code = match compile(&mut module) {
+            if options.backend == Backend::Llvm {
+                print!("{}", llvm::codegen::generate_code(&module));
+                return;
+            }
+
+            let mut code = match compile(&mut module, &options) {
                 Some(v) => v,
                 None => return,
             };
 
-            match DEFAULT_ARCH {
-                "aarch64" => todo!(),
-                "riscv64" => todo!(),
-                "wasm64" => todo!(),
-                "x86_64" => x86_64::codegen::relocate(&mut code),
-                _ => panic!("unsupported architecture!"),
+            match options.arch {
+                Arch::Aarch64 => todo!(),
+                Arch::Riscv64 => todo!(),
+                Arch::Wasm64 => todo!(),
+                Arch::X86_64 => x86_64::codegen::relocate(&mut code),
             }
 
-            match DEFAULT_ARCH {
-                "aarch64" => todo!(),
-                "riscv64" => todo!(),
-                "wasm64" => todo!(),
-                "x86_64" => x86_64::disassemble(&code, std::ptr::null()),
-                _ => panic!("unsupported architecture!"),
+            match options.arch {
+                Arch::Aarch64 => todo!(),
+                Arch::Riscv64 => todo!(),
+                Arch::Wasm64 => todo!(),
+                Arch::X86_64 => x86_64::disassemble(&code, std::ptr::null(), options.syntax),
             }
         }
 
         Some("build") => {
-            let contents = contents.unwrap();
+            // Without -c, the generated code goes to a temporary object file that is linked
+            let compile_only = matches
+                .subcommand_matches("build")
+                .unwrap()
+                .is_present("compile_only");
+
+            let contents = match contents {
+                Some(v) => v,
+                None => {
+                    build_project(compile_only, &options);
+                    return;
+                }
+            };
             let mut root = frontend_ir::Ir::new();
-            check(&contents, "Main", &mut root);
+            check(&contents, "Main", &mut root, &options);
 
             let mut module = backend_ir::convert_frontend_ir_to_backend_ir(
-                &root.modules.iter().next().unwrap().1,
+                root.modules().next().unwrap(),
+                backend_ir::Checks::new(&options),
             );
 
-            let mut code = match compile(&mut module) {
-                Some(v) => v,
-                None => return,
-            };
+            // Executables start at main, so nothing it does not use needs to be generated
+            backend_ir::remove_unused_functions(&mut module, &["main"]);
 
-            match DEFAULT_ARCH {
-                "aarch64" => todo!(),
-                "riscv64" => todo!(),
-                "wasm64" => todo!(),
-                "x86_64" => x86_64::codegen::generate_start_func(&mut code),
-                _ => panic!("unsupported architecture!"),
-            }
+            let object = linker::temp_path("o");
 
-            let f = matches
-                .subcommand_matches("build")
-                .unwrap()
-                .value_of("output")
-                .unwrap_or("a.o")
-                .to_owned();
-
-            let mut artefact = ArtifactBuilder::new(Triple::host())
-                .name(f.clone())
-                .finish();
-
-            let mut funcs: Vec<_> = code.get_funcs().iter().collect();
-            funcs.sort_by(|a, b| a.1.start.cmp(&b.1.start));
-            match artefact.declarations({
-                funcs.iter().map(|v| {
-                    (
-                        v.0,
-                        if v.0 == "_start" || v.0 == "main" {
-                            Decl::function().global().into()
-                        } else if v.1.start == 0 && v.1.end == 0 {
-                            Decl::function_import().into()
-                        } else {
-                            Decl::function().into()
-                        },
-                    )
-                })
-            }) {
-                Ok(_) => (),
-                Err(e) => {
-                    eprintln!("Error declaring functions: {}", e);
+            let files = debug_files(&module, source_path, &contents);
+            if options.backend == Backend::Llvm {
+                let code = generate_llvm(&module, &files, true, None, &options);
+
+                if compile_only {
+                    let f = options
+                        .output
+                        .clone()
+                        .unwrap_or_else(|| String::from("a.ll"));
+                    if let Err(e) = fs::write(&f, &code) {
+                        Renderer::new().error(format!("could not write LLVM IR to {}: {}", f, e));
+                        exit(1);
+                    }
                     return;
                 }
-            }
 
-            for (func, range) in funcs {
-                if range.start == 0 && range.end == 0 {
-                    continue;
+                if let Err(e) = linker::compile_llvm_ir(&code, &object, &options) {
+                    Renderer::new().error(e);
+                    exit(1);
                 }
+                link_executable(&object, &options);
+                return;
+            }
 
-                match artefact.define(func, code.data()[range.start..range.end].to_owned()) {
-                    Ok(_) => (),
-                    Err(e) => {
-                        eprintln!("Error defining function: {}", e);
-                        return;
-                    }
-                }
+            let f = if compile_only {
+                options
+                    .output
+                    .clone()
+                    .unwrap_or_else(|| String::from("a.o"))
+            } else {
+                object.display().to_string()
+            };
+            write_object(&mut module, &f, &files, true, &options);
+
+            if !compile_only {
+                link_executable(&object, &options);
             }
+        }
 
-            for (addr, to) in code.get_relocation_table() {
-                for (from, range) in code.get_funcs() {
-                    if range.start <= *addr && *addr < range.end {
-                        match artefact.link(Link {
-                            from,
-                            to,
-                            at: (addr - range.start) as u64,
-                        }) {
-                            Ok(_) => (),
-                            Err(e) => {
-                                eprintln!("Error linking: {}", e);
-                                return;
-                            }
-                        }
-                        break;
+        Some("doc") => {
+            let format = match matches
+                .subcommand_matches("doc")
+                .unwrap()
+                .value_of("format")
+            {
+                Some(v) => match v.parse() {
+                    Ok(v) => v,
+                    Err(_) => {
+                        eprintln!(
+                            "error: unknown documentation format `{}`; use markdown or html",
+                            v
+                        );
+                        exit(1);
                     }
-                }
-            }
+                },
+                None => DocFormat::Markdown,
+            };
 
-            match artefact.write(match File::create(&f) {
-                Ok(v) => v,
-                Err(e) => {
-                    eprintln!("Error getting file {}: {}", f, e);
-                    exit(1);
-                }
-            }) {
-                Ok(_) => (),
-                Err(e) => {
-                    eprintln!("Error writing artefact to file: {}", e);
-                }
-            }
+            let contents = contents.unwrap();
+            let mut root = frontend_ir::Ir::new();
+            check(&contents, "Main", &mut root, &options);
+            print!("{}", docs::render(root.modules().next().unwrap(), format));
         }
 
         Some("llir") => {
             let contents = contents.unwrap();
             let mut root = frontend_ir::Ir::new();
-            check(&contents, "Main", &mut root);
+            check(&contents, "Main", &mut root, &options);
 
             let module = backend_ir::convert_frontend_ir_to_backend_ir(
-                &root.modules.iter().next().unwrap().1,
+                root.modules().next().unwrap(),
+                backend_ir::Checks::new(&options),
             );
             println!("{}", module);
         }
 
         Some("run") => {
+            check_jit_arch(&options);
             let contents = contents.unwrap();
             let mut root = frontend_ir::Ir::new();
-            check(&contents, "Main", &mut root);
+            check(&contents, "Main", &mut root, &options);
 
             let mut module = backend_ir::convert_frontend_ir_to_backend_ir(
-                &root.modules.iter().next().unwrap().1,
+                root.modules().next().unwrap(),
+                backend_ir::Checks::new(&options),
             );
 
-            let code = match compile(&mut module) {
+            let code = match compile(&mut module, &options) {
                 Some(v) => v,
                 None => return,
             };
 
-            let jit = Jit::new(code);
-            println!("{:#x}", unsafe { jit.call("main") }.unwrap() as u64);
+            let jit = match Runtime::load(&options).and_then(|v| Jit::new(code, &v)) {
+                Ok(v) => v,
+                Err(e) => {
+                    Renderer::new().error(e);
+                    exit(1);
+                }
+            };
+            let main = match root.modules().next().unwrap().global("main") {
+                Some(v) => v.name.clone(),
+                None => {
+                    eprintln!("error: no main function");
+                    exit(1);
+                }
+            };
+
+            // The program exits the same way a compiled one does
+            let value = unsafe { jit.call(&main) }.unwrap();
+            exit(if module.exits_with_main {
+                value as i32
+            } else {
+                0
+            });
         }
 
-        Some("repl") | None => repl(),
+        Some("test") => {
+            let contents = contents.unwrap();
+            let mut root = frontend_ir::Ir::new();
+            check(&contents, "Main", &mut root, &options);
+
+            let mut files = Renderer::new();
+            files.add_file("Main", &contents);
+            let module = root.modules().next().unwrap();
+            let interpreter = Interpreter::new(&root, options.overflow_checks);
+            println!("running {} tests", module.tests.len());
+
+            let mut failed = 0;
+            for test in module.tests.iter() {
+                match interpreter.run_test(&module.name, test) {
+                    Ok(true) => println!("test \"{}\" ... ok", test.name),
+                    result => {
+                        println!("test \"{}\" ... FAILED", test.name);
+                        files.emit(&diagnostics::test_diagnostic(
+                            test,
+                            result.err().as_ref(),
+                            &files,
+                        ));
+                        failed += 1;
+                    }
+                }
+            }
+
+            let status = if failed == 0 { "ok" } else { "FAILED" };
+            println!(
+                "test result: {}. {} passed; {} failed",
+                status,
+                module.tests.len() - failed,
+                failed
+            );
+            if failed != 0 {
+                exit(1);
+            }
+        }
+
+        Some("repl") | None => repl(&options),
 
         _ => unreachable!("Invalid subcommand"),
     }
 }
 
-fn check(s: &str, mod_name: &str, root: &mut frontend_ir::Ir) {
-    let ast = match parser::parse(s) {
-        Ok(v) => v,
+fn get_options(matches: &ArgMatches) -> Result<CompilerOptions, OptionsError> {
+    let mut options = CompilerOptions::load()?;
+
+    // Global flags may appear before or after the subcommand
+    let sub = matches
+        .subcommand_name()
+        .and_then(|v| matches.subcommand_matches(v));
+    for matches in std::iter::once(matches).chain(sub) {
+        for key in OPTION_NAMES {
+            match *key {
+                // Lint flags can be given more than once
+                "allow" | "warn" | "deny" => {
+                    if let Some(values) = matches.values_of(key) {
+                        let value: Vec<_> = values.collect();
+                        options.set(key, &value.join(","), OptionSource::Cli)?;
+                    }
+                }
 
-        Err(_) => {
-            eprintln!("Error parsing!");
-            exit(1);
+                // Flags without a value are set below
+                _ => {
+                    if let Some(value) = matches.value_of(key) {
+                        options.set(key, value, OptionSource::Cli)?;
+                    }
+                }
+            }
+        }
+
+        if matches.is_present("no_prelude") {
+            options.set("prelude", "false", OptionSource::Cli)?;
+        }
+
+        if matches.is_present("warn_lossy") {
+            options.set("warn_lossy", "true", OptionSource::Cli)?;
+        }
+
+        if matches.is_present("overflow_checks") {
+            options.set("overflow_checks", "true", OptionSource::Cli)?;
+        }
+
+        if matches.is_present("release") {
+            options.set("release", "true", OptionSource::Cli)?;
+        }
+
+        if matches.is_present("debug_info") {
+            options.set("debug_info", "true", OptionSource::Cli)?;
         }
+    }
+
+    Ok(options)
+}
+
+fn init_logging(options: &CompilerOptions) {
+    // An explicitly configured filter takes priority over RUST_LOG
+    let filter = match &options.log {
+        Some(v) => EnvFilter::new(v),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
     };
 
-    match frontend_ir::convert_ast_to_ir(mod_name, &s, ast, root) {
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+fn check(s: &str, filename: &str, root: &mut frontend_ir::Ir, options: &CompilerOptions) {
+    let mut files = Renderer::new();
+    let file_id = files.add_file(filename, s);
+
+    let ast = match parser::parse(s) {
         Ok(v) => v,
-        Err(_) => {
-            eprintln!("Error creating ir!");
+
+        Err(errors) => {
+            for e in errors.iter() {
+                files.emit(&diagnostics::parse_diagnostic(e, file_id));
+            }
             exit(1);
         }
     };
 
-    let _ = correctness::check_correctness(root, true);
-}
+    if let Some(path) = options.emit.path(EmitKind::Ast, options.output.as_deref()) {
+        write_artefact(&path, &format!("{:#?}\n", ast));
+    }
+
+    let prelude = if options.prelude {
+        prelude::parse()
+    } else {
+        vec![]
+    };
+
+    if let Err(errors) = frontend_ir::convert_ast_to_ir(filename, s, prelude, ast, root) {
+        for e in errors.iter() {
+            files.emit(&diagnostics::ir_diagnostic(e, &files));
+        }
+        exit(1);
+    }
 
-fn compile(module: &mut backend_ir::IrModule) -> Option<GeneratedCode> {
-    match DEFAULT_ARCH {
-        "aarch64" => Some(aarch64::codegen::generate_code(module)),
-        "riscv64" => todo!(),
-        "wasm64" => todo!(),
-        "x86_64" => Some(x86_64::codegen::generate_code(module)),
-        _ => panic!("unsupported architecture"),
+    // A header can name the module something other than its file
+    let mod_name = &root
+        .modules()
+        .find(|v| v.filename == filename)
+        .unwrap()
+        .name
+        .clone();
+
+    // Lints only look at names, so they are checked before expressions they cannot type yet
+    let mut denied = false;
+    for w in lints::check_module(root.module(mod_name).unwrap()).iter() {
+        if let Some(diagnostic) = diagnostics::lint_diagnostic(w, &files, &options.lints) {
+            denied |= diagnostic.severity == Severity::Error;
+            files.emit(&diagnostic);
+        }
+    }
+    if denied {
+        exit(1);
+    }
+
+    let errors = correctness::check_correctness(root, true, options.warn_lossy);
+    for e in errors.iter() {
+        files.emit(&diagnostics::correctness_diagnostic(e, &files));
+    }
+    if errors.iter().any(|e| e.is_error()) {
+        exit(1);
+    }
+
+    let errors = exhaustive::check_module(root.module(mod_name).unwrap());
+    let mut fail = false;
+    for e in errors.iter() {
+        if let Some(diagnostic) = diagnostics::match_diagnostic(e, &files, &options.lints) {
+            fail |= diagnostic.severity == Severity::Error;
+            files.emit(&diagnostic);
+        }
+    }
+    if fail {
+        exit(1);
+    }
+
+    fold::fold_constants(root);
+    uncurry::uncurry_applications(root);
+    specialise::specialise_applications(root);
+
+    if !options.emit.is_empty() {
+        emit_artefacts(root.module(mod_name).unwrap(), options);
     }
 }
 
-fn repl() {
-    let mut rl = Editor::<()>::new();
-    let mut root = frontend_ir::Ir::new();
-    let mut i = 0;
+/// Writes out the artefacts of a checked module requested with `--emit`.
+fn emit_artefacts(f_module: &frontend_ir::IrModule, options: &CompilerOptions) {
+    let output = options.output.as_deref();
+    if let Some(path) = options.emit.path(EmitKind::Ir, output) {
+        write_artefact(&path, &format!("{}\n", f_module));
+    }
 
-    loop {
-        let readline = rl.readline(">>> ");
-        match readline {
-            Ok(line) => {
-                rl.add_history_entry(&line);
+    if ![
+        EmitKind::Ssa,
+        EmitKind::Llvm,
+        EmitKind::Obj,
+        EmitKind::Staticlib,
+    ]
+    .iter()
+    .any(|v| options.emit.contains(*v))
+    {
+        return;
+    }
 
-                let mod_name = format!("m{}", i);
-                i += 1;
-                check(&line, &mod_name, &mut root);
-                let f_module = root.modules.get(&mod_name).unwrap();
+    let mut module =
+        backend_ir::convert_frontend_ir_to_backend_ir(f_module, backend_ir::Checks::new(options));
+    if let Some(path) = options.emit.path(EmitKind::Ssa, output) {
+        write_artefact(&path, &module.to_string());
+    }
 
-                let mut b_module = backend_ir::convert_frontend_ir_to_backend_ir(f_module);
+    let files = debug_files(&module, &f_module.filename, &f_module.contents);
+    let llvm_path = options.emit.path(EmitKind::Llvm, output);
+    let obj_path = options.emit.path(EmitKind::Obj, output);
+    if llvm_path.is_some() || (obj_path.is_some() && options.backend == Backend::Llvm) {
+        let code = generate_llvm(&module, &files, true, None, options);
 
-                let code = match compile(&mut b_module) {
-                    Some(v) => v,
-                    None => return,
-                };
+        if let Some(path) = llvm_path {
+            write_artefact(&path, &code);
+        }
 
-                let jit = Jit::new(code);
-                println!(
-                    "{:#x}",
-                    unsafe { jit.call(f_module.funcs.iter().next().unwrap().0) }.unwrap() as u64
-                );
+        if let (Some(path), Backend::Llvm) = (&obj_path, options.backend) {
+            if let Err(e) = linker::compile_llvm_ir(&code, Path::new(path), options) {
+                Renderer::new().error(e);
+                exit(1);
             }
+        }
+    }
 
-            Err(ReadlineError::Interrupted) => {
-                println!("^C");
+    if let (Some(path), Backend::Native) = (&obj_path, options.backend) {
+        write_object(&mut module, path, &files, true, options);
+    }
+
+    if let Some(path) = options.emit.path(EmitKind::Staticlib, output) {
+        write_staticlib(&mut module, &path, options);
+    }
+}
+
+/// Compiles the exported functions of a module and the functions they use into a static library
+/// with the LLVM backend, and writes a C header declaring them next to it.
+fn write_staticlib(module: &mut backend_ir::IrModule, path: &str, options: &CompilerOptions) {
+    if options.backend != Backend::Llvm {
+        Renderer::new().error("static libraries can only be built with the LLVM backend");
+        exit(1);
+    }
+
+    // Leaving out main keeps it from colliding with the main function of the C program
+    let roots: Vec<_> = module.exports.iter().map(|v| v.func.clone()).collect();
+    let roots: Vec<_> = roots.iter().map(String::as_str).collect();
+    backend_ir::remove_unused_functions(module, &roots);
+
+    let code = llvm::codegen::generate_code(module).to_string();
+    let object = linker::temp_path("o");
+    let result = linker::compile_llvm_ir(&code, &object, options)
+        .and_then(|_| linker::archive(&object, path));
+    let _ = fs::remove_file(&object);
+    if let Err(e) = result {
+        Renderer::new().error(e);
+        exit(1);
+    }
+
+    let header = Path::new(path).with_extension("h");
+    let name = header
+        .file_stem()
+        .map(|v| v.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    write_artefact(
+        &header.display().to_string(),
+        &backends::generate_c_header(module, &name),
+    );
+}
+
+/// Writes an artefact to a file.
+fn write_artefact(path: &str, contents: &str) {
+    if let Err(e) = fs::write(path, contents) {
+        eprintln!("error writing {}: {}", path, e);
+        exit(1);
+    }
+}
+
+/// Returns the files of a lowered module that debug info refers to. Code that is not from the
+/// prelude is from the file at the given path.
+fn debug_files<'a>(
+    module: &backend_ir::IrModule,
+    path: &str,
+    contents: &'a str,
+) -> Vec<dwarf::DebugFile<'a>> {
+    module
+        .files
+        .iter()
+        .map(|f| {
+            if f == prelude::FILENAME {
+                dwarf::DebugFile {
+                    path: f.clone(),
+                    contents: prelude::SOURCE,
+                }
+            } else {
+                dwarf::DebugFile {
+                    path: String::from(path),
+                    contents,
+                }
             }
+        })
+        .collect()
+}
 
-            Err(ReadlineError::Eof) => {
-                println!("^D");
-                break;
+/// Returns the directory relative paths in debug info are relative to.
+fn debug_dir() -> String {
+    env::current_dir()
+        .map(|v| v.display().to_string())
+        .unwrap_or_default()
+}
+
+/// Generates LLVM IR for a module, along with the `_start` function if `start` is set. With the
+/// `debug_info` option set, the LLVM IR also has debug info referring to the given files.
+/// Otherwise, the code of functions that have not changed is taken from the cache if there is one.
+fn generate_llvm(
+    module: &backend_ir::IrModule,
+    files: &[dwarf::DebugFile],
+    start: bool,
+    cache: Option<&mut CodegenCache>,
+    options: &CompilerOptions,
+) -> String {
+    let mut code = if options.debug_info {
+        llvm::codegen::generate_code_with_debug_info(module, files, &debug_dir())
+    } else if let Some(cache) = cache {
+        llvm::codegen::generate_code_cached(module, cache)
+    } else {
+        llvm::codegen::generate_code(module)
+    };
+    if start {
+        llvm::codegen::generate_start_func(&mut code, options.alloc, module.exits_with_main);
+    }
+    code.to_string()
+}
+
+/// Generates machine code for a module, along with the `_start` function if `start` is set, and
+/// writes it to an object file. With the `debug_info` option set, the object file also gets DWARF
+/// debug info referring to the given files.
+fn write_object(
+    module: &mut backend_ir::IrModule,
+    f: &str,
+    files: &[dwarf::DebugFile],
+    start: bool,
+    options: &CompilerOptions,
+) {
+    let mut code = match compile(module, options) {
+        Some(v) => v,
+        None => exit(1),
+    };
+
+    // Debug info refers to functions by name, so it is generated before the code is laid out
+    let debug_sections = if options.debug_info {
+        match options.arch {
+            Arch::X86_64 => dwarf::generate_debug_sections(
+                &code,
+                module,
+                files,
+                &debug_dir(),
+                x86_64::codegen::arg_location,
+            ),
+            _ => vec![],
+        }
+    } else {
+        vec![]
+    };
+
+    if start {
+        match options.arch {
+            Arch::Aarch64 => todo!(),
+            Arch::Riscv64 => todo!(),
+            Arch::Wasm64 => todo!(),
+            Arch::X86_64 => x86_64::codegen::generate_start_func(
+                &mut code,
+                options.alloc,
+                module.exits_with_main,
+            ),
+        }
+    }
+
+    let mut artefact = ArtifactBuilder::new(Triple::host())
+        .name(String::from(f))
+        .finish();
+
+    let mut funcs: Vec<_> = code.get_funcs().iter().collect();
+    funcs.sort_by_key(|v| v.1.start);
+    match artefact.declarations({
+        funcs.iter().map(|v| {
+            (
+                v.0,
+                if v.0 == "_start" || v.0 == "main" {
+                    Decl::function().global().into()
+                } else if v.1.start == 0 && v.1.end == 0 {
+                    Decl::function_import().into()
+                } else {
+                    Decl::function().into()
+                },
+            )
+        })
+    }) {
+        Ok(_) => (),
+        Err(e) => {
+            Renderer::new().error(format!("could not declare functions: {}", e));
+            exit(1);
+        }
+    }
+
+    for (func, range) in funcs {
+        if range.start == 0 && range.end == 0 {
+            continue;
+        }
+
+        match artefact.define(func, code.data()[range.start..range.end].to_owned()) {
+            Ok(_) => (),
+            Err(e) => {
+                Renderer::new().error(format!("could not define function: {}", e));
+                exit(1);
             }
+        }
+    }
 
-            Err(err) => {
-                println!("Error: {}", err);
+    for (addr, to) in code.get_relocation_table() {
+        // Faerie would otherwise give every relocation an addend of -4, dropping the offsets left
+        // in the code, such as the header that calls skip
+        let reloc = match options.arch {
+            Arch::X86_64 => Reloc::Raw {
+                reloc: goblin::elf::reloc::R_X86_64_PLT32,
+                addend: x86_64::codegen::relocation_addend(&code, *addr),
+            },
+            _ => Reloc::Auto,
+        };
+
+        for (from, range) in code.get_funcs() {
+            if range.start <= *addr && *addr < range.end {
+                match artefact.link_with(
+                    Link {
+                        from,
+                        to,
+                        at: (addr - range.start) as u64,
+                    },
+                    reloc,
+                ) {
+                    Ok(_) => (),
+                    Err(e) => {
+                        Renderer::new().error(format!("could not relocate function: {}", e));
+                        exit(1);
+                    }
+                }
                 break;
             }
         }
     }
+
+    for section in debug_sections.iter() {
+        if let Err(e) = artefact.declare_with(
+            section.name,
+            Decl::section(SectionKind::Debug),
+            section.data.clone(),
+        ) {
+            Renderer::new().error(format!("could not declare {}: {}", section.name, e));
+            exit(1);
+        }
+    }
+
+    for section in debug_sections.iter() {
+        for relocation in section.relocations.iter() {
+            match artefact.link_with(
+                Link {
+                    from: section.name,
+                    to: &relocation.target,
+                    at: relocation.offset as u64,
+                },
+                Reloc::Debug {
+                    size: relocation.size,
+                    addend: relocation.addend as i32,
+                },
+            ) {
+                Ok(_) => (),
+                Err(e) => {
+                    Renderer::new().error(format!("could not relocate {}: {}", section.name, e));
+                    exit(1);
+                }
+            }
+        }
+    }
+
+    match artefact.write(match File::create(f) {
+        Ok(v) => v,
+        Err(e) => {
+            Renderer::new().error(format!("could not create {}: {}", f, e));
+            exit(1);
+        }
+    }) {
+        Ok(_) => (),
+        Err(e) => {
+            Renderer::new().error(format!("could not write {}: {}", f, e));
+            exit(1);
+        }
+    }
+}
+
+/// Builds the project described by the configuration file in the current directory. Each source
+/// file is checked and compiled as its own module into an object file in the target directory,
+/// next to a cache of the code generated for it. The cache keeps the fingerprint of the object
+/// file, so that it is only built again when its code changes, and with the LLVM backend the code
+/// of each function, so that only functions that changed are generated again. Unless only
+/// compiling, the object file of the main file is then linked into the executable named by the
+/// output option, or named after the project in the target directory. Only the main file gets a
+/// `_start` function.
+fn build_project(compile_only: bool, options: &CompilerOptions) {
+    let project = match Project::load(".") {
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            Renderer::new().error(format!(
+                "no files were given and {} has no project table",
+                CONFIG_FILE
+            ));
+            exit(1);
+        }
+        Err(e) => {
+            Renderer::new().error(e);
+            exit(1);
+        }
+    };
+
+    let sources = match project.source_files() {
+        Ok(v) => v,
+        Err(e) => {
+            Renderer::new().error(e);
+            exit(1);
+        }
+    };
+
+    for path in sources.iter() {
+        let filename = path.display().to_string();
+        let contents = match fs::read_to_string(project.root.join(path)) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("error reading file {}: {}", filename, e);
+                exit(1);
+            }
+        };
+
+        let mut root = frontend_ir::Ir::new();
+        check(&contents, &filename, &mut root, options);
+
+        let mut module = backend_ir::convert_frontend_ir_to_backend_ir(
+            root.modules().next().unwrap(),
+            backend_ir::Checks::new(options),
+        );
+
+        // Other modules keep every function, since they are not started from main
+        let start = *path == project.main;
+        if start {
+            backend_ir::remove_unused_functions(&mut module, &["main"]);
+        }
+
+        let object = project.artefact(path, "o");
+        if let Some(dir) = object.parent() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                Renderer::new().error(format!("could not create {}: {}", dir.display(), e));
+                exit(1);
+            }
+        }
+
+        // Object files are only built again when the code they are built from has changed
+        let cache_path = project.artefact(path, "cache");
+        let mut cache = CodegenCache::load(&cache_path);
+        let files = debug_files(&module, &filename, &contents);
+        if options.backend == Backend::Llvm {
+            let code = generate_llvm(&module, &files, start, Some(&mut cache), options);
+            let fingerprint = cache::object_fingerprint(&code, options);
+            if cache.object != fingerprint || !object.exists() {
+                if let Err(e) = linker::compile_llvm_ir(&code, &object, options) {
+                    Renderer::new().error(e);
+                    exit(1);
+                }
+                cache.object = fingerprint;
+            }
+        } else {
+            // Debug info also depends on where the code is in the file
+            let code = format!(
+                "{}\n{}{}",
+                start,
+                module,
+                if options.debug_info {
+                    contents.as_str()
+                } else {
+                    ""
+                }
+            );
+            let fingerprint = cache::object_fingerprint(&code, options);
+            if cache.object != fingerprint || !object.exists() {
+                write_object(
+                    &mut module,
+                    &object.display().to_string(),
+                    &files,
+                    start,
+                    options,
+                );
+                cache.object = fingerprint;
+            }
+        }
+
+        if let Err(e) = cache.save(&cache_path) {
+            Renderer::new().error(format!("could not write {}: {}", cache_path.display(), e));
+            exit(1);
+        }
+    }
+
+    if compile_only {
+        return;
+    }
+
+    let output = options
+        .output
+        .clone()
+        .unwrap_or_else(|| project.executable().display().to_string());
+    if let Err(e) = linker::link(&project.artefact(&project.main, "o"), &output, options) {
+        Renderer::new().error(e);
+        exit(1);
+    }
+}
+
+/// Links an object file into the executable named by the output option, removing the object file
+/// afterwards.
+fn link_executable(object: &Path, options: &CompilerOptions) {
+    let output = options
+        .output
+        .clone()
+        .unwrap_or_else(|| String::from("a.out"));
+    let result = linker::link(object, &output, options);
+    let _ = fs::remove_file(object);
+
+    if let Err(e) = result {
+        Renderer::new().error(e);
+        exit(1);
+    }
+}
+
+fn compile(module: &mut backend_ir::IrModule, options: &CompilerOptions) -> Option<GeneratedCode> {
+    match options.arch {
+        Arch::Aarch64 => Some(aarch64::codegen::generate_code(module)),
+        Arch::Riscv64 => todo!(),
+        Arch::Wasm64 => todo!(),
+        Arch::X86_64 => Some(x86_64::codegen::generate_code(module)),
+    }
+}
+
+fn check_jit_arch(options: &CompilerOptions) {
+    if let Some(e) = jit_arch_error(options) {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+}
+
+fn jit_arch_error(options: &CompilerOptions) -> Option<String> {
+    if options.backend != Backend::Native {
+        Some(format!(
+            "cannot JIT compile with the {} backend",
+            options.backend
+        ))
+    } else if options.arch != Arch::host() {
+        Some(format!(
+            "cannot JIT compile for {} on a {} host",
+            options.arch,
+            Arch::host()
+        ))
+    } else {
+        None
+    }
+}
+
+/// Returns a line of the REPL as a definition, naming the value `it` if the line is only an
+/// expression.
+fn repl_definition(line: &str) -> String {
+    match parser::parse(line) {
+        Ok(ast)
+            if !matches!(
+                ast.first(),
+                None | Some(Ast::Assign(..))
+                    | Some(Ast::AssignTyped(..))
+                    | Some(Ast::AssignFunction(..))
+            ) =>
+        {
+            format!("it = {}", line)
+        }
+
+        // Expressions are not definitions, so they only parse once they are named
+        Err(_) if parser::parse(&format!("it = {}", line)).is_ok() => format!("it = {}", line),
+        _ => String::from(line),
+    }
+}
+
+/// The module the REPL adds the definitions of each line to.
+const REPL_MODULE: &str = "repl";
+
+/// Creates the module the REPL adds each line to, holding only the prelude. The prelude is checked
+/// here so that it is not checked again for every line.
+fn repl_module(options: &CompilerOptions) -> frontend_ir::Ir {
+    let mut root = frontend_ir::Ir::new();
+    let files = Renderer::new();
+    let prelude = if options.prelude {
+        prelude::parse()
+    } else {
+        vec![]
+    };
+
+    if let Err(errors) = frontend_ir::convert_ast_to_ir(REPL_MODULE, "", prelude, vec![], &mut root)
+    {
+        for e in errors.iter() {
+            files.emit(&diagnostics::ir_diagnostic(e, &files));
+        }
+        exit(1);
+    }
+
+    let errors = correctness::check_correctness(&mut root, true, options.warn_lossy);
+    for e in errors.iter() {
+        files.emit(&diagnostics::correctness_diagnostic(e, &files));
+    }
+    if errors.iter().any(|e| e.is_error()) {
+        exit(1);
+    }
+
+    fold::fold_constants(&mut root);
+    uncurry::uncurry_applications(&mut root);
+    specialise::specialise_applications(&mut root);
+    root
+}
+
+/// Parses and checks a line of the REPL, adding its definitions to the REPL module after those of
+/// earlier lines. Only the new definitions are checked, and only problems in the line are
+/// reported. Returns a snapshot of the module from before the line, or None if the line has errors,
+/// in which case its definitions are removed again.
+fn check_repl_line(
+    line: &str,
+    filename: &str,
+    root: &mut frontend_ir::Ir,
+    files: &mut Renderer,
+    options: &CompilerOptions,
+) -> Option<frontend_ir::ModuleSnapshot> {
+    let file_id = files.add_file(filename, line);
+    let ast = match parser::parse(line) {
+        Ok(v) => v,
+
+        Err(errors) => {
+            for e in errors.iter() {
+                files.emit(&diagnostics::parse_diagnostic(e, file_id));
+            }
+            return None;
+        }
+    };
+
+    let module = root.modules.get_mut(REPL_MODULE).unwrap();
+    let snapshot = module.snapshot();
+    if check_repl_definitions(filename, line, ast, root, files, options) {
+        fold::fold_constants(root);
+        uncurry::uncurry_applications(root);
+        specialise::specialise_applications(root);
+        Some(snapshot)
+    } else {
+        root.modules.get_mut(REPL_MODULE).unwrap().restore(snapshot);
+        None
+    }
+}
+
+/// Returns the source of each global value a file defines by name, which tells which definitions
+/// changed when the file is loaded again. Files that do not parse define nothing.
+fn file_definitions(contents: &str) -> HashMap<String, &str> {
+    match parser::parse(contents) {
+        Ok(asts) => asts
+            .iter()
+            .filter_map(|ast| match ast {
+                Ast::Assign(s, name, _)
+                | Ast::AssignTyped(s, name, _, _)
+                | Ast::AssignFunction(s, name, _, _) => Some((name.clone(), &contents[s.clone()])),
+                _ => None,
+            })
+            .collect(),
+        Err(_) => HashMap::new(),
+    }
 }
 
-#[allow(dead_code, unused_mut)]
-fn load_libclosey(path: &str) -> Result<Vec<Jit>, ()> {
-    let buffer = match fs::read(path) {
+/// Loads a file into the REPL module after the definitions of earlier lines and files, so that its
+/// definitions shadow theirs. Loading a file again leaves the globals defined since it was last
+/// loaded using its old definitions, so the globals that use definitions which changed are
+/// reported. Returns false if the file cannot be read or has errors.
+fn load_repl_file(
+    path: &str,
+    loaded: &mut Vec<(String, String)>,
+    root: &mut frontend_ir::Ir,
+    files: &mut Renderer,
+    options: &CompilerOptions,
+) -> bool {
+    let contents = match fs::read_to_string(path) {
         Ok(v) => v,
         Err(e) => {
-            eprintln!("Error reading {}: {}", path, e);
-            return Err(());
+            eprintln!("error reading file {}: {}", path, e);
+            return false;
         }
     };
 
-    let mut jits = vec![];
-    match Object::parse(&buffer) {
-        Ok(Object::Elf(_)) => todo!(),
-        Ok(Object::Mach(_)) => todo!(),
-        Ok(Object::PE(_)) => todo!(),
-
-        Ok(Object::Archive(ar)) => {
-            for member in ar.members() {
-                let buffer = ar.extract(member, &buffer).unwrap();
-                match Object::parse(buffer) {
-                    Ok(Object::Elf(elf)) => {
-                        println!("{:#?}", elf);
+    // The functions of the definitions that changed, along with the globals they are bound to
+    let previous = loaded.iter().position(|(p, _)| p == path);
+    let mut changed = HashMap::new();
+    if let Some(i) = previous {
+        let new = file_definitions(&contents);
+        let module = root.module(REPL_MODULE).unwrap();
+        for (name, source) in file_definitions(&loaded[i].1) {
+            match module.global(&name) {
+                Some(f) if f.loc.filename == path && new.get(&name) != Some(&source) => {
+                    changed.insert(f.name.clone(), name);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    if check_repl_line(&contents, path, root, files, options).is_none() {
+        return false;
+    }
+
+    let module = root.module(REPL_MODULE).unwrap();
+    for (global, func) in module.global_functions() {
+        if func.loc.filename == path {
+            continue;
+        }
+
+        if let Some(f) = module
+            .dependencies(func)
+            .into_iter()
+            .find(|f| changed.contains_key(&f.name))
+        {
+            eprintln!(
+                "warning: `{}` uses the old definition of `{}` from {}, so define it again to use \
+                 the new one",
+                global, changed[&f.name], path
+            );
+        }
+    }
+
+    match previous {
+        Some(i) => loaded[i].1 = contents,
+        None => loaded.push((String::from(path), contents)),
+    }
+    true
+}
+
+/// Adds the definitions of a line to the REPL module and checks them, returning false if there are
+/// errors.
+fn check_repl_definitions(
+    filename: &str,
+    line: &str,
+    ast: Vec<Ast>,
+    root: &mut frontend_ir::Ir,
+    files: &Renderer,
+    options: &CompilerOptions,
+) -> bool {
+    let module = root.modules.get_mut(REPL_MODULE).unwrap();
+    if let Err(errors) = frontend_ir::extend_module(module, filename, line, ast) {
+        for e in errors.iter() {
+            files.emit(&diagnostics::ir_diagnostic(e, files));
+        }
+        return false;
+    }
+
+    // Lints and match checks look at the whole module, so problems in earlier lines are left out
+    let mut fail = false;
+    for w in lints::check_module(root.module(REPL_MODULE).unwrap())
+        .iter()
+        .filter(|w| w.loc().filename == filename)
+    {
+        if let Some(diagnostic) = diagnostics::lint_diagnostic(w, files, &options.lints) {
+            fail |= diagnostic.severity == Severity::Error;
+            files.emit(&diagnostic);
+        }
+    }
+    if fail {
+        return false;
+    }
+
+    let errors = correctness::check_correctness(root, true, options.warn_lossy);
+    for e in errors.iter() {
+        files.emit(&diagnostics::correctness_diagnostic(e, files));
+    }
+    if errors.iter().any(|e| e.is_error()) {
+        return false;
+    }
+
+    for e in exhaustive::check_module(root.module(REPL_MODULE).unwrap())
+        .iter()
+        .filter(|e| e.loc().filename == filename)
+    {
+        if let Some(diagnostic) = diagnostics::match_diagnostic(e, files, &options.lints) {
+            fail |= diagnostic.severity == Severity::Error;
+            files.emit(&diagnostic);
+        }
+    }
+    !fail
+}
+
+fn repl(options: &CompilerOptions) {
+    let mut rl = Editor::<()>::new();
+    let mut root = repl_module(options);
+    let mut files = Renderer::new();
+    let mut arena = None;
+    let mut i = 0;
+
+    // Lines are interpreted unless :compile switches to the JIT
+    let mut compiled = false;
+
+    // The values of interpreted lines, which later lines use instead of evaluating them again
+    let mut values = HashMap::new();
+
+    // The files loaded with :load, with their contents when they were last loaded
+    let mut loaded = vec![];
+
+    loop {
+        let readline = rl.readline(">>> ");
+        match readline {
+            Ok(line) => {
+                rl.add_history_entry(&line);
+
+                if let Some(path) = line.trim().strip_prefix(":load ") {
+                    load_repl_file(path.trim(), &mut loaded, &mut root, &mut files, options);
+                    continue;
+                }
+
+                match line.trim() {
+                    ":compile" => {
+                        match jit_arch_error(options) {
+                            Some(e) => eprintln!("error: {}", e),
+                            None => compiled = true,
+                        }
+                        continue;
+                    }
+
+                    ":interpret" => {
+                        compiled = false;
+                        continue;
+                    }
+
+                    ":reload" => {
+                        if loaded.is_empty() {
+                            eprintln!("error: no files have been loaded");
+                        }
+
+                        let paths: Vec<_> = loaded.iter().map(|(p, _)| p.clone()).collect();
+                        for path in paths {
+                            load_repl_file(&path, &mut loaded, &mut root, &mut files, options);
+                        }
+                        continue;
+                    }
+
+                    ":load" => {
+                        eprintln!("error: :load needs a file");
+                        continue;
                     }
 
-                    Ok(Object::Mach(_)) => todo!(),
+                    _ => (),
+                }
+
+                // Meta-commands show how a line is compiled instead of evaluating it
+                let (command, line) = match line.trim_start().split_once(' ') {
+                    Some((c, rest)) if c.starts_with(':') => (Some(c), repl_definition(rest)),
+                    _ if line.trim_start().starts_with(':') => {
+                        eprintln!("error: {} needs an expression", line.trim());
+                        continue;
+                    }
+                    _ => (None, repl_definition(&line)),
+                };
+                if let Some(c) = command {
+                    if !matches!(c, ":type" | ":ir" | ":llvm") {
+                        eprintln!("error: unknown command {}", c);
+                        continue;
+                    }
+                }
 
-                    Ok(Object::PE(_)) => todo!(),
+                let filename = format!("<line {}>", i);
+                i += 1;
+                let snapshot =
+                    match check_repl_line(&line, &filename, &mut root, &mut files, options) {
+                        Some(v) => v,
+                        None => continue,
+                    };
+
+                let f_module = root.module(REPL_MODULE).unwrap();
+                let (repl_global, repl_func) = match f_module
+                    .global_functions()
+                    .find(|(_, f)| f.loc.filename == filename)
+                {
+                    Some((g, f)) => (String::from(g), f.name.clone()),
+                    None => continue,
+                };
 
-                    Ok(Object::Archive(_)) => unreachable!("Archives cannot contain archives!"),
+                // Only the functions the line defines are shown, not those of the prelude, along
+                // with the functions derived for the types they use
+                let line_funcs: Vec<_> = f_module
+                    .functions()
+                    .filter(|f| f.loc.filename == filename)
+                    .collect();
+                match command {
+                    Some(":type") => {
+                        let func = f_module.funcs.get(&repl_func).unwrap();
+                        println!("{} : {}", repl_global, pretty::type_to_string(&func._type));
+                    }
 
-                    Ok(Object::Unknown(magic)) => {
-                        eprintln!(
-                            "Error reading object file {} in {}: unknown magic number {}",
-                            member, path, magic
+                    Some(":ir") => {
+                        for func in line_funcs.iter() {
+                            println!("{}", func);
+                        }
+                        println!();
+
+                        let mut b_module = backend_ir::convert_frontend_ir_to_backend_ir(
+                            f_module,
+                            backend_ir::Checks::new(options),
                         );
-                        return Err(());
+                        b_module.funcs.retain(|f| {
+                            line_funcs.iter().any(|v| v.name == f.name)
+                                || backend_ir::is_derived(&f.name)
+                        });
+                        print!("{}", b_module);
                     }
 
-                    Err(e) => {
-                        eprintln!("Error reading object file {} in {}: {}", member, path, e);
-                        return Err(());
+                    Some(_) => {
+                        let mut b_module = backend_ir::convert_frontend_ir_to_backend_ir(
+                            f_module,
+                            backend_ir::Checks::new(options),
+                        );
+                        b_module.funcs.retain(|f| {
+                            line_funcs.iter().any(|v| v.name == f.name)
+                                || backend_ir::is_derived(&f.name)
+                        });
+                        print!("{}", llvm::codegen::generate_code(&b_module));
+                    }
+
+                    None if !compiled => {
+                        let interpreter = Interpreter::new(&root, options.overflow_checks)
+                            .with_values(std::mem::take(&mut values));
+                        match interpreter.eval_global(REPL_MODULE, &repl_global) {
+                            Ok(v) => println!("{}", v),
+                            Err(InterpreterError::Exit(code)) => exit(code),
+                            Err(e) => eprintln!("{}", e),
+                        }
+                        values = interpreter.into_values();
+                    }
+
+                    None => {
+                        // Functions compiled for earlier lines are called, not compiled again
+                        if arena.is_none() {
+                            match Runtime::load(options) {
+                                Ok(v) => arena = Some(JitArena::new(v)),
+                                Err(e) => {
+                                    eprintln!("error: {}", e);
+                                    continue;
+                                }
+                            }
+                        }
+                        let arena = arena.as_mut().unwrap();
+                        let compiled = arena.compiled();
+                        let mut b_module = backend_ir::convert_new_functions_to_backend_ir(
+                            f_module,
+                            &compiled,
+                            backend_ir::Checks::new(options),
+                        );
+
+                        // Values are shown by the function derived for their type
+                        let func = &f_module.funcs[&repl_func];
+                        let show = if func.args.is_empty() {
+                            Some(backend_ir::add_show_function(
+                                &mut b_module,
+                                f_module,
+                                &compiled,
+                                &func._type,
+                            ))
+                        } else {
+                            None
+                        };
+
+                        let code = match compile(&mut b_module, options) {
+                            Some(v) => v,
+                            None => return,
+                        };
+
+                        // Functions that take arguments are compiled for later lines to call
+                        if let Err(e) = arena.place(code) {
+                            eprintln!("error: {}", e);
+                        } else if let Some(show) = show {
+                            let value = unsafe { arena.call(&repl_func) }.unwrap();
+                            println!("{}", unsafe { arena.show(&show, value) }.unwrap());
+                        } else {
+                            println!("<function {}>", repl_global);
+                        }
                     }
                 }
+
+                // Commands only show how a line is compiled, so its definitions are not kept
+                if command.is_some() {
+                    root.modules.get_mut(REPL_MODULE).unwrap().restore(snapshot);
+                }
             }
 
-            Ok(jits)
-        }
+            Err(ReadlineError::Interrupted) => {
+                println!("^C");
+            }
 
-        Ok(Object::Unknown(magic)) => {
-            println!("Error parsing {}: unknown magic number {}", path, magic);
-            Err(())
-        }
+            Err(ReadlineError::Eof) => {
+                println!("^D");
+                break;
+            }
 
-        Err(e) => {
-            eprintln!("Error parsing {}: {}", path, e);
-            Err(())
+            Err(err) => {
+                println!("Error: {}", err);
+                break;
+            }
         }
     }
 }
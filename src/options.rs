@@ -0,0 +1,772 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::backends::DEFAULT_ARCH;
+
+/// The name of the project configuration file.
+pub const CONFIG_FILE: &str = "closey.toml";
+
+/// The prefix of environment variables that set compiler options.
+pub const ENV_PREFIX: &str = "CLOSEY_";
+
+/// The path of the runtime library in the crate, where `make` in `lib/` builds it.
+pub const RUNTIME_PATH: &str = "lib/bin/libclosey.a";
+
+/// Returns the path of the runtime library used when the `runtime` option is not set. This is the
+/// runtime library in the first directory containing the compiler's executable that has one, so
+/// that executables built in the crate find it from any directory, or otherwise the one in the
+/// crate the compiler was built from.
+pub fn default_runtime() -> String {
+    let exe = env::current_exe().ok();
+    let found = exe
+        .iter()
+        .flat_map(|v| v.ancestors().skip(1))
+        .map(|v| v.join(RUNTIME_PATH))
+        .find(|v| v.exists());
+
+    found
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join(RUNTIME_PATH))
+        .display()
+        .to_string()
+}
+
+/// Represents an architecture that code can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    Aarch64,
+    Riscv64,
+    Wasm64,
+    X86_64,
+}
+
+impl Arch {
+    /// Returns the architecture of the host machine.
+    pub fn host() -> Arch {
+        DEFAULT_ARCH.parse().unwrap()
+    }
+}
+
+impl FromStr for Arch {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Arch, ()> {
+        match s {
+            "aarch64" | "arm64" => Ok(Arch::Aarch64),
+            "riscv64" => Ok(Arch::Riscv64),
+            "wasm64" => Ok(Arch::Wasm64),
+            "x86_64" | "amd64" => Ok(Arch::X86_64),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Arch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Arch::Aarch64 => write!(f, "aarch64"),
+            Arch::Riscv64 => write!(f, "riscv64"),
+            Arch::Wasm64 => write!(f, "wasm64"),
+            Arch::X86_64 => write!(f, "x86_64"),
+        }
+    }
+}
+
+/// Represents the backend that generates code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Generates machine code for the target architecture directly.
+    Native,
+
+    /// Generates textual LLVM IR, which can be compiled with `llc` or `clang`.
+    Llvm,
+}
+
+impl FromStr for Backend {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Backend, ()> {
+        match s {
+            "native" => Ok(Backend::Native),
+            "llvm" => Ok(Backend::Llvm),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::Native => write!(f, "native"),
+            Backend::Llvm => write!(f, "llvm"),
+        }
+    }
+}
+
+/// Represents the allocator that compiled programs allocate memory with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Allocator {
+    /// The reference counted heap built into the runtime library.
+    Rc,
+
+    /// `malloc` and `free` from libc, with reference counts kept by the runtime library.
+    System,
+
+    /// The Boehm garbage collector from libgc. Reference counting is turned off.
+    Boehm,
+
+    /// The user supplied `closey_alloc` and `closey_free` functions, with reference counts kept by
+    /// the runtime library.
+    Custom,
+}
+
+impl Allocator {
+    /// Returns the runtime function that sets up the allocator before `main` is called, if any.
+    pub fn init_func(&self) -> Option<&'static str> {
+        match self {
+            Allocator::Rc => None,
+            Allocator::System => Some("closey_alloc_system_init"),
+            Allocator::Boehm => Some("closey_alloc_boehm_init"),
+            Allocator::Custom => Some("closey_alloc_custom_init"),
+        }
+    }
+}
+
+impl FromStr for Allocator {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Allocator, ()> {
+        match s {
+            "rc" => Ok(Allocator::Rc),
+            "system" => Ok(Allocator::System),
+            "boehm" | "gc" => Ok(Allocator::Boehm),
+            "custom" => Ok(Allocator::Custom),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Allocator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Allocator::Rc => write!(f, "rc"),
+            Allocator::System => write!(f, "system"),
+            Allocator::Boehm => write!(f, "boehm"),
+            Allocator::Custom => write!(f, "custom"),
+        }
+    }
+}
+
+/// Represents the syntax that assembly is printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsmSyntax {
+    Intel,
+    Att,
+}
+
+impl FromStr for AsmSyntax {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<AsmSyntax, ()> {
+        match s {
+            "intel" | "nasm" => Ok(AsmSyntax::Intel),
+            "att" | "gas" => Ok(AsmSyntax::Att),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for AsmSyntax {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AsmSyntax::Intel => write!(f, "intel"),
+            AsmSyntax::Att => write!(f, "att"),
+        }
+    }
+}
+
+/// Represents an intermediate representation or artefact that can be written out while compiling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmitKind {
+    /// The abstract syntax tree from the parser.
+    Ast,
+
+    /// The frontend IR, after type checking.
+    Ir,
+
+    /// The backend SSA IR.
+    Ssa,
+
+    /// The LLVM IR generated by the LLVM backend.
+    Llvm,
+
+    /// An object file, generated by the selected backend.
+    Obj,
+
+    /// A static library of the functions annotated with `@export`, along with a C header declaring
+    /// them next to it. Only the LLVM backend generates these.
+    Staticlib,
+}
+
+impl EmitKind {
+    /// Returns the extension of the file the artefact is written to by default.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EmitKind::Ast => "ast",
+            EmitKind::Ir => "ir",
+            EmitKind::Ssa => "ssa",
+            EmitKind::Llvm => "ll",
+            EmitKind::Obj => "o",
+            EmitKind::Staticlib => "a",
+        }
+    }
+}
+
+impl FromStr for EmitKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<EmitKind, ()> {
+        match s {
+            "ast" => Ok(EmitKind::Ast),
+            "ir" => Ok(EmitKind::Ir),
+            "ssa" => Ok(EmitKind::Ssa),
+            "llvm" | "llvm-ir" => Ok(EmitKind::Llvm),
+            "obj" => Ok(EmitKind::Obj),
+            "staticlib" => Ok(EmitKind::Staticlib),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for EmitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmitKind::Ast => write!(f, "ast"),
+            EmitKind::Ir => write!(f, "ir"),
+            EmitKind::Ssa => write!(f, "ssa"),
+            EmitKind::Llvm => write!(f, "llvm"),
+            EmitKind::Obj => write!(f, "obj"),
+            EmitKind::Staticlib => write!(f, "staticlib"),
+        }
+    }
+}
+
+/// The artefacts written out while compiling, each with the path it is written to if one was
+/// given. Written as a comma separated list of kinds, each optionally followed by `=path`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmitConfig {
+    pub artefacts: Vec<(EmitKind, Option<String>)>,
+}
+
+impl EmitConfig {
+    /// Returns true if nothing is written out.
+    pub fn is_empty(&self) -> bool {
+        self.artefacts.is_empty()
+    }
+
+    /// Returns true if the given kind of artefact is written out.
+    pub fn contains(&self, kind: EmitKind) -> bool {
+        self.artefacts.iter().any(|v| v.0 == kind)
+    }
+
+    /// Returns the path an artefact is written to, or None if it is not written. Artefacts without
+    /// a path are written next to the output file with its extension replaced, or to `a` with the
+    /// extension if there is no output file.
+    pub fn path(&self, kind: EmitKind, output: Option<&str>) -> Option<String> {
+        let (_, path) = self.artefacts.iter().find(|v| v.0 == kind)?;
+        match path {
+            Some(p) => Some(p.clone()),
+            None => {
+                let output = Path::new(output.unwrap_or("a"));
+                Some(
+                    output
+                        .with_extension(kind.extension())
+                        .display()
+                        .to_string(),
+                )
+            }
+        }
+    }
+}
+
+impl FromStr for EmitConfig {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<EmitConfig, ()> {
+        let mut artefacts = vec![];
+        for item in s.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            let (kind, path) = match item.split_once('=') {
+                Some((k, p)) => (k, Some(String::from(p))),
+                None => (item, None),
+            };
+            artefacts.push((kind.parse()?, path));
+        }
+        Ok(EmitConfig { artefacts })
+    }
+}
+
+impl Display for EmitConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (kind, path)) in self.artefacts.iter().enumerate() {
+            if i != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", kind)?;
+            if let Some(p) = path {
+                write!(f, "={}", p)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Represents a lint, a check for code that is valid but probably a mistake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// Variables bound by patterns or `with` expressions that are never used.
+    UnusedVariables,
+
+    /// Function arguments that are never used.
+    UnusedArguments,
+
+    /// Variables that hide a variable of the same name in an enclosing function.
+    Shadowing,
+
+    /// Match arms that every value they match is matched by an earlier arm.
+    UnreachableArms,
+}
+
+impl Lint {
+    /// Every lint, in the order they are listed in.
+    pub const ALL: &'static [Lint] = &[
+        Lint::UnusedVariables,
+        Lint::UnusedArguments,
+        Lint::Shadowing,
+        Lint::UnreachableArms,
+    ];
+
+    /// Returns the level of the lint when it is not set by any option.
+    pub fn default_level(&self) -> LintLevel {
+        match self {
+            Lint::Shadowing => LintLevel::Allow,
+            _ => LintLevel::Warn,
+        }
+    }
+}
+
+impl FromStr for Lint {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Lint, ()> {
+        match s {
+            "unused_variables" => Ok(Lint::UnusedVariables),
+            "unused_arguments" => Ok(Lint::UnusedArguments),
+            "shadowing" => Ok(Lint::Shadowing),
+            "unreachable_arms" => Ok(Lint::UnreachableArms),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Lint::UnusedVariables => write!(f, "unused_variables"),
+            Lint::UnusedArguments => write!(f, "unused_arguments"),
+            Lint::Shadowing => write!(f, "shadowing"),
+            Lint::UnreachableArms => write!(f, "unreachable_arms"),
+        }
+    }
+}
+
+/// Represents what is done with the code a lint finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    /// Nothing is reported.
+    Allow,
+
+    /// A warning is reported.
+    Warn,
+
+    /// An error is reported and compilation stops.
+    Deny,
+}
+
+impl Display for LintLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintLevel::Allow => write!(f, "allow"),
+            LintLevel::Warn => write!(f, "warn"),
+            LintLevel::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+/// The levels of the lints that are set by options. Lints that are not set have their default
+/// level.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintLevels {
+    pub levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintLevels {
+    /// Returns the level of a lint.
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.levels
+            .get(&lint)
+            .copied()
+            .unwrap_or_else(|| lint.default_level())
+    }
+
+    /// Sets the level of the lints in a comma separated list of lint names. `all` stands for every
+    /// lint. Returns the first name that is not a lint, if any.
+    pub fn set(&mut self, lints: &str, level: LintLevel) -> Result<(), String> {
+        for name in lints.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            if name == "all" {
+                for lint in Lint::ALL {
+                    self.levels.insert(*lint, level);
+                }
+            } else {
+                let lint = name.parse().map_err(|_| String::from(name))?;
+                self.levels.insert(lint, level);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Represents where an option was set from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionSource {
+    /// The option was set by the project configuration file at the given path.
+    ConfigFile(String),
+
+    /// The option was set by the given environment variable.
+    Env(String),
+
+    /// The option was set by a command line flag.
+    Cli,
+}
+
+impl Display for OptionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionSource::ConfigFile(p) => write!(f, "{}", p),
+            OptionSource::Env(v) => write!(f, "environment variable {}", v),
+            OptionSource::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// Represents an error in setting compiler options.
+#[derive(Debug)]
+pub enum OptionsError {
+    /// The option does not exist.
+    UnknownOption(OptionSource, String),
+
+    /// The value given for the option is invalid. The last field describes the expected values.
+    InvalidValue(OptionSource, String, String, &'static str),
+
+    /// The configuration file, or a directory of source files it names, could not be read.
+    Io(String, std::io::Error),
+
+    /// The configuration file is not valid toml.
+    Toml(String, toml::de::Error),
+}
+
+impl Display for OptionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionsError::UnknownOption(s, k) => write!(f, "unknown option `{}` in {}", k, s),
+            OptionsError::InvalidValue(s, k, v, e) => write!(
+                f,
+                "invalid value `{}` for option `{}` in {}; expected {}",
+                v, k, s, e
+            ),
+            OptionsError::Io(p, e) => write!(f, "error reading {}: {}", p, e),
+            OptionsError::Toml(p, e) => write!(f, "error parsing {}: {}", p, e),
+        }
+    }
+}
+
+/// The set of options that control compilation. Options are built up from defaults, then the
+/// project configuration file, then environment variables, then command line flags, with later
+/// sources overriding earlier ones. Every stage of the compiler takes its settings from here.
+#[derive(Debug, Clone)]
+pub struct CompilerOptions {
+    /// The architecture to generate code for.
+    pub arch: Arch,
+
+    /// The backend that generates code.
+    pub backend: Backend,
+
+    /// The optimisation level, from 0 to 3.
+    pub opt_level: u8,
+
+    /// The file to write build artefacts to, if any.
+    pub output: Option<String>,
+
+    /// The filter for compiler logs, in the same syntax as `RUST_LOG`. If unset, `RUST_LOG` is
+    /// used instead.
+    pub log: Option<String>,
+
+    /// Whether the prelude is included before every program.
+    pub prelude: bool,
+
+    /// Whether implicit conversions that can lose precision, such as ints mixed with floats, are
+    /// warned about.
+    pub warn_lossy: bool,
+
+    /// Whether int arithmetic that overflows panics with the location of the operation, rather
+    /// than wrapping around.
+    pub overflow_checks: bool,
+
+    /// Whether generated code leaves out the runtime checks that only catch bugs, such as dividing
+    /// an int by zero, which are undefined behaviour without them.
+    pub release: bool,
+
+    /// Whether generated code includes debug info, so that debuggers can step through the source
+    /// code.
+    pub debug_info: bool,
+
+    /// The allocator that compiled programs allocate memory with.
+    pub alloc: Allocator,
+
+    /// The syntax that assembly is printed in.
+    pub syntax: AsmSyntax,
+
+    /// The linker that executables are linked with.
+    pub linker: String,
+
+    /// Extra flags passed to the linker, separated by whitespace.
+    pub link_flags: String,
+
+    /// The path to the runtime library that executables are linked with.
+    pub runtime: String,
+
+    /// The intermediate representations and artefacts written out while compiling.
+    pub emit: EmitConfig,
+
+    /// The levels of lints, set by the `allow`, `warn`, and `deny` options.
+    pub lints: LintLevels,
+}
+
+impl Default for CompilerOptions {
+    fn default() -> CompilerOptions {
+        CompilerOptions {
+            arch: Arch::host(),
+            backend: Backend::Native,
+            opt_level: 0,
+            output: None,
+            log: None,
+            prelude: true,
+            warn_lossy: false,
+            overflow_checks: false,
+            release: false,
+            debug_info: false,
+            alloc: Allocator::Rc,
+            syntax: AsmSyntax::Intel,
+            linker: String::from("ld"),
+            link_flags: String::new(),
+            runtime: default_runtime(),
+            emit: EmitConfig::default(),
+            lints: LintLevels::default(),
+        }
+    }
+}
+
+/// The names of all options, as used in the configuration file and command line. Environment
+/// variables use the upper case name prefixed with `CLOSEY_`.
+pub const OPTION_NAMES: &[&str] = &[
+    "arch",
+    "backend",
+    "opt_level",
+    "output",
+    "log",
+    "prelude",
+    "warn_lossy",
+    "overflow_checks",
+    "release",
+    "debug_info",
+    "alloc",
+    "syntax",
+    "linker",
+    "link_flags",
+    "runtime",
+    "emit",
+    "allow",
+    "warn",
+    "deny",
+];
+
+impl CompilerOptions {
+    /// Creates the default set of options.
+    pub fn new() -> CompilerOptions {
+        CompilerOptions::default()
+    }
+
+    /// Creates the set of options from the configuration file in the current directory and the
+    /// environment. Command line flags should be applied to the result with `set()`.
+    pub fn load() -> Result<CompilerOptions, OptionsError> {
+        let mut options = CompilerOptions::new();
+        options.apply_file(CONFIG_FILE)?;
+        options.apply_env()?;
+        Ok(options)
+    }
+
+    /// Sets an option from its textual value, validating it.
+    pub fn set(
+        &mut self,
+        key: &str,
+        value: &str,
+        source: OptionSource,
+    ) -> Result<(), OptionsError> {
+        let invalid = |expected| {
+            Err(OptionsError::InvalidValue(
+                source.clone(),
+                String::from(key),
+                String::from(value),
+                expected,
+            ))
+        };
+
+        match key {
+            "arch" => match value.parse() {
+                Ok(v) => self.arch = v,
+                Err(_) => return invalid("one of aarch64, riscv64, wasm64, or x86_64"),
+            },
+
+            "backend" => match value.parse() {
+                Ok(v) => self.backend = v,
+                Err(_) => return invalid("native or llvm"),
+            },
+
+            "opt_level" => match value.parse() {
+                Ok(v) if v <= 3 => self.opt_level = v,
+                _ => return invalid("an integer from 0 to 3"),
+            },
+
+            "output" => self.output = Some(String::from(value)),
+
+            "log" => self.log = Some(String::from(value)),
+
+            "prelude" => match value {
+                "true" => self.prelude = true,
+                "false" => self.prelude = false,
+                _ => return invalid("true or false"),
+            },
+
+            "warn_lossy" => match value {
+                "true" => self.warn_lossy = true,
+                "false" => self.warn_lossy = false,
+                _ => return invalid("true or false"),
+            },
+
+            "overflow_checks" => match value {
+                "true" => self.overflow_checks = true,
+                "false" => self.overflow_checks = false,
+                _ => return invalid("true or false"),
+            },
+
+            "release" => match value {
+                "true" => self.release = true,
+                "false" => self.release = false,
+                _ => return invalid("true or false"),
+            },
+
+            "debug_info" => match value {
+                "true" => self.debug_info = true,
+                "false" => self.debug_info = false,
+                _ => return invalid("true or false"),
+            },
+
+            "alloc" => match value.parse() {
+                Ok(v) => self.alloc = v,
+                Err(_) => return invalid("one of rc, system, boehm, or custom"),
+            },
+
+            "syntax" => match value.parse() {
+                Ok(v) => self.syntax = v,
+                Err(_) => return invalid("intel or att"),
+            },
+
+            "linker" => self.linker = String::from(value),
+
+            "link_flags" => self.link_flags = String::from(value),
+
+            "runtime" => self.runtime = String::from(value),
+
+            "emit" => match value.parse() {
+                Ok(v) => self.emit = v,
+                Err(_) => {
+                    return invalid("a comma separated list of ast, ir, ssa, llvm, or obj, each optionally followed by =path")
+                }
+            },
+
+            "allow" | "warn" | "deny" => {
+                let level = match key {
+                    "allow" => LintLevel::Allow,
+                    "warn" => LintLevel::Warn,
+                    _ => LintLevel::Deny,
+                };
+                if self.lints.set(value, level).is_err() {
+                    return invalid("a comma separated list of unused_variables, unused_arguments, shadowing, unreachable_arms, or all");
+                }
+            }
+
+            _ => return Err(OptionsError::UnknownOption(source, String::from(key))),
+        }
+
+        Ok(())
+    }
+
+    /// Applies the options set in a toml configuration file. Options are read from the `build`
+    /// table. A missing file is not an error.
+    pub fn apply_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), OptionsError> {
+        let path = path.as_ref();
+        let name = path.display().to_string();
+        let contents = match fs::read_to_string(path) {
+            Ok(v) => v,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(OptionsError::Io(name, e)),
+        };
+
+        self.apply_toml(&contents, &name)
+    }
+
+    /// Applies the options set in the `build` table of a toml string.
+    pub fn apply_toml(&mut self, contents: &str, name: &str) -> Result<(), OptionsError> {
+        let value: toml::Value = match contents.parse() {
+            Ok(v) => v,
+            Err(e) => return Err(OptionsError::Toml(String::from(name), e)),
+        };
+
+        if let Some(table) = value.get("build").and_then(|v| v.as_table()) {
+            for (key, value) in table {
+                // Strings are used as is so they are not quoted
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    v => v.to_string(),
+                };
+                self.set(key, &value, OptionSource::ConfigFile(String::from(name)))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies the options set by `CLOSEY_*` environment variables.
+    pub fn apply_env(&mut self) -> Result<(), OptionsError> {
+        for key in OPTION_NAMES {
+            let var = format!("{}{}", ENV_PREFIX, key.to_uppercase());
+            if let Ok(value) = env::var(&var) {
+                self.set(key, &value, OptionSource::Env(var))?;
+            }
+        }
+
+        Ok(())
+    }
+}
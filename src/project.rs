@@ -0,0 +1,162 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::options::{OptionSource, OptionsError, CONFIG_FILE};
+
+/// The extension of Closey source files.
+pub const SOURCE_EXTENSION: &str = "cly";
+
+/// The directory build artefacts are written to, relative to the root of the project.
+pub const TARGET_DIR: &str = "target";
+
+/// Represents a project, a directory of Closey files that are built together. A project is
+/// described by the `project` table of the configuration file in its root directory, and is built
+/// with the options in the `build` table of the same file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    /// The directory the configuration file is in. The other paths are relative to it.
+    pub root: PathBuf,
+
+    /// The name of the project, which names the executable. By default this is the name of the
+    /// root directory.
+    pub name: String,
+
+    /// The file that contains `main`. By default this is `src/main.cly`.
+    pub main: PathBuf,
+
+    /// The directories that source files are found in. By default this is just `src`.
+    pub sources: Vec<PathBuf>,
+}
+
+impl Project {
+    /// Loads the project described by the configuration file in the given directory. Returns
+    /// `None` if there is no configuration file or it has no `project` table.
+    pub fn load<P: AsRef<Path>>(root: P) -> Result<Option<Project>, OptionsError> {
+        let root = root.as_ref();
+        let path = root.join(CONFIG_FILE);
+        let name = path.display().to_string();
+        let contents = match fs::read_to_string(&path) {
+            Ok(v) => v,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(OptionsError::Io(name, e)),
+        };
+
+        Project::from_toml(&contents, &name, root)
+    }
+
+    /// Reads a project from the `project` table of a toml string, with paths relative to the given
+    /// root directory.
+    pub fn from_toml(
+        contents: &str,
+        name: &str,
+        root: &Path,
+    ) -> Result<Option<Project>, OptionsError> {
+        let value: toml::Value = match contents.parse() {
+            Ok(v) => v,
+            Err(e) => return Err(OptionsError::Toml(String::from(name), e)),
+        };
+
+        let table = match value.get("project").and_then(|v| v.as_table()) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+
+        // The directory name of `.` is only known once it is made absolute
+        let dir_name = fs::canonicalize(root)
+            .ok()
+            .and_then(|v| v.file_name().map(|v| v.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| String::from("main"));
+        let mut project = Project {
+            root: root.to_path_buf(),
+            name: dir_name,
+            main: PathBuf::from("src/main.cly"),
+            sources: vec![PathBuf::from("src")],
+        };
+
+        let source = || OptionSource::ConfigFile(String::from(name));
+        for (key, value) in table {
+            let invalid = |expected| {
+                OptionsError::InvalidValue(source(), key.clone(), value.to_string(), expected)
+            };
+
+            match key.as_str() {
+                "name" => match value.as_str() {
+                    Some(v) if !v.is_empty() => project.name = String::from(v),
+                    _ => return Err(invalid("a name")),
+                },
+
+                "main" => match value.as_str() {
+                    Some(v) => project.main = PathBuf::from(v),
+                    None => return Err(invalid("a path")),
+                },
+
+                "sources" => match value.as_array() {
+                    Some(v) if v.iter().all(|v| v.is_str()) => {
+                        project.sources = v
+                            .iter()
+                            .map(|v| PathBuf::from(v.as_str().unwrap()))
+                            .collect();
+                    }
+                    _ => return Err(invalid("a list of paths")),
+                },
+
+                _ => return Err(OptionsError::UnknownOption(source(), key.clone())),
+            }
+        }
+
+        Ok(Some(project))
+    }
+
+    /// Returns the source files of the project, relative to the root directory: every Closey file
+    /// in the source directories and the directories in them, and the main file. The files are
+    /// sorted by path so that they are always built in the same order.
+    pub fn source_files(&self) -> Result<Vec<PathBuf>, OptionsError> {
+        let mut files = vec![self.main.clone()];
+        for dir in self.sources.iter() {
+            find_sources(&self.root, dir, &mut files)?;
+        }
+
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// Returns the path of the artefact built from a source file, with the given extension. The
+    /// target directory mirrors the layout of the project, so that `src/a/b.cly` builds
+    /// `target/src/a/b.o`.
+    pub fn artefact(&self, file: &Path, ext: &str) -> PathBuf {
+        self.root.join(TARGET_DIR).join(file).with_extension(ext)
+    }
+
+    /// Returns the path of the executable built from the project.
+    pub fn executable(&self) -> PathBuf {
+        self.root.join(TARGET_DIR).join(&self.name)
+    }
+}
+
+/// Adds the Closey files in a directory relative to the root, and in the directories in it, to the
+/// list of files.
+fn find_sources(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), OptionsError> {
+    let path = root.join(dir);
+    let entries = match fs::read_dir(&path) {
+        Ok(v) => v,
+        Err(e) => return Err(OptionsError::Io(path.display().to_string(), e)),
+    };
+
+    for entry in entries {
+        let entry = match entry {
+            Ok(v) => v,
+            Err(e) => return Err(OptionsError::Io(path.display().to_string(), e)),
+        };
+
+        let file = dir.join(entry.file_name());
+        if entry.path().is_dir() {
+            find_sources(root, &file, files)?;
+        } else if file.extension() == Some(OsStr::new(SOURCE_EXTENSION)) {
+            files.push(file);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,281 @@
+//! Runs `closeyc` with the flags of its command line and checks that each one reaches the options
+//! of the compiler, and that the executables it builds run.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output, Stdio};
+
+/// Runs `closeyc` with the given arguments from the root of the crate.
+fn closeyc(args: &[&str]) -> Output {
+    closeyc_in(Path::new(env!("CARGO_MANIFEST_DIR")), args)
+}
+
+/// Runs `closeyc` with the given arguments from a directory.
+fn closeyc_in(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_closeyc"))
+        .args(args)
+        .current_dir(dir)
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("closeyc should start")
+}
+
+/// Runs the REPL with the given lines as its input from the root of the crate.
+fn repl(lines: &[&str]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_closeyc"))
+        .arg("repl")
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env_remove("RUST_LOG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("closeyc should start");
+    let input = lines.iter().map(|v| format!("{}\n", v)).collect::<String>();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Returns a path in the temporary directory for a file written by a test.
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("closey-cli-{}-{}", process::id(), name))
+}
+
+/// Returns what a run of `closeyc` printed to stdout.
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Returns what a run of `closeyc` printed to stderr.
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn lint_levels() {
+    let source = "f x: Int = let x = 1 in 2";
+    let output = closeyc(&["analyse", "-e", source]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("warning: Unused variable `x`"));
+    assert!(!stderr(&output).contains("shadows"));
+
+    // Lint flags can be given more than once, and each applies to the lints it names
+    let output = closeyc(&[
+        "analyse",
+        "-A",
+        "unused_arguments",
+        "-A",
+        "unused_variables",
+        "-D",
+        "shadowing",
+        "-e",
+        source,
+    ]);
+    assert!(!output.status.success());
+    let err = stderr(&output);
+    assert!(err.contains("error: Variable `x` shadows"), "{}", err);
+    assert!(!err.contains("Unused"), "{}", err);
+
+    let output = closeyc(&["analyse", "-A", "all", "-e", source]);
+    assert!(output.status.success());
+    assert!(!stderr(&output).contains("warning"), "{}", stderr(&output));
+
+    let output = closeyc(&["analyse", "-W", "unused", "-e", source]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("invalid value `unused` for option `warn`"));
+}
+
+#[test]
+fn no_prelude() {
+    let output = closeyc(&["analyse", "-e", "f = pi"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = closeyc(&["analyse", "--no-prelude", "-e", "f = pi"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Unknown variable `pi`"));
+}
+
+#[test]
+fn warn_lossy() {
+    let source = "f x: Int = x + 0.5";
+    let output = closeyc(&["analyse", "-e", source]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stderr(&output).contains("lose precision"));
+
+    let output = closeyc(&["analyse", "--warn-lossy", "-e", source]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("warning: Implicit conversion may lose precision"));
+}
+
+#[test]
+fn overflow_checks() {
+    let output = closeyc(&["llir", "-e", "f x: Int = x + 1"]);
+    assert!(stdout(&output).contains("callrt @closey_int_add $0 1\n"));
+    let output = closeyc(&["llir", "--overflow-checks", "-e", "f x: Int = x + 1"]);
+    assert!(stdout(&output).contains("callrt @closey_int_add_checked $0 1 &0\n"));
+
+    // The interpreter checks for overflow as well
+    let source = "test \"wraps\" = 9223372036854775807 + 1 < 0";
+    let output = closeyc(&["test", "-e", source]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = closeyc(&["test", "--overflow-checks", "-e", source]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("attempt to add with overflow"));
+}
+
+#[test]
+fn debug_info() {
+    // Objects only have DWARF sections when debug info is asked for
+    let has_debug_info = |flags: &[&str]| {
+        let path = temp_path(&format!("debug{}.o", flags.concat()));
+        let path_str = path.display().to_string();
+        let mut args = vec!["build", "-c", "-e", "main = 3", "-o", &path_str];
+        args.extend(flags);
+        let output = closeyc(&args);
+        assert!(output.status.success(), "{}", stderr(&output));
+        let object = fs::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        object.windows(11).any(|v| v == b".debug_info")
+    };
+    assert!(!has_debug_info(&[]));
+    assert!(has_debug_info(&["-g"]));
+    assert!(has_debug_info(&["--debug-info"]));
+}
+
+#[test]
+fn release() {
+    let output = closeyc(&["llir", "-e", "f x: Int = 7 / x"]);
+    assert!(stdout(&output).contains("callrt @closey_int_div 7 $0 &0\n"));
+    let output = closeyc(&["llir", "--release", "-e", "f x: Int = 7 / x"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("callrt @closey_int_div_unchecked 7 $0\n"));
+}
+
+#[test]
+fn build_native() {
+    // Executables are built from outside of the crate, so the runtime library is found without
+    // being named
+    let programs = [
+        ("main = 3", 3, ""),
+        (
+            "double x: Int = x * 2\nmain = println (concat \"double 21 is \" (show (double 21)))",
+            0,
+            "double 21 is 42\n",
+        ),
+    ];
+
+    for (i, (source, code, printed)) in programs.iter().enumerate() {
+        let executable = temp_path(&format!("native{}", i));
+        let executable_str = executable.display().to_string();
+        let output = closeyc_in(
+            &env::temp_dir(),
+            &["build", "-e", source, "-o", &executable_str],
+        );
+        let err = stderr(&output);
+        if err.contains("could not run `ld`") || err.contains("runtime library was not found") {
+            eprintln!("ld or the runtime library is missing, so programs were not run");
+            return;
+        }
+        assert!(output.status.success(), "{}", err);
+
+        let output = Command::new(&executable).output().unwrap();
+        let _ = fs::remove_file(&executable);
+        assert_eq!(output.status.code(), Some(*code), "{}", source);
+        assert_eq!(stdout(&output), *printed, "{}", source);
+    }
+}
+
+#[test]
+fn run_jit() {
+    // Compiled code calls functions in the runtime library, which the JIT loads
+    let programs = [
+        ("main = 3", 3, ""),
+        ("main = println \"hi\"", 0, "hi\n"),
+        (
+            "double x: Int = x * 2\nmain = println (concat \"double 21 is \" (show (double 21)))",
+            0,
+            "double 21 is 42\n",
+        ),
+    ];
+
+    for (source, code, printed) in programs.iter() {
+        let output = closeyc_in(&env::temp_dir(), &["run", "-e", source]);
+        let err = stderr(&output);
+        if err.contains("could not run `ld`") || err.contains("runtime library was not found") {
+            eprintln!("ld or the runtime library is missing, so programs were not run");
+            return;
+        }
+        assert_eq!(output.status.code(), Some(*code), "{}\n{}", source, err);
+        assert_eq!(stdout(&output), *printed, "{}", source);
+    }
+}
+
+#[test]
+fn repl_compile() {
+    // Compiled lines call the runtime library, and their values are shown the same way as those of
+    // interpreted lines
+    let lines = [
+        "x = println \"hi\"",
+        "y = 1 + 2",
+        "z = concat \"a\" \"b\"",
+        "w = [1, 2, 3]",
+        "f = 2.5",
+        "b = 1 < 2",
+        "o = some y",
+        "double x: Int = x * 2",
+        "d = double 21",
+    ];
+    let expected = "hi\n()\n3\n\"ab\"\n[1, 2, 3]\n2.5\ntrue\nsome 3\n<function double>\n42\n^D\n";
+
+    let output = repl(&lines);
+    assert_eq!(stdout(&output), expected, "{}", stderr(&output));
+
+    let compiled: Vec<_> = std::iter::once(":compile")
+        .chain(lines.iter().cloned())
+        .collect();
+    let output = repl(&compiled);
+    let err = stderr(&output);
+    if err.contains("could not run `ld`") || err.contains("runtime library was not found") {
+        eprintln!("ld or the runtime library is missing, so lines were not compiled");
+        return;
+    }
+    assert_eq!(stdout(&output), expected, "{}", err);
+}
+
+#[test]
+fn repl_expressions() {
+    // A line that is only an expression defines `it`, which later lines can use, and a global
+    // without arguments refers to the one it shadows by its own name
+    let lines = [
+        "concat \"a\" \"b\"",
+        "it",
+        "concat it \"c\"",
+        "it",
+        "x = 5",
+        "x = x + 1",
+        "x * 2",
+    ];
+    let expected = "\"ab\"\n\"ab\"\n\"abc\"\n\"abc\"\n5\n6\n12\n^D\n";
+
+    let output = repl(&lines);
+    assert_eq!(stdout(&output), expected, "{}", stderr(&output));
+    assert_eq!(stderr(&output), "");
+
+    let compiled: Vec<_> = std::iter::once(":compile")
+        .chain(lines.iter().cloned())
+        .collect();
+    let output = repl(&compiled);
+    let err = stderr(&output);
+    if err.contains("could not run `ld`") || err.contains("runtime library was not found") {
+        eprintln!("ld or the runtime library is missing, so lines were not compiled");
+        return;
+    }
+    assert_eq!(stdout(&output), expected, "{}", err);
+}
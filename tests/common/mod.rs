@@ -0,0 +1,19 @@
+//! Helpers shared by the tests that compile the Closey programs in this directory.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The definitions every test program can use, such as `check`.
+pub const PRELUDE: &str = include_str!("prelude.cly");
+
+/// Reads a test program and adds the test prelude after its code, so the locations of its errors
+/// are the same as in the file.
+pub fn read_program(path: &Path) -> io::Result<String> {
+    let mut source = fs::read_to_string(path)?;
+    if !source.ends_with('\n') {
+        source.push('\n');
+    }
+    source.push_str(PRELUDE);
+    Ok(source)
+}
@@ -0,0 +1,105 @@
+//! Runs programs with the interpreter and as executables built by each backend, and checks that
+//! they all exit with the same code. Backends whose tools are missing are skipped.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use closeyc::compiler::Compiler;
+use closeyc::interpreter::Interpreter;
+use closeyc::options::CompilerOptions;
+
+/// The name of the module programs are compiled as, the same as `closeyc build`.
+const MODULE: &str = "Main";
+
+/// The number of executables built so far, which names each one so that tests running at the same
+/// time do not overwrite each other's.
+static BUILT: AtomicUsize = AtomicUsize::new(0);
+
+/// Runs a program with the interpreter and returns the code it exits with.
+fn interpret(source: &str) -> i32 {
+    let mut compiler = Compiler::new(CompilerOptions::default());
+    let ir = compiler
+        .check(MODULE, source)
+        .value
+        .expect("the program should compile");
+    let interpreter = Interpreter::new(&ir, false);
+    interpreter.run_main(MODULE).unwrap() & 255
+}
+
+/// Builds a program with `closeyc build` and the given backend, and returns the code it exits
+/// with. Returns `None` if the tools the backend needs are missing.
+fn build_and_run(source: &str, backend: &str) -> Option<i32> {
+    let id = BUILT.fetch_add(1, Ordering::SeqCst);
+    let executable = env::temp_dir().join(format!("closey-diff-{}-{}", process::id(), id));
+    let output = Command::new(env!("CARGO_BIN_EXE_closeyc"))
+        .args(["build", "--backend", backend, "-e", source, "-o"])
+        .arg(&executable)
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")))
+        .output()
+        .expect("closeyc should start");
+    let err = String::from_utf8_lossy(&output.stderr);
+    if err.contains("could not run") || err.contains("runtime library was not found") {
+        return None;
+    }
+    assert!(output.status.success(), "{}\n{}", source, err);
+
+    let status = Command::new(&executable).status().unwrap();
+    let _ = fs::remove_file(&executable);
+    assert!(
+        status.code().is_some(),
+        "{} crashed with {}",
+        source,
+        status
+    );
+    status.code()
+}
+
+/// Asserts that a program exits with the same code when it is interpreted and when it is built by
+/// each backend.
+fn assert_same(source: &str, expected: i32) {
+    assert_eq!(interpret(source), expected, "interpreted {}", source);
+    for backend in ["native", "llvm"].iter() {
+        match build_and_run(source, backend) {
+            Some(code) => assert_eq!(code, expected, "{} built {}", backend, source),
+            None => eprintln!("the tools for the {} backend are missing", backend),
+        }
+    }
+}
+
+#[test]
+fn global_values() {
+    // Globals without arguments are values, which other functions use rather than their address
+    assert_same("m = 5\nmain = m + 1", 6);
+    assert_same("m = 5\nn = m * 3 + 1\ng x: Int = x + m\nmain = g n", 21);
+    assert_same(
+        "s = \"ab\"\nmain = if string_eq (concat s s) \"abab\" then 1 else 2",
+        1,
+    );
+    assert_same("t = 2.5\nmain = (t * 4.0) as Int", 10);
+
+    // A global that shadows another by its own name refers to the one it shadows
+    assert_same("pi = pi * 2.0\nmain = pi as Int", 6);
+}
+
+#[test]
+fn global_functions() {
+    // Globals without arguments can also be functions, such as lambdas and partial applications
+    assert_same("double = \\x: Int . x * 2\nmain = double 21", 42);
+    assert_same(
+        "add3 a: Int, b: Int, c: Int = a + b + c\nadd_one = add3 1\nmain = add_one 2 3",
+        6,
+    );
+    assert_same(
+        "apply f: Int -> Int, x: Int = f x\ninc = \\x: Int . x + 1\nmain = apply inc 4",
+        5,
+    );
+}
+
+#[test]
+fn prelude_constants() {
+    assert_same("main = (pi * 10.0) as Int", 31);
+    assert_same("main = (tau / pi) as Int", 2);
+}
@@ -0,0 +1,114 @@
+//! Checks what the constant folding pass leaves of functions: operators and `if`s applied to literals
+//! become their values, literals bound with `let` are used in place of their names, and operations
+//! that panic or overflow at runtime are left for the runtime to run.
+
+use closeyc::compiler::Compiler;
+use closeyc::options::CompilerOptions;
+
+/// Compiles a program and returns the body of one of its functions after folding.
+fn folded(source: &str, func: &str) -> String {
+    folded_with(source, func, CompilerOptions::default())
+}
+
+/// Compiles a program with the given options and returns the body of one of its functions after
+/// folding.
+fn folded_with(source: &str, func: &str, options: CompilerOptions) -> String {
+    let mut compiler = Compiler::new(options);
+    let output = compiler.check("Main", source);
+    let ir = output.value.expect("the program should compile");
+    ir.module("Main")
+        .unwrap()
+        .global(func)
+        .unwrap()
+        .body
+        .to_string()
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(folded("f = 1 + 2 * 3 - 10 / 4 % 3", "f"), "5: Int");
+    assert_eq!(folded("f = (1 << 4) | 3 & ~0 ^ 1", "f"), "18: Int");
+    assert_eq!(folded("f = 1.5 * 2.0 + 0.25", "f"), "3.25: Float");
+    assert_eq!(folded("f = (250 : UInt8) - 50", "f"), "200: UInt8");
+    assert_eq!(folded("f = ~(0 : UInt8)", "f"), "255: UInt8");
+}
+
+#[test]
+fn comparisons() {
+    assert_eq!(folded("f = 1 < 2", "f"), "extern true : Bool");
+    assert_eq!(folded("f = 2.0 <= 1.0", "f"), "extern false : Bool");
+    assert_eq!(folded("f = 'a' != 'b'", "f"), "extern true : Bool");
+    assert_eq!(folded("f = \"ab\" == \"ab\"", "f"), "extern true : Bool");
+    assert_eq!(folded("f = (255 : UInt8) > 1", "f"), "extern true : Bool");
+}
+
+#[test]
+fn booleans() {
+    assert_eq!(folded("f = not (1 > 2)", "f"), "extern true : Bool");
+    assert_eq!(folded("f = true xor 1 < 2", "f"), "extern false : Bool");
+    assert_eq!(folded("f = 1 < 2 and 2 < 1", "f"), "extern false : Bool");
+    assert_eq!(folded("f = 2 < 1 or not false", "f"), "extern true : Bool");
+}
+
+#[test]
+fn ifs() {
+    assert_eq!(
+        folded("f = if 2 > 1 then \"yes\" else \"no\"", "f"),
+        "\"yes\": String"
+    );
+    assert_eq!(
+        folded("f = if false then 1 else if true then 2 else 3", "f"),
+        "2: Int"
+    );
+    assert_eq!(
+        folded("f = match 3 to 2 => 20 to n => n * 10", "f"),
+        "30: Int"
+    );
+}
+
+#[test]
+fn let_bindings() {
+    assert_eq!(folded("f = let x = 4 in x * x", "f"), "16: Int");
+    assert_eq!(
+        folded("f = let a = 2\n        b = a + 1\n    in a * b", "f"),
+        "6: Int"
+    );
+    assert_eq!(
+        folded("f = let s = \"a\" in concat s s", "f"),
+        "\"aa\": String"
+    );
+
+    // Bindings captured by lambdas are kept for the lambdas to take
+    let body = folded("f = let k = 3 in (\\n: Int . n + k) (k + 1)", "f");
+    assert!(body.starts_with("with (set k: Int = (3: Int))"), "{}", body);
+    assert!(body.contains("(4: Int)"), "{}", body);
+}
+
+#[test]
+fn runtime_operations() {
+    // Overflow wraps or panics depending on whether it is checked, and division by zero panics
+    let body = folded("f = 9223372036854775807 + 1", "f");
+    assert!(body.contains(" + "), "{}", body);
+    let body = folded("f = (200 : UInt8) * 2", "f");
+    assert!(body.contains(" * "), "{}", body);
+    let body = folded("f = 1 / 0", "f");
+    assert!(body.contains(" / "), "{}", body);
+    let body = folded("f = 1 % 0", "f");
+    assert!(body.contains(" % "), "{}", body);
+
+    // Arms of matches that are not folded are only taken at runtime
+    let body = folded("f x: Bool = if x then 1 + 1 else 0", "f");
+    assert!(body.starts_with("match"), "{}", body);
+}
+
+#[test]
+fn shadowed() {
+    // Constructors declared by a program shadow the intrinsics with the same name. The prelude
+    // uses `true` as a `Bool`, so it is left out
+    let options = CompilerOptions {
+        prelude: false,
+        ..CompilerOptions::default()
+    };
+    let body = folded_with("type T = true | other\nf = 1 < 2", "f", options);
+    assert!(body.contains(" < "), "{}", body);
+}
@@ -0,0 +1,259 @@
+//! Compiles every Closey program in this directory with the LLVM backend and compares the generated
+//! code with its snapshot in `tests/golden`. When `llc` and the runtime library are available, the
+//! programs are also built and run, and what they print is compared with the expected output next
+//! to the snapshot, along with what they print to stderr for programs that are expected to panic.
+//! Each program is built with the native backend as well, and has to print the same. Running with
+//! `UPDATE_GOLDEN=1` writes the snapshots and expected output instead of comparing them.
+
+use std::env;
+use std::fs;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use closeyc::backends::ir as backend_ir;
+use closeyc::compiler::Compiler;
+use closeyc::linker::{self, LinkError};
+use closeyc::options::CompilerOptions;
+use closeyc::project::SOURCE_EXTENSION;
+
+mod common;
+
+/// The name of the module programs are compiled as, the same as `closeyc build`.
+const MODULE: &str = "Main";
+
+/// The programs the native backend does not build correctly yet, with what goes wrong. They are
+/// still built and run, and are reported once they print what is expected.
+const NATIVE_FAILURES: &[(&str, &str)] = &[
+    ("partial_intrinsics", "folding a Church list crashes"),
+    ("propagate", "`?` on an option crashes"),
+];
+
+/// Returns the directory the test programs are in.
+fn tests_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+/// Returns the test programs, sorted by path.
+fn programs() -> Vec<PathBuf> {
+    let mut programs: Vec<_> = fs::read_dir(tests_dir())
+        .unwrap()
+        .map(|v| v.unwrap().path())
+        .filter(|v| v.extension().and_then(|v| v.to_str()) == Some(SOURCE_EXTENSION))
+        .collect();
+    programs.sort();
+    programs
+}
+
+/// Removes the differences between outputs that do not matter: trailing whitespace, blank lines at
+/// the end, and Windows line endings in checked out files.
+fn normalise(s: &str) -> String {
+    let mut out: String = s.lines().map(|v| format!("{}\n", v.trim_end())).collect();
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+/// Compares an output with the file holding what it is expected to be, or writes the file if
+/// snapshots are being updated. Returns an error describing the first difference.
+fn compare(actual: &str, expected: &Path, update: bool) -> Result<(), String> {
+    let actual = normalise(actual);
+    if update {
+        return fs::write(expected, actual)
+            .map_err(|e| format!("could not write {}: {}", expected.display(), e));
+    }
+
+    let expected_contents = match fs::read_to_string(expected) {
+        Ok(v) => normalise(&v),
+        Err(e) => {
+            return Err(format!(
+                "could not read {}: {}; run with UPDATE_GOLDEN=1 to create it",
+                expected.display(),
+                e
+            ))
+        }
+    };
+
+    if actual == expected_contents {
+        return Ok(());
+    }
+
+    let line = actual
+        .lines()
+        .zip(expected_contents.lines())
+        .position(|(a, e)| a != e)
+        .unwrap_or_else(|| {
+            actual
+                .lines()
+                .count()
+                .min(expected_contents.lines().count())
+        });
+    Err(format!(
+        "differs from {} at line {}:\n    expected: {}\n    found:    {}",
+        expected.display(),
+        line + 1,
+        expected_contents
+            .lines()
+            .nth(line)
+            .unwrap_or("<end of file>"),
+        actual.lines().nth(line).unwrap_or("<end of file>")
+    ))
+}
+
+/// Compiles the source of a program into the LLVM IR of an executable.
+fn compile(compiler: &mut Compiler, source: &str) -> Result<String, String> {
+    let output = compiler.check(MODULE, source);
+    let error = output.errors().next().map(|e| e.message.clone());
+    let ir = output.value.ok_or_else(|| {
+        format!(
+            "the program does not compile: {}",
+            error.unwrap_or_default()
+        )
+    })?;
+
+    let mut module = compiler.lower(&ir, MODULE).unwrap();
+    backend_ir::remove_unused_functions(&mut module, &["main"]);
+    Ok(compiler.codegen_llvm(&module, true))
+}
+
+/// Builds LLVM IR into an executable and runs it, returning what it printed to stdout and stderr.
+/// Returns `None` if `llc`, the linker, or the runtime library is missing, or `llc` is too old.
+fn run_llvm(
+    code: &str,
+    name: &str,
+    options: &CompilerOptions,
+) -> Result<Option<(String, String)>, String> {
+    let object = linker::temp_path(&format!("{}.o", name));
+    let executable = linker::temp_path(&format!("{}.out", name));
+    let result = linker::compile_llvm_ir(code, &object, options)
+        .and_then(|_| linker::link(&object, &executable.display().to_string(), options));
+    let _ = fs::remove_file(&object);
+
+    match result {
+        Ok(()) => execute(&executable).map(Some),
+        Err(LinkError::MissingTool(..))
+        | Err(LinkError::MissingRuntime(_))
+        | Err(LinkError::OldLlvm(_)) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Builds the source of a program into an executable with `closeyc build` and the native backend,
+/// and runs it, returning what it printed to stdout and stderr. Returns `None` if the linker or the
+/// runtime library is missing.
+fn run_native(source: &str, name: &str) -> Result<Option<(String, String)>, String> {
+    let executable = linker::temp_path(&format!("{}.native", name));
+    let output = Command::new(env!("CARGO_BIN_EXE_closeyc"))
+        .args(["build", "--backend", "native", "-e", source, "-o"])
+        .arg(&executable)
+        .output()
+        .map_err(|e| format!("could not run closeyc: {}", e))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("could not run") || stderr.contains("runtime library was not found") {
+        return Ok(None);
+    } else if !output.status.success() {
+        return Err(format!("the native backend failed: {}", stderr.trim()));
+    }
+    execute(&executable).map(Some)
+}
+
+/// Runs an executable and removes it, returning what it printed to stdout and stderr. Only what a
+/// program prints is compared, since every test program prints its checks rather than exiting with
+/// them, and programs that panic print where and why to stderr.
+fn execute(executable: &Path) -> Result<(String, String), String> {
+    let output = Command::new(executable).output();
+    let _ = fs::remove_file(executable);
+    let output = output.map_err(|e| format!("could not run the program: {}", e))?;
+    if let Some(signal) = output.status.signal() {
+        return Err(format!("the program was killed by signal {}", signal));
+    }
+
+    Ok((
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    ))
+}
+
+/// Compares what a program printed with its expected output. Most programs print nothing to
+/// stderr, so they have no file for it.
+fn compare_output(
+    name: &str,
+    (stdout, stderr): &(String, String),
+    update: bool,
+) -> Result<(), String> {
+    let golden = tests_dir().join("golden");
+    compare(stdout, &golden.join(format!("{}.out", name)), update)?;
+    let expected = golden.join(format!("{}.err", name));
+    if !stderr.is_empty() || expected.exists() {
+        compare(stderr, &expected, update)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn golden() {
+    let update = env::var("UPDATE_GOLDEN").as_deref() == Ok("1");
+    let golden = tests_dir().join("golden");
+    let options = CompilerOptions::default();
+
+    let mut failures = vec![];
+    let mut skipped = false;
+    for path in programs() {
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let source = match common::read_program(&path) {
+            Ok(v) => v,
+            Err(e) => {
+                failures.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+
+        let mut compiler = Compiler::new(options.clone());
+        let code = match compile(&mut compiler, &source) {
+            Ok(v) => v,
+            Err(e) => {
+                failures.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+
+        if let Err(e) = compare(&code, &golden.join(format!("{}.ll", name)), update) {
+            failures.push(format!("{}: {}", name, e));
+        }
+
+        match run_llvm(&code, &name, &options) {
+            Ok(Some(output)) => {
+                if let Err(e) = compare_output(&name, &output, update) {
+                    failures.push(format!("{}: {}", name, e));
+                }
+            }
+            Ok(None) => skipped = true,
+            Err(e) => failures.push(format!("{}: {}", name, e)),
+        }
+
+        // The expected output is only written from the LLVM backend
+        let known = NATIVE_FAILURES.iter().find(|(v, _)| *v == name);
+        let result = run_native(&source, &name)
+            .and_then(|v| v.map(|v| compare_output(&name, &v, false)).transpose());
+        match (result, known) {
+            (Ok(None), _) => skipped = true,
+            (Ok(Some(())), Some(_)) => failures.push(format!(
+                "{}: passes with the native backend, so remove it from NATIVE_FAILURES",
+                name
+            )),
+            (Ok(Some(())), None) | (Err(_), Some(_)) => (),
+            (Err(e), None) => failures.push(format!("{} with the native backend: {}", name, e)),
+        }
+    }
+
+    if skipped {
+        eprintln!(
+            "llc, the linker, or the runtime library is missing, or llc is too old, so programs were not run"
+        );
+    }
+    if !failures.is_empty() {
+        panic!("{} failures:\n{}", failures.len(), failures.join("\n"));
+    }
+}
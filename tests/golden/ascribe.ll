@@ -0,0 +1,82 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_int_add"(i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_println"(i64)
+declare void @"exit"(i64)
+declare void @"rcfuncfree"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:10:17" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".13"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".14"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".14" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"id_str"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @"sum"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_add"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"check"(i64 1)
+    %l1 = call i64 @"sum"(i64 2, i64 3)
+    %l2 = call i64 @"closey_int_eq"(i64 %l1, i64 5)
+    %l3 = call i64 @"check"(i64 %l2)
+    %l4 = call i64 @"check"(i64 1)
+    %l5 = call i64 @"check"(i64 1)
+    %l6 = call i64 @"id_str"(i64 %l5)
+    call void @"rcfuncfree"(i64 %l5)
+    %l7 = call i64 @"string_concat"(i64 %l4, i64 %l6)
+    call void @"rcfuncfree"(i64 %l4)
+    call void @"rcfuncfree"(i64 %l6)
+    %l8 = call i64 @"string_concat"(i64 %l3, i64 %l7)
+    call void @"rcfuncfree"(i64 %l3)
+    %l9 = call i64 @"string_concat"(i64 %l0, i64 %l8)
+    call void @"rcfuncfree"(i64 %l0)
+    %l10 = call i64 @"closey_println"(i64 %l9)
+    ret i64 %l10
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
@@ -0,0 +1,281 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_bool_not"(i64)
+declare i64 @"closey_int_div"(i64, i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_int_lt"(i64, i64)
+declare i64 @"closey_int_mod"(i64, i64, i64)
+declare i64 @"closey_int_ne"(i64, i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_println"(i64)
+declare void @"exit"(i64)
+declare i64 @"rcclosure"(i64, i64, ptr)
+declare void @"rcfuncfree"(i64)
+declare void @"rcinc"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:5:37" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:6:26" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:6:16" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:11:17" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:5:26" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:3:19" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:4:18" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:7:219" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.19 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.20 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".13"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".14"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_lt"(i64 %a0, i64 10)
+    ret i64 %l0
+}
+
+define i64 @".15"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".16"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_lt"(i64 10, i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @".17"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".18"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 7, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    %l0 = call i64 @"closey_int_mod"(i64 %a0, i64 %a1, i64 %t0)
+    %l1 = call i64 @"closey_int_eq"(i64 %l0, i64 0)
+    ret i64 %l1
+}
+
+define i64 @".19"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".20"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    %l0 = call i64 @"closey_int_div"(i64 10, i64 %a0, i64 %t0)
+    %l1 = call i64 @"closey_int_lt"(i64 %l0, i64 5)
+    ret i64 %l1
+}
+
+define i64 @".21"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @"small"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_eq"(i64 %a0, i64 0)
+    %t0 = ptrtoint ptr @".19" to i64
+    %t1 = sub i64 %t0, 15
+    %l1 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".20" to i64
+    %t3 = sub i64 %t2, 15
+    %t4 = alloca [1 x i64], align 8
+    %t5 = getelementptr [1 x i64], ptr %t4, i64 0, i64 0
+    store i64 %a0, ptr %t5, align 8
+    %l2 = call i64 @"rcclosure"(i64 %t3, i64 1, ptr %t4)
+    %t6 = and i64 %l2, 1
+    %t7 = icmp eq i64 %t6, 0
+    br i1 %t7, label %t8.inc, label %t8.end
+t8.inc:
+    call void @"rcinc"(i64 %l2)
+    br label %t8.end
+t8.end:
+    %l3 = call i64 @"closey_array_push"(i64 %l1, i64 %l2)
+    %t9 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t9, i64 %l3, i64 %t10)
+    call void @"rcfuncfree"(i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l4
+}
+
+define i64 @".22"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = tail call i64 @"small"(i64 3)
+    ret i64 %l0
+}
+
+define i64 @".33"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".34"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".33" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".34" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"divides"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_ne"(i64 %a0, i64 0)
+    %t0 = ptrtoint ptr @".17" to i64
+    %t1 = sub i64 %t0, 15
+    %l1 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".18" to i64
+    %t3 = sub i64 %t2, 15
+    %t4 = alloca [2 x i64], align 8
+    %t5 = getelementptr [2 x i64], ptr %t4, i64 0, i64 0
+    store i64 %a1, ptr %t5, align 8
+    %t6 = getelementptr [2 x i64], ptr %t4, i64 0, i64 1
+    store i64 %a0, ptr %t6, align 8
+    %l2 = call i64 @"rcclosure"(i64 %t3, i64 2, ptr %t4)
+    %t7 = and i64 %l2, 1
+    %t8 = icmp eq i64 %t7, 0
+    br i1 %t8, label %t9.inc, label %t9.end
+t9.inc:
+    call void @"rcinc"(i64 %l2)
+    br label %t9.end
+t9.end:
+    %l3 = call i64 @"closey_array_push"(i64 %l1, i64 %l2)
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %t11 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t10, i64 %l3, i64 %t11)
+    call void @"rcfuncfree"(i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l4
+}
+
+define i64 @"in_range"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_lt"(i64 1, i64 %a0)
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    %l1 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".14" to i64
+    %t3 = sub i64 %t2, 15
+    %t4 = alloca [1 x i64], align 8
+    %t5 = getelementptr [1 x i64], ptr %t4, i64 0, i64 0
+    store i64 %a0, ptr %t5, align 8
+    %l2 = call i64 @"rcclosure"(i64 %t3, i64 1, ptr %t4)
+    %t6 = and i64 %l2, 1
+    %t7 = icmp eq i64 %t6, 0
+    br i1 %t7, label %t8.inc, label %t8.end
+t8.inc:
+    call void @"rcinc"(i64 %l2)
+    br label %t8.end
+t8.end:
+    %l3 = call i64 @"closey_array_push"(i64 %l1, i64 %l2)
+    %t9 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.9, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t9, i64 %l3, i64 %t10)
+    call void @"rcfuncfree"(i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l4
+}
+
+define i64 @"outside"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_lt"(i64 %a0, i64 1)
+    %t0 = ptrtoint ptr @".15" to i64
+    %t1 = sub i64 %t0, 15
+    %l1 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".16" to i64
+    %t3 = sub i64 %t2, 15
+    %t4 = alloca [1 x i64], align 8
+    %t5 = getelementptr [1 x i64], ptr %t4, i64 0, i64 0
+    store i64 %a0, ptr %t5, align 8
+    %l2 = call i64 @"rcclosure"(i64 %t3, i64 1, ptr %t4)
+    %t6 = and i64 %l2, 1
+    %t7 = icmp eq i64 %t6, 0
+    br i1 %t7, label %t8.inc, label %t8.end
+t8.inc:
+    call void @"rcinc"(i64 %l2)
+    br label %t8.end
+t8.end:
+    %l3 = call i64 @"closey_array_push"(i64 %l1, i64 %l2)
+    %t9 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.10, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t9, i64 %l3, i64 %t10)
+    call void @"rcfuncfree"(i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l4
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"in_range"(i64 5)
+    %l1 = call i64 @"check"(i64 %l0)
+    %l2 = call i64 @"in_range"(i64 10)
+    %l3 = call i64 @"closey_bool_not"(i64 %l2)
+    %l4 = call i64 @"check"(i64 %l3)
+    %l5 = call i64 @"outside"(i64 0)
+    %l6 = call i64 @"check"(i64 %l5)
+    %l7 = call i64 @"outside"(i64 5)
+    %l8 = call i64 @"closey_bool_not"(i64 %l7)
+    %l9 = call i64 @"check"(i64 %l8)
+    %l10 = call i64 @"divides"(i64 3, i64 9)
+    %l11 = call i64 @"check"(i64 %l10)
+    %l12 = call i64 @"divides"(i64 0, i64 9)
+    %l13 = call i64 @"closey_bool_not"(i64 %l12)
+    %l14 = call i64 @"check"(i64 %l13)
+    %l15 = call i64 @"small"(i64 0)
+    %t0 = ptrtoint ptr @".21" to i64
+    %t1 = sub i64 %t0, 15
+    %l16 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".22" to i64
+    %t3 = sub i64 %t2, 15
+    %l17 = call i64 @"closey_array_push"(i64 %l16, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.11, i64 32) to i64
+    %l18 = call i64 @"closey_match"(i64 %l15, i64 %t4, i64 %l17, i64 %t5)
+    %l19 = call i64 @"check"(i64 %l18)
+    %l20 = call i64 @"check"(i64 1)
+    %l21 = call i64 @"check"(i64 1)
+    %l22 = call i64 @"check"(i64 1)
+    %l23 = call i64 @"check"(i64 1)
+    %l24 = call i64 @"string_concat"(i64 %l22, i64 %l23)
+    call void @"rcfuncfree"(i64 %l22)
+    call void @"rcfuncfree"(i64 %l23)
+    %l25 = call i64 @"string_concat"(i64 %l21, i64 %l24)
+    call void @"rcfuncfree"(i64 %l21)
+    %l26 = call i64 @"string_concat"(i64 %l20, i64 %l25)
+    call void @"rcfuncfree"(i64 %l20)
+    %l27 = call i64 @"string_concat"(i64 %l19, i64 %l26)
+    call void @"rcfuncfree"(i64 %l19)
+    %l28 = call i64 @"string_concat"(i64 %l14, i64 %l27)
+    call void @"rcfuncfree"(i64 %l14)
+    %l29 = call i64 @"string_concat"(i64 %l11, i64 %l28)
+    call void @"rcfuncfree"(i64 %l11)
+    %l30 = call i64 @"string_concat"(i64 %l9, i64 %l29)
+    call void @"rcfuncfree"(i64 %l9)
+    %l31 = call i64 @"string_concat"(i64 %l6, i64 %l30)
+    call void @"rcfuncfree"(i64 %l6)
+    %l32 = call i64 @"string_concat"(i64 %l4, i64 %l31)
+    call void @"rcfuncfree"(i64 %l4)
+    %l33 = call i64 @"string_concat"(i64 %l1, i64 %l32)
+    call void @"rcfuncfree"(i64 %l1)
+    %l34 = call i64 @"closey_println"(i64 %l33)
+    ret i64 %l34
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
@@ -0,0 +1,201 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_int_add"(i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_int_lt"(i64, i64)
+declare i64 @"closey_int_mul"(i64, i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_println"(i64)
+declare void @"exit"(i64)
+declare i64 @"rcclosure"(i64, i64, ptr)
+declare void @"rcfuncfree"(i64)
+declare void @"rcinc"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:3:34" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:5:44" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:10:17" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".14"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".15"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".13"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 7, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_lt"(i64 %a2, i64 1)
+    %t0 = ptrtoint ptr @".14" to i64
+    %t1 = sub i64 %t0, 15
+    %t2 = alloca [1 x i64], align 8
+    %t3 = getelementptr [1 x i64], ptr %t2, i64 0, i64 0
+    store i64 %a0, ptr %t3, align 8
+    %l1 = call i64 @"rcclosure"(i64 %t1, i64 1, ptr %t2)
+    %t4 = and i64 %l1, 1
+    %t5 = icmp eq i64 %t4, 0
+    br i1 %t5, label %t6.inc, label %t6.end
+t6.inc:
+    call void @"rcinc"(i64 %l1)
+    br label %t6.end
+t6.end:
+    %l2 = call i64 @"closey_array_of"(i64 %l1)
+    %t7 = ptrtoint ptr @".15" to i64
+    %t8 = sub i64 %t7, 15
+    %t9 = alloca [1 x i64], align 8
+    %t10 = getelementptr [1 x i64], ptr %t9, i64 0, i64 0
+    store i64 %a1, ptr %t10, align 8
+    %l3 = call i64 @"rcclosure"(i64 %t8, i64 1, ptr %t9)
+    %t11 = and i64 %l3, 1
+    %t12 = icmp eq i64 %t11, 0
+    br i1 %t12, label %t13.inc, label %t13.end
+t13.inc:
+    call void @"rcinc"(i64 %l3)
+    br label %t13.end
+t13.end:
+    %l4 = call i64 @"closey_array_push"(i64 %l2, i64 %l3)
+    %t14 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    %t15 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    %l5 = call i64 @"closey_match"(i64 %l0, i64 %t14, i64 %l4, i64 %t15)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l3)
+    call void @"rcfuncfree"(i64 %l3)
+    ret i64 %l5
+}
+
+define i64 @".17"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 7, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_add"(i64 %a0, i64 %a2)
+    %l1 = call i64 @"closey_int_add"(i64 %l0, i64 %a1)
+    ret i64 %l1
+}
+
+define i64 @".16"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = tail call i64 @".17"(i64 %a1, i64 %a0, i64 2)
+    ret i64 %l0
+}
+
+define i64 @".19"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".20"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".18"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 7, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_lt"(i64 %a2, i64 1)
+    %t0 = ptrtoint ptr @".19" to i64
+    %t1 = sub i64 %t0, 15
+    %t2 = alloca [1 x i64], align 8
+    %t3 = getelementptr [1 x i64], ptr %t2, i64 0, i64 0
+    store i64 %a0, ptr %t3, align 8
+    %l1 = call i64 @"rcclosure"(i64 %t1, i64 1, ptr %t2)
+    %t4 = and i64 %l1, 1
+    %t5 = icmp eq i64 %t4, 0
+    br i1 %t5, label %t6.inc, label %t6.end
+t6.inc:
+    call void @"rcinc"(i64 %l1)
+    br label %t6.end
+t6.end:
+    %l2 = call i64 @"closey_array_of"(i64 %l1)
+    %t7 = ptrtoint ptr @".20" to i64
+    %t8 = sub i64 %t7, 15
+    %t9 = alloca [1 x i64], align 8
+    %t10 = getelementptr [1 x i64], ptr %t9, i64 0, i64 0
+    store i64 %a1, ptr %t10, align 8
+    %l3 = call i64 @"rcclosure"(i64 %t8, i64 1, ptr %t9)
+    %t11 = and i64 %l3, 1
+    %t12 = icmp eq i64 %t11, 0
+    br i1 %t12, label %t13.inc, label %t13.end
+t13.inc:
+    call void @"rcinc"(i64 %l3)
+    br label %t13.end
+t13.end:
+    %l4 = call i64 @"closey_array_push"(i64 %l2, i64 %l3)
+    %t14 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    %t15 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %l5 = call i64 @"closey_match"(i64 %l0, i64 %t14, i64 %l4, i64 %t15)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l3)
+    call void @"rcfuncfree"(i64 %l3)
+    ret i64 %l5
+}
+
+define i64 @".21"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".22"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"bound"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_mul"(i64 %a0, i64 2)
+    %l1 = tail call i64 @".18"(i64 %l0, i64 %a0, i64 0)
+    ret i64 %l1
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".21" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".22" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"pick"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = tail call i64 @".13"(i64 %a0, i64 %a1, i64 3)
+    ret i64 %l0
+}
+
+define i64 @"nested"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = tail call i64 @".16"(i64 %a0, i64 1)
+    ret i64 %l0
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"pick"(i64 5, i64 7)
+    %l1 = call i64 @"closey_int_eq"(i64 %l0, i64 7)
+    %l2 = call i64 @"check"(i64 %l1)
+    %l3 = call i64 @"nested"(i64 4)
+    %l4 = call i64 @"closey_int_eq"(i64 %l3, i64 7)
+    %l5 = call i64 @"check"(i64 %l4)
+    %l6 = call i64 @"bound"(i64 3)
+    %l7 = call i64 @"closey_int_eq"(i64 %l6, i64 6)
+    %l8 = call i64 @"check"(i64 %l7)
+    %l9 = call i64 @"string_concat"(i64 %l5, i64 %l8)
+    call void @"rcfuncfree"(i64 %l5)
+    call void @"rcfuncfree"(i64 %l8)
+    %l10 = call i64 @"string_concat"(i64 %l2, i64 %l9)
+    call void @"rcfuncfree"(i64 %l2)
+    %l11 = call i64 @"closey_println"(i64 %l10)
+    ret i64 %l11
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
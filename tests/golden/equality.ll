@@ -0,0 +1,262 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_bool_not"(i64)
+declare i64 @"closey_data_0"(i64)
+declare i64 @"closey_data_1"(i64, i64)
+declare i64 @"closey_data_2"(i64, i64, i64)
+declare i64 @"closey_eq_array"(i64, i64, i64)
+declare i64 @"closey_eq_data"(i64, i64, i64, i64)
+declare i64 @"closey_eq_map"(i64, i64, i64, i64)
+declare i64 @"closey_eq_option"(i64, i64, i64)
+declare i64 @"closey_eq_result"(i64, i64, i64)
+declare i64 @"closey_err"(i64)
+declare i64 @"closey_float_eq"(i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_map_insert_int"(i64, i64, i64)
+declare i64 @"closey_map_new"(i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_ok"(i64)
+declare i64 @"closey_println"(i64)
+declare i64 @"closey_some"(i64)
+declare i64 @"closey_string_eq"(i64, i64)
+declare void @"exit"(i64)
+declare void @"rcfuncfree"(i64)
+declare void @"rcinc"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:9:17" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"a" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"b" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"no" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"one" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"two" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+@.string.19 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [19 x i8] }> <{ ptr null, i64 27, i64 0, i64 4611686018427387904, i64 19, [19 x i8] c"1circle,2rect,0dot," }>, align 8
+@.string.20 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [11 x i8] }> <{ ptr null, i64 19, i64 0, i64 4611686018427387904, i64 11, [11 x i8] c"0nil,2cons," }>, align 8
+
+define i64 @".13"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".14"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".14" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_data_2"(i64 1, i64 1, i64 2)
+    %l1 = call i64 @"eq.Shape"(i64 %l0, i64 %l0)
+    %l2 = call i64 @"check"(i64 %l1)
+    %l3 = call i64 @"closey_data_2"(i64 1, i64 2, i64 1)
+    %l4 = call i64 @"eq.Shape"(i64 %l0, i64 %l3)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l3)
+    %l5 = call i64 @"closey_bool_not"(i64 %l4)
+    %l6 = call i64 @"check"(i64 %l5)
+    %l7 = call i64 @"closey_data_1"(i64 0, i64 4602678819172646912)
+    %l8 = call i64 @"closey_data_0"(i64 2)
+    %l9 = call i64 @"eq.Shape"(i64 %l7, i64 %l8)
+    call void @"rcfuncfree"(i64 %l7)
+    %l10 = call i64 @"closey_bool_not"(i64 %l9)
+    %l11 = call i64 @"check"(i64 %l10)
+    %l12 = call i64 @"closey_data_1"(i64 0, i64 4607182418800017408)
+    %l13 = call i64 @"closey_data_0"(i64 0)
+    %t0 = and i64 %l12, 1
+    %t1 = icmp eq i64 %t0, 0
+    br i1 %t1, label %t2.inc, label %t2.end
+t2.inc:
+    call void @"rcinc"(i64 %l12)
+    br label %t2.end
+t2.end:
+    %t3 = and i64 %l13, 1
+    %t4 = icmp eq i64 %t3, 0
+    br i1 %t4, label %t5.inc, label %t5.end
+t5.inc:
+    call void @"rcinc"(i64 %l13)
+    br label %t5.end
+t5.end:
+    %l14 = call i64 @"closey_data_2"(i64 1, i64 %l12, i64 %l13)
+    call void @"rcfuncfree"(i64 %l12)
+    call void @"rcfuncfree"(i64 %l13)
+    %t6 = and i64 %l8, 1
+    %t7 = icmp eq i64 %t6, 0
+    br i1 %t7, label %t8.inc, label %t8.end
+t8.inc:
+    call void @"rcinc"(i64 %l8)
+    br label %t8.end
+t8.end:
+    %t9 = and i64 %l14, 1
+    %t10 = icmp eq i64 %t9, 0
+    br i1 %t10, label %t11.inc, label %t11.end
+t11.inc:
+    call void @"rcinc"(i64 %l14)
+    br label %t11.end
+t11.end:
+    %l15 = call i64 @"closey_data_2"(i64 1, i64 %l8, i64 %l14)
+    call void @"rcfuncfree"(i64 %l8)
+    call void @"rcfuncfree"(i64 %l14)
+    %l16 = call i64 @"eq.List"(i64 %l15, i64 %l15)
+    call void @"rcfuncfree"(i64 %l15)
+    %l17 = call i64 @"check"(i64 %l16)
+    %t12 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    %l18 = call i64 @"closey_array_of"(i64 %t12)
+    %t13 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %l19 = call i64 @"closey_array_push"(i64 %l18, i64 %t13)
+    %t14 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    %l20 = call i64 @"closey_array_of"(i64 %t14)
+    %l21 = call i64 @"eq.Array String"(i64 %l19, i64 %l20)
+    %l22 = call i64 @"closey_bool_not"(i64 %l21)
+    %l23 = call i64 @"check"(i64 %l22)
+    %l24 = call i64 @"closey_some"(i64 3)
+    %l25 = call i64 @"closey_some"(i64 %l24)
+    %l26 = call i64 @"eq.Option (Option Int)"(i64 %l25, i64 %l25)
+    %l27 = call i64 @"check"(i64 %l26)
+    %l28 = call i64 @"closey_ok"(i64 1)
+    %t15 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    %l29 = call i64 @"closey_err"(i64 %t15)
+    %l30 = call i64 @"eq.Result Int"(i64 %l28, i64 %l29)
+    %l31 = call i64 @"closey_bool_not"(i64 %l30)
+    %l32 = call i64 @"check"(i64 %l31)
+    %l33 = call i64 @"closey_map_new"(i64 4)
+    %t16 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %l34 = call i64 @"closey_map_insert_int"(i64 %l33, i64 1, i64 %t16)
+    %t17 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %l35 = call i64 @"closey_map_insert_int"(i64 %l34, i64 2, i64 %t17)
+    %l36 = call i64 @"closey_map_new"(i64 4)
+    %t18 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %l37 = call i64 @"closey_map_insert_int"(i64 %l36, i64 2, i64 %t18)
+    %t19 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %l38 = call i64 @"closey_map_insert_int"(i64 %l37, i64 1, i64 %t19)
+    %l39 = call i64 @"eq.Map Int String"(i64 %l35, i64 %l38)
+    %l40 = call i64 @"check"(i64 %l39)
+    %l41 = call i64 @"string_concat"(i64 %l32, i64 %l40)
+    call void @"rcfuncfree"(i64 %l32)
+    call void @"rcfuncfree"(i64 %l40)
+    %l42 = call i64 @"string_concat"(i64 %l27, i64 %l41)
+    call void @"rcfuncfree"(i64 %l27)
+    %l43 = call i64 @"string_concat"(i64 %l23, i64 %l42)
+    call void @"rcfuncfree"(i64 %l23)
+    %l44 = call i64 @"string_concat"(i64 %l17, i64 %l43)
+    call void @"rcfuncfree"(i64 %l17)
+    %l45 = call i64 @"string_concat"(i64 %l11, i64 %l44)
+    call void @"rcfuncfree"(i64 %l11)
+    %l46 = call i64 @"string_concat"(i64 %l6, i64 %l45)
+    call void @"rcfuncfree"(i64 %l6)
+    %l47 = call i64 @"string_concat"(i64 %l2, i64 %l46)
+    call void @"rcfuncfree"(i64 %l2)
+    %l48 = call i64 @"closey_println"(i64 %l47)
+    ret i64 %l48
+}
+
+define i64 @"eq.Shape"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"eq.Float" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @"eq.Int" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr @"eq.Int" to i64
+    %t5 = sub i64 %t4, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t5)
+    %t6 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.19, i64 32) to i64
+    %l3 = call i64 @"closey_eq_data"(i64 %a0, i64 %a1, i64 %t6, i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l3
+}
+
+define i64 @"eq.List"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"eq.Shape" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @"eq.List" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.20, i64 32) to i64
+    %l2 = call i64 @"closey_eq_data"(i64 %a0, i64 %a1, i64 %t4, i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    ret i64 %l2
+}
+
+define i64 @"eq.Array String"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"eq.String" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_eq_array"(i64 %a0, i64 %a1, i64 %t1)
+    ret i64 %l0
+}
+
+define i64 @"eq.Option (Option Int)"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"eq.Option Int" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_eq_option"(i64 %a0, i64 %a1, i64 %t1)
+    ret i64 %l0
+}
+
+define i64 @"eq.Result Int"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"eq.Int" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_eq_result"(i64 %a0, i64 %a1, i64 %t1)
+    ret i64 %l0
+}
+
+define i64 @"eq.Map Int String"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"eq.Int" to i64
+    %t1 = sub i64 %t0, 15
+    %t2 = ptrtoint ptr @"eq.String" to i64
+    %t3 = sub i64 %t2, 15
+    %l0 = call i64 @"closey_eq_map"(i64 %a0, i64 %a1, i64 %t1, i64 %t3)
+    ret i64 %l0
+}
+
+define i64 @"eq.Float"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_float_eq"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"eq.Int"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_eq"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"eq.String"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_string_eq"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"eq.Option Int"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"eq.Int" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_eq_option"(i64 %a0, i64 %a1, i64 %t1)
+    ret i64 %l0
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
@@ -0,0 +1,246 @@
+declare i64 @"closey_append"(i64, i64)
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_err"(i64)
+declare i64 @"closey_is_err"(i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_println"(i64)
+declare i64 @"closey_read_to_string"(i64)
+declare i64 @"closey_string_eq"(i64, i64)
+declare i64 @"closey_write"(i64, i64)
+declare void @"exit"(i64)
+declare i64 @"rcclosure"(i64, i64, ptr)
+declare void @"rcfuncfree"(i64)
+declare void @"rcinc"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"cd" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"o_eb" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:6:33" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:16:17" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:3:44" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ab" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:9:34" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [21 x i8] }> <{ ptr null, i64 29, i64 0, i64 4611686018427387904, i64 21, [21 x i8] c"/tmp/closey-files.txt" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"abcd" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [17 x i8] }> <{ ptr null, i64 25, i64 0, i64 4611686018427387904, i64 17, [17 x i8] c"/nonexistent/file" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [21 x i8] }> <{ ptr null, i64 29, i64 0, i64 4611686018427387904, i64 21, [21 x i8] c"/nonexistent/dir/file" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"x" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.19 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.20 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.21 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.22 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.23 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.24 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".13"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_string_eq"(i64 %a2, i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @".14"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @"prelude.read_file"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_read_to_string"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @".15"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = tail call i64 @"prelude.read_file"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @".16"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_err"(i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"prelude.append_file"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_append"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"append_then_read"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    %l0 = call i64 @"prelude.append_file"(i64 %a0, i64 %t0)
+    %t1 = and i64 %a0, 1
+    %t2 = icmp eq i64 %t1, 0
+    br i1 %t2, label %t3.inc, label %t3.end
+t3.inc:
+    call void @"rcinc"(i64 %a0)
+    br label %t3.end
+t3.end:
+    %t4 = ptrtoint ptr @".15" to i64
+    %t5 = sub i64 %t4, 15
+    %t6 = alloca [1 x i64], align 8
+    %t7 = getelementptr [1 x i64], ptr %t6, i64 0, i64 0
+    store i64 %a0, ptr %t7, align 8
+    %l1 = call i64 @"rcclosure"(i64 %t5, i64 1, ptr %t6)
+    %t8 = and i64 %l1, 1
+    %t9 = icmp eq i64 %t8, 0
+    br i1 %t9, label %t10.inc, label %t10.end
+t10.inc:
+    call void @"rcinc"(i64 %l1)
+    br label %t10.end
+t10.end:
+    %l2 = call i64 @"closey_array_of"(i64 %l1)
+    %t11 = ptrtoint ptr @".16" to i64
+    %t12 = sub i64 %t11, 15
+    %l3 = call i64 @"closey_array_push"(i64 %l2, i64 %t12)
+    %t13 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    %t14 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t13, i64 %l3, i64 %t14)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    ret i64 %l4
+}
+
+define i64 @".17"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = tail call i64 @"append_then_read"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @".18"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_err"(i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @".19"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".20"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".19" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".20" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"contents_are"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = and i64 %a1, 1
+    %t1 = icmp eq i64 %t0, 0
+    br i1 %t1, label %t2.inc, label %t2.end
+t2.inc:
+    call void @"rcinc"(i64 %a1)
+    br label %t2.end
+t2.end:
+    %t3 = ptrtoint ptr @".13" to i64
+    %t4 = sub i64 %t3, 15
+    %t5 = alloca [1 x i64], align 8
+    %t6 = getelementptr [1 x i64], ptr %t5, i64 0, i64 0
+    store i64 %a1, ptr %t6, align 8
+    %l0 = call i64 @"rcclosure"(i64 %t4, i64 1, ptr %t5)
+    %t7 = and i64 %l0, 1
+    %t8 = icmp eq i64 %t7, 0
+    br i1 %t8, label %t9.inc, label %t9.end
+t9.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t9.end
+t9.end:
+    %l1 = call i64 @"closey_array_of"(i64 %l0)
+    %t10 = ptrtoint ptr @".14" to i64
+    %t11 = sub i64 %t10, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t11)
+    %t12 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %t13 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %a0, i64 %t12, i64 %l2, i64 %t13)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l3
+}
+
+define i64 @"prelude.write_file"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_write"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"write_then_append"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.9, i64 32) to i64
+    %l0 = call i64 @"prelude.write_file"(i64 %a0, i64 %t0)
+    %t1 = and i64 %a0, 1
+    %t2 = icmp eq i64 %t1, 0
+    br i1 %t2, label %t3.inc, label %t3.end
+t3.inc:
+    call void @"rcinc"(i64 %a0)
+    br label %t3.end
+t3.end:
+    %t4 = ptrtoint ptr @".17" to i64
+    %t5 = sub i64 %t4, 15
+    %t6 = alloca [1 x i64], align 8
+    %t7 = getelementptr [1 x i64], ptr %t6, i64 0, i64 0
+    store i64 %a0, ptr %t7, align 8
+    %l1 = call i64 @"rcclosure"(i64 %t5, i64 1, ptr %t6)
+    %t8 = and i64 %l1, 1
+    %t9 = icmp eq i64 %t8, 0
+    br i1 %t9, label %t10.inc, label %t10.end
+t10.inc:
+    call void @"rcinc"(i64 %l1)
+    br label %t10.end
+t10.end:
+    %l2 = call i64 @"closey_array_of"(i64 %l1)
+    %t11 = ptrtoint ptr @".18" to i64
+    %t12 = sub i64 %t11, 15
+    %l3 = call i64 @"closey_array_push"(i64 %l2, i64 %t12)
+    %t13 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    %t14 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.10, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t13, i64 %l3, i64 %t14)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    ret i64 %l4
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.11, i64 32) to i64
+    %l0 = call i64 @"write_then_append"(i64 %t0)
+    %t1 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.12, i64 32) to i64
+    %l1 = call i64 @"contents_are"(i64 %l0, i64 %t1)
+    call void @"rcfuncfree"(i64 %l0)
+    %l2 = call i64 @"check"(i64 %l1)
+    %t2 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.13, i64 32) to i64
+    %l3 = call i64 @"prelude.read_file"(i64 %t2)
+    %l4 = call i64 @"closey_is_err"(i64 %l3)
+    call void @"rcfuncfree"(i64 %l3)
+    %l5 = call i64 @"check"(i64 %l4)
+    %t3 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.14, i64 32) to i64
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.15, i64 32) to i64
+    %l6 = call i64 @"prelude.write_file"(i64 %t3, i64 %t4)
+    %l7 = call i64 @"closey_is_err"(i64 %l6)
+    call void @"rcfuncfree"(i64 %l6)
+    %l8 = call i64 @"check"(i64 %l7)
+    %l9 = call i64 @"string_concat"(i64 %l5, i64 %l8)
+    call void @"rcfuncfree"(i64 %l5)
+    call void @"rcfuncfree"(i64 %l8)
+    %l10 = call i64 @"string_concat"(i64 %l2, i64 %l9)
+    call void @"rcfuncfree"(i64 %l2)
+    %l11 = call i64 @"closey_println"(i64 %l10)
+    ret i64 %l11
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
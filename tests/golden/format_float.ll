@@ -0,0 +1,121 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_format_float"(i64, i64, i64)
+declare i64 @"closey_format_scientific"(i64, i64, i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_println"(i64)
+declare i64 @"closey_show_float"(i64)
+declare i64 @"closey_string_eq"(i64, i64)
+declare void @"exit"(i64)
+declare void @"rcfuncfree"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:7:17" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"3.14" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"2" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"0.12" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [7 x i8] }> <{ ptr null, i64 15, i64 0, i64 4611686018427387904, i64 7, [7 x i8] c"  10.00" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [6 x i8] }> <{ ptr null, i64 14, i64 0, i64 4611686018427387904, i64 6, [6 x i8] c"1.50e3" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"4e-4" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [23 x i8] }> <{ ptr null, i64 31, i64 0, i64 4611686018427387904, i64 23, [23 x i8] c"100000000000000000000.0" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"0.1" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.19 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.20 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.21 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".13"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".14"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".14" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_format_float"(i64 4614256650576692846, i64 2, i64 0)
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    %l1 = call i64 @"closey_string_eq"(i64 %l0, i64 %t0)
+    %l2 = call i64 @"check"(i64 %l1)
+    %l3 = call i64 @"closey_format_float"(i64 4612811918334230528, i64 0, i64 0)
+    %t1 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %l4 = call i64 @"closey_string_eq"(i64 %l3, i64 %t1)
+    %l5 = call i64 @"check"(i64 %l4)
+    %l6 = call i64 @"closey_format_float"(i64 4593671619917905920, i64 2, i64 0)
+    %t2 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    %l7 = call i64 @"closey_string_eq"(i64 %l6, i64 %t2)
+    %l8 = call i64 @"check"(i64 %l7)
+    %l9 = call i64 @"closey_format_float"(i64 4621816865789157835, i64 2, i64 7)
+    %t3 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %l10 = call i64 @"closey_string_eq"(i64 %l9, i64 %t3)
+    %l11 = call i64 @"check"(i64 %l10)
+    %l12 = call i64 @"closey_format_scientific"(i64 4654311885213007872, i64 2, i64 0)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %l13 = call i64 @"closey_string_eq"(i64 %l12, i64 %t4)
+    %l14 = call i64 @"check"(i64 %l13)
+    %l15 = call i64 @"closey_format_scientific"(i64 4556383256155158972, i64 0, i64 0)
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.9, i64 32) to i64
+    %l16 = call i64 @"closey_string_eq"(i64 %l15, i64 %t5)
+    %l17 = call i64 @"check"(i64 %l16)
+    %l18 = call i64 @"closey_format_float"(i64 4906019910204099648, i64 1, i64 0)
+    %t6 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.10, i64 32) to i64
+    %l19 = call i64 @"closey_string_eq"(i64 %l18, i64 %t6)
+    %l20 = call i64 @"check"(i64 %l19)
+    %l21 = call i64 @"show.Float"(i64 4591870180066957722)
+    %t7 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.11, i64 32) to i64
+    %l22 = call i64 @"closey_string_eq"(i64 %l21, i64 %t7)
+    call void @"rcfuncfree"(i64 %l21)
+    %l23 = call i64 @"check"(i64 %l22)
+    %l24 = call i64 @"string_concat"(i64 %l20, i64 %l23)
+    call void @"rcfuncfree"(i64 %l20)
+    call void @"rcfuncfree"(i64 %l23)
+    %l25 = call i64 @"string_concat"(i64 %l17, i64 %l24)
+    call void @"rcfuncfree"(i64 %l17)
+    %l26 = call i64 @"string_concat"(i64 %l14, i64 %l25)
+    call void @"rcfuncfree"(i64 %l14)
+    %l27 = call i64 @"string_concat"(i64 %l11, i64 %l26)
+    call void @"rcfuncfree"(i64 %l11)
+    %l28 = call i64 @"string_concat"(i64 %l8, i64 %l27)
+    call void @"rcfuncfree"(i64 %l8)
+    %l29 = call i64 @"string_concat"(i64 %l5, i64 %l28)
+    call void @"rcfuncfree"(i64 %l5)
+    %l30 = call i64 @"string_concat"(i64 %l2, i64 %l29)
+    call void @"rcfuncfree"(i64 %l2)
+    %l31 = call i64 @"closey_println"(i64 %l30)
+    ret i64 %l31
+}
+
+define i64 @"show.Float"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_show_float"(i64 %a0)
+    ret i64 %l0
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
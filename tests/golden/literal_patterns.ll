@@ -0,0 +1,265 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_int_add"(i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_println"(i64)
+declare i64 @"closey_some"(i64)
+declare void @"exit"(i64)
+declare void @"rcfuncfree"(i64)
+declare i64 @"string_concat"(i64, i64)
+declare i64 @"string_equals"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"zero" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"one" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"many" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"max" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [5 x i8] }> <{ ptr null, i64 13, i64 0, i64 4611686018427387904, i64 5, [5 x i8] c"other" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:25:17" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [7 x i8] }> <{ ptr null, i64 15, i64 0, i64 4611686018427387904, i64 7, [7 x i8] c"si7,sbn" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:14:23" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [14 x i8] }> <{ ptr null, i64 22, i64 0, i64 4611686018427387904, i64 14, [14 x i8] c"\222,hi\225,hello_" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:10:19" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [7 x i8] }> <{ ptr null, i64 15, i64 0, i64 4611686018427387904, i64 7, [7 x i8] c"i0,i1,_" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:2:15" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"c97,c101,_" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:6:17" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [6 x i8] }> <{ ptr null, i64 14, i64 0, i64 4611686018427387904, i64 6, [6 x i8] c"i255,_" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:18:18" }>, align 8
+@.string.19 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [5 x i8] }> <{ ptr null, i64 13, i64 0, i64 4611686018427387904, i64 5, [5 x i8] c"hello" }>, align 8
+@.string.20 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"hey" }>, align 8
+@.string.21 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.22 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.23 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.24 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.25 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.26 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.27 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.28 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.29 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.30 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".13"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".14"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".15"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".16"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 1
+}
+
+define i64 @".17"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 1
+}
+
+define i64 @".18"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @".19"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 1
+}
+
+define i64 @".20"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 2
+}
+
+define i64 @".21"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @".22"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 7
+}
+
+define i64 @".23"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 2, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_add"(i64 %a1, i64 100)
+    ret i64 %l0
+}
+
+define i64 @".24"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @".25"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".26"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".27"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".28"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".27" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".28" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"first"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".22" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".23" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr @".24" to i64
+    %t5 = sub i64 %t4, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t5)
+    %t6 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.9, i64 32) to i64
+    %t7 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.10, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %a0, i64 %t6, i64 %l2, i64 %t7)
+    ret i64 %l3
+}
+
+define i64 @"greet"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".19" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".20" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr @".21" to i64
+    %t5 = sub i64 %t4, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t5)
+    %t6 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.11, i64 32) to i64
+    %t7 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.12, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %a0, i64 %t6, i64 %l2, i64 %t7)
+    ret i64 %l3
+}
+
+define i64 @"name"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".14" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr @".15" to i64
+    %t5 = sub i64 %t4, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t5)
+    %t6 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.13, i64 32) to i64
+    %t7 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.14, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %a0, i64 %t6, i64 %l2, i64 %t7)
+    ret i64 %l3
+}
+
+define i64 @"vowel"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".16" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".17" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr @".18" to i64
+    %t5 = sub i64 %t4, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t5)
+    %t6 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.15, i64 32) to i64
+    %t7 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.16, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %a0, i64 %t6, i64 %l2, i64 %t7)
+    ret i64 %l3
+}
+
+define i64 @"small"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".25" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".26" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.17, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.18, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"name"(i64 1)
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    %l1 = call i64 @"string_equals"(i64 %l0, i64 %t0)
+    call void @"rcfuncfree"(i64 %l0)
+    %l2 = call i64 @"check"(i64 %l1)
+    %l3 = call i64 @"name"(i64 5)
+    %t1 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %l4 = call i64 @"string_equals"(i64 %l3, i64 %t1)
+    call void @"rcfuncfree"(i64 %l3)
+    %l5 = call i64 @"check"(i64 %l4)
+    %l6 = call i64 @"vowel"(i64 101)
+    %l7 = call i64 @"closey_int_eq"(i64 %l6, i64 1)
+    %l8 = call i64 @"check"(i64 %l7)
+    %t2 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.19, i64 32) to i64
+    %l9 = call i64 @"greet"(i64 %t2)
+    %l10 = call i64 @"closey_int_eq"(i64 %l9, i64 2)
+    %l11 = call i64 @"check"(i64 %l10)
+    %t3 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.20, i64 32) to i64
+    %l12 = call i64 @"greet"(i64 %t3)
+    %l13 = call i64 @"closey_int_eq"(i64 %l12, i64 0)
+    %l14 = call i64 @"check"(i64 %l13)
+    %l15 = call i64 @"closey_some"(i64 7)
+    %l16 = call i64 @"first"(i64 %l15)
+    %l17 = call i64 @"closey_int_eq"(i64 %l16, i64 7)
+    %l18 = call i64 @"check"(i64 %l17)
+    %l19 = call i64 @"closey_some"(i64 2)
+    %l20 = call i64 @"first"(i64 %l19)
+    %l21 = call i64 @"closey_int_eq"(i64 %l20, i64 102)
+    %l22 = call i64 @"check"(i64 %l21)
+    %l23 = call i64 @"small"(i64 255)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l24 = call i64 @"string_equals"(i64 %l23, i64 %t4)
+    call void @"rcfuncfree"(i64 %l23)
+    %l25 = call i64 @"check"(i64 %l24)
+    %l26 = call i64 @"string_concat"(i64 %l22, i64 %l25)
+    call void @"rcfuncfree"(i64 %l22)
+    call void @"rcfuncfree"(i64 %l25)
+    %l27 = call i64 @"string_concat"(i64 %l18, i64 %l26)
+    call void @"rcfuncfree"(i64 %l18)
+    %l28 = call i64 @"string_concat"(i64 %l14, i64 %l27)
+    call void @"rcfuncfree"(i64 %l14)
+    %l29 = call i64 @"string_concat"(i64 %l11, i64 %l28)
+    call void @"rcfuncfree"(i64 %l11)
+    %l30 = call i64 @"string_concat"(i64 %l8, i64 %l29)
+    call void @"rcfuncfree"(i64 %l8)
+    %l31 = call i64 @"string_concat"(i64 %l5, i64 %l30)
+    call void @"rcfuncfree"(i64 %l5)
+    %l32 = call i64 @"string_concat"(i64 %l2, i64 %l31)
+    call void @"rcfuncfree"(i64 %l2)
+    %l33 = call i64 @"closey_println"(i64 %l32)
+    ret i64 %l33
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
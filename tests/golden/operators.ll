@@ -0,0 +1,121 @@
+declare i64 @"call_unknown_arity"(i64, i32, ptr)
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_int_add"(i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_int_mul"(i64, i64)
+declare i64 @"closey_int_sub"(i64, i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_println"(i64)
+declare void @"exit"(i64)
+declare void @"rcfuncfree"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:11:17" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".15"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_mul"(i64 %a0, i64 %a0)
+    %l1 = call i64 @"closey_int_add"(i64 %l0, i64 %a1)
+    ret i64 %l1
+}
+
+define i64 @".13"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_mul"(i64 %a0, i64 10)
+    %l1 = call i64 @"closey_int_add"(i64 %l0, i64 %a1)
+    ret i64 %l1
+}
+
+define i64 @".14"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_sub"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @".16"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".17"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"<+>"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    ret i64 %t1
+}
+
+define i64 @"apply"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 6, [7 x i8] zeroinitializer }> {
+    %t0 = alloca [2 x i64], align 8
+    %t1 = getelementptr [2 x i64], ptr %t0, i64 0, i64 0
+    store i64 %a1, ptr %t1, align 8
+    %t2 = getelementptr [2 x i64], ptr %t0, i64 0, i64 1
+    store i64 %a2, ptr %t2, align 8
+    %l0 = call i64 @"call_unknown_arity"(i64 %a0, i32 2, ptr %t0)
+    ret i64 %l0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".16" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".17" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @".13"(i64 1, i64 2)
+    %l1 = call i64 @".13"(i64 %l0, i64 3)
+    %l2 = call i64 @"closey_int_eq"(i64 %l1, i64 123)
+    %l3 = call i64 @"check"(i64 %l2)
+    %l4 = call i64 @".14"(i64 4, i64 1)
+    %l5 = call i64 @".14"(i64 10, i64 %l4)
+    %l6 = call i64 @"closey_int_eq"(i64 %l5, i64 7)
+    %l7 = call i64 @"check"(i64 %l6)
+    %l8 = call i64 @".15"(i64 2, i64 3)
+    %l9 = call i64 @"closey_int_add"(i64 %l8, i64 1)
+    %l10 = call i64 @"closey_int_eq"(i64 %l9, i64 8)
+    %l11 = call i64 @"check"(i64 %l10)
+    %l12 = call i64 @"check"(i64 1)
+    %l13 = call i64 @"<+>"()
+    %l14 = call i64 @"apply"(i64 %l13, i64 4, i64 5)
+    call void @"rcfuncfree"(i64 %l13)
+    %l15 = call i64 @"closey_int_eq"(i64 %l14, i64 45)
+    %l16 = call i64 @"check"(i64 %l15)
+    %l17 = call i64 @"string_concat"(i64 %l12, i64 %l16)
+    call void @"rcfuncfree"(i64 %l12)
+    call void @"rcfuncfree"(i64 %l16)
+    %l18 = call i64 @"string_concat"(i64 %l11, i64 %l17)
+    call void @"rcfuncfree"(i64 %l11)
+    %l19 = call i64 @"string_concat"(i64 %l7, i64 %l18)
+    call void @"rcfuncfree"(i64 %l7)
+    %l20 = call i64 @"string_concat"(i64 %l3, i64 %l19)
+    call void @"rcfuncfree"(i64 %l3)
+    %l21 = call i64 @"closey_println"(i64 %l20)
+    ret i64 %l21
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
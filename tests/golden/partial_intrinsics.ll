@@ -0,0 +1,428 @@
+declare i64 @"call_unknown_arity"(i64, i32, ptr)
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_int_add"(i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_is_some"(i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_max_int"(i64, i64)
+declare i64 @"closey_println"(i64)
+declare i64 @"closey_some"(i64)
+declare i64 @"closey_string_eq"(i64, i64)
+declare void @"exit"(i64)
+declare i64 @"rcclosure"(i64, i64, ptr)
+declare void @"rcfuncfree"(i64)
+declare void @"rcinc"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:10:17" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"a" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"x" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"y" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"xxy" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"b" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ab" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:6:186" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.19 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.20 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".0"(i64 %a0, i64 %a1, i64 %a2, i64 %a3) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 4, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = and i64 %a2, 1
+    %t1 = icmp eq i64 %t0, 0
+    br i1 %t1, label %t2.inc, label %t2.end
+t2.inc:
+    call void @"rcinc"(i64 %a2)
+    br label %t2.end
+t2.end:
+    %t3 = alloca [1 x i64], align 8
+    %t4 = getelementptr [1 x i64], ptr %t3, i64 0, i64 0
+    store i64 %a2, ptr %t4, align 8
+    %l0 = call i64 @"call_unknown_arity"(i64 %a1, i32 1, ptr %t3)
+    call void @"rcfuncfree"(i64 %a2)
+    %t5 = and i64 %l0, 1
+    %t6 = icmp eq i64 %t5, 0
+    br i1 %t6, label %t7.inc, label %t7.end
+t7.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t7.end
+t7.end:
+    %t8 = and i64 %a3, 1
+    %t9 = icmp eq i64 %t8, 0
+    br i1 %t9, label %t10.inc, label %t10.end
+t10.inc:
+    call void @"rcinc"(i64 %a3)
+    br label %t10.end
+t10.end:
+    %t11 = alloca [2 x i64], align 8
+    %t12 = getelementptr [2 x i64], ptr %t11, i64 0, i64 0
+    store i64 %l0, ptr %t12, align 8
+    %t13 = getelementptr [2 x i64], ptr %t11, i64 0, i64 1
+    store i64 %a3, ptr %t13, align 8
+    %l1 = call i64 @"call_unknown_arity"(i64 %a0, i32 2, ptr %t11)
+    call void @"rcfuncfree"(i64 %a3)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @".13"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_add"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @".14"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @"is_some.expanded"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_is_some"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"some.expanded"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_some"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"prelude.compose.is_some.expanded.some.expanded"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"some.expanded"(i64 %a0)
+    %l1 = call i64 @"is_some.expanded"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @".15"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = tail call i64 @"prelude.compose.is_some.expanded.some.expanded"(i64 2)
+    ret i64 %l0
+}
+
+define i64 @".16"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".17"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".16" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".17" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"concat.expanded"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"string_concat"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"concat.expanded.0"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"string_concat"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"println.expanded"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_println"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"prelude.apply.println.expanded"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = tail call i64 @"println.expanded"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"twice.concat.expanded"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"concat.expanded"(i64 %a0, i64 %a1)
+    %l1 = call i64 @"concat.expanded"(i64 %a0, i64 %l0)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @"prefixed"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"concat.expanded.0" to i64
+    %t1 = sub i64 %t0, 15
+    %t2 = alloca [1 x i64], align 8
+    %t3 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    %t4 = getelementptr [1 x i64], ptr %t2, i64 0, i64 0
+    store i64 %t3, ptr %t4, align 8
+    %l0 = call i64 @"rcclosure"(i64 %t1, i64 1, ptr %t2)
+    %t5 = alloca [1 x i64], align 8
+    %t6 = getelementptr [1 x i64], ptr %t5, i64 0, i64 0
+    store i64 %a0, ptr %t6, align 8
+    %l1 = call i64 @"call_unknown_arity"(i64 %l0, i32 1, ptr %t5)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @"prelude.fold"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = and i64 %a1, 1
+    %t1 = icmp eq i64 %t0, 0
+    br i1 %t1, label %t2.inc, label %t2.end
+t2.inc:
+    call void @"rcinc"(i64 %a1)
+    br label %t2.end
+t2.end:
+    %t3 = and i64 %a2, 1
+    %t4 = icmp eq i64 %t3, 0
+    br i1 %t4, label %t5.inc, label %t5.end
+t5.inc:
+    call void @"rcinc"(i64 %a2)
+    br label %t5.end
+t5.end:
+    %t6 = alloca [2 x i64], align 8
+    %t7 = getelementptr [2 x i64], ptr %t6, i64 0, i64 0
+    store i64 %a1, ptr %t7, align 8
+    %t8 = getelementptr [2 x i64], ptr %t6, i64 0, i64 1
+    store i64 %a2, ptr %t8, align 8
+    %l0 = call i64 @"call_unknown_arity"(i64 %a0, i32 2, ptr %t6)
+    call void @"rcfuncfree"(i64 %a2)
+    call void @"rcfuncfree"(i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"sum"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = tail call i64 @"prelude.fold"(i64 %a0, i64 %t1, i64 0)
+    ret i64 %l0
+}
+
+define i64 @"prelude.map"(i64 %a0, i64 %a1, i64 %a2, i64 %a3) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 4, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = and i64 %a2, 1
+    %t1 = icmp eq i64 %t0, 0
+    br i1 %t1, label %t2.inc, label %t2.end
+t2.inc:
+    call void @"rcinc"(i64 %a2)
+    br label %t2.end
+t2.end:
+    %t3 = and i64 %a0, 1
+    %t4 = icmp eq i64 %t3, 0
+    br i1 %t4, label %t5.inc, label %t5.end
+t5.inc:
+    call void @"rcinc"(i64 %a0)
+    br label %t5.end
+t5.end:
+    %t6 = ptrtoint ptr @".0" to i64
+    %t7 = sub i64 %t6, 15
+    %t8 = alloca [2 x i64], align 8
+    %t9 = getelementptr [2 x i64], ptr %t8, i64 0, i64 0
+    store i64 %a2, ptr %t9, align 8
+    %t10 = getelementptr [2 x i64], ptr %t8, i64 0, i64 1
+    store i64 %a0, ptr %t10, align 8
+    %l0 = call i64 @"rcclosure"(i64 %t7, i64 2, ptr %t8)
+    %t11 = and i64 %l0, 1
+    %t12 = icmp eq i64 %t11, 0
+    br i1 %t12, label %t13.inc, label %t13.end
+t13.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t13.end
+t13.end:
+    %t14 = and i64 %a3, 1
+    %t15 = icmp eq i64 %t14, 0
+    br i1 %t15, label %t16.inc, label %t16.end
+t16.inc:
+    call void @"rcinc"(i64 %a3)
+    br label %t16.end
+t16.end:
+    %t17 = alloca [2 x i64], align 8
+    %t18 = getelementptr [2 x i64], ptr %t17, i64 0, i64 0
+    store i64 %l0, ptr %t18, align 8
+    %t19 = getelementptr [2 x i64], ptr %t17, i64 0, i64 1
+    store i64 %a3, ptr %t19, align 8
+    %l1 = call i64 @"call_unknown_arity"(i64 %a1, i32 2, ptr %t17)
+    call void @"rcfuncfree"(i64 %a3)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @"max_int.expanded"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_max_int"(i64 %a0, i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @"prelude.cons"(i64 %a0, i64 %a1, i64 %a2, i64 %a3) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 4, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = and i64 %a2, 1
+    %t1 = icmp eq i64 %t0, 0
+    br i1 %t1, label %t2.inc, label %t2.end
+t2.inc:
+    call void @"rcinc"(i64 %a2)
+    br label %t2.end
+t2.end:
+    %t3 = and i64 %a3, 1
+    %t4 = icmp eq i64 %t3, 0
+    br i1 %t4, label %t5.inc, label %t5.end
+t5.inc:
+    call void @"rcinc"(i64 %a3)
+    br label %t5.end
+t5.end:
+    %t6 = alloca [2 x i64], align 8
+    %t7 = getelementptr [2 x i64], ptr %t6, i64 0, i64 0
+    store i64 %a2, ptr %t7, align 8
+    %t8 = getelementptr [2 x i64], ptr %t6, i64 0, i64 1
+    store i64 %a3, ptr %t8, align 8
+    %l0 = call i64 @"call_unknown_arity"(i64 %a1, i32 2, ptr %t6)
+    call void @"rcfuncfree"(i64 %a3)
+    call void @"rcfuncfree"(i64 %a2)
+    %t9 = and i64 %a0, 1
+    %t10 = icmp eq i64 %t9, 0
+    br i1 %t10, label %t11.inc, label %t11.end
+t11.inc:
+    call void @"rcinc"(i64 %a0)
+    br label %t11.end
+t11.end:
+    %t12 = and i64 %l0, 1
+    %t13 = icmp eq i64 %t12, 0
+    br i1 %t13, label %t14.inc, label %t14.end
+t14.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t14.end
+t14.end:
+    %t15 = alloca [2 x i64], align 8
+    %t16 = getelementptr [2 x i64], ptr %t15, i64 0, i64 0
+    store i64 %a0, ptr %t16, align 8
+    %t17 = getelementptr [2 x i64], ptr %t15, i64 0, i64 1
+    store i64 %l0, ptr %t17, align 8
+    %l1 = call i64 @"call_unknown_arity"(i64 %a2, i32 2, ptr %t15)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %a0)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @"prelude.nil"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 %a1
+}
+
+define i64 @"some.expanded.0"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_some"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"prelude.apply.some.expanded.0"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = tail call i64 @"some.expanded.0"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %t1 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    %l0 = call i64 @"twice.concat.expanded"(i64 %t0, i64 %t1)
+    %t2 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %l1 = call i64 @"closey_string_eq"(i64 %l0, i64 %t2)
+    call void @"rcfuncfree"(i64 %l0)
+    %l2 = call i64 @"check"(i64 %l1)
+    %t3 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %l3 = call i64 @"prefixed"(i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.9, i64 32) to i64
+    %l4 = call i64 @"closey_string_eq"(i64 %l3, i64 %t4)
+    call void @"rcfuncfree"(i64 %l3)
+    %l5 = call i64 @"check"(i64 %l4)
+    %t5 = ptrtoint ptr @"max_int.expanded" to i64
+    %t6 = sub i64 %t5, 15
+    %t7 = alloca [1 x i64], align 8
+    %t8 = getelementptr [1 x i64], ptr %t7, i64 0, i64 0
+    store i64 3, ptr %t8, align 8
+    %l6 = call i64 @"rcclosure"(i64 %t6, i64 1, ptr %t7)
+    %t9 = ptrtoint ptr @"prelude.cons" to i64
+    %t10 = sub i64 %t9, 15
+    %t11 = alloca [2 x i64], align 8
+    %t12 = getelementptr [2 x i64], ptr %t11, i64 0, i64 0
+    store i64 5, ptr %t12, align 8
+    %t13 = ptrtoint ptr @"prelude.nil" to i64
+    %t14 = sub i64 %t13, 15
+    %t15 = getelementptr [2 x i64], ptr %t11, i64 0, i64 1
+    store i64 %t14, ptr %t15, align 8
+    %l7 = call i64 @"rcclosure"(i64 %t10, i64 2, ptr %t11)
+    %t16 = and i64 %l7, 1
+    %t17 = icmp eq i64 %t16, 0
+    br i1 %t17, label %t18.inc, label %t18.end
+t18.inc:
+    call void @"rcinc"(i64 %l7)
+    br label %t18.end
+t18.end:
+    %t19 = ptrtoint ptr @"prelude.cons" to i64
+    %t20 = sub i64 %t19, 15
+    %t21 = alloca [2 x i64], align 8
+    %t22 = getelementptr [2 x i64], ptr %t21, i64 0, i64 0
+    store i64 1, ptr %t22, align 8
+    %t23 = getelementptr [2 x i64], ptr %t21, i64 0, i64 1
+    store i64 %l7, ptr %t23, align 8
+    %l8 = call i64 @"rcclosure"(i64 %t20, i64 2, ptr %t21)
+    call void @"rcfuncfree"(i64 %l7)
+    %t24 = and i64 %l6, 1
+    %t25 = icmp eq i64 %t24, 0
+    br i1 %t25, label %t26.inc, label %t26.end
+t26.inc:
+    call void @"rcinc"(i64 %l6)
+    br label %t26.end
+t26.end:
+    %t27 = and i64 %l8, 1
+    %t28 = icmp eq i64 %t27, 0
+    br i1 %t28, label %t29.inc, label %t29.end
+t29.inc:
+    call void @"rcinc"(i64 %l8)
+    br label %t29.end
+t29.end:
+    %t30 = ptrtoint ptr @"prelude.map" to i64
+    %t31 = sub i64 %t30, 15
+    %t32 = alloca [2 x i64], align 8
+    %t33 = getelementptr [2 x i64], ptr %t32, i64 0, i64 0
+    store i64 %l6, ptr %t33, align 8
+    %t34 = getelementptr [2 x i64], ptr %t32, i64 0, i64 1
+    store i64 %l8, ptr %t34, align 8
+    %l9 = call i64 @"rcclosure"(i64 %t31, i64 2, ptr %t32)
+    call void @"rcfuncfree"(i64 %l6)
+    call void @"rcfuncfree"(i64 %l8)
+    %l10 = call i64 @"sum"(i64 %l9)
+    call void @"rcfuncfree"(i64 %l9)
+    %l11 = call i64 @"closey_int_eq"(i64 %l10, i64 8)
+    %l12 = call i64 @"check"(i64 %l11)
+    %l13 = call i64 @"prelude.apply.some.expanded.0"(i64 1)
+    %l14 = call i64 @"closey_is_some"(i64 %l13)
+    call void @"rcfuncfree"(i64 %l13)
+    %t35 = ptrtoint ptr @".14" to i64
+    %t36 = sub i64 %t35, 15
+    %l15 = call i64 @"closey_array_of"(i64 %t36)
+    %t37 = ptrtoint ptr @".15" to i64
+    %t38 = sub i64 %t37, 15
+    %l16 = call i64 @"closey_array_push"(i64 %l15, i64 %t38)
+    %t39 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.10, i64 32) to i64
+    %t40 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.11, i64 32) to i64
+    %l17 = call i64 @"closey_match"(i64 %l14, i64 %t39, i64 %l16, i64 %t40)
+    %l18 = call i64 @"check"(i64 %l17)
+    %l19 = call i64 @"string_concat"(i64 %l12, i64 %l18)
+    call void @"rcfuncfree"(i64 %l12)
+    call void @"rcfuncfree"(i64 %l18)
+    %l20 = call i64 @"string_concat"(i64 %l5, i64 %l19)
+    call void @"rcfuncfree"(i64 %l5)
+    %l21 = call i64 @"string_concat"(i64 %l2, i64 %l20)
+    call void @"rcfuncfree"(i64 %l2)
+    %l22 = tail call i64 @"prelude.apply.println.expanded"(i64 %l21)
+    ret i64 %l22
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
@@ -0,0 +1,458 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_err"(i64)
+declare i64 @"closey_int_add"(i64, i64)
+declare i64 @"closey_int_div"(i64, i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_int_mod"(i64, i64, i64)
+declare i64 @"closey_int_mul"(i64, i64)
+declare i64 @"closey_is_none"(i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_none"()
+declare i64 @"closey_ok"(i64)
+declare i64 @"closey_println"(i64)
+declare i64 @"closey_some"(i64)
+declare i64 @"closey_string_eq"(i64, i64)
+declare void @"exit"(i64)
+declare i64 @"rcclosure"(i64, i64, ptr)
+declare void @"rcfuncfree"(i64)
+declare void @"rcinc"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:3:38" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"odd" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:3:18" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:3:15" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:4:22" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:5:46" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:18:17" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"o_eb" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:11:37" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:6:18" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:6:15" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:8:35" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:4:28" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:5:34" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.19 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.20 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:7:28" }>, align 8
+@.string.21 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.22 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.23 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.24 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.25 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.26 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.27 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".13"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    %l0 = call i64 @"closey_int_div"(i64 %a0, i64 2, i64 %t0)
+    %l1 = call i64 @"closey_ok"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @".14"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    %l0 = call i64 @"closey_err"(i64 %t0)
+    ret i64 %l0
+}
+
+define i64 @"half"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %l0 = call i64 @"closey_int_mod"(i64 %a0, i64 2, i64 %t0)
+    %l1 = call i64 @"closey_int_eq"(i64 %l0, i64 0)
+    %t1 = ptrtoint ptr @".13" to i64
+    %t2 = sub i64 %t1, 15
+    %t3 = alloca [1 x i64], align 8
+    %t4 = getelementptr [1 x i64], ptr %t3, i64 0, i64 0
+    store i64 %a0, ptr %t4, align 8
+    %l2 = call i64 @"rcclosure"(i64 %t2, i64 1, ptr %t3)
+    %t5 = and i64 %l2, 1
+    %t6 = icmp eq i64 %t5, 0
+    br i1 %t6, label %t7.inc, label %t7.end
+t7.inc:
+    call void @"rcinc"(i64 %l2)
+    br label %t7.end
+t7.end:
+    %l3 = call i64 @"closey_array_of"(i64 %l2)
+    %t8 = ptrtoint ptr @".14" to i64
+    %t9 = sub i64 %t8, 15
+    %l4 = call i64 @"closey_array_push"(i64 %l3, i64 %t9)
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %t11 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    %l5 = call i64 @"closey_match"(i64 %l1, i64 %t10, i64 %l4, i64 %t11)
+    call void @"rcfuncfree"(i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l5
+}
+
+define i64 @".16"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 2, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_ok"(i64 %a1)
+    ret i64 %l0
+}
+
+define i64 @".17"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".15"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 2, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"half"(i64 %a1)
+    %t0 = ptrtoint ptr @".16" to i64
+    %t1 = sub i64 %t0, 15
+    %l1 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".17" to i64
+    %t3 = sub i64 %t2, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %l0, i64 %t4, i64 %l2, i64 %t5)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l3
+}
+
+define i64 @".18"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".20"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 5, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_add"(i64 %a0, i64 %a2)
+    %l1 = call i64 @"closey_ok"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @".21"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".19"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 5, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"half"(i64 %a0)
+    %t0 = ptrtoint ptr @".20" to i64
+    %t1 = sub i64 %t0, 15
+    %t2 = alloca [1 x i64], align 8
+    %t3 = getelementptr [1 x i64], ptr %t2, i64 0, i64 0
+    store i64 %a2, ptr %t3, align 8
+    %l1 = call i64 @"rcclosure"(i64 %t1, i64 1, ptr %t2)
+    %t4 = and i64 %l1, 1
+    %t5 = icmp eq i64 %t4, 0
+    br i1 %t5, label %t6.inc, label %t6.end
+t6.inc:
+    call void @"rcinc"(i64 %l1)
+    br label %t6.end
+t6.end:
+    %l2 = call i64 @"closey_array_of"(i64 %l1)
+    %t7 = ptrtoint ptr @".21" to i64
+    %t8 = sub i64 %t7, 15
+    %l3 = call i64 @"closey_array_push"(i64 %l2, i64 %t8)
+    %t9 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t9, i64 %l3, i64 %t10)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    ret i64 %l4
+}
+
+define i64 @".22"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".23"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_some"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @".24"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_none"()
+    ret i64 %l0
+}
+
+define i64 @".25"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 2, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_mul"(i64 %a1, i64 2)
+    %l1 = call i64 @"closey_some"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @".26"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".27"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 5, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_eq"(i64 %a2, i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @".28"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @".29"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @".30"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_string_eq"(i64 %a2, i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @".31"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".32"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.9, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".7"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 %a1
+}
+
+define i64 @".8"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".31" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".32" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.10, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"error_is"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".29" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = and i64 %a1, 1
+    %t3 = icmp eq i64 %t2, 0
+    br i1 %t3, label %t4.inc, label %t4.end
+t4.inc:
+    call void @"rcinc"(i64 %a1)
+    br label %t4.end
+t4.end:
+    %t5 = ptrtoint ptr @".30" to i64
+    %t6 = sub i64 %t5, 15
+    %t7 = alloca [1 x i64], align 8
+    %t8 = getelementptr [1 x i64], ptr %t7, i64 0, i64 0
+    store i64 %a1, ptr %t8, align 8
+    %l1 = call i64 @"rcclosure"(i64 %t6, i64 1, ptr %t7)
+    %t9 = and i64 %l1, 1
+    %t10 = icmp eq i64 %t9, 0
+    br i1 %t10, label %t11.inc, label %t11.end
+t11.inc:
+    call void @"rcinc"(i64 %l1)
+    br label %t11.end
+t11.end:
+    %l2 = call i64 @"closey_array_push"(i64 %l0, i64 %l1)
+    %t12 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.11, i64 32) to i64
+    %t13 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.12, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %a0, i64 %t12, i64 %l2, i64 %t13)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    ret i64 %l3
+}
+
+define i64 @"even"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.13, i64 32) to i64
+    %l0 = call i64 @"closey_int_mod"(i64 %a0, i64 2, i64 %t0)
+    %l1 = call i64 @"closey_int_eq"(i64 %l0, i64 0)
+    %t1 = ptrtoint ptr @".23" to i64
+    %t2 = sub i64 %t1, 15
+    %t3 = alloca [1 x i64], align 8
+    %t4 = getelementptr [1 x i64], ptr %t3, i64 0, i64 0
+    store i64 %a0, ptr %t4, align 8
+    %l2 = call i64 @"rcclosure"(i64 %t2, i64 1, ptr %t3)
+    %t5 = and i64 %l2, 1
+    %t6 = icmp eq i64 %t5, 0
+    br i1 %t6, label %t7.inc, label %t7.end
+t7.inc:
+    call void @"rcinc"(i64 %l2)
+    br label %t7.end
+t7.end:
+    %l3 = call i64 @"closey_array_of"(i64 %l2)
+    %t8 = ptrtoint ptr @".24" to i64
+    %t9 = sub i64 %t8, 15
+    %l4 = call i64 @"closey_array_push"(i64 %l3, i64 %t9)
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %t11 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.14, i64 32) to i64
+    %l5 = call i64 @"closey_match"(i64 %l1, i64 %t10, i64 %l4, i64 %t11)
+    call void @"rcfuncfree"(i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l5
+}
+
+define i64 @"result_is"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 2, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".27" to i64
+    %t1 = sub i64 %t0, 15
+    %t2 = alloca [1 x i64], align 8
+    %t3 = getelementptr [1 x i64], ptr %t2, i64 0, i64 0
+    store i64 %a1, ptr %t3, align 8
+    %l0 = call i64 @"rcclosure"(i64 %t1, i64 1, ptr %t2)
+    %t4 = and i64 %l0, 1
+    %t5 = icmp eq i64 %t4, 0
+    br i1 %t5, label %t6.inc, label %t6.end
+t6.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t6.end
+t6.end:
+    %l1 = call i64 @"closey_array_of"(i64 %l0)
+    %t7 = ptrtoint ptr @".28" to i64
+    %t8 = sub i64 %t7, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t8)
+    %t9 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.15, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %a0, i64 %t9, i64 %l2, i64 %t10)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l3
+}
+
+define i64 @"quarter"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"half"(i64 %a0)
+    %t0 = ptrtoint ptr @".15" to i64
+    %t1 = sub i64 %t0, 15
+    %l1 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".18" to i64
+    %t3 = sub i64 %t2, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.16, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %l0, i64 %t4, i64 %l2, i64 %t5)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l3
+}
+
+define i64 @"sum_halves"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"half"(i64 %a0)
+    %t0 = ptrtoint ptr @".19" to i64
+    %t1 = sub i64 %t0, 15
+    %t2 = alloca [1 x i64], align 8
+    %t3 = getelementptr [1 x i64], ptr %t2, i64 0, i64 0
+    store i64 %a1, ptr %t3, align 8
+    %l1 = call i64 @"rcclosure"(i64 %t1, i64 1, ptr %t2)
+    %t4 = and i64 %l1, 1
+    %t5 = icmp eq i64 %t4, 0
+    br i1 %t5, label %t6.inc, label %t6.end
+t6.inc:
+    call void @"rcinc"(i64 %l1)
+    br label %t6.end
+t6.end:
+    %l2 = call i64 @"closey_array_of"(i64 %l1)
+    %t7 = ptrtoint ptr @".22" to i64
+    %t8 = sub i64 %t7, 15
+    %l3 = call i64 @"closey_array_push"(i64 %l2, i64 %t8)
+    %t9 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.17, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t9, i64 %l3, i64 %t10)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    ret i64 %l4
+}
+
+define i64 @"prelude.some_or"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".7" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = and i64 %a1, 1
+    %t3 = icmp eq i64 %t2, 0
+    br i1 %t3, label %t4.inc, label %t4.end
+t4.inc:
+    call void @"rcinc"(i64 %a1)
+    br label %t4.end
+t4.end:
+    %t5 = ptrtoint ptr @".8" to i64
+    %t6 = sub i64 %t5, 15
+    %t7 = alloca [1 x i64], align 8
+    %t8 = getelementptr [1 x i64], ptr %t7, i64 0, i64 0
+    store i64 %a1, ptr %t8, align 8
+    %l1 = call i64 @"rcclosure"(i64 %t6, i64 1, ptr %t7)
+    %t9 = and i64 %l1, 1
+    %t10 = icmp eq i64 %t9, 0
+    br i1 %t10, label %t11.inc, label %t11.end
+t11.inc:
+    call void @"rcinc"(i64 %l1)
+    br label %t11.end
+t11.end:
+    %l2 = call i64 @"closey_array_push"(i64 %l0, i64 %l1)
+    %t12 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.18, i64 32) to i64
+    %t13 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.19, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %a0, i64 %t12, i64 %l2, i64 %t13)
+    call void @"rcfuncfree"(i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    ret i64 %l3
+}
+
+define i64 @"twice_even"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"even"(i64 %a0)
+    %t0 = ptrtoint ptr @".25" to i64
+    %t1 = sub i64 %t0, 15
+    %l1 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".26" to i64
+    %t3 = sub i64 %t2, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.18, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.20, i64 32) to i64
+    %l3 = call i64 @"closey_match"(i64 %l0, i64 %t4, i64 %l2, i64 %t5)
+    call void @"rcfuncfree"(i64 %l0)
+    ret i64 %l3
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"quarter"(i64 8)
+    %l1 = call i64 @"result_is"(i64 %l0, i64 2)
+    call void @"rcfuncfree"(i64 %l0)
+    %l2 = call i64 @"check"(i64 %l1)
+    %l3 = call i64 @"quarter"(i64 6)
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    %l4 = call i64 @"error_is"(i64 %l3, i64 %t0)
+    call void @"rcfuncfree"(i64 %l3)
+    %l5 = call i64 @"check"(i64 %l4)
+    %l6 = call i64 @"sum_halves"(i64 4, i64 6)
+    %l7 = call i64 @"result_is"(i64 %l6, i64 5)
+    call void @"rcfuncfree"(i64 %l6)
+    %l8 = call i64 @"check"(i64 %l7)
+    %l9 = call i64 @"sum_halves"(i64 4, i64 7)
+    %t1 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    %l10 = call i64 @"error_is"(i64 %l9, i64 %t1)
+    call void @"rcfuncfree"(i64 %l9)
+    %l11 = call i64 @"check"(i64 %l10)
+    %l12 = call i64 @"twice_even"(i64 4)
+    %l13 = call i64 @"prelude.some_or"(i64 %l12, i64 0)
+    call void @"rcfuncfree"(i64 %l12)
+    %l14 = call i64 @"closey_int_eq"(i64 %l13, i64 8)
+    %l15 = call i64 @"check"(i64 %l14)
+    %l16 = call i64 @"twice_even"(i64 3)
+    %l17 = call i64 @"closey_is_none"(i64 %l16)
+    call void @"rcfuncfree"(i64 %l16)
+    %l18 = call i64 @"check"(i64 %l17)
+    %l19 = call i64 @"string_concat"(i64 %l15, i64 %l18)
+    call void @"rcfuncfree"(i64 %l15)
+    call void @"rcfuncfree"(i64 %l18)
+    %l20 = call i64 @"string_concat"(i64 %l11, i64 %l19)
+    call void @"rcfuncfree"(i64 %l11)
+    %l21 = call i64 @"string_concat"(i64 %l8, i64 %l20)
+    call void @"rcfuncfree"(i64 %l8)
+    %l22 = call i64 @"string_concat"(i64 %l5, i64 %l21)
+    call void @"rcfuncfree"(i64 %l5)
+    %l23 = call i64 @"string_concat"(i64 %l2, i64 %l22)
+    call void @"rcfuncfree"(i64 %l2)
+    %l24 = call i64 @"closey_println"(i64 %l23)
+    ret i64 %l24
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
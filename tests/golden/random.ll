@@ -0,0 +1,200 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_clock_monotonic"(i64)
+declare i64 @"closey_float_ge"(i64, i64)
+declare i64 @"closey_float_lt"(i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_int_ge"(i64, i64)
+declare i64 @"closey_int_gt"(i64, i64)
+declare i64 @"closey_int_lt"(i64, i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_now_millis"(i64)
+declare i64 @"closey_println"(i64)
+declare i64 @"closey_random_float"(i64)
+declare i64 @"closey_random_int"(i64, i64, i64)
+declare i64 @"closey_seed_random"(i64)
+declare void @"exit"(i64)
+declare i64 @"rcclosure"(i64, i64, ptr)
+declare void @"rcfuncfree"(i64)
+declare void @"rcinc"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:12:17" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:4:27" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:3:23" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:5:16" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:8:46" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:8:204" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".13"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".14"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_lt"(i64 %a0, i64 5)
+    ret i64 %l0
+}
+
+define i64 @".15"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    ret i64 %a0
+}
+
+define i64 @".16"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 3, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_float_lt"(i64 %a0, i64 4607182418800017408)
+    ret i64 %l0
+}
+
+define i64 @".17"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".18"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".17" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".18" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"float_in_range"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_float_ge"(i64 %a0, i64 0)
+    %t0 = ptrtoint ptr @".15" to i64
+    %t1 = sub i64 %t0, 15
+    %l1 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".16" to i64
+    %t3 = sub i64 %t2, 15
+    %t4 = alloca [1 x i64], align 8
+    %t5 = getelementptr [1 x i64], ptr %t4, i64 0, i64 0
+    store i64 %a0, ptr %t5, align 8
+    %l2 = call i64 @"rcclosure"(i64 %t3, i64 1, ptr %t4)
+    %t6 = and i64 %l2, 1
+    %t7 = icmp eq i64 %t6, 0
+    br i1 %t7, label %t8.inc, label %t8.end
+t8.inc:
+    call void @"rcinc"(i64 %l2)
+    br label %t8.end
+t8.end:
+    %l3 = call i64 @"closey_array_push"(i64 %l1, i64 %l2)
+    %t9 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t9, i64 %l3, i64 %t10)
+    call void @"rcfuncfree"(i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l4
+}
+
+define i64 @"int_in_range"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_int_ge"(i64 %a0, i64 -5)
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    %l1 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".14" to i64
+    %t3 = sub i64 %t2, 15
+    %t4 = alloca [1 x i64], align 8
+    %t5 = getelementptr [1 x i64], ptr %t4, i64 0, i64 0
+    store i64 %a0, ptr %t5, align 8
+    %l2 = call i64 @"rcclosure"(i64 %t3, i64 1, ptr %t4)
+    %t6 = and i64 %l2, 1
+    %t7 = icmp eq i64 %t6, 0
+    br i1 %t7, label %t8.inc, label %t8.end
+t8.inc:
+    call void @"rcinc"(i64 %l2)
+    br label %t8.end
+t8.end:
+    %l3 = call i64 @"closey_array_push"(i64 %l1, i64 %l2)
+    %t9 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    %t10 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    %l4 = call i64 @"closey_match"(i64 %l0, i64 %t9, i64 %l3, i64 %t10)
+    call void @"rcfuncfree"(i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l4
+}
+
+define i64 @"roll"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %l0 = call i64 @"closey_random_int"(i64 0, i64 1000000, i64 %t0)
+    ret i64 %l0
+}
+
+define i64 @"reseeded_roll"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_seed_random"(i64 %a0)
+    %l1 = tail call i64 @"roll"(i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @"not_backwards"(i64 %a0, i64 %a1) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 2, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_clock_monotonic"(i64 %a1)
+    %l1 = call i64 @"closey_int_ge"(i64 %l0, i64 %a0)
+    ret i64 %l1
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %l0 = call i64 @"closey_random_int"(i64 -5, i64 5, i64 %t0)
+    %l1 = call i64 @"int_in_range"(i64 %l0)
+    %l2 = call i64 @"check"(i64 %l1)
+    %l3 = call i64 @"closey_seed_random"(i64 42)
+    %l4 = call i64 @"closey_random_float"(i64 %l3)
+    %l5 = call i64 @"float_in_range"(i64 %l4)
+    %l6 = call i64 @"check"(i64 %l5)
+    %l7 = call i64 @"reseeded_roll"(i64 7)
+    %l8 = call i64 @"reseeded_roll"(i64 7)
+    %l9 = call i64 @"closey_int_eq"(i64 %l7, i64 %l8)
+    %l10 = call i64 @"check"(i64 %l9)
+    %t1 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.9, i64 32) to i64
+    %l11 = call i64 @"closey_random_int"(i64 3, i64 4, i64 %t1)
+    %l12 = call i64 @"closey_int_eq"(i64 %l11, i64 3)
+    %l13 = call i64 @"check"(i64 %l12)
+    %l14 = call i64 @"closey_seed_random"(i64 1)
+    %l15 = call i64 @"closey_now_millis"(i64 %l14)
+    %l16 = call i64 @"closey_int_gt"(i64 %l15, i64 1600000000000)
+    %l17 = call i64 @"check"(i64 %l16)
+    %l18 = call i64 @"closey_seed_random"(i64 1)
+    %l19 = call i64 @"closey_clock_monotonic"(i64 %l18)
+    %l20 = call i64 @"closey_seed_random"(i64 2)
+    %l21 = call i64 @"not_backwards"(i64 %l19, i64 %l20)
+    %l22 = call i64 @"check"(i64 %l21)
+    %l23 = call i64 @"string_concat"(i64 %l17, i64 %l22)
+    call void @"rcfuncfree"(i64 %l17)
+    call void @"rcfuncfree"(i64 %l22)
+    %l24 = call i64 @"string_concat"(i64 %l13, i64 %l23)
+    call void @"rcfuncfree"(i64 %l13)
+    %l25 = call i64 @"string_concat"(i64 %l10, i64 %l24)
+    call void @"rcfuncfree"(i64 %l10)
+    %l26 = call i64 @"string_concat"(i64 %l6, i64 %l25)
+    call void @"rcfuncfree"(i64 %l6)
+    %l27 = call i64 @"string_concat"(i64 %l2, i64 %l26)
+    call void @"rcfuncfree"(i64 %l2)
+    %l28 = call i64 @"closey_println"(i64 %l27)
+    ret i64 %l28
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
@@ -0,0 +1,296 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_data_0"(i64)
+declare i64 @"closey_data_2"(i64, i64, i64)
+declare i64 @"closey_data_3"(i64, i64, i64, i64)
+declare i64 @"closey_int_add"(i64, i64)
+declare i64 @"closey_int_eq"(i64, i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_max_int"(i64, i64)
+declare i64 @"closey_println"(i64)
+declare void @"exit"(i64)
+declare void @"rcfuncfree"(i64)
+declare void @"rcinc"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [12 x i8] }> <{ ptr null, i64 20, i64 0, i64 4611686018427387904, i64 12, [12 x i8] c"d0,0,d1,2,bb" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:5:15" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [13 x i8] }> <{ ptr null, i64 21, i64 0, i64 4611686018427387904, i64 13, [13 x i8] c"d0,0,d1,3,b_b" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:8:17" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [13 x i8] }> <{ ptr null, i64 21, i64 0, i64 4611686018427387904, i64 13, [13 x i8] c"d0,0,d1,3,bbb" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:11:17" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:24:17" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [12 x i8] }> <{ ptr null, i64 20, i64 0, i64 4611686018427387904, i64 12, [12 x i8] c"d1,0,d0,2,bb" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [10 x i8] }> <{ ptr null, i64 18, i64 0, i64 4611686018427387904, i64 10, [10 x i8] c"Main:14:18" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.19 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.20 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.21 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+
+define i64 @".13"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @"sum"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".14" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @".14"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 2, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"sum"(i64 %a2)
+    %l1 = call i64 @"closey_int_add"(i64 %a1, i64 %l0)
+    ret i64 %l1
+}
+
+define i64 @".15"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @"depth"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".15" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".16" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @".16"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"depth"(i64 %a1)
+    %l1 = call i64 @"depth"(i64 %a2)
+    %l2 = call i64 @"closey_max_int"(i64 %l0, i64 %l1)
+    %l3 = call i64 @"closey_int_add"(i64 1, i64 %l2)
+    ret i64 %l3
+}
+
+define i64 @".17"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @"total"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".17" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".18" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @".18"(i64 %a0, i64 %a1, i64 %a2, i64 %a3) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 4, i32 4, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"total"(i64 %a1)
+    %l1 = call i64 @"closey_int_add"(i64 %l0, i64 %a2)
+    %l2 = call i64 @"total"(i64 %a3)
+    %l3 = call i64 @"closey_int_add"(i64 %l1, i64 %l2)
+    ret i64 %l3
+}
+
+define i64 @".19"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    ret i64 0
+}
+
+define i64 @".20"(i64 %a0, i64 %a1, i64 %a2) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 3, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"sum"(i64 %a1)
+    %l1 = call i64 @"total"(i64 %a2)
+    %l2 = call i64 @"closey_int_add"(i64 %l0, i64 %l1)
+    ret i64 %l2
+}
+
+define i64 @".21"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".22"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".21" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".22" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.9, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"size"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".19" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".20" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.10, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.11, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_data_0"(i64 0)
+    %t0 = and i64 %l0, 1
+    %t1 = icmp eq i64 %t0, 0
+    br i1 %t1, label %t2.inc, label %t2.end
+t2.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t2.end
+t2.end:
+    %l1 = call i64 @"closey_data_2"(i64 1, i64 3, i64 %l0)
+    %t3 = and i64 %l1, 1
+    %t4 = icmp eq i64 %t3, 0
+    br i1 %t4, label %t5.inc, label %t5.end
+t5.inc:
+    call void @"rcinc"(i64 %l1)
+    br label %t5.end
+t5.end:
+    %l2 = call i64 @"closey_data_2"(i64 1, i64 2, i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    %t6 = and i64 %l2, 1
+    %t7 = icmp eq i64 %t6, 0
+    br i1 %t7, label %t8.inc, label %t8.end
+t8.inc:
+    call void @"rcinc"(i64 %l2)
+    br label %t8.end
+t8.end:
+    %l3 = call i64 @"closey_data_2"(i64 1, i64 1, i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    %t9 = and i64 %l0, 1
+    %t10 = icmp eq i64 %t9, 0
+    br i1 %t10, label %t11.inc, label %t11.end
+t11.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t11.end
+t11.end:
+    %t12 = and i64 %l0, 1
+    %t13 = icmp eq i64 %t12, 0
+    br i1 %t13, label %t14.inc, label %t14.end
+t14.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t14.end
+t14.end:
+    %l4 = call i64 @"closey_data_3"(i64 1, i64 %l0, i64 1, i64 %l0)
+    %t15 = and i64 %l0, 1
+    %t16 = icmp eq i64 %t15, 0
+    br i1 %t16, label %t17.inc, label %t17.end
+t17.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t17.end
+t17.end:
+    %t18 = and i64 %l0, 1
+    %t19 = icmp eq i64 %t18, 0
+    br i1 %t19, label %t20.inc, label %t20.end
+t20.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t20.end
+t20.end:
+    %l5 = call i64 @"closey_data_3"(i64 1, i64 %l0, i64 3, i64 %l0)
+    %t21 = and i64 %l5, 1
+    %t22 = icmp eq i64 %t21, 0
+    br i1 %t22, label %t23.inc, label %t23.end
+t23.inc:
+    call void @"rcinc"(i64 %l5)
+    br label %t23.end
+t23.end:
+    %t24 = and i64 %l0, 1
+    %t25 = icmp eq i64 %t24, 0
+    br i1 %t25, label %t26.inc, label %t26.end
+t26.inc:
+    call void @"rcinc"(i64 %l0)
+    br label %t26.end
+t26.end:
+    %l6 = call i64 @"closey_data_3"(i64 1, i64 %l5, i64 4, i64 %l0)
+    call void @"rcfuncfree"(i64 %l0)
+    call void @"rcfuncfree"(i64 %l5)
+    %t27 = and i64 %l4, 1
+    %t28 = icmp eq i64 %t27, 0
+    br i1 %t28, label %t29.inc, label %t29.end
+t29.inc:
+    call void @"rcinc"(i64 %l4)
+    br label %t29.end
+t29.end:
+    %t30 = and i64 %l6, 1
+    %t31 = icmp eq i64 %t30, 0
+    br i1 %t31, label %t32.inc, label %t32.end
+t32.inc:
+    call void @"rcinc"(i64 %l6)
+    br label %t32.end
+t32.end:
+    %l7 = call i64 @"closey_data_3"(i64 1, i64 %l4, i64 2, i64 %l6)
+    call void @"rcfuncfree"(i64 %l4)
+    call void @"rcfuncfree"(i64 %l6)
+    %l8 = call i64 @"sum"(i64 %l3)
+    %l9 = call i64 @"closey_int_eq"(i64 %l8, i64 6)
+    %l10 = call i64 @"check"(i64 %l9)
+    %l11 = call i64 @"depth"(i64 %l7)
+    %l12 = call i64 @"closey_int_eq"(i64 %l11, i64 3)
+    %l13 = call i64 @"check"(i64 %l12)
+    %l14 = call i64 @"total"(i64 %l7)
+    %l15 = call i64 @"closey_int_eq"(i64 %l14, i64 10)
+    %l16 = call i64 @"check"(i64 %l15)
+    %t33 = and i64 %l3, 1
+    %t34 = icmp eq i64 %t33, 0
+    br i1 %t34, label %t35.inc, label %t35.end
+t35.inc:
+    call void @"rcinc"(i64 %l3)
+    br label %t35.end
+t35.end:
+    %t36 = and i64 %l7, 1
+    %t37 = icmp eq i64 %t36, 0
+    br i1 %t37, label %t38.inc, label %t38.end
+t38.inc:
+    call void @"rcinc"(i64 %l7)
+    br label %t38.end
+t38.end:
+    %l17 = call i64 @"closey_data_2"(i64 0, i64 %l3, i64 %l7)
+    call void @"rcfuncfree"(i64 %l3)
+    call void @"rcfuncfree"(i64 %l7)
+    %l18 = call i64 @"size"(i64 %l17)
+    call void @"rcfuncfree"(i64 %l17)
+    %l19 = call i64 @"closey_int_eq"(i64 %l18, i64 16)
+    %l20 = call i64 @"check"(i64 %l19)
+    %l21 = call i64 @"string_concat"(i64 %l16, i64 %l20)
+    call void @"rcfuncfree"(i64 %l16)
+    call void @"rcfuncfree"(i64 %l20)
+    %l22 = call i64 @"string_concat"(i64 %l13, i64 %l21)
+    call void @"rcfuncfree"(i64 %l13)
+    %l23 = call i64 @"string_concat"(i64 %l10, i64 %l22)
+    call void @"rcfuncfree"(i64 %l10)
+    %l24 = call i64 @"closey_println"(i64 %l23)
+    ret i64 %l24
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
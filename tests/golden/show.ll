@@ -0,0 +1,248 @@
+declare i64 @"closey_array_of"(i64)
+declare i64 @"closey_array_push"(i64, i64)
+declare i64 @"closey_data_0"(i64)
+declare i64 @"closey_data_1"(i64, i64)
+declare i64 @"closey_data_2"(i64, i64, i64)
+declare i64 @"closey_match"(i64, i64, i64, i64)
+declare i64 @"closey_println"(i64)
+declare i64 @"closey_show_array"(i64, i64)
+declare i64 @"closey_show_char"(i64)
+declare i64 @"closey_show_data"(i64, i64, i64)
+declare i64 @"closey_show_float"(i64)
+declare i64 @"closey_show_int"(i64)
+declare i64 @"closey_show_option"(i64, i64)
+declare i64 @"closey_show_string"(i64)
+declare i64 @"closey_some"(i64)
+declare i64 @"closey_string_eq"(i64, i64)
+declare void @"exit"(i64)
+declare void @"rcfuncfree"(i64)
+declare void @"rcinc"(i64)
+declare i64 @"string_concat"(i64, i64)
+
+@.string.0 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"T" }>, align 8
+@.string.1 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [1 x i8] }> <{ ptr null, i64 9, i64 0, i64 4611686018427387904, i64 1, [1 x i8] c"F" }>, align 8
+@.string.2 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"tf" }>, align 8
+@.string.3 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [9 x i8] }> <{ ptr null, i64 17, i64 0, i64 4611686018427387904, i64 9, [9 x i8] c"Main:8:17" }>, align 8
+@.string.4 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"42" }>, align 8
+@.string.5 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"-7" }>, align 8
+@.string.6 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"2.5" }>, align 8
+@.string.7 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"hi" }>, align 8
+@.string.8 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"\22hi\22" }>, align 8
+@.string.9 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"'c'" }>, align 8
+@.string.10 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [6 x i8] }> <{ ptr null, i64 14, i64 0, i64 4611686018427387904, i64 6, [6 x i8] c"[1, 2]" }>, align 8
+@.string.11 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [13 x i8] }> <{ ptr null, i64 21, i64 0, i64 4611686018427387904, i64 13, [13 x i8] c"some (some 3)" }>, align 8
+@.string.12 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [43 x i8] }> <{ ptr null, i64 51, i64 0, i64 4611686018427387904, i64 43, [43 x i8] c"layer (circle 0.5) (layer (rect 1 2) blank)" }>, align 8
+@.string.13 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [2 x i8] }> <{ ptr null, i64 10, i64 0, i64 4611686018427387904, i64 2, [2 x i8] c"ft" }>, align 8
+@.string.14 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:54:25" }>, align 8
+@.string.15 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:55:27" }>, align 8
+@.string.16 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:48:18" }>, align 8
+@.string.17 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:49:17" }>, align 8
+@.string.18 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [3 x i8] }> <{ ptr null, i64 11, i64 0, i64 4611686018427387904, i64 3, [3 x i8] c"sbn" }>, align 8
+@.string.19 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:66:33" }>, align 8
+@.string.20 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:65:31" }>, align 8
+@.string.21 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [4 x i8] }> <{ ptr null, i64 12, i64 0, i64 4611686018427387904, i64 4, [4 x i8] c"obe_" }>, align 8
+@.string.22 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [15 x i8] }> <{ ptr null, i64 23, i64 0, i64 4611686018427387904, i64 15, [15 x i8] c"<prelude>:67:26" }>, align 8
+@.string.23 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [14 x i8] }> <{ ptr null, i64 22, i64 0, i64 4611686018427387904, i64 14, [14 x i8] c"2layer,0blank," }>, align 8
+@.string.24 = private unnamed_addr global <{ ptr, i64, i64, i64, i64, [19 x i8] }> <{ ptr null, i64 27, i64 0, i64 4611686018427387904, i64 19, [19 x i8] c"1circle,2rect,0dot," }>, align 8
+
+define i64 @".13"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.0, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @".14"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.1, i64 32) to i64
+    ret i64 %t0
+}
+
+define i64 @"check"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @".13" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @".14" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.2, i64 32) to i64
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.3, i64 32) to i64
+    %l2 = call i64 @"closey_match"(i64 %a0, i64 %t4, i64 %l1, i64 %t5)
+    ret i64 %l2
+}
+
+define i64 @"main"() align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 0, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"show.Int"(i64 42)
+    %t0 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.4, i64 32) to i64
+    %l1 = call i64 @"closey_string_eq"(i64 %l0, i64 %t0)
+    call void @"rcfuncfree"(i64 %l0)
+    %l2 = call i64 @"check"(i64 %l1)
+    %l3 = call i64 @"show.Int"(i64 -7)
+    %t1 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.5, i64 32) to i64
+    %l4 = call i64 @"closey_string_eq"(i64 %l3, i64 %t1)
+    call void @"rcfuncfree"(i64 %l3)
+    %l5 = call i64 @"check"(i64 %l4)
+    %l6 = call i64 @"show.Float"(i64 4612811918334230528)
+    %t2 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.6, i64 32) to i64
+    %l7 = call i64 @"closey_string_eq"(i64 %l6, i64 %t2)
+    call void @"rcfuncfree"(i64 %l6)
+    %l8 = call i64 @"check"(i64 %l7)
+    %t3 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.7, i64 32) to i64
+    %l9 = call i64 @"show.String"(i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.8, i64 32) to i64
+    %l10 = call i64 @"closey_string_eq"(i64 %l9, i64 %t4)
+    call void @"rcfuncfree"(i64 %l9)
+    %l11 = call i64 @"check"(i64 %l10)
+    %l12 = call i64 @"show.Char"(i64 99)
+    %t5 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.9, i64 32) to i64
+    %l13 = call i64 @"closey_string_eq"(i64 %l12, i64 %t5)
+    call void @"rcfuncfree"(i64 %l12)
+    %l14 = call i64 @"check"(i64 %l13)
+    %l15 = call i64 @"closey_array_of"(i64 1)
+    %l16 = call i64 @"closey_array_push"(i64 %l15, i64 2)
+    %l17 = call i64 @"show.Array Int"(i64 %l16)
+    %t6 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.10, i64 32) to i64
+    %l18 = call i64 @"closey_string_eq"(i64 %l17, i64 %t6)
+    call void @"rcfuncfree"(i64 %l17)
+    %l19 = call i64 @"check"(i64 %l18)
+    %l20 = call i64 @"closey_some"(i64 3)
+    %l21 = call i64 @"closey_some"(i64 %l20)
+    %l22 = call i64 @"show.Option (Option Int)"(i64 %l21)
+    %t7 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.11, i64 32) to i64
+    %l23 = call i64 @"closey_string_eq"(i64 %l22, i64 %t7)
+    call void @"rcfuncfree"(i64 %l22)
+    %l24 = call i64 @"check"(i64 %l23)
+    %l25 = call i64 @"closey_data_1"(i64 0, i64 4602678819172646912)
+    %l26 = call i64 @"closey_data_2"(i64 1, i64 1, i64 2)
+    %l27 = call i64 @"closey_data_0"(i64 1)
+    %t8 = and i64 %l26, 1
+    %t9 = icmp eq i64 %t8, 0
+    br i1 %t9, label %t10.inc, label %t10.end
+t10.inc:
+    call void @"rcinc"(i64 %l26)
+    br label %t10.end
+t10.end:
+    %t11 = and i64 %l27, 1
+    %t12 = icmp eq i64 %t11, 0
+    br i1 %t12, label %t13.inc, label %t13.end
+t13.inc:
+    call void @"rcinc"(i64 %l27)
+    br label %t13.end
+t13.end:
+    %l28 = call i64 @"closey_data_2"(i64 0, i64 %l26, i64 %l27)
+    call void @"rcfuncfree"(i64 %l26)
+    call void @"rcfuncfree"(i64 %l27)
+    %t14 = and i64 %l25, 1
+    %t15 = icmp eq i64 %t14, 0
+    br i1 %t15, label %t16.inc, label %t16.end
+t16.inc:
+    call void @"rcinc"(i64 %l25)
+    br label %t16.end
+t16.end:
+    %t17 = and i64 %l28, 1
+    %t18 = icmp eq i64 %t17, 0
+    br i1 %t18, label %t19.inc, label %t19.end
+t19.inc:
+    call void @"rcinc"(i64 %l28)
+    br label %t19.end
+t19.end:
+    %l29 = call i64 @"closey_data_2"(i64 0, i64 %l25, i64 %l28)
+    call void @"rcfuncfree"(i64 %l25)
+    call void @"rcfuncfree"(i64 %l28)
+    %l30 = call i64 @"show.Drawing"(i64 %l29)
+    call void @"rcfuncfree"(i64 %l29)
+    %t20 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.12, i64 32) to i64
+    %l31 = call i64 @"closey_string_eq"(i64 %l30, i64 %t20)
+    call void @"rcfuncfree"(i64 %l30)
+    %l32 = call i64 @"check"(i64 %l31)
+    %l33 = call i64 @"string_concat"(i64 %l24, i64 %l32)
+    call void @"rcfuncfree"(i64 %l24)
+    call void @"rcfuncfree"(i64 %l32)
+    %l34 = call i64 @"string_concat"(i64 %l19, i64 %l33)
+    call void @"rcfuncfree"(i64 %l19)
+    %l35 = call i64 @"string_concat"(i64 %l14, i64 %l34)
+    call void @"rcfuncfree"(i64 %l14)
+    %l36 = call i64 @"string_concat"(i64 %l11, i64 %l35)
+    call void @"rcfuncfree"(i64 %l11)
+    %l37 = call i64 @"string_concat"(i64 %l8, i64 %l36)
+    call void @"rcfuncfree"(i64 %l8)
+    %l38 = call i64 @"string_concat"(i64 %l5, i64 %l37)
+    call void @"rcfuncfree"(i64 %l5)
+    %l39 = call i64 @"string_concat"(i64 %l2, i64 %l38)
+    call void @"rcfuncfree"(i64 %l2)
+    %l40 = call i64 @"closey_println"(i64 %l39)
+    ret i64 %l40
+}
+
+define i64 @"show.Int"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_show_int"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"show.Float"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_show_float"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"show.String"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_show_string"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"show.Char"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 1, [7 x i8] zeroinitializer }> {
+    %l0 = call i64 @"closey_show_char"(i64 %a0)
+    ret i64 %l0
+}
+
+define i64 @"show.Array Int"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"show.Int" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_show_array"(i64 %a0, i64 %t1)
+    ret i64 %l0
+}
+
+define i64 @"show.Option (Option Int)"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"show.Option Int" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_show_option"(i64 %a0, i64 %t1)
+    ret i64 %l0
+}
+
+define i64 @"show.Drawing"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"show.Shape" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @"show.Drawing" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.23, i64 32) to i64
+    %l2 = call i64 @"closey_show_data"(i64 %a0, i64 %t4, i64 %l1)
+    call void @"rcfuncfree"(i64 %l1)
+    ret i64 %l2
+}
+
+define i64 @"show.Option Int"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"show.Int" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_show_option"(i64 %a0, i64 %t1)
+    ret i64 %l0
+}
+
+define i64 @"show.Shape"(i64 %a0) align 16 prefix <{ i8, i32, i32, [7 x i8] }> <{ i8 0, i32 1, i32 0, [7 x i8] zeroinitializer }> {
+    %t0 = ptrtoint ptr @"show.Float" to i64
+    %t1 = sub i64 %t0, 15
+    %l0 = call i64 @"closey_array_of"(i64 %t1)
+    %t2 = ptrtoint ptr @"show.Int" to i64
+    %t3 = sub i64 %t2, 15
+    %l1 = call i64 @"closey_array_push"(i64 %l0, i64 %t3)
+    %t4 = ptrtoint ptr @"show.Int" to i64
+    %t5 = sub i64 %t4, 15
+    %l2 = call i64 @"closey_array_push"(i64 %l1, i64 %t5)
+    %t6 = ptrtoint ptr getelementptr inbounds (i8, ptr @.string.24, i64 32) to i64
+    %l3 = call i64 @"closey_show_data"(i64 %a0, i64 %t6, i64 %l2)
+    call void @"rcfuncfree"(i64 %l2)
+    ret i64 %l3
+}
+
+define void @"_start"() noreturn {
+    call i64 @"main"()
+    call void @"exit"(i64 0)
+    unreachable
+}
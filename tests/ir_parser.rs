@@ -0,0 +1,242 @@
+//! Checks the parser of the textual form of the backend IR: modules printed and parsed again are the
+//! same as the modules they were printed from, passes run on parsed modules, and malformed modules
+//! are errors rather than panics.
+
+use std::path::Path;
+
+use closeyc::backends::ir::parser::{parse_function, parse_module};
+use closeyc::backends::ir::{calculate_lifetimes, remove_unused_functions, IrModule};
+use closeyc::compiler::Compiler;
+use closeyc::options::CompilerOptions;
+
+mod common;
+
+/// A module using every kind of line, instruction, and argument the IR has.
+const MODULE: &str = r#"extern bool closey_is_odd(int64_t, bool)
+extern double closey_sqrt(double)
+
+export int64_t add(int64_t, int64_t) = add
+export uint32_t newline() = newline
+
+&0 = "T"
+&1 = "tab\there \"quoted\"\n"
+&2 = "ünïcödé ✓"
+
+add(2):
+    %0 = callrt @closey_int_add $0 $1
+    ret %0
+
+newline(0):
+    %0 = load '\n'
+    %1 = load ' '
+    %2 = load '\''
+    %3 = load '✓'
+    ret %0
+
+main(0):
+    %0 = call @add 1 -2
+    %1 = apply @add %0
+    rcinc %1
+    %2 = call? %1 9223372036854775807
+    rcfuncfree %1
+    %3 = load &1
+    %4 = callrt @closey_sqrt 4611686018427387904
+    %5 = call @"show.Array Int" %3
+    %6 = tailcall @add %2 &0
+    ret %6
+
+show.Array Int(1):
+    %0 = callrt @closey_show_array $0 @show.Int
+    ret %0
+
+"#;
+
+/// Prints a module, parses it, and checks that the parsed module prints the same way and has the
+/// same functions, strings, externs, and exports.
+fn assert_round_trips(module: &IrModule) {
+    let printed = module.to_string();
+    let parsed = match parse_module(&printed) {
+        Ok(v) => v,
+        Err(e) => panic!("{}\n{}", e, printed),
+    };
+    assert_eq!(parsed.to_string(), printed);
+
+    assert_eq!(parsed.strings, module.strings);
+    assert_eq!(parsed.exports.len(), module.exports.len());
+    let mut externs: Vec<_> = parsed.externs.keys().collect();
+    let mut expected: Vec<_> = module.externs.keys().collect();
+    externs.sort();
+    expected.sort();
+    assert_eq!(externs, expected);
+
+    assert_eq!(parsed.funcs.len(), module.funcs.len());
+    for (p, f) in parsed.funcs.iter().zip(module.funcs.iter()) {
+        assert_eq!(p.name, f.name);
+        assert_eq!(p.argc, f.argc);
+        assert_eq!(p.ssas.len(), f.ssas.len());
+        for (ps, fs) in p.ssas.iter().zip(f.ssas.iter()) {
+            assert_eq!(ps.local, fs.local, "{}: {}", f.name, fs);
+            assert_eq!(ps.local_lifetime, fs.local_lifetime, "{}: {}", f.name, fs);
+            assert!(ps.args == fs.args, "{}: {}", f.name, fs);
+        }
+    }
+}
+
+#[test]
+fn round_trip_handwritten() {
+    let module = parse_module(MODULE).unwrap();
+    assert_eq!(module.to_string(), MODULE);
+    assert_round_trips(&module);
+}
+
+#[test]
+fn round_trip_programs() {
+    // Lowered programs use the instructions the way the compiler generates them, including
+    // reference counting and the strings of panic locations
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    for name in ["captures", "show", "specialise", "uncurry"].iter() {
+        let source = common::read_program(&dir.join(format!("{}.cly", name))).unwrap();
+        let mut compiler = Compiler::new(CompilerOptions::default());
+        let ir = compiler
+            .check("Main", &source)
+            .value
+            .expect("the program should compile");
+        let mut module = compiler.lower(&ir, "Main").unwrap();
+        remove_unused_functions(&mut module, &["main"]);
+
+        // Lifetimes are not printed, and passes that insert instructions leave them to be
+        // calculated again by the backends
+        for func in module.funcs.iter_mut() {
+            calculate_lifetimes(func);
+        }
+        assert_round_trips(&module);
+    }
+}
+
+#[test]
+fn lifetimes() {
+    // Lifetimes count the instructions from the one that assigns a local to its last use
+    let mut func = parse_function(
+        "f(1):
+    %0 = load $0
+    %1 = load 1
+    rcinc %0
+    %2 = callrt @closey_int_add %0 %1
+    %3 = load 2
+    ret %2",
+    )
+    .unwrap();
+
+    let expected = [3, 2, 2, 0];
+    let lifetimes = |func: &closeyc::backends::ir::IrFunction| -> Vec<usize> {
+        func.ssas
+            .iter()
+            .filter(|v| v.local.is_some())
+            .map(|v| v.local_lifetime)
+            .collect()
+    };
+    assert_eq!(lifetimes(&func), expected);
+
+    // Recalculating lifetimes after they are changed gives the same lifetimes again
+    for ssa in func.ssas.iter_mut() {
+        ssa.local_lifetime = 100;
+    }
+    calculate_lifetimes(&mut func);
+    assert_eq!(lifetimes(&func), expected);
+}
+
+#[test]
+fn unused_functions() {
+    let mut module = parse_module(
+        "main(0):
+    %0 = call @used 1
+    ret %0
+
+used(1):
+    %0 = apply @also_used $0
+    ret %0
+
+also_used(2):
+    %0 = callrt @closey_int_add $0 $1
+    ret %0
+
+unused(0):
+    %0 = call @used 2
+    ret %0",
+    )
+    .unwrap();
+
+    remove_unused_functions(&mut module, &["main"]);
+    let names: Vec<_> = module.funcs.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, ["main", "used", "also_used"]);
+}
+
+#[test]
+fn malformed() {
+    let cases = [
+        ("    ret 0", 1, "instruction outside of a function"),
+        ("f(x):", 1, "invalid argument count x"),
+        ("(1):", 1, "expected a function header but found `(1):`"),
+        ("f(0)", 1, "expected a function header but found `f(0)`"),
+        ("f(0):\n    %0 = jump 1", 2, "unknown instruction jump"),
+        ("f(0):\n    %0 =", 2, "unknown instruction %0"),
+        ("f(0):\n    ret %0", 2, "%0 is used before it is assigned"),
+        (
+            "f(0):\n    %0 = load 1\n    %0 = load 2",
+            3,
+            "%0 is assigned more than once",
+        ),
+        ("f(0):\n    %x = load 1", 2, "invalid index x"),
+        ("f(0):\n    %0 = load $", 2, "invalid index "),
+        ("f(0):\n    %0 = load @", 2, "invalid argument @"),
+        (
+            "f(0):\n    %0 = load 99999999999999999999",
+            2,
+            "invalid argument 99999999999999999999",
+        ),
+        ("f(0):\n    %0 = load 'ab'", 2, "invalid char 'ab'"),
+        ("f(0):\n    %0 = load 'a", 2, "unterminated char 'a"),
+        (
+            "f(0):\n    %0 = load '\\u{110000}'",
+            2,
+            "invalid char '\\u{110000}'",
+        ),
+        (
+            "f(0):\n    %0 = call @\"f 1",
+            2,
+            "unterminated function name @\"f 1",
+        ),
+        (
+            "f(0):\n    %0 = call @\"\\u{d800}\"",
+            2,
+            "invalid function name @\"\\u{d800}\"",
+        ),
+        ("&1 = \"a\"", 1, "expected string &0 but found &1"),
+        ("&0 \"a\"", 1, "expected `=` after a string"),
+        ("&0 = \"a", 1, "invalid string \"a"),
+        ("&0 = \"\\\"", 1, "invalid string \"\\\""),
+        (
+            "extern int64_t",
+            1,
+            "expected a return type and name in `int64_t`",
+        ),
+        ("extern int64_t f(", 1, "expected arguments after f("),
+        ("extern int64_t f(float)", 1, "unknown C type float"),
+        ("export int64_t f()", 1, "expected `=` after an export"),
+    ];
+
+    for (source, line, msg) in cases.iter() {
+        match parse_module(source) {
+            Ok(_) => panic!("`{}` should not parse", source),
+            Err(e) => {
+                assert_eq!(e.line, *line, "{}", source);
+                assert_eq!(e.msg, *msg, "{}", source);
+            }
+        }
+    }
+
+    match parse_function("f(0):\n    ret 0\n\ng(0):\n    ret 1") {
+        Ok(_) => panic!("two functions should not parse as one"),
+        Err(e) => assert_eq!(e.msg, "expected one function but found 2"),
+    }
+}
@@ -0,0 +1,118 @@
+//! Checks the range of int literals: the largest and smallest values of each type can be written in
+//! every base, negated where the type is signed, and literals one past them are errors.
+
+use closeyc::compiler::Compiler;
+use closeyc::options::CompilerOptions;
+
+/// Compiles a program defining `f` and returns its body, or the message of the first error.
+fn compile(source: &str) -> Result<String, String> {
+    let mut compiler = Compiler::new(CompilerOptions::default());
+    let output = compiler.check("Main", source);
+    if let Some(e) = output.errors().next() {
+        return Err(e.message.clone());
+    }
+
+    let ir = output.value.expect("the program should compile");
+    Ok(ir
+        .module("Main")
+        .unwrap()
+        .global("f")
+        .unwrap()
+        .body
+        .to_string())
+}
+
+/// Asserts that every literal is a value of a type, shown as it is after folding.
+fn assert_values(literals: &[&str], expected: &str) {
+    for literal in literals {
+        let body = compile(&format!("f = {}", literal));
+        assert_eq!(body.as_deref(), Ok(expected), "{}", literal);
+    }
+}
+
+/// Asserts that every literal is out of the range of a type.
+fn assert_out_of_range(literals: &[&str], _type: &str) {
+    let expected = format!("Int literal is out of the range of `{}`", _type);
+    for literal in literals {
+        let body = compile(&format!("f = {}", literal));
+        assert_eq!(body, Err(expected.clone()), "{}", literal);
+    }
+}
+
+#[test]
+fn int_max() {
+    assert_values(
+        &[
+            "9223372036854775807",
+            "0x7FFF_FFFF_FFFF_FFFF",
+            "0o777_777_777_777_777_777_777",
+            "0b0111111111111111111111111111111111111111111111111111111111111111",
+        ],
+        "9223372036854775807: Int",
+    );
+    assert_out_of_range(
+        &[
+            "9223372036854775808",
+            "0x8000_0000_0000_0000",
+            "0o1_000_000_000_000_000_000_000",
+            "0b1000000000000000000000000000000000000000000000000000000000000000",
+        ],
+        "Int",
+    );
+}
+
+#[test]
+fn int_min() {
+    assert_values(
+        &[
+            "-9223372036854775808",
+            "-0x8000_0000_0000_0000",
+            "-0o1_000_000_000_000_000_000_000",
+            "-0b1000000000000000000000000000000000000000000000000000000000000000",
+            "(-9223372036854775808)",
+        ],
+        "-9223372036854775808: Int",
+    );
+    assert_out_of_range(
+        &[
+            "-9223372036854775809",
+            "-0x8000_0000_0000_0001",
+            "-0o1_000_000_000_000_000_000_001",
+            "-0b1000000000000000000000000000000000000000000000000000000000000001",
+        ],
+        "Int",
+    );
+}
+
+#[test]
+fn sized() {
+    assert_values(
+        &["127i8", "0x7Fi8", "0o177i8", "0b0111_1111i8"],
+        "127: Int8",
+    );
+    assert_values(
+        &["-128i8", "-0x80i8", "-0o200i8", "-0b1000_0000i8"],
+        "-128: Int8",
+    );
+    assert_out_of_range(&["128i8", "0x80i8", "0o200i8", "0b1000_0000i8"], "Int8");
+    assert_out_of_range(&["-129i8", "-0x81i8", "-0o201i8", "-0b1000_0001i8"], "Int8");
+
+    assert_values(
+        &["255u8", "0xFFu8", "0o377u8", "0b1111_1111u8"],
+        "255: UInt8",
+    );
+    assert_values(&["-0u8"], "0: UInt8");
+    assert_out_of_range(&["256u8", "0x100u8", "0o400u8", "0b1_0000_0000u8"], "UInt8");
+    assert_out_of_range(&["-1u8"], "UInt8");
+}
+
+#[test]
+fn negative() {
+    // `-` only makes a literal negative where a value starts, and is subtraction elsewhere
+    assert_values(&["0 - 1", "-1", "1 * -1", "(-2) + 1"], "-1: Int");
+    assert_values(&["-1.5", "0.5 - 2.0"], "-1.5: Float");
+    assert_eq!(
+        compile("f = match 0 - 1 to -1 => 1 to _ => 0"),
+        Ok(String::from("1: Int"))
+    );
+}
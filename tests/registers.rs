@@ -0,0 +1,191 @@
+//! Checks the registers and spill slots the x86_64 backend allocates to the locals of handwritten
+//! functions: locals alive at the same time never share one, and locals alive across a call are
+//! kept in registers that calls keep or spilled.
+
+use closeyc::backends::allocate_registers;
+use closeyc::backends::ir::parser::parse_function;
+use closeyc::backends::ir::IrFunction;
+use closeyc::backends::x86_64::codegen::callee_saved_registers;
+
+/// A local of an allocated function, with the indices of the instruction that assigns it and its
+/// last use, and the register or spill slot it was allocated.
+struct Allocated {
+    local: usize,
+    start: usize,
+    end: usize,
+    register: usize,
+}
+
+/// Parses a function and allocates its locals, returning them and the number of spill slots used.
+fn allocate(s: &str) -> (IrFunction, Vec<Allocated>, usize) {
+    let mut func = parse_function(s).expect("the function should parse");
+    let spilled = allocate_registers(&mut func, &callee_saved_registers());
+    let locals = func
+        .ssas
+        .iter()
+        .enumerate()
+        .filter_map(|(i, ssa)| {
+            ssa.local.map(|local| Allocated {
+                local,
+                start: i,
+                end: i + ssa.local_lifetime,
+                register: ssa.local_register,
+            })
+        })
+        .collect();
+    (func, locals, spilled)
+}
+
+/// Panics if two locals that are alive at the same time were allocated the same register or spill
+/// slot, or if a register or spill slot past those used was allocated.
+fn assert_no_overlaps(locals: &[Allocated], spilled: usize) {
+    let count = callee_saved_registers().len() + spilled;
+    for (i, a) in locals.iter().enumerate() {
+        assert!(a.register < count, "%{} is in {}", a.local, a.register);
+        for b in locals[i + 1..].iter() {
+            let overlap = a.start <= b.end && b.start <= a.end;
+            assert!(
+                !overlap || a.register != b.register,
+                "%{} and %{} are alive at the same time but share {}",
+                a.local,
+                b.local,
+                a.register
+            );
+        }
+    }
+}
+
+/// Returns true if a call is made while the local is alive, after it is assigned and before its last
+/// use.
+fn across_call(func: &IrFunction, local: &Allocated) -> bool {
+    (local.start + 1..local.end).any(|i| func.ssas[i].instr.is_call())
+}
+
+/// Returns true if calls keep the value of the register or spill slot, which they do for every spill
+/// slot.
+fn kept_by_calls(register: usize) -> bool {
+    callee_saved_registers()
+        .get(register)
+        .copied()
+        .unwrap_or(true)
+}
+
+#[test]
+fn overlapping_locals() {
+    // The first ten locals are alive until they are loaded again in reverse order, so eleven are
+    // alive at once, four more than there are registers
+    let (_, locals, spilled) = allocate(
+        "f(0):
+    %0 = load 0
+    %1 = load 1
+    %2 = load 2
+    %3 = load 3
+    %4 = load 4
+    %5 = load 5
+    %6 = load 6
+    %7 = load 7
+    %8 = load 8
+    %9 = load 9
+    %10 = load %9
+    %11 = load %8
+    %12 = load %7
+    %13 = load %6
+    %14 = load %5
+    %15 = load %4
+    %16 = load %3
+    %17 = load %2
+    %18 = load %1
+    %19 = load %0
+    ret %19",
+    );
+    assert_eq!(spilled, 4);
+    assert_no_overlaps(&locals, spilled);
+}
+
+#[test]
+fn reused_registers() {
+    // Each local dies where the next is assigned, so none are spilled
+    let (_, locals, spilled) = allocate(
+        "f(1):
+    %0 = load $0
+    %1 = callrt @closey_int_add %0 1
+    %2 = callrt @closey_int_add %1 2
+    %3 = callrt @closey_int_add %2 3
+    %4 = callrt @closey_int_add %3 4
+    %5 = callrt @closey_int_add %4 5
+    %6 = callrt @closey_int_add %5 6
+    %7 = callrt @closey_int_add %6 7
+    %8 = callrt @closey_int_add %7 8
+    ret %8",
+    );
+    assert_eq!(spilled, 0);
+    assert_no_overlaps(&locals, spilled);
+}
+
+#[test]
+fn locals_across_calls() {
+    // The results of the first calls are alive across the later ones, while the argument of each
+    // call dies at it
+    let (func, locals, spilled) = allocate(
+        "nested(1):
+    %0 = call @step $0 1
+    %1 = call @step $0 2
+    %2 = call @step $0 3
+    %3 = call @step $0 4
+    %4 = call @step $0 5
+    %5 = call @step $0 6
+    %6 = call @step $0 7
+    %7 = call @step $0 8
+    %8 = call @step $0 9
+    %9 = call @step $0 10
+    %10 = callrt @closey_int_sub %8 %9
+    %11 = callrt @closey_int_sub %7 %10
+    %12 = callrt @closey_int_sub %6 %11
+    %13 = callrt @closey_int_sub %5 %12
+    %14 = callrt @closey_int_sub %4 %13
+    %15 = callrt @closey_int_sub %3 %14
+    %16 = callrt @closey_int_sub %2 %15
+    %17 = callrt @closey_int_sub %1 %16
+    %18 = callrt @closey_int_sub %0 %17
+    ret %18",
+    );
+    assert_no_overlaps(&locals, spilled);
+
+    let mut kept = 0;
+    for local in locals.iter() {
+        if across_call(&func, local) {
+            assert!(
+                kept_by_calls(local.register),
+                "%{} is alive across a call but is in {}, which calls overwrite",
+                local.local,
+                local.register
+            );
+            kept += 1;
+        }
+    }
+    assert_eq!(kept, 9);
+}
+
+#[test]
+fn prefers_registers_calls_overwrite() {
+    // Locals that are not alive across a call do not need a register the function has to save
+    let (func, locals, spilled) = allocate(
+        "f(1):
+    %0 = load $0
+    %1 = callrt @closey_int_add %0 1
+    %2 = call @g %1
+    %3 = callrt @closey_int_add %2 1
+    ret %3",
+    );
+    assert_eq!(spilled, 0);
+    assert_no_overlaps(&locals, spilled);
+    for local in locals.iter() {
+        assert!(!across_call(&func, local));
+        assert!(
+            !kept_by_calls(local.register),
+            "%{} is in {}, which calls keep",
+            local.local,
+            local.register
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! Checks the queries tooling makes of an environment: the scopes it is made of, from innermost to
+//! outermost, and the variables visible from the current scope.
+
+use closeyc::frontend::ir::{ArityInfo, Location};
+use closeyc::frontend::scopes::Scope;
+use closeyc::frontend::types::{arc, Type};
+
+/// Binds a variable of type `Int` in the current scope.
+fn put(scope: &mut Scope, name: &str) {
+    scope.put_var(
+        name,
+        &arc::new(Type::Int),
+        ArityInfo::Unknown,
+        &Location::empty(),
+        true,
+        "Main",
+    );
+}
+
+/// Returns the names of the variables bound in each scope, from innermost to outermost.
+fn names(scope: &Scope) -> Vec<Vec<&str>> {
+    scope
+        .iter()
+        .map(|v| v.variables().into_iter().map(|v| v.0).collect())
+        .collect()
+}
+
+#[test]
+fn frames() {
+    let mut scope = Scope::new();
+    put(&mut scope, "main");
+    scope.push_scope(true);
+    put(&mut scope, "x");
+    put(&mut scope, "y");
+    scope.push_scope(false);
+    put(&mut scope, "x");
+    put(&mut scope, "z");
+    put(&mut scope, "z");
+
+    assert_eq!(
+        names(&scope),
+        [vec!["x", "z"], vec!["x", "y"], vec!["main"]]
+    );
+    let depths: Vec<_> = scope.iter().map(|v| v.depth()).collect();
+    assert_eq!(depths, [2, 1, 0]);
+    let functions: Vec<_> = scope.iter().map(|v| v.is_function()).collect();
+    assert_eq!(functions, [false, true, false]);
+    assert_eq!(scope.iter().count(), scope.depth() + 1);
+
+    // Each scope only has its own bindings, and the innermost scope shadows the others
+    assert!(scope.iter().nth(1).unwrap().get_var("z").is_none());
+    assert!(scope.iter().nth(1).unwrap().get_var("x").is_some());
+    let visible: Vec<_> = scope.visible_bindings().into_iter().map(|v| v.0).collect();
+    assert_eq!(visible, ["main", "x", "y", "z"]);
+    assert_eq!(scope.scope_of("x"), Some(2));
+
+    scope.pop_scope();
+    assert_eq!(names(&scope), [vec!["x", "y"], vec!["main"]]);
+    assert_eq!(scope.scope_of("x"), Some(1));
+
+    scope.pop_scope();
+    assert_eq!(names(&scope), [vec!["main"]]);
+}